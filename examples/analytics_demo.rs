@@ -0,0 +1,145 @@
+//! Screen view tracking demo
+//!
+//! `AnalyticsMiddleware` prints a line to stdout for every screen view.
+//! Clicking the link of the current page again reports nothing, "Old
+//! inbox" redirects to the inbox and is reported as a single view of it, and
+//! "← Back" is reported with direction `Back`.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.add_route(Route::new("/", |_, _, _| {
+                page("Home", "Navigate and watch the terminal.")
+            }));
+            router.add_route(
+                Route::new("/inbox", |_, _, _| page("Inbox", "3 unread messages.")).name("inbox"),
+            );
+            router.add_route(Route::new("/messages/:id", |_, _, params| {
+                let id = params.get("id").cloned().unwrap_or_default();
+                page("Message", &format!("Message #{id}"))
+            }));
+            router.add_route(Route::new("/old-inbox", |_, _, _| {
+                page("Old inbox", "Never shown, it redirects.")
+            }));
+
+            router.add_middleware(AnalyticsMiddleware::new(|view| {
+                println!(
+                    "[view #{}] {} -> {} ({:?}, from {})",
+                    view.sequence,
+                    view.name,
+                    view.path,
+                    view.direction,
+                    view.referrer.as_deref().unwrap_or("-"),
+                );
+            }));
+        });
+
+        let bounds = Bounds::centered(None, size(px(900.), px(600.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Screen View Tracking Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| cx.new(AnalyticsDemoApp::new),
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct AnalyticsDemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl AnalyticsDemoApp {
+    fn new(cx: &mut Context<'_, Self>) -> Self {
+        Self {
+            outlet: cx.new(|_| RouterOutlet::new()),
+        }
+    }
+}
+
+impl Render for AnalyticsDemoApp {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .size_full()
+            .bg(rgb(0x1e1e1e))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .w_48()
+                    .p_4()
+                    .gap_2()
+                    .bg(rgb(0x252526))
+                    .child(nav_link("home", "Home", |cx| {
+                        Navigator::push(cx, "/");
+                    }))
+                    .child(nav_link("inbox", "Inbox", |cx| {
+                        Navigator::push(cx, "/inbox");
+                    }))
+                    .child(nav_link("message-1", "Message 1", |cx| {
+                        Navigator::push(cx, "/messages/1");
+                    }))
+                    .child(nav_link("old-inbox", "Old inbox", |cx| {
+                        // A redirect: the replace lands before the view is reported
+                        Navigator::push(cx, "/old-inbox");
+                        Navigator::replace(cx, "/inbox");
+                    }))
+                    .child(nav_link("back", "← Back", |cx| {
+                        Navigator::pop(cx);
+                    })),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
+
+fn page(title: &str, body: &str) -> AnyElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_4()
+        .p_8()
+        .child(
+            div()
+                .text_2xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(title.to_string()),
+        )
+        .child(div().text_color(rgb(0xcccccc)).child(body.to_string()))
+        .into_any_element()
+}
+
+fn nav_link(
+    id: &'static str,
+    label: &'static str,
+    navigate: impl Fn(&mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .id(id)
+        .px_3()
+        .py_2()
+        .rounded_md()
+        .cursor_pointer()
+        .text_color(rgb(0xcccccc))
+        .hover(|this| this.bg(rgb(0x2a2d2e)))
+        .child(label)
+        .on_click(move |_, window, cx| {
+            navigate(cx);
+            window.refresh();
+        })
+}
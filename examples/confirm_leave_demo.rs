@@ -0,0 +1,219 @@
+//! Unsaved changes demo
+//!
+//! The compose page marks itself dirty while its draft differs from the saved
+//! one. Leaving it then (a link, the back button or closing the window) asks
+//! for confirmation with a native prompt; "Keep editing" stays on the page.
+//! Saving clears the flag, so leaving is no longer held.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const ACCENT: u32 = 0x4fc3f7;
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.add_route(Route::new("/", |_, _, _| {
+                div()
+                    .p_8()
+                    .text_color(rgb(0xcccccc))
+                    .child("Open Compose, type something, then try to leave.")
+            }));
+            router.add_route(
+                Route::component("/compose", Composer::new).confirm_leave("Discard your draft?"),
+            );
+            router.set_confirm_handler(ask);
+        });
+
+        let bounds = Bounds::centered(None, size(px(800.), px(500.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Unsaved Changes Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |window, cx| {
+                Navigator::confirm_leave_on_close(window, cx);
+                let outlet = cx.new(|_| RouterOutlet::new());
+                cx.new(|_| DemoApp { outlet })
+            },
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+/// Ask with a native prompt in the active window
+fn ask(cx: &mut App, message: &str, decide: LeaveDecision) {
+    let Some(window) = cx.active_window() else {
+        decide(false);
+        return;
+    };
+    let answer = window.update(cx, |_, window, cx| {
+        window.prompt(
+            PromptLevel::Warning,
+            message,
+            Some("Your changes have not been saved."),
+            &["Discard", "Keep editing"],
+            cx,
+        )
+    });
+    let Ok(answer) = answer else {
+        decide(false);
+        return;
+    };
+    cx.spawn(async move |_| decide(answer.await == Ok(0)))
+        .detach();
+}
+
+struct DemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl Render for DemoApp {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let dirty = Navigator::is_dirty(cx);
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(rgb(0x1e1e1e))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .p_4()
+                    .bg(rgb(0x252526))
+                    .child(button("back", "Back", |cx| {
+                        Navigator::pop(cx);
+                    }))
+                    .child(button("home", "Home", |cx| {
+                        Navigator::push(cx, "/");
+                    }))
+                    .child(button("compose", "Compose", |cx| {
+                        Navigator::push(cx, "/compose");
+                    }))
+                    .when(dirty, |bar| {
+                        bar.child(
+                            div()
+                                .px_3()
+                                .py_2()
+                                .text_color(rgb(ACCENT))
+                                .child("● unsaved"),
+                        )
+                    }),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
+
+fn button(id: &'static str, label: &'static str, on_click: fn(&mut App)) -> impl IntoElement {
+    div()
+        .id(id)
+        .px_3()
+        .py_2()
+        .rounded_md()
+        .cursor_pointer()
+        .text_color(rgb(0xcccccc))
+        .hover(|this| this.bg(rgb(0x2a2d2e)))
+        .child(label)
+        .on_click(move |_, window, cx| {
+            on_click(cx);
+            window.refresh();
+        })
+}
+
+/// A single-line text field whose draft is saved explicitly
+struct Composer {
+    draft: String,
+    saved: String,
+    focus_handle: Option<FocusHandle>,
+}
+
+impl Composer {
+    fn new() -> Self {
+        Self {
+            draft: String::new(),
+            saved: String::new(),
+            focus_handle: None,
+        }
+    }
+
+    fn edited(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        Navigator::set_dirty(cx, self.draft != self.saved);
+        // The toolbar shows the flag too
+        window.refresh();
+    }
+}
+
+impl Render for Composer {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let focus_handle = self
+            .focus_handle
+            .get_or_insert_with(|| {
+                let handle = cx.focus_handle();
+                window.focus(&handle);
+                handle
+            })
+            .clone();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_4()
+            .p_8()
+            .child(
+                div()
+                    .id("draft")
+                    .track_focus(&focus_handle)
+                    .px_3()
+                    .py_2()
+                    .min_h_10()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(rgb(0x3c3c3c))
+                    .focus(|this| this.border_color(rgb(ACCENT)))
+                    .text_color(rgb(0xffffff))
+                    .child(format!("{}|", self.draft))
+                    .on_key_down(cx.listener(|composer, event: &KeyDownEvent, window, cx| {
+                        let keystroke = &event.keystroke;
+                        if keystroke.key == "backspace" {
+                            composer.draft.pop();
+                        } else if let Some(typed) = keystroke
+                            .key_char
+                            .as_ref()
+                            .filter(|_| !keystroke.modifiers.platform)
+                        {
+                            composer.draft.push_str(typed);
+                        } else {
+                            return;
+                        }
+                        composer.edited(window, cx);
+                    })),
+            )
+            .child(
+                div()
+                    .id("save")
+                    .w_24()
+                    .px_3()
+                    .py_2()
+                    .rounded_md()
+                    .cursor_pointer()
+                    .bg(rgb(0x0e639c))
+                    .text_color(rgb(0xffffff))
+                    .child("Save")
+                    .on_click(cx.listener(|composer, _, window, cx| {
+                        composer.saved = composer.draft.clone();
+                        composer.edited(window, cx);
+                    })),
+            )
+    }
+}
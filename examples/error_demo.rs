@@ -1,10 +1,17 @@
 //! Error Handlers Demo - RouterLink Example
 //!
-//! Demonstrates RouterLink usage with valid and invalid routes.
+//! Demonstrates RouterLink usage with valid and invalid routes, and an error
+//! boundary: the "Broken report" child panics on its first build, and the
+//! reports layout keeps its tabs while the boundary shows the error with a
+//! retry button in the child's place.
 
 use gpui::prelude::*;
 use gpui::*;
 use gpui_navigator::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the broken report already failed once
+static REPORT_FAILED: AtomicBool = AtomicBool::new(false);
 
 fn main() {
     env_logger::init();
@@ -26,6 +33,23 @@ fn main() {
                 })
                 .transition(Transition::slide_right(300)),
             );
+            router.add_route(
+                Route::new("/reports", |window, cx, _| {
+                    reports_layout(window, cx).into_any_element()
+                })
+                .error_boundary(|error, retry, _cx| report_error(error, retry))
+                .children(vec![
+                    Route::new("summary", |_, _, _| report_page("All systems normal.")).into(),
+                    Route::new("broken", |_, _, _| {
+                        assert!(
+                            REPORT_FAILED.swap(true, Ordering::SeqCst),
+                            "The report service is unreachable"
+                        );
+                        report_page("Loaded on the second attempt.")
+                    })
+                    .into(),
+                ]),
+            );
         });
 
         let bounds = Bounds::centered(None, size(px(1000.), px(700.)), cx);
@@ -139,6 +163,72 @@ fn sidebar(cx: &mut Context<'_, DemoApp>) -> impl IntoElement {
         )
         .child(nav_link(cx, "/invalid", "Not Found #1"))
         .child(nav_link(cx, "/missing", "Not Found #2"))
+        .child(div().h_px().bg(rgb(0x3e3e3e)).my_2())
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0xcccccc))
+                .mb_2()
+                .child("Error Boundary"),
+        )
+        .child(nav_link(cx, "/reports/summary", "Report summary"))
+        .child(nav_link(cx, "/reports/broken", "Broken report"))
+        .child(nav_link(cx, "/reports/missing", "Missing report"))
+}
+
+fn reports_layout(window: &mut Window, cx: &mut App) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .size_full()
+        .p_8()
+        .gap_4()
+        .child(
+            div()
+                .text_3xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child("Reports"),
+        )
+        .child(
+            div()
+                .flex_1()
+                .p_6()
+                .bg(rgb(0x252526))
+                .rounded(px(12.))
+                .border_1()
+                .border_color(rgb(0x3e3e3e))
+                .child(render_router_outlet(window, cx, None)),
+        )
+}
+
+fn report_page(text: &'static str) -> impl IntoElement {
+    div().text_base().text_color(rgb(0xcccccc)).child(text)
+}
+
+fn report_error(error: &RouteError, retry: RetryHandle) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_3()
+        .child(
+            div()
+                .text_color(rgb(0xf48771))
+                .child(format!("This report failed: {}", error)),
+        )
+        .child(
+            div()
+                .id("retry-report")
+                .px_3()
+                .py_2()
+                .rounded_md()
+                .bg(rgb(0x0e639c))
+                .text_color(rgb(0xffffff))
+                .cursor_pointer()
+                .child("Retry")
+                .on_click(move |_, _, cx| retry.retry(cx)),
+        )
 }
 
 fn nav_link(cx: &mut Context<'_, DemoApp>, path: &str, label: &str) -> Div {
@@ -0,0 +1,152 @@
+//! Checkout flow demo
+//!
+//! Three checkout steps tagged as a flow, with a stepper bound to its
+//! `FlowState`. Next and Back follow the steps, reached steps can be clicked,
+//! and going back to an earlier step invalidates the ones after it. Leaving
+//! for the shop resets the flow.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const STEPS: [(&str, &str); 3] = [
+    ("/checkout/cart", "Cart"),
+    ("/checkout/address", "Address"),
+    ("/checkout/payment", "Payment"),
+];
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.set_config(
+                RouterConfig::new()
+                    .flow_policy("checkout", FlowPolicy::InvalidateForward)
+                    .flow_exit("checkout", FlowExit::Reset),
+            );
+            router.add_route(Route::new("/", |_, _, _| page("Shop")));
+            for (step, (path, title)) in STEPS.into_iter().enumerate() {
+                router
+                    .add_route(Route::new(path, move |_, _, _| page(title)).flow("checkout", step));
+            }
+        });
+
+        let bounds = Bounds::centered(None, size(px(700.), px(450.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Checkout Flow Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| {
+                let outlet = cx.new(|_| RouterOutlet::new());
+                cx.new(|_| DemoApp { outlet })
+            },
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct DemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl Render for DemoApp {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let state = Navigator::flow_state(cx, "checkout").unwrap_or_default();
+        let button = |id: &'static str, label: &'static str| {
+            div()
+                .id(id)
+                .px_3()
+                .py_1()
+                .rounded_md()
+                .bg(rgb(0xe0e0e0))
+                .cursor_pointer()
+                .child(label)
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(rgb(0xffffff))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_4()
+                    .p_4()
+                    .bg(rgb(0xf3f3f3))
+                    .children(
+                        STEPS
+                            .iter()
+                            .enumerate()
+                            .map(|(step, (path, title))| stepper_item(step, path, title, &state)),
+                    ),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+            .child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .p_4()
+                    .child(button("shop", "Shop").on_click(|_, _, cx| {
+                        Navigator::push(cx, "/");
+                    }))
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .child(button("back", "Back").on_click(|_, _, cx| {
+                                Navigator::flow_prev(cx, "checkout");
+                            }))
+                            .child(button("next", "Next").on_click(|_, _, cx| {
+                                if Navigator::flow_next(cx, "checkout").is_none()
+                                    && !Navigator::flow_state(cx, "checkout")
+                                        .is_some_and(|state| state.is_active())
+                                {
+                                    Navigator::push(cx, STEPS[0].0);
+                                }
+                            })),
+                    ),
+            )
+    }
+}
+
+/// A step of the stepper: bold when current, clickable once reached
+fn stepper_item(step: usize, path: &'static str, title: &str, state: &FlowState) -> Stateful<Div> {
+    let item = div()
+        .id(("step", step))
+        .px_2()
+        .child(format!("{}. {}", step + 1, title));
+    let item = if state.current == Some(step) {
+        item.font_weight(FontWeight::BOLD)
+    } else {
+        item
+    };
+    if state.is_visited(step) {
+        item.text_color(rgb(0x1a73e8))
+            .cursor_pointer()
+            .on_click(move |_, _, cx| {
+                Navigator::push(cx, path);
+            })
+    } else {
+        item.text_color(rgb(0x999999))
+    }
+}
+
+fn page(title: &str) -> AnyElement {
+    div()
+        .p_8()
+        .text_2xl()
+        .font_weight(FontWeight::BOLD)
+        .child(title.to_string())
+        .into_any_element()
+}
@@ -0,0 +1,133 @@
+//! Focus management demo
+//!
+//! After each navigation keyboard focus moves to the new page's heading, drawn
+//! with a visible focus ring. Click a link (which takes focus, see its ring)
+//! and watch the ring move to the heading once the transition has finished.
+//! Routes without a focus target would focus the outlet container instead.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const FOCUS_RING: u32 = 0x4fc3f7;
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.set_config(RouterConfig::new().focus_policy(FocusPolicy::Custom));
+
+            router.add_route(
+                Route::new("/", |_, cx, _| page(cx, "Home", "Pick a page on the left."))
+                    .focus_target("heading")
+                    .transition(Transition::fade(200)),
+            );
+            router.add_route(
+                Route::new("/inbox", |_, cx, _| page(cx, "Inbox", "3 unread messages."))
+                    .focus_target("heading")
+                    .transition(Transition::slide_left(250)),
+            );
+            router.add_route(
+                Route::new("/archive", |_, cx, _| {
+                    page(cx, "Archive", "Nothing archived yet.")
+                })
+                .focus_target("heading")
+                .transition(Transition::fade(200)),
+            );
+        });
+
+        let bounds = Bounds::centered(None, size(px(900.), px(600.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Focus Management Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| cx.new(FocusDemoApp::new),
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct FocusDemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl FocusDemoApp {
+    fn new(cx: &mut Context<'_, Self>) -> Self {
+        Self {
+            outlet: cx.new(|_| RouterOutlet::new()),
+        }
+    }
+}
+
+impl Render for FocusDemoApp {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .size_full()
+            .bg(rgb(0x1e1e1e))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .w_48()
+                    .p_4()
+                    .gap_2()
+                    .bg(rgb(0x252526))
+                    .child(nav_link("home", "/", "Home"))
+                    .child(nav_link("inbox", "/inbox", "Inbox"))
+                    .child(nav_link("archive", "/archive", "Archive")),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
+
+fn page(cx: &mut App, title: &'static str, body: &'static str) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_4()
+        .p_8()
+        .child(
+            div()
+                .route_focus_target("heading", cx)
+                .px_2()
+                .rounded_md()
+                .border_2()
+                .border_color(transparent_black())
+                .focus(|this| this.border_color(rgb(FOCUS_RING)))
+                .text_2xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(title),
+        )
+        .child(div().text_color(rgb(0xcccccc)).child(body))
+}
+
+fn nav_link(id: &'static str, path: &'static str, label: &'static str) -> impl IntoElement {
+    div()
+        .id(id)
+        .tab_index(0)
+        .px_3()
+        .py_2()
+        .rounded_md()
+        .cursor_pointer()
+        .border_2()
+        .border_color(transparent_black())
+        .text_color(rgb(0xcccccc))
+        .hover(|this| this.bg(rgb(0x2a2d2e)))
+        .focus(|this| this.border_color(rgb(FOCUS_RING)))
+        .child(label)
+        .on_click(move |_, window, cx| {
+            Navigator::push(cx, path);
+            window.refresh();
+        })
+}
@@ -0,0 +1,119 @@
+//! Hero transition demo
+//!
+//! Clicking a tile of the grid opens its detail page, and the tile flies
+//! from its place in the grid to the large swatch on that page while the
+//! pages fade. Going back flies it home again.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const COLORS: [(&str, u32); 6] = [
+    ("Tomato", 0xff6347),
+    ("Gold", 0xffd700),
+    ("Sea green", 0x2e8b57),
+    ("Steel blue", 0x4682b4),
+    ("Orchid", 0xda70d6),
+    ("Slate", 0x708090),
+];
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.add_route(Route::new("/", |_, _, _| grid()).transition(Transition::fade(400)));
+            router.add_route(
+                Route::new("/colors/:index", |_, _, params| {
+                    let index = params
+                        .get("index")
+                        .and_then(|index| index.parse::<usize>().ok())
+                        .filter(|index| *index < COLORS.len())
+                        .unwrap_or_default();
+                    detail(index)
+                })
+                .transition(Transition::fade(400)),
+            );
+        });
+
+        let bounds = Bounds::centered(None, size(px(700.), px(560.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Hero Transition Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| cx.new(|_| RouterOutlet::new()),
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+/// Tiles opening their detail page
+fn grid() -> Div {
+    div()
+        .flex()
+        .flex_wrap()
+        .gap_6()
+        .p_8()
+        .size_full()
+        .bg(rgb(0xffffff))
+        .children(COLORS.iter().enumerate().map(|(index, (name, color))| {
+            div()
+                .id(("tile", index))
+                .flex()
+                .flex_col()
+                .gap_2()
+                .cursor_pointer()
+                .on_click(move |_, window, cx| {
+                    Navigator::push(cx, format!("/colors/{index}"));
+                    window.refresh();
+                })
+                .child(
+                    div()
+                        .size(px(160.))
+                        .rounded_md()
+                        .bg(rgb(*color))
+                        .hero(format!("color-{index}")),
+                )
+                .child(*name)
+        }))
+}
+
+/// Large swatch of one color
+fn detail(index: usize) -> Div {
+    let (name, color) = COLORS[index];
+    div()
+        .flex()
+        .flex_col()
+        .gap_4()
+        .p_8()
+        .size_full()
+        .bg(rgb(0xffffff))
+        .child(
+            div()
+                .id("back")
+                .cursor_pointer()
+                .text_color(rgb(0x2563eb))
+                .child("← All colors")
+                .on_click(|_, window, cx| {
+                    Navigator::back(cx);
+                    window.refresh();
+                }),
+        )
+        .child(
+            div()
+                .w_full()
+                .h(px(320.))
+                .bg(rgb(color))
+                .hero(format!("color-{index}")),
+        )
+        .child(div().text_2xl().child(name))
+        .child(format!("#{color:06x}"))
+}
@@ -0,0 +1,88 @@
+//! History menu demo
+//!
+//! Visit a few pages, then use the arrows: a click goes back or forward one
+//! page, a right click or a long press lists the pages in that direction to
+//! jump to. Pages are labelled with their `title` meta.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const PAGES: [(&str, &str); 4] = [
+    ("/", "Inbox"),
+    ("/drafts", "Drafts"),
+    ("/sent", "Sent"),
+    ("/archive", "Archive"),
+];
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            for (path, title) in PAGES {
+                router.add_route(Route::new(path, move |_, _, _| page(title)).meta("title", title));
+            }
+        });
+
+        let bounds = Bounds::centered(None, size(px(700.), px(450.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("History Menu Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| {
+                let outlet = cx.new(|_| RouterOutlet::new());
+                cx.new(|_| DemoApp { outlet })
+            },
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct DemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl Render for DemoApp {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let arrow = |button: Div| button.px_3().py_1().rounded_md().text_xl();
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(rgb(0xffffff))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_4()
+                    .p_4()
+                    .bg(rgb(0xf3f3f3))
+                    .child(arrow(BackButton::new().child("←").build(cx)))
+                    .child(arrow(ForwardButton::new().child("→").build(cx)))
+                    .children(
+                        PAGES
+                            .iter()
+                            .map(|(path, title)| router_link(cx, *path, *title)),
+                    ),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
+
+fn page(title: &str) -> impl IntoElement {
+    div()
+        .p_8()
+        .text_2xl()
+        .font_weight(FontWeight::BOLD)
+        .child(title.to_string())
+}
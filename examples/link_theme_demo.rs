@@ -0,0 +1,136 @@
+//! Link theme demo
+//!
+//! The navigation links take their colors from the router's link theme.
+//! "Switch theme" swaps a light and a dark theme at runtime; the links pick
+//! the new colors up on the next render. The "Settings" link styles itself
+//! with `RouterLink::style` instead, handling every state in one closure.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+fn light() -> RouterLinkTheme {
+    RouterLinkTheme::default()
+}
+
+fn dark() -> RouterLinkTheme {
+    RouterLinkTheme {
+        active: rgb(0x4fc3f7).into(),
+        inactive: rgb(0xcccccc).into(),
+        hover: rgb(0xffffff).into(),
+        disabled: rgba(0xcccccc66).into(),
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.add_route(Route::new("/", |_, _, _| page("Home")));
+            router.add_route(Route::new("/projects", |_, _, _| page("Projects")));
+            router.add_route(Route::new("/settings", |_, _, _| page("Settings")));
+        });
+
+        let bounds = Bounds::centered(None, size(px(700.), px(450.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Link Theme Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| {
+                let outlet = cx.new(|_| RouterOutlet::new());
+                cx.new(|_| DemoApp {
+                    outlet,
+                    dark: false,
+                })
+            },
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct DemoApp {
+    outlet: Entity<RouterOutlet>,
+    dark: bool,
+}
+
+impl Render for DemoApp {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let (background, bar) = if self.dark {
+            (rgb(0x1e1e1e), rgb(0x252526))
+        } else {
+            (rgb(0xffffff), rgb(0xf3f3f3))
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(background)
+            .text_color(cx.global::<GlobalRouter>().link_theme().inactive)
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_4()
+                    .p_4()
+                    .bg(bar)
+                    .child(router_link(cx, "/", "Home"))
+                    .child(router_link(cx, "/projects", "Projects"))
+                    .child(settings_link(cx))
+                    .child(div().flex_1())
+                    .child(
+                        div()
+                            .id("switch-theme")
+                            .px_3()
+                            .py_1()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(rgb(0x888888))
+                            .cursor_pointer()
+                            .child("Switch theme")
+                            .on_click(cx.listener(|app, _, window, cx| {
+                                app.dark = !app.dark;
+                                let theme = if app.dark { dark() } else { light() };
+                                cx.update_global::<GlobalRouter, _>(|router, _| {
+                                    router.set_link_theme(theme);
+                                });
+                                window.refresh();
+                            })),
+                    ),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
+
+/// A pill-shaped link styled for every state by one closure
+fn settings_link(cx: &mut Context<'_, DemoApp>) -> Div {
+    let theme = *cx.global::<GlobalRouter>().link_theme();
+    RouterLink::new("/settings")
+        .child("Settings")
+        .style(move |link, state| {
+            link.px_3()
+                .py_1()
+                .rounded_full()
+                .text_color(theme.color(state))
+                .when(state.active, |link| link.bg(theme.active.opacity(0.15)))
+                .when(state.hovered, |link| link.bg(theme.hover.opacity(0.1)))
+        })
+        .build(cx)
+}
+
+fn page(title: &str) -> impl IntoElement {
+    div()
+        .p_8()
+        .text_2xl()
+        .font_weight(FontWeight::BOLD)
+        .child(title.to_string())
+}
@@ -0,0 +1,195 @@
+//! Modal route demo
+//!
+//! A photo grid where clicking a photo opens it in a lightbox. The lightbox is a
+//! modal route: the grid stays mounted underneath and `Navigator::pop` closes it.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const PHOTOS: [(u32, &str); 6] = [
+    (0xe57373, "Sunset"),
+    (0x64b5f6, "Lake"),
+    (0x81c784, "Forest"),
+    (0xffb74d, "Desert"),
+    (0xba68c8, "Aurora"),
+    (0x4db6ac, "Lagoon"),
+];
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.add_route(Route::new("/", |_, _, _| photo_grid()).name("grid"));
+
+            router.add_route(
+                Route::new("/photos/:id", |_, _, params| lightbox(params, false))
+                    .name("photo")
+                    .presentation(Presentation::Modal)
+                    .transition(Transition::zoom_in(200)),
+            );
+
+            // Navigating inside the lightbox keeps the grid underneath
+            router.add_route(
+                Route::new("/photos/:id/info", |_, _, params| lightbox(params, true))
+                    .name("photo.info")
+                    .presentation(Presentation::Modal)
+                    .transition(Transition::fade(150)),
+            );
+        });
+
+        let bounds = Bounds::centered(None, size(px(900.), px(650.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Modal Route Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| cx.new(ModalDemoApp::new),
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct ModalDemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl ModalDemoApp {
+    fn new(cx: &mut Context<'_, Self>) -> Self {
+        Self {
+            outlet: cx.new(|_| RouterOutlet::new().modal_backdrop(rgba(0x000000aa))),
+        }
+    }
+}
+
+impl Render for ModalDemoApp {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .size_full()
+            .bg(rgb(0xfafafa))
+            .child(self.outlet.clone())
+    }
+}
+
+fn photo_grid() -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .size_full()
+        .p_8()
+        .gap_6()
+        .child(
+            div()
+                .text_2xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0x212121))
+                .child("Photos"),
+        )
+        .child(
+            div()
+                .flex()
+                .flex_wrap()
+                .gap_4()
+                .children(PHOTOS.iter().enumerate().map(|(id, (color, title))| {
+                    let path = format!("/photos/{}", id);
+                    div()
+                        .id(("photo", id))
+                        .flex()
+                        .items_end()
+                        .w_48()
+                        .h_32()
+                        .p_2()
+                        .rounded_md()
+                        .cursor_pointer()
+                        .bg(rgb(*color))
+                        .text_color(rgb(0xffffff))
+                        .child(*title)
+                        .on_click(move |_, window, cx| {
+                            Navigator::push(cx, path.clone());
+                            window.refresh();
+                        })
+                })),
+        )
+}
+
+fn lightbox(params: &RouteParams, show_info: bool) -> impl IntoElement {
+    let id = params.get_as::<usize>("id").unwrap_or(0) % PHOTOS.len();
+    let (color, title) = PHOTOS[id];
+    let info_path = format!("/photos/{}/info", id);
+
+    div()
+        .flex()
+        .items_center()
+        .justify_center()
+        .size_full()
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .w(px(480.))
+                .rounded_lg()
+                .overflow_hidden()
+                .bg(rgb(0xffffff))
+                .shadow_lg()
+                .child(div().h(px(300.)).bg(rgb(color)))
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .justify_between()
+                        .p_4()
+                        .child(div().text_xl().child(title))
+                        .child(
+                            div()
+                                .flex()
+                                .gap_2()
+                                .when(!show_info, |this| {
+                                    this.child(button("info", "Info", move |cx| {
+                                        Navigator::push(cx, info_path.clone());
+                                    }))
+                                })
+                                .child(button("close", "Close", |cx| {
+                                    Navigator::pop(cx);
+                                })),
+                        ),
+                )
+                .when(show_info, |this| {
+                    this.child(
+                        div()
+                            .px_4()
+                            .pb_4()
+                            .text_sm()
+                            .text_color(rgb(0x666666))
+                            .child(format!("Photo #{} - Close returns to the photo", id)),
+                    )
+                }),
+        )
+}
+
+fn button(
+    id: &'static str,
+    label: &'static str,
+    on_click: impl Fn(&mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .id(id)
+        .px_3()
+        .py_1()
+        .rounded_md()
+        .cursor_pointer()
+        .bg(rgb(0xeeeeee))
+        .hover(|this| this.bg(rgb(0xe0e0e0)))
+        .child(label)
+        .on_click(move |_, window, cx| {
+            on_click(cx);
+            window.refresh();
+        })
+}
@@ -51,6 +51,7 @@ fn main() {
                             .name("products.list")
                             .into(),
                         Route::component_with_params(":id", |params| {
+                            #[allow(clippy::or_fun_call)]
                             let id = params.get("id").unwrap_or(&"unknown".to_string()).clone();
                             ProductDetailPage::new(id)
                         })
@@ -241,6 +242,7 @@ impl Render for DashboardLayout {
 }
 
 impl DashboardLayout {
+    #[allow(clippy::unused_self)]
     fn sidebar_link(
         &self,
         cx: &mut Context<'_, Self>,
@@ -416,6 +418,7 @@ impl Render for ProductListPage {
 }
 
 impl ProductListPage {
+    #[allow(clippy::unused_self)]
     fn product_link(&self, cx: &mut Context<'_, Self>, id: &str, name: &str) -> impl IntoElement {
         let path = format!("/products/{}", id);
         let name = name.to_string();
@@ -485,7 +488,7 @@ impl Render for ProductDetailPage {
                     .on_mouse_down(
                         MouseButton::Left,
                         cx.listener(|_view, _event, _window, cx| {
-                            Navigator::push(cx, "/products/list".to_string());
+                            Navigator::push(cx, "../list");
                         }),
                     )
                     .child("← Back to List"),
@@ -0,0 +1,179 @@
+//! Skeleton screen demo
+//!
+//! The inbox loads its messages for a second and a half. Meanwhile the
+//! outlet shows a list skeleton shaped like the page, which slides in with
+//! the route's transition and crossfades to the messages once they arrive.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+use std::time::Duration;
+
+const LOAD_TIME: Duration = Duration::from_millis(1500);
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.set_config(RouterConfig::new().skeleton_crossfade(Duration::from_millis(300)));
+            router.add_route(
+                Route::new("/", |_, _, _| {
+                    div()
+                        .p_8()
+                        .text_xl()
+                        .child("Open the inbox to load its messages")
+                })
+                .transition(Transition::slide_right(300)),
+            );
+            router.add_route(
+                Route::new("/inbox", |_, _, params| {
+                    match params.data::<Vec<Message>>() {
+                        Some(DataState::Ready(messages)) => message_list(&messages),
+                        _ => div().p_8().child("Could not load the inbox"),
+                    }
+                })
+                .loader(|_, cx| {
+                    let timer = cx.background_executor().timer(LOAD_TIME);
+                    async move {
+                        timer.await;
+                        Ok(fetch_messages())
+                    }
+                })
+                .skeleton(|_, _| list_skeleton())
+                .transition(Transition::slide_left(300)),
+            );
+        });
+
+        let bounds = Bounds::centered(None, size(px(600.), px(500.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Skeleton Screen Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| {
+                let outlet = cx.new(|_| RouterOutlet::new());
+                cx.new(|_| DemoApp { outlet })
+            },
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct Message {
+    from: &'static str,
+    subject: &'static str,
+}
+
+fn fetch_messages() -> Vec<Message> {
+    vec![
+        Message {
+            from: "Ada",
+            subject: "Notes on the analytical engine",
+        },
+        Message {
+            from: "Grace",
+            subject: "Found a moth in relay 70",
+        },
+        Message {
+            from: "Linus",
+            subject: "Just a hobby, won't be big",
+        },
+        Message {
+            from: "Margaret",
+            subject: "Landing software is ready",
+        },
+    ]
+}
+
+fn message_list(messages: &[Message]) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .p_4()
+        .children(messages.iter().map(|message| {
+            div()
+                .flex()
+                .gap_4()
+                .h(px(48.))
+                .px_4()
+                .items_center()
+                .rounded_md()
+                .bg(rgb(0xf5f5f5))
+                .child(
+                    div()
+                        .w(px(100.))
+                        .font_weight(FontWeight::BOLD)
+                        .child(message.from),
+                )
+                .child(message.subject)
+        }))
+}
+
+/// Rows shaped like the message list, without content
+fn list_skeleton() -> Div {
+    let bar = |width: f32| div().h(px(12.)).w(px(width)).rounded_sm().bg(rgb(0xdddddd));
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .p_4()
+        .children((0..4).map(move |_| {
+            div()
+                .flex()
+                .gap_4()
+                .h(px(48.))
+                .px_4()
+                .items_center()
+                .rounded_md()
+                .bg(rgb(0xf5f5f5))
+                .child(bar(80.))
+                .child(bar(220.))
+        }))
+}
+
+struct DemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl Render for DemoApp {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let nav_button = |id: &'static str, label: &'static str, path: &'static str| {
+            div()
+                .id(id)
+                .px_3()
+                .py_1()
+                .rounded_md()
+                .bg(rgb(0xe0e0e0))
+                .cursor_pointer()
+                .child(label)
+                .on_click(move |_, _, cx| {
+                    Navigator::push(cx, path);
+                })
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(rgb(0xffffff))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .p_4()
+                    .bg(rgb(0xf3f3f3))
+                    .child(nav_button("home", "Home", "/"))
+                    .child(nav_button("inbox", "Inbox", "/inbox")),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
@@ -0,0 +1,209 @@
+//! Master-detail split view demo
+//!
+//! A mail-style layout: the message list on the left, the selected message on
+//! the right. Selecting a message (click, or the up and down arrow keys) only
+//! navigates the `detail` outlet with `Navigator::set_outlet`, so the list
+//! keeps its scroll position and is not rebuilt.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const MESSAGES: usize = 40;
+const SELECTED: u32 = 0xe3f2fd;
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.set_config(RouterConfig::new().initial_path("/inbox"));
+
+            router.add_route(
+                Route::component("/inbox", || {
+                    SplitRouterView::new("list", "detail")
+                        .list_size(SplitSize::Ratio(0.35))
+                        .detail_placeholder(|| {
+                            div()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .size_full()
+                                .text_color(rgb(0x9e9e9e))
+                                .child("Select a message")
+                                .into_any_element()
+                        })
+                })
+                .named_outlet("list", vec![Route::component("", MessageList::new).into()])
+                .named_outlet(
+                    "detail",
+                    vec![Route::new("messages/:id", |_, _, params| message(params)).into()],
+                ),
+            );
+        });
+
+        let bounds = Bounds::centered(None, size(px(1000.), px(650.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Split View Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |window, cx| cx.new(|cx| SplitDemoApp::new(window, cx)),
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+/// Message shown in the detail outlet, if any
+fn selected(cx: &App) -> Option<usize> {
+    Navigator::outlet_path(cx, "detail")?
+        .strip_prefix("messages/")?
+        .parse()
+        .ok()
+}
+
+fn select(cx: &mut App, id: usize) {
+    Navigator::set_outlet(cx, "detail", format!("messages/{}", id));
+}
+
+struct SplitDemoApp {
+    outlet: Entity<RouterOutlet>,
+    focus_handle: FocusHandle,
+}
+
+impl SplitDemoApp {
+    fn new(window: &mut Window, cx: &mut Context<'_, Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+        Self {
+            outlet: cx.new(|_| RouterOutlet::new()),
+            focus_handle,
+        }
+    }
+}
+
+impl Render for SplitDemoApp {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(rgb(0xffffff))
+            // Up and down move the selection through the list
+            .on_key_down(|event: &KeyDownEvent, _, cx| {
+                let current = selected(cx);
+                let next = match event.keystroke.key.as_str() {
+                    "down" => current.map_or(0, |id| (id + 1).min(MESSAGES - 1)),
+                    "up" => current.map_or(0, |id| id.saturating_sub(1)),
+                    _ => return,
+                };
+                if Some(next) != current {
+                    select(cx, next);
+                }
+            })
+            .child(self.outlet.clone())
+    }
+}
+
+/// The message list, highlighting the message shown in the detail outlet
+struct MessageList {
+    selected: Option<usize>,
+    router_changed: Option<Subscription>,
+}
+
+impl MessageList {
+    fn new() -> Self {
+        Self {
+            selected: None,
+            router_changed: None,
+        }
+    }
+}
+
+impl Render for MessageList {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        // Only the selection changes this view; redraw it when it does
+        if self.router_changed.is_none() {
+            self.selected = selected(cx);
+            self.router_changed = Some(cx.observe_global::<GlobalRouter>(|list, cx| {
+                let selected = selected(cx);
+                if selected != list.selected {
+                    list.selected = selected;
+                    cx.notify();
+                }
+            }));
+        }
+
+        div()
+            .id("messages")
+            .flex()
+            .flex_col()
+            .size_full()
+            .overflow_y_scroll()
+            .children((0..MESSAGES).map(|id| {
+                let is_selected = self.selected == Some(id);
+                div()
+                    .id(("message", id))
+                    .flex()
+                    .flex_col()
+                    .px_4()
+                    .py_3()
+                    .border_b_1()
+                    .border_color(rgb(0xeeeeee))
+                    .cursor_pointer()
+                    .when(is_selected, |row| row.bg(rgb(SELECTED)))
+                    .hover(|row| row.bg(rgb(0xf5f5f5)))
+                    .child(
+                        div()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(rgb(0x212121))
+                            .child(sender(id)),
+                    )
+                    .child(div().text_sm().text_color(rgb(0x757575)).child(subject(id)))
+                    .on_click(move |_, _, cx| select(cx, id))
+            }))
+    }
+}
+
+fn sender(id: usize) -> String {
+    const NAMES: [&str; 5] = ["Ada", "Grace", "Linus", "Margaret", "Ken"];
+    NAMES[id % NAMES.len()].to_string()
+}
+
+fn subject(id: usize) -> String {
+    format!("Status update #{}", id + 1)
+}
+
+fn message(params: &RouteParams) -> impl IntoElement {
+    let id = params.get_as::<usize>("id").unwrap_or(0);
+    div()
+        .flex()
+        .flex_col()
+        .size_full()
+        .p_8()
+        .gap_4()
+        .child(
+            div()
+                .text_2xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0x212121))
+                .child(subject(id)),
+        )
+        .child(
+            div()
+                .text_color(rgb(0x757575))
+                .child(format!("From {}", sender(id))),
+        )
+        .child(div().text_color(rgb(0x424242)).child(format!(
+            "Message {} of {}. Only this pane is redrawn when you pick another \
+             message; the list keeps its scroll position.",
+            id + 1,
+            MESSAGES
+        )))
+}
@@ -235,6 +235,7 @@ impl Render for CounterPage {
 }
 
 impl CounterPage {
+    #[allow(clippy::unused_self)]
     fn button<F>(&self, cx: &mut Context<'_, Self>, label: &str, on_click: F) -> impl IntoElement
     where
         F: Fn(&mut Self, &mut Context<'_, Self>) + 'static,
@@ -362,6 +363,7 @@ impl Render for FormPage {
 }
 
 impl FormPage {
+    #[allow(clippy::unused_self)]
     fn button<F>(&self, cx: &mut Context<'_, Self>, label: &str, on_click: F) -> impl IntoElement
     where
         F: Fn(&mut Self, &mut Context<'_, Self>) + 'static,
@@ -0,0 +1,88 @@
+//! Swipe navigation demo
+//!
+//! Visit a few pages with the links, then swipe two fingers right on the
+//! trackpad to go back and left to go forward. The page follows the fingers;
+//! letting go before a third of the way springs it back. On the first page
+//! there is nothing to go back to, so the page only gives a little.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+const PAGES: [(&str, &str, u32); 4] = [
+    ("/", "Inbox", 0xe3f2fd),
+    ("/drafts", "Drafts", 0xfff3e0),
+    ("/sent", "Sent", 0xe8f5e9),
+    ("/archive", "Archive", 0xf3e5f5),
+];
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            for (path, title, color) in PAGES {
+                router.add_route(Route::new(path, move |_, _, _| page(title, color)));
+            }
+        });
+
+        let bounds = Bounds::centered(None, size(px(700.), px(450.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Swipe Navigation Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| {
+                let outlet = cx.new(|_| RouterOutlet::new());
+                let swipes = cx.new(|_| SwipeNavigator::new(outlet));
+                cx.new(|_| DemoApp { swipes })
+            },
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct DemoApp {
+    swipes: Entity<SwipeNavigator>,
+}
+
+impl Render for DemoApp {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(rgb(0xffffff))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_4()
+                    .p_4()
+                    .bg(rgb(0xf3f3f3))
+                    .children(
+                        PAGES
+                            .iter()
+                            .map(|(path, title, _)| router_link(cx, *path, *title)),
+                    ),
+            )
+            .child(div().flex_1().child(self.swipes.clone()))
+    }
+}
+
+fn page(title: &str, color: u32) -> impl IntoElement {
+    div()
+        .size_full()
+        .p_8()
+        .bg(rgb(color))
+        .text_2xl()
+        .font_weight(FontWeight::BOLD)
+        .child(title.to_string())
+}
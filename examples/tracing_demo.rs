@@ -0,0 +1,275 @@
+//! Navigation diagnostics demo
+//!
+//! Run with `cargo run --example tracing_demo --features tracing`. Every
+//! navigation prints its `router.navigate` span with the `match` span below
+//! it, and the outcome once it is done. The "Missing page" link shows the
+//! warning for a path no route matches, "Admin" the one for a guard redirecting
+//! to the home page.
+//!
+//! The subscriber below is a minimal stand-in for `tracing_subscriber::fmt()`;
+//! apps would normally install that instead:
+//!
+//! ```ignore
+//! tracing_subscriber::fmt()
+//!     .with_max_level(tracing::Level::TRACE)
+//!     .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+//!     .init();
+//! ```
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata};
+
+fn main() {
+    tracing::subscriber::set_global_default(FmtSubscriber::default())
+        .expect("no other subscriber installed");
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.add_route(Route::new("/", |_, _, _| {
+                page("Home", "Navigate and watch the terminal.")
+            }));
+            router.add_route(
+                Route::new("/users", |window, cx, _| {
+                    div()
+                        .flex()
+                        .flex_col()
+                        .child(page("Users", "Nested outlet below."))
+                        .child(render_router_outlet(window, cx, None))
+                        .into_any_element()
+                })
+                .children(vec![Route::new(":id", |_, _, params| {
+                    let id = params.get("id").cloned().unwrap_or_default();
+                    page("User", &format!("User #{id}"))
+                })
+                .into()]),
+            );
+            router.add_route(
+                Route::new("/admin", |_, _, _| page("Admin", "Not reachable."))
+                    .guard(AuthGuard::new(|_| false, "/")),
+            );
+        });
+
+        let bounds = Bounds::centered(None, size(px(900.), px(600.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Navigation Diagnostics Demo".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| cx.new(TracingDemoApp::new),
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+struct TracingDemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl TracingDemoApp {
+    fn new(cx: &mut Context<'_, Self>) -> Self {
+        Self {
+            outlet: cx.new(|_| RouterOutlet::new()),
+        }
+    }
+}
+
+impl Render for TracingDemoApp {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .size_full()
+            .bg(rgb(0x1e1e1e))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .w_48()
+                    .p_4()
+                    .gap_2()
+                    .bg(rgb(0x252526))
+                    .child(nav_link("home", "/", "Home"))
+                    .child(nav_link("user-1", "/users/1", "User 1"))
+                    .child(nav_link("user-2", "/users/2", "User 2"))
+                    .child(nav_link("admin", "/admin", "Admin"))
+                    .child(nav_link("missing", "/missing", "Missing page"))
+                    .child(
+                        div()
+                            .id("back")
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xcccccc))
+                            .hover(|this| this.bg(rgb(0x2a2d2e)))
+                            .child("← Back")
+                            .on_click(|_, window, cx| {
+                                Navigator::pop(cx);
+                                window.refresh();
+                            }),
+                    ),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
+
+fn page(title: &str, body: &str) -> AnyElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_4()
+        .p_8()
+        .child(
+            div()
+                .text_2xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(title.to_string()),
+        )
+        .child(div().text_color(rgb(0xcccccc)).child(body.to_string()))
+        .into_any_element()
+}
+
+fn nav_link(id: &'static str, path: &'static str, label: &'static str) -> impl IntoElement {
+    div()
+        .id(id)
+        .px_3()
+        .py_2()
+        .rounded_md()
+        .cursor_pointer()
+        .text_color(rgb(0xcccccc))
+        .hover(|this| this.bg(rgb(0x2a2d2e)))
+        .child(label)
+        .on_click(move |_, window, cx| {
+            // The router only pre-checks guards; report a blocked link
+            if let AccessCheck::Blocked(reason) = Navigator::can_access(cx, path) {
+                println!("blocked: {reason}");
+                return;
+            }
+            Navigator::push(cx, path);
+            window.refresh();
+        })
+}
+
+/// Prints spans and events to stdout, indented by span depth
+///
+/// A span is printed when entered; fields recorded later (like the
+/// navigation's `outcome`) are printed when it exits. `render.outlet` spans
+/// are trace-level and filtered out here, as outlets render on every frame.
+#[derive(Default)]
+struct FmtSubscriber {
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, Span>>,
+    depth: Mutex<usize>,
+}
+
+struct Span {
+    name: &'static str,
+    fields: Fields,
+    recorded: Fields,
+}
+
+/// Collects fields as `name=value` pairs
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let _ = write!(self.0, " {}={:?}", field.name(), value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {value:?}");
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+impl FmtSubscriber {
+    fn print(&self, line: &str) {
+        let depth = *self.depth.lock().unwrap();
+        println!("{}{}", "  ".repeat(depth), line);
+    }
+}
+
+impl tracing::Subscriber for FmtSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with("gpui_navigator") && *metadata.level() <= Level::DEBUG
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut span = Span {
+            name: attrs.metadata().name(),
+            fields: Fields::default(),
+            recorded: Fields::default(),
+        };
+        attrs.record(&mut span.fields);
+        self.spans.lock().unwrap().insert(id, span);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, id: &Id, values: &Record<'_>) {
+        if let Some(span) = self.spans.lock().unwrap().get_mut(&id.into_u64()) {
+            values.record(&mut span.recorded);
+        }
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(event.metadata().level().to_string());
+        event.record(&mut fields);
+        self.print(&fields.0);
+    }
+
+    fn enter(&self, id: &Id) {
+        let line = self
+            .spans
+            .lock()
+            .unwrap()
+            .get(&id.into_u64())
+            .map(|span| format!("{}{}", span.name, span.fields.0));
+        if let Some(line) = line {
+            self.print(&line);
+        }
+        *self.depth.lock().unwrap() += 1;
+    }
+
+    fn exit(&self, id: &Id) {
+        {
+            let mut depth = self.depth.lock().unwrap();
+            *depth = depth.saturating_sub(1);
+        }
+        let line = self
+            .spans
+            .lock()
+            .unwrap()
+            .get_mut(&id.into_u64())
+            .filter(|span| !span.recorded.0.is_empty())
+            .map(|span| format!("{} done{}", span.name, std::mem::take(&mut span.recorded.0)));
+        if let Some(line) = line {
+            self.print(&line);
+        }
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        self.spans.lock().unwrap().remove(&id.into_u64());
+        true
+    }
+}
@@ -52,6 +52,43 @@ fn main() {
                 .name("slide-down")
                 .transition(Transition::slide_down(1000)), // 1 секунда
             );
+
+            router.add_route(
+                Route::new("/zoom-in", |_, _, _| zoom_in_page().into_any_element())
+                    .name("zoom-in")
+                    .transition(Transition::zoom_in(1000)),
+            );
+
+            router.add_route(
+                Route::new("/zoom-out", |_, _, _| zoom_out_page().into_any_element())
+                    .name("zoom-out")
+                    .transition(Transition::zoom_out(1000)),
+            );
+
+            // Automatic slides: drilling down slides left, going back up slides right
+            router.add_route(
+                Route::new("/auto", |_, _, _| auto_list_page().into_any_element())
+                    .name("auto")
+                    .transition(Transition::slide_auto(600).dim_old(0.3)),
+            );
+
+            router.add_route(
+                Route::new("/auto/:item", |_, _, params| {
+                    let item = params.get("item").cloned().unwrap_or_default();
+                    auto_detail_page(item).into_any_element()
+                })
+                .name("auto-detail")
+                .transition(Transition::slide_auto(600).dim_old(0.3)),
+            );
+
+            // The page animates its own content using the transition progress
+            router.add_route(
+                Route::animated("/stagger", |_, _, _, progress| {
+                    stagger_page(progress).into_any_element()
+                })
+                .name("stagger")
+                .transition(Transition::fade(1000)),
+            );
         });
 
         // Create and open window
@@ -177,6 +214,34 @@ fn sidebar(
             &current_path,
             outlet.clone(),
         ))
+        .child(nav_button(
+            cx,
+            "Zoom In",
+            "/zoom-in",
+            &current_path,
+            outlet.clone(),
+        ))
+        .child(nav_button(
+            cx,
+            "Zoom Out",
+            "/zoom-out",
+            &current_path,
+            outlet.clone(),
+        ))
+        .child(nav_button(
+            cx,
+            "Auto Slide",
+            "/auto",
+            &current_path,
+            outlet.clone(),
+        ))
+        .child(nav_button(
+            cx,
+            "Stagger",
+            "/stagger",
+            &current_path,
+            outlet.clone(),
+        ))
         .child(div().h_px().bg(rgb(0xe0e0e0)).my_4())
         .child(
             div()
@@ -277,6 +342,159 @@ fn slide_down_page() -> impl IntoElement {
     )
 }
 
+fn zoom_in_page() -> impl IntoElement {
+    page_container(
+        "Zoom In".to_string(),
+        "Transition::zoom_in(1000) - Page grows from 80% to full size while fading in.".to_string(),
+        rgb(0x9c27b0),
+        rgb(0xf3e5f5), // Light purple background
+    )
+}
+
+fn zoom_out_page() -> impl IntoElement {
+    page_container(
+        "Zoom Out".to_string(),
+        "Transition::zoom_out(1000) - Page shrinks from 120% to full size while fading in."
+            .to_string(),
+        rgb(0xff5722),
+        rgb(0xfbe9e7), // Light deep orange background
+    )
+}
+
+fn auto_list_page() -> impl IntoElement {
+    let items = ["apples", "pears", "plums"];
+
+    div()
+        .flex()
+        .flex_col()
+        .size_full()
+        .bg(rgb(0xede7f6))
+        .p_8()
+        .items_center()
+        .justify_center()
+        .gap_4()
+        .child(
+            div()
+                .text_3xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0x212121))
+                .child("Auto Slide"),
+        )
+        .child(
+            div()
+                .max_w_96()
+                .text_center()
+                .text_color(rgb(0x666666))
+                .line_height(relative(1.5))
+                .child(
+                    "Transition::slide_auto(600) - Open an item to slide in from the right; \
+                     going back slides the other way.",
+                ),
+        )
+        .children(items.into_iter().map(|item| {
+            demo_button(item, format!("Open {}", item), move |cx| {
+                Navigator::push(cx, format!("/auto/{}", item));
+            })
+        }))
+}
+
+fn auto_detail_page(item: String) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .size_full()
+        .bg(rgb(0xd1c4e9))
+        .p_8()
+        .items_center()
+        .justify_center()
+        .gap_4()
+        .child(
+            div()
+                .text_3xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0x212121))
+                .child(format!("Item: {}", item)),
+        )
+        .child(
+            div()
+                .text_color(rgb(0x666666))
+                .child("One level deeper than the list."),
+        )
+        .child(demo_button("back", "← Back to the list", |cx| {
+            Navigator::pop(cx);
+        }))
+}
+
+fn demo_button(
+    id: &'static str,
+    label: impl Into<SharedString>,
+    on_click: impl Fn(&mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .id(id)
+        .px_6()
+        .py_3()
+        .rounded_md()
+        .cursor_pointer()
+        .bg(rgb(0x673ab7))
+        .text_color(rgb(0xffffff))
+        .hover(|this| this.bg(rgb(0x5e35b1)))
+        .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+            on_click(cx);
+            window.refresh();
+        })
+        .child(label.into())
+}
+
+fn stagger_page(progress: TransitionProgress) -> impl IntoElement {
+    let colors = [rgb(0xe91e63), rgb(0x3f51b5), rgb(0x009688)];
+
+    div()
+        .flex()
+        .flex_col()
+        .size_full()
+        .bg(rgb(0xfce4ec))
+        .p_8()
+        .items_center()
+        .justify_center()
+        .gap_6()
+        .child(
+            div()
+                .flex()
+                .gap_4()
+                .children(colors.iter().enumerate().map(|(i, color)| {
+                    // Each box slides up and fades in after the previous one
+                    let t = progress.staggered(i, colors.len());
+                    div()
+                        .w_24()
+                        .h_24()
+                        .rounded_lg()
+                        .bg(*color)
+                        .shadow_lg()
+                        .opacity(t)
+                        .mt(px(40.0 * (1.0 - t)))
+                })),
+        )
+        .child(
+            div()
+                .text_3xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0x212121))
+                .child("Staggered Content"),
+        )
+        .child(
+            div()
+                .max_w_96()
+                .text_center()
+                .text_color(rgb(0x666666))
+                .line_height(relative(1.5))
+                .child(
+                    "Route::animated - The builder receives the transition progress and \
+                     staggers the boxes while the page fades in.",
+                ),
+        )
+}
+
 fn page_container(
     title: String,
     description: String,
@@ -0,0 +1,143 @@
+//! Per-route window hints demo
+//!
+//! The workspace and the quick capture page ask for different window titles
+//! and minimum sizes. Switching between them retitles the window and grows it
+//! when it is smaller than the page's minimum; going back restores the
+//! workspace's title.
+
+use gpui::prelude::*;
+use gpui::*;
+use gpui_navigator::*;
+
+fn main() {
+    env_logger::init();
+
+    Application::new().run(|cx: &mut App| {
+        init_router(cx, |router| {
+            router.set_base_window_hints(
+                WindowHints::new()
+                    .title("Workspace")
+                    .min_size(size(px(480.), px(320.))),
+            );
+            router.set_window_hint_applier(apply_hints);
+
+            router.add_route(Route::new("/", |_, _, _| {
+                page("Workspace", "The main window, at any size above 480×320.")
+            }));
+            router.add_route(
+                Route::new("/quick-capture", |_, _, _| {
+                    page("Quick Capture", "Asks for at least 900×600.")
+                })
+                .window_hints(
+                    WindowHints::new()
+                        .title("Quick Capture")
+                        .min_size(size(px(900.), px(600.))),
+                ),
+            );
+        });
+
+        let bounds = Bounds::centered(None, size(px(600.), px(400.)), cx);
+        cx.open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                titlebar: Some(TitlebarOptions {
+                    title: Some("Workspace".into()),
+                    appears_transparent: false,
+                    traffic_light_position: None,
+                }),
+                ..Default::default()
+            },
+            |_, cx| {
+                let outlet = cx.new(|_| RouterOutlet::new());
+                cx.new(|_| DemoApp { outlet })
+            },
+        )
+        .unwrap();
+
+        cx.activate(true);
+    });
+}
+
+/// Set the title and grow the window to the minimum size
+///
+/// GPUI cannot change the minimum size of an open window, so the demo only
+/// makes sure the window is not smaller than it.
+fn apply_hints(hints: &WindowHints, window: &mut Window, cx: &mut App) {
+    apply_window_title(hints, window, cx);
+    if let Some(min_size) = hints.min_size {
+        let current = window.viewport_size();
+        if current.width < min_size.width || current.height < min_size.height {
+            window.resize(size(
+                current.width.max(min_size.width),
+                current.height.max(min_size.height),
+            ));
+        }
+    }
+}
+
+struct DemoApp {
+    outlet: Entity<RouterOutlet>,
+}
+
+impl Render for DemoApp {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(rgb(0x1e1e1e))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .p_4()
+                    .bg(rgb(0x252526))
+                    .child(button("back", "Back", |cx| {
+                        Navigator::pop(cx);
+                    }))
+                    .child(button("workspace", "Workspace", |cx| {
+                        Navigator::push(cx, "/");
+                    }))
+                    .child(button("capture", "Quick Capture", |cx| {
+                        Navigator::push(cx, "/quick-capture");
+                    })),
+            )
+            .child(div().flex_1().child(self.outlet.clone()))
+    }
+}
+
+fn page(title: &str, description: &str) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .p_8()
+        .child(
+            div()
+                .text_2xl()
+                .font_weight(FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(title.to_string()),
+        )
+        .child(
+            div()
+                .text_color(rgb(0xcccccc))
+                .child(description.to_string()),
+        )
+}
+
+fn button(id: &'static str, label: &'static str, on_click: fn(&mut App)) -> impl IntoElement {
+    div()
+        .id(id)
+        .px_3()
+        .py_2()
+        .rounded_md()
+        .cursor_pointer()
+        .text_color(rgb(0xcccccc))
+        .hover(|this| this.bg(rgb(0x2a2d2e)))
+        .child(label)
+        .on_click(move |_, window, cx| {
+            on_click(cx);
+            window.refresh();
+        })
+}
@@ -0,0 +1,95 @@
+//! Procedural macros for `gpui-navigator`
+//!
+//! Enable the `macros` feature of `gpui-navigator` to use them; the
+//! expansions refer to items of the `gpui_navigator` crate.
+
+use gpui_navigator_paths::validate_route_path;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// Check a route path at compile time and turn it into a `PathPattern`
+///
+/// The path is validated by `gpui_navigator::validate_route_path`, as the
+/// router does at runtime; invalid paths fail to compile with its message.
+/// The pattern carries the parsed segments, so routes created from it do not
+/// parse the path again. It can be used wherever a route path is expected,
+/// and in constants:
+///
+/// ```ignore
+/// use gpui_navigator::{route_path, Navigator, PathPattern, Route};
+///
+/// const USER: PathPattern = route_path!("/users/:id");
+///
+/// router.add_route(Route::new(USER, user_page));
+/// Navigator::push(cx, route_path!("/settings"));
+/// ```
+#[proc_macro]
+pub fn route_path(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+    let path = literal.value();
+
+    if let Err(e) = validate_route_path(&path) {
+        return syn::Error::new(
+            literal.span(),
+            format!("Invalid route path '{}': {}", path, e),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let segments = parse_path(&path).into_iter().map(|segment| match segment {
+        Segment::Static(name) => quote!(::gpui_navigator::PathSegment::Static(#name)),
+        Segment::Param(name) => quote!(::gpui_navigator::PathSegment::Param(#name)),
+        Segment::Wildcard => quote!(::gpui_navigator::PathSegment::Wildcard),
+    });
+
+    quote! {{
+        const PATTERN: ::gpui_navigator::PathPattern =
+            ::gpui_navigator::PathPattern::from_parsed(#path, &[#(#segments),*]);
+        PATTERN
+    }}
+    .into()
+}
+
+/// Segment of a path, as `gpui_navigator::PathSegment`
+#[derive(Debug, PartialEq, Eq)]
+enum Segment<'a> {
+    Static(&'a str),
+    Param(&'a str),
+    Wildcard,
+}
+
+/// Split a path into its segments, as the router's matcher does
+fn parse_path(path: &str) -> Vec<Segment<'_>> {
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .map(|segment| {
+            if segment == "*" {
+                Segment::Wildcard
+            } else if let Some(param_name) = segment.strip_prefix(':') {
+                Segment::Param(param_name)
+            } else {
+                Segment::Static(segment)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("/files/:id/*"),
+            vec![
+                Segment::Static("files"),
+                Segment::Param("id"),
+                Segment::Wildcard
+            ]
+        );
+        assert!(parse_path("/").is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+//! Route path rules shared by `gpui-navigator` and its macros
+//!
+//! `route_path!` checks paths at compile time with the same function the
+//! router uses at runtime, so both reject the same paths with the same
+//! messages. Use it through `gpui_navigator::validate_route_path`.
+
+use std::collections::HashSet;
+
+/// Validate a route path pattern
+///
+/// Returns an error message if the path is invalid, None otherwise.
+///
+/// # Validation Rules
+///
+/// - Path can be empty (for index routes)
+/// - Path must start with '/' or be relative (no leading '/')
+/// - No consecutive slashes ('//')
+/// - Trailing slashes are allowed but not recommended (normalized internally)
+/// - Parameter names must be alphanumeric and not empty
+/// - No duplicate parameter names
+/// - A wildcard (`*`) can only be the last segment
+pub fn validate_route_path(path: &str) -> Result<(), String> {
+    // Empty path is allowed for index routes
+    if path.is_empty() {
+        return Ok(());
+    }
+
+    // Consecutive slashes check
+    if path.contains("//") {
+        return Err("Route path cannot contain consecutive slashes".to_string());
+    }
+
+    // Note: Trailing slashes are allowed for compatibility
+    // They are normalized during route matching
+
+    // A wildcard matches the rest of the path, so nothing may follow it
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    if segments.by_ref().any(|segment| segment == "*") && segments.next().is_some() {
+        return Err("Wildcard '*' must be the last segment of a route path".to_string());
+    }
+
+    // Extract and validate parameters
+    let mut param_names = HashSet::new();
+    for segment in path.split('/') {
+        if let Some(param) = segment.strip_prefix(':') {
+            // Check parameter name is not empty
+            if param.is_empty() {
+                return Err("Route parameter name cannot be empty".to_string());
+            }
+
+            // Check for constraint syntax (:id{uuid})
+            let param_name = param.find('{').map_or(param, |pos| &param[..pos]);
+
+            // Check parameter name is alphanumeric
+            if !param_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(format!(
+                    "Route parameter '{}' must contain only alphanumeric characters and underscores",
+                    param_name
+                ));
+            }
+
+            // Check for duplicate parameters
+            if !param_names.insert(param_name) {
+                return Err(format!("Duplicate route parameter: '{}'", param_name));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! Navigation as gpui actions
+//!
+//! Menu items and key bindings dispatch actions. After
+//! [`bind_router_actions`], the actions of this module navigate without a
+//! handler per item:
+//!
+//! ```ignore
+//! use gpui::{Menu, MenuItem};
+//! use gpui_navigator::actions::{self, NavigateBack, NavigateTo};
+//!
+//! actions::bind_router_actions(cx);
+//! cx.set_menus(vec![
+//!     Menu {
+//!         name: "View".into(),
+//!         items: vec![
+//!             MenuItem::action("Dashboard", NavigateTo::path("/dashboard")),
+//!             MenuItem::action("Back", NavigateBack),
+//!         ],
+//!     },
+//!     // One item per route the palette lists, kept in sync with the table
+//!     Menu {
+//!         name: "Go".into(),
+//!         items: actions::navigation_menu_items(cx),
+//!     },
+//! ]);
+//! ```
+//!
+//! [`NavigateTo`] deserializes from keymap files:
+//!
+//! ```json
+//! {
+//!   "bindings": {
+//!     "cmd-1": ["navigator::NavigateTo", { "path": "/dashboard" }],
+//!     "cmd-u": ["navigator::NavigateTo", { "name": "user.detail", "params": { "id": "me" } }],
+//!     "cmd-[": "navigator::NavigateBack"
+//!   }
+//! }
+//! ```
+//!
+//! Navigations the actions start carry [`NavigationSource::Action`], except
+//! going back and forward, which are [`NavigationSource::History`] whatever
+//! asks for them.
+//!
+//! The handlers navigate through [`Navigator`], so the usual rules apply to
+//! actions dispatched mid-navigation: while an outlet renders they are
+//! queued until the render is done (see
+//! [`RouterConfig::on_render_navigation`](crate::RouterConfig::on_render_navigation)),
+//! and one dispatched while a blocking loader holds a navigation supersedes
+//! it, like any other push.
+
+use crate::config::NavOptions;
+use crate::context::{GlobalRouter, Navigator};
+use crate::error::report_refused_navigations;
+use crate::palette::NavigationCommand;
+use crate::route::{IntoRoute, RouteDescriptor};
+use crate::{warn_log, NavigationSource, QueryParams, RouteParams};
+use gpui::private::schemars::JsonSchema;
+use gpui::{App, MenuItem};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Navigate to a path, or to a named route with params
+///
+/// Registered as `navigator::NavigateTo`. When `name` is set, `path` is not
+/// used.
+// The registration `gpui::Action` generates is what uses `unsafe`
+#[allow(clippy::unsafe_derive_deserialize)]
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, gpui::Action,
+)]
+#[action(namespace = navigator)]
+#[serde(default, deny_unknown_fields)]
+#[schemars(crate = "gpui::private::schemars")]
+pub struct NavigateTo {
+    /// Path to push, e.g. `/dashboard`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Name of the route to push
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Params of the named route
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+impl NavigateTo {
+    /// Navigate to `path`
+    pub fn path(path: impl Into<String>) -> Self {
+        Self {
+            path: Some(path.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Navigate to the route registered under `name`
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Set a param of the named route
+    pub fn param(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.params.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Action running a palette command, `None` if it needs input
+    ///
+    /// Named commands navigate by name with their provided params; others
+    /// to their expanded pattern.
+    pub fn for_command(command: &NavigationCommand) -> Option<Self> {
+        if command.needs_input {
+            return None;
+        }
+        if let Some(name) = &command.name {
+            return Some(Self {
+                name: Some(name.clone()),
+                params: command.hints.clone(),
+                ..Self::default()
+            });
+        }
+        command.target(&RouteParams::new()).ok().map(Self::path)
+    }
+}
+
+impl IntoRoute for NavigateTo {
+    fn into_route(self) -> RouteDescriptor {
+        match self.name {
+            Some(name) => RouteDescriptor {
+                path: name.clone(),
+                params: RouteParams::from_map(self.params.into_iter().collect()),
+                builder: None,
+                name: Some(name),
+                query: QueryParams::new(),
+            },
+            None => self.path.unwrap_or_default().into_route(),
+        }
+    }
+}
+
+gpui::actions!(
+    navigator,
+    [
+        /// Go back in history, like `Navigator::pop`
+        NavigateBack,
+        /// Go forward in history, like `Navigator::forward`
+        NavigateForward,
+        /// Navigate to the current path again, rerunning guards and loaders
+        NavigateReload,
+    ]
+);
+
+/// Install global handlers for the navigation actions
+///
+/// Call once, after [`init_router`](crate::init_router). Actions
+/// dispatched while no router is initialized are ignored. [`NavigateBack`]
+/// and [`NavigateForward`] with no entry left run the handlers set with
+/// [`RouterConfig::on_back_at_root`](crate::RouterConfig::on_back_at_root)
+/// and [`RouterConfig::on_forward_at_end`](crate::RouterConfig::on_forward_at_end),
+/// if any.
+pub fn bind_router_actions(cx: &mut App) {
+    cx.on_action(|action: &NavigateTo, cx| {
+        if action.path.is_none() && action.name.is_none() {
+            warn_log!("NavigateTo has neither a path nor a name");
+            return;
+        }
+        navigate(cx, |cx| {
+            Navigator::push_opts(cx, action.clone(), action_options());
+        });
+    });
+    cx.on_action(|_: &NavigateBack, cx| {
+        navigate(cx, |cx| {
+            Navigator::back(cx);
+        });
+    });
+    cx.on_action(|_: &NavigateForward, cx| {
+        navigate(cx, |cx| {
+            Navigator::forward(cx);
+        });
+    });
+    cx.on_action(|_: &NavigateReload, cx| {
+        navigate(cx, |cx| {
+            let path = Navigator::current_path(cx);
+            Navigator::replace_opts(cx, path, action_options());
+        });
+    });
+}
+
+/// Options of the pushes and replaces actions make
+fn action_options() -> NavOptions {
+    NavOptions::new().source(NavigationSource::Action)
+}
+
+/// Run an action's navigation, then report refusals and refresh windows
+fn navigate(cx: &mut App, navigation: impl FnOnce(&mut App)) {
+    if !cx.has_global::<GlobalRouter>() {
+        warn_log!("Navigation action dispatched before the router was initialized");
+        return;
+    }
+    navigation(cx);
+    report_refused_navigations(cx);
+    crate::context::refresh_windows(cx);
+}
+
+/// Menu items for the navigation commands of the active route table
+///
+/// One [`NavigateTo`] item per command of
+/// [`Navigator::navigation_commands`] that needs no input, labelled like
+/// the command.
+pub fn navigation_menu_items(cx: &App) -> Vec<MenuItem> {
+    menu_items(&Navigator::navigation_commands(cx))
+}
+
+/// Menu items for `commands`, skipping those that need input
+pub fn menu_items(commands: &[NavigationCommand]) -> Vec<MenuItem> {
+    commands
+        .iter()
+        .filter_map(|command| {
+            let action = NavigateTo::for_command(command)?;
+            Some(MenuItem::action(command.label.clone(), action))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{init_router, Route, RouterConfig, RouterOutlet};
+    use gpui::{BorrowAppContext, IntoElement, TestAppContext, VisualTestContext};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn open_app(cx: &mut TestAppContext) -> &mut VisualTestContext {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| gpui::div().into_any_element()));
+                router.add_route(
+                    Route::new("/dashboard", |_, _, _| gpui::div().into_any_element())
+                        .name("dashboard"),
+                );
+                router.add_route(
+                    Route::new("/users/:id", |_, _, _| gpui::div().into_any_element())
+                        .name("user.detail"),
+                );
+            });
+            bind_router_actions(cx);
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx
+    }
+
+    #[gpui::test]
+    fn test_navigate_to_updates_current_path(cx: &mut TestAppContext) {
+        let cx = open_app(cx);
+
+        cx.dispatch_action(NavigateTo::path("/dashboard"));
+        assert_eq!(cx.read(Navigator::current_path), "/dashboard");
+
+        cx.dispatch_action(NavigateTo::named("user.detail").param("id", 7));
+        assert_eq!(cx.read(Navigator::current_path), "/users/7");
+
+        cx.dispatch_action(NavigateReload);
+        assert_eq!(cx.read(Navigator::current_path), "/users/7");
+
+        // Unknown names are refused, leaving the current path
+        cx.dispatch_action(NavigateTo::named("missing"));
+        assert_eq!(cx.read(Navigator::current_path), "/users/7");
+    }
+
+    #[gpui::test]
+    fn test_back_and_forward_respect_history(cx: &mut TestAppContext) {
+        let cx = open_app(cx);
+
+        // Nothing to go back or forward to yet
+        cx.dispatch_action(NavigateBack);
+        cx.dispatch_action(NavigateForward);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        cx.dispatch_action(NavigateTo::path("/dashboard"));
+        cx.dispatch_action(NavigateBack);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert!(cx.read(Navigator::can_go_forward));
+
+        cx.dispatch_action(NavigateForward);
+        assert_eq!(cx.read(Navigator::current_path), "/dashboard");
+        assert!(!cx.read(Navigator::can_go_forward));
+        cx.dispatch_action(NavigateForward);
+        assert_eq!(cx.read(Navigator::current_path), "/dashboard");
+    }
+
+    #[gpui::test]
+    fn test_back_at_root_runs_app_handler(cx: &mut TestAppContext) {
+        let cx = open_app(cx);
+        let backs = Arc::new(AtomicUsize::new(0));
+        let counted = backs.clone();
+        cx.update(|_, cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.set_config(RouterConfig::new().on_back_at_root(move |_| {
+                    counted.fetch_add(1, Ordering::SeqCst);
+                }));
+            });
+        });
+
+        cx.dispatch_action(NavigateTo::path("/dashboard"));
+        cx.dispatch_action(NavigateBack);
+        assert_eq!(backs.load(Ordering::SeqCst), 0);
+        cx.dispatch_action(NavigateBack);
+        assert_eq!(backs.load(Ordering::SeqCst), 1);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[cfg(feature = "middleware")]
+    #[gpui::test]
+    fn test_actions_stamp_their_source(cx: &mut TestAppContext) {
+        use crate::{AnalyticsMiddleware, ScreenView};
+        use std::sync::Mutex;
+
+        let cx = open_app(cx);
+        let views = Arc::new(Mutex::new(Vec::new()));
+        let captured = views.clone();
+        cx.update(|_, cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.add_middleware(AnalyticsMiddleware::new(move |view: ScreenView| {
+                    captured.lock().unwrap().push(view.source);
+                }));
+            });
+        });
+
+        cx.dispatch_action(NavigateTo::path("/dashboard"));
+        cx.dispatch_action(NavigateTo::named("user.detail").param("id", 7));
+        cx.dispatch_action(NavigateBack);
+        cx.run_until_parked();
+
+        assert_eq!(
+            *views.lock().unwrap(),
+            vec![
+                NavigationSource::Action,
+                NavigationSource::Action,
+                NavigationSource::History
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_keymap_action_round_trips(cx: &mut TestAppContext) {
+        let action = NavigateTo::named("user.detail").param("id", "me");
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "name": "user.detail", "params": { "id": "me" } })
+        );
+
+        let built = cx
+            .update(|cx| cx.build_action("navigator::NavigateTo", Some(json)))
+            .unwrap();
+        assert!(built.partial_eq(&action));
+
+        let built = cx
+            .update(|cx| {
+                cx.build_action(
+                    "navigator::NavigateTo",
+                    Some(serde_json::json!({ "path": "/dashboard" })),
+                )
+            })
+            .unwrap();
+        assert!(built.partial_eq(&NavigateTo::path("/dashboard")));
+        assert!(cx
+            .update(|cx| cx.build_action("navigator::NavigateBack", None))
+            .unwrap()
+            .partial_eq(&NavigateBack));
+    }
+
+    #[gpui::test]
+    fn test_menu_items_follow_commands(cx: &mut TestAppContext) {
+        let _ = open_app(cx);
+        let commands = cx.read(Navigator::navigation_commands);
+        let actions: Vec<NavigateTo> = commands
+            .iter()
+            .filter_map(NavigateTo::for_command)
+            .collect();
+
+        // `user.detail` needs an id
+        assert_eq!(actions, vec![NavigateTo::named("dashboard")]);
+        assert_eq!(menu_items(&commands).len(), 1);
+    }
+}
@@ -45,6 +45,13 @@ struct ParentRouteCacheEntry {
     parent_route_id: RouteId,
 }
 
+/// Cached result of resolving the child an outlet renders
+#[derive(Debug, Clone)]
+struct ChildRouteCacheEntry {
+    child_route_id: RouteId,
+    params: RouteParams,
+}
+
 /// Cache performance statistics
 #[derive(Debug, Clone, Default)]
 pub struct CacheStats {
@@ -53,6 +60,12 @@ pub struct CacheStats {
     pub child_hits: usize,
     pub child_misses: usize,
     pub invalidations: usize,
+    /// Components currently kept alive by routes with keep-alive
+    pub kept_alive: usize,
+    /// Revisits that reused a kept-alive component
+    pub kept_alive_reuses: usize,
+    /// Kept-alive components dropped, by limit or by hand
+    pub kept_alive_evictions: usize,
 }
 
 impl CacheStats {
@@ -86,14 +99,38 @@ impl CacheStats {
     }
 }
 
+impl std::fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "parent {:.1}% ({}/{}), child {:.1}% ({}/{}), overall {:.1}%, invalidations {}",
+            self.parent_hit_rate() * 100.0,
+            self.parent_hits,
+            self.parent_hits + self.parent_misses,
+            self.child_hit_rate() * 100.0,
+            self.child_hits,
+            self.child_hits + self.child_misses,
+            self.overall_hit_rate() * 100.0,
+            self.invalidations
+        )
+    }
+}
+
 /// Route resolution cache with LRU eviction
 ///
 /// Default capacity: 1000 entries per cache.
+///
+/// Parent lookups only depend on the route tree, so they survive navigation
+/// and are dropped by [`RouteCache::clear`] when routes change. Outlet
+/// resolutions (the child an outlet renders) are keyed by path and outlet
+/// name, and navigation drops those of the paths it leaves and reaches with
+/// [`RouteCache::invalidate_path`].
 #[derive(Debug)]
 pub struct RouteCache {
     parent_cache: LruCache<String, ParentRouteCacheEntry>,
-    child_cache: LruCache<OutletCacheKey, RouteParams>,
+    child_cache: LruCache<OutletCacheKey, ChildRouteCacheEntry>,
     stats: CacheStats,
+    enabled: bool,
 }
 
 impl RouteCache {
@@ -109,7 +146,36 @@ impl RouteCache {
             parent_cache: LruCache::new(cap),
             child_cache: LruCache::new(cap),
             stats: CacheStats::default(),
+            enabled: true,
+        }
+    }
+
+    /// Maximum number of entries per cache
+    pub fn capacity(&self) -> usize {
+        self.parent_cache.cap().get()
+    }
+
+    /// Change the capacity, evicting least recently used entries if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let cap = NonZeroUsize::new(capacity).expect("Cache capacity must be non-zero");
+        self.parent_cache.resize(cap);
+        self.child_cache.resize(cap);
+    }
+
+    /// Check if lookups go through the cache
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable the cache
+    ///
+    /// Disabling drops all cached entries; lookups then always miss
+    /// without being recorded in the stats.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled && self.enabled {
+            self.clear();
         }
+        self.enabled = enabled;
     }
 
     pub fn clear(&mut self) {
@@ -119,7 +185,37 @@ impl RouteCache {
         self.stats.invalidations += 1;
     }
 
+    /// Drop cached outlet resolutions for a single path
+    ///
+    /// Parent lookups are left alone since they don't depend on navigation.
+    pub fn invalidate_path(&mut self, path: &str) {
+        let stale: Vec<OutletCacheKey> = self
+            .child_cache
+            .iter()
+            .filter(|(key, _)| key.path == path)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        if stale.is_empty() {
+            return;
+        }
+
+        trace_log!(
+            "Invalidating {} outlet cache entries for path '{}'",
+            stale.len(),
+            path
+        );
+        for key in &stale {
+            self.child_cache.pop(key);
+        }
+        self.stats.invalidations += 1;
+    }
+
     pub fn get_parent(&mut self, path: &str) -> Option<RouteId> {
+        if !self.enabled {
+            return None;
+        }
+
         if let Some(entry) = self.parent_cache.get(path) {
             self.stats.parent_hits += 1;
             trace_log!("Parent cache hit for path: '{}'", path);
@@ -132,6 +228,10 @@ impl RouteCache {
     }
 
     pub fn set_parent(&mut self, path: String, parent_route_id: RouteId) {
+        if !self.enabled {
+            return;
+        }
+
         trace_log!(
             "Caching parent route '{}' for path '{}'",
             parent_route_id.path,
@@ -141,10 +241,65 @@ impl RouteCache {
             .push(path, ParentRouteCacheEntry { parent_route_id });
     }
 
+    /// Child route the outlet named `outlet_name` renders for `path`, with
+    /// its params
+    pub fn get_child(
+        &mut self,
+        path: &str,
+        outlet_name: Option<&str>,
+    ) -> Option<(RouteId, RouteParams)> {
+        if !self.enabled {
+            return None;
+        }
+
+        let key = OutletCacheKey {
+            path: path.to_string(),
+            outlet_name: outlet_name.map(str::to_string),
+        };
+        if let Some(entry) = self.child_cache.get(&key) {
+            self.stats.child_hits += 1;
+            trace_log!("Child cache hit for path: '{}'", path);
+            Some((entry.child_route_id.clone(), entry.params.clone()))
+        } else {
+            self.stats.child_misses += 1;
+            trace_log!("Child cache miss for path: '{}'", path);
+            None
+        }
+    }
+
+    /// Cache the child route an outlet renders for `path`
+    pub fn set_child(
+        &mut self,
+        path: String,
+        outlet_name: Option<String>,
+        child_route_id: RouteId,
+        params: RouteParams,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        self.child_cache.push(
+            OutletCacheKey { path, outlet_name },
+            ChildRouteCacheEntry {
+                child_route_id,
+                params,
+            },
+        );
+    }
+
     pub fn stats(&self) -> &CacheStats {
         &self.stats
     }
 
+    /// Record kept-alive components reused and dropped, with the number
+    /// still kept
+    pub(crate) fn record_kept_alive(&mut self, live: usize, reuses: usize, evictions: usize) {
+        self.stats.kept_alive = live;
+        self.stats.kept_alive_reuses += reuses;
+        self.stats.kept_alive_evictions += evictions;
+    }
+
     pub fn reset_stats(&mut self) {
         self.stats = CacheStats::default();
     }
@@ -176,6 +331,7 @@ impl Clone for RouteCache {
             parent_cache: LruCache::new(parent_cap),
             child_cache: LruCache::new(child_cap),
             stats: self.stats.clone(),
+            enabled: self.enabled,
         }
     }
 }
@@ -222,6 +378,56 @@ mod tests {
         assert_eq!(cache.stats().invalidations, 1);
     }
 
+    #[test]
+    fn test_invalidate_path_keeps_other_entries() {
+        let mut cache = RouteCache::new();
+        cache.set_parent("/dashboard".to_string(), RouteId::from_path("/"));
+        let child = || RouteId::from_path("/settings/general");
+        cache.set_child("/dashboard".to_string(), None, child(), RouteParams::new());
+        cache.set_child("/settings".to_string(), None, child(), RouteParams::new());
+        cache.set_child(
+            "/settings".to_string(),
+            Some("sidebar".to_string()),
+            child(),
+            RouteParams::new(),
+        );
+
+        cache.invalidate_path("/settings");
+        assert_eq!(cache.child_cache_size(), 1);
+        assert_eq!(cache.parent_cache_size(), 1);
+        assert_eq!(cache.stats().invalidations, 1);
+
+        // Nothing cached for this path, so nothing is counted
+        cache.invalidate_path("/unknown");
+        assert_eq!(cache.stats().invalidations, 1);
+    }
+
+    #[test]
+    fn test_disabled_cache() {
+        let mut cache = RouteCache::new();
+        cache.set_parent("/a".to_string(), RouteId::from_path("/"));
+        cache.set_enabled(false);
+        assert_eq!(cache.parent_cache_size(), 0);
+
+        cache.set_parent("/a".to_string(), RouteId::from_path("/"));
+        assert!(cache.get_parent("/a").is_none());
+        assert_eq!(cache.parent_cache_size(), 0);
+        assert_eq!(cache.stats().parent_misses, 0);
+    }
+
+    #[test]
+    fn test_set_capacity_evicts() {
+        let mut cache = RouteCache::with_capacity(4);
+        for path in ["/a", "/b", "/c", "/d"] {
+            cache.set_parent(path.to_string(), RouteId::from_path("/"));
+        }
+
+        cache.set_capacity(2);
+        assert_eq!(cache.capacity(), 2);
+        assert_eq!(cache.parent_cache_size(), 2);
+        assert!(cache.get_parent("/d").is_some());
+    }
+
     #[test]
     fn test_hit_rate_calculation() {
         let mut cache = RouteCache::new();
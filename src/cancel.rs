@@ -0,0 +1,154 @@
+//! Navigation cancellation
+//!
+//! Every navigation gets a [`NavigationToken`]. Async work started for a
+//! navigation checks it to notice that the navigation no longer matters,
+//! instead of comparing the current path afterwards:
+//!
+//! ```ignore
+//! use gpui_navigator::Route;
+//!
+//! Route::new("/users/:id", user_page).loader(|params, cx| {
+//!     let token = params.navigation_token().cloned().unwrap_or_default();
+//!     let request = fetch_user(params.get("id").cloned());
+//!     async move {
+//!         let user = request.await?;
+//!         if token.is_cancelled() {
+//!             return Err("superseded".to_string());
+//!         }
+//!         Ok(user)
+//!     }
+//! })
+//! ```
+//!
+//! The router cancels the token of a navigation when
+//! - a newer navigation starts before it commits,
+//! - its blocking loader exceeds its deadline,
+//! - a newer navigation commits after it, leaving its page,
+//! - the app quits.
+//!
+//! Results of cancelled navigations are discarded: a blocking loader that
+//! finishes after its navigation was cancelled commits nothing. Page code
+//! gets the token of the navigation that showed it with
+//! [`Navigator::current_navigation_token`](crate::Navigator::current_navigation_token).
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+/// Cancellation flag shared by the async steps of one navigation
+///
+/// Clones share the flag. Tokens made with [`NavigationToken::new`] are
+/// only cancelled by [`NavigationToken::cancel`].
+#[derive(Clone, Default)]
+pub struct NavigationToken {
+    state: Arc<Mutex<TokenState>>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: bool,
+    /// Tasks waiting in [`NavigationToken::cancelled`]
+    wakers: Vec<Waker>,
+}
+
+impl NavigationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the navigation was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.lock().cancelled
+    }
+
+    /// Cancel the navigation, waking the tasks waiting for it
+    ///
+    /// Cancelling twice does nothing.
+    pub fn cancel(&self) {
+        let wakers = {
+            let mut state = self.lock();
+            if state.cancelled {
+                return;
+            }
+            state.cancelled = true;
+            std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Wait until the navigation is cancelled
+    ///
+    /// Race it against other work to stop that work early.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+        }
+    }
+
+    /// Whether both tokens belong to the same navigation
+    pub fn same_navigation(&self, other: &NavigationToken) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TokenState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl std::fmt::Debug for NavigationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NavigationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Future returned by [`NavigationToken::cancelled`]
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct Cancelled {
+    token: NavigationToken,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.token.lock();
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_wakes_waiters() {
+        let token = NavigationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        assert!(clone.same_navigation(&token));
+        assert!(!NavigationToken::new().same_navigation(&token));
+
+        let waiter = std::thread::spawn(move || pollster::block_on(clone.cancelled()));
+        token.cancel();
+        waiter.join().unwrap();
+        assert!(token.is_cancelled());
+
+        // Already cancelled tokens resolve right away
+        pollster::block_on(token.cancelled());
+        token.cancel();
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,705 @@
+//! Router-wide configuration
+//!
+//! [`RouterConfig`] holds settings that apply to the whole router rather than
+//! to a single route. Set it with `GlobalRouter::set_config` (e.g. inside the
+//! `init_router` closure, before adding routes).
+
+use crate::flow::{FlowExit, FlowPolicy};
+use crate::params::preserve_query;
+use crate::NavigationSource;
+use gpui::App;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What to do when a route is registered with a name that is already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateNamePolicy {
+    /// Panic with both paths in the message
+    Panic,
+    /// Log an error and keep the route that was registered first
+    KeepFirst,
+}
+
+impl Default for DuplicateNamePolicy {
+    /// `Panic` in debug builds, `KeepFirst` in release builds
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Panic
+        } else {
+            Self::KeepFirst
+        }
+    }
+}
+
+/// What to do when a route is registered with a path pattern that is taken
+///
+/// Patterns are compared by their segments, so `/users` and `/users/` are
+/// the same. See [`RouterConfig::on_duplicate_route`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Log a warning naming both registrations and keep both routes; the one
+    /// registered first matches
+    #[default]
+    Warn,
+    /// Refuse the new route: `add_route` panics and `try_add_route` returns
+    /// an error naming both registrations
+    Panic,
+    /// Put the new route, with its names and children, in place of the existing one
+    ReplaceExisting,
+    /// Ignore the new route
+    KeepExisting,
+}
+
+/// What to do with a navigation that arrives too soon after the previous one
+///
+/// See [`RouterConfig::min_navigation_interval`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThrottlePolicy {
+    /// Hold the navigation until the interval has elapsed; if more arrive in
+    /// the meantime, only the last one is applied
+    #[default]
+    Coalesce,
+    /// Ignore the navigation
+    Drop,
+}
+
+/// What a second `init_router` call does
+///
+/// See [`RouterConfig::on_reinit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReinitPolicy {
+    /// Add the routes to the existing router like `GlobalRouter::extend`,
+    /// keeping history and the current path
+    #[default]
+    Extend,
+    /// Replace the router, starting over at `/`
+    Replace,
+}
+
+/// Which query params of the current location a navigation keeps
+///
+/// See [`RouterConfig::preserve_query_keys`] and [`NavOptions`]. Preserved
+/// params never override those the new path sets itself.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PreserveQuery {
+    /// Keep every query param
+    All,
+    /// Keep the listed keys
+    Keys(Vec<String>),
+    /// Keep nothing
+    #[default]
+    None,
+}
+
+impl PreserveQuery {
+    /// Merge the preserved query params of the `from` path into `to`
+    ///
+    /// ```
+    /// use gpui_navigator::PreserveQuery;
+    ///
+    /// let policy = PreserveQuery::Keys(vec!["theme".to_string()]);
+    /// assert_eq!(policy.apply("/a?theme=dark&page=2", "/b"), "/b?theme=dark");
+    /// assert_eq!(policy.apply("/a?theme=dark", "/b?theme=light"), "/b?theme=light");
+    /// ```
+    pub fn apply(&self, from: &str, to: &str) -> String {
+        match self {
+            Self::All => preserve_query(from, to, |_| true),
+            Self::Keys(keys) => preserve_query(from, to, |key| keys.iter().any(|k| k == key)),
+            Self::None => to.to_string(),
+        }
+    }
+}
+
+/// Options for a single navigation
+///
+/// See [`Navigator::push_opts`](crate::Navigator::push_opts).
+#[derive(Debug, Clone, Default)]
+pub struct NavOptions {
+    /// Query params to keep from the current location
+    ///
+    /// `None` uses the router's [`RouterConfig::preserve_query_keys`].
+    pub preserve_query: Option<PreserveQuery>,
+    /// Resolve paths without a leading `/` or dot against the current route
+    ///
+    /// Paths starting with `./` or `../` are always relative (see
+    /// [`relative`](crate::relative)); other paths only with this set.
+    pub relative: bool,
+    /// What started the navigation
+    ///
+    /// `None` keeps the source of the caller, [`NavigationSource::Programmatic`]
+    /// for plain `Navigator::push`.
+    pub source: Option<NavigationSource>,
+}
+
+impl NavOptions {
+    /// Create options using the router's defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set which query params to keep from the current location
+    pub fn preserve_query(mut self, preserve: PreserveQuery) -> Self {
+        self.preserve_query = Some(preserve);
+        self
+    }
+
+    /// Resolve bare paths like `details` against the current route
+    pub fn relative(mut self, relative: bool) -> Self {
+        self.relative = relative;
+        self
+    }
+
+    /// Set what started the navigation, e.g. a shortcut of the app's own
+    pub fn source(mut self, source: NavigationSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+/// Where keyboard focus moves after a navigation
+///
+/// See [`RouterConfig::focus_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FocusPolicy {
+    /// Leave focus where it is
+    #[default]
+    None,
+    /// Focus the container `RouterOutlet` wraps the new content in
+    OutletContainer,
+    /// Focus the element the page marked with the route's
+    /// `Route::focus_target` key, or the outlet container for routes without one
+    Custom,
+}
+
+/// What a navigation made while an outlet builds a route does
+///
+/// A builder navigating (say, pushing `/login` when signed out) changes the
+/// router while the outlet is reading it. See
+/// [`RouterConfig::on_render_navigation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderNavigationPolicy {
+    /// Log a warning and apply the navigation once the render finished
+    #[default]
+    Defer,
+    /// Drop the navigation and panic once the builder returns, naming the
+    /// path navigated to (the outlet renders the panic like any builder
+    /// panic unless `catch_builder_panics` is disabled)
+    Panic,
+    /// Apply the navigation right away
+    Allow,
+}
+
+/// What a navigation that timed out leaves on screen
+///
+/// See [`RouterConfig::navigation_timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeoutFallback {
+    /// Stay on the current page, as if the navigation had not been made
+    #[default]
+    KeepCurrent,
+    /// Commit the path with the timeout as its loader error, so outlets
+    /// render the error page; going back returns to the page before
+    ErrorPage,
+}
+
+/// What happens to history when another route table is activated
+///
+/// See [`RouterConfig::on_table_switch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableHistoryPolicy {
+    /// Drop the history of the table left
+    #[default]
+    Clear,
+    /// Keep the history of the table left, restoring it when that table is
+    /// activated again
+    Archive,
+}
+
+/// App-level handler for going back or forward with no history left
+///
+/// See [`RouterConfig::on_back_at_root`] and
+/// [`RouterConfig::on_forward_at_end`].
+#[derive(Clone)]
+pub struct HistoryEdgeHandler(Arc<dyn Fn(&mut App) + Send + Sync>);
+
+impl HistoryEdgeHandler {
+    /// Wrap `handler`
+    pub fn new(handler: impl Fn(&mut App) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    /// Run the handler
+    pub fn call(&self, cx: &mut App) {
+        (self.0)(cx);
+    }
+}
+
+impl std::fmt::Debug for HistoryEdgeHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HistoryEdgeHandler")
+    }
+}
+
+/// Router-wide configuration
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{init_router, DuplicateNamePolicy, RouterConfig};
+///
+/// init_router(cx, |router| {
+///     router.set_config(RouterConfig::new().on_duplicate_name(DuplicateNamePolicy::KeepFirst));
+///     // add routes...
+/// });
+/// ```
+#[derive(Debug, Clone)]
+// Independent switches, each set with its own builder method
+#[allow(clippy::struct_excessive_bools)]
+pub struct RouterConfig {
+    /// Policy for duplicate route names
+    pub duplicate_names: DuplicateNamePolicy,
+    /// Policy for routes registered with a path pattern that is taken
+    pub duplicate_routes: DuplicatePolicy,
+    /// Minimum time between navigations (no throttling if `None`)
+    pub min_navigation_interval: Option<Duration>,
+    /// What happens to navigations arriving within the interval
+    pub throttle_policy: ThrottlePolicy,
+    /// Whether back and forward navigation is throttled too
+    pub throttle_history: bool,
+    /// Whether outlets render the error page when a route builder panics
+    pub catch_builder_panics: bool,
+    /// Where keyboard focus moves after a navigation
+    pub focus_policy: FocusPolicy,
+    /// What calling `init_router` again does
+    pub reinit: ReinitPolicy,
+    /// Query params every push and replace keeps from the current location
+    pub preserve_query: PreserveQuery,
+    /// Whether pushes and replaces to paths matching no route are refused
+    pub strict_navigation: bool,
+    /// Locales paths may start with (no locale segments if empty)
+    pub locales: Vec<String>,
+    /// Locale used until a path names one (the first of `locales` if `None`)
+    pub default_locale: Option<String>,
+    /// Path `init_router` starts at instead of `/`
+    pub initial_path: Option<String>,
+    /// Whether navigations skip their transitions until a frame is presented
+    pub suppress_transitions_until_first_frame: bool,
+    /// Whether slot content plays its route's transition when it changes
+    pub animate_slots: bool,
+    /// Whether a navigation batch leaves a single history entry
+    pub batch_collapse_history: bool,
+    /// Whether pushing the next forward entry's path moves forward to it
+    pub reuse_forward_on_push: bool,
+    /// Whether named route URLs are refused for params their path has no place for
+    pub strict_url_params: bool,
+    /// Params and query keys whose values state dumps mask
+    pub redact_keys: Vec<String>,
+    /// What navigations made while an outlet builds a route do
+    pub render_navigation: RenderNavigationPolicy,
+    /// How long a navigation may wait for its blocking loader (no limit if `None`)
+    pub navigation_timeout: Option<Duration>,
+    /// How long a route's skeleton takes to crossfade to the loaded page
+    pub skeleton_crossfade: Duration,
+    /// What a navigation that timed out leaves on screen
+    pub timeout_fallback: TimeoutFallback,
+    /// What happens to history when another route table is activated
+    pub table_history: TableHistoryPolicy,
+    /// Table going back at the root of another table activates first
+    pub back_to_table: Option<String>,
+    /// What going back with no earlier entry does (nothing if `None`)
+    pub back_at_root: Option<HistoryEdgeHandler>,
+    /// What going forward with no later entry does (nothing if `None`)
+    pub forward_at_end: Option<HistoryEdgeHandler>,
+    /// Components kept alive across all routes with keep-alive (no limit if `None`)
+    pub max_kept_alive: Option<usize>,
+    /// Levels a registered route tree may have
+    pub max_route_depth: usize,
+    /// What going back to an earlier step does in each flow
+    pub flow_policies: HashMap<String, FlowPolicy>,
+    /// What leaving each flow does to its state
+    pub flow_exits: HashMap<String, FlowExit>,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        Self {
+            duplicate_names: DuplicateNamePolicy::default(),
+            duplicate_routes: DuplicatePolicy::default(),
+            min_navigation_interval: None,
+            throttle_policy: ThrottlePolicy::default(),
+            throttle_history: false,
+            catch_builder_panics: true,
+            focus_policy: FocusPolicy::default(),
+            reinit: ReinitPolicy::default(),
+            preserve_query: PreserveQuery::default(),
+            strict_navigation: false,
+            locales: Vec::new(),
+            default_locale: None,
+            initial_path: None,
+            suppress_transitions_until_first_frame: false,
+            animate_slots: false,
+            batch_collapse_history: false,
+            reuse_forward_on_push: true,
+            strict_url_params: false,
+            redact_keys: Vec::new(),
+            render_navigation: RenderNavigationPolicy::default(),
+            navigation_timeout: None,
+            skeleton_crossfade: Duration::from_millis(150),
+            timeout_fallback: TimeoutFallback::default(),
+            table_history: TableHistoryPolicy::default(),
+            back_to_table: None,
+            back_at_root: None,
+            forward_at_end: None,
+            max_kept_alive: None,
+            max_route_depth: crate::route::DEFAULT_MAX_ROUTE_DEPTH,
+            flow_policies: HashMap::new(),
+            flow_exits: HashMap::new(),
+        }
+    }
+}
+
+impl RouterConfig {
+    /// Create a configuration with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the policy for duplicate route names
+    pub fn on_duplicate_name(mut self, policy: DuplicateNamePolicy) -> Self {
+        self.duplicate_names = policy;
+        self
+    }
+
+    /// Set the policy for routes registered with a path pattern that is taken
+    pub fn on_duplicate_route(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_routes = policy;
+        self
+    }
+
+    /// Throttle navigations arriving within `interval` of the previous one
+    ///
+    /// Guards against double clicks pushing the same page twice. Routes can
+    /// override the interval with `Route::debounce`. Back and forward are
+    /// exempt unless [`RouterConfig::throttle_history`] is set.
+    pub fn min_navigation_interval(mut self, interval: Duration) -> Self {
+        self.min_navigation_interval = Some(interval);
+        self
+    }
+
+    /// Set whether throttled navigations are coalesced or dropped
+    pub fn throttle_policy(mut self, policy: ThrottlePolicy) -> Self {
+        self.throttle_policy = policy;
+        self
+    }
+
+    /// Also throttle back and forward navigation
+    ///
+    /// Throttled back and forward navigations are always dropped, since
+    /// coalescing them would lose steps.
+    pub fn throttle_history(mut self, throttle: bool) -> Self {
+        self.throttle_history = throttle;
+        self
+    }
+
+    /// Set whether a panicking route builder renders the error page
+    ///
+    /// Enabled by default: outlets catch the panic, log it and show
+    /// `DefaultPages::render_error` instead of the route until the next
+    /// navigation. Disable it to fail fast during development.
+    pub fn catch_builder_panics(mut self, catch: bool) -> Self {
+        self.catch_builder_panics = catch;
+        self
+    }
+
+    /// Set where keyboard focus moves after a navigation
+    ///
+    /// Without a policy focus stays on whatever had it, typically the link
+    /// that was clicked, so screen reader users may not notice the page
+    /// changed. Focus moves once the enter transition has completed, for back
+    /// and forward navigation too. See [`FocusPolicy`].
+    pub fn focus_policy(mut self, policy: FocusPolicy) -> Self {
+        self.focus_policy = policy;
+        self
+    }
+
+    /// Set what navigating from a route builder does
+    ///
+    /// Navigating while an outlet builds a route can render inconsistent
+    /// frames or loop. By default such navigations are applied after the
+    /// render, with a warning naming the path: redirect with a guard or
+    /// lifecycle hook instead. See [`RenderNavigationPolicy`].
+    pub fn on_render_navigation(mut self, policy: RenderNavigationPolicy) -> Self {
+        self.render_navigation = policy;
+        self
+    }
+
+    /// Abort navigations still waiting for their guards or their blocking
+    /// loader after `timeout`
+    ///
+    /// The pending guards or loader are cancelled, the navigation is
+    /// recorded as refused with a
+    /// [`NavigationError::Timeout`](crate::NavigationError::Timeout) and no
+    /// route change happens (see [`RouterConfig::on_navigation_timeout`];
+    /// a navigation whose guards timed out always keeps the current page).
+    /// `after_navigation` middleware still running at the deadline is
+    /// dropped. Routes can set a stricter deadline with
+    /// [`Route::navigation_timeout`](crate::Route::navigation_timeout).
+    pub fn navigation_timeout(mut self, timeout: Duration) -> Self {
+        self.navigation_timeout = Some(timeout);
+        self
+    }
+
+    /// Crossfade route skeletons to their page over `duration` (default: 150ms)
+    ///
+    /// Used when a loader finishes while the route's
+    /// [`skeleton`](crate::Route::skeleton) is on screen; the route's own
+    /// transition is not played again. `Duration::ZERO` swaps the page in
+    /// at once. Needs the `transition` feature.
+    pub fn skeleton_crossfade(mut self, duration: Duration) -> Self {
+        self.skeleton_crossfade = duration;
+        self
+    }
+
+    /// Set what a navigation that timed out leaves on screen
+    ///
+    /// By default the current page stays. See [`TimeoutFallback`].
+    pub fn on_navigation_timeout(mut self, fallback: TimeoutFallback) -> Self {
+        self.timeout_fallback = fallback;
+        self
+    }
+
+    /// Set what happens to history when another route table is activated
+    ///
+    /// By default the history of the table left is dropped. See
+    /// [`GlobalRouter::activate_table`](crate::GlobalRouter::activate_table).
+    pub fn on_table_switch(mut self, policy: TableHistoryPolicy) -> Self {
+        self.table_history = policy;
+        self
+    }
+
+    /// Go back to the table `name` from the root of any other table
+    ///
+    /// Going back with no earlier entry while another route table is active
+    /// activates `name` (at [`initial_path`](Self::initial_path), or its
+    /// archived history) before [`on_back_at_root`](Self::on_back_at_root)
+    /// is considered. Usually the table the app starts in, `"default"`
+    /// unless [`switch_table`](crate::GlobalRouter::switch_table) is called
+    /// during init.
+    pub fn back_at_root_to_table(mut self, name: impl Into<String>) -> Self {
+        self.back_to_table = Some(name.into());
+        self
+    }
+
+    /// Run `handler` when going back with no earlier entry
+    ///
+    /// Called by [`Navigator::pop`](crate::Navigator::pop), the
+    /// `NavigateBack` action and back swipes when
+    /// [`Navigator::can_pop`](crate::Navigator::can_pop) is false, e.g. to
+    /// minimize the app or close the window. By default nothing happens.
+    /// The pop returns [`PopOutcome::Delegated`](crate::PopOutcome::Delegated).
+    ///
+    /// ```ignore
+    /// RouterConfig::new().on_back_at_root(|cx| cx.hide())
+    /// ```
+    pub fn on_back_at_root(mut self, handler: impl Fn(&mut App) + Send + Sync + 'static) -> Self {
+        self.back_at_root = Some(HistoryEdgeHandler::new(handler));
+        self
+    }
+
+    /// Run `handler` when going forward with no later entry
+    ///
+    /// The counterpart of [`on_back_at_root`](Self::on_back_at_root) for
+    /// [`Navigator::forward`](crate::Navigator::forward).
+    pub fn on_forward_at_end(mut self, handler: impl Fn(&mut App) + Send + Sync + 'static) -> Self {
+        self.forward_at_end = Some(HistoryEdgeHandler::new(handler));
+        self
+    }
+
+    /// Set what calling `init_router` again does
+    ///
+    /// By default a second call logs a warning and adds its routes to the
+    /// existing router, so plugins can contribute routes without the app
+    /// jumping back to `/`. See [`ReinitPolicy`].
+    pub fn on_reinit(mut self, policy: ReinitPolicy) -> Self {
+        self.reinit = policy;
+        self
+    }
+
+    /// Keep these query params of the current location on every push and replace
+    ///
+    /// For cross-cutting params like `?theme=dark` or `?debug=1`: navigating
+    /// to `/settings` from `/home?theme=dark&page=2` goes to
+    /// `/settings?theme=dark`. A path setting the key itself keeps its value.
+    /// Back and forward restore history entries as they were. Single
+    /// navigations can choose otherwise with [`NavOptions`].
+    pub fn preserve_query_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.preserve_query = PreserveQuery::Keys(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Refuse pushes and replaces to paths that match no route
+    ///
+    /// By default such a navigation goes through and outlets render the
+    /// not-found page. In strict mode the current path and history stay as
+    /// they are; the error is kept for `Navigator::last_error` and passed to
+    /// the router's `ErrorHandlers::on_refused` handler.
+    pub fn strict_navigation(mut self, strict: bool) -> Self {
+        self.strict_navigation = strict;
+        self
+    }
+
+    /// Accept paths starting with one of these locales
+    ///
+    /// `/de/einstellungen` then resolves like `/einstellungen` would in the
+    /// `de` locale: the segment is stripped, localized paths registered with
+    /// `Route::localized_path` are translated, and matches get the locale as
+    /// their `locale` param. Paths without a locale segment keep the current
+    /// locale. See the [`locale`](crate::locale) module.
+    pub fn locales<I, S>(mut self, locales: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.locales = locales.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the locale used until a path names one
+    ///
+    /// Defaults to the first of [`RouterConfig::locales`].
+    pub fn default_locale(mut self, locale: impl Into<String>) -> Self {
+        self.default_locale = Some(locale.into());
+        self
+    }
+
+    /// Start at `path` instead of `/`
+    ///
+    /// `init_router` navigates there once the routes are added, running
+    /// guards and middleware like any navigation. The path becomes the first
+    /// history entry, so there is nothing to go back to. To choose the path
+    /// at startup, see [`init_router_with_initial`](crate::init_router_with_initial).
+    pub fn initial_path(mut self, path: impl Into<String>) -> Self {
+        self.initial_path = Some(path.into());
+        self
+    }
+
+    /// Show navigations without transitions until the first frame is presented
+    ///
+    /// Navigations made while the app starts (restoring a session, applying
+    /// a deep link) then appear at once instead of animating before the
+    /// window is fully visible. Outlets tell the router when their window
+    /// presented a frame; see `GlobalRouter::frame_presented`.
+    pub fn suppress_transitions_until_first_frame(mut self, suppress: bool) -> Self {
+        self.suppress_transitions_until_first_frame = suppress;
+        self
+    }
+
+    /// Animate slot content when navigation changes it (default: false)
+    ///
+    /// [`RouterSlot`](crate::RouterSlot)s then play the transition of the
+    /// route filling them as their content enters, like nested outlets do.
+    /// Needs the `transition` feature.
+    pub fn animate_slots(mut self, animate: bool) -> Self {
+        self.animate_slots = animate;
+        self
+    }
+
+    /// Keep only the last entry a navigation batch pushes
+    ///
+    /// By default every push of a batch (see `GlobalRouter::begin_batch`)
+    /// stays in history, so going back steps through them.
+    pub fn batch_collapse_history(mut self, collapse: bool) -> Self {
+        self.batch_collapse_history = collapse;
+        self
+    }
+
+    /// Move forward instead of pushing when the path is the next forward entry
+    /// (default: true)
+    ///
+    /// Going back and then following the same link again then keeps the
+    /// forward stack, entries beyond the next one included. Disabled, every
+    /// push truncates the forward stack. Replaces always replace.
+    pub fn reuse_forward_on_push(mut self, reuse: bool) -> Self {
+        self.reuse_forward_on_push = reuse;
+        self
+    }
+
+    /// Refuse to generate named route URLs for params their route's path has
+    /// no place for (default: false, they go into the query string)
+    ///
+    /// `GlobalRouter::try_url_for` then fails with `UrlForError::ExtraParams`,
+    /// and `push_named` and named targets are refused, catching misspelled
+    /// param names.
+    pub fn strict_url_params(mut self, strict: bool) -> Self {
+        self.strict_url_params = strict;
+        self
+    }
+
+    /// Mask the values of these params and query keys in state dumps
+    ///
+    /// `GlobalRouter::dump_state` and `Navigator::dump` then show
+    /// `[redacted]` instead, in paths too, so dumps of sessions with tokens
+    /// in their URLs can be shared. See the [`dump`](crate::dump) module.
+    pub fn redact_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redact_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Keep at most `max` components alive across all routes with keep-alive
+    ///
+    /// Beyond it the least recently used ones are dropped, whichever route
+    /// they belong to, after their route's `on_evict` callback ran. The
+    /// components on screen are never dropped. See
+    /// [`Route::keep_alive_max`](crate::Route::keep_alive_max).
+    pub fn max_kept_alive_entities(mut self, max: usize) -> Self {
+        self.max_kept_alive = Some(max);
+        self
+    }
+
+    /// Refuse route trees nested deeper than `depth` levels (default: 32)
+    ///
+    /// The top-level route counts as one level. Deeper trees are refused
+    /// like other invalid ones (see [`validate_route_tree`]). Depths above
+    /// 256 are lowered to it, the depth lookups stop descending at.
+    ///
+    /// [`validate_route_tree`]: crate::validate_route_tree
+    pub fn max_route_depth(mut self, depth: usize) -> Self {
+        self.max_route_depth = depth.min(crate::route::ROUTE_LOOKUP_DEPTH);
+        self
+    }
+
+    /// Set what navigating back to an earlier step of `flow` does to the
+    /// steps after it (default: [`FlowPolicy::KeepForward`])
+    ///
+    /// See the [`flow`](crate::flow) module.
+    pub fn flow_policy(mut self, flow: impl Into<String>, policy: FlowPolicy) -> Self {
+        self.flow_policies.insert(flow.into(), policy);
+        self
+    }
+
+    /// Set what navigating out of `flow` does to its state (default:
+    /// [`FlowExit::Persist`])
+    pub fn flow_exit(mut self, flow: impl Into<String>, exit: FlowExit) -> Self {
+        self.flow_exits.insert(flow.into(), exit);
+        self
+    }
+
+    /// Locale the router starts in, `None` without locales
+    pub fn initial_locale(&self) -> Option<&str> {
+        if self.locales.is_empty() {
+            return None;
+        }
+        self.default_locale
+            .as_deref()
+            .or_else(|| self.locales.first().map(String::as_str))
+    }
+}
@@ -4,12 +4,71 @@
 //! It exposes the `Navigator` API for navigation operations and manages router lifecycle.
 
 #[cfg(feature = "cache")]
-use crate::cache::{CacheStats, RouteCache};
-use crate::route::NamedRouteRegistry;
+use crate::cache::{CacheStats, RouteCache, RouteId};
+use crate::cancel::NavigationToken;
+use crate::config::{
+    DuplicateNamePolicy, DuplicatePolicy, NavOptions, ReinitPolicy, RenderNavigationPolicy,
+    RouterConfig, TableHistoryPolicy, ThrottlePolicy, TimeoutFallback,
+};
+use crate::diagnostics::{self, Outcome};
+use crate::dump::{HistoryEntryDump, Redactor, RouteDump, RouterStateDump};
+#[cfg(any(feature = "guard", feature = "middleware"))]
+use crate::filter::{FilteredEntry, FilteredKind, RouteExplanation, RouteFilter};
+use crate::flow::FlowState;
+#[cfg(feature = "guard")]
+use crate::guards::{
+    check_guards, poll_guards, sort_by_priority, AccessCheck, BoxedGuard, ConfirmHandler,
+    GuardPoll, GuardResult, GuardVerdict, LeaveDecision, RouteGuard, VerdictFuture,
+};
+#[cfg(feature = "transition")]
+use crate::hero::HeroRegistry;
+use crate::history::{split_fragment, EntryKind, HistoryEntry, HistoryListener};
+use crate::loader::{DataState, LoaderData, LoaderMode, PendingCommit, PendingLoad};
+use crate::locale::{canonical_path, localize_path, split_locale};
+use crate::matcher::{CompiledPattern, UrlForError};
+use crate::meta::{MetaValue, TypedMeta};
+#[cfg(feature = "middleware")]
+use crate::middleware::{
+    spawn_middleware, AfterNavigation, BoxedMiddleware, NavigationMiddleware, NavigationOutcome,
+    RouteMiddleware,
+};
+#[cfg(feature = "cache")]
+use crate::nested::is_absolute_child;
+use crate::nested::{path_segments, resolve_child_route_at, ResolvedChildRoute};
+use crate::palette::{commands_for, fuzzy_match, NavigationCommand, ScoredCommand};
+use crate::relative::{is_relative, resolve_relative, route_levels};
+#[cfg(any(feature = "guard", feature = "middleware"))]
+use crate::route::chain_pattern;
+#[cfg(feature = "guard")]
+use crate::route::{chain_meta, chain_typed_meta};
+use crate::route::{chain_meta_typed, match_route_chain, route_full_paths};
+use crate::route::{route_tree_issues, NamedRouteRegistry, RouteBuilder, RouteIssue, RouteRef};
+use crate::throttle::ThrottledNavigation;
 #[cfg(feature = "transition")]
 use crate::transition::Transition;
-use crate::{IntoRoute, Route, RouteChangeEvent, RouteParams, RouterState};
-use gpui::{App, BorrowAppContext, Global};
+#[cfg(feature = "cache")]
+use crate::widgets::find_route_by_full_path;
+use crate::widgets::{
+    find_parent_route_with_path, DefaultPages, OutletPlaceholder, RouterLinkTheme,
+};
+use crate::window_hints::{WindowHintApplier, WindowHints};
+use crate::{
+    build_child_path, debug_log, error_log, warn_log, ErrorHandlers, IntoRoute,
+    NavigationDirection, NavigationError, NavigationResult, NavigationSource, NotFoundDetails,
+    ParamsDiff, PopOutcome, PushOutcome, QueryParams, Route, RouteChangeEvent, RouteConfig,
+    RouteDescriptor, RouteParams, RouterHandle, RouterState,
+};
+#[cfg(feature = "guard")]
+use futures::future::{select, Either};
+use gpui::{
+    AnyWindowHandle, App, AsyncApp, BackgroundExecutor, BorrowAppContext, Context, Entity,
+    FocusHandle, Global, SharedString, Subscription, Task, Window,
+};
+use std::borrow::BorrowMut;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 // ============================================================================
 // NavigationRequest
@@ -27,6 +86,7 @@ use gpui::{App, BorrowAppContext, Global};
 /// let request = NavigationRequest::new("/dashboard".to_string());
 /// assert_eq!(request.to, "/dashboard");
 /// ```
+#[derive(Clone)]
 pub struct NavigationRequest {
     /// The path we're navigating from (if any)
     pub from: Option<String>,
@@ -36,6 +96,34 @@ pub struct NavigationRequest {
 
     /// Route parameters extracted from the path
     pub params: RouteParams,
+
+    /// How the navigation moves through history
+    pub direction: NavigationDirection,
+
+    /// Time spent on the path navigated from, for committed navigations
+    pub previous_dwell: Option<Duration>,
+
+    /// Token cancelled when the navigation is superseded (see [`crate::cancel`])
+    pub token: NavigationToken,
+
+    /// Full pattern of the route navigated from, `None` if it matched none
+    pub from_pattern: Option<String>,
+
+    /// Meta of the route navigated from, merged along its chain with inner
+    /// routes overriding outer ones
+    pub from_meta: HashMap<String, String>,
+
+    /// Meta of the route navigated to, merged like `from_meta`
+    pub to_meta: HashMap<String, String>,
+
+    /// Typed meta of the route navigated from, merged like `from_meta`
+    pub from_typed_meta: TypedMeta,
+
+    /// Typed meta of the route navigated to, merged like `from_meta`
+    pub to_typed_meta: TypedMeta,
+
+    /// What started the navigation
+    pub source: NavigationSource,
 }
 
 impl NavigationRequest {
@@ -45,6 +133,15 @@ impl NavigationRequest {
             from: None,
             to,
             params: RouteParams::new(),
+            direction: NavigationDirection::Forward,
+            previous_dwell: None,
+            token: NavigationToken::new(),
+            from_pattern: None,
+            from_meta: HashMap::new(),
+            to_meta: HashMap::new(),
+            from_typed_meta: TypedMeta::new(),
+            to_typed_meta: TypedMeta::new(),
+            source: NavigationSource::Programmatic,
         }
     }
 
@@ -52,8 +149,7 @@ impl NavigationRequest {
     pub fn with_from(to: String, from: String) -> Self {
         Self {
             from: Some(from),
-            to,
-            params: RouteParams::new(),
+            ..Self::new(to)
         }
     }
 
@@ -62,6 +158,48 @@ impl NavigationRequest {
         self.params = params;
         self
     }
+
+    /// Set the navigation direction
+    pub fn with_direction(mut self, direction: NavigationDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Set the token of the navigation
+    pub fn with_token(mut self, token: NavigationToken) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Set the pattern and meta of the route navigated from
+    pub fn with_from_route(
+        mut self,
+        pattern: Option<String>,
+        meta: HashMap<String, String>,
+    ) -> Self {
+        self.from_pattern = pattern;
+        self.from_meta = meta;
+        self
+    }
+
+    /// Set the meta of the route navigated to
+    pub fn with_to_meta(mut self, meta: HashMap<String, String>) -> Self {
+        self.to_meta = meta;
+        self
+    }
+
+    /// Set the typed meta of the routes navigated from and to
+    pub fn with_typed_meta(mut self, from: TypedMeta, to: TypedMeta) -> Self {
+        self.from_typed_meta = from;
+        self.to_typed_meta = to;
+        self
+    }
+
+    /// Set what started the navigation
+    pub fn with_source(mut self, source: NavigationSource) -> Self {
+        self.source = source;
+        self
+    }
 }
 
 impl std::fmt::Debug for NavigationRequest {
@@ -70,6 +208,15 @@ impl std::fmt::Debug for NavigationRequest {
             .field("from", &self.from)
             .field("to", &self.to)
             .field("params", &self.params)
+            .field("direction", &self.direction)
+            .field("previous_dwell", &self.previous_dwell)
+            .field("token", &self.token)
+            .field("from_pattern", &self.from_pattern)
+            .field("from_meta", &self.from_meta)
+            .field("to_meta", &self.to_meta)
+            .field("from_typed_meta", &self.from_typed_meta)
+            .field("to_typed_meta", &self.to_typed_meta)
+            .field("source", &self.source)
             .finish_non_exhaustive()
     }
 }
@@ -78,6 +225,175 @@ impl std::fmt::Debug for NavigationRequest {
 // GlobalRouter
 // ============================================================================
 
+/// Opens a route outside the current outlet, e.g. in a new window
+pub type OpenRouteHandler = Arc<dyn Fn(&str, &mut App) + Send + Sync>;
+
+/// Navigations applied together, see [`GlobalRouter::begin_batch`]
+#[derive(Clone)]
+struct NavigationBatch {
+    /// Number of `begin_batch` calls not ended yet
+    depth: usize,
+    /// Whether navigations keep their transitions
+    animate: bool,
+    /// History index the batch started at
+    start: usize,
+    /// First navigation committed in the batch
+    first: Option<RouteChangeEvent>,
+    /// Navigations queued for the middleware when the batch started
+    #[cfg(feature = "middleware")]
+    navigated: usize,
+}
+
+/// A navigation made while an outlet was building a route
+#[derive(Clone)]
+enum RenderNavigation {
+    /// Push or replace of `path` as given, with its options
+    Commit {
+        path: String,
+        options: NavOptions,
+        commit: PendingCommit,
+    },
+    /// [`GlobalRouter::try_push`] or [`GlobalRouter::start_at`]
+    Try {
+        path: String,
+        commit: PendingCommit,
+        source: NavigationSource,
+    },
+    /// Going back to the history entry at the index
+    Back(Option<usize>),
+    /// Going forward to the history entry at the index
+    Forward(usize),
+    /// A [`NavigatorHandle`] chain
+    Chain(Vec<ChainStep>),
+}
+
+/// A navigation held until leaving the current route is confirmed
+#[cfg(feature = "guard")]
+#[derive(Clone)]
+enum HeldNavigation {
+    /// Push or replace of a resolved path, in `locale` if it has one
+    Commit {
+        locale: Option<String>,
+        path: String,
+        commit: PendingCommit,
+        source: NavigationSource,
+    },
+    /// Going back to the history entry at the index
+    Back(Option<usize>),
+    /// Going forward to the history entry at the index
+    Forward(usize),
+    /// A [`NavigatorHandle`] chain
+    Chain(Vec<ChainStep>),
+    /// Closing the window
+    Close(AnyWindowHandle),
+}
+
+/// Leave confirmation waiting for an answer, see [`ConfirmLeaveGuard`](crate::ConfirmLeaveGuard)
+#[cfg(feature = "guard")]
+#[derive(Clone)]
+struct PendingLeave {
+    id: u64,
+    message: String,
+    /// Navigation applied if leaving is confirmed
+    navigation: HeldNavigation,
+    /// Whether the confirm handler was asked
+    prompted: bool,
+}
+
+/// A push or replace waiting for guards that have not resolved
+#[cfg(feature = "guard")]
+#[derive(Clone)]
+struct GuardedNavigation {
+    /// Number of the check, so the answer of a superseded one is dropped
+    id: u64,
+    locale: Option<String>,
+    path: String,
+    commit: PendingCommit,
+    source: NavigationSource,
+    fragment: Option<String>,
+    /// Token the navigation keeps once it starts
+    token: Option<NavigationToken>,
+    /// Path a guard redirected the navigation away from
+    redirected_from: Option<String>,
+    /// Redirects followed to get here
+    redirects: usize,
+    /// When the guards were first asked, which the navigation timeout
+    /// counts from
+    since: Instant,
+    /// When the push or replace was called
+    #[cfg(feature = "middleware")]
+    started: Option<Instant>,
+    /// Finishes the check; dropping it cancels the check
+    _task: Arc<Task<()>>,
+}
+
+/// How a push or replace went through the guards of its target
+#[cfg_attr(not(feature = "guard"), allow(dead_code))]
+enum Guarded {
+    /// The guards let it through, or it had none
+    Through(PushOutcome),
+    /// It waits for guards that have not resolved
+    Held { path: String },
+    /// A guard, or a redirect loop, refused it
+    Refused { error: NavigationError },
+    /// A guard redirected it to `to`
+    Redirected { to: String, outcome: PushOutcome },
+}
+
+impl Guarded {
+    /// Outcome of the navigation, as the push or replace reports it
+    fn into_outcome(self) -> PushOutcome {
+        match self {
+            Self::Through(outcome) | Self::Redirected { outcome, .. } => outcome,
+            Self::Held { path } => PushOutcome::Pending { path },
+            Self::Refused { error } => PushOutcome::Refused { error },
+        }
+    }
+
+    /// Result of the navigation to `path`, as [`GlobalRouter::try_push`]
+    /// reports it
+    fn into_result(self, path: String) -> NavigationResult {
+        match self {
+            Self::Through(outcome) => outcome.into(),
+            Self::Held { path } => NavigationResult::Pending { path },
+            Self::Refused { error } => PushOutcome::Refused { error }.into(),
+            Self::Redirected { to, .. } => NavigationResult::Redirected { from: path, to },
+        }
+    }
+}
+
+/// Guard redirects followed by one navigation before it is refused as a loop
+#[cfg(feature = "guard")]
+pub const MAX_REDIRECTS: usize = 10;
+
+/// Why switching to the active table `name` left the current entry as it is
+fn throttled_replace(name: &str) -> String {
+    format!("Switching to the active table '{}' was throttled", name)
+}
+
+/// Name of the route table routes are added to outside [`GlobalRouter::define_table`]
+const DEFAULT_TABLE: &str = "default";
+
+/// Last generation given out, shared by every router so a replaced router
+/// never repeats one (see [`GlobalRouter::generation`])
+static LAST_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// A generation no router has had yet
+fn next_generation() -> u64 {
+    LAST_GENERATION.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// A route table while another one is active, see [`GlobalRouter::define_table`]
+#[derive(Clone, Default)]
+struct RouteTable {
+    routes: Vec<RouteRef>,
+    /// Registration number of each top-level route
+    registrations: Vec<usize>,
+    named_routes: NamedRouteRegistry,
+    /// History entries and current index, archived when the table was left
+    history: Option<(Vec<HistoryEntry>, usize)>,
+}
+
 /// Global router state accessible from any component
 #[derive(Clone)]
 pub struct GlobalRouter {
@@ -90,6 +406,145 @@ pub struct GlobalRouter {
     /// Transition override for next navigation
     #[cfg(feature = "transition")]
     next_transition: Option<Transition>,
+    /// Router-wide configuration
+    config: RouterConfig,
+    /// Path of the load in flight
+    loading: Option<String>,
+    /// Running loader; dropping it cancels the load
+    loader_task: Option<Arc<Task<()>>>,
+    /// Path of a navigation waiting for its blocking loader
+    blocked_on: Option<String>,
+    /// What started the navigation waiting for its blocking loader
+    blocked_source: NavigationSource,
+    /// Resolver of the initial path; dropping it cancels the resolution
+    initial_task: Option<Arc<Task<()>>>,
+    /// Pages rendered by outlets for loading and errors
+    default_pages: Arc<DefaultPages>,
+    /// Navigation held back by throttling
+    pub(crate) throttled: Option<ThrottledNavigation>,
+    /// Timer applying the held navigation; dropping it cancels it
+    pub(crate) throttle_task: Option<Arc<Task<()>>>,
+    /// Clock for throttling (the app's executor, so tests control time)
+    clock: Option<BackgroundExecutor>,
+    /// Builders that panicked since the last navigation, with the message
+    poisoned: Vec<(RouteBuilder, String)>,
+    /// Data prepared for cached routes this frame, by builder and params
+    prepared: Vec<(RouteBuilder, String, LoaderData)>,
+    /// Focus handles of elements marked as focus targets, by key
+    pub(crate) focus_targets: HashMap<String, FocusHandle>,
+    /// Opens routes in a new window, for links opened with a modifier
+    new_window_handler: Option<OpenRouteHandler>,
+    /// Middleware run for every navigation, in priority order
+    #[cfg(feature = "middleware")]
+    pub(crate) middleware: Vec<Arc<BoxedMiddleware>>,
+    /// Middleware added with a filter, in registration order
+    #[cfg(feature = "middleware")]
+    filtered_middleware: Vec<(RouteFilter, Arc<BoxedMiddleware>)>,
+    /// Navigations started without an `App`, whose `before_navigation`
+    /// middleware has not run
+    #[cfg(feature = "middleware")]
+    pub(crate) navigating: Vec<NavigationRequest>,
+    /// Committed navigations whose `after_navigation` middleware has not run,
+    /// with the time each started
+    #[cfg(feature = "middleware")]
+    pub(crate) navigated: Vec<(Instant, AfterNavigation)>,
+    /// When the push or replace being processed was called
+    #[cfg(feature = "middleware")]
+    pipeline_started: Option<Instant>,
+    /// Path a guard redirected the navigation being processed away from
+    redirected_from: Option<String>,
+    /// Token of the navigation that committed last
+    navigation_token: NavigationToken,
+    /// Token of the navigation started but not committed yet
+    in_flight_token: Option<NavigationToken>,
+    /// Token given to the guards of the navigation being checked
+    checked_token: Option<NavigationToken>,
+    /// Fragment of the push or replace being processed, put back on the path
+    /// when it commits
+    pending_fragment: Option<String>,
+    /// Error of the last refused navigation
+    last_error: Option<NavigationError>,
+    /// Handlers notified of refused navigations
+    error_handlers: ErrorHandlers,
+    /// Refused navigations not passed to the handlers yet
+    pub(crate) unreported_errors: Vec<NavigationError>,
+    /// Placeholder of the `RouterOutlet` being rendered, for nested outlets
+    pub(crate) outlet_placeholder: Option<OutletPlaceholder>,
+    /// Route whose builder is running; the outlets it renders show its children
+    pub(crate) building_route: Option<RouteRef>,
+    /// Registration number of each top-level route, in route order
+    registrations: Vec<usize>,
+    /// Number of routes registered so far, including refused ones
+    registered: usize,
+    /// Problems found in the route trees added so far
+    route_issues: Vec<RouteIssue>,
+    /// Handle shared by background tasks, created on first use
+    handle: Option<RouterHandle>,
+    /// Paths shown by named outlets, set with [`GlobalRouter::set_outlet_path`]
+    outlet_paths: HashMap<String, String>,
+    /// Batch of navigations being applied
+    batch: Option<NavigationBatch>,
+    /// Whether an outlet's window presented a frame yet
+    frame_presented: bool,
+    /// Navigation held until leaving the current route is confirmed
+    #[cfg(feature = "guard")]
+    leave: Option<PendingLeave>,
+    /// Number of leave confirmations asked for so far
+    #[cfg(feature = "guard")]
+    leave_requests: u64,
+    /// Asks whether to leave a dirty route
+    #[cfg(feature = "guard")]
+    confirm_handler: Option<ConfirmHandler>,
+    /// Navigation waiting for its guards to resolve
+    #[cfg(feature = "guard")]
+    guarded: Option<GuardedNavigation>,
+    /// Navigation made without an `App`, applied with one on the next tick
+    /// so its transition guards can run
+    #[cfg(feature = "guard")]
+    deferred: Option<Arc<Task<()>>>,
+    /// Number of guard checks navigations waited for so far
+    #[cfg(feature = "guard")]
+    guard_checks: u64,
+    /// Guard redirects followed by the navigation being processed
+    #[cfg(feature = "guard")]
+    redirects: usize,
+    /// Set while a chain applies its steps, whose destination the chain
+    /// checks once they are applied
+    #[cfg(feature = "guard")]
+    chaining: bool,
+    /// When the guards of the navigation being resumed were first asked
+    #[cfg(feature = "guard")]
+    guards_since: Option<Instant>,
+    /// The app the router was installed in, to finish navigations made
+    /// without it: guard checks and throttled navigations
+    app: Option<AsyncApp>,
+    /// Guards on every navigation, see [`GlobalRouter::add_transition_guard`]
+    #[cfg(feature = "guard")]
+    transition_guards: Vec<Arc<BoxedGuard>>,
+    /// Guards added with a filter, in registration order
+    #[cfg(feature = "guard")]
+    filtered_guards: Vec<(RouteFilter, Arc<BoxedGuard>)>,
+    /// Navigations made while building routes, with the path each went to
+    render_navigations: Vec<(String, RenderNavigation)>,
+    /// Whether applying `render_navigations` is scheduled
+    render_flush_scheduled: bool,
+    /// Window hints of routes without their own
+    base_window_hints: WindowHints,
+    /// Window hints applied last, `None` until a route set its own
+    pub(crate) applied_window_hints: Option<WindowHints>,
+    /// Applies window hints to the outlet's window
+    window_hint_applier: WindowHintApplier,
+    /// Colors of links without styling of their own
+    link_theme: RouterLinkTheme,
+    /// Route tables other than the active one, by name
+    tables: HashMap<String, RouteTable>,
+    /// Name of the route table navigation works on
+    active_table: String,
+    /// Changes whenever the route tree outlets resolve against is replaced
+    generation: u64,
+    /// Bounds of the heroes on screen and their flights
+    #[cfg(feature = "transition")]
+    pub(crate) heroes: HeroRegistry,
 }
 
 impl GlobalRouter {
@@ -102,995 +557,9311 @@ impl GlobalRouter {
             named_routes: NamedRouteRegistry::new(),
             #[cfg(feature = "transition")]
             next_transition: None,
+            config: RouterConfig::default(),
+            loading: None,
+            loader_task: None,
+            blocked_on: None,
+            blocked_source: NavigationSource::Programmatic,
+            initial_task: None,
+            default_pages: Arc::new(DefaultPages::new()),
+            throttled: None,
+            throttle_task: None,
+            clock: None,
+            poisoned: Vec::new(),
+            prepared: Vec::new(),
+            focus_targets: HashMap::new(),
+            new_window_handler: None,
+            #[cfg(feature = "middleware")]
+            middleware: Vec::new(),
+            #[cfg(feature = "middleware")]
+            filtered_middleware: Vec::new(),
+            #[cfg(feature = "middleware")]
+            navigating: Vec::new(),
+            #[cfg(feature = "middleware")]
+            navigated: Vec::new(),
+            #[cfg(feature = "middleware")]
+            pipeline_started: None,
+            redirected_from: None,
+            navigation_token: NavigationToken::new(),
+            in_flight_token: None,
+            checked_token: None,
+            pending_fragment: None,
+            last_error: None,
+            error_handlers: ErrorHandlers::new(),
+            unreported_errors: Vec::new(),
+            outlet_placeholder: None,
+            building_route: None,
+            registrations: Vec::new(),
+            registered: 0,
+            route_issues: Vec::new(),
+            handle: None,
+            outlet_paths: HashMap::new(),
+            batch: None,
+            frame_presented: false,
+            #[cfg(feature = "guard")]
+            leave: None,
+            #[cfg(feature = "guard")]
+            leave_requests: 0,
+            #[cfg(feature = "guard")]
+            confirm_handler: None,
+            #[cfg(feature = "guard")]
+            guarded: None,
+            #[cfg(feature = "guard")]
+            deferred: None,
+            #[cfg(feature = "guard")]
+            guard_checks: 0,
+            #[cfg(feature = "guard")]
+            redirects: 0,
+            #[cfg(feature = "guard")]
+            chaining: false,
+            #[cfg(feature = "guard")]
+            guards_since: None,
+            app: None,
+            #[cfg(feature = "guard")]
+            transition_guards: Vec::new(),
+            #[cfg(feature = "guard")]
+            filtered_guards: Vec::new(),
+            render_navigations: Vec::new(),
+            render_flush_scheduled: false,
+            base_window_hints: WindowHints::default(),
+            applied_window_hints: None,
+            window_hint_applier: Arc::new(crate::window_hints::apply_window_title),
+            link_theme: RouterLinkTheme::default(),
+            tables: HashMap::new(),
+            active_table: DEFAULT_TABLE.to_string(),
+            generation: next_generation(),
+            #[cfg(feature = "transition")]
+            heroes: HeroRegistry::default(),
         }
     }
 
-    /// Register a route
-    pub fn add_route(&mut self, route: Route) {
-        // Register named route if it has a name
-        if let Some(name) = &route.config.name {
-            self.named_routes
-                .register(name.clone(), route.config.path.clone());
-        }
+    /// Create a global router with the given configuration
+    pub fn with_config(config: RouterConfig) -> Self {
+        let mut router = Self {
+            config,
+            ..Self::new()
+        };
+        router.sync_config();
+        router
+    }
 
-        self.state.add_route(route);
-        // Clear cache when routes change
-        #[cfg(feature = "cache")]
-        self.nested_cache.clear();
+    /// Identity of the route tree outlets resolve against
+    ///
+    /// Increases whenever [`init_router`] installs a router, routes are added
+    /// with [`GlobalRouter::extend`] and another route table is activated.
+    /// Outlets that drew a different generation drop what they cached and
+    /// resolve the current path from scratch, without a transition.
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
-    /// Navigate to a named route with parameters
-    pub fn push_named(&mut self, name: &str, params: &RouteParams) -> Option<RouteChangeEvent> {
-        let url = self.named_routes.url_for(name, params)?;
-        Some(self.push(url))
+    /// Whether the initial path given to [`init_router_with_initial_async`]
+    /// is still being resolved
+    ///
+    /// Outlets render the loading page meanwhile.
+    pub fn resolving_initial(&self) -> bool {
+        self.initial_task.is_some()
     }
 
-    /// Generate URL for a named route
-    pub fn url_for(&self, name: &str, params: &RouteParams) -> Option<String> {
-        self.named_routes.url_for(name, params)
+    /// Get the router configuration
+    pub fn config(&self) -> &RouterConfig {
+        &self.config
     }
 
-    /// Navigate to a path
-    pub fn push(&mut self, path: String) -> RouteChangeEvent {
-        // Clear cache on navigation
-        #[cfg(feature = "cache")]
-        self.nested_cache.clear();
-        self.state.push(path)
+    /// Replace the router configuration
+    ///
+    /// Settings that affect route registration (such as the duplicate name
+    /// policy) only apply to routes added afterwards.
+    pub fn set_config(&mut self, config: RouterConfig) {
+        self.config = config;
+        self.sync_config();
     }
 
-    /// Replace current path
-    pub fn replace(&mut self, path: String) -> RouteChangeEvent {
-        // Clear cache on navigation
-        #[cfg(feature = "cache")]
-        self.nested_cache.clear();
-        self.state.replace(path)
+    /// Apply the configuration to the router state
+    fn sync_config(&mut self) {
+        self.sync_locale();
+        self.state
+            .set_reuse_forward_on_push(self.config.reuse_forward_on_push);
+        self.state.set_flow_policies(
+            self.config.flow_policies.clone(),
+            self.config.flow_exits.clone(),
+        );
+        self.named_routes.set_strict(self.config.strict_url_params);
+        for table in self.tables.values_mut() {
+            table.named_routes.set_strict(self.config.strict_url_params);
+        }
     }
 
-    /// Go back
-    pub fn back(&mut self) -> Option<RouteChangeEvent> {
-        // Clear cache on navigation
-        #[cfg(feature = "cache")]
-        self.nested_cache.clear();
-        self.state.back()
+    /// Start in the default locale unless the current one is still configured
+    fn sync_locale(&mut self) {
+        let configured = self
+            .locale()
+            .is_some_and(|locale| self.config.locales.iter().any(|known| known == locale));
+        if !configured {
+            let locale = self.config.initial_locale().map(str::to_string);
+            self.state.set_locale(locale);
+        }
     }
 
-    /// Go forward
-    pub fn forward(&mut self) -> Option<RouteChangeEvent> {
-        // Clear cache on navigation
-        #[cfg(feature = "cache")]
-        self.nested_cache.clear();
-        self.state.forward()
+    /// Set how routes are opened in a new window
+    ///
+    /// `RouterLink` calls it for middle clicks and clicks with the secondary
+    /// modifier (Cmd on macOS, Ctrl elsewhere), unless the link has its own
+    /// `RouterLink::on_secondary_open` handler.
+    pub fn set_new_window_handler(
+        &mut self,
+        handler: impl Fn(&str, &mut App) + Send + Sync + 'static,
+    ) {
+        self.new_window_handler = Some(Arc::new(handler));
     }
 
-    /// Get current path
-    pub fn current_path(&self) -> &str {
-        self.state.current_path()
+    /// Handler set with [`GlobalRouter::set_new_window_handler`]
+    pub fn new_window_handler(&self) -> Option<&OpenRouteHandler> {
+        self.new_window_handler.as_ref()
     }
 
-    /// Get current route match (with caching, requires mutable)
-    pub fn current_match(&mut self) -> Option<crate::RouteMatch> {
-        self.state.current_match()
+    /// Set the handlers notified of refused navigations
+    pub fn set_error_handlers(&mut self, handlers: ErrorHandlers) {
+        self.error_handlers = handlers;
     }
 
-    /// Get current route match (immutable, no caching)
+    /// Set how to ask before leaving a dirty route
     ///
-    /// Use this from Render implementations and other immutable contexts
-    pub fn current_match_immutable(&self) -> Option<crate::RouteMatch> {
-        self.state.current_match_immutable()
+    /// Called with the message of the route's
+    /// [`ConfirmLeaveGuard`](crate::ConfirmLeaveGuard) when a navigation
+    /// away from it is held. Show a dialog and answer with `decide(true)` to
+    /// leave or `decide(false)` to stay; `decide` can be called later, from
+    /// any thread.
+    #[cfg(feature = "guard")]
+    pub fn set_confirm_handler(
+        &mut self,
+        handler: impl Fn(&mut App, &str, LeaveDecision) + Send + Sync + 'static,
+    ) {
+        self.confirm_handler = Some(Arc::new(handler));
     }
 
-    /// Get the current matched Route
+    /// Add a guard checked on every navigation, whatever the route
     ///
-    /// Returns the shared `Arc<Route>` that matched the current path.
-    /// Useful for accessing the route's children and builder without cloning.
-    pub fn current_route(&self) -> Option<&std::sync::Arc<crate::route::Route>> {
-        self.state.current_route()
+    /// Transition guards run on every navigation that commits: pushes and
+    /// replaces, going back or forward, navigator chains and route table
+    /// switches, before the guards of the target route.
+    /// [`GlobalRouter::can_access`] asks them too. Navigations made without
+    /// an `App`, such as [`GlobalRouter::back`], ask them on the next tick
+    /// and happen then if they allow it. Their request
+    /// carries the meta of both the route left and the one navigated to, so
+    /// they can act on moving between kinds of routes; see
+    /// [`TransitionGuard`](crate::TransitionGuard).
+    #[cfg(feature = "guard")]
+    pub fn add_transition_guard<G>(&mut self, guard: G)
+    where
+        G: RouteGuard<
+            Future = std::pin::Pin<Box<dyn std::future::Future<Output = GuardResult> + Send>>,
+        >,
+    {
+        self.transition_guards.push(Arc::new(Box::new(guard)));
     }
 
-    /// Check if can go back
-    pub fn can_go_back(&self) -> bool {
-        self.state.can_go_back()
+    /// Add a guard checked on navigations to the routes `filter` selects
+    ///
+    /// Like a guard attached to every selected route, without touching
+    /// them: it runs wherever route guards do, after the transition guards
+    /// and before the guards of the routes themselves, which run at equal
+    /// [`RouteGuard::priority`] in registration order. See
+    /// [`crate::filter`] for what filters match and
+    /// [`GlobalRouter::explain`] for the guards applying to a path.
+    ///
+    /// ```ignore
+    /// router.add_guard_filtered(
+    ///     AuthGuard::new(is_logged_in, "/login"),
+    ///     RouteFilter::has_meta("requiresAuth"),
+    /// );
+    /// ```
+    #[cfg(feature = "guard")]
+    pub fn add_guard_filtered<G>(&mut self, guard: G, filter: RouteFilter)
+    where
+        G: RouteGuard<
+            Future = std::pin::Pin<Box<dyn std::future::Future<Output = GuardResult> + Send>>,
+        >,
+    {
+        self.filtered_guards
+            .push((filter, Arc::new(Box::new(guard))));
     }
 
-    /// Check if can go forward
-    pub fn can_go_forward(&self) -> bool {
-        self.state.can_go_forward()
+    /// Filtered guards selecting the navigation to `path`, resolving to `chain`
+    #[cfg(feature = "guard")]
+    pub(crate) fn filtered_guards<'a>(
+        &'a self,
+        path: &'a str,
+        chain: &'a [&RouteRef],
+    ) -> impl Iterator<Item = &'a BoxedGuard> + 'a {
+        self.filtered_guards
+            .iter()
+            .filter(move |(filter, _)| filter.matches(path, chain))
+            .map(|(_, guard)| &**guard)
     }
 
-    /// Get mutable state reference
-    pub fn state_mut(&mut self) -> &mut RouterState {
-        &mut self.state
+    /// Handle for navigating from background tasks
+    ///
+    /// The handle is created on first use and shared afterwards; see
+    /// [`RouterHandle`]. Panics if the router was not initialized.
+    pub fn handle(cx: &mut App) -> RouterHandle {
+        let existing = cx
+            .global::<GlobalRouter>()
+            .handle
+            .clone()
+            .filter(|handle| !handle.is_closed());
+        existing.unwrap_or_else(|| {
+            let handle = RouterHandle::new(cx);
+            cx.global_mut::<GlobalRouter>().handle = Some(handle.clone());
+            handle
+        })
     }
 
-    /// Get state reference
-    pub fn state(&self) -> &RouterState {
-        &self.state
+    /// Stop counting time spent on the current route
+    ///
+    /// Dwell times ([`Navigator::time_on_current_route`],
+    /// [`RouteChangeEvent::previous_dwell`]) measure wall time, including
+    /// time the app spends suspended or in the background. Call this when
+    /// the app is suspended and [`GlobalRouter::resume_dwell`] when it
+    /// resumes to leave that time out.
+    pub fn pause_dwell(cx: &mut App) {
+        cx.global_mut::<GlobalRouter>().state.pause_dwell();
     }
 
-    /// Get nested route cache (mutable)
-    #[cfg(feature = "cache")]
-    pub fn nested_cache_mut(&mut self) -> &mut RouteCache {
-        &mut self.nested_cache
+    /// Count time spent on the current route again
+    ///
+    /// See [`GlobalRouter::pause_dwell`].
+    pub fn resume_dwell(cx: &mut App) {
+        cx.global_mut::<GlobalRouter>().state.resume_dwell();
     }
 
-    /// Get nested route cache statistics
-    #[cfg(feature = "cache")]
-    pub fn cache_stats(&self) -> &CacheStats {
-        self.nested_cache.stats()
+    /// Handlers set with [`GlobalRouter::set_error_handlers`]
+    pub fn error_handlers(&self) -> &ErrorHandlers {
+        &self.error_handlers
     }
 
-    /// Set transition for the next navigation
-    ///
-    /// This override will be used for the next push/replace operation,
-    /// then automatically cleared.
-    ///
-    /// # Example
-    /// ```ignore
-    /// use gpui_navigator::{GlobalRouter, Transition};
+    /// Where matching `path` against the registered routes fails
     ///
-    /// cx.update_global::<GlobalRouter, _>(|router, _| {
-    ///     router.set_next_transition(Transition::fade(300));
-    ///     router.push("/page".to_string());
-    /// });
-    /// ```
-    #[cfg(feature = "transition")]
-    pub fn set_next_transition(&mut self, transition: Transition) {
-        self.next_transition = Some(transition);
+    /// Localized paths are matched by their canonical form.
+    pub fn not_found_details(&self, path: &str) -> NotFoundDetails {
+        let (_, path) = self.delocalize(path);
+        NotFoundDetails::new(self.state.routes(), &path)
     }
 
-    /// Get and consume the next transition override
+    /// Error of the last refused navigation
     ///
-    /// Returns the transition override if set, and clears it.
-    /// Used internally by navigation methods.
-    #[cfg(feature = "transition")]
-    pub fn take_next_transition(&mut self) -> Option<Transition> {
-        self.next_transition.take()
+    /// Cleared by the next navigation that goes through.
+    pub fn last_error(&self) -> Option<&NavigationError> {
+        self.last_error.as_ref()
     }
 
-    /// Check if there's a transition override set
-    #[cfg(feature = "transition")]
-    pub fn has_next_transition(&self) -> bool {
-        self.next_transition.is_some()
+    /// Record a navigation the router refused
+    fn refuse(&mut self, error: NavigationError) {
+        warn_log!("Navigation refused: {}", error);
+        self.last_error = Some(error.clone());
+        self.unreported_errors.push(error);
     }
 
-    /// Clear transition override without consuming it
-    #[cfg(feature = "transition")]
-    pub fn clear_next_transition(&mut self) {
-        self.next_transition = None;
+    /// Register a route
+    ///
+    /// Names of the route and its nested children are registered with their
+    /// full paths.
+    ///
+    /// The whole route tree is checked with
+    /// [`validate_route_tree`](crate::validate_route_tree) first: warnings
+    /// are logged, and a tree with errors (an invalid path, an
+    /// absolute child path outside its parent, a route that is its own
+    /// ancestor, more levels than [`RouterConfig::max_route_depth`]) is not
+    /// registered. All of them are kept in [`GlobalRouter::route_issues`].
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the route tree has errors; release builds
+    /// log them. Panics if the path pattern is taken and the duplicate route
+    /// policy is [`DuplicatePolicy::Panic`] (use
+    /// [`GlobalRouter::try_add_route`] to handle those), or if a name is
+    /// already taken and the duplicate name policy is
+    /// [`DuplicateNamePolicy::Panic`] (the default in debug builds).
+    pub fn add_route(&mut self, route: Route) {
+        if let Err(e) = self.check_route_tree(&route) {
+            debug_assert!(false, "Invalid route: {}", e);
+            error_log!("Invalid route, not registered: {}", e);
+            return;
+        }
+        if let Err(e) = self.insert_route(route) {
+            panic!("Invalid route: {}", e);
+        }
     }
 
-    /// Navigate with a specific transition
-    ///
-    /// Convenience method that sets the transition and navigates in one call.
+    /// Register a route, returning an error for invalid route trees
     ///
-    /// # Example
-    /// ```ignore
-    /// use gpui_navigator::{GlobalRouter, Transition};
+    /// The route and its descendants are checked with
+    /// [`validate_route_tree`](crate::validate_route_tree), allowing
+    /// [`RouterConfig::max_route_depth`] levels; errors are returned joined
+    /// by `; `. Child paths starting with `/` are absolute and must start
+    /// with their parent's full path (`/dashboard/reports` under
+    /// `/dashboard`).
     ///
-    /// cx.update_global::<GlobalRouter, _>(|router, _| {
-    ///     router.push_with_transition("/page".to_string(), Transition::slide_left(300));
-    /// });
-    /// ```
-    #[cfg(feature = "transition")]
-    pub fn push_with_transition(
-        &mut self,
-        path: String,
-        transition: Transition,
-    ) -> RouteChangeEvent {
-        self.set_next_transition(transition);
-        self.push(path)
+    /// A top-level route with the same path pattern (ignoring trailing
+    /// slashes) is handled according to [`RouterConfig::on_duplicate_route`].
+    /// Messages name routes by registration number, counting from 1.
+    pub fn try_add_route(&mut self, route: Route) -> Result<(), String> {
+        self.check_route_tree(&route)?;
+        self.insert_route(route)
     }
 
-    /// Replace with a specific transition
-    #[cfg(feature = "transition")]
-    pub fn replace_with_transition(
-        &mut self,
-        path: String,
-        transition: Transition,
-    ) -> RouteChangeEvent {
-        self.set_next_transition(transition);
-        self.replace(path)
+    /// Problems found in the route trees added so far, in the order found
+    ///
+    /// Includes the errors of trees that were refused. See
+    /// [`validate_route_tree`](crate::validate_route_tree).
+    pub fn route_issues(&self) -> &[RouteIssue] {
+        &self.route_issues
     }
-}
 
-impl Default for GlobalRouter {
-    fn default() -> Self {
-        Self::new()
+    /// Validate the tree of `route`, keeping its issues and logging warnings
+    ///
+    /// Returns the errors joined by `; `, if any.
+    fn check_route_tree(&mut self, route: &Route) -> Result<(), String> {
+        let issues = route_tree_issues(route, self.config.max_route_depth);
+        let errors: Vec<String> = issues
+            .iter()
+            .filter(|issue| issue.is_error())
+            .map(ToString::to_string)
+            .collect();
+        for issue in issues.iter().filter(|issue| !issue.is_error()) {
+            warn_log!("{}", issue);
+        }
+        self.route_issues.extend(issues);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
     }
-}
 
-impl Global for GlobalRouter {}
+    /// Register a route whose tree was checked
+    fn insert_route(&mut self, route: Route) -> Result<(), String> {
+        self.registered += 1;
+        let registration = self.registered;
+
+        // Pathless layouts take no path, so they never clash
+        let segments = path_segments(&route.config.path);
+        let existing = self.state.routes().iter().position(|existing| {
+            !route.pathless
+                && !existing.pathless
+                && path_segments(&existing.config.path) == segments
+        });
+        if let Some(index) = existing {
+            let message = format!(
+                "Duplicate route '{}': registration #{} has the same pattern as #{} ('{}')",
+                route.config.path,
+                registration,
+                self.registrations[index],
+                self.state.routes()[index].config.path
+            );
+            match self.config.duplicate_routes {
+                DuplicatePolicy::Warn => {
+                    warn_log!("{}", message);
+                }
+                DuplicatePolicy::Panic => return Err(message),
+                DuplicatePolicy::KeepExisting => {
+                    warn_log!("{}; keeping #{}", message, self.registrations[index]);
+                    return Ok(());
+                }
+                DuplicatePolicy::ReplaceExisting => {
+                    debug_log!("{}; replacing it", message);
+                    self.state.replace_route(index, route);
+                    self.registrations[index] = registration;
+                    self.rebuild_route_names();
+                    #[cfg(feature = "cache")]
+                    self.nested_cache.clear();
+                    return Ok(());
+                }
+            }
+        }
 
-/// Trait for accessing the global router from context
-pub trait UseRouter {
-    /// Get reference to global router
-    fn router(&self) -> &GlobalRouter;
+        self.state.add_route(route);
+        self.registrations.push(registration);
+        if let Some(route) = self.state.routes().last().cloned() {
+            self.register_route_names(&route, "");
+        }
 
-    /// Update global router
-    fn update_router<F, R>(&mut self, f: F) -> R
-    where
-        F: FnOnce(&mut GlobalRouter, &mut App) -> R;
-}
+        // Clear cache when routes change
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
 
-impl UseRouter for App {
-    fn router(&self) -> &GlobalRouter {
-        self.global::<GlobalRouter>()
+        Ok(())
     }
 
-    fn update_router<F, R>(&mut self, f: F) -> R
-    where
-        F: FnOnce(&mut GlobalRouter, &mut App) -> R,
-    {
-        self.update_global(f)
+    /// Check whether a route with this path pattern is registered
+    ///
+    /// Nested routes are looked up by their full path; trailing slashes are
+    /// ignored.
+    pub fn has_route(&self, pattern: &str) -> bool {
+        let segments = path_segments(pattern);
+        route_full_paths(self.state.routes(), "")
+            .iter()
+            .any(|path| path_segments(path) == segments)
     }
-}
 
-/// Initialize global router with routes
-///
-/// # Example
-///
-/// ```ignore
-/// use gpui_navigator::{init_router, Route};
-///
-/// fn main() {
-///     App::new().run(|cx| {
-///         init_router(cx, |router| {
-///             router.add_route(Route::new("/", |_, _cx, _params| gpui::div().into_any_element()));
-///             router.add_route(Route::new("/users/:id", |_, _cx, _params| gpui::div().into_any_element()));
-///         });
-///     });
-/// }
-/// ```
-pub fn init_router<F>(cx: &mut App, configure: F)
-where
-    F: FnOnce(&mut GlobalRouter),
-{
-    let mut router = GlobalRouter::new();
-    configure(&mut router);
-    cx.set_global(router);
-}
-
-/// Navigate to a path using global router
-///
-/// # Example
-///
-/// ```ignore
-/// use gpui_navigator::navigate;
-///
-/// // In any component with access to App
-/// navigate(cx, "/users/123");
-/// ```
-pub fn navigate(cx: &mut App, path: impl Into<String>) {
-    cx.update_router(|router, _cx| {
-        router.push(path.into());
-    });
-}
-
-/// Get current path from global router
-pub fn current_path(cx: &App) -> String {
-    cx.router().current_path().to_string()
-}
-
-/// Handle for Navigator.of(context) pattern
-///
-/// Provides instance methods for chained navigation calls.
-pub struct NavigatorHandle<'a, C: BorrowAppContext> {
-    cx: &'a mut C,
-}
-
-impl<C: BorrowAppContext> NavigatorHandle<'_, C> {
-    /// Navigate to a new path
+    /// Number of registered routes, nested ones included
     ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// use gpui_navigator::{Navigator, PageRoute};
-    ///
-    /// // Simple path
-    /// Navigator::of(cx).push("/users");
-    ///
-    /// // With PageRoute
-    /// Navigator::of(cx).push(PageRoute::builder("/users/:id", |_, _cx, _params| gpui::div())
-    ///     .with_param("id".into(), "123".into()));
-    /// ```
-    pub fn push(self, route: impl IntoRoute) -> Self {
-        let descriptor = route.into_route();
-        self.cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.push(descriptor.path);
-        });
-        self
+    /// Pathless layouts (see [`Route::group_layout`]) are not counted.
+    pub fn route_count(&self) -> usize {
+        route_full_paths(self.state.routes(), "").len()
     }
 
-    /// Replace current path without adding to history
-    pub fn replace(self, route: impl IntoRoute) -> Self {
-        let descriptor = route.into_route();
-        self.cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.replace(descriptor.path);
-        });
-        self
+    /// Register the names of all routes again, after routes were removed or replaced
+    fn rebuild_route_names(&mut self) {
+        self.named_routes.clear();
+        for route in self.state.routes().to_vec() {
+            self.register_route_names(&route, "");
+        }
     }
 
-    /// Go back to the previous route
-    pub fn pop(self) -> Self {
-        self.cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.back();
-        });
-        self
-    }
+    /// Register names for a route and its children, depth-first
+    fn register_route_names(&mut self, route: &RouteRef, parent_path: &str) {
+        let full_path = if parent_path.is_empty() {
+            route.config.path.clone()
+        } else {
+            build_child_path(parent_path, &route.config.path).into_owned()
+        };
+
+        if let Some(name) = route.config.name.as_ref().filter(|_| !route.pathless) {
+            if let Some(existing) = self.named_routes.get(name).map(str::to_string) {
+                match self.config.duplicate_names {
+                    DuplicateNamePolicy::Panic => panic!(
+                        "Duplicate route name '{}': registered for '{}', cannot reuse for '{}'",
+                        name, existing, full_path
+                    ),
+                    DuplicateNamePolicy::KeepFirst => {
+                        error_log!(
+                            "Duplicate route name '{}': keeping '{}', ignoring '{}'",
+                            name,
+                            existing,
+                            full_path
+                        );
+                    }
+                }
+            } else {
+                self.named_routes.register_route(
+                    name.clone(),
+                    full_path.clone(),
+                    Arc::clone(route),
+                );
+            }
+        }
 
-    /// Go forward in history
-    pub fn forward(self) -> Self {
-        self.cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.forward();
-        });
-        self
+        for child in route.get_children() {
+            self.register_route_names(child, &full_path);
+        }
+        for name in route.named_outlet_names() {
+            for child in route.get_named_children(name).unwrap_or_default() {
+                self.register_route_names(child, &full_path);
+            }
+        }
     }
-}
-
-/// Navigation API for convenient route navigation
-///
-/// Provides static methods for navigation operations:
-/// - `Navigator::push(cx, "/path")` - Navigate to a new page
-/// - `Navigator::pop(cx)` - Go back to previous page
-/// - `Navigator::replace(cx, "/path")` - Replace current page
-///
-/// Works with any context that has access to App (`Context<V>`, `App`, etc.)
-///
-/// # Example
-///
-/// ```ignore
-/// use gpui_navigator::Navigator;
-///
-/// // Navigate to a new route
-/// Navigator::push(cx, "/users/123");
-///
-/// // Go back
-/// Navigator::pop(cx);
-///
-/// // Replace current route
-/// Navigator::replace(cx, "/login");
-/// ```
-pub struct Navigator;
 
-impl Navigator {
-    /// Get a NavigatorHandle for the given context
+    /// Register a listener notified after each history change
     ///
-    /// This allows chained navigation calls:
-    /// ```ignore
-    /// use gpui_navigator::Navigator;
+    /// See [`HistoryListener`] for when callbacks run.
+    pub fn add_history_listener(&mut self, listener: Box<dyn HistoryListener>) {
+        self.state.add_history_listener(listener);
+    }
+
+    /// Register middleware run for every navigation
     ///
-    /// // Chained style
-    /// Navigator::of(cx).push("/users");
-    /// Navigator::of(cx).pop();
+    /// Unlike [`Route::middleware`], it applies to all routes. Its
+    /// `before_navigation` runs as a push or replace starts, ahead of the
+    /// guards, and its `after_navigation` once a navigation is committed, from
+    /// [`run_navigation_middleware`] (which the router calls after each
+    /// navigation).
+    /// A replace committed before that is treated as a redirect: only its
+    /// destination is reported, with the direction and source path of the
+    /// navigation it redirected. Higher [`RouteMiddleware::priority`] runs
+    /// first.
     ///
-    /// // Or direct style (also works)
-    /// Navigator::push(cx, "/users");
-    /// Navigator::pop(cx);
-    /// ```
-    pub fn of<C: BorrowAppContext>(cx: &mut C) -> NavigatorHandle<'_, C> {
-        NavigatorHandle { cx }
+    /// [`run_navigation_middleware`]: crate::middleware::run_navigation_middleware
+    #[cfg(feature = "middleware")]
+    pub fn add_middleware<M>(&mut self, middleware: M)
+    where
+        M: RouteMiddleware<
+            Future = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>,
+        >,
+    {
+        self.middleware.push(Arc::new(Box::new(middleware)));
+        self.middleware.sort_by_key(|m| -m.priority());
     }
 
-    /// Navigate to a new path
+    /// Add middleware for navigations to the routes `filter` selects
     ///
-    /// # Example
+    /// Runs like [`GlobalRouter::add_middleware`], only for the selected
+    /// routes. Higher [`RouteMiddleware::priority`] runs first; at equal
+    /// priority, filtered middleware runs first, in registration order,
+    /// then the middleware of the routes and the unfiltered kind. See
+    /// [`crate::filter`] for what filters match.
     ///
     /// ```ignore
-    /// use gpui_navigator::{Navigator, PageRoute};
-    ///
-    /// // Simple string path
-    /// Navigator::push(cx, "/users/123");
-    ///
-    /// // With PageRoute and params
-    /// Navigator::push(cx, PageRoute::builder("/profile", |_, _cx, _params| gpui::div())
-    ///     .with_param("userId".into(), "456".into()));
+    /// router.add_middleware_filtered(
+    ///     AuditMiddleware::new(),
+    ///     RouteFilter::path("/admin/**").except(RouteFilter::path("/admin/health")),
+    /// );
     /// ```
-    pub fn push(cx: &mut impl BorrowAppContext, route: impl IntoRoute) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.push(descriptor.path);
-        });
+    #[cfg(feature = "middleware")]
+    pub fn add_middleware_filtered<M>(&mut self, middleware: M, filter: RouteFilter)
+    where
+        M: RouteMiddleware<
+            Future = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>,
+        >,
+    {
+        self.filtered_middleware
+            .push((filter, Arc::new(Box::new(middleware))));
     }
 
-    /// Replace current path without adding to history
+    /// Filtered middleware selecting the navigation to `path`, resolving to
+    /// `chain`
+    #[cfg(feature = "middleware")]
+    pub(crate) fn filtered_middleware<'a>(
+        &'a self,
+        path: &'a str,
+        chain: &'a [&RouteRef],
+    ) -> impl Iterator<Item = &'a Arc<BoxedMiddleware>> + 'a {
+        self.filtered_middleware
+            .iter()
+            .filter(move |(filter, _)| filter.matches(path, chain))
+            .map(|(_, middleware)| middleware)
+    }
+
+    /// Global middleware for the navigation to `path`, resolving to
+    /// `chain`: the filtered kind selecting it, then the unfiltered kind, by
+    /// priority
+    #[cfg(feature = "middleware")]
+    pub(crate) fn middleware_for(
+        &self,
+        path: &str,
+        chain: &[&RouteRef],
+    ) -> Vec<Arc<BoxedMiddleware>> {
+        let mut middleware: Vec<_> = self
+            .filtered_middleware(path, chain)
+            .chain(&self.middleware)
+            .cloned()
+            .collect();
+        middleware.sort_by_key(|m| -m.priority());
+        middleware
+    }
+
+    /// Guards and middleware that apply to navigations to `path`
     ///
-    /// # Example
+    /// Lists the filtered entries whose filter selects the path, with the
+    /// filter they were added with, and the guards and middleware of the
+    /// routes it matches. Unfiltered and transition guards, which apply
+    /// everywhere, are not listed.
     ///
     /// ```ignore
-    /// use gpui_navigator::{Navigator, PageRoute};
-    ///
-    /// // Simple string path
-    /// Navigator::replace(cx, "/login");
-    ///
-    /// // With PageRoute
-    /// Navigator::replace(cx, PageRoute::builder("/login", |_, _cx, _params| gpui::div())
-    ///     .with_param("redirect".into(), "/dashboard".into()));
+    /// eprintln!("{}", router.explain("/admin/users"));
+    /// // /admin/users -> /admin/users
+    /// //   filtered middleware AuditMiddleware by path(/admin/**)
     /// ```
-    pub fn replace(cx: &mut impl BorrowAppContext, route: impl IntoRoute) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.replace(descriptor.path);
-        });
+    #[cfg(any(feature = "guard", feature = "middleware"))]
+    pub fn explain(&self, path: &str) -> RouteExplanation {
+        let (_, canonical) = self.delocalize(split_fragment(path).0);
+        let chain = match_route_chain(self.state.routes(), &canonical)
+            .map(|(chain, _)| chain)
+            .unwrap_or_default();
+        let mut explanation = RouteExplanation {
+            path: path.to_string(),
+            pattern: (!chain.is_empty()).then(|| chain_pattern(&chain)),
+            ..RouteExplanation::default()
+        };
+
+        #[cfg(feature = "guard")]
+        {
+            explanation.filtered.extend(
+                self.filtered_guards
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (filter, _))| filter.matches(&canonical, &chain))
+                    .map(|(index, (filter, guard))| FilteredEntry {
+                        kind: FilteredKind::Guard,
+                        name: guard.name().to_string(),
+                        filter: filter.clone(),
+                        index,
+                    }),
+            );
+            explanation.route_guards = chain
+                .iter()
+                .flat_map(|route| &route.guards)
+                .map(|guard| guard.name().to_string())
+                .collect();
+        }
+        #[cfg(feature = "middleware")]
+        {
+            explanation.filtered.extend(
+                self.filtered_middleware
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (filter, _))| filter.matches(&canonical, &chain))
+                    .map(|(index, (filter, middleware))| FilteredEntry {
+                        kind: FilteredKind::Middleware,
+                        name: middleware.name().to_string(),
+                        filter: filter.clone(),
+                        index,
+                    }),
+            );
+            explanation.route_middleware = chain
+                .iter()
+                .flat_map(|route| &route.middleware)
+                .map(|middleware| middleware.name().to_string())
+                .collect();
+        }
+        explanation
     }
 
-    /// Go back to the previous route
+    /// Drop guard results remembered for route param `key` set to `value`
     ///
-    /// # Example
+    /// Guards of every route, nested ones included, are told through
+    /// [`RouteGuard::invalidate_scope`], so a [`ParamScopedGuard`] checks
+    /// paths with that value again.
     ///
-    /// ```ignore
-    /// use gpui_navigator::Navigator;
+    /// [`RouteGuard::invalidate_scope`]: crate::RouteGuard::invalidate_scope
+    /// [`ParamScopedGuard`]: crate::ParamScopedGuard
+    #[cfg(feature = "guard")]
+    pub fn invalidate_guard_scope(&self, key: &str, value: &str) {
+        fn invalidate(routes: &[RouteRef], key: &str, value: &str) {
+            for route in routes {
+                for guard in &route.guards {
+                    guard.invalidate_scope(key, value);
+                }
+                invalidate(route.get_children(), key, value);
+            }
+        }
+        invalidate(self.state.routes(), key, value);
+        for (_, guard) in &self.filtered_guards {
+            guard.invalidate_scope(key, value);
+        }
+    }
+
+    /// Check whether the guards on `path` would let navigation through
     ///
-    /// if Navigator::can_pop(cx) {
-    ///     Navigator::pop(cx);
-    /// }
-    /// ```
-    pub fn pop(cx: &mut impl BorrowAppContext) {
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.back();
-        });
+    /// Only synchronous guards are evaluated (see [`RouteGuard::synchronous`]).
+    /// Nothing is navigated, recorded in history or cached. Paths that match
+    /// no route are reported as allowed.
+    ///
+    /// [`RouteGuard::synchronous`]: crate::RouteGuard::synchronous
+    #[cfg(feature = "guard")]
+    pub fn can_access(&self, cx: &App, path: &str) -> AccessCheck {
+        self.poll_navigation(cx, self.current_path(), split_fragment(path).0)
+            .into()
     }
 
-    /// Alias for pop() - go back (kept for compatibility)
-    pub fn back(cx: &mut impl BorrowAppContext) {
-        Self::pop(cx);
+    /// Evaluate the synchronous transition guards, then the guards on
+    /// `path`, for a navigation from `from` without navigating
+    #[cfg(feature = "guard")]
+    fn poll_navigation(&self, cx: &App, from: &str, path: &str) -> GuardPoll {
+        let request = self.guard_request(from, path, self.state.routes());
+        match self.poll_transition(cx, &request) {
+            GuardPoll::Passed { unknown } => match self.poll_guards(cx, from, path, request.source)
+            {
+                GuardPoll::Passed { unknown: later } => GuardPoll::Passed {
+                    unknown: unknown || later,
+                },
+                blocked => blocked,
+            },
+            blocked => blocked,
+        }
     }
 
-    /// Go forward in history
-    pub fn forward(cx: &mut impl BorrowAppContext) {
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.forward();
-        });
+    /// Evaluate the synchronous guards on `path` without navigating, for a
+    /// navigation started by `source`
+    #[cfg(feature = "guard")]
+    fn poll_guards(&self, cx: &App, from: &str, path: &str, source: NavigationSource) -> GuardPoll {
+        let passed = GuardPoll::Passed { unknown: false };
+        let Some((chain, _)) = match_route_chain(self.state.routes(), path) else {
+            return passed;
+        };
+
+        let guards: Vec<_> = self
+            .filtered_guards(path, &chain)
+            .chain(chain.iter().flat_map(|route| route.guards.iter()))
+            .collect();
+        if guards.is_empty() {
+            return passed;
+        }
+
+        let request = self
+            .guard_request(from, path, self.state.routes())
+            .with_source(source);
+        let _span = diagnostics::guards(path);
+        poll_guards(&guards, cx, &request)
     }
 
-    /// Get current path
-    ///
-    /// Works with `Context<V>` since it derefs to App
+    /// Evaluate the synchronous transition guards on `request`
+    #[cfg(feature = "guard")]
+    fn poll_transition(&self, cx: &App, request: &NavigationRequest) -> GuardPoll {
+        if self.transition_guards.is_empty() {
+            return GuardPoll::Passed { unknown: false };
+        }
+        let guards: Vec<_> = self
+            .transition_guards
+            .iter()
+            .map(|guard| &**guard)
+            .collect();
+        let _span = diagnostics::guards(&request.to);
+        poll_guards(&guards, cx, request)
+    }
+
+    /// Request guards see for a navigation from `from` to `to`
     ///
-    /// # Example
+    /// `from` is matched against the current routes and `to` against
+    /// `to_routes`, which differ on a route table switch.
+    #[cfg(feature = "guard")]
+    fn guard_request(&self, from: &str, to: &str, to_routes: &[RouteRef]) -> NavigationRequest {
+        let (_, from) = self.delocalize(from);
+        let (from_pattern, from_meta, from_typed_meta) =
+            match_route_chain(self.state.routes(), &from)
+                .map(|(chain, _)| {
+                    (
+                        Some(chain_pattern(&chain)),
+                        chain_meta(&chain),
+                        chain_typed_meta(&chain),
+                    )
+                })
+                .unwrap_or_default();
+        let (to_meta, to_typed_meta, params) = match_route_chain(to_routes, to)
+            .map(|(chain, route_match)| {
+                (
+                    chain_meta(&chain),
+                    chain_typed_meta(&chain),
+                    RouteParams::from_map(route_match.params),
+                )
+            })
+            .unwrap_or_default();
+        NavigationRequest::with_from(to.to_string(), from)
+            .with_params(params)
+            .with_token(self.checked_token.clone().unwrap_or_default())
+            .with_from_route(from_pattern, from_meta)
+            .with_to_meta(to_meta)
+            .with_typed_meta(from_typed_meta, to_typed_meta)
+            .with_source(self.state.source().clone())
+    }
+
+    /// Run `f` with the navigations it makes started by `source`
     ///
-    /// ```ignore
-    /// use gpui_navigator::Navigator;
+    /// The requests guards see and the events committed carry the source;
+    /// the previous one is restored afterwards.
+    pub fn with_source<R>(
+        &mut self,
+        source: NavigationSource,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let outer = self.state.replace_source(source);
+        let result = f(self);
+        self.state.replace_source(outer);
+        result
+    }
+
+    /// Show `path` in the named outlets called `name`
     ///
-    /// let path = Navigator::current_path(cx);
-    /// ```
-    pub fn current_path(cx: &App) -> String {
-        cx.global::<GlobalRouter>().current_path().to_string()
+    /// The path is relative to the outlet's parent route: `items/42` makes a
+    /// `detail` outlet of `/inbox` show its `items/:id` child. Only those
+    /// outlets change; the current path and history stay as they are. The
+    /// outlet keeps the path across navigations until it is cleared.
+    pub fn set_outlet_path(&mut self, name: impl Into<String>, path: impl Into<String>) {
+        self.outlet_paths.insert(name.into(), path.into());
     }
 
-    /// Check if can go back
-    pub fn can_pop(cx: &App) -> bool {
-        cx.global::<GlobalRouter>().can_go_back()
+    /// Path shown by the named outlets called `name`, if one was set
+    pub fn outlet_path(&self, name: &str) -> Option<&str> {
+        self.outlet_paths.get(name).map(String::as_str)
     }
 
-    /// Alias for can_pop() - check if can go back (kept for compatibility)
-    pub fn can_go_back(cx: &App) -> bool {
-        Self::can_pop(cx)
+    /// Make the named outlets called `name` follow the current path again
+    pub fn clear_outlet_path(&mut self, name: &str) {
+        self.outlet_paths.remove(name);
     }
 
-    /// Navigate to a named route with parameters
+    /// Look up a registered route by name
+    pub fn route_by_name(&self, name: &str) -> Option<&RouteRef> {
+        self.named_routes.get_route(name)
+    }
+
+    /// Keep the old name of a renamed route working
     ///
-    /// # Example
+    /// Navigations and URLs through `alias` go to the route named `name`,
+    /// with a deprecation warning logged the first time. See
+    /// [`NamedRouteRegistry::register_alias`].
     ///
     /// ```ignore
-    /// use gpui_navigator::{Navigator, RouteParams};
-    ///
-    /// let mut params = RouteParams::new();
-    /// params.set("id".into(), "123".into());
-    ///
-    /// Navigator::push_named(cx, "user.detail", &params);
+    /// router.add_route(Route::new("/users/:id", user_page).name("users.detail"));
+    /// router.register_alias("user.profile", "users.detail");
     /// ```
-    pub fn push_named(cx: &mut impl BorrowAppContext, name: &str, params: &RouteParams) {
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.push_named(name, params);
-        });
+    pub fn register_alias(&mut self, alias: impl Into<String>, name: impl Into<String>) {
+        self.named_routes.register_alias(alias, name);
     }
 
-    /// Generate URL for a named route
-    ///
-    /// # Example
+    /// Named routes with their full path patterns and aliases, by name
+    pub fn named_routes(&self) -> Vec<(String, String, Vec<String>)> {
+        let mut named: Vec<(String, String, Vec<String>)> = self
+            .named_routes
+            .names()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    self.named_routes.get(name).unwrap_or_default().to_string(),
+                    self.named_routes
+                        .aliases_of(name)
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                )
+            })
+            .collect();
+        named.sort();
+        named
+    }
+
+    /// Commands a command palette can offer for the active route table
     ///
-    /// ```ignore
-    /// use gpui_navigator::{Navigator, RouteParams};
+    /// One per listed route, depth-first in registration order. See
+    /// [`crate::palette`] for which routes are listed and how meta
+    /// configures them.
+    pub fn navigation_commands(&self) -> Vec<NavigationCommand> {
+        commands_for(self.state.routes(), "")
+    }
+
+    /// Navigation commands whose label matches `query`, best first
     ///
-    /// let mut params = RouteParams::new();
-    /// params.set("id".into(), "123".into());
+    /// See [`crate::palette::fuzzy_match`].
+    pub fn fuzzy_match(&self, query: &str) -> Vec<ScoredCommand> {
+        fuzzy_match(&self.navigation_commands(), query)
+    }
+
+    /// Navigate to the route of `command`, with `params` over its provided ones
     ///
-    /// let url = Navigator::url_for(cx, "user.detail", &params);
-    /// assert_eq!(url, Some("/users/123".to_string()));
-    /// ```
-    pub fn url_for(cx: &App, name: &str, params: &RouteParams) -> Option<String> {
-        cx.global::<GlobalRouter>().url_for(name, params)
+    /// Named routes go through [`GlobalRouter::push_named`]; others are
+    /// pushed at their expanded pattern, refused with `InvalidParams` when
+    /// a param is missing or rejected.
+    pub fn run_command(
+        &mut self,
+        command: &NavigationCommand,
+        params: &RouteParams,
+    ) -> NavigationResult {
+        if let Some(name) = &command.name {
+            return self.push_named(name, &command.params_with(params));
+        }
+        match command.target(params) {
+            Ok(url) => self.push_reported(None, url),
+            Err(error) => self.refuse_target(NavigationError::InvalidParams {
+                message: error.to_string(),
+            }),
+        }
     }
 
-    /// Check if can go forward
-    pub fn can_go_forward(cx: &App) -> bool {
-        cx.global::<GlobalRouter>().can_go_forward()
+    /// Register a route tree described by a [`RouteConfig`]
+    ///
+    /// Builders are looked up by route name in `builders`, for the config
+    /// itself and for each of its `children`. See [`Route::from_config`].
+    pub fn add_route_config(
+        &mut self,
+        config: RouteConfig,
+        builders: &HashMap<String, RouteBuilder>,
+    ) -> Result<(), String> {
+        let route = Route::from_config(config, builders)?;
+        self.try_add_route(route)
     }
 
-    /// Set transition for the next navigation
+    /// Add routes to the running router
     ///
-    /// The transition will be used for the next push/replace call,
-    /// then automatically cleared.
+    /// Unlike [`init_router`], history and the current path are kept. If the
+    /// current path resolves to a different route afterwards (e.g. it was
+    /// not found before), it is re-resolved in place without a history
+    /// change and windows are refreshed. Without a router yet, this is
+    /// [`init_router`].
     ///
-    /// # Example
     /// ```ignore
-    /// use gpui_navigator::{Navigator, Transition};
-    ///
-    /// Navigator::set_next_transition(cx, Transition::fade(300));
-    /// Navigator::push(cx, "/page");
+    /// GlobalRouter::extend(cx, |router| {
+    ///     router.add_route(Route::new("/plugin", plugin_page));
+    /// });
     /// ```
-    #[cfg(feature = "transition")]
-    pub fn set_next_transition(cx: &mut impl BorrowAppContext, transition: Transition) {
+    pub fn extend<F>(cx: &mut App, configure: F)
+    where
+        F: FnOnce(&mut GlobalRouter),
+    {
+        if !cx.has_global::<GlobalRouter>() {
+            init_router(cx, configure);
+            return;
+        }
         cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.set_next_transition(transition);
+            let previous = router.current_leaf_route();
+            configure(router);
+            router.routes_changed(previous.as_ref());
+            router.generation = next_generation();
         });
+        refresh_windows(cx);
     }
 
-    /// Navigate with a specific transition
-    ///
-    /// # Example
-    /// ```ignore
-    /// use gpui_navigator::{Navigator, Transition};
+    /// Unregister a top-level route by path pattern or name
     ///
-    /// Navigator::push_with_transition(cx, "/page", Transition::slide_left(300));
+    /// The route's names and those of its children are removed and caches
+    /// are invalidated. History is kept; if the current path matched the
+    /// route, the outlet shows the not-found page. Returns `false` if no
+    /// top-level route has this path or name.
+    pub fn remove_route(&mut self, path_or_name: &str) -> bool {
+        let target = self.named_routes.get_route(path_or_name).cloned();
+        let Some(index) = self.state.routes().iter().position(|route| {
+            route.config.path == path_or_name
+                || target
+                    .as_ref()
+                    .is_some_and(|target| Arc::ptr_eq(route, target))
+        }) else {
+            warn_log!("No top-level route '{}' to remove", path_or_name);
+            return false;
+        };
+
+        let previous = self.current_leaf_route();
+        let removed = self.state.remove_route(index);
+        debug_log!("Removed route '{}'", removed.config.path);
+
+        // Rebuild names so the removed route's children go too
+        self.registrations.remove(index);
+        self.rebuild_route_names();
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+
+        self.routes_changed(previous.as_ref());
+        true
+    }
+
+    /// Innermost route matching the current path
+    fn current_leaf_route(&self) -> Option<RouteRef> {
+        match_route_chain(self.state.routes(), self.current_path())
+            .and_then(|(chain, _)| chain.last().map(|route| Arc::clone(route)))
+    }
+
+    /// Re-resolve the current path after the route table changed
+    fn routes_changed(&mut self, previous: Option<&RouteRef>) {
+        let current = self.current_leaf_route();
+        let unchanged = match (previous, &current) {
+            (Some(previous), Some(current)) => Arc::ptr_eq(previous, current),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+
+        let path = self.current_path().to_string();
+        debug_log!(
+            "Current path '{}' now resolves to {:?}",
+            path,
+            current.as_ref().map(|route| &route.config.path)
+        );
+        self.state.retain_scoped_states();
+        self.schedule_loader(&path, None);
+    }
+
+    /// Add routes to the route table `name`, creating it if needed
+    ///
+    /// Route tables are separate route trees, such as the signed-out and
+    /// the signed-in shell of an app. Only the active table's routes match
+    /// and navigation works on it; the others wait for
+    /// [`GlobalRouter::activate_table`]. `configure` adds routes as usual,
+    /// and they go to `name`. Routes added outside a table belong to the
+    /// `default` table, active until another one is.
+    ///
+    /// ```ignore
+    /// init_router(cx, |router| {
+    ///     router.define_table("auth", |router| {
+    ///         router.add_route(Route::new("/", |_, _, _| welcome()));
+    ///         router.add_route(Route::new("/login", |_, _, _| login()));
+    ///     });
+    ///     router.define_table("app", |router| {
+    ///         router.add_route(Route::new("/", |_, _, _| workspace()));
+    ///         router.add_route(Route::new("/users/:id", |_, _, _| user()).name("user.detail"));
+    ///     });
+    ///     router.switch_table("auth", "/").unwrap();
+    /// });
     /// ```
-    #[cfg(feature = "transition")]
-    pub fn push_with_transition(
-        cx: &mut impl BorrowAppContext,
-        route: impl IntoRoute,
-        transition: Transition,
-    ) {
-        let descriptor = route.into_route();
+    pub fn define_table<F>(&mut self, name: &str, configure: F)
+    where
+        F: FnOnce(&mut GlobalRouter),
+    {
+        if name == self.active_table {
+            let previous = self.current_leaf_route();
+            configure(self);
+            self.routes_changed(previous.as_ref());
+            return;
+        }
+        let table = self.tables.remove(name).unwrap_or_default();
+        let active = self.swap_table(table);
+        configure(self);
+        let defined = self.swap_table(active);
+        self.tables.insert(name.to_string(), defined);
+    }
+
+    /// Put the routes and names of `table` in place of the active ones,
+    /// returning those
+    fn swap_table(&mut self, mut table: RouteTable) -> RouteTable {
+        table.routes = self.state.swap_routes(table.routes);
+        std::mem::swap(&mut self.registrations, &mut table.registrations);
+        std::mem::swap(&mut self.named_routes, &mut table.named_routes);
+        self.named_routes.set_strict(self.config.strict_url_params);
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+        table
+    }
+
+    /// Activate the route table `name`, starting at `initial_path`
+    ///
+    /// Navigations in flight are cancelled and the history of the table
+    /// left is dropped or archived (see [`RouterConfig::on_table_switch`]).
+    /// A table with archived history returns to it instead of starting at
+    /// `initial_path`. Loader results, scoped state and caches are dropped,
+    /// and the change is reported as a single replace. Activating the active
+    /// table replaces the current entry with `initial_path`.
+    ///
+    /// Without an `App` to run them with, the transition guards are asked
+    /// on the next tick, and the switch happens then if they allow it; the
+    /// returned event describes the switch that will happen.
+    /// [`GlobalRouter::activate_table`] asks them right away.
+    pub fn switch_table(
+        &mut self,
+        name: &str,
+        initial_path: impl Into<String>,
+    ) -> Result<RouteChangeEvent, String> {
+        let initial_path = initial_path.into();
+        if name == self.active_table {
+            return self.replace_in_table(initial_path);
+        }
+        #[cfg(feature = "guard")]
+        if !self.transition_guards.is_empty() && self.tables.contains_key(name) {
+            let event = self.pending_event(initial_path.clone(), PendingCommit::Replace);
+            let (name, path) = (name.to_string(), initial_path.clone());
+            self.next_tick(&initial_path, move |router, cx| {
+                let _ = router.checked_switch_table(cx, &name, path);
+            });
+            return Ok(event);
+        }
+        self.enter_table(name, initial_path)
+    }
+
+    /// Replace the current entry of the active table with `initial_path`
+    fn replace_in_table(&mut self, initial_path: String) -> Result<RouteChangeEvent, String> {
+        let name = self.active_table.clone();
+        self.replace(initial_path)
+            .into_event()
+            .ok_or_else(|| throttled_replace(&name))
+    }
+
+    /// Activate the route table `name`, other than the active one, starting
+    /// at `initial_path`
+    fn enter_table(
+        &mut self,
+        name: &str,
+        initial_path: String,
+    ) -> Result<RouteChangeEvent, String> {
+        let Some(table) = self.tables.remove(name) else {
+            return Err(format!("No route table '{}'", name));
+        };
+        debug_log!(
+            "Switching route table '{}' -> '{}'",
+            self.active_table,
+            name
+        );
+
+        // Whatever was under way belongs to the table left
+        self.cancel_navigations();
+        self.loading = None;
+        self.loader_task = None;
+        self.blocked_on = None;
+        self.throttled = None;
+        self.throttle_task = None;
+        #[cfg(feature = "guard")]
+        {
+            self.leave = None;
+        }
+        self.render_navigations.clear();
+        self.outlet_paths.clear();
+        self.poisoned.clear();
+        self.clear_prepared();
+
+        let archive = (self.config.table_history == TableHistoryPolicy::Archive)
+            .then(|| self.state.history_snapshot());
+        let (routes, event) = self
+            .state
+            .switch_routes(table.routes, table.history, initial_path);
+        let left = RouteTable {
+            routes,
+            registrations: std::mem::replace(&mut self.registrations, table.registrations),
+            named_routes: std::mem::replace(&mut self.named_routes, table.named_routes),
+            history: archive,
+        };
+        self.named_routes.set_strict(self.config.strict_url_params);
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+        let left_name = std::mem::replace(&mut self.active_table, name.to_string());
+        self.tables.insert(left_name, left);
+        self.generation = next_generation();
+
+        self.begin_navigation();
+        self.commit_navigation();
+        self.batched(&event);
+        #[cfg(feature = "middleware")]
+        self.record_navigation(&event);
+        let path = self.current_path().to_string();
+        self.schedule_loader(&path, None);
+        Ok(event)
+    }
+
+    /// Activate the route table `name`, starting at `initial_path`
+    ///
+    /// See [`GlobalRouter::switch_table`]; windows are refreshed afterwards.
+    /// Transition guards (see [`GlobalRouter::add_transition_guard`]) are
+    /// asked first: a denial is returned as the error and a redirect starts
+    /// the table at its target.
+    ///
+    /// ```ignore
+    /// // Signed in: show the workspace
+    /// GlobalRouter::activate_table(cx, "app", "/")?;
+    /// ```
+    pub fn activate_table(
+        cx: &mut App,
+        name: &str,
+        initial_path: impl Into<String>,
+    ) -> Result<RouteChangeEvent, String> {
+        let event = cx.update_global::<GlobalRouter, _>(|router, cx| {
+            router.checked_switch_table(cx, name, initial_path.into())
+        })?;
+        refresh_windows(cx);
+        Ok(event)
+    }
+
+    /// Activate the route table `name` at `initial_path`, asking the
+    /// transition guards with `cx` first
+    fn checked_switch_table(
+        &mut self,
+        cx: &App,
+        name: &str,
+        initial_path: String,
+    ) -> Result<RouteChangeEvent, String> {
+        if name == self.active_table {
+            let options = NavOptions::default();
+            return match self.navigate_opts(
+                Some(cx),
+                PendingCommit::Replace,
+                initial_path,
+                &options,
+            ) {
+                Guarded::Refused {
+                    error: NavigationError::GuardBlocked { reason },
+                } => Err(reason),
+                Guarded::Refused { error } => Err(error.to_string()),
+                guarded => guarded
+                    .into_outcome()
+                    .into_event()
+                    .ok_or_else(|| throttled_replace(name)),
+            };
+        }
+        #[cfg(feature = "guard")]
+        {
+            let (initial_path, source) = self.guard_table_switch(cx, name, initial_path)?;
+            self.with_source(source, |router| router.enter_table(name, initial_path))
+        }
+        #[cfg(not(feature = "guard"))]
+        self.enter_table(name, initial_path)
+    }
+
+    /// Check the transition guards on switching to the table `name` at `path`
+    ///
+    /// Returns the path to start at and the source to switch with, the
+    /// redirect target if a guard redirects, or the reason a guard denied,
+    /// recorded as a refused navigation.
+    #[cfg(feature = "guard")]
+    fn guard_table_switch(
+        &mut self,
+        cx: &App,
+        name: &str,
+        path: String,
+    ) -> Result<(String, NavigationSource), String> {
+        let source = self.state.source().clone();
+        let routes = if name == self.active_table {
+            self.state.routes()
+        } else if let Some(table) = self.tables.get(name) {
+            &table.routes
+        } else {
+            return Ok((path, source));
+        };
+        let request = self.guard_request(self.current_path(), &path, routes);
+        match self.poll_transition(cx, &request).settled(&path) {
+            GuardPoll::Passed { .. } => Ok((path, source)),
+            GuardPoll::Denied(reason) => {
+                self.refuse(NavigationError::GuardBlocked {
+                    reason: reason.clone(),
+                });
+                Err(reason)
+            }
+            GuardPoll::Redirected { to, guard, .. } => {
+                Ok((to, NavigationSource::Redirect { from_guard: guard }))
+            }
+        }
+    }
+
+    /// What going back or forward with no entry left does instead
+    ///
+    /// Going back from the root of a table other than the one set with
+    /// [`RouterConfig::back_at_root_to_table`] activates that table;
+    /// otherwise the handler configured for `direction` runs. Returns `None`
+    /// if neither is set.
+    fn history_edge(cx: &mut App, direction: NavigationDirection) -> Option<PopOutcome> {
+        let router = cx.global::<Self>();
+        let back = direction == NavigationDirection::Back;
+        if let Some(table) = router.config.back_to_table.as_ref().filter(|table| {
+            back && **table != router.active_table && router.tables.contains_key(*table)
+        }) {
+            let table = table.clone();
+            let initial_path = router.config.initial_path.clone();
+            debug_log!("No history left, going back to route table '{}'", table);
+            let event =
+                Self::activate_table(cx, &table, initial_path.unwrap_or_else(|| "/".into()));
+            return Some(event.ok().into());
+        }
+        let handler = if back {
+            router.config.back_at_root.clone()
+        } else {
+            router.config.forward_at_end.clone()
+        }?;
+        debug_log!("No history left, running the app's {:?} handler", direction);
+        handler.call(cx);
+        Some(PopOutcome::Delegated)
+    }
+
+    /// Name of the route table navigation works on
+    pub fn active_table(&self) -> &str {
+        &self.active_table
+    }
+
+    /// Configure the nested route cache
+    ///
+    /// `capacity` is the number of entries kept per cache and must be non-zero.
+    /// Disabling the cache drops everything cached so far.
+    #[cfg(feature = "cache")]
+    pub fn configure_cache(&mut self, capacity: usize, enabled: bool) {
+        self.nested_cache.set_capacity(capacity);
+        self.nested_cache.set_enabled(enabled);
+    }
+
+    /// Find the parent route whose outlet renders `path`
+    ///
+    /// Results are cached per path and survive navigation, so revisiting a
+    /// section of the app skips the route tree walk.
+    pub fn find_parent_route(&mut self, path: &str) -> Option<Arc<Route>> {
+        #[cfg(feature = "cache")]
+        if let Some(id) = self.nested_cache.get_parent(path) {
+            if let Some(route) = find_route_by_full_path(self.state.routes(), &id.path) {
+                diagnostics::outlet_cache(true);
+                return Some(Arc::clone(route));
+            }
+        }
+        #[cfg(feature = "cache")]
+        diagnostics::outlet_cache(false);
+
+        let (route, full_path) = find_parent_route_with_path(self.state.routes(), path)?;
+        let route = Arc::clone(route);
+
+        // Pathless layouts cannot be told apart by their full path
+        #[cfg(feature = "cache")]
+        if !route.pathless {
+            self.nested_cache
+                .set_parent(path.to_string(), RouteId::from_path(full_path));
+        }
+        #[cfg(not(feature = "cache"))]
+        let _ = full_path;
+
+        Some(route)
+    }
+
+    /// Resolve the child `parent_route`, whose full pattern is `parent_path`,
+    /// renders in the outlet named `outlet_name` for `path`
+    ///
+    /// Results are cached per path and outlet until a navigation leaves or
+    /// reaches the path, so rendering the same page again skips matching the
+    /// children.
+    #[cfg_attr(not(feature = "cache"), allow(clippy::unused_self))]
+    pub(crate) fn resolve_outlet_child(
+        &mut self,
+        parent_route: &Arc<Route>,
+        parent_path: &str,
+        path: &str,
+        params: &RouteParams,
+        outlet_name: Option<&str>,
+    ) -> Option<ResolvedChildRoute> {
+        #[cfg(feature = "cache")]
+        if let Some((id, params)) = self.nested_cache.get_child(path, outlet_name) {
+            let children = match outlet_name {
+                Some(name) => parent_route.get_named_children(name).unwrap_or_default(),
+                None => parent_route.get_children(),
+            };
+            if let Some(child) = children
+                .iter()
+                .find(|child| !child.pathless && child_full_path(parent_path, child) == id.path)
+            {
+                return Some((Arc::clone(child), params));
+            }
+        }
+
+        let (child, params) =
+            resolve_child_route_at(parent_route, parent_path, path, params, outlet_name)?;
+        // Pathless layouts cannot be told apart by their full path
+        #[cfg(feature = "cache")]
+        if !child.pathless {
+            self.nested_cache.set_child(
+                path.to_string(),
+                outlet_name.map(str::to_string),
+                RouteId::from_path(child_full_path(parent_path, &child)),
+                params.clone(),
+            );
+        }
+        Some((child, params))
+    }
+
+    /// Navigate to a named route with parameters
+    ///
+    /// Returns `NotFound` for an unknown name and a `NamedRoute` error if no
+    /// URL can be generated from `params` (see [`GlobalRouter::try_url_for`]);
+    /// in both cases no navigation happens and the error is recorded like a
+    /// refused navigation (see [`GlobalRouter::last_error`]). The URL is then
+    /// pushed and reported like [`GlobalRouter::try_push`]: `Blocked`,
+    /// `Redirected` or `Pending` when its guards stop, redirect or hold it.
+    /// Without an `App` the guards run on the next tick, so the push is
+    /// `Pending` if the route has any; [`Navigator::push_named`] runs them
+    /// right away.
+    pub fn push_named(&mut self, name: &str, params: &RouteParams) -> NavigationResult {
+        self.push_named_with(None, name, params)
+    }
+
+    /// Push the named route `name`, running its guards with `cx`
+    fn push_named_with(
+        &mut self,
+        cx: Option<&App>,
+        name: &str,
+        params: &RouteParams,
+    ) -> NavigationResult {
+        match self.named_url(name, params) {
+            Ok(url) => self.push_reported(cx, url),
+            Err(error) => self.refuse_target(error),
+        }
+    }
+
+    /// Push `path`, reporting how its guards took it
+    fn push_reported(&mut self, cx: Option<&App>, path: String) -> NavigationResult {
+        self.navigate_opts(
+            cx,
+            PendingCommit::Push,
+            path.clone(),
+            &NavOptions::default(),
+        )
+        .into_result(path)
+    }
+
+    /// Path a navigation to `route` goes to
+    ///
+    /// Named targets (see [`NamedTarget`](crate::NamedTarget)) are looked up
+    /// with their params and query; other descriptors go to their path. An
+    /// unknown name gives a `RouteNotFound` error and params the route's path
+    /// cannot take a `NamedRoute` error.
+    pub fn target_path(&self, route: &RouteDescriptor) -> Result<String, NavigationError> {
+        let Some(name) = &route.name else {
+            return Ok(route.path.clone());
+        };
+        let url = self.named_url(name, &route.params)?;
+        if route.query.is_empty() {
+            Ok(url)
+        } else {
+            Ok(format!("{}?{}", url, route.query.to_query_string()))
+        }
+    }
+
+    /// URL of the named route `name` with `params` filled in
+    fn named_url(&self, name: &str, params: &RouteParams) -> Result<String, NavigationError> {
+        self.try_url_for(name, params).map_err(|error| match error {
+            UrlForError::UnknownRoute(name) => NavigationError::RouteNotFound {
+                path: name,
+                details: None,
+            },
+            error => NavigationError::NamedRoute {
+                name: name.to_string(),
+                error,
+            },
+        })
+    }
+
+    /// Record a target that could not be resolved as a refused navigation
+    fn refuse_target(&mut self, error: NavigationError) -> NavigationResult {
+        self.refuse(error.clone());
+        match error {
+            NavigationError::RouteNotFound { path, .. } => NavigationResult::NotFound { path },
+            error => NavigationResult::Error(error),
+        }
+    }
+
+    /// Navigate to `route` with `navigate`, if its target resolves
+    ///
+    /// A target that does not resolve is refused, leaving the current path.
+    pub(crate) fn navigate_to(
+        &mut self,
+        route: &RouteDescriptor,
+        navigate: impl FnOnce(&mut Self, String) -> PushOutcome,
+    ) -> PushOutcome {
+        match self.target_path(route) {
+            Ok(path) => navigate(self, path),
+            Err(error) => {
+                self.refuse(error.clone());
+                PushOutcome::Refused { error }
+            }
+        }
+    }
+
+    /// Push or replace `route` with options for this navigation, running its
+    /// guards with `cx`
+    pub(crate) fn navigate_in(
+        &mut self,
+        cx: &App,
+        route: &RouteDescriptor,
+        commit: PendingCommit,
+        options: &NavOptions,
+    ) -> PushOutcome {
+        self.navigate_to(route, |router, path| {
+            router
+                .navigate_opts(Some(cx), commit, path, options)
+                .into_outcome()
+        })
+    }
+
+    /// Generate URL for a named route
+    ///
+    /// Fails for unknown names, missing params and values their param's
+    /// constraint rejects, and with [`RouterConfig::strict_url_params`] for
+    /// params the route's path has no place for. See
+    /// [`NamedRouteRegistry::try_url_for`].
+    ///
+    /// Routes of another route table are named `table:name`, such as
+    /// `app:user.detail` (see [`GlobalRouter::define_table`]).
+    pub fn try_url_for(&self, name: &str, params: &RouteParams) -> Result<String, UrlForError> {
+        if let Some((table, name)) = name.split_once(':') {
+            if table == self.active_table {
+                return self.named_routes.try_url_for(name, params);
+            }
+            if let Some(table) = self.tables.get(table) {
+                return table.named_routes.try_url_for(name, params);
+            }
+        }
+        self.named_routes.try_url_for(name, params)
+    }
+
+    /// Generate URL for a named route
+    ///
+    /// Returns `None` where [`GlobalRouter::try_url_for`] fails.
+    #[deprecated(
+        since = "0.1.4",
+        note = "use `try_url_for`, which says why no URL was generated"
+    )]
+    pub fn url_for(&self, name: &str, params: &RouteParams) -> Option<String> {
+        self.try_url_for(name, params).ok()
+    }
+
+    /// Generate URL for a named route in `locale`
+    ///
+    /// The URL starts with the locale segment and uses the localized paths
+    /// of the route and its parents, falling back to their canonical paths.
+    /// See [`Route::localized_path`].
+    pub fn url_for_locale(&self, name: &str, params: &RouteParams, locale: &str) -> Option<String> {
+        let url = self.try_url_for(name, params).ok()?;
+        Some(localize_path(self.state.routes(), &url, locale))
+    }
+
+    /// Current locale
+    ///
+    /// `None` unless [`RouterConfig::locales`] is set.
+    pub fn locale(&self) -> Option<&str> {
+        self.state.locale()
+    }
+
+    /// Current path as written in the current locale
+    ///
+    /// E.g. `/de/einstellungen` for `/settings`; the current path itself
+    /// without locales.
+    pub fn localized_path(&self) -> String {
+        match self.locale() {
+            Some(locale) => localize_path(self.state.routes(), self.current_path(), locale),
+            None => self.current_path().to_string(),
+        }
+    }
+
+    /// Switch to `locale`, replacing the current entry
+    ///
+    /// The current route stays, with its params and query; matches get the
+    /// new `locale` param. A locale missing from [`RouterConfig::locales`] is
+    /// refused with a [`NavigationError::NavigationFailed`].
+    pub fn set_locale(&mut self, locale: &str) -> PushOutcome {
+        if !self.config.locales.iter().any(|known| known == locale) {
+            let error = NavigationError::NavigationFailed {
+                message: format!("Unknown locale '{}'", locale),
+            };
+            self.refuse(error.clone());
+            return PushOutcome::Refused { error };
+        }
+        self.state.set_locale(Some(locale.to_string()));
+        let path = self.current_path().to_string();
+        self.replace(path)
+    }
+
+    /// Resolve a relative `path` against the current route
+    ///
+    /// Paths starting with `./` or `../` are resolved, as are bare paths
+    /// when [`NavOptions::relative`] is set; see [`crate::relative`]. A bare
+    /// fragment (`#usage`) points into the current path.
+    pub fn resolve_path(&self, path: &str, options: &NavOptions) -> String {
+        if path.starts_with('#') {
+            return format!("{}{}", self.current_path(), path);
+        }
+        let bare = options.relative && !path.starts_with('/');
+        if !bare && !is_relative(path) {
+            return path.to_string();
+        }
+        let levels = route_levels(self.state.routes(), self.current_path());
+        resolve_relative(&levels, path)
+    }
+
+    /// Split the locale segment off `path` and translate localized paths
+    ///
+    /// Returns the locale named by the path, if any, and the canonical path.
+    fn delocalize(&self, path: &str) -> (Option<String>, String) {
+        if self.config.locales.is_empty() {
+            return (None, path.to_string());
+        }
+        let (locale, rest) = split_locale(path, &self.config.locales);
+        let canonical = match locale.or_else(|| self.locale()) {
+            Some(in_locale) => canonical_path(self.state.routes(), &rest, in_locale),
+            None => rest,
+        };
+        (locale.map(str::to_string), canonical)
+    }
+
+    /// Navigate to a path
+    ///
+    /// If the route has a [`LoaderMode::Blocking`] loader, history is only
+    /// updated once the loader finishes, and [`PushOutcome::Pending`] is
+    /// returned meanwhile. Navigations held back or dropped by
+    /// throttling (see [`RouterConfig::min_navigation_interval`]) return
+    /// [`PushOutcome::Throttled`] or [`PushOutcome::Dropped`] instead.
+    ///
+    /// Query params listed in [`RouterConfig::preserve_query_keys`] are
+    /// carried over from the current location. Paths starting with `./` or
+    /// `../` resolve against the current route (see
+    /// [`GlobalRouter::resolve_path`]).
+    ///
+    /// Under [`RouterConfig::strict_navigation`] a path matching no route is
+    /// refused: nothing changes and [`PushOutcome::Refused`] is returned.
+    ///
+    /// Without an `App` to run them with, the guards of the target are asked
+    /// on the next tick and the navigation waits for them, like one waiting
+    /// for its loader; [`Navigator::push`] asks them right away.
+    pub fn push(&mut self, path: String) -> PushOutcome {
+        self.push_opts(path, &NavOptions::default())
+    }
+
+    /// Navigate to a path, reporting whether it matches a route
+    ///
+    /// The path is matched before anything is committed. The guards of the
+    /// matching routes run first: a denial leaves the current path as it is
+    /// and returns `Blocked`, a redirect pushes the redirect target instead
+    /// and returns `Redirected`. Guards that have not resolved yet hold the
+    /// navigation until they do and return `Pending`. A path matching no route
+    /// returns `NotFound`; it is still pushed unless
+    /// [`RouterConfig::strict_navigation`] is set.
+    pub fn try_push(&mut self, cx: &App, path: String) -> NavigationResult {
+        self.try_navigate(cx, path, PendingCommit::Push)
+    }
+
+    /// Start at `path`, replacing the first history entry
+    ///
+    /// Runs like [`GlobalRouter::try_push`], but the path (or the target of
+    /// a guard redirect) replaces the current entry instead of adding one,
+    /// so there is nothing to go back to. Called by [`init_router`] for
+    /// [`RouterConfig::initial_path`] and by [`init_router_with_initial`].
+    pub fn start_at(&mut self, cx: &App, path: String) -> NavigationResult {
+        self.try_navigate(cx, path, PendingCommit::Replace)
+    }
+
+    /// Push or replace `path` with its guards, reporting how it went
+    fn try_navigate(&mut self, cx: &App, path: String, commit: PendingCommit) -> NavigationResult {
+        let source = self.state.source().clone();
+        if self.defer_in_render(&path, || RenderNavigation::Try {
+            path: path.clone(),
+            commit,
+            source,
+        }) {
+            return NavigationResult::Error(NavigationError::NavigationFailed {
+                message: format!("Navigation to '{}' was made while rendering", path),
+            });
+        }
+        let path = self.resolve_path(&path, &NavOptions::default());
+        let (_, canonical) = self.delocalize(split_fragment(&path).0);
+        let matched = match_route_chain(self.state.routes(), &canonical).is_some();
+        let guarded = self.navigate_opts(Some(cx), commit, path.clone(), &NavOptions::default());
+        if matched {
+            guarded.into_result(path)
+        } else {
+            NavigationResult::NotFound { path }
+        }
+    }
+
+    /// Navigate to a path with options for this navigation
+    pub fn push_opts(&mut self, path: String, options: &NavOptions) -> PushOutcome {
+        self.navigate_opts(None, PendingCommit::Push, path, options)
+            .into_outcome()
+    }
+
+    /// Replace current path
+    ///
+    /// Blocking loaders and throttling delay the replacement like
+    /// [`GlobalRouter::push`], and query params are preserved and unknown
+    /// paths refused the same way.
+    pub fn replace(&mut self, path: String) -> PushOutcome {
+        self.replace_opts(path, &NavOptions::default())
+    }
+
+    /// Replace current path with options for this navigation
+    pub fn replace_opts(&mut self, path: String, options: &NavOptions) -> PushOutcome {
+        self.navigate_opts(None, PendingCommit::Replace, path, options)
+            .into_outcome()
+    }
+
+    /// Push or replace `path` with options for this navigation
+    ///
+    /// The guards of the target run with `cx`; without it they run on the
+    /// next tick, and the navigation waits for them.
+    fn navigate_opts(
+        &mut self,
+        cx: Option<&App>,
+        commit: PendingCommit,
+        path: String,
+        options: &NavOptions,
+    ) -> Guarded {
+        if let Some(source) = options.source.clone() {
+            let options = NavOptions {
+                source: None,
+                ..options.clone()
+            };
+            return self.with_source(source, |router| {
+                router.navigate_opts(cx, commit, path, &options)
+            });
+        }
+        let direction = commit.direction();
+        let source = self.state.source().clone();
+        if self.defer_in_render(&path, || RenderNavigation::Commit {
+            path: path.clone(),
+            options: options.clone().source(source),
+            commit,
+        }) {
+            return Guarded::Through(PushOutcome::Navigated(self.unchanged_event(direction)));
+        }
+        // A newer navigation supersedes the one waiting for its guards
+        #[cfg(feature = "guard")]
+        {
+            self.guarded = None;
+            self.deferred = None;
+        }
+        #[cfg(feature = "middleware")]
+        {
+            self.pipeline_started = Some(self.now());
+        }
+        let path = self.resolve_path(&path, options);
+        let (path, fragment) = split_fragment(&path);
+        let fragment = fragment.map(str::to_string);
+        let (locale, path) = self.delocalize(path);
+        let path = self.preserve_query(path, options);
+        let span = diagnostics::navigate(self.current_path(), Some(&path), direction);
+        if let Some(error) = self.refuse_unknown(&path) {
+            span.outcome(Outcome::Refused);
+            return Guarded::Refused { error };
+        }
+        if let Some(event) =
+            self.change_fragment(locale.as_deref(), &path, fragment.as_deref(), commit)
+        {
+            return Guarded::Through(PushOutcome::Navigated(event));
+        }
+        self.pending_fragment = fragment;
+        #[cfg(feature = "middleware")]
+        self.before_navigation(cx, &path, direction);
+        #[cfg(feature = "guard")]
+        let guarded = self.check_guards(cx, locale.as_deref(), &path, commit, &span);
+        #[cfg(not(feature = "guard"))]
+        let guarded = {
+            let _ = cx;
+            None
+        };
+        let guarded = guarded
+            .unwrap_or_else(|| Guarded::Through(self.commit_checked(locale, path, commit, &span)));
+        // Refused, or held back to start later with a token of its own
+        if let Some(token) = self.checked_token.take() {
+            token.cancel();
+        }
+        guarded
+    }
+
+    /// Run the `before_navigation` middleware of a navigation to `path`
+    ///
+    /// Runs ahead of the guards, so it also sees navigations they refuse.
+    /// Without `cx` the calls wait for [`run_navigation_middleware`], which
+    /// the router runs once the current update is done.
+    ///
+    /// [`run_navigation_middleware`]: crate::middleware::run_navigation_middleware
+    #[cfg(feature = "middleware")]
+    fn before_navigation(&mut self, cx: Option<&App>, path: &str, direction: NavigationDirection) {
+        let middleware = NavigationMiddleware::new(self, path);
+        if middleware.is_empty() {
+            return;
+        }
+        let params = match_route_chain(self.state.routes(), path)
+            .map(|(_, route_match)| RouteParams::from_map(route_match.params))
+            .unwrap_or_default();
+        let request = NavigationRequest::with_from(path.to_string(), self.current_path().into())
+            .with_params(params)
+            .with_direction(direction)
+            .with_source(self.state.source().clone());
+        let Some(cx) = cx else {
+            self.navigating.push(request);
+            return;
+        };
+        let timeout = self.navigation_timeout(path);
+        let _span = diagnostics::middleware(path, "before");
+        for m in middleware.by_priority() {
+            let future = m.before_navigation(cx, &request);
+            spawn_middleware(cx, future, "before", path, timeout);
+        }
+    }
+
+    /// Hold a push or replace of `path` if it leaves a dirty route, or else
+    /// apply it
+    fn commit_checked(
+        &mut self,
+        locale: Option<String>,
+        path: String,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> PushOutcome {
+        #[cfg(feature = "guard")]
+        if let Some(outcome) = self.hold_commit(locale.as_deref(), &path, commit, span) {
+            return outcome;
+        }
+        self.proceed(locale, path, commit, span)
+    }
+
+    /// Whether a navigation to `path` has guards to ask
+    #[cfg(feature = "guard")]
+    fn has_guards(&self, path: &str) -> bool {
+        !self.transition_guards.is_empty()
+            || match_route_chain(self.state.routes(), path).is_some_and(|(chain, _)| {
+                self.filtered_guards(path, &chain).next().is_some()
+                    || chain.iter().any(|route| !route.guards.is_empty())
+            })
+    }
+
+    /// Ask the transition guards, then the guards of `path`, about a
+    /// navigation there from the current path
+    #[cfg(feature = "guard")]
+    fn check_navigation(&self, cx: &App, path: &str) -> Result<GuardVerdict, VerdictFuture> {
+        let chain = match_route_chain(self.state.routes(), path).map(|(chain, _)| chain);
+        let request = self.guard_request(self.current_path(), path, self.state.routes());
+        let mut guards: Vec<_> = self
+            .transition_guards
+            .iter()
+            .map(|guard| &**guard)
+            .collect();
+        sort_by_priority(&mut guards);
+        if let Some(chain) = &chain {
+            let mut route_guards: Vec<_> = self
+                .filtered_guards(path, chain)
+                .chain(chain.iter().flat_map(|route| route.guards.iter()))
+                .collect();
+            sort_by_priority(&mut route_guards);
+            guards.extend(route_guards);
+        }
+        let _span = diagnostics::guards(path);
+        check_guards(&guards, cx, &request)
+    }
+
+    /// Run the guards of a push or replace of `path`
+    ///
+    /// Returns `None` if they let it through right away. A denial refuses
+    /// the navigation and a redirect navigates to the target instead.
+    /// Guards that have not resolved, or all of them without `cx`, hold the
+    /// navigation until they do.
+    #[cfg(feature = "guard")]
+    fn check_guards(
+        &mut self,
+        cx: Option<&App>,
+        locale: Option<&str>,
+        path: &str,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> Option<Guarded> {
+        if self.chaining || !self.has_guards(path) {
+            return None;
+        }
+        // Guards see the token the navigation keeps once it starts
+        self.checked_token.get_or_insert_with(NavigationToken::new);
+        let Some(cx) = cx else {
+            return Some(self.hold_for_guards(None, locale, path, commit, span));
+        };
+        match self.check_navigation(cx, path) {
+            Ok(verdict) => self.apply_verdict(Some(cx), path, verdict, commit, span),
+            Err(future) => {
+                Some(self.hold_for_guards(Some((cx, future)), locale, path, commit, span))
+            }
+        }
+    }
+
+    /// Apply what the guards of a push or replace of `path` decided
+    ///
+    /// Returns `None` if they allowed it.
+    #[cfg(feature = "guard")]
+    fn apply_verdict(
+        &mut self,
+        cx: Option<&App>,
+        path: &str,
+        verdict: GuardVerdict,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> Option<Guarded> {
+        match verdict {
+            GuardVerdict::Allow => None,
+            GuardVerdict::Deny(reason) => {
+                Some(self.refuse_guarded(NavigationError::GuardBlocked { reason }, span))
+            }
+            GuardVerdict::Redirect { to, guard, .. } => {
+                Some(self.follow_redirect(cx, path, to, guard, commit, span))
+            }
+        }
+    }
+
+    /// Refuse a push or replace its guards stopped
+    #[cfg(feature = "guard")]
+    fn refuse_guarded(&mut self, error: NavigationError, span: &diagnostics::Entered) -> Guarded {
+        self.refuse(error.clone());
+        span.outcome(Outcome::Refused);
+        Guarded::Refused { error }
+    }
+
+    /// Push or replace `to` instead of `path`, which a guard redirected
+    ///
+    /// The guards of `to` run too. A navigation redirected more than
+    /// [`MAX_REDIRECTS`] times is refused as a redirect loop.
+    #[cfg(feature = "guard")]
+    fn follow_redirect(
+        &mut self,
+        cx: Option<&App>,
+        path: &str,
+        to: String,
+        guard: String,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> Guarded {
+        if self.redirects >= MAX_REDIRECTS {
+            diagnostics::redirect_loop(path);
+            let error = NavigationError::NavigationFailed {
+                message: format!("Too many redirects navigating to '{}'", path),
+            };
+            return self.refuse_guarded(error, span);
+        }
+        self.redirects += 1;
+        // The entry committed for the target is marked as a redirect
+        self.redirected_from = Some(path.to_string());
+        let source = NavigationSource::Redirect { from_guard: guard };
+        let guarded = self.with_source(source, |router| {
+            router.navigate_opts(cx, commit, to.clone(), &NavOptions::default())
+        });
+        self.redirected_from = None;
+        self.redirects -= 1;
+        match guarded {
+            Guarded::Through(outcome) => Guarded::Redirected { to, outcome },
+            other => other,
+        }
+    }
+
+    /// Hold a push or replace of `path` until its guards resolve
+    ///
+    /// `pending` finishes a check started with the app, unless the
+    /// navigation timeout passes first. Without it, the guards are asked on
+    /// the next tick through the app the router was installed in; a router
+    /// that [`init_router`] did not install refuses the navigation instead,
+    /// since its guards cannot run.
+    #[cfg(feature = "guard")]
+    fn hold_for_guards(
+        &mut self,
+        pending: Option<(&App, VerdictFuture)>,
+        locale: Option<&str>,
+        path: &str,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> Guarded {
+        self.guard_checks += 1;
+        let id = self.guard_checks;
+        let since = self.guards_since.unwrap_or_else(|| self.now());
+        let task = match pending {
+            Some((cx, future)) => {
+                // Measured from when the guards were first asked
+                let timer = self.navigation_timeout(path).map(|timeout| {
+                    let waited = self.now().saturating_duration_since(since);
+                    cx.background_executor()
+                        .timer(timeout.saturating_sub(waited))
+                });
+                Some(cx.spawn(async move |cx| {
+                    // `None` once the deadline passed; dropping the future
+                    // cancels the check
+                    let verdict = match timer {
+                        Some(timer) => match select(future, timer).await {
+                            Either::Left((verdict, _)) => Some(verdict),
+                            Either::Right(_) => None,
+                        },
+                        None => Some(future.await),
+                    };
+                    let _ = cx.update(|cx| match verdict {
+                        Some(verdict) => resume_guarded(cx, id, Some(verdict)),
+                        None => time_out_guarded(cx, id),
+                    });
+                }))
+            }
+            None => self.app.as_ref().map(|app| {
+                app.spawn(async move |cx| {
+                    let _ = cx.update(|cx| resume_guarded(cx, id, None));
+                })
+            }),
+        };
+        let Some(task) = task else {
+            let error = NavigationError::GuardBlocked {
+                reason: format!("Guards of '{}' cannot run without an app", path),
+            };
+            return self.refuse_guarded(error, span);
+        };
+
+        debug_log!("Holding navigation to '{}' until its guards resolve", path);
+        span.outcome(Outcome::Pending);
+        self.guarded = Some(GuardedNavigation {
+            id,
+            locale: locale.map(str::to_string),
+            path: path.to_string(),
+            commit,
+            source: self.state.source().clone(),
+            fragment: self.pending_fragment.take(),
+            token: self.checked_token.take(),
+            redirected_from: self.redirected_from.clone(),
+            redirects: self.redirects,
+            since,
+            #[cfg(feature = "middleware")]
+            started: self.pipeline_started,
+            _task: Arc::new(task),
+        });
+        Guarded::Held {
+            path: path.to_string(),
+        }
+    }
+
+    /// Go on with the navigation held as guard check `id`, with the verdict
+    /// of its guards, or asking them now if they were not asked yet
+    ///
+    /// Does nothing if a newer navigation superseded it.
+    #[cfg(feature = "guard")]
+    fn resume_guarded(&mut self, cx: &App, id: u64, verdict: Option<GuardVerdict>) {
+        if self.guarded.as_ref().map_or(true, |held| held.id != id) {
+            debug_log!("Dropping the guard check of a superseded navigation");
+            return;
+        }
+        let Some(held) = self.guarded.take() else {
+            return;
+        };
+        self.pending_fragment = held.fragment;
+        self.checked_token = held.token;
+        self.redirected_from = held.redirected_from;
+        self.redirects = held.redirects;
+        self.guards_since = Some(held.since);
+        #[cfg(feature = "middleware")]
+        {
+            self.pipeline_started = held.started;
+        }
+        let (locale, path, commit) = (held.locale, held.path, held.commit);
+        let span = diagnostics::navigate(self.current_path(), Some(&path), commit.direction());
+        self.with_source(held.source, |router| {
+            let guarded = match verdict.map_or_else(|| router.check_navigation(cx, &path), Ok) {
+                Ok(verdict) => router.apply_verdict(Some(cx), &path, verdict, commit, &span),
+                Err(future) => Some(router.hold_for_guards(
+                    Some((cx, future)),
+                    locale.as_deref(),
+                    &path,
+                    commit,
+                    &span,
+                )),
+            };
+            if guarded.is_none() {
+                router.commit_checked(locale, path, commit, &span);
+            }
+        });
+        self.redirected_from = None;
+        self.redirects = 0;
+        self.guards_since = None;
+        if let Some(token) = self.checked_token.take() {
+            token.cancel();
+        }
+    }
+
+    /// Abort the navigation held as guard check `id`, whose guards were
+    /// still pending at the navigation timeout
+    ///
+    /// The current route stays, whatever [`RouterConfig::on_navigation_timeout`]
+    /// says, since the guards never let the navigation through. Does nothing
+    /// if a newer navigation superseded it.
+    #[cfg(feature = "guard")]
+    fn time_out_guarded(&mut self, id: u64) {
+        if self.guarded.as_ref().map_or(true, |held| held.id != id) {
+            return;
+        }
+        let Some(held) = self.guarded.take() else {
+            return;
+        };
+        let elapsed = self.now().saturating_duration_since(held.since);
+        debug_log!(
+            "Guards of '{}' did not resolve within {:?}",
+            held.path,
+            elapsed
+        );
+        if let Some(token) = held.token {
+            token.cancel();
+        }
+        self.refuse(NavigationError::Timeout {
+            path: held.path,
+            elapsed,
+        });
+    }
+
+    /// Switch to `locale`, then push or replace `path` unless throttled
+    fn proceed(
+        &mut self,
+        locale: Option<String>,
+        path: String,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> PushOutcome {
+        if locale.is_some() {
+            self.state.set_locale(locale);
+        }
+        if let Some(outcome) = self.throttle(&path, commit) {
+            span.outcome(match outcome {
+                PushOutcome::Dropped { .. } => Outcome::Dropped,
+                _ => Outcome::Throttled,
+            });
+            return outcome;
+        }
+        self.navigate_now(path, commit, span)
+    }
+
+    /// Refuse `path` if it matches no route and navigation is strict
+    ///
+    /// Clears the last error otherwise, since the navigation goes through.
+    fn refuse_unknown(&mut self, path: &str) -> Option<NavigationError> {
+        if self.config.strict_navigation && match_route_chain(self.state.routes(), path).is_none() {
+            let error = NavigationError::RouteNotFound {
+                path: path.to_string(),
+                details: Some(Box::new(NotFoundDetails::new(self.state.routes(), path))),
+            };
+            self.refuse(error.clone());
+            return Some(error);
+        }
+        self.last_error = None;
+        None
+    }
+
+    /// Move to `fragment` of the current path, if `path` is the current path
+    ///
+    /// Only history changes: guards, loaders, throttling, middleware and
+    /// transitions are skipped, so the route stays mounted.
+    fn change_fragment(
+        &mut self,
+        locale: Option<&str>,
+        path: &str,
+        fragment: Option<&str>,
+        commit: PendingCommit,
+    ) -> Option<RouteChangeEvent> {
+        let current = self.current_fragment();
+        if path != self.current_path()
+            || locale.is_some_and(|locale| self.locale() != Some(locale))
+            || (fragment.is_none() && current.is_none())
+        {
+            return None;
+        }
+        debug_log!("Moving to fragment {:?} of '{}'", fragment, path);
+        // Moving to the fragment already shown adds no entry
+        let same = fragment == current;
+        let location = match fragment {
+            Some(fragment) => format!("{}#{}", path, fragment),
+            None => path.to_string(),
+        };
+        let event = match commit {
+            PendingCommit::Push if !same => self.state.push(location),
+            _ => self.state.replace(location),
+        };
+        self.batched(&event);
+        Some(event)
+    }
+
+    /// Describe a navigation that stays on the current path
+    fn unchanged_event(&self, direction: NavigationDirection) -> RouteChangeEvent {
+        let current = self.current_path().to_string();
+        self.state.describe_change(RouteChangeEvent {
+            fragment: self.current_fragment().map(str::to_string),
+            ..RouteChangeEvent::new(Some(current.clone()), current, direction)
+        })
+    }
+
+    /// Add the query params kept from the current location to `path`
+    fn preserve_query(&self, path: String, options: &NavOptions) -> String {
+        let policy = options
+            .preserve_query
+            .as_ref()
+            .unwrap_or(&self.config.preserve_query);
+        policy.apply(self.current_path(), &path)
+    }
+
+    /// Push or replace, unless a blocking loader has to run first
+    fn navigate_now(
+        &mut self,
+        path: String,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> PushOutcome {
+        self.begin_navigation();
+        if self.schedule_loader(&path, Some(commit)) {
+            span.outcome(Outcome::Pending);
+            return PushOutcome::Pending { path };
+        }
+        span.matched(self.state.routes(), &path);
+        PushOutcome::Navigated(match commit {
+            PendingCommit::Push => self.commit_push(path),
+            PendingCommit::Replace => self.commit_replace(path),
+        })
+    }
+
+    /// Start a navigation, cancelling the one still in flight
+    fn begin_navigation(&mut self) {
+        // It supersedes a navigation waiting for its guards
+        #[cfg(feature = "guard")]
+        {
+            self.guarded = None;
+            self.deferred = None;
+        }
+        if let Some(superseded) = self.in_flight_token.take() {
+            superseded.cancel();
+        }
+        self.in_flight_token = Some(self.checked_token.take().unwrap_or_default());
+    }
+
+    /// Make the navigation in flight the current one, cancelling the token
+    /// of the page it leaves
+    fn commit_navigation(&mut self) {
+        let token = self.in_flight_token.take().unwrap_or_default();
+        std::mem::replace(&mut self.navigation_token, token).cancel();
+        // Heroes on the page left fly from where they are now
+        #[cfg(feature = "transition")]
+        self.heroes.depart();
+    }
+
+    /// Cancel the navigation in flight and the current one
+    ///
+    /// Called when the app quits.
+    pub(crate) fn cancel_navigations(&mut self) {
+        #[cfg(feature = "guard")]
+        {
+            self.guarded = None;
+            self.deferred = None;
+        }
+        if let Some(token) = self.in_flight_token.take() {
+            token.cancel();
+        }
+        self.navigation_token.cancel();
+    }
+
+    /// Token of the navigation that committed last
+    ///
+    /// Cancelled once a newer navigation commits, leaving its page, or when
+    /// the app quits. See [`crate::cancel`].
+    pub fn navigation_token(&self) -> &NavigationToken {
+        &self.navigation_token
+    }
+
+    /// Describe a navigation that has not been committed yet
+    #[cfg(feature = "guard")]
+    fn pending_event(&self, to: String, commit: PendingCommit) -> RouteChangeEvent {
+        self.state.describe_change(RouteChangeEvent {
+            fragment: self.pending_fragment.clone(),
+            ..RouteChangeEvent::new(
+                Some(self.current_path().to_string()),
+                to,
+                commit.direction(),
+            )
+        })
+    }
+
+    fn commit_push(&mut self, path: String) -> RouteChangeEvent {
+        let event = self
+            .state
+            .push(with_fragment(path, self.pending_fragment.take()));
+        self.commit_navigation();
+        if let Some(from) = &self.redirected_from {
+            self.state.mark_redirect(from.clone());
+        }
+        #[cfg(feature = "transition")]
+        {
+            let transition = self.take_next_transition();
+            self.state.set_navigation_transition(transition);
+        }
+        self.batched(&event);
+        #[cfg(feature = "cache")]
+        self.invalidate_navigation(&event);
+        #[cfg(feature = "middleware")]
+        self.record_navigation(&event);
+        event
+    }
+
+    fn commit_replace(&mut self, path: String) -> RouteChangeEvent {
+        let event = self
+            .state
+            .replace(with_fragment(path, self.pending_fragment.take()));
+        self.commit_navigation();
+        if let Some(from) = &self.redirected_from {
+            self.state.mark_redirect(from.clone());
+        }
+        #[cfg(feature = "transition")]
+        {
+            let transition = self.take_next_transition();
+            self.state.set_navigation_transition(transition);
+        }
+        self.batched(&event);
+        #[cfg(feature = "cache")]
+        self.invalidate_navigation(&event);
+        #[cfg(feature = "middleware")]
+        self.record_navigation(&event);
+        event
+    }
+
+    /// Go back
+    ///
+    /// Loaders of the route navigated to run deferred. Returns `None` if
+    /// there is no previous entry, or if the navigation was dropped because
+    /// [`RouterConfig::throttle_history`] is set.
+    pub fn back(&mut self) -> Option<RouteChangeEvent> {
+        let previous = self.state.history().current_index().checked_sub(1);
+        #[cfg(feature = "guard")]
+        if previous.is_some_and(|index| self.travel_next_tick(index)) {
+            return None;
+        }
+        self.back_to(previous)
+    }
+
+    /// Go back, skipping entries there is no point in returning to
+    ///
+    /// Skips entries committed by a guard redirect (see
+    /// [`EntryKind::Redirect`]) and entries whose guards now deny or redirect,
+    /// or are not synchronous, so going back from a page reached after logging in does
+    /// not land on the login page again. Stays put and returns `None` if no
+    /// earlier entry is left. Throttled like [`GlobalRouter::back`].
+    pub fn smart_back(&mut self, cx: &App) -> Option<RouteChangeEvent> {
+        match self.smart_back_target(cx) {
+            Some(target) => self.travel_to(cx, target),
+            None => self.back_to(None),
+        }
+    }
+
+    /// History index [`GlobalRouter::smart_back`] goes back to
+    pub(crate) fn smart_back_target(&self, cx: &App) -> Option<usize> {
+        let history = self.state.history();
+        history.entries()[..history.current_index()]
+            .iter()
+            .rposition(|entry| !entry.kind.is_redirect() && self.can_return_to(cx, &entry.path))
+    }
+
+    /// Check whether the guards on a history entry's path let it be shown
+    #[cfg_attr(not(feature = "guard"), allow(clippy::unused_self))]
+    fn can_return_to(&self, cx: &App, path: &str) -> bool {
+        #[cfg(feature = "guard")]
+        {
+            let (_, canonical) = self.delocalize(path);
+            matches!(
+                self.poll_guards(
+                    cx,
+                    self.current_path(),
+                    &canonical,
+                    NavigationSource::History
+                ),
+                GuardPoll::Passed { unknown: false }
+            )
+        }
+        #[cfg(not(feature = "guard"))]
+        {
+            let _ = (cx, path);
+            true
+        }
+    }
+
+    /// Go back to the history entry at `index`, if there is one
+    fn back_to(&mut self, index: Option<usize>) -> Option<RouteChangeEvent> {
+        if self.defer_in_render("back", || RenderNavigation::Back(index)) {
+            return None;
+        }
+        let span = diagnostics::navigate(self.current_path(), None, NavigationDirection::Back);
+        #[cfg(feature = "guard")]
+        if self.hold_travel(index, HeldNavigation::Back(index), &span) {
+            return None;
+        }
+        if self.throttle_history() {
+            span.outcome(Outcome::Dropped);
+            return None;
+        }
+        let event = index.and_then(|index| self.state.back_to(index));
+        if let Some(event) = &event {
+            span.record("to", &event.to);
+            span.matched(self.state.routes(), &event.to);
+            self.last_error = None;
+            self.batched(event);
+            self.navigated(self.now());
+            // The route stays mounted when only the fragment changes
+            if !event.fragment_only {
+                self.begin_navigation();
+                self.commit_navigation();
+                #[cfg(feature = "cache")]
+                self.invalidate_navigation(event);
+                #[cfg(feature = "middleware")]
+                self.record_navigation(event);
+                self.schedule_loader(&event.to, None);
+            }
+        } else {
+            span.outcome(Outcome::NoHistory);
+        }
+        event
+    }
+
+    /// Go forward
+    ///
+    /// Loaders of the route navigated to run deferred. Throttled like
+    /// [`GlobalRouter::back`].
+    pub fn forward(&mut self) -> Option<RouteChangeEvent> {
+        let next = self.state.history().current_index() + 1;
+        #[cfg(feature = "guard")]
+        if self.travel_next_tick(next) {
+            return None;
+        }
+        self.forward_to(next)
+    }
+
+    /// Go to the history entry at `index`, back or forward
+    ///
+    /// Entries in between are skipped, so the middleware and listeners see a
+    /// single navigation. Returns `None` if `index` is the current entry or
+    /// out of range; otherwise behaves like [`GlobalRouter::back`] and
+    /// [`GlobalRouter::forward`], throttling and leave confirmation included.
+    pub fn go_to_index(&mut self, index: usize) -> Option<RouteChangeEvent> {
+        #[cfg(feature = "guard")]
+        if self.travel_next_tick(index) {
+            return None;
+        }
+        self.go_to(index)
+    }
+
+    /// Go to the history entry at `index` without asking the transition
+    /// guards
+    fn go_to(&mut self, index: usize) -> Option<RouteChangeEvent> {
+        let current = self.state.history().current_index();
+        if index < current {
+            self.back_to(Some(index))
+        } else if index > current && index < self.state.history().len() {
+            self.forward_to(index)
+        } else {
+            None
+        }
+    }
+
+    /// Go to the history entry at `index`, checking the transition guards
+    ///
+    /// Like [`GlobalRouter::go_to_index`], with the guards added by
+    /// [`GlobalRouter::add_transition_guard`] asked first: a denial keeps the
+    /// current entry and is recorded as a refused navigation, a redirect
+    /// pushes its target instead.
+    pub fn travel_to(&mut self, cx: &App, index: usize) -> Option<RouteChangeEvent> {
+        #[cfg(feature = "guard")]
+        if let Some(entry) = self.state.history().entries().get(index) {
+            let (_, target) = self.delocalize(&entry.path);
+            let request = self
+                .guard_request(self.current_path(), &target, self.state.routes())
+                .with_source(NavigationSource::History);
+            match self.poll_transition(cx, &request).settled(&target) {
+                GuardPoll::Passed { .. } => {}
+                GuardPoll::Denied(reason) => {
+                    self.refuse(NavigationError::GuardBlocked { reason });
+                    return None;
+                }
+                GuardPoll::Redirected { to, guard, .. } => {
+                    // The entry committed for the target is marked as a redirect
+                    self.redirected_from = Some(target);
+                    let source = NavigationSource::Redirect { from_guard: guard };
+                    let event = self.with_source(source, |router| {
+                        router
+                            .navigate_opts(
+                                Some(cx),
+                                PendingCommit::Push,
+                                to,
+                                &NavOptions::default(),
+                            )
+                            .into_outcome()
+                            .into_event()
+                    });
+                    self.redirected_from = None;
+                    return event;
+                }
+            }
+        }
+        #[cfg(not(feature = "guard"))]
+        let _ = cx;
+        self.go_to(index)
+    }
+
+    /// Go to the history entry at `index` with [`GlobalRouter::travel_to`]
+    /// on the next tick, if there are transition guards to ask
+    ///
+    /// Returns whether the travel was put off, or refused for a router
+    /// [`init_router`] did not install.
+    #[cfg(feature = "guard")]
+    fn travel_next_tick(&mut self, index: usize) -> bool {
+        if self.transition_guards.is_empty() || self.chaining {
+            return false;
+        }
+        let Some(entry) = self.state.history().entries().get(index) else {
+            return false;
+        };
+        let path = entry.path.clone();
+        self.next_tick(&path, move |router, cx| {
+            router.travel_to(cx, index);
+        });
+        true
+    }
+
+    /// Run `navigate` with the app on the next tick, for a navigation to
+    /// `path` made without it whose guards have to be asked
+    ///
+    /// A newer navigation supersedes it. A router [`init_router`] did not
+    /// install refuses the navigation instead, since its guards cannot run.
+    #[cfg(feature = "guard")]
+    fn next_tick(&mut self, path: &str, navigate: impl FnOnce(&mut Self, &App) + 'static) {
+        let Some(app) = &self.app else {
+            self.refuse(NavigationError::GuardBlocked {
+                reason: format!("Guards of '{}' cannot run without an app", path),
+            });
+            return;
+        };
+        debug_log!("Asking the guards of '{}' on the next tick", path);
+        let task = app.spawn(async move |cx| {
+            let _ = cx.update(|cx| {
+                if !cx.has_global::<GlobalRouter>() {
+                    return;
+                }
+                cx.update_global::<GlobalRouter, _>(|router, cx| {
+                    router.deferred = None;
+                    navigate(router, cx);
+                });
+                crate::error::report_refused_navigations(cx);
+                refresh_windows(cx);
+            });
+        });
+        self.guarded = None;
+        self.deferred = Some(Arc::new(task));
+    }
+
+    /// Go forward to the history entry at `index`, if there is one
+    fn forward_to(&mut self, index: usize) -> Option<RouteChangeEvent> {
+        if self.defer_in_render("forward", || RenderNavigation::Forward(index)) {
+            return None;
+        }
+        let span = diagnostics::navigate(self.current_path(), None, NavigationDirection::Forward);
+        #[cfg(feature = "guard")]
+        if self.hold_travel(Some(index), HeldNavigation::Forward(index), &span) {
+            return None;
+        }
+        if self.throttle_history() {
+            span.outcome(Outcome::Dropped);
+            return None;
+        }
+        let event = self.state.forward_to(index);
+        if let Some(event) = &event {
+            span.record("to", &event.to);
+            span.matched(self.state.routes(), &event.to);
+            self.last_error = None;
+            self.batched(event);
+            self.navigated(self.now());
+            // The route stays mounted when only the fragment changes
+            if !event.fragment_only {
+                self.begin_navigation();
+                self.commit_navigation();
+                #[cfg(feature = "cache")]
+                self.invalidate_navigation(event);
+                #[cfg(feature = "middleware")]
+                self.record_navigation(event);
+                self.schedule_loader(&event.to, None);
+            }
+        } else {
+            span.outcome(Outcome::NoHistory);
+        }
+        event
+    }
+
+    /// Queue a navigation to `target` made while an outlet builds a route
+    ///
+    /// Returns whether it was queued rather than left to apply now, see
+    /// [`RouterConfig::on_render_navigation`].
+    fn defer_in_render(
+        &mut self,
+        target: &str,
+        navigation: impl FnOnce() -> RenderNavigation,
+    ) -> bool {
+        if !self.state.in_render() {
+            return false;
+        }
+        match self.config.render_navigation {
+            RenderNavigationPolicy::Allow => return false,
+            RenderNavigationPolicy::Defer => {
+                warn_log!(
+                    "Navigation to '{}' while rendering '{}' is applied after the render; \
+                     navigate from a guard or lifecycle hook instead",
+                    target,
+                    self.current_path()
+                );
+            }
+            RenderNavigationPolicy::Panic => {}
+        }
+        self.render_navigations
+            .push((target.to_string(), navigation()));
+        true
+    }
+
+    /// Apply a navigation made while an outlet was building a route
+    fn apply_render_navigation(&mut self, cx: &App, navigation: RenderNavigation) {
+        match navigation {
+            RenderNavigation::Commit {
+                path,
+                options,
+                commit,
+            } => {
+                self.navigate_opts(Some(cx), commit, path, &options);
+            }
+            RenderNavigation::Try {
+                path,
+                commit,
+                source,
+            } => {
+                self.with_source(source, |router| router.try_navigate(cx, path, commit));
+            }
+            RenderNavigation::Back(index) => {
+                self.back_to(index);
+            }
+            RenderNavigation::Forward(index) => {
+                self.forward_to(index);
+            }
+            RenderNavigation::Chain(steps) => {
+                self.apply_chain(cx, steps);
+            }
+        }
+    }
+
+    /// Start a batch of navigations
+    ///
+    /// Until the matching [`GlobalRouter::end_batch`], navigations are
+    /// applied without transitions or throttling, and the global middleware
+    /// is not told about them one by one: ending the batch reports a single
+    /// navigation from where the batch started to where it ended. Use it for
+    /// navigations made in a row, like restoring a session and then applying
+    /// a deep link. Batches nest; only the outermost one reports.
+    ///
+    /// History keeps every entry pushed in the batch unless
+    /// [`RouterConfig::batch_collapse_history`] is set.
+    pub fn begin_batch(&mut self) {
+        self.start_batch(false);
+    }
+
+    /// Start a batch, or nest one in the running batch
+    fn start_batch(&mut self, animate: bool) {
+        if let Some(batch) = &mut self.batch {
+            batch.depth += 1;
+            return;
+        }
+        self.batch = Some(NavigationBatch {
+            depth: 1,
+            animate,
+            start: self.state.history().current_index(),
+            first: None,
+            #[cfg(feature = "middleware")]
+            navigated: self.navigated.len(),
+        });
+    }
+
+    /// End a batch started with [`GlobalRouter::begin_batch`]
+    ///
+    /// Returns the navigation the outermost batch amounts to, from the path
+    /// before its first navigation to the current one, or `None` if it
+    /// committed nothing (and for nested batches).
+    pub fn end_batch(&mut self) -> Option<RouteChangeEvent> {
+        let batch = self.batch.as_mut()?;
+        batch.depth -= 1;
+        if batch.depth > 0 {
+            return None;
+        }
+        let batch = self.batch.take()?;
+        let first = batch.first?;
+
+        if self.config.batch_collapse_history {
+            self.state.collapse_history_since(batch.start);
+        }
+        let current = self.state.history().current_index();
+        let direction = match current.cmp(&batch.start) {
+            std::cmp::Ordering::Greater => NavigationDirection::Forward,
+            std::cmp::Ordering::Less => NavigationDirection::Back,
+            std::cmp::Ordering::Equal => NavigationDirection::Replace,
+        };
+        let event = self.state.describe_change(RouteChangeEvent {
+            fragment: self.current_fragment().map(str::to_string),
+            previous_dwell: first.previous_dwell,
+            ..RouteChangeEvent::new(first.from, self.current_path(), direction)
+        });
+        #[cfg(feature = "middleware")]
+        self.report_batch(batch.navigated, &event);
+        Some(event)
+    }
+
+    /// Whether a batch of navigations is being applied
+    pub fn in_batch(&self) -> bool {
+        self.batch.is_some()
+    }
+
+    /// Run `update` with the router's refreshes and queued effects held back
+    ///
+    /// Windows the router refreshes while `update` runs (after extending
+    /// the routes, retrying a route, applying an action...) and effects
+    /// queued with [`GlobalRouter::queue_effect`] are applied once it
+    /// returns, in a single deferred step followed by one refresh. Wrap
+    /// updates that navigate and change app state in several steps, so
+    /// views observing the router and that state render once. Calls nest;
+    /// the outermost one flushes.
+    ///
+    /// This holds back effects only: navigations inside still commit one
+    /// by one, with their transitions. [`Navigator::batch`] makes several
+    /// navigations count as one.
+    ///
+    /// ```ignore
+    /// GlobalRouter::with_batched_effects(cx, |cx| {
+    ///     Navigator::push(cx, "/login");
+    ///     GlobalRouter::queue_effect(cx, |cx| cx.set_global(Session::signed_out()));
+    ///     GlobalRouter::queue_effect(cx, |cx| analytics::track(cx, "signed_out"));
+    /// });
+    /// ```
+    pub fn with_batched_effects<R>(cx: &mut App, update: impl FnOnce(&mut App) -> R) -> R {
+        cx.default_global::<RouterEffects>().depth += 1;
+        let result = update(cx);
+        let effects = cx.global_mut::<RouterEffects>();
+        effects.depth -= 1;
+        if effects.depth == 0 && (effects.refresh || !effects.queued.is_empty()) {
+            schedule_effects(cx);
+        }
+        result
+    }
+
+    /// Run `effect` with the router's next flush of effects
+    ///
+    /// Effects queued in one update, or inside
+    /// [`GlobalRouter::with_batched_effects`], run together in a deferred
+    /// step, in the order they were queued, and windows are refreshed once
+    /// after the last one.
+    pub fn queue_effect(cx: &mut App, effect: impl FnOnce(&mut App) + 'static) {
+        let effects = cx.default_global::<RouterEffects>();
+        effects.queued.push(Box::new(effect));
+        if effects.depth == 0 {
+            schedule_effects(cx);
+        }
+    }
+
+    /// Note that an outlet's window presented a frame
+    ///
+    /// Ends the startup period of
+    /// [`RouterConfig::suppress_transitions_until_first_frame`]. Outlets
+    /// call this after their first frame; apps rendering without an outlet
+    /// can call it themselves.
+    pub fn frame_presented(&mut self) {
+        self.frame_presented = true;
+    }
+
+    /// Whether [`GlobalRouter::frame_presented`] was called
+    pub fn has_presented_frame(&self) -> bool {
+        self.frame_presented
+    }
+
+    /// Whether navigations are shown without transitions right now
+    pub fn suppresses_transitions(&self) -> bool {
+        self.batch.as_ref().is_some_and(|batch| !batch.animate)
+            || (self.config.suppress_transitions_until_first_frame && !self.frame_presented)
+    }
+
+    /// Message to confirm before leaving the current route for `target`
+    ///
+    /// `None` unless the route is dirty and has a
+    /// [`ConfirmLeaveGuard`](crate::ConfirmLeaveGuard), or if `target` is the
+    /// current path. A `None` target is always away.
+    #[cfg(feature = "guard")]
+    fn leave_message(&self, target: Option<&str>) -> Option<String> {
+        if !self.state.is_dirty() || target == Some(self.current_path()) {
+            return None;
+        }
+        let (_, canonical) = self.delocalize(self.current_path());
+        let (chain, _) = match_route_chain(self.state.routes(), &canonical)?;
+        chain
+            .iter()
+            .rev()
+            .find_map(|route| route.confirm_leave.as_ref())
+            .map(|guard| guard.message().to_string())
+    }
+
+    /// Hold `navigation` until leaving is confirmed
+    ///
+    /// Returns `false` if it was refused instead, for lack of a confirm
+    /// handler. If a confirmation is pending already, `navigation` replaces
+    /// the navigation it holds.
+    #[cfg(feature = "guard")]
+    fn hold_leave(&mut self, message: String, navigation: HeldNavigation) -> bool {
+        if self.confirm_handler.is_none() {
+            self.refuse(NavigationError::GuardBlocked { reason: message });
+            return false;
+        }
+        if let Some(leave) = &mut self.leave {
+            leave.navigation = navigation;
+            return true;
+        }
+        debug_log!("Holding navigation until leaving is confirmed");
+        self.leave_requests += 1;
+        self.leave = Some(PendingLeave {
+            id: self.leave_requests,
+            message,
+            navigation,
+            prompted: false,
+        });
+        true
+    }
+
+    /// Hold a push or replace of `path` if it leaves a dirty route
+    #[cfg(feature = "guard")]
+    fn hold_commit(
+        &mut self,
+        locale: Option<&str>,
+        path: &str,
+        commit: PendingCommit,
+        span: &diagnostics::Entered,
+    ) -> Option<PushOutcome> {
+        let message = self.leave_message(Some(path))?;
+        let navigation = HeldNavigation::Commit {
+            locale: locale.map(str::to_string),
+            path: path.to_string(),
+            commit,
+            source: self.state.source().clone(),
+        };
+        Some(if self.hold_leave(message.clone(), navigation) {
+            span.outcome(Outcome::Pending);
+            PushOutcome::Pending {
+                path: path.to_string(),
+            }
+        } else {
+            span.outcome(Outcome::Refused);
+            PushOutcome::Refused {
+                error: NavigationError::GuardBlocked { reason: message },
+            }
+        })
+    }
+
+    /// Hold going back or forward to the entry at `index` if it leaves a
+    /// dirty route
+    ///
+    /// Returns whether the navigation must not happen now.
+    #[cfg(feature = "guard")]
+    fn hold_travel(
+        &mut self,
+        index: Option<usize>,
+        navigation: HeldNavigation,
+        span: &diagnostics::Entered,
+    ) -> bool {
+        let target = index
+            .and_then(|index| self.state.history().entries().get(index))
+            .map(|entry| entry.path.clone());
+        let Some(message) = target.and_then(|target| self.leave_message(Some(&target))) else {
+            return false;
+        };
+        let outcome = if self.hold_leave(message, navigation) {
+            Outcome::Pending
+        } else {
+            Outcome::Refused
+        };
+        span.outcome(outcome);
+        true
+    }
+
+    /// Hold closing `window` if the current route is dirty
+    ///
+    /// Returns whether the window must stay open for now.
+    #[cfg(feature = "guard")]
+    fn hold_close(&mut self, window: AnyWindowHandle) -> bool {
+        let Some(message) = self.leave_message(None) else {
+            return false;
+        };
+        self.hold_leave(message, HeldNavigation::Close(window));
+        true
+    }
+
+    /// Message of the leave confirmation waiting for an answer
+    ///
+    /// See [`ConfirmLeaveGuard`](crate::ConfirmLeaveGuard).
+    #[cfg(feature = "guard")]
+    pub fn confirming_leave(&self) -> Option<&str> {
+        self.leave.as_ref().map(|leave| leave.message.as_str())
+    }
+
+    /// Whether a leave confirmation waits for the confirm handler to be asked
+    #[cfg(feature = "guard")]
+    pub(crate) fn leave_unprompted(&self) -> bool {
+        self.leave.as_ref().is_some_and(|leave| !leave.prompted)
+    }
+
+    /// Mark the pending leave confirmation as asked, returning what to ask
+    /// the confirm handler
+    #[cfg(feature = "guard")]
+    pub(crate) fn take_leave_prompt(&mut self) -> Option<(u64, String, ConfirmHandler)> {
+        let handler = self.confirm_handler.clone()?;
+        let leave = self.leave.as_mut().filter(|leave| !leave.prompted)?;
+        leave.prompted = true;
+        Some((leave.id, leave.message.clone(), handler))
+    }
+
+    /// Apply the answer to the leave confirmation `id`
+    ///
+    /// Leaving clears the dirty flag and applies the held navigation.
+    /// Returns the window to close if the navigation was closing it.
+    #[cfg(feature = "guard")]
+    pub(crate) fn decide_leave(
+        &mut self,
+        cx: &App,
+        id: u64,
+        leave: bool,
+    ) -> Option<AnyWindowHandle> {
+        if self.leave.as_ref().map_or(true, |pending| pending.id != id) {
+            debug_log!("Ignoring the answer to an outdated leave confirmation");
+            return None;
+        }
+        let pending = self.leave.take()?;
+        if !leave {
+            debug_log!("Staying on '{}'", self.current_path());
+            return None;
+        }
+
+        self.state.set_dirty(false);
+        match pending.navigation {
+            HeldNavigation::Commit {
+                locale,
+                path,
+                commit,
+                source,
+            } => {
+                #[cfg(feature = "middleware")]
+                {
+                    self.pipeline_started = Some(self.now());
+                }
+                let span =
+                    diagnostics::navigate(self.current_path(), Some(&path), commit.direction());
+                self.with_source(source, |router| router.proceed(locale, path, commit, &span));
+            }
+            HeldNavigation::Back(index) => {
+                self.back_to(index);
+            }
+            HeldNavigation::Forward(index) => {
+                self.forward_to(index);
+            }
+            HeldNavigation::Chain(steps) => {
+                self.apply_chain(cx, steps);
+            }
+            HeldNavigation::Close(window) => return Some(window),
+        }
+        None
+    }
+
+    /// Apply a step of a [`NavigatorHandle`] chain on its own
+    fn apply_step(&mut self, cx: &App, step: ChainStep) -> Option<RouteChangeEvent> {
+        match step {
+            ChainStep::Push(route) => self
+                .navigate_in(cx, &route, PendingCommit::Push, &NavOptions::default())
+                .into_event(),
+            ChainStep::Replace(route) => self
+                .navigate_in(cx, &route, PendingCommit::Replace, &NavOptions::default())
+                .into_event(),
+            ChainStep::Pop => self.smart_back(cx),
+            ChainStep::Forward => self.forward_to(self.state.history().current_index() + 1),
+        }
+    }
+
+    /// Apply the steps of a [`NavigatorHandle`] chain as one navigation
+    ///
+    /// Runs as a batch keeping transitions. Pops only skip redirect entries;
+    /// the transition guards and the synchronous guards of the final
+    /// destination then decide, and a denial, or a guard that has not
+    /// resolved, restores the router as it was before the chain.
+    fn apply_chain(&mut self, cx: &App, steps: Vec<ChainStep>) -> Option<RouteChangeEvent> {
+        if self.defer_in_render("a navigator chain", || {
+            RenderNavigation::Chain(steps.clone())
+        }) {
+            return None;
+        }
+        #[cfg(feature = "guard")]
+        if let Some(message) = self.leave_message(None) {
+            self.hold_leave(message, HeldNavigation::Chain(steps));
+            return None;
+        }
+        let start = self.current_path().to_string();
+        #[cfg(feature = "guard")]
+        let before = (self.state.clone(), self.batch.clone());
+        #[cfg(all(feature = "guard", feature = "middleware"))]
+        let queued = self.navigated.len();
+
+        self.start_batch(true);
+        #[cfg(feature = "guard")]
+        {
+            self.chaining = true;
+        }
+        for step in steps {
+            match step {
+                ChainStep::Pop => {
+                    self.back_past_redirects();
+                }
+                step => {
+                    self.apply_step(cx, step);
+                }
+            }
+        }
+        #[cfg(feature = "guard")]
+        {
+            self.chaining = false;
+        }
+
+        let destination = self.current_path().to_string();
+        #[cfg(feature = "guard")]
+        if destination != start {
+            match self
+                .poll_navigation(cx, &start, &destination)
+                .settled(&destination)
+            {
+                GuardPoll::Passed { .. } => {}
+                GuardPoll::Denied(reason) => {
+                    (self.state, self.batch) = before;
+                    #[cfg(feature = "middleware")]
+                    self.navigated.truncate(queued);
+                    self.refuse(NavigationError::GuardBlocked { reason });
+                    return None;
+                }
+                GuardPoll::Redirected { to, guard, .. } => {
+                    // The entry committed for the target is marked as a redirect
+                    self.redirected_from = Some(destination);
+                    let source = NavigationSource::Redirect { from_guard: guard };
+                    self.with_source(source, |router| {
+                        router.navigate_opts(
+                            Some(cx),
+                            PendingCommit::Replace,
+                            to,
+                            &NavOptions::default(),
+                        )
+                    });
+                    self.redirected_from = None;
+                }
+            }
+        }
+        #[cfg(not(feature = "guard"))]
+        let _ = (start, destination);
+        self.end_batch()
+    }
+
+    /// Go back to the closest earlier entry not committed by a guard redirect
+    fn back_past_redirects(&mut self) -> Option<RouteChangeEvent> {
+        let history = self.state.history();
+        let target = history.entries()[..history.current_index()]
+            .iter()
+            .rposition(|entry| !entry.kind.is_redirect());
+        self.back_to(target)
+    }
+
+    /// Record a committed navigation in the running batch
+    ///
+    /// Navigations shown without transitions get [`Transition::None`].
+    fn batched(&mut self, event: &RouteChangeEvent) {
+        #[cfg(feature = "transition")]
+        if self.suppresses_transitions() {
+            self.state.set_navigation_transition(Some(Transition::None));
+        }
+        if let Some(batch) = &mut self.batch {
+            batch.first.get_or_insert_with(|| event.clone());
+        }
+    }
+
+    /// Merge the navigations a batch queued for the middleware into `event`
+    #[cfg(feature = "middleware")]
+    fn report_batch(&mut self, queued_before: usize, event: &RouteChangeEvent) {
+        let mut batched = self
+            .navigated
+            .split_off(queued_before.min(self.navigated.len()));
+        let Some((last_started, mut last)) = batched.pop() else {
+            return;
+        };
+        let started = batched
+            .first()
+            .map_or(last_started, |(started, _)| *started);
+        last.event = event.clone();
+        last.pipeline_duration = self.now().saturating_duration_since(started);
+        self.navigated.push((started, last));
+    }
+
+    /// Queue a committed navigation for the global middleware
+    ///
+    /// A replace of a destination that was not reported yet is a redirect and
+    /// takes that destination's place.
+    #[cfg(feature = "middleware")]
+    fn record_navigation(&mut self, event: &RouteChangeEvent) {
+        let started = self.pipeline_started.take();
+        let redirected_from = self.redirected_from.take();
+        if NavigationMiddleware::new(self, &event.to).is_empty() {
+            return;
+        }
+        let now = self.now();
+        let params = self
+            .current_match_immutable()
+            .map(|route_match| RouteParams::from_map(route_match.params))
+            .unwrap_or_default();
+        let mut navigation = AfterNavigation::new(event.clone(), self.state.routes(), params);
+
+        if event.direction == NavigationDirection::Replace {
+            if let Some((started, last)) = self
+                .navigated
+                .last_mut()
+                .filter(|(_, last)| event.from.as_deref() == Some(last.event.to.as_str()))
+            {
+                navigation.event.from.clone_from(&last.event.from);
+                navigation.event.direction = last.event.direction;
+                navigation.event.previous_dwell = last.event.previous_dwell;
+                if navigation.outcome == NavigationOutcome::Matched {
+                    navigation.outcome = NavigationOutcome::Redirected {
+                        from: last.event.to.clone(),
+                    };
+                }
+                navigation.pipeline_duration = now.saturating_duration_since(*started);
+                *last = navigation;
+                return;
+            }
+        }
+
+        if let Some(from) = redirected_from {
+            if navigation.outcome == NavigationOutcome::Matched {
+                navigation.outcome = NavigationOutcome::Redirected { from };
+            }
+        }
+        let started = started.unwrap_or(now);
+        navigation.pipeline_duration = now.saturating_duration_since(started);
+        self.navigated.push((started, navigation));
+    }
+
+    /// Current time on the app's clock, or the system clock without an app
+    fn now(&self) -> Instant {
+        self.clock
+            .as_ref()
+            .map_or_else(Instant::now, BackgroundExecutor::now)
+    }
+
+    /// When a navigation using `interval` may be applied, if not yet
+    fn throttled_until(&self, interval: Option<Duration>, now: Instant) -> Option<Instant> {
+        let due = self.state.last_navigation()? + interval?;
+        (now < due).then_some(due)
+    }
+
+    /// Hold back or drop a push or replace arriving within the interval
+    ///
+    /// Returns the outcome if the navigation must not happen now. Otherwise
+    /// it is recorded as the last navigation, superseding any held one. A
+    /// held navigation starts the timer applying it, unless one is running;
+    /// without an app to run it on, the navigation is dropped.
+    fn throttle(&mut self, path: &str, commit: PendingCommit) -> Option<PushOutcome> {
+        let now = self.now();
+        let interval = match_route_chain(self.state.routes(), path)
+            .and_then(|(chain, _)| chain.iter().rev().find_map(|route| route.debounce))
+            .or(self.config.min_navigation_interval);
+
+        let due = self
+            .throttled_until(interval, now)
+            .filter(|_| self.batch.is_none());
+        let Some(due) = due else {
+            self.navigated(now);
+            return None;
+        };
+        let path = path.to_string();
+        match (&self.config.throttle_policy, &self.app) {
+            (ThrottlePolicy::Coalesce, Some(app)) => {
+                debug_log!("Holding navigation to '{}' (throttled)", path);
+                if self.throttle_task.is_none() {
+                    let task = crate::throttle::spawn_throttle_timer(app, due - now);
+                    self.throttle_task = Some(Arc::new(task));
+                }
+                self.throttled = Some(ThrottledNavigation {
+                    path: path.clone(),
+                    commit,
+                    source: self.state.source().clone(),
+                    due,
+                });
+                Some(PushOutcome::Throttled { path })
+            }
+            _ => {
+                debug_log!("Dropping navigation to '{}' (throttled)", path);
+                Some(PushOutcome::Dropped { path })
+            }
+        }
+    }
+
+    /// Whether a back or forward navigation has to be dropped
+    fn throttle_history(&mut self) -> bool {
+        if !self.config.throttle_history {
+            return false;
+        }
+        let throttled = self
+            .throttled_until(self.config.min_navigation_interval, self.now())
+            .is_some();
+        if throttled {
+            debug_log!("Dropping history navigation (throttled)");
+        }
+        throttled
+    }
+
+    /// Record a navigation, superseding any held one
+    ///
+    /// Route builders that panicked get another chance.
+    fn navigated(&mut self, now: Instant) {
+        self.state.set_last_navigation(now);
+        self.throttled = None;
+        self.throttle_task = None;
+        self.poisoned.clear();
+    }
+
+    /// Apply the held navigation if it is due
+    ///
+    /// Returns the time left if it is not due yet.
+    pub(crate) fn flush_throttled(&mut self) -> Option<Duration> {
+        let throttled = self.throttled.take()?;
+        let now = self.now();
+        if now < throttled.due {
+            let remaining = throttled.due - now;
+            self.throttled = Some(throttled);
+            return Some(remaining);
+        }
+        self.navigated(now);
+        let span = diagnostics::navigate(
+            self.current_path(),
+            Some(&throttled.path),
+            throttled.commit.direction(),
+        );
+        self.with_source(throttled.source, |router| {
+            router.navigate_now(throttled.path, throttled.commit, &span)
+        });
+        None
+    }
+
+    /// Mark a route builder as panicked until the next navigation
+    pub(crate) fn poison_builder(&mut self, builder: &RouteBuilder, message: String) {
+        self.poisoned.push((builder.clone(), message));
+    }
+
+    /// Panic message of a builder that panicked since the last navigation
+    /// Forget builder panics and reload `path` if its loader failed
+    ///
+    /// Loaders only rerun while `path` is still the current path.
+    pub(crate) fn retry(&mut self, path: &str) {
+        self.poisoned.clear();
+        if self.current_path() == path && matches!(self.route_data(path), Some(DataState::Error(_)))
+        {
+            self.schedule_loader(path, None);
+        }
+    }
+
+    pub(crate) fn builder_panic(&self, builder: &RouteBuilder) -> Option<&str> {
+        self.poisoned
+            .iter()
+            .find(|(poisoned, _)| poisoned.ptr_eq(builder))
+            .map(|(_, message)| message.as_str())
+    }
+
+    /// Data prepared this frame for `builder`'s route with params `key`
+    pub(crate) fn prepared_data(&self, builder: &RouteBuilder, key: &str) -> Option<LoaderData> {
+        self.prepared
+            .iter()
+            .find(|(prepared, params, _)| prepared.ptr_eq(builder) && params == key)
+            .map(|(_, _, data)| Arc::clone(data))
+    }
+
+    /// Keep data prepared for `builder`'s route until the frame ends
+    ///
+    /// Returns whether it is the first data of the frame.
+    pub(crate) fn store_prepared(
+        &mut self,
+        builder: &RouteBuilder,
+        key: String,
+        data: LoaderData,
+    ) -> bool {
+        self.prepared.push((builder.clone(), key, data));
+        self.prepared.len() == 1
+    }
+
+    /// Forget data prepared during the last frame
+    pub(crate) fn clear_prepared(&mut self) {
+        self.prepared.clear();
+    }
+
+    /// Path of a navigation held back by throttling
+    pub fn throttled_navigation(&self) -> Option<&str> {
+        self.throttled
+            .as_ref()
+            .map(|throttled| throttled.path.as_str())
+    }
+
+    /// How long a navigation to `route` may wait: the stricter of the
+    /// router's and the route's deadline
+    fn timeout_of(&self, route: &Route) -> Option<Duration> {
+        match (self.config.navigation_timeout, route.navigation_timeout) {
+            (Some(router), Some(route)) => Some(router.min(route)),
+            (router, route) => router.or(route),
+        }
+    }
+
+    /// How long a navigation to `path` may wait, see
+    /// [`RouterConfig::navigation_timeout`]
+    #[cfg(any(feature = "guard", feature = "middleware"))]
+    pub(crate) fn navigation_timeout(&self, path: &str) -> Option<Duration> {
+        match match_route_chain(self.state.routes(), path) {
+            Some((chain, _)) => chain.last().and_then(|route| self.timeout_of(route)),
+            None => self.config.navigation_timeout,
+        }
+    }
+
+    /// Cancel any running loader and schedule the one for `path`
+    ///
+    /// Returns `true` if the navigation (`commit`) must wait for the loader.
+    fn schedule_loader(&mut self, path: &str, commit: Option<PendingCommit>) -> bool {
+        // Navigating anywhere supersedes the previous load
+        self.abandon_load();
+        self.blocked_on = None;
+
+        let Some((chain, route_match)) = match_route_chain(self.state.routes(), path) else {
+            return false;
+        };
+        let Some(route) = chain.last() else {
+            return false;
+        };
+        let Some(load) = route.loader.clone() else {
+            return false;
+        };
+
+        let commit = commit.filter(|_| route.loader_mode == LoaderMode::Blocking);
+        let timeout = self.timeout_of(route);
+        let deadline = commit.and(timeout).map(|timeout| (self.now(), timeout));
+        let generation = self.state.data_mut().begin(path);
+        let Some(app) = &self.app else {
+            warn_log!("Loader of '{}' cannot run without an app", path);
+            self.state.data_mut().finish(
+                path,
+                generation,
+                Err(format!("Loader of '{}' cannot run without an app", path)),
+            );
+            return false;
+        };
+        let token = self
+            .in_flight_token
+            .clone()
+            .unwrap_or_else(|| self.navigation_token.clone());
+        let task = crate::loader::spawn_loader(
+            app,
+            PendingLoad {
+                path: path.to_string(),
+                params: RouteParams::from_map(route_match.params)
+                    .with_navigation_token(token.clone()),
+                token,
+                load,
+                generation,
+                commit,
+                deadline,
+            },
+        );
+        self.loading = Some(path.to_string());
+        self.loader_task = Some(Arc::new(task));
+
+        if commit.is_some() {
+            self.blocked_on = Some(path.to_string());
+            self.blocked_source = self.state.source().clone();
+        }
+        commit.is_some()
+    }
+
+    /// Cancel the load in flight, dropping the data it was loading
+    fn abandon_load(&mut self) {
+        self.loader_task = None;
+        if let Some(path) = self.loading.take() {
+            if self.route_data(&path).is_some_and(DataState::is_loading) {
+                self.state.data_mut().remove(&path);
+            }
+        }
+    }
+
+    /// Store a loader result, committing the navigation it blocked
+    pub(crate) fn finish_load(
+        &mut self,
+        path: &str,
+        generation: u64,
+        result: Result<LoaderData, String>,
+        commit: Option<PendingCommit>,
+    ) {
+        if !self.state.data_mut().finish(path, generation, result) {
+            debug_log!("Discarding superseded load for '{}'", path);
+            return;
+        }
+
+        self.loading = None;
+        self.blocked_on = None;
+        self.commit_loaded(path, commit);
+    }
+
+    /// Abort the navigation blocked on the load of `path`, which timed out
+    /// after waiting `elapsed`
+    ///
+    /// Does nothing if the load was superseded in the meantime.
+    pub(crate) fn time_out_load(
+        &mut self,
+        path: &str,
+        generation: u64,
+        commit: Option<PendingCommit>,
+        elapsed: Duration,
+    ) {
+        let error = NavigationError::Timeout {
+            path: path.to_string(),
+            elapsed,
+        };
+        if !self
+            .state
+            .data_mut()
+            .finish(path, generation, Err(error.to_string()))
+        {
+            debug_log!("Discarding superseded timeout for '{}'", path);
+            return;
+        }
+        self.loading = None;
+
+        self.blocked_on = None;
+        if let Some(token) = self.in_flight_token.take() {
+            token.cancel();
+        }
+        self.refuse(error);
+        match self.config.timeout_fallback {
+            TimeoutFallback::KeepCurrent => self.state.data_mut().remove(path),
+            TimeoutFallback::ErrorPage => self.commit_loaded(path, commit),
+        }
+    }
+
+    /// Commit the navigation a load blocked, if any
+    fn commit_loaded(&mut self, path: &str, commit: Option<PendingCommit>) {
+        let source = std::mem::take(&mut self.blocked_source);
+        match commit {
+            Some(PendingCommit::Push) => {
+                self.with_source(source, |router| router.commit_push(path.to_string()));
+            }
+            Some(PendingCommit::Replace) => {
+                self.with_source(source, |router| router.commit_replace(path.to_string()));
+            }
+            None => {}
+        }
+    }
+
+    /// Path of a navigation waiting for its blocking loader or its guards
+    pub fn pending_navigation(&self) -> Option<&str> {
+        self.blocked_on.as_deref().or_else(|| self.guarded_path())
+    }
+
+    /// Path of a navigation waiting for its guards
+    #[cfg_attr(not(feature = "guard"), allow(clippy::unused_self))]
+    fn guarded_path(&self) -> Option<&str> {
+        #[cfg(feature = "guard")]
+        {
+            self.guarded.as_ref().map(|held| held.path.as_str())
+        }
+        #[cfg(not(feature = "guard"))]
+        {
+            None
+        }
+    }
+
+    /// Snapshot of the router's state, for debugging and bug reports
+    ///
+    /// Values of the params and query keys listed in
+    /// [`RouterConfig::redact_keys`] are masked. See [`crate::dump`];
+    /// [`Navigator::dump`] formats the snapshot as a report.
+    pub fn dump_state(&self) -> RouterStateDump {
+        let routes = self.state.routes();
+        let params_of = |path: &str| {
+            match_route_chain(routes, path)
+                .map(|(_, route_match)| route_match.params)
+                .unwrap_or_default()
+        };
+        let mut redactor = Redactor::new(&self.config.redact_keys);
+
+        let path = self.current_path();
+        let params = self.state.current_params();
+        let query = QueryParams::from_query_string(path.split_once('?').map_or("", |(_, q)| q));
+        let matched = match_route_chain(routes, path)
+            .map(|(chain, _)| {
+                let mut full_path = String::new();
+                chain
+                    .into_iter()
+                    .filter(|route| !route.pathless)
+                    .map(|route| {
+                        full_path = if full_path.is_empty() {
+                            route.config.path.clone()
+                        } else {
+                            build_child_path(&full_path, &route.config.path).into_owned()
+                        };
+                        RouteDump {
+                            path: full_path.clone(),
+                            name: route.config.name.clone(),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let history = self.state.history();
+        let entries = history
+            .entries()
+            .iter()
+            .map(|entry| HistoryEntryDump {
+                path: redactor.path(&entry.path, &params_of(&entry.path)),
+                redirected_from: match &entry.kind {
+                    EntryKind::Redirect { from } => Some(redactor.path(from, &params_of(from))),
+                    EntryKind::Navigation => None,
+                },
+            })
+            .collect();
+
+        let mut named_routes: Vec<String> = self.named_routes.names().map(str::to_string).collect();
+        named_routes.sort();
+        let pending_navigation = self
+            .blocked_on
+            .as_deref()
+            .or_else(|| self.guarded_path())
+            .or_else(|| self.throttled.as_ref().map(|held| held.path.as_str()))
+            .map(|pending| redactor.path(pending, &params_of(pending)));
+
+        RouterStateDump {
+            path: redactor.path(path, params.all()),
+            params: params
+                .iter()
+                .map(|(key, value)| (key.clone(), redactor.value(key, value)))
+                .collect(),
+            query: query
+                .iter()
+                .map(|(key, values)| {
+                    let values = values.iter().map(|value| redactor.value(key, value));
+                    (key.clone(), values.collect())
+                })
+                .collect(),
+            matched,
+            history: entries,
+            history_index: history.current_index(),
+            route_count: self.route_count(),
+            named_routes,
+            #[cfg(feature = "cache")]
+            cache_stats: self.cache_stats().clone(),
+            pending_navigation,
+            // Last, so values masked anywhere above are masked in the message
+            last_error: self.last_error.as_ref().map(|error| match error {
+                NavigationError::RouteNotFound { path, .. } => NavigationError::RouteNotFound {
+                    path: redactor.path(path, &params_of(path)),
+                    details: None,
+                }
+                .to_string(),
+                error => redactor.text(&error.to_string()),
+            }),
+        }
+    }
+
+    /// Loader output for a path, if its route has a loader
+    pub fn route_data(&self, path: &str) -> Option<&DataState<LoaderData>> {
+        self.state.data().get(path)
+    }
+
+    /// Loader mode of the route matching `path`, if it has a loader
+    pub fn loader_mode(&self, path: &str) -> Option<LoaderMode> {
+        let (chain, _) = match_route_chain(self.state.routes(), path)?;
+        chain
+            .last()
+            .filter(|route| route.loader.is_some())
+            .map(|route| route.loader_mode)
+    }
+
+    /// Focus target key of the route matching `path`
+    ///
+    /// In nested routes the deepest route with a focus target wins.
+    pub fn focus_target(&self, path: &str) -> Option<&str> {
+        let (chain, _) = match_route_chain(self.state.routes(), path)?;
+        chain
+            .iter()
+            .rev()
+            .find_map(|route| route.focus_target.as_deref())
+    }
+
+    /// Focus handle of the element marked with `key`, once a page marked it
+    pub fn focus_target_handle(&self, key: &str) -> Option<&FocusHandle> {
+        self.focus_targets.get(key)
+    }
+
+    /// Window hints for `path`: the base hints, overridden by the hints of
+    /// each matched route from the outermost in
+    pub fn window_hints(&self, path: &str) -> WindowHints {
+        let Some((chain, _)) = match_route_chain(self.state.routes(), path) else {
+            return self.base_window_hints.clone();
+        };
+        chain
+            .iter()
+            .filter_map(|route| route.window_hints.as_ref())
+            .fold(self.base_window_hints.clone(), |hints, route_hints| {
+                hints.merged(route_hints)
+            })
+    }
+
+    /// Window hints of routes without their own
+    pub fn base_window_hints(&self) -> &WindowHints {
+        &self.base_window_hints
+    }
+
+    /// Set the window hints of routes without their own
+    ///
+    /// They are restored when navigating away from a route with hints, for
+    /// the fields the next route does not set.
+    pub fn set_base_window_hints(&mut self, hints: WindowHints) {
+        self.base_window_hints = hints;
+    }
+
+    /// Applier of window hints, see [`GlobalRouter::set_window_hint_applier`]
+    pub fn window_hint_applier(&self) -> &WindowHintApplier {
+        &self.window_hint_applier
+    }
+
+    /// Set what applying [`WindowHints`] to a window does
+    ///
+    /// Called with the merged hints whenever they change after a navigation.
+    /// The default, [`apply_window_title`](crate::apply_window_title), only
+    /// sets the title.
+    pub fn set_window_hint_applier(
+        &mut self,
+        applier: impl Fn(&WindowHints, &mut Window, &mut App) + Send + Sync + 'static,
+    ) {
+        self.window_hint_applier = Arc::new(applier);
+    }
+
+    /// Colors of links, see [`GlobalRouter::set_link_theme`]
+    pub fn link_theme(&self) -> &RouterLinkTheme {
+        &self.link_theme
+    }
+
+    /// Set the colors of `router_link` and of [`RouterLink`](crate::RouterLink)s
+    /// without styling of their own
+    ///
+    /// Links pick the theme up when they are next rendered, so refresh the
+    /// window after switching it at runtime.
+    pub fn set_link_theme(&mut self, theme: RouterLinkTheme) {
+        self.link_theme = theme;
+    }
+
+    /// Pages rendered by outlets while loading and on loader errors
+    pub fn default_pages(&self) -> &Arc<DefaultPages> {
+        &self.default_pages
+    }
+
+    /// Set the pages rendered by outlets while loading and on loader errors
+    pub fn set_default_pages(&mut self, pages: DefaultPages) {
+        self.default_pages = Arc::new(pages);
+    }
+
+    /// Drop cached outlet resolutions for the paths involved in a navigation
+    #[cfg(feature = "cache")]
+    fn invalidate_navigation(&mut self, event: &RouteChangeEvent) {
+        if let Some(from) = &event.from {
+            self.nested_cache.invalidate_path(from);
+        }
+        self.nested_cache.invalidate_path(&event.to);
+    }
+
+    /// Get current path
+    pub fn current_path(&self) -> &str {
+        self.state.current_path()
+    }
+
+    /// Current path as a [`SharedString`], cloned without copying the path
+    pub fn current_path_shared(&self) -> &SharedString {
+        self.state.current_path_shared()
+    }
+
+    /// Fragment of the current location, without the `#`
+    pub fn current_fragment(&self) -> Option<&str> {
+        self.state.history().current_fragment()
+    }
+
+    /// Get current route match (with caching, requires mutable)
+    pub fn current_match(&mut self) -> Option<crate::RouteMatch> {
+        self.state.current_match()
+    }
+
+    /// Get current route match (immutable, no caching)
+    ///
+    /// Use this from Render implementations and other immutable contexts
+    pub fn current_match_immutable(&self) -> Option<crate::RouteMatch> {
+        self.state.current_match_immutable()
+    }
+
+    /// Params of the current path, split by the level of the matched route
+    /// chain that introduced them, outermost first
+    ///
+    /// See [`RouterState::params_by_level`].
+    pub fn params_by_level(&self) -> Vec<RouteParams> {
+        self.state.params_by_level(self.current_path())
+    }
+
+    /// Get the current matched Route
+    ///
+    /// Returns the shared `Arc<Route>` that matched the current path.
+    /// Useful for accessing the route's children and builder without cloning.
+    pub fn current_route(&self) -> Option<&Arc<Route>> {
+        self.state.current_route()
+    }
+
+    /// Typed meta of type `T` of the current route, inherited from its
+    /// parents unless it sets its own
+    ///
+    /// See [`Route::meta_typed`].
+    pub fn current_meta_typed<T: MetaValue>(&self) -> Option<&T> {
+        let (_, path) = self.delocalize(self.current_path());
+        let (chain, _) = match_route_chain(self.state.routes(), &path)?;
+        chain_meta_typed(&chain)
+    }
+
+    /// Number of leading routes the matched chains of two paths share
+    ///
+    /// Outlets use this to tell which part of the tree a navigation changed:
+    /// going from `/dashboard/overview` to `/dashboard/analytics` shares the
+    /// `/dashboard` level (depth 1), so the dashboard layout is kept and only
+    /// its child outlet re-renders and transitions. A level with different
+    /// params (`/users/1/posts` to `/users/2/posts`) is not shared.
+    pub fn shared_route_depth(&self, from: &str, to: &str) -> usize {
+        self.state.shared_route_depth(from, to)
+    }
+
+    /// Match the current path against `pattern`, which need not be registered
+    ///
+    /// With `prefix`, the current path may continue past the pattern. See
+    /// [`Navigator::matched_against`] and [`Navigator::is_active`].
+    pub fn match_current(&self, pattern: &str, prefix: bool) -> Option<crate::RouteMatch> {
+        let pattern = CompiledPattern::parse(pattern);
+        let path = self.current_path();
+        if prefix {
+            pattern.matches_prefix(path)
+        } else {
+            pattern.matches(path)
+        }
+    }
+
+    /// Whether the current path matches `pattern`, like [`GlobalRouter::match_current`]
+    ///
+    /// Patterns of static segments only, as most links use, are compared
+    /// segment by segment without allocating; others are matched in full.
+    pub fn is_current(&self, pattern: &str, prefix: bool) -> bool {
+        let mut expected = pattern.split('/').filter(|segment| !segment.is_empty());
+        if expected
+            .clone()
+            .any(|segment| segment == "*" || segment.starts_with(':'))
+        {
+            return self.match_current(pattern, prefix).is_some();
+        }
+        let path = self.current_path();
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let mut actual = path.split('/').filter(|segment| !segment.is_empty());
+        loop {
+            match (expected.next(), actual.next()) {
+                (Some(expected), Some(actual)) if expected == actual => {}
+                (None, None) => return true,
+                (None, Some(_)) => return prefix,
+                _ => return false,
+            }
+        }
+    }
+
+    /// How params changed with the last navigation
+    ///
+    /// See [`RouterState::params_diff`].
+    pub fn params_diff(&self) -> Option<ParamsDiff> {
+        self.state.params_diff()
+    }
+
+    /// Path of the page shown underneath the current modal route
+    ///
+    /// See [`RouterState::modal_base_path`].
+    pub fn modal_base_path(&self) -> Option<&str> {
+        self.state.modal_base_path()
+    }
+
+    /// Check if can go back
+    pub fn can_go_back(&self) -> bool {
+        self.state.can_go_back()
+    }
+
+    /// Check if can go forward
+    pub fn can_go_forward(&self) -> bool {
+        self.state.can_go_forward()
+    }
+
+    /// Get mutable state reference
+    pub fn state_mut(&mut self) -> &mut RouterState {
+        &mut self.state
+    }
+
+    /// Get state reference
+    pub fn state(&self) -> &RouterState {
+        &self.state
+    }
+
+    /// Get nested route cache (mutable)
+    #[cfg(feature = "cache")]
+    pub fn nested_cache_mut(&mut self) -> &mut RouteCache {
+        &mut self.nested_cache
+    }
+
+    /// Get nested route cache statistics
+    #[cfg(feature = "cache")]
+    pub fn cache_stats(&self) -> &CacheStats {
+        self.nested_cache.stats()
+    }
+
+    /// Set transition for the next navigation
+    ///
+    /// This override will be used for the next push/replace operation,
+    /// then automatically cleared.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use gpui_navigator::{GlobalRouter, Transition};
+    ///
+    /// cx.update_global::<GlobalRouter, _>(|router, _| {
+    ///     router.set_next_transition(Transition::fade(300));
+    ///     router.push("/page".to_string());
+    /// });
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn set_next_transition(&mut self, transition: Transition) {
+        self.next_transition = Some(transition);
+    }
+
+    /// Show the route navigated to last without its transition
+    ///
+    /// For navigations the user already watched happen, such as a swipe.
+    #[cfg(feature = "gestures")]
+    pub(crate) fn skip_transition(&mut self) {
+        self.state.set_navigation_transition(Some(Transition::None));
+    }
+
+    /// Get and consume the next transition override
+    ///
+    /// Returns the transition override if set, and clears it.
+    /// Used internally by navigation methods.
+    #[cfg(feature = "transition")]
+    pub fn take_next_transition(&mut self) -> Option<Transition> {
+        self.next_transition.take()
+    }
+
+    /// Check if there's a transition override set
+    #[cfg(feature = "transition")]
+    pub fn has_next_transition(&self) -> bool {
+        self.next_transition.is_some()
+    }
+
+    /// Clear transition override without consuming it
+    #[cfg(feature = "transition")]
+    pub fn clear_next_transition(&mut self) {
+        self.next_transition = None;
+    }
+
+    /// Navigate with a specific transition
+    ///
+    /// Convenience method that sets the transition and navigates in one call.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use gpui_navigator::{GlobalRouter, Transition};
+    ///
+    /// cx.update_global::<GlobalRouter, _>(|router, _| {
+    ///     router.push_with_transition("/page".to_string(), Transition::slide_left(300));
+    /// });
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn push_with_transition(&mut self, path: String, transition: Transition) -> PushOutcome {
+        self.set_next_transition(transition);
+        self.push(path)
+    }
+
+    /// Replace with a specific transition
+    #[cfg(feature = "transition")]
+    pub fn replace_with_transition(&mut self, path: String, transition: Transition) -> PushOutcome {
+        self.set_next_transition(transition);
+        self.replace(path)
+    }
+}
+
+impl Default for GlobalRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for GlobalRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("GlobalRouter");
+        debug
+            .field("state", &self.state)
+            .field("config", &self.config)
+            .field("named_routes", &self.named_routes)
+            .field("outlet_paths", &self.outlet_paths)
+            .field("pending_navigation", &self.pending_navigation())
+            .field(
+                "throttled",
+                &self.throttled.as_ref().map(|held| held.path.as_str()),
+            )
+            .field("last_error", &self.last_error)
+            .field("redirected_from", &self.redirected_from)
+            .field("in_batch", &self.batch.is_some())
+            .field("registered", &self.registered)
+            .field("active_table", &self.active_table);
+        #[cfg(feature = "cache")]
+        debug.field("cache_stats", self.cache_stats());
+        #[cfg(feature = "transition")]
+        debug.field("next_transition", &self.next_transition);
+        #[cfg(feature = "middleware")]
+        debug
+            .field("middleware", &self.middleware.len())
+            .field("filtered_middleware", &self.filtered_middleware.len())
+            .field("navigated", &self.navigated.len());
+        debug.finish_non_exhaustive()
+    }
+}
+
+impl Global for GlobalRouter {}
+
+/// Effect queued with [`GlobalRouter::queue_effect`]
+type QueuedEffect = Box<dyn FnOnce(&mut App)>;
+
+/// Refreshes and effects held for a single flush, see
+/// [`GlobalRouter::with_batched_effects`]
+#[derive(Default)]
+struct RouterEffects {
+    /// Number of `with_batched_effects` calls not returned yet
+    depth: usize,
+    /// Effects queued with [`GlobalRouter::queue_effect`]
+    queued: Vec<QueuedEffect>,
+    /// Whether windows are to be refreshed
+    refresh: bool,
+    /// Whether the flush is deferred already
+    scheduled: bool,
+    /// Whether navigations of the router schedule a flush
+    observing: bool,
+    /// Windows showing an outlet, which get the window hints of the route
+    outlet_windows: Vec<AnyWindowHandle>,
+}
+
+impl Global for RouterEffects {}
+
+/// Go on with the navigation held as guard check `id`, see
+/// [`GlobalRouter::resume_guarded`]
+#[cfg(feature = "guard")]
+fn resume_guarded(cx: &mut App, id: u64, verdict: Option<GuardVerdict>) {
+    if !cx.has_global::<GlobalRouter>() {
+        return;
+    }
+    cx.update_global::<GlobalRouter, _>(|router, cx| router.resume_guarded(cx, id, verdict));
+    crate::error::report_refused_navigations(cx);
+    refresh_windows(cx);
+}
+
+/// Abort the navigation held as guard check `id`, see
+/// [`GlobalRouter::time_out_guarded`]
+#[cfg(feature = "guard")]
+fn time_out_guarded(cx: &mut App, id: u64) {
+    if !cx.has_global::<GlobalRouter>() {
+        return;
+    }
+    cx.update_global::<GlobalRouter, _>(|router, _| router.time_out_guarded(id));
+    crate::error::report_refused_navigations(cx);
+    refresh_windows(cx);
+}
+
+/// Refresh the windows once the router's current effects are done
+///
+/// Router code calls this instead of [`App::refresh_windows`], so the
+/// refreshes of one update, or of a [`GlobalRouter::with_batched_effects`]
+/// call, end in a single one.
+pub fn refresh_windows(cx: &mut App) {
+    let effects = cx.default_global::<RouterEffects>();
+    effects.refresh = true;
+    if effects.depth == 0 {
+        schedule_effects(cx);
+    }
+}
+
+/// Defer the flush of the router's effects, unless it is already
+fn schedule_effects(cx: &mut App) {
+    let effects = cx.default_global::<RouterEffects>();
+    if !std::mem::replace(&mut effects.scheduled, true) {
+        cx.defer(flush_effects);
+    }
+}
+
+/// Flush the router's effects whenever a navigation leaves work for
+/// [`run_navigation_effects`]
+fn observe_navigations(cx: &mut App) {
+    if std::mem::replace(&mut cx.default_global::<RouterEffects>().observing, true) {
+        return;
+    }
+    cx.observe_global::<GlobalRouter>(|cx| {
+        if cx
+            .try_global::<GlobalRouter>()
+            .is_some_and(navigation_effects_pending)
+        {
+            schedule_effects(cx);
+        }
+    })
+    .detach();
+}
+
+/// Whether committed or refused navigations left work for
+/// [`run_navigation_effects`]
+fn navigation_effects_pending(router: &GlobalRouter) -> bool {
+    #[cfg(feature = "middleware")]
+    if !router.navigating.is_empty() || !router.navigated.is_empty() {
+        return true;
+    }
+    #[cfg(feature = "guard")]
+    if router.leave_unprompted() {
+        return true;
+    }
+    let state = router.state();
+    !router.unreported_errors.is_empty()
+        || state.scoped_states().has_disposed()
+        || state.kept_alive().has_dropped()
+        || crate::window_hints::pending_window_hints(router).is_some()
+}
+
+/// Note that `window` shows an outlet, so it gets the route's window hints
+///
+/// A window noted for the first time gets the current ones with the next
+/// flush.
+pub fn note_outlet_window(window: &Window, cx: &mut App) {
+    let handle = window.window_handle();
+    let windows = &mut cx.default_global::<RouterEffects>().outlet_windows;
+    if windows.contains(&handle) {
+        return;
+    }
+    windows.push(handle);
+    if cx
+        .try_global::<GlobalRouter>()
+        .and_then(crate::window_hints::pending_window_hints)
+        .is_some()
+    {
+        schedule_effects(cx);
+    }
+}
+
+/// Finish the navigations made since the last flush
+///
+/// Disposes scoped state and evicts kept-alive components they dropped,
+/// runs their middleware, asks to confirm leaving a dirty route, reports
+/// refused navigations and applies the window hints of the route navigated
+/// to. Each step takes what the navigations queued, so it runs once per
+/// navigation, whether an outlet is on screen or not.
+fn run_navigation_effects(cx: &mut App) {
+    if !cx.has_global::<GlobalRouter>() {
+        return;
+    }
+    crate::scope::dispose_route_states(cx);
+    crate::keep_alive::run_evict_callbacks(cx);
+    #[cfg(feature = "middleware")]
+    crate::middleware::run_navigation_middleware(cx);
+    #[cfg(feature = "guard")]
+    crate::guards::prompt_leave_confirmation(cx);
+    crate::error::report_refused_navigations(cx);
+    let windows = cx.global::<RouterEffects>().outlet_windows.clone();
+    for handle in windows {
+        let applied = handle.update(cx, |_, window, cx| {
+            crate::window_hints::apply_window_hints(window, cx);
+        });
+        if applied.is_err() {
+            cx.global_mut::<RouterEffects>()
+                .outlet_windows
+                .retain(|window| *window != handle);
+        }
+    }
+}
+
+/// Finish the navigations made, run the queued effects, then refresh the
+/// windows once
+fn flush_effects(cx: &mut App) {
+    cx.default_global::<RouterEffects>().depth += 1;
+    // Effects may navigate or queue more effects; those run in this flush too
+    loop {
+        run_navigation_effects(cx);
+        let queued = std::mem::take(&mut cx.global_mut::<RouterEffects>().queued);
+        if queued.is_empty() {
+            break;
+        }
+        cx.global_mut::<RouterEffects>().refresh = true;
+        for effect in queued {
+            effect(cx);
+        }
+    }
+    let effects = cx.global_mut::<RouterEffects>();
+    effects.depth -= 1;
+    effects.scheduled = false;
+    if std::mem::take(&mut effects.refresh) {
+        cx.refresh_windows();
+    }
+}
+
+/// Trait for accessing the global router from context
+pub trait UseRouter {
+    /// Get reference to global router
+    fn router(&self) -> &GlobalRouter;
+
+    /// Update global router
+    fn update_router<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut GlobalRouter, &mut App) -> R;
+}
+
+impl UseRouter for App {
+    fn router(&self) -> &GlobalRouter {
+        self.global::<GlobalRouter>()
+    }
+
+    fn update_router<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut GlobalRouter, &mut App) -> R,
+    {
+        self.update_global(f)
+    }
+}
+
+// Spelled out so calls on a `Context` do not depend on deref to `App`
+impl<V> UseRouter for Context<'_, V> {
+    fn router(&self) -> &GlobalRouter {
+        self.global::<GlobalRouter>()
+    }
+
+    fn update_router<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut GlobalRouter, &mut App) -> R,
+    {
+        self.update_global::<GlobalRouter, _>(|router, cx| f(router, cx))
+    }
+}
+
+/// Initialize global router with routes
+///
+/// Calling it again once a router exists logs a warning and, by default,
+/// adds the routes with [`GlobalRouter::extend`] instead of starting over;
+/// see [`RouterConfig::on_reinit`].
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{init_router, Route};
+///
+/// fn main() {
+///     App::new().run(|cx| {
+///         init_router(cx, |router| {
+///             router.add_route(Route::new("/", |_, _cx, _params| gpui::div().into_any_element()));
+///             router.add_route(Route::new("/users/:id", |_, _cx, _params| gpui::div().into_any_element()));
+///         });
+///     });
+/// }
+/// ```
+pub fn init_router<F>(cx: &mut App, configure: F)
+where
+    F: FnOnce(&mut GlobalRouter),
+{
+    if let Some(router) = cx.try_global::<GlobalRouter>() {
+        if router.config().reinit == ReinitPolicy::Extend {
+            warn_log!("init_router called again, adding routes to the existing router");
+            GlobalRouter::extend(cx, configure);
+            return;
+        }
+        warn_log!("init_router called again, replacing the router");
+    }
+
+    let mut router = GlobalRouter::new();
+    router.clock = Some(cx.background_executor().clone());
+    router.app = Some(cx.to_async());
+    router
+        .state
+        .set_clock(Arc::new(cx.background_executor().clone()));
+    configure(&mut router);
+    let initial_path = router.config.initial_path.clone();
+    cx.set_global(router);
+    observe_navigations(cx);
+    cx.on_app_quit(|cx| {
+        if cx.has_global::<GlobalRouter>() {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.cancel_navigations());
+        }
+        async {}
+    })
+    .detach();
+    if let Some(path) = initial_path {
+        start_router_at(cx, path);
+    }
+    // Outlets already on screen show the new router's routes
+    refresh_windows(cx);
+}
+
+/// Initialize the router, starting at the path returned by `initial`
+///
+/// `initial` runs before the router exists, e.g. to read a saved session or
+/// command-line arguments, and takes precedence over
+/// [`RouterConfig::initial_path`]. Guards and middleware run on the path as
+/// for any navigation, so a protected path redirects like it would later;
+/// the result is the first history entry. See [`GlobalRouter::start_at`].
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{init_router_with_initial, Route};
+///
+/// init_router_with_initial(
+///     cx,
+///     |cx| restore_last_path(cx).unwrap_or_else(|| "/".to_string()),
+///     |router| {
+///         router.add_route(Route::new("/", home));
+///         router.add_route(Route::new("/projects/:id", project).guard(AuthGuard::new(is_logged_in, "/login")));
+///     },
+/// );
+/// ```
+pub fn init_router_with_initial<I, F>(cx: &mut App, initial: I, configure: F)
+where
+    I: FnOnce(&mut App) -> String,
+    F: FnOnce(&mut GlobalRouter),
+{
+    let path = initial(cx);
+    init_router(cx, configure);
+    start_router_at(cx, path);
+}
+
+/// Initialize the router, starting at the path `initial` resolves to
+///
+/// Like [`init_router_with_initial`] for paths that take time to find out.
+/// Until the task finishes, outlets render
+/// [`DefaultPages::render_loading`](crate::DefaultPages::render_loading)
+/// and [`GlobalRouter::resolving_initial`] is `true`; navigating away in the
+/// meantime does not cancel it.
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::init_router_with_initial_async;
+///
+/// init_router_with_initial_async(
+///     cx,
+///     |cx| cx.spawn(async move |_| load_session().await.last_path),
+///     |router| {
+///         // add routes...
+///     },
+/// );
+/// ```
+pub fn init_router_with_initial_async<I, F>(cx: &mut App, initial: I, configure: F)
+where
+    I: FnOnce(&mut App) -> Task<String>,
+    F: FnOnce(&mut GlobalRouter),
+{
+    let resolve = initial(cx);
+    init_router(cx, configure);
+
+    let task = cx.spawn(async move |cx| {
+        let path = resolve.await;
+        let _ = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.initial_task = None);
+            start_router_at(cx, path);
+            refresh_windows(cx);
+        });
+    });
+    cx.update_global::<GlobalRouter, _>(|router, _| {
+        router.initial_task = Some(Arc::new(task));
+    });
+}
+
+/// Put `fragment` back on `path`
+fn with_fragment(path: String, fragment: Option<String>) -> String {
+    match fragment {
+        Some(fragment) => format!("{}#{}", path, fragment),
+        None => path,
+    }
+}
+
+/// Start the router at `path` and report a refusal
+fn start_router_at(cx: &mut App, path: String) {
+    cx.update_global::<GlobalRouter, _>(|router, cx| router.start_at(cx, path));
+    crate::error::report_refused_navigations(cx);
+}
+
+/// Mark an outlet as building a route, or done, returning the previous flag
+///
+/// Done with the outermost route, navigations the builders made are applied
+/// by a deferred task, or turned into a panic, following
+/// [`RouterConfig::on_render_navigation`].
+pub fn set_in_render(cx: &mut App, in_render: bool) -> bool {
+    if !cx.has_global::<GlobalRouter>() {
+        return false;
+    }
+    let (was_rendering, policy, targets) = cx.update_global::<GlobalRouter, _>(|router, _| {
+        let was_rendering = router.state.set_in_render(in_render);
+        let done = !in_render && !router.render_flush_scheduled;
+        let targets: Vec<String> = if done {
+            router
+                .render_navigations
+                .iter()
+                .map(|(target, _)| target.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        (was_rendering, router.config.render_navigation, targets)
+    });
+    if targets.is_empty() {
+        return was_rendering;
+    }
+
+    if policy == RenderNavigationPolicy::Panic {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.render_navigations.clear());
+        panic!(
+            "Route builder navigated to '{}' while rendering; navigate from a guard or \
+             lifecycle hook instead",
+            targets.join("', '")
+        );
+    }
+    cx.update_global::<GlobalRouter, _>(|router, _| router.render_flush_scheduled = true);
+    cx.defer(apply_render_navigations);
+    was_rendering
+}
+
+/// Apply the navigations route builders made during the last render
+fn apply_render_navigations(cx: &mut App) {
+    let navigations = cx.update_global::<GlobalRouter, _>(|router, _| {
+        router.render_flush_scheduled = false;
+        std::mem::take(&mut router.render_navigations)
+    });
+    for (_, navigation) in navigations {
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            router.apply_render_navigation(cx, navigation);
+        });
+    }
+    crate::error::report_refused_navigations(cx);
+    refresh_windows(cx);
+}
+
+/// Navigate to a path using global router
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::navigate;
+///
+/// // In any component with access to App
+/// navigate(cx, "/users/123");
+/// ```
+pub fn navigate(cx: &mut App, path: impl Into<String>) {
+    Navigator::push(cx, path.into());
+}
+
+/// Get current path from global router
+#[deprecated(
+    since = "0.1.4",
+    note = "use `Navigator::current_path`, which returns a `SharedString` without copying the path"
+)]
+pub fn current_path(cx: &App) -> String {
+    cx.router().current_path().to_string()
+}
+
+/// Handle for Navigator.of(context) pattern
+///
+/// Collects chained navigation calls and applies them together when the
+/// handle is committed ([`NavigatorHandle::commit`], [`NavigatorHandle::events`])
+/// or dropped. History goes through every step, but observers see only the
+/// final state, so outlets never render the routes passed through:
+///
+/// - one [`RouteChangeEvent`] describes the chain, from the path before it to
+///   the path after it, and the global middleware sees a single navigation;
+/// - only the synchronous guards of the final destination run: if they deny,
+///   or one of them is not synchronous, nothing is applied and the
+///   navigation is refused like
+///   [`Navigator::try_push`]; if they redirect, the redirect target replaces
+///   the destination.
+///
+/// [`NavigatorHandle::commit_each`] applies each step right away instead, as
+/// separate navigations.
+pub struct NavigatorHandle<'a, C: BorrowMut<App>> {
+    cx: &'a mut C,
+    /// Steps not applied yet
+    pending: Vec<ChainStep>,
+    /// Whether steps are applied as they are called
+    each: bool,
+    events: Vec<RouteChangeEvent>,
+}
+
+/// A navigation chained on a [`NavigatorHandle`]
+#[derive(Clone)]
+enum ChainStep {
+    Push(Arc<RouteDescriptor>),
+    Replace(Arc<RouteDescriptor>),
+    Pop,
+    Forward,
+}
+
+impl<C: BorrowMut<App>> NavigatorHandle<'_, C> {
+    /// Navigate to a new path
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, PageRoute};
+    ///
+    /// // Simple path
+    /// Navigator::of(cx).push("/users");
+    ///
+    /// // With PageRoute
+    /// Navigator::of(cx).push(PageRoute::builder("/users/:id", |_, _cx, _params| gpui::div())
+    ///     .with_param("id".into(), "123".into()));
+    /// ```
+    pub fn push(self, route: impl IntoRoute) -> Self {
+        self.step(ChainStep::Push(Arc::new(route.into_route())))
+    }
+
+    /// Replace current path without adding to history
+    pub fn replace(self, route: impl IntoRoute) -> Self {
+        self.step(ChainStep::Replace(Arc::new(route.into_route())))
+    }
+
+    /// Go back to the previous route
+    ///
+    /// Skips entries committed by a guard redirect like [`Navigator::pop`].
+    /// Entries whose guards deny are skipped only by
+    /// [`commit_each`](Self::commit_each) chains; otherwise the guards of the
+    /// chain's final destination decide. Records no event if there is no
+    /// entry to go back to.
+    pub fn pop(self) -> Self {
+        self.step(ChainStep::Pop)
+    }
+
+    /// Go forward in history
+    ///
+    /// Records no event if there is no next entry.
+    pub fn forward(self) -> Self {
+        self.step(ChainStep::Forward)
+    }
+
+    /// Apply the steps chained so far as one navigation
+    ///
+    /// Steps chained afterwards form the next navigation. Dropping the handle
+    /// commits too.
+    pub fn commit(mut self) -> Self {
+        self.apply_pending();
+        self
+    }
+
+    /// Apply every step as its own navigation from now on
+    ///
+    /// Steps chained before are committed first.
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // Three navigations, three events
+    /// let events = Navigator::of(cx).commit_each().push("/a").push("/b").pop().events();
+    /// ```
+    pub fn commit_each(mut self) -> Self {
+        self.apply_pending();
+        self.each = true;
+        self
+    }
+
+    /// Events of the navigations made through this handle, in order
+    ///
+    /// Commits the steps chained so far first.
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let events = Navigator::of(cx).push("/users").push("/users/1").pop().events();
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(events[0].to, "/users");
+    /// ```
+    pub fn events(mut self) -> Vec<RouteChangeEvent> {
+        self.apply_pending();
+        std::mem::take(&mut self.events)
+    }
+
+    /// Get a handle for navigating from background tasks
+    ///
+    /// Commits the steps chained so far first. See [`GlobalRouter::handle`].
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let router = Navigator::of(cx).downgrade();
+    /// cx.background_spawn(async move {
+    ///     router.push("/conversations/42").await;
+    /// })
+    /// .detach();
+    /// ```
+    pub fn downgrade(mut self) -> RouterHandle {
+        self.apply_pending();
+        GlobalRouter::handle(self.cx.borrow_mut())
+    }
+
+    fn step(mut self, step: ChainStep) -> Self {
+        if self.each {
+            let event = self
+                .cx
+                .borrow_mut()
+                .update_global::<GlobalRouter, _>(|router, cx| router.apply_step(cx, step));
+            self.events.extend(event);
+        } else {
+            self.pending.push(step);
+        }
+        self
+    }
+
+    fn apply_pending(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let steps = std::mem::take(&mut self.pending);
+        let event = self
+            .cx
+            .borrow_mut()
+            .update_global::<GlobalRouter, _>(|router, cx| router.apply_chain(cx, steps));
+        self.events.extend(event);
+    }
+}
+
+impl<C: BorrowMut<App>> Drop for NavigatorHandle<'_, C> {
+    fn drop(&mut self) {
+        self.apply_pending();
+    }
+}
+
+/// Navigation through a window, see [`WindowExt::navigator`]
+pub struct WindowNavigator<'a> {
+    window: &'a mut Window,
+    cx: &'a mut App,
+}
+
+impl WindowNavigator<'_> {
+    /// Navigate to a new path, see [`Navigator::push_in`]
+    pub fn push(self, route: impl IntoRoute) -> PushOutcome {
+        Navigator::push_in(self.window, self.cx, route)
+    }
+
+    /// Replace the current path, see [`Navigator::replace_in`]
+    pub fn replace(self, route: impl IntoRoute) -> PushOutcome {
+        Navigator::replace_in(self.window, self.cx, route)
+    }
+
+    /// Go back, see [`Navigator::pop_in`]
+    pub fn pop(self) -> PopOutcome {
+        Navigator::pop_in(self.window, self.cx)
+    }
+
+    /// Go forward, see [`Navigator::forward_in`]
+    pub fn forward(self) -> PopOutcome {
+        Navigator::forward_in(self.window, self.cx)
+    }
+
+    /// Go to the history entry at `index`, see [`Navigator::go_to_index_in`]
+    pub fn go_to_index(self, index: usize) -> Option<RouteChangeEvent> {
+        Navigator::go_to_index_in(self.window, self.cx, index)
+    }
+
+    /// Navigate to a named route, see [`Navigator::push_named_in`]
+    pub fn push_named(self, name: &str, params: &RouteParams) -> NavigationResult {
+        Navigator::push_named_in(self.window, self.cx, name, params)
+    }
+}
+
+/// Navigation from a [`Window`]
+///
+/// For handlers that have the window and the app as separate borrows, where
+/// `Navigator::push(cx, ..)` does not resolve. The window is refreshed after
+/// each navigation.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui::*;
+/// use gpui_navigator::{GlobalRouter, WindowExt};
+///
+/// struct Settings;
+///
+/// impl Render for Settings {
+///     fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+///         div()
+///             .child(div().id("back").child("Back").on_click(|_, window, cx| {
+///                 window.navigator(cx).pop();
+///             }))
+///             .child(div().id("sync").child("Sync").on_click(cx.listener(
+///                 |_this, _event, _window, cx| {
+///                     // Off the main thread, through the async handle
+///                     let router = GlobalRouter::handle(cx);
+///                     cx.background_spawn(async move {
+///                         router.push("/settings/synced").await;
+///                     })
+///                     .detach();
+///                 },
+///             )))
+///     }
+/// }
+/// ```
+pub trait WindowExt {
+    /// Navigate from this window, refreshing it afterwards
+    fn navigator<'a>(&'a mut self, cx: &'a mut App) -> WindowNavigator<'a>;
+}
+
+impl WindowExt for Window {
+    fn navigator<'a>(&'a mut self, cx: &'a mut App) -> WindowNavigator<'a> {
+        WindowNavigator { window: self, cx }
+    }
+}
+
+/// Navigation API for convenient route navigation
+///
+/// Provides static methods for navigation operations:
+/// - `Navigator::push(cx, "/path")` - Navigate to a new page
+/// - `Navigator::pop(cx)` - Go back to previous page
+/// - `Navigator::replace(cx, "/path")` - Replace current page
+///
+/// Works with any context that has access to App (`Context<V>`, `App`, etc.)
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::Navigator;
+///
+/// // Navigate to a new route
+/// Navigator::push(cx, "/users/123");
+///
+/// // Go back
+/// Navigator::pop(cx);
+///
+/// // Replace current route
+/// Navigator::replace(cx, "/login");
+/// ```
+pub struct Navigator;
+
+impl Navigator {
+    /// Get a NavigatorHandle for the given context
+    ///
+    /// This allows chained navigation calls, applied as one navigation when
+    /// the handle is dropped (see [`NavigatorHandle`]):
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // Chained style
+    /// Navigator::of(cx).push("/users");
+    /// Navigator::of(cx).pop();
+    ///
+    /// // Or direct style (also works)
+    /// Navigator::push(cx, "/users");
+    /// Navigator::pop(cx);
+    /// ```
+    pub fn of<C: BorrowMut<App>>(cx: &mut C) -> NavigatorHandle<'_, C> {
+        NavigatorHandle {
+            cx,
+            pending: Vec::new(),
+            each: false,
+            events: Vec::new(),
+        }
+    }
+
+    /// Navigate to a new path
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, PageRoute};
+    ///
+    /// // Simple string path
+    /// Navigator::push(cx, "/users/123");
+    ///
+    /// // Sibling of the current route
+    /// Navigator::push(cx, "../settings");
+    ///
+    /// // With PageRoute and params
+    /// Navigator::push(cx, PageRoute::builder("/profile", |_, _cx, _params| gpui::div())
+    ///     .with_param("userId".into(), "456".into()));
+    /// ```
+    pub fn push(cx: &mut impl BorrowMut<App>, route: impl IntoRoute) -> PushOutcome {
+        Self::push_opts(cx, route, NavOptions::default())
+    }
+
+    /// Navigate to a new path, reporting whether it matches a route
+    ///
+    /// See [`GlobalRouter::try_push`]. Refused navigations are passed to the
+    /// router's `ErrorHandlers::on_refused` handler right away.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, NavigationResult};
+    ///
+    /// match Navigator::try_push(cx, "/users/42") {
+    ///     NavigationResult::Success { .. } => {}
+    ///     NavigationResult::Redirected { to, .. } => println!("redirected to {}", to),
+    ///     other => println!("navigation failed: {:?}", other),
+    /// }
+    /// ```
+    pub fn try_push(cx: &mut App, route: impl IntoRoute) -> NavigationResult {
+        let descriptor = route.into_route();
+        let result = cx.update_global::<GlobalRouter, _>(|router, cx| {
+            match router.target_path(&descriptor) {
+                Ok(path) => router.try_push(cx, path),
+                Err(error) => router.refuse_target(error),
+            }
+        });
+        crate::error::report_refused_navigations(cx);
+        result
+    }
+
+    /// Open a URL handed to the app from outside, such as a deep link
+    ///
+    /// The scheme and host are dropped, so `https://example.com/users/42`
+    /// and `myapp://open/users/42?tab=posts` open `/users/42` and
+    /// `/users/42?tab=posts`; a URL without `://` is taken as a path. Runs
+    /// like [`Navigator::try_push`], with [`NavigationSource::DeepLink`] as
+    /// the source guards and subscribers see.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // URLs from `Application::on_open_urls`, sent on to the app
+    /// for url in urls {
+    ///     Navigator::handle_external_url(cx, &url);
+    /// }
+    /// ```
+    pub fn handle_external_url(cx: &mut App, url: &str) -> NavigationResult {
+        let path = external_path(url);
+        let result = cx.update_global::<GlobalRouter, _>(|router, cx| {
+            router.with_source(NavigationSource::DeepLink, |router| {
+                router.try_push(cx, path)
+            })
+        });
+        crate::error::report_refused_navigations(cx);
+        result
+    }
+
+    /// Error of the last refused navigation
+    ///
+    /// Set when [`RouterConfig::strict_navigation`] refuses a path matching no
+    /// route, a guard denies a navigation or a named route cannot
+    /// be resolved; cleared by the next navigation that goes through.
+    pub fn last_error(cx: &App) -> Option<NavigationError> {
+        cx.global::<GlobalRouter>().last_error().cloned()
+    }
+
+    /// Readable report of the router's state, for bug reports
+    ///
+    /// Formats [`GlobalRouter::dump_state`], masking the values listed in
+    /// [`RouterConfig::redact_keys`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// eprintln!("{}", Navigator::dump(cx));
+    /// ```
+    pub fn dump(cx: &App) -> String {
+        cx.global::<GlobalRouter>().dump_state().to_string()
+    }
+
+    /// Replace current path without adding to history
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, PageRoute};
+    ///
+    /// // Simple string path
+    /// Navigator::replace(cx, "/login");
+    ///
+    /// // With PageRoute
+    /// Navigator::replace(cx, PageRoute::builder("/login", |_, _cx, _params| gpui::div())
+    ///     .with_param("redirect".into(), "/dashboard".into()));
+    /// ```
+    pub fn replace(cx: &mut impl BorrowMut<App>, route: impl IntoRoute) -> PushOutcome {
+        Self::replace_opts(cx, route, NavOptions::default())
+    }
+
+    /// Navigate to a new route with options for this navigation
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{NavOptions, Navigator, PreserveQuery};
+    ///
+    /// // Keep every query param of the current location
+    /// Navigator::push_opts(cx, "/reports", NavOptions::new().preserve_query(PreserveQuery::All));
+    /// ```
+    pub fn push_opts(
+        cx: &mut impl BorrowMut<App>,
+        route: impl IntoRoute,
+        options: NavOptions,
+    ) -> PushOutcome {
+        let descriptor = route.into_route();
+        cx.borrow_mut()
+            .update_global::<GlobalRouter, _>(|router, cx| {
+                router.navigate_in(cx, &descriptor, PendingCommit::Push, &options)
+            })
+    }
+
+    /// Replace current path with options for this navigation
+    pub fn replace_opts(
+        cx: &mut impl BorrowMut<App>,
+        route: impl IntoRoute,
+        options: NavOptions,
+    ) -> PushOutcome {
+        let descriptor = route.into_route();
+        cx.borrow_mut()
+            .update_global::<GlobalRouter, _>(|router, cx| {
+                router.navigate_in(cx, &descriptor, PendingCommit::Replace, &options)
+            })
+    }
+
+    /// Go back to the previous route
+    ///
+    /// Entries committed by a guard redirect, and entries whose guards now
+    /// deny, are skipped (see [`GlobalRouter::smart_back`]); use
+    /// [`Navigator::pop_raw`] to go back exactly one entry.
+    ///
+    /// With no earlier entry, the table set with
+    /// [`RouterConfig::back_at_root_to_table`] is activated, or else the
+    /// handler set with [`RouterConfig::on_back_at_root`] runs. Returns
+    /// [`PopOutcome::NoOp`] if neither applies or the navigation was
+    /// throttled.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, PopOutcome};
+    ///
+    /// if let PopOutcome::Navigated(event) = Navigator::pop(cx) {
+    ///     println!("Back to {}", event.to);
+    /// }
+    /// ```
+    pub fn pop(cx: &mut impl BorrowMut<App>) -> PopOutcome {
+        let cx = cx.borrow_mut();
+        if !Self::can_pop(cx) {
+            if let Some(outcome) = GlobalRouter::history_edge(cx, NavigationDirection::Back) {
+                return outcome;
+            }
+        }
+        cx.update_global::<GlobalRouter, _>(|router, cx| router.smart_back(cx))
+            .into()
+    }
+
+    /// Go back exactly one history entry, whatever it is
+    ///
+    /// Unlike [`Navigator::pop`], redirect entries are not skipped.
+    pub fn pop_raw(cx: &mut impl BorrowMut<App>) -> Option<RouteChangeEvent> {
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let previous = router.state.history().current_index().checked_sub(1)?;
+            router.travel_to(cx.borrow_mut(), previous)
+        })
+    }
+
+    /// Alias for pop() - go back (kept for compatibility)
+    pub fn back(cx: &mut impl BorrowMut<App>) -> PopOutcome {
+        Self::pop(cx)
+    }
+
+    /// Go forward in history
+    ///
+    /// With no next entry, the handler set with
+    /// [`RouterConfig::on_forward_at_end`] runs. Returns
+    /// [`PopOutcome::NoOp`] if there is none or the navigation was
+    /// throttled.
+    pub fn forward(cx: &mut impl BorrowMut<App>) -> PopOutcome {
+        let cx = cx.borrow_mut();
+        if !Self::can_go_forward(cx) {
+            if let Some(outcome) = GlobalRouter::history_edge(cx, NavigationDirection::Forward) {
+                return outcome;
+            }
+        }
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let next = router.state.history().current_index() + 1;
+            router.travel_to(cx, next)
+        })
+        .into()
+    }
+
+    /// Go to the history entry at `index`, back or forward
+    ///
+    /// See [`GlobalRouter::travel_to`].
+    pub fn go_to_index(cx: &mut impl BorrowMut<App>, index: usize) -> Option<RouteChangeEvent> {
+        cx.update_global::<GlobalRouter, _>(|router, cx| router.travel_to(cx.borrow_mut(), index))
+    }
+
+    /// Navigate to a new path from a window's event handler
+    ///
+    /// Like [`Navigator::push`], taking the window and the app separately as
+    /// `on_click` and `cx.listener` handlers get them, and refreshing the
+    /// window so the new route renders in the next frame.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui::*;
+    /// use gpui_navigator::Navigator;
+    ///
+    /// struct Inbox;
+    ///
+    /// impl Render for Inbox {
+    ///     fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+    ///         div()
+    ///             .child(div().id("compose").child("Compose").on_click(|_, window, cx| {
+    ///                 Navigator::push_in(window, cx, "/compose");
+    ///             }))
+    ///             .child(div().id("sent").child("Sent").on_click(cx.listener(
+    ///                 |_this, _event, window, cx| {
+    ///                     Navigator::push_in(window, cx, "/sent");
+    ///                 },
+    ///             )))
+    ///     }
+    /// }
+    /// ```
+    pub fn push_in(window: &mut Window, cx: &mut App, route: impl IntoRoute) -> PushOutcome {
+        let outcome = Self::push(cx, route);
+        window.refresh();
+        outcome
+    }
+
+    /// Replace the current path from a window's event handler
+    ///
+    /// See [`Navigator::push_in`].
+    pub fn replace_in(window: &mut Window, cx: &mut App, route: impl IntoRoute) -> PushOutcome {
+        let outcome = Self::replace(cx, route);
+        window.refresh();
+        outcome
+    }
+
+    /// Go back from a window's event handler, like [`Navigator::pop`]
+    pub fn pop_in(window: &mut Window, cx: &mut App) -> PopOutcome {
+        let outcome = Self::pop(cx);
+        window.refresh();
+        outcome
+    }
+
+    /// Go forward from a window's event handler, like [`Navigator::forward`]
+    pub fn forward_in(window: &mut Window, cx: &mut App) -> PopOutcome {
+        let outcome = Self::forward(cx);
+        window.refresh();
+        outcome
+    }
+
+    /// Go to the history entry at `index` from a window's event handler,
+    /// like [`Navigator::go_to_index`]
+    pub fn go_to_index_in(
+        window: &mut Window,
+        cx: &mut App,
+        index: usize,
+    ) -> Option<RouteChangeEvent> {
+        let event = Self::go_to_index(cx, index);
+        window.refresh();
+        event
+    }
+
+    /// Navigate to a named route from a window's event handler, like
+    /// [`Navigator::push_named`]
+    pub fn push_named_in(
+        window: &mut Window,
+        cx: &mut App,
+        name: &str,
+        params: &RouteParams,
+    ) -> NavigationResult {
+        let result = Self::push_named(cx, name, params);
+        window.refresh();
+        result
+    }
+
+    /// Apply the navigations made in `navigate` as one
+    ///
+    /// They show without transitions, and the middleware sees a single
+    /// navigation from the path before to the path after; see
+    /// [`GlobalRouter::begin_batch`]. Returns that navigation, or `None` if
+    /// nothing was navigated.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // Restore the session, then apply the deep link, in one go
+    /// Navigator::batch(cx, |cx| {
+    ///     Navigator::push(cx, "/projects/7");
+    ///     Navigator::push(cx, "/projects/7/issues/42");
+    /// });
+    /// ```
+    pub fn batch<C: BorrowAppContext>(
+        cx: &mut C,
+        navigate: impl FnOnce(&mut C),
+    ) -> Option<RouteChangeEvent> {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.begin_batch());
+        navigate(cx);
+        cx.update_global::<GlobalRouter, _>(|router, _| router.end_batch())
+    }
+
+    /// Get current path
+    ///
+    /// Works with `Context<V>` since it derefs to App
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let path = Navigator::current_path(cx);
+    /// ```
+    ///
+    /// Cloning the returned [`SharedString`] does not copy the path; use
+    /// [`Navigator::with_current_path`] to only look at it.
+    pub fn current_path(cx: &App) -> SharedString {
+        cx.global::<GlobalRouter>().current_path_shared().clone()
+    }
+
+    /// Get current path as an owned `String`
+    #[deprecated(
+        since = "0.1.4",
+        note = "use `current_path`, which returns a `SharedString` without copying the path"
+    )]
+    pub fn current_path_string(cx: &App) -> String {
+        Self::current_path(cx).to_string()
+    }
+
+    /// Call `f` with the current path, borrowed from the router
+    ///
+    /// For reads in render code, such as highlighting the current nav item.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let in_settings = Navigator::with_current_path(cx, |path| path.starts_with("/settings"));
+    /// ```
+    pub fn with_current_path<R>(cx: &App, f: impl FnOnce(&str) -> R) -> R {
+        f(cx.global::<GlobalRouter>().current_path())
+    }
+
+    /// Token of the navigation that showed the current page
+    ///
+    /// Tie work the page starts to it: the token is cancelled once another
+    /// navigation commits or the app quits. See [`crate::cancel`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let token = Navigator::current_navigation_token(cx);
+    /// cx.spawn(async move |_| {
+    ///     let results = search(query);
+    ///     futures::select! {
+    ///         results = results.fuse() => show(results),
+    ///         _ = token.cancelled().fuse() => {}
+    ///     }
+    /// })
+    /// .detach();
+    /// ```
+    pub fn current_navigation_token(cx: &App) -> NavigationToken {
+        cx.global::<GlobalRouter>().navigation_token().clone()
+    }
+
+    /// Fragment of the current location, without the `#`
+    ///
+    /// Pages with in-page anchors scroll to it after a navigation, see
+    /// [`RouteChangeEvent::fragment_only`](crate::RouteChangeEvent::fragment_only).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // After Navigator::push(cx, "/docs/install#requirements")
+    /// assert_eq!(Navigator::current_fragment(cx).as_deref(), Some("requirements"));
+    /// ```
+    pub fn current_fragment(cx: &App) -> Option<String> {
+        cx.global::<GlobalRouter>()
+            .current_fragment()
+            .map(str::to_string)
+    }
+
+    /// Typed meta of type `T` of the current route, inherited from its
+    /// parents unless it sets its own
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, RouteTitle};
+    ///
+    /// let title = Navigator::current_meta_typed::<RouteTitle>(cx).map(|title| title.0);
+    /// ```
+    pub fn current_meta_typed<T: MetaValue>(cx: &App) -> Option<T> {
+        cx.global::<GlobalRouter>().current_meta_typed().cloned()
+    }
+
+    /// Show `path` in the named outlets called `name` only
+    ///
+    /// See [`GlobalRouter::set_outlet_path`]. A [`SplitRouterView`] redraws
+    /// just the pane showing the outlet.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // The list pane stays as it is
+    /// Navigator::set_outlet(cx, "detail", "items/42");
+    /// ```
+    ///
+    /// [`SplitRouterView`]: crate::SplitRouterView
+    pub fn set_outlet(
+        cx: &mut impl BorrowAppContext,
+        name: impl Into<String>,
+        path: impl Into<String>,
+    ) {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.set_outlet_path(name, path));
+    }
+
+    /// Path shown by the named outlets called `name`, if one was set
+    pub fn outlet_path(cx: &App, name: &str) -> Option<String> {
+        cx.global::<GlobalRouter>()
+            .outlet_path(name)
+            .map(str::to_string)
+    }
+
+    /// Make the named outlets called `name` follow the current path again
+    pub fn clear_outlet(cx: &mut impl BorrowAppContext, name: &str) {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.clear_outlet_path(name));
+    }
+
+    /// Check whether the current user may navigate to `path`
+    ///
+    /// Runs the synchronous guards of the matching route (and its parents)
+    /// without navigating. Useful for hiding or disabling menu entries.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{AccessCheck, Navigator};
+    ///
+    /// if !Navigator::can_access(cx, "/admin").is_blocked() {
+    ///     // render the admin menu entry
+    /// }
+    /// ```
+    #[cfg(feature = "guard")]
+    pub fn can_access(cx: &App, path: impl AsRef<str>) -> AccessCheck {
+        cx.global::<GlobalRouter>().can_access(cx, path.as_ref())
+    }
+
+    /// Drop guard results remembered for route param `key` set to `value`
+    ///
+    /// See [`GlobalRouter::invalidate_guard_scope`].
+    #[cfg(feature = "guard")]
+    pub fn invalidate_guard_scope(cx: &App, key: &str, value: &str) {
+        cx.global::<GlobalRouter>()
+            .invalidate_guard_scope(key, value);
+    }
+
+    /// Mark the current route as having unsaved changes, or not
+    ///
+    /// Navigating away from a dirty route with [`Route::confirm_leave`] is
+    /// held until the user confirms; see
+    /// [`ConfirmLeaveGuard`](crate::ConfirmLeaveGuard). The flag is cleared
+    /// when the router navigates to another path.
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // In the text field's change handler
+    /// Navigator::set_dirty(cx, draft != saved);
+    /// ```
+    pub fn set_dirty(cx: &mut impl BorrowMut<App>, dirty: bool) {
+        let cx = cx.borrow_mut();
+        if cx.global::<GlobalRouter>().state.is_dirty() != dirty {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.state.set_dirty(dirty));
+        }
+    }
+
+    /// Whether the current route has unsaved changes
+    pub fn is_dirty(cx: &App) -> bool {
+        cx.global::<GlobalRouter>().state.is_dirty()
+    }
+
+    /// Ask before closing `window` while the current route is dirty
+    ///
+    /// Closing the window is held like a navigation away from the route
+    /// (see [`ConfirmLeaveGuard`](crate::ConfirmLeaveGuard)); the window
+    /// closes once leaving is confirmed. Replaces the window's
+    /// `on_window_should_close` callback.
+    ///
+    /// ```ignore
+    /// cx.open_window(options, |window, cx| {
+    ///     Navigator::confirm_leave_on_close(window, cx);
+    ///     cx.new(|_| AppView::new())
+    /// });
+    /// ```
+    #[cfg(feature = "guard")]
+    pub fn confirm_leave_on_close(window: &Window, cx: &App) {
+        window.on_window_should_close(cx, |window, cx| {
+            let handle = window.window_handle();
+            let held = cx.has_global::<GlobalRouter>()
+                && cx.update_global::<GlobalRouter, _>(|router, _| router.hold_close(handle));
+            if held {
+                crate::guards::prompt_leave_confirmation(cx);
+                crate::error::report_refused_navigations(cx);
+            }
+            !held
+        });
+    }
+
+    /// Check if can go back
+    pub fn can_pop(cx: &App) -> bool {
+        cx.global::<GlobalRouter>().can_go_back()
+    }
+
+    /// Alias for can_pop() - check if can go back (kept for compatibility)
+    pub fn can_go_back(cx: &App) -> bool {
+        Self::can_pop(cx)
+    }
+
+    /// Navigate to a named route with parameters
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, RouteParams};
+    ///
+    /// let params = RouteParams::single("id", "123");
+    ///
+    /// Navigator::push_named(cx, "user.detail", &params);
+    /// ```
+    pub fn push_named(
+        cx: &mut impl BorrowMut<App>,
+        name: &str,
+        params: &RouteParams,
+    ) -> NavigationResult {
+        let cx = cx.borrow_mut();
+        let result = cx.update_global::<GlobalRouter, _>(|router, cx| {
+            router.push_named_with(Some(cx), name, params)
+        });
+        crate::error::report_refused_navigations(cx);
+        result
+    }
+
+    /// Guards and middleware that apply to navigations to `path`
+    ///
+    /// See [`GlobalRouter::explain`].
+    #[cfg(any(feature = "guard", feature = "middleware"))]
+    pub fn explain(cx: &App, path: &str) -> RouteExplanation {
+        cx.global::<GlobalRouter>().explain(path)
+    }
+
+    /// Commands a command palette can offer for the active route table
+    ///
+    /// See [`GlobalRouter::navigation_commands`].
+    pub fn navigation_commands(cx: &App) -> Vec<NavigationCommand> {
+        cx.global::<GlobalRouter>().navigation_commands()
+    }
+
+    /// Navigate to the route of a palette command
+    ///
+    /// See [`GlobalRouter::run_command`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, RouteParams};
+    ///
+    /// let best = Navigator::fuzzy_match(cx, "open user").into_iter().next();
+    /// if let Some(best) = best.filter(|best| !best.command.needs_input) {
+    ///     Navigator::run_command(cx, &best.command, &RouteParams::new());
+    /// }
+    /// ```
+    pub fn run_command(
+        cx: &mut impl BorrowAppContext,
+        command: &NavigationCommand,
+        params: &RouteParams,
+    ) -> NavigationResult {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.run_command(command, params))
+    }
+
+    /// Navigation commands whose label matches `query`, best first
+    ///
+    /// See [`GlobalRouter::fuzzy_match`].
+    pub fn fuzzy_match(cx: &App, query: &str) -> Vec<ScoredCommand> {
+        cx.global::<GlobalRouter>().fuzzy_match(query)
+    }
+
+    /// Generate URL for a named route
+    ///
+    /// See [`GlobalRouter::try_url_for`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, RouteParams};
+    ///
+    /// let params = RouteParams::single("id", "123");
+    ///
+    /// let url = Navigator::try_url_for(cx, "user.detail", &params);
+    /// assert_eq!(url, Ok("/users/123".to_string()));
+    /// ```
+    pub fn try_url_for(cx: &App, name: &str, params: &RouteParams) -> Result<String, UrlForError> {
+        cx.global::<GlobalRouter>().try_url_for(name, params)
+    }
+
+    /// Generate URL for a named route
+    ///
+    /// Returns `None` where [`Navigator::try_url_for`] fails.
+    #[deprecated(
+        since = "0.1.4",
+        note = "use `try_url_for`, which says why no URL was generated"
+    )]
+    pub fn url_for(cx: &App, name: &str, params: &RouteParams) -> Option<String> {
+        Self::try_url_for(cx, name, params).ok()
+    }
+
+    /// Generate URL for a named route in `locale`
+    ///
+    /// See [`GlobalRouter::url_for_locale`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, RouteParams};
+    ///
+    /// let params = RouteParams::from_map([("id".to_string(), "7".to_string())].into());
+    /// // "/de/benutzer/7" for a route registered as `/users/:id` with
+    /// // `.localized_path("de", "/benutzer/:id")`
+    /// let url = Navigator::url_for_locale(cx, "user.detail", &params, "de");
+    /// ```
+    pub fn url_for_locale(
+        cx: &App,
+        name: &str,
+        params: &RouteParams,
+        locale: &str,
+    ) -> Option<String> {
+        cx.global::<GlobalRouter>()
+            .url_for_locale(name, params, locale)
+    }
+
+    /// Current locale, `None` unless [`RouterConfig::locales`] is set
+    pub fn locale(cx: &App) -> Option<String> {
+        cx.global::<GlobalRouter>().locale().map(str::to_string)
+    }
+
+    /// Current path as written in the current locale
+    ///
+    /// See [`GlobalRouter::localized_path`].
+    pub fn localized_path(cx: &App) -> String {
+        cx.global::<GlobalRouter>().localized_path()
+    }
+
+    /// Switch to `locale`, replacing the current entry
+    ///
+    /// The current route stays, with its params and query. See
+    /// [`GlobalRouter::set_locale`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // On `/en/users/7?tab=posts`
+    /// Navigator::set_locale(cx, "de");
+    /// assert_eq!(Navigator::localized_path(cx), "/de/benutzer/7?tab=posts");
+    /// ```
+    pub fn set_locale(cx: &mut impl BorrowAppContext, locale: &str) -> PushOutcome {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.set_locale(locale))
+    }
+
+    /// Time spent on the current route since its history entry became current
+    ///
+    /// Going back or forward to an entry starts over; a replace starts a new
+    /// entry. Measures wall time unless paused with
+    /// [`GlobalRouter::pause_dwell`].
+    pub fn time_on_current_route(cx: &App) -> Duration {
+        cx.global::<GlobalRouter>()
+            .state()
+            .history()
+            .current_dwell()
+    }
+
+    /// Check if can go forward
+    pub fn can_go_forward(cx: &App) -> bool {
+        cx.global::<GlobalRouter>().can_go_forward()
+    }
+
+    /// Open `path` with the router's new window handler
+    ///
+    /// Returns `false` without a handler (see
+    /// [`GlobalRouter::set_new_window_handler`]).
+    pub fn open_in_new_window(cx: &mut App, path: &str) -> bool {
+        let Some(handler) = cx
+            .try_global::<GlobalRouter>()
+            .and_then(|router| router.new_window_handler().cloned())
+        else {
+            return false;
+        };
+        handler(path, cx);
+        true
+    }
+
+    /// Check if the current path is within `pattern`
+    ///
+    /// The pattern uses route syntax (`:param`, constraints like
+    /// `:id{int}` and `*`) and is matched against the leading segments of
+    /// the current path, so `/settings` is active on `/settings/profile`.
+    /// Trailing slashes and the query string are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let show_badge = Navigator::is_active(cx, "/inbox/:threadId");
+    /// ```
+    pub fn is_active(cx: &App, pattern: &str) -> bool {
+        cx.global::<GlobalRouter>().is_current(pattern, true)
+    }
+
+    /// Resolve a relative path against the current route
+    ///
+    /// See [`GlobalRouter::resolve_path`]. Absolute paths are returned as
+    /// they are.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::{NavOptions, Navigator};
+    ///
+    /// // On /shops/5/items/9
+    /// assert_eq!(Navigator::resolve_path(cx, "../reviews", &NavOptions::new()), "/shops/5/reviews");
+    /// ```
+    pub fn resolve_path(cx: &App, path: &str, options: &NavOptions) -> String {
+        cx.global::<GlobalRouter>().resolve_path(path, options)
+    }
+
+    /// Check if the current path matches `pattern` entirely
+    ///
+    /// Like [`Navigator::is_active`], but `/settings` is not active on
+    /// `/settings/profile`.
+    pub fn is_active_exact(cx: &App, pattern: &str) -> bool {
+        cx.global::<GlobalRouter>().is_current(pattern, false)
+    }
+
+    /// Match the current path against `pattern`, extracting its params
+    ///
+    /// The pattern does not have to be a registered route. It must match the
+    /// whole path; end it with `*` to allow more segments.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// if let Some(m) = Navigator::matched_against(cx, "/inbox/:threadId/*") {
+    ///     let thread = m.params.get("threadId");
+    /// }
+    /// ```
+    pub fn matched_against(cx: &App, pattern: &str) -> Option<crate::RouteMatch> {
+        cx.global::<GlobalRouter>().match_current(pattern, false)
+    }
+
+    /// State of type `T` scoped to the current route, created on first use
+    ///
+    /// Looks for the innermost route in the matched chain that registered a
+    /// `T` with [`Route::state`], so nested routes see their parents' state.
+    /// The value is kept while that route stays matched with the same params
+    /// and dropped when navigation leaves it. Returns `None` if no route in
+    /// the chain has a `T`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let draft = Navigator::route_state::<Draft>(cx).unwrap();
+    /// draft.update(cx, |draft, _| draft.text.push_str("Hi"));
+    /// ```
+    pub fn route_state<T: 'static>(cx: &mut App) -> Option<Entity<T>> {
+        crate::scope::route_state(cx)
+    }
+
+    /// Drop the kept-alive components of the routes matching `path_or_pattern`
+    ///
+    /// Matches the part of the path a route matched (`/documents/7`, that
+    /// instance only) or its full pattern (`/documents/:id`, all of them).
+    /// Their routes' `on_evict` callbacks run, and the components on screen
+    /// are left alone. Returns the number of components dropped. See the
+    /// [`keep_alive`](crate::keep_alive) module.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // The document is gone, so is its editor
+    /// Navigator::drop_kept_alive(cx, &format!("/documents/{}", id));
+    /// ```
+    pub fn drop_kept_alive(cx: &mut App, path_or_pattern: &str) -> usize {
+        let dropped = cx
+            .update_global::<GlobalRouter, _>(|router, _| router.drop_kept_alive(path_or_pattern));
+        crate::keep_alive::run_evict_callbacks(cx);
+        dropped
+    }
+
+    /// Progress through the flow called `flow`
+    ///
+    /// `None` until one of its steps is shown. See the [`flow`](crate::flow)
+    /// module.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// let reached = Navigator::flow_state(cx, "checkout").map_or(0, |state| state.highest);
+    /// ```
+    pub fn flow_state(cx: &App, flow: &str) -> Option<FlowState> {
+        cx.global::<GlobalRouter>().flow_state(flow).cloned()
+    }
+
+    /// Push the step of `flow` following the current one
+    ///
+    /// The step's params are taken from the current path. Returns `None`
+    /// outside the flow or on its last step.
+    pub fn flow_next(cx: &mut impl BorrowMut<App>, flow: &str) -> Option<RouteChangeEvent> {
+        Self::push_flow_step(cx, flow, true)
+    }
+
+    /// Push the step of `flow` before the current one
+    ///
+    /// Unlike [`Navigator::pop`], this follows the flow's ordinals rather
+    /// than history. Returns `None` outside the flow or on its first step.
+    pub fn flow_prev(cx: &mut impl BorrowMut<App>, flow: &str) -> Option<RouteChangeEvent> {
+        Self::push_flow_step(cx, flow, false)
+    }
+
+    fn push_flow_step(
+        cx: &mut impl BorrowMut<App>,
+        flow: &str,
+        forward: bool,
+    ) -> Option<RouteChangeEvent> {
+        cx.borrow_mut()
+            .update_global::<GlobalRouter, _>(|router, cx| {
+                let path = router.flow_step_path(flow, forward)?;
+                let options = NavOptions::default();
+                router
+                    .navigate_opts(Some(cx), PendingCommit::Push, path, &options)
+                    .into_outcome()
+                    .into_event()
+            })
+    }
+
+    /// How the route params changed with the current navigation
+    ///
+    /// Compares the params before and after the last navigation, also across
+    /// routes. Returns `None` before the first navigation.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// if Navigator::params_changed(cx).is_some_and(|diff| diff.contains("fileId")) {
+    ///     // reload the open file
+    /// }
+    /// ```
+    pub fn params_changed(cx: &App) -> Option<ParamsDiff> {
+        cx.global::<GlobalRouter>().params_diff()
+    }
+
+    /// Params of the current path introduced by each level of the matched
+    /// route chain, outermost first
+    ///
+    /// See [`RouterState::params_by_level`]. Builders still get the params
+    /// of their own level and every level above; these are for keying
+    /// caches on a level's own inputs only.
+    pub fn params_by_level(cx: &App) -> Vec<RouteParams> {
+        cx.global::<GlobalRouter>().params_by_level()
+    }
+
+    /// Params introduced by the route at `depth` of the matched chain
+    ///
+    /// Depth 0 is the top-level route; pathless layouts count as a level.
+    /// Empty when the chain is not that deep.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// // In the `/orgs/:orgId` layout, on /orgs/7/members/3: `{orgId: 7}`
+    /// let own = Navigator::params_for_level(cx, 0);
+    /// let summary = org_summaries.entry(own.get("orgId").cloned()).or_insert_with(load);
+    /// ```
+    pub fn params_for_level(cx: &App, depth: usize) -> RouteParams {
+        Self::params_by_level(cx)
+            .into_iter()
+            .nth(depth)
+            .unwrap_or_default()
+    }
+
+    /// Params introduced by the deepest matched route only
+    ///
+    /// On `/orgs/7/members/3` that is `memberId` without the `orgId` of the
+    /// layout above. Empty if no route matches.
+    pub fn own_params(cx: &App) -> RouteParams {
+        Self::params_by_level(cx).pop().unwrap_or_default()
+    }
+
+    /// Call `callback` with the old and new value whenever the `key` param changes
+    ///
+    /// The callback runs after the navigation that changed the param, including
+    /// navigations that add or remove it. Dropping the returned subscription
+    /// stops the observation.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use gpui_navigator::Navigator;
+    ///
+    /// Navigator::observe_param(cx, "fileId", |old, new, cx| {
+    ///     println!("switched from {:?} to {:?}", old, new);
+    /// })
+    /// .detach();
+    /// ```
+    pub fn observe_param<F>(cx: &mut App, key: impl Into<String>, callback: F) -> Subscription
+    where
+        F: Fn(Option<&str>, Option<&str>, &mut App) + 'static,
+    {
+        let key = key.into();
+        let param = move |cx: &App| {
+            cx.try_global::<GlobalRouter>()
+                .and_then(|router| router.state.current_params().get(&key).cloned())
+        };
+        let mut last = param(cx);
+
+        cx.observe_global::<GlobalRouter>(move |cx| {
+            let current = param(cx);
+            if current != last {
+                let old = std::mem::replace(&mut last, current.clone());
+                callback(old.as_deref(), current.as_deref(), cx);
+            }
+        })
+    }
+
+    /// Set transition for the next navigation
+    ///
+    /// The transition will be used for the next push/replace call,
+    /// then automatically cleared.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, Transition};
+    ///
+    /// Navigator::set_next_transition(cx, Transition::fade(300));
+    /// Navigator::push(cx, "/page");
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn set_next_transition(cx: &mut impl BorrowAppContext, transition: Transition) {
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.set_next_transition(transition);
+        });
+    }
+
+    /// Navigate with a specific transition
+    ///
+    /// # Example
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, Transition};
+    ///
+    /// Navigator::push_with_transition(cx, "/page", Transition::slide_left(300));
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn push_with_transition(
+        cx: &mut impl BorrowAppContext,
+        route: impl IntoRoute,
+        transition: Transition,
+    ) -> PushOutcome {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.navigate_to(&descriptor, |router, path| {
+                router.push_with_transition(path, transition)
+            })
+        })
+    }
+
+    /// Replace with a specific transition
+    ///
+    /// # Example
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, Transition};
+    ///
+    /// Navigator::replace_with_transition(cx, "/page", Transition::fade(200));
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn replace_with_transition(
+        cx: &mut impl BorrowAppContext,
+        route: impl IntoRoute,
+        transition: Transition,
+    ) -> PushOutcome {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.navigate_to(&descriptor, |router, path| {
+                router.replace_with_transition(path, transition)
+            })
+        })
+    }
+
+    /// Push named route with a specific transition
+    ///
+    /// # Example
+    /// ```ignore
+    /// use gpui_navigator::{Navigator, RouteParams, Transition};
+    ///
+    /// let params = RouteParams::single("id", "123");
+    /// Navigator::push_named_with_transition(
+    ///     cx,
+    ///     "user.detail",
+    ///     &params,
+    ///     Transition::slide_right(300)
+    /// );
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn push_named_with_transition(
+        cx: &mut impl BorrowAppContext,
+        name: &str,
+        params: &RouteParams,
+        transition: Transition,
+    ) -> NavigationResult {
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.set_next_transition(transition);
+            let result = router.push_named(name, params);
+            if !result.is_success() {
+                router.clear_next_transition();
+            }
+            result
+        })
+    }
+}
+
+/// Path of the app an external URL points to, dropping its scheme and host
+fn external_path(url: &str) -> String {
+    let Some((_, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    match rest.find(['/', '?', '#']) {
+        Some(start) if rest[start..].starts_with('/') => rest[start..].to_string(),
+        Some(start) => format!("/{}", &rest[start..]),
+        None => "/".to_string(),
+    }
+}
+
+/// Full path of `child`, a child of the route whose full path is
+/// `parent_path`
+#[cfg(feature = "cache")]
+fn child_full_path(parent_path: &str, child: &Route) -> String {
+    if is_absolute_child(parent_path, &child.config.path) {
+        child.config.path.clone()
+    } else {
+        build_child_path(parent_path, &child.config.path).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::page;
+    use gpui::{IntoElement, TestAppContext};
+
+    #[gpui::test]
+    fn test_nav_push(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/users", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Test initial state
+        let initial_path = cx.read(Navigator::current_path);
+        assert_eq!(initial_path, "/");
+
+        // Test push navigation; the first one comes from the initial path
+        let event = cx
+            .update(|cx| Navigator::push(cx, "/users"))
+            .into_event()
+            .unwrap();
+        assert_eq!(event.from.as_deref(), Some("/"));
+        assert_eq!(event.to, "/users");
+        assert_eq!(event.direction, NavigationDirection::Forward);
+
+        let current_path = cx.read(Navigator::current_path);
+        assert_eq!(current_path, "/users");
+
+        // Test push with parameters
+        let event = cx
+            .update(|cx| Navigator::push(cx, "/users/123"))
+            .into_event()
+            .unwrap();
+        assert_eq!(event.from.as_deref(), Some("/users"));
+        assert_eq!(event.to, "/users/123");
+
+        let current_path = cx.read(Navigator::current_path);
+        assert_eq!(current_path, "/users/123");
+    }
+
+    #[gpui::test]
+    fn test_nav_back_forward(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/page1", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/page2", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Navigate to multiple pages
+        cx.update(|cx| {
+            Navigator::push(cx, "/page1");
+            Navigator::push(cx, "/page2");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/page2");
+        assert!(cx.read(Navigator::can_pop));
+
+        // Test back navigation
+        let event = cx.update(Navigator::pop).into_event().unwrap();
+        assert_eq!(event.from.as_deref(), Some("/page2"));
+        assert_eq!(event.to, "/page1");
+        assert_eq!(event.direction, NavigationDirection::Back);
+
+        assert_eq!(cx.read(Navigator::current_path), "/page1");
+        assert!(cx.read(Navigator::can_pop));
+        assert!(cx.read(Navigator::can_go_forward));
+
+        // Test forward navigation
+        let event = cx.update(Navigator::forward).into_event().unwrap();
+        assert_eq!(event.from.as_deref(), Some("/page1"));
+        assert_eq!(event.to, "/page2");
+        assert_eq!(event.direction, NavigationDirection::Forward);
+
+        assert_eq!(cx.read(Navigator::current_path), "/page2");
+        assert!(!cx.read(Navigator::can_go_forward));
+
+        // Nothing to go forward to
+        assert!(cx.update(Navigator::forward).into_event().is_none());
+    }
+
+    #[gpui::test]
+    fn test_nav_fragments(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| {
+            let checks = Arc::clone(&checks);
+            init_router(cx, move |router| {
+                router.add_route(page("/"));
+                let docs = page("/docs/:page");
+                #[cfg(feature = "guard")]
+                let docs = docs.guard(crate::AuthGuard::new(
+                    move |_| {
+                        checks.fetch_add(1, Ordering::SeqCst);
+                        true
+                    },
+                    "/",
+                ));
+                #[cfg(not(feature = "guard"))]
+                let _ = checks;
+                router.add_route(docs);
+            });
+        });
+
+        assert!(cx
+            .update(|cx| Navigator::try_push(cx, "/docs/intro"))
+            .is_success());
+        let event = cx
+            .update(|cx| Navigator::push(cx, "/docs/install#requirements"))
+            .into_event()
+            .unwrap();
+        assert_eq!(event.to, "/docs/install");
+        assert_eq!(event.fragment.as_deref(), Some("requirements"));
+        assert!(!event.fragment_only);
+        assert_eq!(cx.read(Navigator::current_path), "/docs/install");
+        assert_eq!(
+            cx.read(Navigator::current_fragment).as_deref(),
+            Some("requirements")
+        );
+        let route_match = cx.update(|cx| cx.global_mut::<GlobalRouter>().current_match());
+        assert_eq!(
+            route_match.unwrap().fragment.as_deref(),
+            Some("requirements")
+        );
+        let checked = checks.load(Ordering::SeqCst);
+
+        // A bare fragment stays on the route, skipping its guards
+        let event = cx
+            .update(|cx| Navigator::push(cx, "#usage"))
+            .into_event()
+            .unwrap();
+        assert!(event.fragment_only);
+        assert_eq!(event.to, "/docs/install");
+        assert_eq!(event.fragment.as_deref(), Some("usage"));
+        assert!(cx
+            .update(|cx| Navigator::try_push(cx, "#usage"))
+            .is_success());
+        assert_eq!(checks.load(Ordering::SeqCst), checked);
+        assert_eq!(
+            cx.read(|cx| cx.global::<GlobalRouter>().state().history().len()),
+            4
+        );
+
+        // Back and forward restore the fragment
+        let event = cx.update(Navigator::pop).into_event().unwrap();
+        assert!(event.fragment_only);
+        assert_eq!(
+            cx.read(Navigator::current_fragment).as_deref(),
+            Some("requirements")
+        );
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_fragment), None);
+        let event = cx.update(Navigator::forward).into_event().unwrap();
+        assert!(!event.fragment_only);
+        assert_eq!(event.fragment.as_deref(), Some("requirements"));
+
+        // Leaving the fragment keeps the route too
+        let event = cx
+            .update(|cx| Navigator::push(cx, "/docs/install"))
+            .into_event()
+            .unwrap();
+        assert!(event.fragment_only);
+        assert_eq!(cx.read(Navigator::current_fragment), None);
+    }
+
+    #[gpui::test]
+    fn test_nav_replace(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/login", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/home", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Navigate and then replace
+        let event = cx.update(|cx| {
+            Navigator::push(cx, "/login");
+            Navigator::replace(cx, "/home")
+        });
+        let event = event.into_event().unwrap();
+        assert_eq!(event.from.as_deref(), Some("/login"));
+        assert_eq!(event.to, "/home");
+        assert_eq!(event.direction, NavigationDirection::Replace);
+
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        // After replace, going back should skip the replaced route
+        let event = cx.update(Navigator::pop).into_event().unwrap();
+        assert_eq!(event.from.as_deref(), Some("/home"));
+        assert_eq!(event.to, "/");
+
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_nav_can_go_back_boundaries(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // At initial state, can't go back
+        assert!(!cx.read(Navigator::can_pop));
+
+        // After navigation, can go back
+        cx.update(|cx| {
+            Navigator::push(cx, "/page1");
+        });
+
+        assert!(cx.read(Navigator::can_pop));
+
+        // After going back, can't go back further
+        cx.update(|cx| {
+            Navigator::pop(cx);
+        });
+
+        assert!(!cx.read(Navigator::can_pop));
+    }
+
+    #[gpui::test]
+    fn test_nav_multiple_pushes(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/step1", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/step2", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/step3", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Navigate through multiple pages
+        cx.update(|cx| {
+            Navigator::push(cx, "/step1");
+            Navigator::push(cx, "/step2");
+            Navigator::push(cx, "/step3");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/step3");
+
+        // Go back multiple times
+        cx.update(|cx| {
+            Navigator::pop(cx);
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/step2");
+
+        cx.update(|cx| {
+            Navigator::pop(cx);
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/step1");
+
+        cx.update(|cx| {
+            Navigator::pop(cx);
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_nav_with_route_parameters(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new(
+                    "/posts/:id/comments/:commentId",
+                    |_, _cx, _params| gpui::div().into_any_element(),
+                ));
+            });
+        });
+
+        // Navigate to routes with parameters
+        cx.update(|cx| {
+            Navigator::push(cx, "/users/42");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/users/42");
+
+        cx.update(|cx| {
+            Navigator::push(cx, "/posts/123/comments/456");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/posts/123/comments/456");
+    }
+
+    #[gpui::test]
+    fn test_navigator_api_style(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/home", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/profile", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Test Flutter-style Navigator.of(context).push()
+        cx.update(|cx| {
+            Navigator::of(cx).push("/home");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        // Test chaining: the chain is one navigation
+        let events = cx.update(|cx| Navigator::of(cx).push("/profile").pop().forward().events());
+        let paths: Vec<_> = events
+            .iter()
+            .map(|event| (event.from.as_deref(), event.to.as_str(), event.direction))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![(Some("/home"), "/profile", NavigationDirection::Forward)]
+        );
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        // Unless every step is committed on its own
+        let events = cx.update(|cx| {
+            Navigator::of(cx)
+                .commit_each()
+                .push("/profile")
+                .pop()
+                .forward()
+                .events()
+        });
+        let paths: Vec<_> = events
+            .iter()
+            .map(|event| (event.from.as_deref(), event.to.as_str(), event.direction))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (Some("/home"), "/profile", NavigationDirection::Forward),
+                (Some("/profile"), "/home", NavigationDirection::Back),
+                (Some("/home"), "/profile", NavigationDirection::Forward),
+            ]
+        );
+        cx.update(Navigator::pop);
+
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        // Test replace
+        cx.update(|cx| {
+            Navigator::of(cx).replace("/profile");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/profile");
+
+        // After replace, we're still at index 1 in history, so we can still go back to "/"
+        assert!(cx.read(Navigator::can_pop));
+
+        // Pop back to "/"
+        cx.update(|cx| {
+            Navigator::of(cx).pop();
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        // Now we're at the root, can't go back anymore
+        assert!(!cx.read(Navigator::can_pop));
+        assert!(cx.update(|cx| Navigator::of(cx).pop().events()).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_material_route_with_params(cx: &mut TestAppContext) {
+        use crate::PageRoute;
+
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Test PageRoute with params
+        cx.update(|cx| {
+            Navigator::push(
+                cx,
+                PageRoute::builder("/users/:id", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                })
+                .with_param("id", "123"),
+            );
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/users/:id");
+
+        // Test with Navigator.of() style
+        cx.update(|cx| {
+            Navigator::of(cx).push(
+                PageRoute::builder("/users/:id", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                })
+                .with_param("id", "456"),
+            );
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/users/:id");
+    }
+
+    #[gpui::test]
+    fn test_string_into_route(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/home", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Test that strings still work with IntoRoute
+        cx.update(|cx| {
+            Navigator::push(cx, "/home");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        // Test with &str
+        cx.update(|cx| {
+            let path = "/home";
+            Navigator::push(cx, path);
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        // Test String
+        cx.update(|cx| {
+            Navigator::push(cx, String::from("/home"));
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+    }
+
+    #[gpui::test]
+    fn test_both_api_styles(cx: &mut TestAppContext) {
+        // Initialize router
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/page1", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/page2", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Use static API
+        cx.update(|cx| {
+            Navigator::push(cx, "/page1");
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/page1");
+
+        // Use Flutter-style API
+        cx.update(|cx| {
+            Navigator::of(cx).push("/page2");
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/page2");
+
+        // Mix both styles
+        cx.update(|cx| {
+            Navigator::pop(cx); // Static API
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/page1");
+
+        cx.update(|cx| {
+            Navigator::of(cx).pop(); // Flutter style
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[cfg(feature = "cache")]
+    #[gpui::test]
+    fn test_parent_route_cache_survives_navigation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/dashboard", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .child(
+                        Route::new("overview", |_, _cx, _params| gpui::div().into_any_element())
+                            .into(),
+                    ),
+                );
+                router.add_route(
+                    Route::new("/settings", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .child(
+                        Route::new("general", |_, _cx, _params| gpui::div().into_any_element())
+                            .into(),
+                    ),
+                );
+            });
+        });
+
+        let resolve_current = |cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                cx.update_global::<GlobalRouter, _>(|router, _| {
+                    let path = router.current_path().to_string();
+                    router
+                        .find_parent_route(&path)
+                        .map(|route| route.config.path.clone())
+                })
+            })
+        };
+
+        // A -> B -> A
+        cx.update(|cx| Navigator::push(cx, "/dashboard/overview"));
+        assert_eq!(resolve_current(cx).as_deref(), Some("/dashboard"));
+
+        cx.update(|cx| Navigator::push(cx, "/settings/general"));
+        assert_eq!(resolve_current(cx).as_deref(), Some("/settings"));
+
+        cx.update(Navigator::pop);
+        assert_eq!(resolve_current(cx).as_deref(), Some("/dashboard"));
+
+        let stats = cx.read(|cx| cx.global::<GlobalRouter>().cache_stats().clone());
+        assert_eq!(stats.parent_misses, 2);
+        assert_eq!(stats.parent_hits, 1);
+    }
+
+    #[cfg(feature = "cache")]
+    #[gpui::test]
+    fn test_outlet_child_cache_dropped_by_navigation(cx: &mut TestAppContext) {
+        use crate::RouterOutlet;
+        use gpui::{ParentElement, VisualTestContext};
+
+        let layout = |path: &str, child: &str| {
+            Route::new(path, |window, cx, _| {
+                gpui::div()
+                    .child(crate::render_router_outlet(window, cx, None))
+                    .into_any_element()
+            })
+            .child(Route::new(child, |_, _, _| gpui::div().into_any_element()).into())
+        };
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(layout("/dashboard", "overview"));
+                router.add_route(layout("/settings", "general"));
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        let render = |cx: &mut VisualTestContext, path: Option<&str>| {
+            cx.update(|window, cx| {
+                match path {
+                    Some("..") => {
+                        Navigator::pop(cx);
+                    }
+                    Some(path) => {
+                        Navigator::push(cx, path.to_string());
+                    }
+                    None => {}
+                }
+                window.refresh();
+            });
+            cx.run_until_parked();
+            cx.read(|cx| cx.global::<GlobalRouter>().cache_stats().clone())
+        };
+
+        let first = render(cx, Some("/dashboard/overview"));
+        let again = render(cx, None);
+        // Rendering the same page again reuses the resolved child
+        assert_eq!(again.child_misses, first.child_misses);
+        assert!(again.child_hits > first.child_hits);
+
+        // A -> B -> A: the entries of both paths are dropped on navigation
+        let away = render(cx, Some("/settings/general"));
+        let back = render(cx, Some(".."));
+        assert!(back.child_misses > away.child_misses);
+        assert!(back.invalidations > again.invalidations);
+        let settled = render(cx, None);
+        assert_eq!(settled.child_misses, back.child_misses);
+        assert!(settled.child_hits > back.child_hits);
+    }
+
+    #[gpui::test]
+    fn test_history_listener_sees_navigation(cx: &mut TestAppContext) {
+        use crate::history::{History, HistoryEntry};
+        use std::sync::Mutex;
+
+        struct PathRecorder(Arc<Mutex<Vec<String>>>);
+
+        impl HistoryListener for PathRecorder {
+            fn on_push(&self, entry: &HistoryEntry, _history: &History) {
+                self.0.lock().unwrap().push(format!("push {}", entry.path));
+            }
+
+            fn on_travel(&self, _from_index: usize, _to_index: usize, history: &History) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("travel {}", history.current_path()));
+            }
+        }
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorder = PathRecorder(calls.clone());
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_history_listener(Box::new(recorder));
+            });
+            Navigator::push(cx, "/users");
+            Navigator::pop(cx);
+        });
+
+        assert_eq!(*calls.lock().unwrap(), vec!["push /users", "travel /"]);
+    }
+
+    fn init_editor_routes(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/editor/:fileId", |_, _, _| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/settings", |_, _, _| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_params_changed(cx: &mut TestAppContext) {
+        init_editor_routes(cx);
+        assert!(cx.read(Navigator::params_changed).is_none());
+
+        let event = cx.update(|cx| {
+            Navigator::push(cx, "/editor/a.rs");
+            cx.update_global::<GlobalRouter, _>(|router, _| router.push("/editor/b.rs".into()))
+        });
+        let event = event.into_event().unwrap();
+        assert!(event.same_route);
+        let diff = event.params_diff.unwrap();
+        assert_eq!(
+            diff.changed["fileId"],
+            ("a.rs".to_string(), "b.rs".to_string())
+        );
+        assert_eq!(cx.read(Navigator::params_changed), Some(diff));
+
+        // Navigating to a route without the param removes it
+        let event = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.push("/settings".into()))
+        });
+        let event = event.into_event().unwrap();
+        assert!(!event.same_route && event.params_diff.is_none());
+        let diff = cx.read(Navigator::params_changed).unwrap();
+        assert_eq!(diff.removed["fileId"], "b.rs");
+        assert!(diff.added.is_empty() && diff.changed.is_empty());
+
+        // Navigating to the same path again changes nothing
+        let event = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.push("/settings".into()))
+        });
+        let event = event.into_event().unwrap();
+        assert!(event.same_route);
+        assert!(event.params_diff.unwrap().is_empty());
+        assert!(cx.read(Navigator::params_changed).unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    fn test_event_describes_resolved_route(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/users/:id", |_, _, _| gpui::div().into_any_element()).children(
+                        vec![
+                            Route::new("posts", |_, _, _| gpui::div().into_any_element())
+                                .name("user.posts")
+                                .into(),
+                        ],
+                    ),
+                );
+            });
+        });
+
+        let event = cx
+            .update(|cx| Navigator::push(cx, "/users/7/posts?sort=new"))
+            .into_event()
+            .unwrap();
+        assert!(event.matched);
+        assert_eq!(event.pattern.as_deref(), Some("/users/:id/posts"));
+        assert_eq!(event.route_name.as_deref(), Some("user.posts"));
+        assert_eq!(event.params.get("id"), Some(&"7".to_string()));
+        assert_eq!(event.query.get("sort"), Some(&"new".to_string()));
+
+        let event = cx.update(Navigator::pop).into_event().unwrap();
+        assert!(!event.matched);
+        assert!(event.pattern.is_none() && event.route_name.is_none());
+        assert!(event.params.is_empty() && event.query.is_empty());
+    }
+
+    #[gpui::test]
+    fn test_observe_param(cx: &mut TestAppContext) {
+        use std::sync::Mutex;
+
+        init_editor_routes(cx);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let _subscription = cx.update(|cx| {
+            Navigator::observe_param(cx, "fileId", move |old, new, _| {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push(format!("{:?} -> {:?}", old, new));
+            })
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/editor/a.rs"));
+        cx.update(|cx| Navigator::push(cx, "/editor/a.rs"));
+        cx.update(|cx| Navigator::push(cx, "/editor/b.rs"));
+        cx.update(|cx| Navigator::push(cx, "/settings"));
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                r#"None -> Some("a.rs")"#,
+                r#"Some("a.rs") -> Some("b.rs")"#,
+                r#"Some("b.rs") -> None"#,
+            ]
+        );
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_can_access(cx: &mut TestAppContext) {
+        use crate::{AccessCheck, AuthGuard, GuardResult, RouteGuard};
+        use std::future::Future;
+        use std::pin::Pin;
+
+        /// Guard doing real async work
+        struct RemoteGuard;
+
+        impl RouteGuard for RemoteGuard {
+            type Future = Pin<Box<dyn Future<Output = GuardResult> + Send>>;
+
+            fn check(&self, _cx: &App, _request: &NavigationRequest) -> Self::Future {
+                Box::pin(async { GuardResult::deny("remote") })
+            }
+
+            fn synchronous(&self) -> bool {
+                false
+            }
+        }
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/admin", |_, _cx, _params| gpui::div().into_any_element())
+                        .guard(AuthGuard::new(|_| false, "/login"))
+                        .child(
+                            Route::new("users/:id", |_, _cx, _params| {
+                                gpui::div().into_any_element()
+                            })
+                            .into(),
+                        ),
+                );
+                router.add_route(
+                    Route::new("/remote", |_, _cx, _params| gpui::div().into_any_element())
+                        .guard(RemoteGuard),
+                );
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+
+            assert!(Navigator::can_access(cx, "/").is_allowed());
+            assert!(Navigator::can_access(cx, "/missing").is_allowed());
+            assert_eq!(Navigator::can_access(cx, "/remote"), AccessCheck::Unknown);
+            // Parent guards apply to children
+            assert!(Navigator::can_access(cx, "/admin/users/1?tab=roles").is_blocked());
+
+            // Nothing was navigated
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert!(!Navigator::can_pop(cx));
+        });
+    }
+
+    fn initial_path_routes(router: &mut GlobalRouter) {
+        for path in ["/", "/projects/:id", "/settings"] {
+            router.add_route(Route::new(path, |_, _cx, _params| {
+                gpui::div().into_any_element()
+            }));
+        }
+    }
+
+    #[gpui::test]
+    fn test_initial_path(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router_with_initial(cx, |_| "/projects/7".to_string(), initial_path_routes);
+            assert_eq!(Navigator::current_path(cx), "/projects/7");
+            assert!(!Navigator::can_pop(cx));
+        });
+    }
+
+    #[gpui::test]
+    fn test_initial_path_config(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().initial_path("/settings"));
+                initial_path_routes(router);
+            });
+            assert_eq!(Navigator::current_path(cx), "/settings");
+            assert!(!Navigator::can_pop(cx));
+
+            Navigator::push(cx, "/projects/1");
+            assert!(Navigator::can_pop(cx));
+        });
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_initial_path_runs_guards(cx: &mut TestAppContext) {
+        use crate::AuthGuard;
+
+        cx.update(|cx| {
+            init_router_with_initial(
+                cx,
+                |_| "/projects/7".to_string(),
+                |router| {
+                    router.add_route(
+                        Route::new("/projects/:id", |_, _cx, _params| {
+                            gpui::div().into_any_element()
+                        })
+                        .guard(AuthGuard::new(|_| false, "/login")),
+                    );
+                    router.add_route(Route::new("/login", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    }));
+                },
+            );
+
+            assert_eq!(Navigator::current_path(cx), "/login");
+            assert!(!Navigator::can_pop(cx));
+        });
+    }
+
+    #[gpui::test]
+    fn test_initial_path_async(cx: &mut TestAppContext) {
+        let delay = Duration::from_millis(50);
+        cx.update(|cx| {
+            init_router_with_initial_async(
+                cx,
+                |cx| {
+                    let timer = cx.background_executor().timer(delay);
+                    cx.background_executor().spawn(async move {
+                        timer.await;
+                        "/projects/7".to_string()
+                    })
+                },
+                |router| {
+                    for path in ["/", "/projects/:id"] {
+                        router.add_route(Route::new(path, |_, _cx, _params| {
+                            gpui::div().into_any_element()
+                        }));
+                    }
+                },
+            );
+            assert!(cx.router().resolving_initial());
+            assert_eq!(Navigator::current_path(cx), "/");
+        });
+
+        cx.executor().advance_clock(delay);
+        cx.run_until_parked();
+
+        cx.update(|cx| {
+            assert!(!cx.router().resolving_initial());
+            assert_eq!(Navigator::current_path(cx), "/projects/7");
+            assert!(!Navigator::can_pop(cx));
+        });
+    }
+
+    #[cfg(feature = "cache")]
+    #[gpui::test]
+    fn test_configure_cache(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/dashboard", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .child(
+                        Route::new("overview", |_, _cx, _params| gpui::div().into_any_element())
+                            .into(),
+                    ),
+                );
+                router.configure_cache(16, false);
+            });
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                assert_eq!(router.nested_cache_mut().capacity(), 16);
+                assert!(router.find_parent_route("/dashboard/overview").is_some());
+                assert!(router.find_parent_route("/dashboard/overview").is_some());
+                assert_eq!(router.cache_stats().parent_hits, 0);
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_add_route_config(cx: &mut TestAppContext) {
+        let mut builders: HashMap<String, RouteBuilder> = HashMap::new();
+        for name in ["dashboard", "overview", "settings"] {
+            builders.insert(
+                name.to_string(),
+                RouteBuilder::new(|_, _cx, _params| gpui::div()),
+            );
+        }
+
+        let config = RouteConfig::new("/dashboard")
+            .name("dashboard")
+            .child(RouteConfig::new("overview").name("overview"))
+            .child(RouteConfig::new("settings").name("settings"));
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route_config(config, &builders).unwrap();
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/dashboard/settings"));
+
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                let parent = router.find_parent_route("/dashboard/settings").unwrap();
+                assert_eq!(parent.config.path, "/dashboard");
+
+                let (child, _) = crate::resolve_child_route(
+                    &parent,
+                    "/dashboard/settings",
+                    &RouteParams::new(),
+                    None,
+                )
+                .unwrap();
+                assert_eq!(child.config.path, "settings");
+                assert!(child.builder.is_some());
+            });
+        });
+
+        assert_eq!(
+            cx.read(|cx| Navigator::try_url_for(cx, "dashboard", &RouteParams::new()).ok()),
+            Some("/dashboard".to_string())
+        );
+    }
+
+    #[gpui::test]
+    fn test_nested_route_names_use_full_path(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/users", |_, _cx, _params| gpui::div().into_any_element())
+                        .name("users")
+                        .child(
+                            Route::new(":id", |_, _cx, _params| gpui::div().into_any_element())
+                                .name("user.detail")
+                                .into(),
+                        ),
+                );
+            });
+        });
+
+        let route = cx.read(|cx| {
+            cx.global::<GlobalRouter>()
+                .route_by_name("user.detail")
+                .cloned()
+        });
+        assert_eq!(route.unwrap().config.path, ":id");
+
+        let params = RouteParams::single("id", "7");
+        assert_eq!(
+            cx.read(|cx| Navigator::try_url_for(cx, "user.detail", &params).ok()),
+            Some("/users/7".to_string())
+        );
+    }
+
+    #[gpui::test]
+    fn test_nested_route_names_two_levels(cx: &mut TestAppContext) {
+        // The route table of the nested demo, with reviews one level deeper
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/").name("home"));
+                router.add_route(page("/dashboard").name("dashboard").children(vec![
+                    page("").into(),
+                    page("overview").name("dashboard.overview").into(),
+                    page("settings").name("dashboard.settings").into(),
+                ]));
+                router.add_route(page("/products").name("products").children(vec![
+                        page("").into(),
+                        page("list").name("products.list").into(),
+                        page(":id")
+                            .name("products.detail")
+                            .child(
+                                page("reviews/:review")
+                                    .name("products.detail.review")
+                                    .into(),
+                            )
+                            .into(),
+                    ]));
+            });
+        });
+
+        let params = RouteParams::from([("id", "123"), ("review", "9")]);
+        let url_for = |cx: &mut TestAppContext, name: &str| {
+            cx.read(|cx| Navigator::try_url_for(cx, name, &params).ok())
+        };
+        assert_eq!(
+            url_for(cx, "dashboard.overview").as_deref(),
+            Some("/dashboard/overview?id=123&review=9")
+        );
+        assert_eq!(
+            url_for(cx, "products.detail").as_deref(),
+            Some("/products/123?review=9")
+        );
+        assert_eq!(
+            url_for(cx, "products.detail.review").as_deref(),
+            Some("/products/123/reviews/9")
+        );
+
+        let result = cx.update(|cx| Navigator::push_named(cx, "dashboard.settings", &params));
+        assert!(result.is_success());
+        assert_eq!(
+            cx.read(Navigator::current_path),
+            "/dashboard/settings?id=123&review=9"
+        );
+
+        let result = cx.update(|cx| Navigator::push_named(cx, "products.detail.review", &params));
+        assert!(result.is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/products/123/reviews/9");
+
+        // Params of every level are required
+        let partial = RouteParams::single("review", "9");
+        let result = cx.update(|cx| Navigator::push_named(cx, "products.detail.review", &partial));
+        assert!(!result.is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/products/123/reviews/9");
+    }
+
+    #[gpui::test]
+    fn test_time_on_current_route(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                for path in ["/", "/inbox"] {
+                    router.add_route(Route::new(path, |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    }));
+                }
+            });
+        });
+        let wait = |cx: &mut TestAppContext, seconds| {
+            cx.executor().advance_clock(Duration::from_secs(seconds));
+        };
+
+        wait(cx, 2);
+        assert_eq!(
+            cx.read(Navigator::time_on_current_route),
+            Duration::from_secs(2)
+        );
+        let event = cx
+            .update(|cx| Navigator::push(cx, "/inbox"))
+            .into_event()
+            .unwrap();
+        assert_eq!(event.previous_dwell, Some(Duration::from_secs(2)));
+        assert_eq!(cx.read(Navigator::time_on_current_route), Duration::ZERO);
+
+        // Time spent suspended is left out
+        wait(cx, 1);
+        cx.update(GlobalRouter::pause_dwell);
+        wait(cx, 600);
+        cx.update(GlobalRouter::resume_dwell);
+        wait(cx, 1);
+        assert_eq!(
+            cx.read(Navigator::time_on_current_route),
+            Duration::from_secs(2)
+        );
+
+        let event = cx
+            .update(|cx| Navigator::replace(cx, "/"))
+            .into_event()
+            .unwrap();
+        assert_eq!(event.previous_dwell, Some(Duration::from_secs(2)));
+        wait(cx, 3);
+        let event = cx.update(Navigator::pop).into_event().unwrap();
+        assert_eq!(event.previous_dwell, Some(Duration::from_secs(3)));
+    }
+
+    #[gpui::test]
+    fn test_localized_paths(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(
+                    RouterConfig::new()
+                        .locales(["en", "de"])
+                        .default_locale("en"),
+                );
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/settings")
+                        .name("settings")
+                        .localized_path("de", "/einstellungen"),
+                );
+                router.add_route(
+                    page("/users/:id")
+                        .name("user")
+                        .localized_path("de", "/benutzer/:id")
+                        .child(page("posts/:post").name("user.post").into()),
+                );
+            });
+        });
+        let params = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .current_match_immutable()
+                    .map(|route_match| route_match.params)
+                    .unwrap_or_default()
+            })
+        };
+        assert_eq!(cx.read(Navigator::locale).as_deref(), Some("en"));
+
+        // Both forms resolve to the canonical route, with the locale param
+        for (path, locale) in [
+            ("/settings", "en"),
+            ("/en/settings", "en"),
+            ("/de/einstellungen", "de"),
+            ("/de/settings", "de"),
+        ] {
+            let result = cx.update(|cx| Navigator::try_push(cx, path));
+            assert!(result.is_success(), "{}", path);
+            assert_eq!(cx.read(Navigator::current_path), "/settings");
+            assert_eq!(params(cx)["locale"], locale);
+        }
+        // Without a locale segment the locale stays
+        cx.update(|cx| Navigator::push(cx, "/users/7"));
+        assert_eq!(params(cx)["locale"], "de");
+        assert_eq!(cx.read(Navigator::localized_path), "/de/benutzer/7");
+
+        cx.update(|cx| Navigator::push(cx, "/de/benutzer/7/posts/3?sort=new"));
+        assert_eq!(
+            cx.read(Navigator::current_path),
+            "/users/7/posts/3?sort=new"
+        );
+        assert_eq!(params(cx)["id"], "7");
+        assert_eq!(params(cx)["post"], "3");
+
+        // URLs per locale, falling back to canonical paths
+        let user = RouteParams::from([("id", "7"), ("post", "3")]);
+        let url_for = |cx: &mut TestAppContext, name: &str, locale: &str| {
+            cx.read(|cx| Navigator::url_for_locale(cx, name, &user, locale))
+        };
+        assert_eq!(
+            url_for(cx, "settings", "de").as_deref(),
+            Some("/de/einstellungen?id=7&post=3")
+        );
+        assert_eq!(
+            url_for(cx, "settings", "en").as_deref(),
+            Some("/en/settings?id=7&post=3")
+        );
+        assert_eq!(
+            url_for(cx, "user.post", "de").as_deref(),
+            Some("/de/benutzer/7/posts/3")
+        );
+        assert_eq!(url_for(cx, "missing", "de"), None);
+    }
+
+    #[gpui::test]
+    fn test_set_locale_keeps_route(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().locales(["en", "de"]));
+                router.add_route(
+                    Route::new("/users/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .localized_path("de", "/benutzer/:id"),
+                );
+            });
+            Navigator::push(cx, "/en/users/7?tab=posts");
+        });
+        assert_eq!(cx.read(Navigator::localized_path), "/en/users/7?tab=posts");
+
+        let event = cx
+            .update(|cx| Navigator::set_locale(cx, "de"))
+            .into_event()
+            .unwrap();
+        assert_eq!(event.direction, NavigationDirection::Replace);
+        assert!(event.same_route);
+        assert_eq!(cx.read(Navigator::current_path), "/users/7?tab=posts");
+        assert_eq!(
+            cx.read(Navigator::localized_path),
+            "/de/benutzer/7?tab=posts"
+        );
+        assert_eq!(
+            cx.read(|cx| cx.global::<GlobalRouter>().state().history().len()),
+            2
+        );
+        let route_match = cx.read(|cx| cx.global::<GlobalRouter>().current_match_immutable());
+        let route_match = route_match.unwrap();
+        assert_eq!(route_match.params["id"], "7");
+        assert_eq!(route_match.params["locale"], "de");
+        assert_eq!(route_match.query["tab"], "posts");
+
+        // Unknown locales are refused
+        cx.update(|cx| Navigator::set_locale(cx, "fr"));
+        assert_eq!(cx.read(Navigator::locale).as_deref(), Some("de"));
+        assert!(cx.read(Navigator::last_error).is_some());
+    }
+
+    #[gpui::test]
+    fn test_multi_segment_and_absolute_child_paths(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/dashboard", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .child(
+                        Route::new("reports/:year", |_, _cx, _params| {
+                            gpui::div().into_any_element()
+                        })
+                        .name("reports")
+                        .into(),
+                    )
+                    .child(
+                        Route::new("/dashboard/team/members", |_, _cx, _params| {
+                            gpui::div().into_any_element()
+                        })
+                        .name("members")
+                        .into(),
+                    ),
+                );
+            });
+        });
+
+        let params = RouteParams::single("year", "2024");
+        assert_eq!(
+            cx.read(|cx| Navigator::try_url_for(cx, "reports", &params).ok()),
+            Some("/dashboard/reports/2024".to_string())
+        );
+        assert_eq!(
+            cx.read(|cx| Navigator::try_url_for(cx, "members", &RouteParams::new()).ok()),
+            Some("/dashboard/team/members".to_string())
+        );
+    }
+
+    #[gpui::test]
+    fn test_extend_keeps_history(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/docs").child(page(":page").into()));
+            });
+            Navigator::push(cx, "/docs");
+            Navigator::push(cx, "/docs/intro");
+            Navigator::push(cx, "/plugin");
+            assert!(cx.global::<GlobalRouter>().current_route().is_none());
+
+            // A plugin contributes routes later
+            init_router(cx, |router| {
+                router.add_route(page("/plugin").name("plugin"));
+                router.add_route(page("/plugin/about"));
+            });
+
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.current_path(), "/plugin");
+            let paths: Vec<_> = router
+                .state()
+                .history()
+                .entries()
+                .iter()
+                .map(|entry| entry.path.as_str())
+                .collect();
+            assert_eq!(paths, vec!["/", "/docs", "/docs/intro", "/plugin"]);
+            assert_eq!(
+                router
+                    .current_route()
+                    .map(|route| route.config.path.as_str()),
+                Some("/plugin")
+            );
+
+            Navigator::push(cx, "/plugin/about");
+            assert_eq!(
+                cx.global::<GlobalRouter>()
+                    .current_route()
+                    .map(|route| route.config.path.as_str()),
+                Some("/plugin/about")
+            );
+            Navigator::pop(cx);
+            Navigator::pop(cx);
+            assert_eq!(Navigator::current_path(cx), "/docs/intro");
+        });
+    }
+
+    #[gpui::test]
+    fn test_reinit_replace_policy(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().on_reinit(ReinitPolicy::Replace));
+                router.add_route(page("/"));
+                router.add_route(page("/docs"));
+            });
+            Navigator::push(cx, "/docs");
+
+            init_router(cx, |router| router.add_route(page("/")));
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert!(!Navigator::can_pop(cx));
+        });
+    }
+
+    #[gpui::test]
+    fn test_remove_route(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/plugin")
+                        .name("plugin")
+                        .child(page("settings").name("plugin.settings").into()),
+                );
+            });
+            Navigator::push(cx, "/plugin/settings");
+        });
+
         cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.push_with_transition(descriptor.path, transition);
+            assert!(!router.remove_route("plugin.settings"));
+            assert!(router.remove_route("plugin"));
+            assert!(!router.remove_route("/plugin"));
+
+            assert!(router.route_by_name("plugin").is_none());
+            assert!(router.route_by_name("plugin.settings").is_none());
+            assert_eq!(router.current_path(), "/plugin/settings");
+            assert!(router.current_route().is_none());
+            assert!(router.find_parent_route("/plugin/settings").is_none());
+
+            assert!(router.remove_route("/"));
+            assert!(router.state().routes().is_empty());
+        });
+    }
+
+    /// Router with signed-out (`auth`) and signed-in (`app`) route tables
+    fn init_tables(cx: &mut App, policy: TableHistoryPolicy) {
+        init_router(cx, |router| {
+            router.set_config(RouterConfig::new().on_table_switch(policy));
+            router.define_table("auth", |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/login").name("login"));
+            });
+            router.define_table("app", |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users/:id").name("user.detail"));
+            });
+            router.switch_table("auth", "/").unwrap();
+        });
+    }
+
+    #[gpui::test]
+    fn test_route_tables(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_tables(cx, TableHistoryPolicy::Clear);
+            Navigator::push(cx, "/login");
+
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.active_table(), "auth");
+            // The inactive table's routes neither match nor have names
+            assert!(!router.has_route("/users/:id"));
+            assert!(router.route_by_name("user.detail").is_none());
+            // but its URLs can be generated
+            assert_eq!(
+                router.try_url_for("app:user.detail", &RouteParams::single("id", 7)),
+                Ok("/users/7".to_string())
+            );
+            assert_eq!(
+                router.try_url_for("auth:login", &RouteParams::new()),
+                Ok("/login".to_string())
+            );
+        });
+
+        // Signing in mid-session
+        let event = cx
+            .update(|cx| GlobalRouter::activate_table(cx, "app", "/users/1"))
+            .unwrap();
+        assert_eq!(event.from.as_deref(), Some("/login"));
+        assert_eq!(event.to, "/users/1");
+        assert_eq!(event.direction, NavigationDirection::Replace);
+        cx.update(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.active_table(), "app");
+            assert_eq!(
+                router
+                    .current_route()
+                    .map(|route| route.config.path.as_str()),
+                Some("/users/:id")
+            );
+            assert!(!router.has_route("/login"));
+            assert!(router.route_by_name("login").is_none());
+            // History starts over
+            assert_eq!(router.state().history().len(), 1);
+            assert!(!Navigator::can_pop(cx));
+
+            Navigator::push(cx, "/login");
+            assert!(cx.global::<GlobalRouter>().current_route().is_none());
+        });
+
+        // Signing out drops the app's history
+        cx.update(|cx| {
+            GlobalRouter::activate_table(cx, "auth", "/").unwrap();
+            GlobalRouter::activate_table(cx, "app", "/").unwrap();
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), 1);
+            assert!(GlobalRouter::activate_table(cx, "admin", "/").is_err());
+            assert_eq!(cx.global::<GlobalRouter>().active_table(), "app");
+        });
+    }
+
+    #[gpui::test]
+    fn test_route_table_archived_history(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_tables(cx, TableHistoryPolicy::Archive);
+            Navigator::push(cx, "/login");
+            GlobalRouter::activate_table(cx, "app", "/").unwrap();
+            Navigator::push(cx, "/users/1");
+            Navigator::push(cx, "/users/2");
+            Navigator::pop(cx);
+
+            // Back to the signed-out shell where it was left
+            GlobalRouter::activate_table(cx, "auth", "/").unwrap();
+            assert_eq!(Navigator::current_path(cx), "/login");
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), 2);
+
+            // and back to the app, forward entry included
+            GlobalRouter::activate_table(cx, "app", "/").unwrap();
+            assert_eq!(Navigator::current_path(cx), "/users/1");
+            assert!(Navigator::can_go_forward(cx));
+            Navigator::forward(cx);
+            assert_eq!(Navigator::current_path(cx), "/users/2");
+            let params = cx.global::<GlobalRouter>().state().current_params();
+            assert_eq!(params.get("id").map(String::as_str), Some("2"));
+        });
+    }
+
+    type EdgeLog = Arc<std::sync::Mutex<Vec<String>>>;
+
+    /// Config logging the history edge handlers, with the path they ran on
+    fn logging_edges(config: RouterConfig, log: &EdgeLog) -> RouterConfig {
+        let (back, forward) = (log.clone(), log.clone());
+        config
+            .on_back_at_root(move |cx| {
+                let path = Navigator::current_path(cx);
+                back.lock().unwrap().push(format!("back at {path}"));
+            })
+            .on_forward_at_end(move |cx| {
+                let path = Navigator::current_path(cx);
+                forward.lock().unwrap().push(format!("forward at {path}"));
+            })
+    }
+
+    #[gpui::test]
+    fn test_history_edges_delegate_to_app(cx: &mut TestAppContext) {
+        let log = EdgeLog::default();
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(logging_edges(RouterConfig::new(), &log));
+                router.add_route(page("/"));
+                router.add_route(page("/inbox"));
+            });
+
+            // With history left, the handlers are not called
+            Navigator::push(cx, "/inbox");
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/");
+            assert_eq!(Navigator::forward(cx).into_event().unwrap().to, "/inbox");
+            assert!(log.lock().unwrap().is_empty());
+
+            // At either end, once per call
+            assert!(Navigator::forward(cx).is_delegated());
+            assert!(Navigator::pop(cx).is_navigated());
+            assert!(Navigator::pop(cx).is_delegated());
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert_eq!(*log.lock().unwrap(), ["forward at /inbox", "back at /"]);
+        });
+
+        // Without handlers nothing happens
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.set_config(RouterConfig::new()));
+            assert!(matches!(Navigator::pop(cx), PopOutcome::NoOp));
+            assert!(Navigator::forward(cx).is_navigated());
+            assert!(matches!(Navigator::forward(cx), PopOutcome::NoOp));
+        });
+        assert_eq!(log.lock().unwrap().len(), 2);
+    }
+
+    #[gpui::test]
+    fn test_back_at_root_returns_to_primary_table(cx: &mut TestAppContext) {
+        let log = EdgeLog::default();
+        cx.update(|cx| {
+            init_tables(cx, TableHistoryPolicy::Archive);
+            let config = RouterConfig::new()
+                .on_table_switch(TableHistoryPolicy::Archive)
+                .back_at_root_to_table("app");
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.set_config(logging_edges(config, &log));
+            });
+            GlobalRouter::activate_table(cx, "app", "/").unwrap();
+            Navigator::push(cx, "/users/1");
+            GlobalRouter::activate_table(cx, "auth", "/").unwrap();
+            Navigator::push(cx, "/login");
+
+            // History of the other table first
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/");
+            // then the primary table, at its archived entry
+            let event = Navigator::pop(cx).into_event().unwrap();
+            assert_eq!(event.to, "/users/1");
+            assert_eq!(cx.global::<GlobalRouter>().active_table(), "app");
+            assert!(log.lock().unwrap().is_empty());
+
+            // then its own history, and only then the app
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/");
+            assert!(Navigator::pop(cx).is_delegated());
+            assert_eq!(cx.global::<GlobalRouter>().active_table(), "app");
+            assert_eq!(*log.lock().unwrap(), ["back at /"]);
+        });
+    }
+
+    #[test]
+    fn test_duplicate_route_policies() {
+        let router_with = |policy| {
+            let mut router =
+                GlobalRouter::with_config(RouterConfig::new().on_duplicate_route(policy));
+            router.add_route(page("/"));
+            router.add_route(
+                page("/users")
+                    .name("users")
+                    .child(page("list").name("users.list").into()),
+            );
+            router
+        };
+        let replacement = || {
+            page("/users/")
+                .name("people")
+                .child(page("all").name("people.all").into())
+        };
+
+        // Warn keeps both, the first one matching
+        let mut router = router_with(DuplicatePolicy::Warn);
+        router.add_route(replacement());
+        assert_eq!(router.state().routes().len(), 3);
+        assert!(router.has_route("/users/all"));
+        assert_eq!(router.route_count(), 5);
+
+        // Panic refuses it, naming both registrations
+        let mut router = router_with(DuplicatePolicy::Panic);
+        assert_eq!(
+            router.try_add_route(replacement()),
+            Err(
+                "Duplicate route '/users/': registration #3 has the same pattern as #2 \
+                 ('/users')"
+                    .to_string()
+            )
+        );
+        assert_eq!(router.route_count(), 3);
+
+        // KeepExisting ignores it
+        let mut router = router_with(DuplicatePolicy::KeepExisting);
+        router.add_route(replacement());
+        assert_eq!(router.route_count(), 3);
+        assert!(router.has_route("/users/list/"));
+        assert!(!router.has_route("/users/all"));
+        assert!(router.route_by_name("people").is_none());
+
+        // ReplaceExisting swaps the route, its children and names in place
+        let mut router = router_with(DuplicatePolicy::ReplaceExisting);
+        router.add_route(replacement());
+        assert_eq!(router.route_count(), 3);
+        assert_eq!(router.state().routes()[1].config.path, "/users/");
+        assert!(router.has_route("/users/all"));
+        assert!(!router.has_route("/users/list"));
+        assert!(router.route_by_name("users").is_none());
+        assert!(router.route_by_name("users.list").is_none());
+        assert_eq!(
+            router.try_url_for("people.all", &RouteParams::new()).ok(),
+            Some("/users/all".to_string())
+        );
+
+        // The same path twice is a duplicate too
+        let mut router = router_with(DuplicatePolicy::Panic);
+        assert!(router.try_add_route(page("/")).is_err());
+        router.remove_route("/");
+        assert!(router.try_add_route(page("/")).is_ok());
+    }
+
+    #[gpui::test]
+    fn test_absolute_child_outside_parent_is_rejected(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                let result = router.try_add_route(
+                    Route::new("/dashboard", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .child(
+                        Route::new("/settings/profile", |_, _cx, _params| {
+                            gpui::div().into_any_element()
+                        })
+                        .into(),
+                    ),
+                );
+                assert_eq!(
+                    result,
+                    Err(
+                        "Absolute child path '/settings/profile' is not under its parent \
+                         '/dashboard'"
+                            .to_string()
+                    )
+                );
+                assert!(router.state().routes().is_empty());
+            });
+        });
+    }
+
+    #[test]
+    fn test_invalid_descendant_path_is_rejected() {
+        let mut child = page("reports");
+        child.config.path = "reports//q3".to_string();
+        let mut router = GlobalRouter::new();
+        let result = router.try_add_route(
+            page("/dashboard").child(
+                page("reports")
+                    .named_outlet("aside", vec![child.into()])
+                    .into(),
+            ),
+        );
+        assert_eq!(
+            result,
+            Err(
+                "Invalid route path 'reports//q3': Route path cannot contain consecutive \
+                 slashes"
+                    .to_string()
+            )
+        );
+        assert!(router.state().routes().is_empty());
+        assert_eq!(router.route_issues().len(), 1);
+        assert_eq!(
+            router.route_issues()[0].path,
+            "/dashboard/reports/reports//q3"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid route: Absolute child path '/other/stats'")]
+    fn test_named_outlet_child_outside_parent_panics() {
+        GlobalRouter::new().add_route(
+            page("/dashboard").named_outlet("sidebar", vec![page("/other/stats").into()]),
+        );
+    }
+
+    #[test]
+    fn test_deep_route_tree_is_rejected() {
+        // `/l0/l1/.../l99`
+        let chain = || {
+            (0..100)
+                .rev()
+                .fold(None, |child: Option<Route>, level| {
+                    let route = page(&format!("{}l{level}", if level == 0 { "/" } else { "" }));
+                    Some(match child {
+                        Some(child) => route.child(child.into()),
+                        None => route,
+                    })
+                })
+                .unwrap()
+        };
+        let mut router = GlobalRouter::new();
+        let error = router.try_add_route(chain()).unwrap_err();
+        assert!(
+            error.ends_with("is nested deeper than 32 levels"),
+            "{error}"
+        );
+        assert!(router.state().routes().is_empty());
+
+        let mut router = GlobalRouter::new();
+        router.set_config(RouterConfig::new().max_route_depth(100));
+        router.add_route(chain());
+        let deepest = format!(
+            "/{}",
+            (0..100)
+                .map(|level| format!("l{level}"))
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+        assert!(router.has_route(&deepest));
+        assert!(find_parent_route_with_path(router.state().routes(), &deepest).is_some());
+    }
+
+    #[test]
+    fn test_ambiguous_index_routes_warn() {
+        let mut router = GlobalRouter::new();
+        router.add_route(
+            page("/settings")
+                .children(vec![
+                    page("").into(),
+                    page("index").name("settings.index").into(),
+                ])
+                .named_outlet(
+                    "aside",
+                    vec![
+                        Route::group_layout(|_, _, _| gpui::div())
+                            .child(page("").into())
+                            .into(),
+                        page("help").into(),
+                    ],
+                ),
+        );
+        assert!(router.has_route("/settings"));
+
+        let issues = router.route_issues();
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_error());
+        assert_eq!(issues[0].path, "/settings");
+        assert_eq!(
+            issues[0].message,
+            "Route '/settings' has 2 index routes in its default outlet ('', 'index'); only the \
+             first is shown"
+        );
+    }
+
+    #[test]
+    fn test_undeclared_named_outlet_warns() {
+        let mut router = GlobalRouter::new();
+        router.add_route(
+            page("/dashboard")
+                .named_outlet("sidebar", vec![page("stats").into()])
+                .named_outlet("footer", vec![page("links").into()])
+                .declares_outlets(["sidebar"]),
+        );
+        let issues = router.route_issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, crate::IssueSeverity::Warning);
+        assert_eq!(
+            issues[0].message,
+            "Named outlet 'footer' of '/dashboard' is not rendered by its builder (declared: \
+             sidebar)"
+        );
+    }
+
+    #[test]
+    fn test_valid_route_tree_has_no_issues() {
+        let mut router = GlobalRouter::new();
+        router.add_route(page("/").name("home"));
+        router.add_route(
+            Route::group_layout(|_, _, _| gpui::div())
+                .children(vec![page("/login").into(), page("/register").into()]),
+        );
+        router.add_route(
+            page("/orgs/:orgId{int}")
+                .children(vec![
+                    page("").into(),
+                    page("teams/:teamId")
+                        .children(vec![
+                            page("index").into(),
+                            page("/orgs/:orgId{int}/teams/:teamId/members").into(),
+                        ])
+                        .into(),
+                    Route::group_layout(|_, _, _| gpui::div())
+                        .child(page("billing").into())
+                        .into(),
+                ])
+                .named_outlet("sidebar", vec![page("").into(), page("activity").into()])
+                .named_outlet("footer", vec![page("links").into()])
+                .declares_outlets(["sidebar", "footer"]),
+        );
+        router.add_route(page("/files/*"));
+        assert!(
+            router.route_issues().is_empty(),
+            "{:?}",
+            router.route_issues()
+        );
+        assert_eq!(router.route_count(), 13);
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_push_named_reports_guards(cx: &mut TestAppContext) {
+        use crate::{guard_fn, AuthGuard, GuardResult};
+        use std::future::ready;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/login"));
+                router.add_route(
+                    page("/admin")
+                        .name("admin")
+                        .guard(AuthGuard::new(|_| false, "/login")),
+                );
+                router.add_route(page("/vault").name("vault").guard(guard_fn(|_, _| {
+                    Box::pin(ready(GuardResult::deny("locked"))) as _
+                })));
+            });
+
+            let result = Navigator::push_named(cx, "admin", &RouteParams::new());
+            assert!(matches!(
+                result,
+                NavigationResult::Redirected { from, to } if from == "/admin" && to == "/login"
+            ));
+            assert_eq!(Navigator::current_path(cx), "/login");
+
+            let result = Navigator::push_named(cx, "vault", &RouteParams::new());
+            assert!(
+                matches!(result, NavigationResult::Blocked { reason, .. } if reason == "locked")
+            );
+            assert_eq!(Navigator::current_path(cx), "/login");
+
+            // Without an App the guards run later, so the push waits for them
+            let result = cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.push_named("vault", &RouteParams::new())
+            });
+            assert!(result.is_pending());
+        });
+    }
+
+    #[gpui::test]
+    fn test_push_named_missing_params(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/users/:id{int}", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("detail"),
+                );
+            });
+        });
+
+        let result = cx.update(|cx| Navigator::push_named(cx, "detail", &RouteParams::new()));
+        assert!(result.is_error());
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        // Values are checked against the param's constraint
+        let abc = RouteParams::single("id", "abc");
+        let violation = UrlForError::ConstraintViolation {
+            param: "id".to_string(),
+            constraint: crate::matcher::Constraint::Numeric,
+            value: "abc".to_string(),
+        };
+        assert_eq!(
+            cx.read(|cx| Navigator::try_url_for(cx, "detail", &abc)),
+            Err(violation.clone())
+        );
+        let result = cx.update(|cx| Navigator::push_named(cx, "detail", &abc));
+        assert!(result.is_error());
+        assert!(matches!(
+            cx.read(Navigator::last_error),
+            Some(NavigationError::NamedRoute { name, error }) if name == "detail" && error == violation
+        ));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        let result = cx.update(|cx| Navigator::push_named(cx, "missing", &RouteParams::new()));
+        assert!(result.is_not_found());
+        assert!(matches!(
+            cx.read(Navigator::last_error),
+            Some(NavigationError::RouteNotFound { path, .. }) if path == "missing"
+        ));
+
+        let params = RouteParams::single("id", "42");
+        let result = cx.update(|cx| Navigator::push_named(cx, "detail", &params));
+        assert!(result.is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/users/42");
+    }
+
+    #[gpui::test]
+    fn test_url_for_round_trips_reserved_characters(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/files/:name", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("file"),
+                );
+            });
+        });
+
+        for name in ["reports/q3.pdf", "why?", "#1", "100%", "q3 final.pdf"] {
+            let params = RouteParams::single("name", name);
+            let url = cx
+                .read(|cx| Navigator::try_url_for(cx, "file", &params))
+                .unwrap();
+            let event = cx
+                .update(|cx| Navigator::push(cx, url.clone()))
+                .into_event()
+                .unwrap();
+            assert!(event.matched, "{}", url);
+            assert_eq!(event.params.get("name").unwrap(), name, "{}", url);
+            assert_eq!(cx.read(Navigator::current_path), url);
+            let route_match = cx
+                .read(|cx| cx.global::<GlobalRouter>().current_match_immutable())
+                .unwrap();
+            assert_eq!(route_match.params.get("name").unwrap(), name, "{}", url);
+        }
+        assert_eq!(cx.read(Navigator::current_path), "/files/q3%20final.pdf");
+    }
+
+    #[gpui::test]
+    fn test_params_by_level(cx: &mut TestAppContext) {
+        let page = |path: &str| Route::new(path, |_, _cx, _params| gpui::div().into_any_element());
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    page("/orgs/:orgId{int}").child(
+                        Route::group_layout(|_, _, _| gpui::div())
+                            .child(
+                                page("members/:memberId")
+                                    .child(page("/orgs/:orgId{int}/members/:memberId/:tab").into())
+                                    .into(),
+                            )
+                            .into(),
+                    ),
+                );
+            });
+        });
+
+        let event = cx
+            .update(|cx| Navigator::push(cx, "/orgs/7/members/3/roles?sort=asc"))
+            .into_event()
+            .unwrap();
+        let levels: Vec<Vec<(String, String)>> = event
+            .level_params
+            .iter()
+            .map(|level| {
+                let mut params: Vec<_> = level
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                params.sort();
+                params
+            })
+            .collect();
+        let pair = |key: &str, value: &str| (key.to_string(), value.to_string());
+        assert_eq!(
+            levels,
+            vec![
+                vec![pair("orgId", "7")],
+                vec![],
+                vec![pair("memberId", "3")],
+                vec![pair("tab", "roles")],
+            ]
+        );
+        assert_eq!(event.params.len(), 3);
+
+        assert_eq!(cx.read(Navigator::params_by_level).len(), 4);
+        let org = cx.read(|cx| Navigator::params_for_level(cx, 0));
+        assert_eq!(org.get("orgId").map(String::as_str), Some("7"));
+        assert_eq!(org.len(), 1);
+        assert!(cx.read(|cx| Navigator::params_for_level(cx, 1)).is_empty());
+        assert!(cx.read(|cx| Navigator::params_for_level(cx, 9)).is_empty());
+        let own = cx.read(Navigator::own_params);
+        assert_eq!(own.get("tab").map(String::as_str), Some("roles"));
+        assert_eq!(own.len(), 1);
+
+        // A layout's own path gives a single level
+        cx.update(|cx| Navigator::push(cx, "/orgs/7"));
+        assert_eq!(
+            cx.read(Navigator::own_params)
+                .get("orgId")
+                .map(String::as_str),
+            Some("7")
+        );
+        cx.update(|cx| Navigator::push(cx, "/nowhere"));
+        assert!(cx.read(Navigator::params_by_level).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_renamed_route_alias(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/users/:id", |_, _, _| gpui::div().into_any_element())
+                        .name("users.detail"),
+                );
+                router.add_route(
+                    Route::new("/", |_, _, _| gpui::div().into_any_element()).name("home"),
+                );
+                router.register_alias("user.profile", "users.detail");
+            });
+        });
+
+        let params = RouteParams::single("id", "7");
+        assert_eq!(
+            cx.read(|cx| Navigator::try_url_for(cx, "user.profile", &params)),
+            Ok("/users/7".to_string())
+        );
+        let result = cx.update(|cx| Navigator::push_named(cx, "user.profile", &params));
+        assert!(result.is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/users/7");
+
+        // Aliases survive routes being registered again
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.remove_route("home");
+                assert_eq!(
+                    router.named_routes(),
+                    vec![(
+                        "users.detail".to_string(),
+                        "/users/:id".to_string(),
+                        vec!["user.profile".to_string()]
+                    )]
+                );
+            });
         });
     }
 
-    /// Replace with a specific transition
-    ///
-    /// # Example
-    /// ```ignore
-    /// use gpui_navigator::{Navigator, Transition};
-    ///
-    /// Navigator::replace_with_transition(cx, "/page", Transition::fade(200));
-    /// ```
-    #[cfg(feature = "transition")]
-    pub fn replace_with_transition(
-        cx: &mut impl BorrowAppContext,
-        route: impl IntoRoute,
-        transition: Transition,
-    ) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.replace_with_transition(descriptor.path, transition);
+    #[gpui::test]
+    fn test_push_named_target(cx: &mut TestAppContext) {
+        use crate::NamedTarget;
+
+        crate::targets! {
+            USER_DETAIL = "user.detail";
+        }
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/users/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name(USER_DETAIL),
+                );
+            });
+
+            let event = Navigator::push(
+                cx,
+                NamedTarget::new(USER_DETAIL)
+                    .param("id", 42)
+                    .query("tab", "posts"),
+            )
+            .into_event()
+            .unwrap();
+            assert_eq!(event.to, "/users/42?tab=posts");
+            assert_eq!(Navigator::current_path(cx), "/users/42?tab=posts");
+
+            // Missing params and unknown names are refused
+            let result = Navigator::try_push(cx, NamedTarget::new(USER_DETAIL));
+            assert!(result.is_error());
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::NamedRoute {
+                    error: UrlForError::MissingParams(missing),
+                    ..
+                }) if missing == ["id"]
+            ));
+            let outcome = Navigator::push(cx, NamedTarget::new("user.detial").param("id", 1));
+            assert!(outcome.is_refused());
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::RouteNotFound { path, .. }) if path == "user.detial"
+            ));
+            assert_eq!(Navigator::current_path(cx), "/users/42?tab=posts");
+
+            let result = Navigator::try_push(cx, NamedTarget::new(USER_DETAIL).param("id", 7));
+            assert!(result.is_success());
+            assert_eq!(Navigator::current_path(cx), "/users/7");
         });
     }
 
-    /// Push named route with a specific transition
-    ///
-    /// # Example
-    /// ```ignore
-    /// use gpui_navigator::{Navigator, RouteParams, Transition};
-    ///
-    /// let mut params = RouteParams::new();
-    /// params.set("id".to_string(), "123".to_string());
-    /// Navigator::push_named_with_transition(
-    ///     cx,
-    ///     "user.detail",
-    ///     &params,
-    ///     Transition::slide_right(300)
-    /// );
-    /// ```
-    #[cfg(feature = "transition")]
-    pub fn push_named_with_transition(
-        cx: &mut impl BorrowAppContext,
-        name: &str,
-        params: &RouteParams,
-        transition: Transition,
-    ) {
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.set_next_transition(transition);
-            router.push_named(name, params);
+    #[gpui::test]
+    #[should_panic(expected = "Duplicate route name 'detail'")]
+    fn test_duplicate_route_name_panics(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router
+                    .set_config(RouterConfig::new().on_duplicate_name(DuplicateNamePolicy::Panic));
+                router.add_route(
+                    Route::new("/users/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("detail"),
+                );
+                router.add_route(
+                    Route::new("/posts/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("detail"),
+                );
+            });
         });
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use gpui::{IntoElement, TestAppContext};
+    #[gpui::test]
+    #[should_panic(expected = "Duplicate route name 'overview'")]
+    fn test_duplicate_nested_route_name_panics(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router
+                    .set_config(RouterConfig::new().on_duplicate_name(DuplicateNamePolicy::Panic));
+                for parent in ["/dashboard", "/admin"] {
+                    router.add_route(
+                        Route::new(parent, |_, _cx, _params| gpui::div().into_any_element()).child(
+                            Route::new("overview", |_, _cx, _params| {
+                                gpui::div().into_any_element()
+                            })
+                            .name("overview")
+                            .into(),
+                        ),
+                    );
+                }
+            });
+        });
+    }
 
     #[gpui::test]
-    fn test_nav_push(cx: &mut TestAppContext) {
-        // Initialize router
+    fn test_duplicate_route_name_keeps_first(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/users", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.set_config(
+                    RouterConfig::new().on_duplicate_name(DuplicateNamePolicy::KeepFirst),
+                );
+                router.add_route(
+                    Route::new("/users/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("detail"),
+                );
+                router.add_route(
+                    Route::new("/posts/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("detail"),
+                );
             });
         });
 
-        // Test initial state
-        let initial_path = cx.read(Navigator::current_path);
-        assert_eq!(initial_path, "/");
+        let params = RouteParams::single("id", "1");
+        assert_eq!(
+            cx.read(|cx| Navigator::try_url_for(cx, "detail", &params).ok()),
+            Some("/users/1".to_string())
+        );
+    }
 
-        // Test push navigation
+    #[gpui::test]
+    fn test_active_route_helpers(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            Navigator::push(cx, "/users");
+            init_router(cx, |_| {});
+            // Patterns need not be registered routes
+            Navigator::push(cx, "/inbox/42/reply/");
         });
 
-        let current_path = cx.read(Navigator::current_path);
-        assert_eq!(current_path, "/users");
+        cx.read(|cx| {
+            assert!(Navigator::is_active(cx, "/inbox"));
+            assert!(Navigator::is_active(cx, "/inbox/:threadId"));
+            assert!(Navigator::is_active(cx, "/inbox/:threadId{int}/reply"));
+            assert!(Navigator::is_active(cx, "/inbox/*"));
+            assert!(!Navigator::is_active(cx, "/in"));
+            assert!(!Navigator::is_active(cx, "/inbox/:threadId{uuid}"));
+            assert!(!Navigator::is_active_exact(
+                cx,
+                "/inbox/:threadId{uuid}/reply"
+            ));
+
+            // Constraints are parsed off the param name
+            let route_match =
+                Navigator::matched_against(cx, "/inbox/:threadId{int}/reply").unwrap();
+            assert_eq!(route_match.params.get("threadId"), Some(&"42".to_string()));
+
+            // Trailing slashes don't matter
+            assert!(Navigator::is_active_exact(cx, "/inbox/:threadId/reply"));
+            assert!(Navigator::is_active_exact(cx, "/inbox/42/reply/"));
+            assert!(!Navigator::is_active_exact(cx, "/inbox/:threadId"));
+
+            let route_match = Navigator::matched_against(cx, "/inbox/:threadId/*").unwrap();
+            assert_eq!(route_match.path, "/inbox/42/reply/");
+            assert_eq!(route_match.params.get("threadId"), Some(&"42".to_string()));
+            assert!(Navigator::matched_against(cx, "/settings/*").is_none());
+        });
+    }
 
-        // Test push with parameters
+    #[gpui::test]
+    fn test_preserve_query_keys(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            Navigator::push(cx, "/users/123");
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().preserve_query_keys(["theme", "debug"]));
+                router.add_route(page("/"));
+                router.add_route(page("/reports"));
+                router.add_route(page("/users/:id").name("user"));
+            });
+            Navigator::replace(cx, "/?theme=dark&page=2");
+
+            // Only the listed keys are kept, and keys the path sets win
+            Navigator::push(cx, "/reports?debug=0");
+            assert_eq!(Navigator::current_path(cx), "/reports?debug=0&theme=dark");
+            let route_match = cx
+                .global::<GlobalRouter>()
+                .current_match_immutable()
+                .unwrap();
+            assert_eq!(route_match.query.get("theme"), Some(&"dark".to_string()));
+            assert_eq!(route_match.query.get("debug"), Some(&"0".to_string()));
+            assert!(!route_match.query.contains_key("page"));
+
+            // Named routes are generated without them, then preserved on push
+            let params = RouteParams::single("id", "7");
+            assert_eq!(
+                Navigator::try_url_for(cx, "user", &params).ok().as_deref(),
+                Some("/users/7")
+            );
+            Navigator::push_named(cx, "user", &params);
+            assert_eq!(Navigator::current_path(cx), "/users/7?debug=0&theme=dark");
+            assert_eq!(
+                cx.global::<GlobalRouter>()
+                    .current_match_immutable()
+                    .unwrap()
+                    .params
+                    .get("id"),
+                Some(&"7".to_string())
+            );
+
+            // Back restores the entries as they were
+            Navigator::pop(cx);
+            Navigator::pop(cx);
+            assert_eq!(Navigator::current_path(cx), "/?theme=dark&page=2");
         });
+    }
 
-        let current_path = cx.read(Navigator::current_path);
-        assert_eq!(current_path, "/users/123");
+    #[gpui::test]
+    fn test_push_opts_preserve_query(cx: &mut TestAppContext) {
+        use crate::PreserveQuery;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().preserve_query_keys(["theme"]));
+                router.add_route(page("/"));
+                router.add_route(page("/a"));
+                router.add_route(page("/b"));
+            });
+            Navigator::replace(cx, "/?theme=dark&page=2");
+
+            let all = NavOptions::new().preserve_query(PreserveQuery::All);
+            Navigator::push_opts(cx, "/a?page=3", all);
+            assert_eq!(Navigator::current_path(cx), "/a?page=3&theme=dark");
+
+            let none = NavOptions::new().preserve_query(PreserveQuery::None);
+            Navigator::push_opts(cx, "/b", none);
+            assert_eq!(Navigator::current_path(cx), "/b");
+
+            Navigator::replace_opts(
+                cx,
+                "/a",
+                NavOptions::new().preserve_query(PreserveQuery::Keys(vec!["page".into()])),
+            );
+            assert_eq!(Navigator::current_path(cx), "/a");
+        });
     }
 
     #[gpui::test]
-    fn test_nav_back_forward(cx: &mut TestAppContext) {
-        // Initialize router
+    fn test_strict_navigation(cx: &mut TestAppContext) {
+        let refused = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&refused);
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/page1", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/page2", |_, _cx, _params| {
-                    gpui::div().into_any_element()
+                router.set_config(RouterConfig::new().strict_navigation(true));
+                router.set_error_handlers(ErrorHandlers::new().on_refused(move |_, error| {
+                    seen.lock().unwrap().push(error.to_string());
                 }));
+                router.add_route(page("/"));
+                router.add_route(page("/a"));
             });
+
+            Navigator::push(cx, "/typo");
+            Navigator::replace(cx, "/typo?tab=1");
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert!(!Navigator::can_pop(cx));
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::RouteNotFound { path, .. }) if path == "/typo?tab=1"
+            ));
+            let Some(NavigationError::RouteNotFound {
+                details: Some(details),
+                ..
+            }) = Navigator::last_error(cx)
+            else {
+                panic!("strict navigation should explain the miss");
+            };
+            assert_eq!(details.failing_segment.as_deref(), Some("typo"));
+            assert_eq!(details.candidates, ["a"]);
+
+            // Plain pushes are reported once something renders or reports
+            assert!(refused.lock().unwrap().is_empty());
+            crate::report_refused_navigations(cx);
+            assert_eq!(refused.lock().unwrap().len(), 2);
+
+            assert!(Navigator::try_push(cx, "/missing").is_not_found());
+            assert_eq!(refused.lock().unwrap().len(), 3);
+            assert_eq!(Navigator::current_path(cx), "/");
+
+            assert!(Navigator::try_push(cx, "/a").is_success());
+            assert_eq!(Navigator::current_path(cx), "/a");
+            assert!(Navigator::last_error(cx).is_none());
         });
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_try_push_runs_guards(cx: &mut TestAppContext) {
+        use crate::{AuthGuard, RoleGuard};
 
-        // Navigate to multiple pages
         cx.update(|cx| {
-            Navigator::push(cx, "/page1");
-            Navigator::push(cx, "/page2");
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/login"));
+                router.add_route(page("/admin").guard(AuthGuard::new(|_| false, "/login")));
+                router.add_route(page("/reports").guard(RoleGuard::new(
+                    |_| Some("user".to_string()),
+                    "admin",
+                    None::<String>,
+                )));
+            });
+
+            let result = Navigator::try_push(cx, "/reports");
+            assert!(result.is_blocked());
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::GuardBlocked { .. })
+            ));
+
+            let result = Navigator::try_push(cx, "/admin");
+            assert!(result.is_redirected());
+            assert_eq!(result.redirect_path(), Some("/login"));
+            assert_eq!(Navigator::current_path(cx), "/login");
+            assert!(Navigator::last_error(cx).is_none());
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/page2");
-        assert!(cx.read(Navigator::can_pop));
+    #[cfg(feature = "guard")]
+    fn login_flow(cx: &mut App) -> Arc<std::sync::atomic::AtomicBool> {
+        use crate::AuthGuard;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let logged_in = Arc::new(AtomicBool::new(false));
+        let check = Arc::clone(&logged_in);
+        init_router(cx, |router| {
+            router.add_route(page("/"));
+            router.add_route(page("/home"));
+            router.add_route(page("/login"));
+            router.add_route(page("/settings").guard(AuthGuard::new(
+                move |_| check.load(Ordering::SeqCst),
+                "/login",
+            )));
+        });
+
+        // Redirected to log in, then back to the page asked for
+        Navigator::push(cx, "/home");
+        assert!(Navigator::try_push(cx, "/settings").is_redirected());
+        logged_in.store(true, Ordering::SeqCst);
+        assert!(Navigator::try_push(cx, "/settings").is_success());
+        logged_in
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_pop_skips_redirect_entries(cx: &mut TestAppContext) {
+        use crate::EntryKind;
 
-        // Test back navigation
         cx.update(|cx| {
-            Navigator::pop(cx);
+            login_flow(cx);
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(
+                router.state().history().entries()[2].kind,
+                EntryKind::Redirect {
+                    from: "/settings".to_string()
+                }
+            );
+
+            let event = Navigator::pop(cx).into_event().unwrap();
+            assert_eq!(event.to, "/home");
+            assert_eq!(event.direction, NavigationDirection::Back);
+            // The redirect entry is still there to go forward through
+            assert_eq!(Navigator::forward(cx).into_event().unwrap().to, "/login");
+        });
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_pop_raw_keeps_redirect_entries(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            login_flow(cx);
+            assert_eq!(Navigator::pop_raw(cx).unwrap().to, "/login");
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/page1");
-        assert!(cx.read(Navigator::can_pop));
-        assert!(cx.read(Navigator::can_go_forward));
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_pop_skips_entries_guards_deny(cx: &mut TestAppContext) {
+        use std::sync::atomic::Ordering;
 
-        // Test forward navigation
         cx.update(|cx| {
-            Navigator::forward(cx);
+            let logged_in = login_flow(cx);
+            Navigator::push(cx, "/about");
+            logged_in.store(false, Ordering::SeqCst);
+
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/home");
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/page2");
-        assert!(!cx.read(Navigator::can_go_forward));
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_pop_stays_without_viable_entry(cx: &mut TestAppContext) {
+        use crate::AuthGuard;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/login"));
+                router.add_route(page("/admin").guard(AuthGuard::new(|_| false, "/login")));
+            });
+            Navigator::replace(cx, "/admin");
+            Navigator::push(cx, "/login");
+
+            assert!(Navigator::pop(cx).into_event().is_none());
+            assert_eq!(Navigator::current_path(cx), "/login");
+        });
     }
 
     #[gpui::test]
-    fn test_nav_replace(cx: &mut TestAppContext) {
-        // Initialize router
+    fn test_events_carry_navigation_source(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/login", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/home", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                for path in ["/", "/inbox", "/settings"] {
+                    router.add_route(page(path));
+                }
+            });
+
+            let event = Navigator::push(cx, "/inbox").into_event().unwrap();
+            assert_eq!(event.source, NavigationSource::Programmatic);
+            let shortcut = NavOptions::new().source(NavigationSource::Action);
+            let event = Navigator::push_opts(cx, "/settings", shortcut)
+                .into_event()
+                .unwrap();
+            assert_eq!(event.source, NavigationSource::Action);
+            // The override only applies to that navigation
+            assert_eq!(
+                Navigator::replace(cx, "/").into_event().unwrap().source,
+                NavigationSource::Programmatic
+            );
+
+            let back = Navigator::pop(cx).into_event().unwrap();
+            assert_eq!(back.source, NavigationSource::History);
+            let forward = Navigator::forward(cx).into_event().unwrap();
+            assert_eq!(forward.source, NavigationSource::History);
+        });
+
+        assert_eq!(
+            external_path("https://example.com/inbox?unread=1#top"),
+            "/inbox?unread=1#top"
+        );
+        assert_eq!(external_path("myapp://open?tab=2"), "/?tab=2");
+        assert_eq!(external_path("myapp://open"), "/");
+        assert_eq!(external_path("/settings"), "/settings");
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_guards_read_navigation_source(cx: &mut TestAppContext) {
+        use crate::{guard_fn, GuardResult, TransitionGuard};
+        use std::sync::Mutex;
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/inbox"));
+                // Imports only start from files opened with the app
+                router.add_route(page("/import").guard(guard_fn(|_, request| {
+                    let result = if request.source == NavigationSource::DeepLink {
+                        GuardResult::allow()
+                    } else {
+                        GuardResult::deny("Imports start from outside the app")
+                    };
+                    Box::pin(std::future::ready(result)) as _
+                })));
+                router.add_transition_guard(TransitionGuard::new(
+                    |_| true,
+                    move |_, request| {
+                        log.lock().unwrap().push(request.source.clone());
+                        GuardResult::allow()
+                    },
+                ));
+            });
+
+            assert!(Navigator::try_push(cx, "/import").is_blocked());
+            let result = Navigator::handle_external_url(cx, "myapp://open/import?file=a.csv");
+            assert!(result.is_success());
+            assert_eq!(Navigator::current_path(cx), "/import?file=a.csv");
+            assert!(Navigator::try_push(cx, "/inbox").is_success());
+            // Going back is no deep link, so the import entry is skipped
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/");
+        });
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                NavigationSource::Programmatic,
+                NavigationSource::DeepLink,
+                NavigationSource::Programmatic,
+                NavigationSource::History
+            ]
+        );
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_redirects_carry_guard_source(cx: &mut TestAppContext) {
+        use crate::{GuardResult, TransitionGuard};
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        // Armed once the history is set up
+        let armed = Arc::new(AtomicBool::new(false));
+        let guard_armed = armed.clone();
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                for path in ["/", "/inbox", "/archive"] {
+                    router.add_route(page(path));
+                }
+                router.add_transition_guard(TransitionGuard::new(
+                    move |request| request.to == "/archive" && guard_armed.load(Ordering::SeqCst),
+                    |_, _| GuardResult::redirect("/inbox"),
+                ));
+            });
+            Navigator::push(cx, "/archive");
+            Navigator::push(cx, "/");
+            armed.store(true, Ordering::SeqCst);
+
+            let event = cx
+                .update_global::<GlobalRouter, _>(|router, cx| router.travel_to(cx, 1))
+                .unwrap();
+            assert_eq!(event.to, "/inbox");
+            assert_eq!(
+                event.source,
+                NavigationSource::Redirect {
+                    from_guard: "TransitionGuard".to_string()
+                }
+            );
+            // The source of the navigation redirected is restored afterwards
+            assert_eq!(
+                Navigator::push(cx, "/").into_event().unwrap().source,
+                NavigationSource::Programmatic
+            );
+        });
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_async_guard_denies_push(cx: &mut TestAppContext) {
+        use crate::{guard_fn, GuardResult};
+
+        const CHECK: Duration = Duration::from_millis(30);
+        let signed_in = |cx: &App, _: &NavigationRequest| {
+            let check = cx.background_executor().timer(CHECK);
+            Box::pin(async move {
+                check.await;
+                GuardResult::deny("Not signed in")
+            }) as _
+        };
+        let open = |cx: &App, _: &NavigationRequest| {
+            let check = cx.background_executor().timer(CHECK);
+            Box::pin(async move {
+                check.await;
+                GuardResult::allow()
+            }) as _
+        };
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/admin").guard(guard_fn(signed_in)));
+                router.add_route(page("/reports").guard(guard_fn(open)));
+            });
+            Navigator::push(cx, "/admin");
+            // The push waits for the guard instead of going through
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert_eq!(cx.router().pending_navigation(), Some("/admin"));
+        });
+        cx.executor().advance_clock(CHECK);
+        cx.run_until_parked();
+        cx.update(|cx| {
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert_eq!(cx.router().pending_navigation(), None);
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::GuardBlocked { reason }) if reason == "Not signed in"
+            ));
+
+            // Pushed without a context, the guards are asked on the next tick
+            cx.update_router(|router, _| router.push("/admin".to_string()));
+        });
+        cx.run_until_parked();
+        cx.executor().advance_clock(CHECK);
+        cx.run_until_parked();
+        cx.update(|cx| {
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert!(!Navigator::can_pop(cx));
+
+            Navigator::push(cx, "/reports");
+            assert_eq!(Navigator::current_path(cx), "/");
+        });
+        cx.executor().advance_clock(CHECK);
+        cx.run_until_parked();
+        cx.update(|cx| assert_eq!(Navigator::current_path(cx), "/reports"));
+    }
+
+    /// Router with a `/vault` section marked secure, whose leaving guard
+    /// denies while `deny` is set; returns the paths it was asked about
+    #[cfg(feature = "guard")]
+    fn vault_router(
+        cx: &mut App,
+        deny: Arc<std::sync::atomic::AtomicBool>,
+    ) -> Arc<std::sync::Mutex<Vec<String>>> {
+        use crate::{GuardResult, TransitionGuard};
+        use std::sync::atomic::Ordering;
+
+        let checked = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = checked.clone();
+        init_router(cx, |router| {
+            router.add_route(page("/"));
+            router.add_route(page("/settings"));
+            router.add_route(
+                page("/vault")
+                    .meta("secure", "true")
+                    .children(vec![page("keys").into()]),
+            );
+            router.add_route(page("/audit").meta("secure", "true"));
+            router.add_transition_guard(TransitionGuard::on_leaving_meta(
+                "secure",
+                move |_, request| {
+                    assert_eq!(
+                        request.from_meta.get("secure").map(String::as_str),
+                        Some("true")
+                    );
+                    log.lock().unwrap().push(format!(
+                        "{} -> {}",
+                        request.from_pattern.as_deref().unwrap_or_default(),
+                        request.to
+                    ));
+                    if deny.load(Ordering::SeqCst) {
+                        GuardResult::deny("Vault is unlocked")
+                    } else {
+                        GuardResult::allow()
+                    }
+                },
+            ));
+        });
+        checked
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_transition_guard_on_leaving_meta(cx: &mut TestAppContext) {
+        let deny = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        cx.update(|cx| {
+            let checked = vault_router(cx, deny.clone());
+
+            // Insecure to secure, and within the secure routes: not checked
+            Navigator::try_push(cx, "/vault");
+            Navigator::try_push(cx, "/vault/keys");
+            Navigator::try_push(cx, "/audit");
+            assert!(checked.lock().unwrap().is_empty());
+
+            // Secure to insecure
+            Navigator::try_push(cx, "/vault/keys");
+            Navigator::try_push(cx, "/settings");
+            assert_eq!(*checked.lock().unwrap(), vec!["/vault/keys -> /settings"]);
+        });
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Secure(bool);
+
+    impl crate::MetaKey for Secure {
+        const NAME: &'static str = "secure";
+    }
+
+    #[gpui::test]
+    fn test_current_meta_typed_inherits(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/vault")
+                        .meta_typed(Secure(true))
+                        .meta("secure", "string")
+                        .children(vec![
+                            page("keys").into(),
+                            page("public").meta_typed(Secure(false)).into(),
+                        ]),
+                );
+            });
+            assert_eq!(Navigator::current_meta_typed::<Secure>(cx), None);
+
+            Navigator::push(cx, "/vault/keys");
+            assert_eq!(
+                Navigator::current_meta_typed::<Secure>(cx),
+                Some(Secure(true))
+            );
+            // The string key of the same name is left alone
+            assert_eq!(Navigator::current_meta_typed::<String>(cx), None);
+
+            Navigator::push(cx, "/vault/public");
+            assert_eq!(
+                Navigator::current_meta_typed::<Secure>(cx),
+                Some(Secure(false))
+            );
+        });
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_transition_guard_on_leaving_typed_meta(cx: &mut TestAppContext) {
+        use crate::{GuardResult, TransitionGuard};
+
+        cx.update(|cx| {
+            let checked = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let log = checked.clone();
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/vault")
+                        .meta_typed(Secure(true))
+                        .children(vec![page("keys").into()]),
+                );
+                // Same name as a string key only
+                router.add_route(page("/audit").meta("secure", "true"));
+                router.add_transition_guard(TransitionGuard::on_leaving_meta_typed::<Secure, _>(
+                    move |_, request| {
+                        assert_eq!(request.from_typed_meta.get(), Some(&Secure(true)));
+                        log.lock().unwrap().push(request.to.clone());
+                        GuardResult::allow()
+                    },
+                ));
+            });
+
+            Navigator::try_push(cx, "/vault/keys");
+            Navigator::try_push(cx, "/vault");
+            assert!(checked.lock().unwrap().is_empty());
+            Navigator::try_push(cx, "/audit");
+            Navigator::try_push(cx, "/");
+            assert_eq!(*checked.lock().unwrap(), vec!["/audit"]);
+        });
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_navigation_timeout_aborts_hanging_guard(cx: &mut TestAppContext) {
+        use crate::guard_fn;
+
+        const TIMEOUT: Duration = Duration::from_millis(100);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().navigation_timeout(TIMEOUT));
+                router.add_route(page("/"));
+                router.add_route(page("/settings"));
+                // A guard that never resolves
+                router.add_route(
+                    page("/hang").guard(guard_fn(|_, _| Box::pin(std::future::pending()) as _)),
+                );
             });
+            Navigator::push(cx, "/hang");
+            assert_eq!(cx.router().pending_navigation(), Some("/hang"));
         });
 
-        // Navigate and then replace
+        cx.executor().advance_clock(TIMEOUT);
+        cx.run_until_parked();
         cx.update(|cx| {
-            Navigator::push(cx, "/login");
-            Navigator::replace(cx, "/home");
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert_eq!(cx.router().pending_navigation(), None);
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::Timeout { path, elapsed })
+                    if path == "/hang" && elapsed == TIMEOUT
+            ));
+
+            // The router is not stuck
+            Navigator::push(cx, "/settings");
+            assert_eq!(Navigator::current_path(cx), "/settings");
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/home");
-
-        // After replace, going back should skip the replaced route
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_transition_guard_on_push(cx: &mut TestAppContext) {
+        let deny = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let checked = cx.update(|cx| {
+            let checked = vault_router(cx, deny.clone());
+            Navigator::push(cx, "/vault/keys");
+
+            let outcome = Navigator::push(cx, "/settings");
+            assert!(!outcome.is_navigated());
+            assert!(outcome.event().is_none());
+            assert_eq!(Navigator::current_path(cx), "/vault/keys");
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::GuardBlocked { .. })
+            ));
+
+            // Without a context, the guards are asked on the next tick
+            cx.update_router(|router, _| router.replace("/settings".to_string()));
+            checked
+        });
+        cx.run_until_parked();
         cx.update(|cx| {
-            Navigator::pop(cx);
+            assert_eq!(Navigator::current_path(cx), "/vault/keys");
+            cx.update_router(|router, _| router.back());
         });
+        cx.run_until_parked();
+        cx.update(|cx| assert_eq!(Navigator::current_path(cx), "/vault/keys"));
+        assert_eq!(
+            *checked.lock().unwrap(),
+            vec![
+                "/vault/keys -> /settings",
+                "/vault/keys -> /settings",
+                "/vault/keys -> /",
+            ]
+        );
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/");
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_transition_guard_deny_stays_on_secure_route(cx: &mut TestAppContext) {
+        use std::sync::atomic::Ordering;
+
+        let deny = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        cx.update(|cx| {
+            let checked = vault_router(cx, deny.clone());
+            Navigator::try_push(cx, "/vault/keys");
+
+            Navigator::try_push(cx, "/settings");
+            assert_eq!(Navigator::current_path(cx), "/vault/keys");
+            assert!(matches!(
+                cx.global::<GlobalRouter>().last_error(),
+                Some(NavigationError::GuardBlocked { .. })
+            ));
+            assert!(Navigator::can_access(cx, "/settings").is_blocked());
+            assert!(Navigator::can_access(cx, "/audit").is_allowed());
+
+            // Back and forward are checked too
+            assert!(Navigator::pop(cx).into_event().is_none());
+            assert_eq!(Navigator::current_path(cx), "/vault/keys");
+            deny.store(false, Ordering::SeqCst);
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/");
+            Navigator::forward(cx);
+            deny.store(true, Ordering::SeqCst);
+            assert!(Navigator::go_to_index(cx, 0).is_none());
+            assert_eq!(Navigator::current_path(cx), "/vault/keys");
+            assert_eq!(
+                *checked.lock().unwrap(),
+                vec![
+                    "/vault/keys -> /settings",
+                    "/vault/keys -> /settings",
+                    "/vault/keys -> /",
+                    "/vault/keys -> /",
+                    "/vault/keys -> /",
+                ]
+            );
+        });
     }
 
+    #[cfg(feature = "guard")]
     #[gpui::test]
-    fn test_nav_can_go_back_boundaries(cx: &mut TestAppContext) {
-        // Initialize router
+    fn test_transition_guard_on_table_switch(cx: &mut TestAppContext) {
+        use crate::{GuardResult, TransitionGuard};
+
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
+                router.define_table("app", |router| {
+                    router.add_route(page("/"));
+                    router.add_route(page("/vault").meta("secure", "true"));
+                });
+                router.define_table("auth", |router| {
+                    router.add_route(page("/login"));
+                });
+                router.switch_table("app", "/vault").unwrap();
+                router.add_transition_guard(TransitionGuard::on_leaving_meta("secure", |_, _| {
+                    GuardResult::deny("Lock the vault first")
                 }));
             });
+
+            assert_eq!(
+                GlobalRouter::activate_table(cx, "auth", "/login").unwrap_err(),
+                "Lock the vault first"
+            );
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.active_table(), "app");
+            assert_eq!(router.current_path(), "/vault");
         });
+    }
 
-        // At initial state, can't go back
-        assert!(!cx.read(Navigator::can_pop));
+    /// Router with pages `/`, `/a`, `/b` and `/c` fading in, configured by `config`
+    fn batch_router(cx: &mut App, config: RouterConfig) {
+        init_router(cx, |router| {
+            router.set_config(config);
+            for path in ["/", "/a", "/b", "/c"] {
+                #[cfg(feature = "transition")]
+                router.add_route(page(path).transition(Transition::fade(200)));
+                #[cfg(not(feature = "transition"))]
+                router.add_route(page(path));
+            }
+        });
+    }
 
-        // After navigation, can go back
+    #[gpui::test]
+    fn test_navigator_handle_chain_is_atomic(cx: &mut TestAppContext) {
+        let notified = std::rc::Rc::new(std::cell::Cell::new(0));
         cx.update(|cx| {
-            Navigator::push(cx, "/page1");
+            batch_router(cx, RouterConfig::new());
+            let notified = notified.clone();
+            cx.observe_global::<GlobalRouter>(move |_| notified.set(notified.get() + 1))
+                .detach();
         });
+        notified.set(0);
 
-        assert!(cx.read(Navigator::can_pop));
+        // Observers see the chain once
+        let events = cx.update(|cx| Navigator::of(cx).push("/a").push("/b").push("/c").events());
+        assert_eq!(notified.get(), 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].from.as_deref(), Some("/"));
+        assert_eq!(events[0].to, "/c");
+        assert_eq!(events[0].direction, NavigationDirection::Forward);
 
-        // After going back, can't go back further
         cx.update(|cx| {
-            Navigator::pop(cx);
+            assert_eq!(Navigator::current_path(cx), "/c");
+            // History still goes through every step
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), 4);
+            // Transitions are kept, unlike in batches
+            #[cfg(feature = "transition")]
+            assert!(matches!(
+                cx.global::<GlobalRouter>().state().current_transition(),
+                Transition::Fade { .. }
+            ));
+
+            // Committed on drop
+            Navigator::of(cx).pop().pop();
+            assert_eq!(Navigator::current_path(cx), "/a");
+            assert!(!cx.global::<GlobalRouter>().in_batch());
         });
-
-        assert!(!cx.read(Navigator::can_pop));
     }
 
+    #[cfg(feature = "guard")]
     #[gpui::test]
-    fn test_nav_multiple_pushes(cx: &mut TestAppContext) {
-        // Initialize router
+    fn test_navigator_handle_chain_guards_final_destination(cx: &mut TestAppContext) {
+        use crate::{AuthGuard, RoleGuard};
+
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/step1", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/step2", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/step3", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(page("/"));
+                router.add_route(page("/open"));
+                router.add_route(page("/login"));
+                router.add_route(page("/admin").guard(AuthGuard::new(|_| false, "/login")));
+                router.add_route(page("/reports").guard(RoleGuard::new(
+                    |_| Some("user".to_string()),
+                    "admin",
+                    None::<String>,
+                )));
             });
+
+            // Routes passed through are not guarded
+            let events = Navigator::of(cx).push("/reports").push("/open").events();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].to, "/open");
+
+            // A denied destination leaves the router untouched
+            let events = Navigator::of(cx).push("/").push("/reports").events();
+            assert!(events.is_empty());
+            assert_eq!(Navigator::current_path(cx), "/open");
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), 3);
+            assert!(!cx.global::<GlobalRouter>().in_batch());
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::GuardBlocked { .. })
+            ));
+
+            // A redirect replaces the destination
+            let events = Navigator::of(cx).push("/").push("/admin").events();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].to, "/login");
+            assert_eq!(Navigator::current_path(cx), "/login");
         });
+    }
 
-        // Navigate through multiple pages
+    #[gpui::test]
+    fn test_batch_reports_one_navigation(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            Navigator::push(cx, "/step1");
-            Navigator::push(cx, "/step2");
-            Navigator::push(cx, "/step3");
+            batch_router(cx, RouterConfig::new());
+
+            let event = Navigator::batch(cx, |cx| {
+                Navigator::push(cx, "/a");
+                Navigator::push(cx, "/b");
+                Navigator::push(cx, "/c");
+            })
+            .unwrap();
+            assert_eq!(event.from.as_deref(), Some("/"));
+            assert_eq!(event.to, "/c");
+            assert_eq!(event.direction, NavigationDirection::Forward);
+            assert!(!cx.global::<GlobalRouter>().in_batch());
+
+            let router = cx.global::<GlobalRouter>();
+            #[cfg(feature = "transition")]
+            assert!(matches!(
+                router.state().current_transition(),
+                Transition::None
+            ));
+            // Every push stays in history
+            assert_eq!(router.state().history().len(), 4);
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/b");
+
+            // Nothing navigated
+            assert!(Navigator::batch(cx, |_| {}).is_none());
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/step3");
-
-        // Go back multiple times
+    #[gpui::test]
+    fn test_batch_collapse_history(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            Navigator::pop(cx);
+            batch_router(cx, RouterConfig::new().batch_collapse_history(true));
+
+            let event = Navigator::batch(cx, |cx| {
+                Navigator::push(cx, "/a");
+                Navigator::push(cx, "/b");
+                Navigator::push(cx, "/c");
+            })
+            .unwrap();
+            assert_eq!(event.from.as_deref(), Some("/"));
+            assert_eq!(event.to, "/c");
+
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), 2);
+            assert_eq!(Navigator::pop(cx).into_event().unwrap().to, "/");
         });
-        assert_eq!(cx.read(Navigator::current_path), "/step2");
+    }
 
+    #[gpui::test]
+    fn test_nested_batches_report_once(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            Navigator::pop(cx);
+            batch_router(cx, RouterConfig::new());
+
+            let event = Navigator::batch(cx, |cx| {
+                Navigator::push(cx, "/a");
+                assert!(Navigator::batch(cx, |cx| {
+                    Navigator::push(cx, "/b");
+                })
+                .is_none());
+                Navigator::replace(cx, "/c");
+            })
+            .unwrap();
+            assert_eq!(event.from.as_deref(), Some("/"));
+            assert_eq!(event.to, "/c");
         });
-        assert_eq!(cx.read(Navigator::current_path), "/step1");
+    }
 
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_transitions_suppressed_until_first_frame(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            Navigator::pop(cx);
+            batch_router(
+                cx,
+                RouterConfig::new().suppress_transitions_until_first_frame(true),
+            );
+
+            Navigator::push(cx, "/a");
+            let router = cx.global::<GlobalRouter>();
+            assert!(router.suppresses_transitions());
+            assert!(matches!(
+                router.state().current_transition(),
+                Transition::None
+            ));
+
+            cx.update_global::<GlobalRouter, _>(|router, _| router.frame_presented());
+            Navigator::push(cx, "/b");
+            assert!(matches!(
+                cx.global::<GlobalRouter>().state().current_transition(),
+                Transition::Fade {
+                    duration_ms: 200,
+                    ..
+                }
+            ));
         });
-        assert_eq!(cx.read(Navigator::current_path), "/");
     }
 
     #[gpui::test]
-    fn test_nav_with_route_parameters(cx: &mut TestAppContext) {
-        // Initialize router
+    fn test_dump_state_reflects_navigation(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new(
-                    "/posts/:id/comments/:commentId",
-                    |_, _cx, _params| gpui::div().into_any_element(),
-                ));
+                router.add_route(page("/").name("home"));
+                router.add_route(
+                    page("/users").children(vec![page(":id").name("user.detail").into()]),
+                );
             });
+            Navigator::push(cx, "/users");
+            Navigator::push(cx, "/users/7?tab=posts&tab=likes");
+            Navigator::push(cx, "/users/8");
+            Navigator::pop(cx);
+
+            let dump = cx.global::<GlobalRouter>().dump_state();
+            assert_eq!(dump.path, "/users/7?tab=posts&tab=likes");
+            assert_eq!(dump.params.get("id").map(String::as_str), Some("7"));
+            assert_eq!(dump.query["tab"], vec!["posts", "likes"]);
+            assert_eq!(
+                dump.matched,
+                vec![
+                    RouteDump {
+                        path: "/users".to_string(),
+                        name: None,
+                    },
+                    RouteDump {
+                        path: "/users/:id".to_string(),
+                        name: Some("user.detail".to_string()),
+                    },
+                ]
+            );
+            let history: Vec<_> = dump
+                .history
+                .iter()
+                .map(|entry| entry.path.as_str())
+                .collect();
+            assert_eq!(
+                history,
+                vec!["/", "/users", "/users/7?tab=posts&tab=likes", "/users/8"]
+            );
+            assert_eq!(dump.history_index, 2);
+            assert_eq!(dump.route_count, 3);
+            assert_eq!(dump.named_routes, vec!["home", "user.detail"]);
+            assert_eq!(dump.pending_navigation, None);
+            assert_eq!(dump.last_error, None);
+
+            let report = Navigator::dump(cx);
+            assert!(report.contains("  matched: /users > /users/:id (user.detail)"));
+            assert!(report.contains("  history (3 of 4):"));
+            assert!(report.contains("    > /users/7?tab=posts&tab=likes"));
+
+            let debug = format!("{:?}", cx.global::<GlobalRouter>());
+            assert!(debug.starts_with("GlobalRouter {"), "{}", debug);
+            assert!(debug.contains("/users/8"));
         });
+    }
+
+    #[gpui::test]
+    fn test_dump_state_redacts_keys(cx: &mut TestAppContext) {
+        use crate::dump::REDACTED;
 
-        // Navigate to routes with parameters
         cx.update(|cx| {
-            Navigator::push(cx, "/users/42");
+            init_router(cx, |router| {
+                router.set_config(
+                    RouterConfig::new()
+                        .redact_keys(["token"])
+                        .strict_navigation(true),
+                );
+                router.add_route(page("/"));
+                router.add_route(page("/reset/:token"));
+            });
+            Navigator::push(cx, "/reset/abc123?token=xyz&step=2");
+            Navigator::push(cx, "/missing?token=xyz");
+
+            let dump = cx.global::<GlobalRouter>().dump_state();
+            assert_eq!(dump.path, "/reset/[redacted]?token=[redacted]&step=2");
+            assert_eq!(dump.params["token"], REDACTED);
+            assert_eq!(dump.query["token"], vec![REDACTED]);
+            assert_eq!(dump.query["step"], vec!["2"]);
+            assert_eq!(dump.history[1].path, dump.path);
+            assert_eq!(
+                dump.last_error.as_deref(),
+                Some("Route not found: /missing?token=[redacted]")
+            );
+
+            let report = Navigator::dump(cx);
+            assert!(!report.contains("abc123"), "{}", report);
+            assert!(!report.contains("xyz"), "{}", report);
+            #[cfg(feature = "serde")]
+            {
+                let json = serde_json::to_string(&dump).unwrap();
+                assert!(!json.contains("abc123") && !json.contains("xyz"));
+            }
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/users/42");
+    /// Leave confirmations asked so far, with their message
+    #[cfg(feature = "guard")]
+    type Asked = Arc<std::sync::Mutex<Vec<(String, LeaveDecision)>>>;
 
+    #[cfg(feature = "guard")]
+    fn confirm_leave_router(cx: &mut TestAppContext) -> Asked {
+        let asked = Asked::default();
+        let handler_asked = asked.clone();
         cx.update(|cx| {
-            Navigator::push(cx, "/posts/123/comments/456");
+            init_router(cx, move |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/form").confirm_leave("Discard changes?"));
+                router.add_route(page("/other"));
+                router.set_confirm_handler(move |_, message, decide| {
+                    handler_asked
+                        .lock()
+                        .unwrap()
+                        .push((message.to_string(), decide));
+                });
+            });
+            Navigator::push(cx, "/form");
+            Navigator::set_dirty(cx, true);
         });
+        asked
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/posts/123/comments/456");
+    /// Answer the only leave confirmation asked
+    #[cfg(feature = "guard")]
+    fn answer(cx: &mut TestAppContext, asked: &Asked, leave: bool) {
+        let (message, decide) = asked.lock().unwrap().pop().unwrap();
+        assert_eq!(message, "Discard changes?");
+        decide(leave);
+        cx.run_until_parked();
     }
 
+    #[cfg(feature = "guard")]
     #[gpui::test]
-    fn test_navigator_api_style(cx: &mut TestAppContext) {
-        // Initialize router
-        cx.update(|cx| {
-            init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/home", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/profile", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-            });
-        });
+    fn test_confirm_leave_accept(cx: &mut TestAppContext) {
+        let asked = confirm_leave_router(cx);
 
-        // Test Flutter-style Navigator.of(context).push()
         cx.update(|cx| {
-            Navigator::of(cx).push("/home");
+            Navigator::push(cx, "/other");
+            assert_eq!(Navigator::current_path(cx), "/form");
+            assert_eq!(
+                cx.global::<GlobalRouter>().confirming_leave(),
+                Some("Discard changes?")
+            );
+            crate::prompt_leave_confirmation(cx);
+            // Asked once, even if prompted again
+            crate::prompt_leave_confirmation(cx);
         });
+        assert_eq!(asked.lock().unwrap().len(), 1);
 
-        assert_eq!(cx.read(Navigator::current_path), "/home");
-
-        // Test chaining
+        answer(cx, &asked, true);
         cx.update(|cx| {
-            Navigator::of(cx).push("/profile").pop();
+            assert_eq!(Navigator::current_path(cx), "/other");
+            assert!(!Navigator::is_dirty(cx));
+            assert!(cx.global::<GlobalRouter>().confirming_leave().is_none());
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_confirm_leave_reject(cx: &mut TestAppContext) {
+        let asked = confirm_leave_router(cx);
 
-        // Test replace
+        // Going back is held too
         cx.update(|cx| {
-            Navigator::of(cx).replace("/profile");
+            assert!(Navigator::pop(cx).into_event().is_none());
+            crate::prompt_leave_confirmation(cx);
         });
+        answer(cx, &asked, false);
+        cx.update(|cx| {
+            assert_eq!(Navigator::current_path(cx), "/form");
+            assert!(Navigator::is_dirty(cx));
+            assert!(cx.global::<GlobalRouter>().confirming_leave().is_none());
 
-        assert_eq!(cx.read(Navigator::current_path), "/profile");
-
-        // After replace, we're still at index 1 in history, so we can still go back to "/"
-        assert!(cx.read(Navigator::can_pop));
+            // Staying on the same path needs no confirmation
+            Navigator::replace(cx, "/form");
+            assert!(cx.global::<GlobalRouter>().confirming_leave().is_none());
 
-        // Pop back to "/"
+            // The next navigation away asks again
+            Navigator::of(cx).push("/other").push("/");
+            crate::prompt_leave_confirmation(cx);
+        });
+        answer(cx, &asked, true);
         cx.update(|cx| {
-            Navigator::of(cx).pop();
+            // The chain is applied as a whole
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), 4);
         });
-
-        assert_eq!(cx.read(Navigator::current_path), "/");
-
-        // Now we're at the root, can't go back anymore
-        assert!(!cx.read(Navigator::can_pop));
     }
 
+    #[cfg(feature = "guard")]
     #[gpui::test]
-    fn test_material_route_with_params(cx: &mut TestAppContext) {
-        use crate::PageRoute;
-
-        // Initialize router
+    fn test_dirty_cleared_after_navigation(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(page("/"));
+                router.add_route(page("/notes"));
+                router.add_route(page("/form").confirm_leave("Discard changes?"));
             });
-        });
 
-        // Test PageRoute with params
-        cx.update(|cx| {
-            Navigator::push(
-                cx,
-                PageRoute::builder("/users/:id", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                })
-                .with_param("id", "123"),
-            );
+            // Routes without a confirmation are left right away
+            Navigator::push(cx, "/notes");
+            Navigator::set_dirty(cx, true);
+            Navigator::push(cx, "/");
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert!(!Navigator::is_dirty(cx));
+            Navigator::pop(cx);
+            assert!(!Navigator::is_dirty(cx));
+
+            // Without a confirm handler, leaving a dirty route is refused
+            Navigator::push(cx, "/form");
+            Navigator::set_dirty(cx, true);
+            Navigator::push(cx, "/");
+            assert_eq!(Navigator::current_path(cx), "/form");
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::GuardBlocked { .. })
+            ));
+
+            // Saved: nothing to confirm
+            Navigator::set_dirty(cx, false);
+            Navigator::push(cx, "/");
+            assert_eq!(Navigator::current_path(cx), "/");
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/users/:id");
-
-        // Test with Navigator.of() style
-        cx.update(|cx| {
-            Navigator::of(cx).push(
-                PageRoute::builder("/users/:id", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                })
-                .with_param("id", "456"),
-            );
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_confirm_leave_on_close(cx: &mut TestAppContext) {
+        let asked = confirm_leave_router(cx);
+        let window = cx.add_window(|window, cx| {
+            Navigator::confirm_leave_on_close(window, cx);
+            crate::RouterOutlet::new()
         });
+        let cx = &mut gpui::VisualTestContext::from_window(window.into(), cx);
 
-        assert_eq!(cx.read(Navigator::current_path), "/users/:id");
+        assert!(!cx.simulate_close());
+        assert_eq!(asked.lock().unwrap().len(), 1);
+        answer(cx, &asked, false);
+        assert!(window.update(cx, |_, _, _| ()).is_ok());
+
+        assert!(!cx.simulate_close());
+        answer(cx, &asked, true);
+        assert!(window.update(cx, |_, _, _| ()).is_err());
     }
 
     #[gpui::test]
-    fn test_string_into_route(cx: &mut TestAppContext) {
-        // Initialize router
+    fn test_navigate_from_window(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/home", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                for path in ["/", "/inbox", "/sent"] {
+                    router.add_route(Route::new(path, |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    }));
+                }
             });
         });
-
-        // Test that strings still work with IntoRoute
-        cx.update(|cx| {
-            Navigator::push(cx, "/home");
+        let (_, cx) = cx.add_window_view(|_, _| crate::RouterOutlet::new());
+
+        cx.update(|window, cx| {
+            let event = Navigator::push_in(window, cx, "/inbox")
+                .into_event()
+                .unwrap();
+            assert_eq!(event.to, "/inbox");
+            window.navigator(cx).push("/sent");
+            assert_eq!(
+                window.navigator(cx).pop().into_event().unwrap().to,
+                "/inbox"
+            );
+            assert_eq!(
+                Navigator::forward_in(window, cx).into_event().unwrap().to,
+                "/sent"
+            );
+            Navigator::replace_in(window, cx, "/");
         });
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert_eq!(
+            cx.read(|cx| cx.global::<GlobalRouter>().state().history().len()),
+            3
+        );
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+    #[gpui::test]
+    fn test_use_router_on_context(cx: &mut TestAppContext) {
+        cx.update(|cx| init_router(cx, |_| {}));
+        let (view, cx) = cx.add_window_view(|_, _| crate::RouterOutlet::new());
 
-        // Test with &str
-        cx.update(|cx| {
-            let path = "/home";
-            Navigator::push(cx, path);
+        view.update(cx, |_, cx| {
+            cx.update_router(|router, _| router.push("/settings".to_string()));
+            assert_eq!(cx.router().current_path(), "/settings");
         });
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+    /// App state changed by the effects of a navigation
+    struct Session(Vec<&'static str>);
 
-        // Test String
-        cx.update(|cx| {
-            Navigator::push(cx, String::from("/home"));
-        });
+    impl Global for Session {}
 
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+    /// View observing the router and the session, counting its renders
+    struct RenderCounter {
+        renders: Arc<std::sync::atomic::AtomicUsize>,
     }
 
-    #[gpui::test]
-    fn test_both_api_styles(cx: &mut TestAppContext) {
-        // Initialize router
+    impl gpui::Render for RenderCounter {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+            use gpui::{InteractiveElement, StatefulInteractiveElement, Styled};
+
+            self.renders
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            gpui::div()
+                .id("sign-out")
+                .size_full()
+                .on_click(cx.listener(|_, _, _, cx| {
+                    Navigator::push(cx, "/login");
+                    for step in ["cleared", "tracked"] {
+                        GlobalRouter::queue_effect(cx, move |cx| {
+                            cx.global_mut::<Session>().0.push(step);
+                        });
+                    }
+                }))
+        }
+    }
+
+    fn open_counter(
+        cx: &mut TestAppContext,
+    ) -> (
+        Arc<std::sync::atomic::AtomicUsize>,
+        &mut gpui::VisualTestContext,
+    ) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/page1", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/page2", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(Route::new("/", |_, _, _| gpui::div()));
+                router.add_route(Route::new("/login", |_, _, _| gpui::div()));
             });
+            cx.set_global(Session(Vec::new()));
         });
-
-        // Use static API
-        cx.update(|cx| {
-            Navigator::push(cx, "/page1");
+        let renders = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = Arc::clone(&renders);
+        let (_, cx) = cx.add_window_view(move |_, cx| {
+            cx.observe_global::<GlobalRouter>(|_, cx| cx.notify())
+                .detach();
+            cx.observe_global::<Session>(|_, cx| cx.notify()).detach();
+            RenderCounter { renders: counted }
         });
-        assert_eq!(cx.read(Navigator::current_path), "/page1");
+        cx.run_until_parked();
+        renders.store(0, std::sync::atomic::Ordering::SeqCst);
+        (renders, cx)
+    }
 
-        // Use Flutter-style API
-        cx.update(|cx| {
-            Navigator::of(cx).push("/page2");
-        });
-        assert_eq!(cx.read(Navigator::current_path), "/page2");
+    #[gpui::test]
+    fn test_navigation_effects_render_once(cx: &mut TestAppContext) {
+        let (renders, cx) = open_counter(cx);
+
+        // Pressing the button redraws it; count from its release
+        let position = gpui::point(gpui::px(5.), gpui::px(5.));
+        cx.simulate_mouse_down(position, gpui::MouseButton::Left, gpui::Modifiers::none());
+        cx.run_until_parked();
+        renders.store(0, std::sync::atomic::Ordering::SeqCst);
+        cx.simulate_mouse_up(position, gpui::MouseButton::Left, gpui::Modifiers::none());
+        cx.run_until_parked();
+
+        assert_eq!(cx.read(Navigator::current_path), "/login");
+        assert_eq!(
+            cx.read(|cx| cx.global::<Session>().0.clone()),
+            vec!["cleared", "tracked"]
+        );
+        assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
 
-        // Mix both styles
-        cx.update(|cx| {
-            Navigator::pop(cx); // Static API
+    #[gpui::test]
+    fn test_batched_effects_wait_for_the_batch(cx: &mut TestAppContext) {
+        let (renders, cx) = open_counter(cx);
+
+        cx.update(|_, cx| {
+            GlobalRouter::with_batched_effects(cx, |cx| {
+                Navigator::push(cx, "/login");
+                GlobalRouter::queue_effect(cx, |cx| cx.global_mut::<Session>().0.push("cleared"));
+                // Nested calls flush with the outermost one
+                GlobalRouter::with_batched_effects(cx, |cx| {
+                    GlobalRouter::extend(cx, |router| {
+                        router.add_route(Route::new("/welcome", |_, _, _| gpui::div()));
+                    });
+                    GlobalRouter::queue_effect(cx, |cx| {
+                        cx.global_mut::<Session>().0.push("tracked");
+                    });
+                });
+                assert!(cx.global::<Session>().0.is_empty());
+            });
         });
-        assert_eq!(cx.read(Navigator::current_path), "/page1");
+        cx.run_until_parked();
 
-        cx.update(|cx| {
-            Navigator::of(cx).pop(); // Flutter style
-        });
-        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert_eq!(
+            cx.read(|cx| cx.global::<Session>().0.clone()),
+            vec!["cleared", "tracked"]
+        );
+        assert_eq!(renders.load(std::sync::atomic::Ordering::SeqCst), 1);
     }
 }
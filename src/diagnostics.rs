@@ -0,0 +1,471 @@
+//! Structured navigation diagnostics
+//!
+//! With the `tracing` feature, navigation is traced as a tree of spans:
+//!
+//! - `router.navigate` (info) with `from`, `to`, `direction` and `outcome`
+//!   - `match` (debug) with `path` and the matched `route`
+//!   - `guards` (debug) with one event per guard (`guard`, `priority`, `result`)
+//!   - `middleware` (debug) with `path` and `phase`
+//! - `render.outlet` (trace) with `outlet`, `path`, the resolved `child` and
+//!   whether the parent route lookup was a `cache` hit or miss
+//!
+//! Not-found paths, denying guards and redirect loops emit warn-level events
+//! with a `path` field. Without `tracing` every helper here is a no-op; the
+//! `log` backend keeps its plain messages from the logging macros.
+
+use crate::route::RouteRef;
+#[cfg(feature = "tracing")]
+use crate::scope::route_scopes;
+use crate::NavigationDirection;
+
+/// How a navigation ended, recorded as the `outcome` of `router.navigate`
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The path was committed and matches a route
+    Success,
+    /// The path was committed but matches no route
+    NotFound,
+    /// The commit waits for a blocking loader
+    Pending,
+    /// Held back until the throttling interval elapses
+    Throttled,
+    /// Dropped by throttling
+    Dropped,
+    /// Back or forward without a history entry to go to
+    NoHistory,
+    /// Not committed because no route matches (strict navigation)
+    Refused,
+}
+
+impl Outcome {
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::NotFound => "not_found",
+            Self::Pending => "pending",
+            Self::Throttled => "throttled",
+            Self::Dropped => "dropped",
+            Self::NoHistory => "no_history",
+            Self::Refused => "refused",
+        }
+    }
+}
+
+/// An entered span, exited on drop
+pub struct Entered {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+}
+
+impl Entered {
+    /// Record a field declared when the span was created
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables, clippy::unused_self))]
+    pub fn record(&self, field: &str, value: &str) {
+        #[cfg(feature = "tracing")]
+        self.span.record(field, value);
+    }
+
+    /// Record a field whose value is only computed with `tracing` enabled
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables, clippy::unused_self))]
+    pub fn record_with(&self, field: &str, value: impl FnOnce() -> Option<String>) {
+        #[cfg(feature = "tracing")]
+        if let Some(value) = value() {
+            self.record(field, &value);
+        }
+    }
+
+    /// Record the `outcome` of `router.navigate`
+    pub fn outcome(&self, outcome: Outcome) {
+        self.record("outcome", outcome.as_str());
+    }
+
+    /// Match `path` in a `match` span and record the outcome of the commit
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables, clippy::unused_self))]
+    pub fn matched(&self, routes: &[RouteRef], path: &str) {
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::debug_span!("match", path, route = tracing::field::Empty).entered();
+            match route_scopes(routes, path).last() {
+                Some(scope) => {
+                    span.record("route", scope.route.as_str());
+                    self.outcome(Outcome::Success);
+                }
+                None => {
+                    tracing::warn!(path, "No route matches the path");
+                    self.outcome(Outcome::NotFound);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<tracing::Span> for Entered {
+    fn from(span: tracing::Span) -> Self {
+        Self {
+            span: span.entered(),
+        }
+    }
+}
+
+/// Enter the `router.navigate` span
+///
+/// `to` is recorded later for back and forward, where the target is only
+/// known once history has moved.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn navigate(from: &str, to: Option<&str>, direction: NavigationDirection) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!(
+        "router.navigate",
+        from,
+        to,
+        direction = ?direction,
+        outcome = tracing::field::Empty,
+    )
+    .into();
+    #[cfg(not(feature = "tracing"))]
+    Entered {}
+}
+
+/// Enter the `guards` span for the guards of `path`
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+#[cfg_attr(not(feature = "guard"), allow(dead_code))]
+pub fn guards(path: &str) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!("guards", path).into();
+    #[cfg(not(feature = "tracing"))]
+    Entered {}
+}
+
+/// Report the result of one guard inside the `guards` span
+///
+/// A `deny` or `redirect` result also emits a warn-level event for `path`.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+#[cfg_attr(not(feature = "guard"), allow(dead_code))]
+pub fn guard_result(path: &str, guard: &str, priority: i32, result: &str) {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::debug!(guard, priority, result, "Guard checked");
+        if matches!(result, "deny" | "redirect") {
+            tracing::warn!(path, guard, result, "Guard blocked navigation");
+        }
+    }
+}
+
+/// Enter the `middleware` span for the `before` or `after` phase of `path`
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+#[cfg_attr(not(feature = "middleware"), allow(dead_code))]
+pub fn middleware(path: &str, phase: &str) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!("middleware", path, phase).into();
+    #[cfg(not(feature = "tracing"))]
+    Entered {}
+}
+
+/// Report that following redirects from `path` was given up
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+#[cfg_attr(not(feature = "guard"), allow(dead_code))]
+pub fn redirect_loop(path: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(path, "Redirect loop");
+}
+
+/// Enter the `render.outlet` span
+///
+/// `child` and `cache` are recorded once known.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn render_outlet(outlet: Option<&str>, path: &str) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::trace_span!(
+        "render.outlet",
+        outlet = outlet.unwrap_or("default"),
+        path,
+        child = tracing::field::Empty,
+        cache = tracing::field::Empty,
+    )
+    .into();
+    #[cfg(not(feature = "tracing"))]
+    Entered {}
+}
+
+/// Record whether the parent route lookup of the outlet being rendered hit
+/// the cache
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+#[cfg_attr(not(feature = "cache"), allow(dead_code))]
+pub fn outlet_cache(hit: bool) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("cache", if hit { "hit" } else { "miss" });
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::{init_router, Navigator, Route, RouterOutlet};
+    use gpui::{div, IntoElement, TestAppContext};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    /// A span, or a warn-level event, with its recorded fields
+    #[derive(Debug, Default)]
+    struct CapturedSpan {
+        name: &'static str,
+        fields: HashMap<String, String>,
+        /// Index of the span it was recorded in
+        parent: Option<usize>,
+    }
+
+    impl Visit for CapturedSpan {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.fields
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    /// Subscriber keeping every span and warn-level event, in order, with
+    /// the span each was entered in
+    #[derive(Clone, Default)]
+    struct Capture {
+        spans: Arc<Mutex<Vec<CapturedSpan>>>,
+        entered: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl Capture {
+        fn spans(&self, name: &str) -> Vec<HashMap<String, String>> {
+            self.spans
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|span| span.name == name)
+                .map(|span| span.fields.clone())
+                .collect()
+        }
+
+        /// Paths of the warn-level events
+        fn warnings(&self) -> Vec<String> {
+            self.spans("warn")
+                .into_iter()
+                .filter_map(|mut fields| fields.remove("path"))
+                .collect()
+        }
+
+        /// Spans and warnings named `name` or nested in one, indented by
+        /// depth, e.g. `  guards /admin`
+        #[cfg_attr(not(feature = "guard"), allow(dead_code))]
+        fn tree(&self, name: &str) -> Vec<String> {
+            let spans = self.spans.lock().unwrap();
+            let ancestors = |mut index: usize| {
+                let mut chain = vec![index];
+                while let Some(parent) = spans[index].parent {
+                    chain.push(parent);
+                    index = parent;
+                }
+                chain
+            };
+            (0..spans.len())
+                .filter_map(|index| {
+                    let chain = ancestors(index);
+                    let root = chain.iter().rposition(|&i| spans[i].name == name)?;
+                    let span = &spans[index];
+                    let details: Vec<&str> = ["phase", "message", "to", "path"]
+                        .iter()
+                        .filter_map(|field| span.fields.get(*field).map(String::as_str))
+                        .collect();
+                    Some(format!(
+                        "{}{} {}",
+                        "  ".repeat(root),
+                        span.name,
+                        details.join(" ")
+                    ))
+                })
+                .collect()
+        }
+
+        fn push(&self, mut span: CapturedSpan) -> usize {
+            span.parent = self.entered.lock().unwrap().last().copied();
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(span);
+            spans.len() - 1
+        }
+    }
+
+    /// Index in `Capture::spans` of the span with `id`
+    fn index(id: &Id) -> usize {
+        usize::try_from(id.into_u64()).unwrap() - 1
+    }
+
+    impl tracing::Subscriber for Capture {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut span = CapturedSpan {
+                name: attrs.metadata().name(),
+                ..CapturedSpan::default()
+            };
+            attrs.record(&mut span);
+            Id::from_u64(self.push(span) as u64 + 1)
+        }
+
+        fn record(&self, id: &Id, values: &Record<'_>) {
+            if let Some(span) = self.spans.lock().unwrap().get_mut(index(id)) {
+                values.record(span);
+            }
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            if *event.metadata().level() == tracing::Level::WARN {
+                let mut warning = CapturedSpan {
+                    name: "warn",
+                    ..CapturedSpan::default()
+                };
+                event.record(&mut warning);
+                self.push(warning);
+            }
+        }
+
+        fn enter(&self, id: &Id) {
+            self.entered.lock().unwrap().push(index(id));
+        }
+
+        fn exit(&self, _: &Id) {
+            self.entered.lock().unwrap().pop();
+        }
+    }
+
+    #[gpui::test]
+    fn test_navigate_span_outcome(cx: &mut TestAppContext) {
+        let capture = Capture::default();
+        let _guard = tracing::subscriber::set_default(capture.clone());
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(Route::new("/users/:id", |_, _, _| div().into_any_element()));
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/users/7");
+            Navigator::push(cx, "/missing");
+            window.refresh();
+        });
+        cx.run_until_parked();
+
+        let navigations = capture.spans("router.navigate");
+        assert_eq!(navigations.len(), 2);
+        assert_eq!(navigations[0]["to"], "/users/7");
+        assert_eq!(navigations[0]["direction"], "Forward");
+        assert_eq!(navigations[0]["outcome"], "success");
+        assert_eq!(navigations[1]["from"], "/users/7");
+        assert_eq!(navigations[1]["outcome"], "not_found");
+
+        let matches = capture.spans("match");
+        assert_eq!(matches[0]["route"], "/users/:id");
+        assert_eq!(capture.warnings(), vec!["/missing"]);
+        assert!(capture
+            .spans("render.outlet")
+            .iter()
+            .any(|outlet| outlet["path"] == "/missing"));
+    }
+
+    #[cfg(all(feature = "guard", feature = "middleware"))]
+    #[gpui::test]
+    fn test_push_span_tree(cx: &mut TestAppContext) {
+        use crate::{guard_fn, middleware_fn, GuardResult};
+        use std::future::ready;
+
+        let capture = Capture::default();
+        let _guard = tracing::subscriber::set_default(capture.clone());
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(Route::new("/login", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::new("/admin", |_, _, _| div().into_any_element()).guard(guard_fn(
+                        |_, _| Box::pin(ready(GuardResult::redirect("/login"))) as _,
+                    )),
+                );
+                router.add_middleware(middleware_fn(
+                    |_, _| Box::pin(ready(())) as _,
+                    |_, _| Box::pin(ready(())) as _,
+                ));
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/admin");
+            window.refresh();
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            capture.tree("router.navigate"),
+            vec![
+                "router.navigate /admin",
+                "  middleware before /admin",
+                "  guards /admin",
+                "    warn Guard blocked navigation /admin",
+                "  router.navigate /login",
+                "    middleware before /login",
+                "    match /login",
+            ]
+        );
+        // After middleware runs once the destination is committed
+        let middleware = capture.spans("middleware");
+        assert_eq!(middleware.len(), 3);
+        assert_eq!(middleware[2]["phase"], "after");
+        assert_eq!(middleware[2]["path"], "/login");
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_redirect_loop_span_tree(cx: &mut TestAppContext) {
+        use crate::context::MAX_REDIRECTS;
+        use crate::{guard_fn, GuardResult};
+        use std::future::ready;
+
+        let capture = Capture::default();
+        let _guard = tracing::subscriber::set_default(capture.clone());
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                for (path, to) in [("/a", "/b"), ("/b", "/a")] {
+                    router.add_route(Route::new(path, |_, _, _| div().into_any_element()).guard(
+                        guard_fn(move |_, _| Box::pin(ready(GuardResult::redirect(to))) as _),
+                    ));
+                }
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/a");
+            window.refresh();
+        });
+        cx.run_until_parked();
+
+        // Each redirect navigates inside the one it came from, and the loop
+        // is reported where it is given up
+        let tree = capture.tree("router.navigate");
+        let navigations = tree
+            .iter()
+            .filter(|line| line.trim_start().starts_with("router.navigate"))
+            .count();
+        assert_eq!(navigations, MAX_REDIRECTS + 1);
+        let depth = "  ".repeat(MAX_REDIRECTS + 1);
+        assert!(tree.contains(&format!("{depth}warn Redirect loop /a")));
+    }
+}
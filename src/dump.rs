@@ -0,0 +1,277 @@
+//! Router state dumps for bug reports
+//!
+//! [`GlobalRouter::dump_state`](crate::GlobalRouter::dump_state) collects
+//! what is relevant when debugging navigation into a [`RouterStateDump`]:
+//! the current location and matched routes, history, registered routes and
+//! whatever is pending or failed. `Navigator::dump` formats it as a
+//! multi-line report:
+//!
+//! ```ignore
+//! use gpui_navigator::Navigator;
+//!
+//! eprintln!("{}", Navigator::dump(cx));
+//! ```
+//!
+//! Values of the params and query keys listed in
+//! [`RouterConfig::redact_keys`](crate::RouterConfig::redact_keys) are
+//! replaced with [`REDACTED`], in paths as well as in the maps, so dumps can
+//! be attached to bug reports as they are. With the `serde` feature the dump
+//! serializes to a plain structure.
+
+#[cfg(feature = "cache")]
+use crate::CacheStats;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// What redacted values are replaced with
+pub const REDACTED: &str = "[redacted]";
+
+/// Snapshot of the router's state
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RouterStateDump {
+    /// Current path, including its query
+    pub path: String,
+    /// Params of the current route
+    pub params: BTreeMap<String, String>,
+    /// Query params of the current path, with all their values
+    pub query: BTreeMap<String, Vec<String>>,
+    /// Routes matched by the current path, outermost first
+    pub matched: Vec<RouteDump>,
+    /// History entries, oldest first
+    pub history: Vec<HistoryEntryDump>,
+    /// Index of the current entry in `history`
+    pub history_index: usize,
+    /// Number of registered routes, nested ones included
+    pub route_count: usize,
+    /// Names of the named routes, sorted
+    pub named_routes: Vec<String>,
+    /// Nested route cache statistics
+    #[cfg(feature = "cache")]
+    pub cache_stats: CacheStats,
+    /// Path of a navigation waiting for a blocking loader or throttling
+    pub pending_navigation: Option<String>,
+    /// Error of the last refused navigation
+    pub last_error: Option<String>,
+}
+
+/// A route of the matched chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RouteDump {
+    /// Full path pattern, including the parent routes' paths
+    pub path: String,
+    /// Route name, if it has one
+    pub name: Option<String>,
+}
+
+/// A history entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HistoryEntryDump {
+    /// Path of the entry
+    pub path: String,
+    /// Path a guard redirected away from, for entries committed by a redirect
+    pub redirected_from: Option<String>,
+}
+
+impl fmt::Display for RouterStateDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn pairs<V>(map: &BTreeMap<String, V>, value: impl Fn(&V) -> String) -> String {
+            if map.is_empty() {
+                return "none".to_string();
+            }
+            map.iter()
+                .map(|(key, v)| format!("{}={}", key, value(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        writeln!(f, "Router state")?;
+        writeln!(f, "  path: {}", self.path)?;
+        writeln!(f, "  params: {}", pairs(&self.params, String::clone))?;
+        writeln!(
+            f,
+            "  query: {}",
+            pairs(&self.query, |values| values.join("|"))
+        )?;
+        if self.matched.is_empty() {
+            writeln!(f, "  matched: none")?;
+        } else {
+            let chain: Vec<_> = self
+                .matched
+                .iter()
+                .map(|route| match &route.name {
+                    Some(name) => format!("{} ({})", route.path, name),
+                    None => route.path.clone(),
+                })
+                .collect();
+            writeln!(f, "  matched: {}", chain.join(" > "))?;
+        }
+        writeln!(
+            f,
+            "  history ({} of {}):",
+            self.history_index + 1,
+            self.history.len()
+        )?;
+        for (index, entry) in self.history.iter().enumerate() {
+            let marker = if index == self.history_index {
+                ">"
+            } else {
+                " "
+            };
+            write!(f, "    {} {}", marker, entry.path)?;
+            if let Some(from) = &entry.redirected_from {
+                write!(f, " (redirected from {})", from)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "  routes: {}", self.route_count)?;
+        if self.named_routes.is_empty() {
+            writeln!(f, "  named routes: none")?;
+        } else {
+            writeln!(f, "  named routes: {}", self.named_routes.join(", "))?;
+        }
+        #[cfg(feature = "cache")]
+        writeln!(
+            f,
+            "  cache: parent {}/{} hits, child {}/{} hits, {} invalidations",
+            self.cache_stats.parent_hits,
+            self.cache_stats.parent_hits + self.cache_stats.parent_misses,
+            self.cache_stats.child_hits,
+            self.cache_stats.child_hits + self.cache_stats.child_misses,
+            self.cache_stats.invalidations
+        )?;
+        writeln!(
+            f,
+            "  pending navigation: {}",
+            self.pending_navigation.as_deref().unwrap_or("none")
+        )?;
+        write!(
+            f,
+            "  last error: {}",
+            self.last_error.as_deref().unwrap_or("none")
+        )
+    }
+}
+
+/// Masks the values of redacted keys, remembering them to mask free text
+pub(crate) struct Redactor<'a> {
+    keys: &'a [String],
+    secrets: Vec<String>,
+}
+
+impl<'a> Redactor<'a> {
+    pub(crate) fn new(keys: &'a [String]) -> Self {
+        Self {
+            keys,
+            secrets: Vec::new(),
+        }
+    }
+
+    fn is_redacted(&self, key: &str) -> bool {
+        self.keys.iter().any(|redacted| redacted == key)
+    }
+
+    /// `value` of `key`, masked if the key is redacted
+    pub(crate) fn value(&mut self, key: &str, value: &str) -> String {
+        if !self.is_redacted(key) {
+            return value.to_string();
+        }
+        self.remember(value);
+        REDACTED.to_string()
+    }
+
+    /// `path` with the segments holding redacted `params` and the values of
+    /// redacted query keys masked
+    pub(crate) fn path<'p>(
+        &mut self,
+        path: &str,
+        params: impl IntoIterator<Item = (&'p String, &'p String)>,
+    ) -> String {
+        let secrets: Vec<&String> = params
+            .into_iter()
+            .filter(|(key, value)| self.is_redacted(key) && !value.is_empty())
+            .map(|(_, value)| value)
+            .collect();
+        let (route_path, query) = match path.split_once('?') {
+            Some((route_path, query)) => (route_path, Some(query)),
+            None => (path, None),
+        };
+
+        let mut masked = route_path
+            .split('/')
+            .map(|segment| {
+                if secrets.iter().any(|secret| *secret == segment) {
+                    REDACTED
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        for secret in secrets {
+            // Wildcard params span several segments
+            if secret.contains('/') {
+                masked = masked.replace(secret.as_str(), REDACTED);
+            }
+            self.remember(secret);
+        }
+
+        if let Some(query) = query {
+            let pairs: Vec<String> = query
+                .split('&')
+                .map(|pair| match pair.split_once('=') {
+                    Some((key, value)) => format!("{}={}", key, self.value(key, value)),
+                    None => pair.to_string(),
+                })
+                .collect();
+            masked.push('?');
+            masked.push_str(&pairs.join("&"));
+        }
+        masked
+    }
+
+    /// Mask `secret` in free text from now on
+    fn remember(&mut self, secret: &str) {
+        if !secret.is_empty() && !self.secrets.iter().any(|known| known == secret) {
+            self.secrets.push(secret.to_string());
+        }
+    }
+
+    /// `text` with every value masked so far masked too
+    pub(crate) fn text(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_redact_path() {
+        let keys = vec!["token".to_string()];
+        let mut redactor = Redactor::new(&keys);
+        let params = params(&[("token", "s3cr3t"), ("id", "7")]);
+
+        let path = redactor.path(
+            "/reset/s3cr3t/users/7?token=abc&tab=posts",
+            params.iter().map(|(key, value)| (key, value)),
+        );
+        assert_eq!(path, "/reset/[redacted]/users/7?token=[redacted]&tab=posts");
+        assert_eq!(
+            redactor.text("Invalid link /reset/s3cr3t (abc)"),
+            "Invalid link /reset/[redacted] ([redacted])"
+        );
+    }
+}
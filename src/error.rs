@@ -2,9 +2,13 @@
 //!
 //! Provides error types and handlers for navigation failures, 404s, and other routing errors.
 
-use gpui::{AnyElement, App};
+use crate::matcher::UrlForError;
+use crate::route::{parse_path, PathSegment, RouteRef};
+use crate::GlobalRouter;
+use gpui::{AnyElement, App, BorrowAppContext};
 use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 
 // ============================================================================
 // Navigation Result Types
@@ -22,6 +26,11 @@ pub enum NavigationResult {
         reason: String,
         redirect: Option<String>,
     },
+    /// A guard redirected the navigation, which went to `to` instead of `from`
+    Redirected { from: String, to: String },
+    /// Navigation to `path` waits for guards that have not resolved yet, or
+    /// was held back by throttling
+    Pending { path: String },
     /// Navigation error
     Error(NavigationError),
 }
@@ -30,7 +39,13 @@ pub enum NavigationResult {
 #[derive(Debug, Clone)]
 pub enum NavigationError {
     /// Route not found
-    RouteNotFound { path: String },
+    ///
+    /// `details` says where matching failed, for paths checked against the
+    /// route table (not for unknown route names).
+    RouteNotFound {
+        path: String,
+        details: Option<Box<NotFoundDetails>>,
+    },
 
     /// Guard blocked navigation
     GuardBlocked { reason: String },
@@ -38,9 +53,15 @@ pub enum NavigationError {
     /// Invalid route parameters
     InvalidParams { message: String },
 
+    /// No URL could be generated for the named route `name`
+    NamedRoute { name: String, error: UrlForError },
+
     /// Navigation failed
     NavigationFailed { message: String },
 
+    /// Navigation was still waiting for its blocking loader at the deadline
+    Timeout { path: String, elapsed: Duration },
+
     /// Custom error
     Custom { message: String },
 }
@@ -48,7 +69,7 @@ pub enum NavigationError {
 impl fmt::Display for NavigationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            NavigationError::RouteNotFound { path } => {
+            NavigationError::RouteNotFound { path, .. } => {
                 write!(f, "Route not found: {}", path)
             }
             NavigationError::GuardBlocked { reason } => {
@@ -57,9 +78,15 @@ impl fmt::Display for NavigationError {
             NavigationError::InvalidParams { message } => {
                 write!(f, "Invalid parameters: {}", message)
             }
+            NavigationError::NamedRoute { name, error } => {
+                write!(f, "Invalid parameters for route '{}': {}", name, error)
+            }
             NavigationError::NavigationFailed { message } => {
                 write!(f, "Navigation failed: {}", message)
             }
+            NavigationError::Timeout { path, elapsed } => {
+                write!(f, "Navigation to {} timed out after {:?}", path, elapsed)
+            }
             NavigationError::Custom { message } => {
                 write!(f, "{}", message)
             }
@@ -85,23 +112,202 @@ impl NavigationResult {
         matches!(self, NavigationResult::Blocked { .. })
     }
 
+    /// Check if navigation was redirected
+    pub fn is_redirected(&self) -> bool {
+        matches!(self, NavigationResult::Redirected { .. })
+    }
+
+    /// Check if navigation waits for its guards
+    pub fn is_pending(&self) -> bool {
+        matches!(self, NavigationResult::Pending { .. })
+    }
+
     /// Check if there was an error
     pub fn is_error(&self) -> bool {
         matches!(self, NavigationResult::Error(_))
     }
 
-    /// Get redirect path if blocked with redirect
+    /// Get redirect path if blocked with redirect or redirected
     pub fn redirect_path(&self) -> Option<&str> {
         match self {
             NavigationResult::Blocked {
                 redirect: Some(path),
                 ..
-            } => Some(path),
+            }
+            | NavigationResult::Redirected { to: path, .. } => Some(path),
             _ => None,
         }
     }
 }
 
+// ============================================================================
+// Not Found Details
+// ============================================================================
+
+/// Where matching a path against the route table failed
+///
+/// Computed from the registered routes (named outlets aside): the longest
+/// leading part of the path some route matches, the segment after it and the
+/// segments routes continue that prefix with. Not found pages use it to show
+/// what went wrong and to suggest close matches:
+///
+/// ```ignore
+/// ErrorHandlers::new().on_not_found(|_cx, details| {
+///     let hint = match details.suggestions().first() {
+///         Some(close) => format!("Did you mean {}?", close),
+///         None => String::new(),
+///     };
+///     div().child(format!("Nothing at {}", details)).child(hint).into_any_element()
+/// })
+/// ```
+///
+/// Displays as the attempted path, so handlers written for plain paths keep
+/// formatting the same.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotFoundDetails {
+    /// Path that was navigated to
+    pub attempted: String,
+    /// Longest leading part of the path some route matches, `/` if none
+    pub matched_prefix: String,
+    /// First segment after `matched_prefix`, if the path has one
+    pub failing_segment: Option<String>,
+    /// Segments routes accept after `matched_prefix`, in registration order
+    ///
+    /// Parameters are listed as `:name` and wildcards as `*`.
+    pub candidates: Vec<String>,
+}
+
+impl NotFoundDetails {
+    /// Explain why `path` matches none of `routes`
+    pub(crate) fn new(routes: &[RouteRef], path: &str) -> Self {
+        let segments = crate::nested::path_segments(path);
+        let mut patterns = Vec::new();
+        collect_patterns(routes, "", 0, &mut patterns);
+        let parsed: Vec<Vec<PathSegment<'_>>> =
+            patterns.iter().map(|pattern| parse_path(pattern)).collect();
+
+        let matched = |pattern: &[PathSegment<'_>]| {
+            pattern
+                .iter()
+                .zip(&segments)
+                .take_while(|(part, segment)| match part {
+                    PathSegment::Static(name) => name == *segment,
+                    PathSegment::Param(_) | PathSegment::Wildcard => true,
+                })
+                .count()
+        };
+        let depth = parsed
+            .iter()
+            .map(|pattern| matched(pattern))
+            .max()
+            .unwrap_or(0);
+
+        let mut candidates: Vec<String> = Vec::new();
+        for pattern in &parsed {
+            if pattern.len() <= depth || matched(pattern) < depth {
+                continue;
+            }
+            let candidate = match pattern[depth] {
+                PathSegment::Static(name) => name.to_string(),
+                PathSegment::Param(name) => format!(":{}", name),
+                PathSegment::Wildcard => "*".to_string(),
+            };
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        Self {
+            attempted: path.to_string(),
+            matched_prefix: format!("/{}", segments[..depth].join("/")),
+            failing_segment: segments.get(depth).map(|segment| (*segment).to_string()),
+            candidates,
+        }
+    }
+
+    /// Static candidates close to the failing segment, closest first
+    ///
+    /// Compares case-insensitively and allows about one edit per three
+    /// characters, so `setings` suggests `settings` but `xyz` suggests
+    /// nothing.
+    pub fn suggestions(&self) -> Vec<&str> {
+        let Some(failing) = &self.failing_segment else {
+            return Vec::new();
+        };
+        close_matches(
+            failing,
+            self.candidates
+                .iter()
+                .map(String::as_str)
+                .filter(|candidate| !candidate.starts_with(':') && *candidate != "*"),
+        )
+    }
+}
+
+impl fmt::Display for NotFoundDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.attempted)
+    }
+}
+
+/// Full path patterns of `routes` and their children
+fn collect_patterns(
+    routes: &[RouteRef],
+    parent_path: &str,
+    depth: usize,
+    patterns: &mut Vec<String>,
+) {
+    if routes.is_empty() || crate::route::lookup_too_deep(depth, parent_path) {
+        return;
+    }
+    for route in routes {
+        let full_path = if parent_path.is_empty() {
+            route.config.path.clone()
+        } else {
+            crate::nested::build_child_path(parent_path, &route.config.path).into_owned()
+        };
+        if !route.pathless {
+            patterns.push(full_path.clone());
+        }
+        collect_patterns(route.get_children(), &full_path, depth + 1, patterns);
+    }
+}
+
+/// Candidates close to `word`, closest first
+///
+/// Compares case-insensitively and allows about one edit per three
+/// characters of `word`.
+pub(crate) fn close_matches<'a>(
+    word: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let word = word.to_lowercase();
+    let limit = word.chars().count().div_ceil(3);
+    let mut close: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (levenshtein(&word, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    close.sort_by_key(|(distance, _)| *distance);
+    close.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Number of single-character edits turning `a` into `b`
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
 // ============================================================================
 // Error Handlers
 // ============================================================================
@@ -110,15 +316,25 @@ impl NavigationResult {
 pub type ErrorHandler = Arc<dyn Fn(&mut App, &NavigationError) -> AnyElement + Send + Sync>;
 
 /// Handler for 404 not found
-pub type NotFoundHandler = Arc<dyn Fn(&mut App, &str) -> AnyElement + Send + Sync>;
+pub type NotFoundHandler = Arc<dyn Fn(&mut App, &NotFoundDetails) -> AnyElement + Send + Sync>;
+
+/// Handler notified of navigations the router refused
+pub type RefusedHandler = Arc<dyn Fn(&mut App, &NavigationError) + Send + Sync>;
 
 /// Collection of error handlers for the router
+///
+/// Install them with `GlobalRouter::set_error_handlers` to be notified of
+/// refused navigations.
+#[derive(Clone)]
 pub struct ErrorHandlers {
     /// Handler for 404 not found errors
     pub not_found: Option<NotFoundHandler>,
 
     /// Handler for general navigation errors
     pub error: Option<ErrorHandler>,
+
+    /// Handler for navigations the router refused
+    pub refused: Option<RefusedHandler>,
 }
 
 impl ErrorHandlers {
@@ -127,18 +343,30 @@ impl ErrorHandlers {
         Self {
             not_found: None,
             error: None,
+            refused: None,
         }
     }
 
     /// Set the 404 not found handler
+    ///
+    /// The handler gets the [`NotFoundDetails`] of the path, which outlets
+    /// render it for when no route matches.
     pub fn on_not_found<F>(mut self, handler: F) -> Self
     where
-        F: Fn(&mut App, &str) -> AnyElement + Send + Sync + 'static,
+        F: Fn(&mut App, &NotFoundDetails) -> AnyElement + Send + Sync + 'static,
     {
         self.not_found = Some(Arc::new(handler));
         self
     }
 
+    /// Set a 404 not found handler that only needs the attempted path
+    pub fn on_not_found_path<F>(self, handler: F) -> Self
+    where
+        F: Fn(&mut App, &str) -> AnyElement + Send + Sync + 'static,
+    {
+        self.on_not_found(move |cx, details| handler(cx, &details.attempted))
+    }
+
     /// Set the general error handler
     pub fn on_error<F>(mut self, handler: F) -> Self
     where
@@ -148,9 +376,30 @@ impl ErrorHandlers {
         self
     }
 
+    /// Set the handler for navigations the router refused
+    ///
+    /// Called with the error recorded by a push to an unknown path under
+    /// `RouterConfig::strict_navigation`, a guard denying `Navigator::try_push`
+    /// or an unknown route name.
+    pub fn on_refused<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&mut App, &NavigationError) + Send + Sync + 'static,
+    {
+        self.refused = Some(Arc::new(handler));
+        self
+    }
+
     /// Render a 404 not found page
+    ///
+    /// `path` is explained against the routes of the global router, if one
+    /// is installed.
     pub fn render_not_found(&self, cx: &mut App, path: &str) -> Option<AnyElement> {
-        self.not_found.as_ref().map(|handler| handler(cx, path))
+        let handler = self.not_found.as_ref()?;
+        let details = match cx.try_global::<GlobalRouter>() {
+            Some(router) => router.not_found_details(path),
+            None => NotFoundDetails::new(&[], path),
+        };
+        Some(handler(cx, &details))
     }
 
     /// Render an error page
@@ -165,6 +414,93 @@ impl Default for ErrorHandlers {
     }
 }
 
+/// Pass navigations refused since the last call to the `refused` handler
+///
+/// `Navigator::try_push` calls this right away, and the router after each
+/// navigation, so apps rarely need it.
+pub fn report_refused_navigations(cx: &mut App) {
+    if !cx
+        .try_global::<GlobalRouter>()
+        .is_some_and(|router| !router.unreported_errors.is_empty())
+    {
+        return;
+    }
+    let (errors, handler) = cx.update_global::<GlobalRouter, _>(|router, _| {
+        (
+            std::mem::take(&mut router.unreported_errors),
+            router.error_handlers().refused.clone(),
+        )
+    });
+    let Some(handler) = handler else {
+        return;
+    };
+    for error in &errors {
+        handler(cx, error);
+    }
+}
+
+// ============================================================================
+// Error Boundaries
+// ============================================================================
+
+/// Why a route could not render its content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// The route's builder panicked
+    Panic { message: String },
+    /// The route's blocking loader failed
+    Loader { message: String },
+    /// None of the route's children matched the path
+    NotFound { path: String },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::Panic { message } | RouteError::Loader { message } => {
+                write!(f, "{}", message)
+            }
+            RouteError::NotFound { path } => write!(f, "No route matches '{}'", path),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+/// Re-attempts rendering a route that failed
+///
+/// Handed to error boundaries along with the [`RouteError`].
+#[derive(Debug, Clone)]
+pub struct RetryHandle {
+    path: String,
+}
+
+impl RetryHandle {
+    pub(crate) fn new(path: impl Into<String>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path that failed to render
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Build the route again and rerun its loader if it failed
+    ///
+    /// Does nothing once the router has navigated elsewhere.
+    pub fn retry(&self, cx: &mut App) {
+        if !cx.has_global::<GlobalRouter>() {
+            return;
+        }
+        cx.update_global::<GlobalRouter, _>(|router, _| router.retry(&self.path));
+        crate::context::refresh_windows(cx);
+    }
+}
+
+/// Renders a route's [`RouteError`] in place of its content
+pub type ErrorBoundary =
+    Arc<dyn Fn(&RouteError, RetryHandle, &mut App) -> AnyElement + Send + Sync>;
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -208,6 +544,7 @@ mod tests {
     fn test_navigation_error_display() {
         let error = NavigationError::RouteNotFound {
             path: "/test".to_string(),
+            details: None,
         };
         assert_eq!(error.to_string(), "Route not found: /test");
     }
@@ -220,17 +557,82 @@ mod tests {
     }
 
     #[gpui::test]
+    #[allow(clippy::unused_async, clippy::future_not_send)]
     async fn test_on_not_found(cx: &mut TestAppContext) {
-        let handlers = ErrorHandlers::new()
-            .on_not_found(|_cx, path| div().child(format!("404: {}", path)).into_any_element());
+        let handlers = ErrorHandlers::new().on_not_found(|_cx, details| {
+            div().child(format!("404: {}", details)).into_any_element()
+        });
 
         assert!(handlers.not_found.is_some());
 
         let element = cx.update(|cx| handlers.render_not_found(cx, "/invalid"));
         assert!(element.is_some());
+
+        let seen = Arc::new(std::sync::Mutex::new(String::new()));
+        let handlers = ErrorHandlers::new().on_not_found_path({
+            let seen = Arc::clone(&seen);
+            move |_cx, path| {
+                *seen.lock().unwrap() = path.to_string();
+                div().into_any_element()
+            }
+        });
+        cx.update(|cx| handlers.render_not_found(cx, "/invalid"));
+        assert_eq!(*seen.lock().unwrap(), "/invalid");
+    }
+
+    fn route_table() -> Vec<RouteRef> {
+        use crate::Route;
+
+        vec![
+            Arc::new(Route::new("/", |_, _, _| div())),
+            Arc::new(Route::new("/settings", |_, _, _| div()).children(vec![
+                Route::new("profile", |_, _, _| div()).into(),
+                Route::new("privacy", |_, _, _| div()).into(),
+            ])),
+            Arc::new(Route::new("/users/:id", |_, _, _| div())),
+        ]
+    }
+
+    #[test]
+    fn test_not_found_details_nested_typo() {
+        let details = NotFoundDetails::new(&route_table(), "/settings/profil?tab=1");
+        assert_eq!(details.attempted, "/settings/profil?tab=1");
+        assert_eq!(details.matched_prefix, "/settings");
+        assert_eq!(details.failing_segment.as_deref(), Some("profil"));
+        assert_eq!(details.candidates, ["profile", "privacy"]);
+        assert_eq!(details.suggestions(), ["profile"]);
+
+        // Params match any segment
+        let details = NotFoundDetails::new(&route_table(), "/users/7/posts");
+        assert_eq!(details.matched_prefix, "/users/7");
+        assert_eq!(details.failing_segment.as_deref(), Some("posts"));
+        assert!(details.candidates.is_empty());
+    }
+
+    #[test]
+    fn test_not_found_details_root_typo() {
+        let details = NotFoundDetails::new(&route_table(), "/Setings");
+        assert_eq!(details.matched_prefix, "/");
+        assert_eq!(details.failing_segment.as_deref(), Some("Setings"));
+        assert_eq!(details.candidates, ["settings", "users"]);
+        assert_eq!(details.suggestions(), ["settings"]);
+
+        let details = NotFoundDetails::new(&route_table(), "/xyz");
+        assert!(details.suggestions().is_empty());
+    }
+
+    #[test]
+    fn test_not_found_details_empty_table() {
+        let details = NotFoundDetails::new(&[], "/anything/at/all");
+        assert_eq!(details.matched_prefix, "/");
+        assert_eq!(details.failing_segment.as_deref(), Some("anything"));
+        assert!(details.candidates.is_empty());
+        assert!(details.suggestions().is_empty());
+        assert_eq!(details.to_string(), "/anything/at/all");
     }
 
     #[gpui::test]
+    #[allow(clippy::unused_async, clippy::future_not_send)]
     async fn test_on_error(cx: &mut TestAppContext) {
         let handlers = ErrorHandlers::new()
             .on_error(|_cx, error| div().child(format!("Error: {}", error)).into_any_element());
@@ -239,6 +641,7 @@ mod tests {
 
         let error = NavigationError::RouteNotFound {
             path: "/test".to_string(),
+            details: None,
         };
 
         let element = cx.update(|cx| handlers.render_error(cx, &error));
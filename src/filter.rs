@@ -0,0 +1,592 @@
+//! Route filters for guards and middleware registered on the router
+//!
+//! Guards and middleware attached to a route apply to that route and its
+//! children; those added with [`GlobalRouter::add_middleware`] or
+//! [`GlobalRouter::add_transition_guard`] apply everywhere. A
+//! [`RouteFilter`] is the middle ground: it selects the routes a guard or
+//! middleware added with [`GlobalRouter::add_guard_filtered`] or
+//! [`GlobalRouter::add_middleware_filtered`] applies to.
+//!
+//! ```ignore
+//! use gpui_navigator::{AuthGuard, RouteFilter};
+//!
+//! init_router(cx, |router| {
+//!     // Everything under /admin except its health check
+//!     router.add_middleware_filtered(
+//!         AuditMiddleware::new(),
+//!         RouteFilter::path("/admin/**").except(RouteFilter::path("/admin/health")),
+//!     );
+//!     // Every route tagged `requiresAuth`, whatever its path
+//!     router.add_guard_filtered(
+//!         AuthGuard::new(is_logged_in, "/login"),
+//!         RouteFilter::has_meta("requiresAuth"),
+//!     );
+//! });
+//! ```
+//!
+//! Filters are checked against the route chain a navigation resolves to:
+//! path globs against the path's segments, meta predicates against the meta
+//! of the chain (a child's value over its parent's, typed meta through
+//! [`RouteFilter::has_meta_typed`]), and name patterns
+//! against the names of the routes in the chain. [`GlobalRouter::explain`]
+//! lists the filtered entries that apply to a path.
+//!
+//! [`GlobalRouter::add_middleware`]: crate::GlobalRouter::add_middleware
+//! [`GlobalRouter::add_transition_guard`]: crate::GlobalRouter::add_transition_guard
+//! [`GlobalRouter::add_guard_filtered`]: crate::GlobalRouter::add_guard_filtered
+//! [`GlobalRouter::add_middleware_filtered`]: crate::GlobalRouter::add_middleware_filtered
+//! [`GlobalRouter::explain`]: crate::GlobalRouter::explain
+
+use crate::meta::MetaValue;
+use crate::nested::path_segments;
+use crate::route::RouteRef;
+use std::any::TypeId;
+use std::fmt;
+use std::ops::Not;
+
+/// Selects the routes a filtered guard or middleware applies to
+///
+/// Built from path globs, meta predicates and name patterns, combined with
+/// [`and`](Self::and), [`or`](Self::or), [`except`](Self::except) and `!`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteFilter {
+    kind: FilterKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FilterKind {
+    Path(PathGlob),
+    HasMeta(String),
+    MetaEquals(String, String),
+    HasTypedMeta(TypeId, &'static str),
+    Name(String),
+    And(Box<RouteFilter>, Box<RouteFilter>),
+    Or(Box<RouteFilter>, Box<RouteFilter>),
+    Not(Box<RouteFilter>),
+}
+
+/// A path glob split into segments when the filter is built
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PathGlob {
+    source: String,
+    segments: Vec<GlobSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GlobSegment {
+    Literal(String),
+    /// `*`: exactly one segment
+    One,
+    /// `**`: any number of segments, none included
+    Any,
+}
+
+impl RouteFilter {
+    fn new(kind: FilterKind) -> Self {
+        Self { kind }
+    }
+
+    /// Paths matching `glob`
+    ///
+    /// Segments are compared whole; `*` matches one segment and `**` any
+    /// number of them, none included. `/admin` matches only `/admin`,
+    /// `/admin/*` its direct children and `/admin/**` it and everything
+    /// below. The query and fragment of the path are ignored.
+    pub fn path(glob: impl Into<String>) -> Self {
+        let source = glob.into();
+        let segments = path_segments(&source)
+            .into_iter()
+            .map(|segment| match segment {
+                "*" => GlobSegment::One,
+                "**" => GlobSegment::Any,
+                literal => GlobSegment::Literal(literal.to_string()),
+            })
+            .collect();
+        Self::new(FilterKind::Path(PathGlob { source, segments }))
+    }
+
+    /// Routes whose chain has the meta `key`, whatever its value
+    pub fn has_meta(key: impl Into<String>) -> Self {
+        Self::new(FilterKind::HasMeta(key.into()))
+    }
+
+    /// Routes whose chain has the meta `key` set to `value`
+    pub fn meta_eq(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::new(FilterKind::MetaEquals(key.into(), value.into()))
+    }
+
+    /// Routes whose chain has typed meta of type `T`, set with
+    /// [`Route::meta_typed`](crate::Route::meta_typed)
+    pub fn has_meta_typed<T: MetaValue>() -> Self {
+        Self::new(FilterKind::HasTypedMeta(TypeId::of::<T>(), T::meta_name()))
+    }
+
+    /// Routes of a chain with a route named like `pattern`
+    ///
+    /// `*` in the pattern matches any characters, so `admin.*` matches
+    /// `admin.users` and `admin.users.detail`.
+    pub fn name(pattern: impl Into<String>) -> Self {
+        Self::new(FilterKind::Name(pattern.into()))
+    }
+
+    /// Routes matching both this filter and `other`
+    pub fn and(self, other: Self) -> Self {
+        Self::new(FilterKind::And(Box::new(self), Box::new(other)))
+    }
+
+    /// Routes matching this filter or `other`
+    pub fn or(self, other: Self) -> Self {
+        Self::new(FilterKind::Or(Box::new(self), Box::new(other)))
+    }
+
+    /// Routes matching this filter but not `other`
+    pub fn except(self, other: Self) -> Self {
+        self.and(!other)
+    }
+
+    /// Whether the navigation to `path`, resolving to `chain`, is selected
+    ///
+    /// `chain` is outermost first, as [`match_route_chain`] returns it; an
+    /// empty chain only has a path to match.
+    ///
+    /// [`match_route_chain`]: crate::route::match_route_chain
+    pub(crate) fn matches(&self, path: &str, chain: &[&RouteRef]) -> bool {
+        match &self.kind {
+            FilterKind::Path(glob) => glob_matches(&glob.segments, &path_segments(path)),
+            FilterKind::HasMeta(key) => chain_meta_value(chain, key).is_some(),
+            FilterKind::MetaEquals(key, value) => {
+                chain_meta_value(chain, key) == Some(value.as_str())
+            }
+            FilterKind::HasTypedMeta(type_id, _) => chain
+                .iter()
+                .any(|route| route.config.typed_meta.contains_type(*type_id)),
+            FilterKind::Name(pattern) => chain
+                .iter()
+                .filter_map(|route| route.config.name.as_deref())
+                .any(|name| wildcard_matches(pattern, name)),
+            FilterKind::And(left, right) => left.matches(path, chain) && right.matches(path, chain),
+            FilterKind::Or(left, right) => left.matches(path, chain) || right.matches(path, chain),
+            FilterKind::Not(filter) => !filter.matches(path, chain),
+        }
+    }
+}
+
+impl Not for RouteFilter {
+    type Output = Self;
+
+    /// Routes not matching the filter
+    fn not(self) -> Self {
+        Self::new(FilterKind::Not(Box::new(self)))
+    }
+}
+
+impl fmt::Display for RouteFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FilterKind::Path(glob) => write!(f, "path({})", glob.source),
+            FilterKind::HasMeta(key) => write!(f, "meta({})", key),
+            FilterKind::MetaEquals(key, value) => write!(f, "meta({} = {})", key, value),
+            FilterKind::HasTypedMeta(_, name) => write!(f, "typed meta({})", name),
+            FilterKind::Name(pattern) => write!(f, "name({})", pattern),
+            FilterKind::And(left, right) => write!(f, "({} and {})", left, right),
+            FilterKind::Or(left, right) => write!(f, "({} or {})", left, right),
+            FilterKind::Not(filter) => write!(f, "not {}", filter),
+        }
+    }
+}
+
+/// Value of the meta `key` in `chain`, the innermost route's first
+fn chain_meta_value<'a>(chain: &[&'a RouteRef], key: &str) -> Option<&'a str> {
+    chain
+        .iter()
+        .rev()
+        .find_map(|route| route.config.meta.get(key))
+        .map(String::as_str)
+}
+
+/// Whether `glob` matches the whole of `path`
+fn glob_matches(glob: &[GlobSegment], path: &[&str]) -> bool {
+    match glob.split_first() {
+        None => path.is_empty(),
+        Some((GlobSegment::Any, rest)) => {
+            (0..=path.len()).any(|skip| glob_matches(rest, &path[skip..]))
+        }
+        Some((segment, rest)) => match path.split_first() {
+            Some((first, path_rest)) => {
+                let matched = match segment {
+                    GlobSegment::Literal(literal) => literal == first,
+                    _ => true,
+                };
+                matched && glob_matches(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any characters
+fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(text) = text.strip_prefix(prefix) else {
+        return false;
+    };
+    (0..=text.len())
+        .filter(|&start| text.is_char_boundary(start))
+        .any(|start| wildcard_matches(rest, &text[start..]))
+}
+
+/// What a filtered entry is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilteredKind {
+    /// Added with [`GlobalRouter::add_guard_filtered`](crate::GlobalRouter::add_guard_filtered)
+    Guard,
+    /// Added with [`GlobalRouter::add_middleware_filtered`](crate::GlobalRouter::add_middleware_filtered)
+    Middleware,
+}
+
+/// A filtered guard or middleware applying to an explained path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilteredEntry {
+    /// Guard or middleware
+    pub kind: FilteredKind,
+    /// Name the guard or middleware reports
+    pub name: String,
+    /// The filter it was added with
+    pub filter: RouteFilter,
+    /// Position among the entries of its kind, in registration order
+    pub index: usize,
+}
+
+/// Guards and middleware that apply to a path
+///
+/// Returned by [`GlobalRouter::explain`](crate::GlobalRouter::explain).
+/// Entries are in the order they run at equal priority: filtered ones in
+/// registration order, then those of the matched routes, outermost first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteExplanation {
+    /// The explained path
+    pub path: String,
+    /// Full pattern of the matched route, `None` if no route matches
+    pub pattern: Option<String>,
+    /// Filtered guards and middleware whose filter selects the path
+    pub filtered: Vec<FilteredEntry>,
+    /// Names of the guards of the matched routes
+    pub route_guards: Vec<String>,
+    /// Names of the middleware of the matched routes
+    pub route_middleware: Vec<String>,
+}
+
+impl fmt::Display for RouteExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} -> {}",
+            self.path,
+            self.pattern.as_deref().unwrap_or("no route")
+        )?;
+        for entry in &self.filtered {
+            let kind = match entry.kind {
+                FilteredKind::Guard => "guard",
+                FilteredKind::Middleware => "middleware",
+            };
+            writeln!(f, "  filtered {} {} by {}", kind, entry.name, entry.filter)?;
+        }
+        for name in &self.route_guards {
+            writeln!(f, "  route guard {}", name)?;
+        }
+        for name in &self.route_middleware {
+            writeln!(f, "  route middleware {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::match_route_chain;
+    use crate::test_util::page;
+    #[cfg(feature = "guard")]
+    use crate::GlobalRouter;
+    use crate::Route;
+    use gpui::{App, TestAppContext};
+    use std::sync::{Arc, Mutex};
+
+    fn routes() -> Vec<Route> {
+        vec![
+            page("/admin")
+                .name("admin")
+                .meta("requiresAuth", "true")
+                .meta_typed(crate::RouteTitle::new("Admin"))
+                .children(vec![
+                    page("users").name("admin.users").into(),
+                    page("health")
+                        .name("admin.health")
+                        .meta("requiresAuth", "false")
+                        .into(),
+                    page("").into(),
+                ]),
+            page("/about").name("about"),
+        ]
+    }
+
+    fn selects(filter: &RouteFilter, path: &str) -> bool {
+        let routes: Vec<RouteRef> = routes().into_iter().map(Arc::new).collect();
+        let chain = match_route_chain(&routes, path)
+            .map(|(chain, _)| chain)
+            .unwrap_or_default();
+        filter.matches(path, &chain)
+    }
+
+    #[test]
+    fn test_path_globs() {
+        let exact = RouteFilter::path("/admin");
+        assert!(selects(&exact, "/admin"));
+        assert!(selects(&exact, "/admin/?tab=1"));
+        assert!(!selects(&exact, "/admin/users"));
+
+        let children = RouteFilter::path("/admin/*");
+        assert!(selects(&children, "/admin/users"));
+        assert!(!selects(&children, "/admin"));
+        assert!(!selects(&children, "/admin/users/7"));
+
+        let below = RouteFilter::path("/admin/**");
+        assert!(selects(&below, "/admin"));
+        assert!(selects(&below, "/admin/users/7"));
+        assert!(!selects(&below, "/administration"));
+        assert!(!selects(&below, "/about"));
+
+        let inner = RouteFilter::path("/**/health");
+        assert!(selects(&inner, "/admin/health"));
+        assert!(selects(&inner, "/health"));
+        assert!(!selects(&inner, "/admin/health/details"));
+    }
+
+    #[test]
+    fn test_meta_and_name_predicates() {
+        let tagged = RouteFilter::has_meta("requiresAuth");
+        assert!(selects(&tagged, "/admin/users"));
+        assert!(!selects(&tagged, "/about"));
+        // Paths matching no route have no meta
+        assert!(!selects(&tagged, "/missing"));
+
+        // The child's value wins over its parent's
+        let required = RouteFilter::meta_eq("requiresAuth", "true");
+        assert!(selects(&required, "/admin/users"));
+        assert!(!selects(&required, "/admin/health"));
+
+        let admin = RouteFilter::name("admin.*");
+        assert!(selects(&admin, "/admin/users"));
+        assert!(!selects(&admin, "/admin"));
+        assert!(selects(&RouteFilter::name("admin"), "/admin/users"));
+        assert!(!selects(&RouteFilter::name("adm"), "/admin"));
+
+        // Typed meta is inherited too, and kept apart from the string keys
+        let titled = RouteFilter::has_meta_typed::<crate::RouteTitle>();
+        assert!(selects(&titled, "/admin/health"));
+        assert!(!selects(&titled, "/about"));
+        assert!(!selects(&RouteFilter::has_meta("title"), "/admin"));
+        assert_eq!(titled.to_string(), "typed meta(title)");
+    }
+
+    #[test]
+    fn test_combinators() {
+        let audited = RouteFilter::path("/admin/**").except(RouteFilter::path("/admin/health"));
+        assert!(selects(&audited, "/admin"));
+        assert!(selects(&audited, "/admin/users"));
+        assert!(!selects(&audited, "/admin/health"));
+
+        let either = RouteFilter::name("about").or(RouteFilter::path("/admin/health"));
+        assert!(selects(&either, "/about"));
+        assert!(selects(&either, "/admin/health"));
+        assert!(!selects(&either, "/admin/users"));
+        assert!(selects(&!either, "/admin/users"));
+
+        assert_eq!(
+            audited.to_string(),
+            "(path(/admin/**) and not path(/admin/health))"
+        );
+    }
+
+    #[test]
+    fn test_wildcard_names() {
+        assert!(wildcard_matches("*", ""));
+        assert!(wildcard_matches("a*c", "abbc"));
+        assert!(wildcard_matches("*.detail", "user.detail"));
+        assert!(!wildcard_matches("*.detail", "user.details"));
+        assert!(wildcard_matches("ü*", "über"));
+    }
+
+    type Log = Arc<Mutex<Vec<&'static str>>>;
+
+    /// Guard logging its name and allowing every navigation
+    #[cfg(feature = "guard")]
+    struct Logged {
+        name: &'static str,
+        log: Log,
+    }
+
+    #[cfg(feature = "guard")]
+    impl crate::RouteGuard for Logged {
+        type Future =
+            std::pin::Pin<Box<dyn std::future::Future<Output = crate::GuardResult> + Send>>;
+
+        fn check(&self, _cx: &App, _request: &crate::NavigationRequest) -> Self::Future {
+            self.log.lock().unwrap().push(self.name);
+            Box::pin(async { crate::GuardResult::allow() })
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    /// Middleware logging its name after each navigation
+    #[cfg(feature = "middleware")]
+    struct Recorded {
+        name: &'static str,
+        log: Log,
+    }
+
+    #[cfg(feature = "middleware")]
+    impl crate::RouteMiddleware for Recorded {
+        type Future = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>>;
+
+        fn before_navigation(
+            &self,
+            _cx: &App,
+            _request: &crate::NavigationRequest,
+        ) -> Self::Future {
+            Box::pin(async {})
+        }
+
+        fn after_navigation(
+            &self,
+            _cx: &App,
+            _navigation: &crate::AfterNavigation,
+        ) -> Self::Future {
+            self.log.lock().unwrap().push(self.name);
+            Box::pin(async {})
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_filtered_guards_run_before_route_guards(cx: &mut TestAppContext) {
+        let log = Log::default();
+        let logged = |name| Logged {
+            name,
+            log: log.clone(),
+        };
+        let mut router = GlobalRouter::new();
+        router.add_route(
+            page("/admin")
+                .meta("requiresAuth", "true")
+                .guard(logged("admin"))
+                .children(vec![page("users").guard(logged("users")).into()]),
+        );
+        router.add_route(page("/about"));
+        router.add_guard_filtered(logged("auth"), RouteFilter::has_meta("requiresAuth"));
+        router.add_guard_filtered(
+            logged("audit"),
+            RouteFilter::path("/admin/**").except(RouteFilter::path("/admin")),
+        );
+
+        cx.update(|cx| {
+            assert!(router.try_push(cx, "/admin/users".into()).is_success());
+            assert_eq!(*log.lock().unwrap(), ["auth", "audit", "admin", "users"]);
+
+            log.lock().unwrap().clear();
+            assert!(router.try_push(cx, "/admin".into()).is_success());
+            assert_eq!(*log.lock().unwrap(), ["auth", "admin"]);
+
+            log.lock().unwrap().clear();
+            assert!(router.try_push(cx, "/about".into()).is_success());
+            assert!(log.lock().unwrap().is_empty());
+        });
+    }
+
+    #[cfg(feature = "middleware")]
+    #[gpui::test]
+    fn test_filtered_middleware_skips_excluded_routes(cx: &mut TestAppContext) {
+        let log = Log::default();
+        cx.update(|cx| {
+            crate::init_router(cx, |router| {
+                for route in routes() {
+                    router.add_route(route);
+                }
+                router.add_middleware_filtered(
+                    Recorded {
+                        name: "audit",
+                        log: log.clone(),
+                    },
+                    RouteFilter::path("/admin/**").except(RouteFilter::path("/admin/health")),
+                );
+            });
+            for path in ["/admin/users", "/admin/health", "/about", "/admin"] {
+                crate::Navigator::push(cx, path);
+                crate::middleware::run_navigation_middleware(cx);
+            }
+        });
+        assert_eq!(*log.lock().unwrap(), ["audit", "audit"]);
+    }
+
+    #[cfg(all(feature = "guard", feature = "middleware"))]
+    #[test]
+    fn test_explain_lists_filtered_entries() {
+        let log = Log::default();
+        let mut router = GlobalRouter::new();
+        for route in routes() {
+            router.add_route(route);
+        }
+        router.add_guard_filtered(
+            Logged {
+                name: "auth",
+                log: log.clone(),
+            },
+            RouteFilter::meta_eq("requiresAuth", "true"),
+        );
+        router.add_middleware_filtered(
+            Recorded {
+                name: "audit",
+                log: log.clone(),
+            },
+            RouteFilter::name("admin.*"),
+        );
+
+        let explanation = router.explain("/admin/users");
+        assert_eq!(explanation.pattern.as_deref(), Some("/admin/users"));
+        let entries: Vec<_> = explanation
+            .filtered
+            .iter()
+            .map(|entry| (entry.kind, entry.name.as_str(), entry.filter.to_string()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    FilteredKind::Guard,
+                    "auth",
+                    "meta(requiresAuth = true)".to_string()
+                ),
+                (
+                    FilteredKind::Middleware,
+                    "audit",
+                    "name(admin.*)".to_string()
+                ),
+            ]
+        );
+
+        assert_eq!(router.explain("/admin/health").filtered.len(), 1);
+        let missing = router.explain("/missing");
+        assert_eq!(missing.pattern, None);
+        assert!(missing.filtered.is_empty());
+        assert!(missing.to_string().starts_with("/missing -> no route"));
+    }
+}
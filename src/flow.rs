@@ -0,0 +1,342 @@
+//! Multi-step flows
+//!
+//! Wizards (checkout, onboarding) tag their routes as the steps of a named
+//! flow with [`Route::flow`](crate::Route::flow). The router then keeps an
+//! ordered record of the flow on every committed navigation, unlike history
+//! which also holds the way back:
+//!
+//! ```ignore
+//! use gpui_navigator::{init_router, FlowExit, FlowPolicy, Navigator, Route, RouterConfig};
+//!
+//! init_router(cx, |router| {
+//!     router.set_config(
+//!         RouterConfig::new()
+//!             .flow_policy("checkout", FlowPolicy::InvalidateForward)
+//!             .flow_exit("checkout", FlowExit::Reset),
+//!     );
+//!     router.add_route(Route::new("/checkout/cart", cart).flow("checkout", 0));
+//!     router.add_route(Route::new("/checkout/address", address).flow("checkout", 1));
+//!     router.add_route(Route::new("/checkout/payment", payment).flow("checkout", 2));
+//! });
+//!
+//! // In the page
+//! let state = Navigator::flow_state(cx, "checkout").unwrap();
+//! if state.is_visited(2) {
+//!     // show the payment step as reachable
+//! }
+//! Navigator::flow_next(cx, "checkout");
+//! ```
+//!
+//! [`Navigator::flow_next`](crate::Navigator::flow_next) and
+//! [`Navigator::flow_prev`](crate::Navigator::flow_prev) push the step with
+//! the next or previous ordinal, filling its params from the current ones.
+
+use crate::context::GlobalRouter;
+use crate::matcher::CompiledPattern;
+use crate::meta::MetaKey;
+use crate::nested::build_child_path;
+use crate::route::{chain_meta_typed, match_route_chain, RouteRef};
+use crate::trace_log;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Flow a route belongs to, see [`Route::flow`](crate::Route::flow)
+///
+/// Stored in the route's typed meta.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowStep {
+    /// Name of the flow
+    pub flow: String,
+    /// Position of the route in the flow
+    pub step: usize,
+}
+
+impl MetaKey for FlowStep {
+    const NAME: &'static str = "flow";
+}
+
+/// What navigating back to an earlier step does to the steps after it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlowPolicy {
+    /// Later steps stay visited (default)
+    #[default]
+    KeepForward,
+    /// Later steps are no longer visited, and the highest step reached
+    /// becomes the current one
+    InvalidateForward,
+}
+
+/// What navigating to a route outside a flow does to its state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlowExit {
+    /// The state stays, without a current step, for when the user comes
+    /// back (default)
+    #[default]
+    Persist,
+    /// The state is dropped
+    Reset,
+}
+
+/// Progress through a flow
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlowState {
+    /// Step shown, `None` while a route outside the flow is
+    pub current: Option<usize>,
+    /// Highest step reached
+    pub highest: usize,
+    /// Steps reached
+    pub visited: BTreeSet<usize>,
+}
+
+impl FlowState {
+    /// Check if a step of the flow is shown
+    pub fn is_active(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Check if `step` was reached
+    pub fn is_visited(&self, step: usize) -> bool {
+        self.visited.contains(&step)
+    }
+
+    /// Enter `step` under `policy`
+    fn enter(&mut self, step: usize, policy: FlowPolicy) {
+        if policy == FlowPolicy::InvalidateForward {
+            self.visited.retain(|visited| *visited <= step);
+            self.highest = step;
+        } else {
+            self.highest = self.highest.max(step);
+        }
+        self.visited.insert(step);
+        self.current = Some(step);
+    }
+}
+
+/// State of the flows navigated through, kept on the router state
+#[derive(Debug, Clone, Default)]
+pub struct FlowTracker {
+    states: HashMap<String, FlowState>,
+    policies: HashMap<String, FlowPolicy>,
+    exits: HashMap<String, FlowExit>,
+}
+
+impl FlowTracker {
+    /// State of `flow`, `None` if none of its steps was shown (or since the
+    /// flow was reset)
+    pub fn get(&self, flow: &str) -> Option<&FlowState> {
+        self.states.get(flow)
+    }
+
+    /// Set the policies of the flows, see [`RouterConfig::flow_policy`](crate::RouterConfig::flow_policy)
+    pub(crate) fn set_policies(
+        &mut self,
+        policies: HashMap<String, FlowPolicy>,
+        exits: HashMap<String, FlowExit>,
+    ) {
+        self.policies = policies;
+        self.exits = exits;
+    }
+
+    /// Record a navigation to `path`
+    pub(crate) fn navigated(&mut self, routes: &[RouteRef], path: &str) {
+        let entered = match_route_chain(routes, path)
+            .and_then(|(chain, _)| chain_meta_typed::<FlowStep>(&chain).cloned());
+
+        let exits = &self.exits;
+        self.states.retain(|flow, state| {
+            if entered
+                .as_ref()
+                .is_some_and(|entered| entered.flow == *flow)
+                || !state.is_active()
+            {
+                return true;
+            }
+            state.current = None;
+            let reset = exits.get(flow).copied().unwrap_or_default() == FlowExit::Reset;
+            if reset {
+                trace_log!("Leaving flow '{}', resetting it", flow);
+            }
+            !reset
+        });
+
+        if let Some(FlowStep { flow, step }) = entered {
+            let policy = self.policies.get(&flow).copied().unwrap_or_default();
+            trace_log!("Flow '{}' at step {}", flow, step);
+            self.states.entry(flow).or_default().enter(step, policy);
+        }
+    }
+
+    /// Drop all flow states
+    pub(crate) fn clear(&mut self) {
+        self.states.clear();
+    }
+}
+
+/// Full patterns of the steps of `flow` in `routes`, by ordinal
+///
+/// The first route registered for an ordinal wins.
+pub(crate) fn flow_steps(routes: &[RouteRef], flow: &str) -> BTreeMap<usize, String> {
+    fn collect(
+        routes: &[RouteRef],
+        parent_path: &str,
+        flow: &str,
+        steps: &mut BTreeMap<usize, String>,
+    ) {
+        for route in routes {
+            let full_path = if parent_path.is_empty() {
+                route.config.path.clone()
+            } else {
+                build_child_path(parent_path, &route.config.path).into_owned()
+            };
+            if let Some(step) = route
+                .get_meta::<FlowStep>()
+                .filter(|step| step.flow == flow)
+            {
+                steps.entry(step.step).or_insert_with(|| full_path.clone());
+            }
+            collect(route.get_children(), &full_path, flow, steps);
+        }
+    }
+
+    let mut steps = BTreeMap::new();
+    collect(routes, "", flow, &mut steps);
+    steps
+}
+
+impl GlobalRouter {
+    /// Progress through `flow`, see the [`flow`](crate::flow) module
+    pub fn flow_state(&self, flow: &str) -> Option<&FlowState> {
+        self.state().flows().get(flow)
+    }
+
+    /// Path of the step of `flow` after (`forward`) or before the current one
+    ///
+    /// Params of the step are taken from the current path. `None` outside
+    /// the flow, at its ends, or when the step needs params the current path
+    /// does not have.
+    pub fn flow_step_path(&self, flow: &str, forward: bool) -> Option<String> {
+        let current = self.flow_state(flow)?.current?;
+        let steps = flow_steps(self.state().routes(), flow);
+        let pattern = if forward {
+            steps.range(current + 1..).next()
+        } else {
+            steps.range(..current).next_back()
+        }
+        .map(|(_, pattern)| pattern)?;
+        CompiledPattern::parse(pattern)
+            .expand_path(&self.state().current_params())
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::page;
+    use crate::{init_router, Navigator, Route, RouterConfig};
+    use gpui::{div, IntoElement, TestAppContext};
+
+    fn setup(cx: &mut TestAppContext, config: RouterConfig) {
+        cx.update(|cx| {
+            init_router(cx, move |router| {
+                router.set_config(config);
+                router.add_route(page("/"));
+                router.add_route(
+                    Route::new("/checkout/:order", |_, _, _| div().into_any_element()).children(
+                        vec![
+                            Route::new("cart", |_, _, _| div().into_any_element())
+                                .flow("checkout", 0)
+                                .into(),
+                            Route::new("address", |_, _, _| div().into_any_element())
+                                .flow("checkout", 1)
+                                .into(),
+                            Route::new("payment", |_, _, _| div().into_any_element())
+                                .flow("checkout", 2)
+                                .into(),
+                        ],
+                    ),
+                );
+            });
+        });
+    }
+
+    fn state(cx: &mut TestAppContext) -> Option<FlowState> {
+        cx.update(|cx| Navigator::flow_state(cx, "checkout"))
+    }
+
+    fn visited(cx: &mut TestAppContext) -> Vec<usize> {
+        state(cx).unwrap().visited.into_iter().collect()
+    }
+
+    #[gpui::test]
+    fn test_flow_next_and_prev(cx: &mut TestAppContext) {
+        setup(cx, RouterConfig::new());
+
+        cx.update(|cx| Navigator::push(cx, "/checkout/7/cart"));
+        let event = cx
+            .update(|cx| Navigator::flow_next(cx, "checkout"))
+            .unwrap();
+        assert_eq!(event.to, "/checkout/7/address");
+        cx.update(|cx| Navigator::flow_next(cx, "checkout"));
+        assert_eq!(
+            cx.update(|cx| Navigator::current_path(cx)),
+            "/checkout/7/payment"
+        );
+        assert!(cx
+            .update(|cx| Navigator::flow_next(cx, "checkout"))
+            .is_none());
+
+        let event = cx
+            .update(|cx| Navigator::flow_prev(cx, "checkout"))
+            .unwrap();
+        assert_eq!(event.to, "/checkout/7/address");
+        let state = state(cx).unwrap();
+        assert_eq!((state.current, state.highest), (Some(1), 2));
+        assert_eq!(visited(cx), vec![0, 1, 2]);
+    }
+
+    #[gpui::test]
+    fn test_jumping_back_invalidates_forward(cx: &mut TestAppContext) {
+        setup(
+            cx,
+            RouterConfig::new().flow_policy("checkout", FlowPolicy::InvalidateForward),
+        );
+
+        for step in ["cart", "address", "payment"] {
+            cx.update(|cx| Navigator::push(cx, format!("/checkout/7/{}", step)));
+        }
+        cx.update(|cx| Navigator::push(cx, "/checkout/7/cart"));
+        let state = state(cx).unwrap();
+        assert_eq!((state.current, state.highest), (Some(0), 0));
+        assert_eq!(visited(cx), vec![0]);
+    }
+
+    #[gpui::test]
+    fn test_leaving_flow(cx: &mut TestAppContext) {
+        setup(cx, RouterConfig::new());
+        cx.update(|cx| Navigator::push(cx, "/checkout/7/cart"));
+        cx.update(|cx| Navigator::push(cx, "/checkout/7/address"));
+
+        // Persisted by default, without a current step
+        cx.update(|cx| Navigator::push(cx, "/"));
+        let state = state(cx).unwrap();
+        assert_eq!((state.current, state.highest), (None, 1));
+        assert!(cx
+            .update(|cx| Navigator::flow_next(cx, "checkout"))
+            .is_none());
+    }
+
+    #[gpui::test]
+    fn test_reset_on_exit(cx: &mut TestAppContext) {
+        setup(
+            cx,
+            RouterConfig::new().flow_exit("checkout", FlowExit::Reset),
+        );
+        cx.update(|cx| Navigator::push(cx, "/checkout/7/cart"));
+        cx.update(|cx| Navigator::push(cx, "/checkout/7/address"));
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert!(state(cx).is_none());
+        cx.update(|cx| Navigator::push(cx, "/checkout/7/address"));
+        assert_eq!(visited(cx), vec![1]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Focus management after navigation
+//!
+//! With a [`FocusPolicy`](crate::FocusPolicy) other than `None`, keyboard
+//! focus moves to the new page once its enter transition has completed, so
+//! screen reader users notice the page changed:
+//!
+//! ```ignore
+//! use gpui_navigator::{init_router, FocusPolicy, Route, RouteFocusExt, RouterConfig};
+//!
+//! init_router(cx, |router| {
+//!     router.set_config(RouterConfig::new().focus_policy(FocusPolicy::Custom));
+//!     router.add_route(
+//!         Route::new("/settings", |_, cx, _params| {
+//!             div().child(div().route_focus_target("heading", cx).child("Settings"))
+//!         })
+//!         .focus_target("heading"),
+//!     );
+//! });
+//! ```
+//!
+//! `RouterOutlet` wraps its content in a focusable container, which
+//! `FocusPolicy::OutletContainer` focuses. `FocusPolicy::Custom` focuses the
+//! element marked with [`RouteFocusExt::route_focus_target`] instead, falling
+//! back to the container for routes without a focus target.
+
+use crate::config::FocusPolicy;
+use crate::context::GlobalRouter;
+use crate::{trace_log, warn_log};
+use gpui::{App, BorrowAppContext, FocusHandle, InteractiveElement, Window};
+
+/// Mark elements as targets of [`Route::focus_target`](crate::Route::focus_target)
+pub trait RouteFocusExt: InteractiveElement + Sized {
+    /// Let the router focus this element after navigating to a route whose
+    /// focus target is `key`
+    fn route_focus_target(self, key: &str, cx: &mut App) -> Self {
+        let handle = focus_target_handle(cx, key);
+        self.track_focus(&handle)
+    }
+}
+
+impl<E: InteractiveElement> RouteFocusExt for E {}
+
+/// Focus handle of the focus target `key`, created on first use
+pub fn focus_target_handle(cx: &mut App, key: &str) -> FocusHandle {
+    if let Some(handle) = cx
+        .try_global::<GlobalRouter>()
+        .and_then(|router| router.focus_target_handle(key))
+    {
+        return handle.clone();
+    }
+
+    let handle = cx.focus_handle();
+    if cx.has_global::<GlobalRouter>() {
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.focus_targets.insert(key.to_string(), handle.clone());
+        });
+    }
+    handle
+}
+
+/// Move focus to the content of `path` according to the focus policy
+///
+/// `container` is the outlet's focusable container. Focus moves after the
+/// current frame, once the content has been rendered.
+pub(crate) fn focus_route_content(
+    window: &mut Window,
+    cx: &mut App,
+    path: &str,
+    container: &FocusHandle,
+) {
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
+        return;
+    };
+    let handle = match router.config().focus_policy {
+        FocusPolicy::None => return,
+        FocusPolicy::OutletContainer => container.clone(),
+        FocusPolicy::Custom => match router.focus_target(path) {
+            Some(key) => router.focus_target_handle(key).cloned().unwrap_or_else(|| {
+                warn_log!("No element marked as focus target '{}' for '{}'", key, path);
+                container.clone()
+            }),
+            None => container.clone(),
+        },
+    };
+
+    trace_log!("Moving focus to the content of '{}'", path);
+    window.defer(cx, move |window, _| window.focus(&handle));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{init_router, Navigator, Route, RouterConfig, RouterOutlet};
+    use gpui::{div, IntoElement, ParentElement, TestAppContext, VisualTestContext};
+
+    /// Set up `/` and `/settings` routes with the focus policy and an outlet
+    fn setup(cx: &mut TestAppContext, policy: FocusPolicy, route: Route) -> &mut VisualTestContext {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().focus_policy(policy));
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(route);
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+        cx
+    }
+
+    fn navigate(cx: &mut VisualTestContext, path: &str) {
+        cx.update(|window, cx| {
+            Navigator::push(cx, path);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    fn test_outlet_container_focused_after_navigation(cx: &mut TestAppContext) {
+        let settings = Route::new("/settings", |_, _, _| div().into_any_element());
+        #[cfg(feature = "transition")]
+        let settings = settings.transition(crate::Transition::fade(200));
+        let cx = setup(cx, FocusPolicy::OutletContainer, settings);
+        let focused = |cx: &mut VisualTestContext| cx.update(|window, cx| window.focused(cx));
+        assert!(focused(cx).is_none());
+
+        navigate(cx, "/settings");
+        // Focus waits for the enter transition to complete
+        #[cfg(feature = "transition")]
+        {
+            assert!(focused(cx).is_none());
+            // The test platform draws animation frames on refresh only
+            cx.executor()
+                .advance_clock(std::time::Duration::from_millis(200));
+            cx.update(|window, _| window.refresh());
+            cx.run_until_parked();
+        }
+        let container = focused(cx).expect("outlet container should be focused");
+
+        // Back navigation follows the same policy
+        cx.update(|window, cx| {
+            window.blur();
+            Navigator::pop(cx);
+            window.refresh();
+        });
+        cx.run_until_parked();
+        assert_eq!(focused(cx), Some(container));
+    }
+
+    #[gpui::test]
+    fn test_custom_focus_target(cx: &mut TestAppContext) {
+        let settings = Route::new("/settings", |_, cx, _| {
+            div()
+                .child(div().route_focus_target("heading", cx).child("Settings"))
+                .into_any_element()
+        })
+        .focus_target("heading");
+        let cx = setup(cx, FocusPolicy::Custom, settings);
+
+        navigate(cx, "/settings");
+        cx.update(|window, cx| {
+            let handle = focus_target_handle(cx, "heading");
+            assert!(handle.is_focused(window));
+        });
+    }
+}
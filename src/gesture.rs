@@ -0,0 +1,567 @@
+//! Swipe navigation
+//!
+//! [`SwipeNavigator`] wraps a `RouterOutlet` so that two-finger horizontal
+//! swipes on a trackpad go back and forward, the way macOS apps do:
+//!
+//! ```ignore
+//! use gpui_navigator::{RouterOutlet, SwipeNavigator};
+//!
+//! let outlet = cx.new(|_| RouterOutlet::new());
+//! let swipes = cx.new(|_| SwipeNavigator::new(outlet));
+//! ```
+//!
+//! While the fingers move, the current page follows them and the page the
+//! swipe leads to slides in from the side, laid out like a slide
+//! transition. Letting go past the threshold finishes the slide and
+//! navigates; otherwise the pages slide back. Without a page to go to, the
+//! current page only moves a little and springs back; letting go past the
+//! threshold then does what [`Navigator::pop`](crate::Navigator::pop) or
+//! [`Navigator::forward`](crate::Navigator::forward) do with no history
+//! left, such as running
+//! [`RouterConfig::on_back_at_root`](crate::RouterConfig::on_back_at_root).
+//! When transitions are
+//! suppressed (see [`GlobalRouter::suppresses_transitions`]) nothing moves:
+//! a swipe past the threshold navigates once the fingers lift.
+//!
+//! Only scroll events with exact pixel deltas and touch phases, as
+//! trackpads send them, are tracked; mouse wheels scroll as usual.
+
+use crate::context::{GlobalRouter, Navigator};
+use crate::outlet_plan::OutletLayer;
+use crate::trace_log;
+use crate::transition::{slide_layers, SlideDirection};
+use gpui::{
+    canvas, div, AnyView, App, BorrowAppContext, Context, Div, InteractiveElement, IntoElement,
+    ParentElement, Pixels, Render, ScrollDelta, ScrollWheelEvent, Styled, TouchPhase, Window,
+};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Fraction of the width a swipe must cover to navigate
+pub const DEFAULT_SWIPE_THRESHOLD: f32 = 0.35;
+
+/// How long the pages take to slide from the edge to rest
+pub const DEFAULT_SETTLE_DURATION: Duration = Duration::from_millis(250);
+
+/// Farthest a swipe without a page to go to moves the current page, as a
+/// fraction of the width
+pub const RUBBER_BAND_LIMIT: f32 = 0.1;
+
+/// Where a swipe leads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeDirection {
+    /// Fingers moving right: back in history
+    Back,
+    /// Fingers moving left: forward in history
+    Forward,
+}
+
+impl SwipeDirection {
+    /// Direction of a swipe whose fingers moved `delta_x` pixels
+    pub fn from_delta(delta_x: f32) -> Self {
+        if delta_x > 0.0 {
+            Self::Back
+        } else {
+            Self::Forward
+        }
+    }
+
+    /// Slide the pages make, the one of navigating this way
+    pub fn slide(self) -> SlideDirection {
+        match self {
+            Self::Back => SlideDirection::Right,
+            Self::Forward => SlideDirection::Left,
+        }
+    }
+
+    /// Distance the fingers moved towards this direction
+    fn along(self, distance: f32) -> f32 {
+        match self {
+            Self::Back => distance,
+            Self::Forward => -distance,
+        }
+    }
+}
+
+/// Progress of a swipe whose fingers moved `distance` pixels towards its
+/// direction, across a view `width` pixels wide
+///
+/// Moving back past the start gives 0.0; moving the whole width gives 1.0.
+pub fn swipe_progress(distance: f32, width: f32) -> f32 {
+    if width <= 0.0 {
+        return 0.0;
+    }
+    (distance / width).clamp(0.0, 1.0)
+}
+
+/// Offset of the current page, as a fraction of `width`, when the fingers
+/// moved `distance` pixels with no page to go to
+///
+/// Follows the fingers at first, then resists more and more without ever
+/// passing [`RUBBER_BAND_LIMIT`]. The offset has the sign of `distance`.
+pub fn rubber_band(distance: f32, width: f32) -> f32 {
+    if width <= 0.0 {
+        return 0.0;
+    }
+    let moved = distance.abs() / width;
+    let offset = moved / (1.0 + moved / RUBBER_BAND_LIMIT);
+    offset.copysign(distance)
+}
+
+/// Whether a swipe let go at `progress` navigates
+pub fn swipe_commits(progress: f32, threshold: f32) -> bool {
+    progress >= threshold
+}
+
+/// Value settling from `from` to `to`, `elapsed` into the settle
+///
+/// The whole way (a distance of 1.0) takes `duration`, shorter ways take
+/// proportionally less, easing out. Returns `None` once settled.
+pub fn settle_progress(from: f32, to: f32, elapsed: Duration, duration: Duration) -> Option<f32> {
+    let total = duration.as_secs_f32() * (to - from).abs().min(1.0);
+    if total <= 0.0 || elapsed.as_secs_f32() >= total {
+        return None;
+    }
+    let t = elapsed.as_secs_f32() / total;
+    let eased = 1.0 - (1.0 - t).powi(3);
+    Some(from + (to - from) * eased)
+}
+
+/// A swipe being tracked or settling
+#[derive(Debug, Clone)]
+struct Swipe {
+    direction: SwipeDirection,
+    /// History index and path the swipe leads to, `None` if there is none
+    target: Option<(usize, String)>,
+    /// Pixels the fingers moved, positive to the right
+    distance: f32,
+    /// Set once the fingers lifted
+    settle: Option<Settle>,
+}
+
+/// Pages sliding to rest after the fingers lifted
+#[derive(Debug, Clone, Copy)]
+struct Settle {
+    /// Progress (or rubber band offset) when the fingers lifted
+    from: f32,
+    /// Whether the swipe navigates once settled
+    commit: bool,
+    started: Instant,
+}
+
+impl Swipe {
+    /// Progress of the slide, or the rubber band offset without a target
+    fn tracked_value(&self, width: f32) -> f32 {
+        if self.target.is_some() {
+            swipe_progress(self.direction.along(self.distance), width)
+        } else {
+            rubber_band(self.distance, width)
+        }
+    }
+}
+
+/// Wraps a view (usually a `RouterOutlet`) to navigate with trackpad swipes
+///
+/// Swiping right goes back like [`Navigator::pop`](crate::Navigator::pop),
+/// skipping redirects and guarded entries; swiping left goes forward. See
+/// the [module docs](self).
+pub struct SwipeNavigator {
+    content: AnyView,
+    threshold: f32,
+    parallax: f32,
+    settle_duration: Duration,
+    swipe: Option<Swipe>,
+    /// Width the view was laid out with
+    width: Rc<Cell<Pixels>>,
+}
+
+impl SwipeNavigator {
+    /// Wrap `content`, typically an `Entity<RouterOutlet>`
+    pub fn new(content: impl Into<AnyView>) -> Self {
+        Self {
+            content: content.into(),
+            threshold: DEFAULT_SWIPE_THRESHOLD,
+            parallax: 1.0,
+            settle_duration: DEFAULT_SETTLE_DURATION,
+            swipe: None,
+            width: Rc::default(),
+        }
+    }
+
+    /// Set the fraction of the width a swipe must cover to navigate
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set how far the current page moves relative to the page sliding in
+    ///
+    /// See [`Transition::Slide`](crate::Transition::Slide); 1.0 by default.
+    pub fn parallax(mut self, parallax: f32) -> Self {
+        self.parallax = parallax.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set how long the pages take to slide from the edge to rest
+    pub fn settle_duration(mut self, duration: Duration) -> Self {
+        self.settle_duration = duration;
+        self
+    }
+
+    /// Whether a swipe is being tracked or settling
+    pub fn is_swiping(&self) -> bool {
+        self.swipe.is_some()
+    }
+
+    fn on_scroll(
+        &mut self,
+        event: &ScrollWheelEvent,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let ScrollDelta::Pixels(delta) = event.delta else {
+            return;
+        };
+        let (dx, dy) = (f32::from(delta.x), f32::from(delta.y));
+        match event.touch_phase {
+            TouchPhase::Started => {
+                if self
+                    .swipe
+                    .as_ref()
+                    .map_or(true, |swipe| swipe.settle.is_some())
+                {
+                    self.swipe = None;
+                }
+            }
+            TouchPhase::Moved => {
+                if let Some(swipe) = self.swipe.as_mut().filter(|swipe| swipe.settle.is_none()) {
+                    swipe.distance += dx;
+                } else if self.swipe.is_none() && dx.abs() > dy.abs() {
+                    // Only mostly horizontal swipes navigate
+                    self.swipe = Some(Swipe {
+                        direction: SwipeDirection::from_delta(dx),
+                        target: swipe_target(SwipeDirection::from_delta(dx), cx),
+                        distance: dx,
+                        settle: None,
+                    });
+                } else {
+                    return;
+                }
+                cx.stop_propagation();
+                cx.notify();
+            }
+            TouchPhase::Ended => self.release(window, cx),
+        }
+    }
+
+    /// Let go of the swipe: slide to rest, navigating if it went far enough
+    fn release(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let width = f32::from(self.width.get());
+        let Some(swipe) = self.swipe.as_mut().filter(|swipe| swipe.settle.is_none()) else {
+            return;
+        };
+        let from = swipe.tracked_value(width);
+        let commit = swipe.target.is_some() && swipe_commits(from, self.threshold);
+        let past_edge = swipe.target.is_none()
+            && swipe_commits(
+                swipe_progress(swipe.direction.along(swipe.distance), width),
+                self.threshold,
+            );
+        trace_log!(
+            "Swipe {:?} released at {:.2}, commit={}, past_edge={}",
+            swipe.direction,
+            from,
+            commit,
+            past_edge
+        );
+        swipe.settle = Some(Settle {
+            from,
+            commit,
+            started: cx.background_executor().now(),
+        });
+        if past_edge {
+            // Outside this view's update, in case the app's handler touches it
+            let direction = swipe.direction;
+            cx.defer(move |cx| {
+                match direction {
+                    SwipeDirection::Back => Navigator::pop(cx),
+                    SwipeDirection::Forward => Navigator::forward(cx),
+                };
+            });
+        }
+        if !animations_enabled(cx) {
+            self.finish(cx);
+        }
+        window.refresh();
+    }
+
+    /// End the swipe, navigating if it commits
+    fn finish(&mut self, cx: &mut App) {
+        let Some(swipe) = self.swipe.take() else {
+            return;
+        };
+        let commit = swipe.settle.is_some_and(|settle| settle.commit);
+        let Some((index, path)) = swipe.target.filter(|_| commit) else {
+            return;
+        };
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            // History may have changed while the pages settled
+            let entries = router.state().history().entries();
+            if entries.get(index).map(|entry| entry.path.as_str()) != Some(path.as_str()) {
+                return;
+            }
+            if router.travel_to(cx, index).is_some() {
+                router.skip_transition();
+            }
+        });
+    }
+
+    /// Progress (or rubber band offset) to draw, finishing settled swipes
+    fn current_value(&mut self, window: &mut Window, cx: &mut App) -> Option<f32> {
+        let width = f32::from(self.width.get());
+        let swipe = self.swipe.as_ref()?;
+        let Some(settle) = swipe.settle else {
+            return Some(swipe.tracked_value(width));
+        };
+        let to = if settle.commit { 1.0 } else { 0.0 };
+        let elapsed = cx
+            .background_executor()
+            .now()
+            .saturating_duration_since(settle.started);
+        match settle_progress(settle.from, to, elapsed, self.settle_duration) {
+            Some(value) => {
+                window.request_animation_frame();
+                Some(value)
+            }
+            None => {
+                self.finish(cx);
+                None
+            }
+        }
+    }
+}
+
+/// History index and path a swipe in `direction` leads to
+fn swipe_target(direction: SwipeDirection, cx: &App) -> Option<(usize, String)> {
+    let router = cx.try_global::<GlobalRouter>()?;
+    let history = router.state().history();
+    let index = match direction {
+        SwipeDirection::Back => router.smart_back_target(cx)?,
+        SwipeDirection::Forward => {
+            Some(history.current_index() + 1).filter(|index| *index < history.len())?
+        }
+    };
+    Some((index, history.entries()[index].path.clone()))
+}
+
+/// Whether pages follow the fingers, see [`GlobalRouter::suppresses_transitions`]
+fn animations_enabled(cx: &App) -> bool {
+    cx.try_global::<GlobalRouter>()
+        .map_or(true, |router| !router.suppresses_transitions())
+}
+
+impl Render for SwipeNavigator {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let value = if animations_enabled(cx) {
+            self.current_value(window, cx)
+        } else {
+            None
+        };
+        let width = Rc::clone(&self.width);
+        let content = self.content.clone();
+
+        let layers = match (value, self.swipe.as_ref()) {
+            (Some(progress), Some(swipe)) => match &swipe.target {
+                Some((_, path)) => {
+                    let pages = cx
+                        .try_global::<GlobalRouter>()
+                        .map(|router| std::sync::Arc::clone(router.default_pages()))
+                        .unwrap_or_default();
+                    let target = cx
+                        .try_global::<GlobalRouter>()
+                        .map(|router| OutletLayer::for_path(router, path));
+                    let entering = target.map(|layer| layer.build(window, cx, &pages));
+                    slide_layers(
+                        content,
+                        div().size_full().children(entering),
+                        swipe.direction.slide(),
+                        progress,
+                        self.parallax,
+                    )
+                }
+                None => rubber_banded(content, progress),
+            },
+            _ => div().size_full().child(content),
+        };
+
+        div()
+            .id("swipe-navigator")
+            .relative()
+            .size_full()
+            .on_scroll_wheel(cx.listener(Self::on_scroll))
+            .child(
+                canvas(
+                    move |bounds, _, _| width.set(bounds.size.width),
+                    |_, (), _, _| {},
+                )
+                .absolute()
+                .size_full(),
+            )
+            .child(layers)
+    }
+}
+
+/// The current page moved by a rubber band `offset`
+fn rubber_banded(content: AnyView, offset: f32) -> Div {
+    div().relative().size_full().overflow_hidden().child(
+        div()
+            .absolute()
+            .size_full()
+            .left(gpui::relative(offset))
+            .child(content),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{init_router, Route, RouterConfig, RouterOutlet};
+    use gpui::{point, px, AppContext, Modifiers, TestAppContext, VisualTestContext};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_swipe_math() {
+        assert_eq!(SwipeDirection::from_delta(12.0), SwipeDirection::Back);
+        assert_eq!(SwipeDirection::from_delta(-12.0), SwipeDirection::Forward);
+        assert_eq!(SwipeDirection::Back.slide(), SlideDirection::Right);
+
+        assert!((swipe_progress(200.0, 800.0) - 0.25).abs() < f32::EPSILON);
+        assert!(swipe_progress(-50.0, 800.0).abs() < f32::EPSILON);
+        assert!((swipe_progress(2000.0, 800.0) - 1.0).abs() < f32::EPSILON);
+        assert!(swipe_progress(100.0, 0.0).abs() < f32::EPSILON);
+        assert!((SwipeDirection::Forward.along(-200.0) - 200.0).abs() < f32::EPSILON);
+
+        assert!(!swipe_commits(0.34, DEFAULT_SWIPE_THRESHOLD));
+        assert!(swipe_commits(0.35, DEFAULT_SWIPE_THRESHOLD));
+    }
+
+    #[test]
+    fn test_rubber_band_resists() {
+        // Follows the fingers at first
+        let small = rubber_band(8.0, 800.0);
+        assert!((small - 0.01).abs() < 0.001);
+        // then never passes the limit, whichever way
+        assert!(rubber_band(800.0, 800.0) < RUBBER_BAND_LIMIT);
+        assert!(rubber_band(100_000.0, 800.0) < RUBBER_BAND_LIMIT);
+        assert!(rubber_band(100.0, 800.0) < rubber_band(200.0, 800.0));
+        assert!((rubber_band(-200.0, 800.0) + rubber_band(200.0, 800.0)).abs() < f32::EPSILON);
+        assert!(rubber_band(200.0, 0.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_settle_progress() {
+        let duration = Duration::from_millis(200);
+        // Half the way takes half the time
+        assert_eq!(
+            settle_progress(0.5, 1.0, Duration::from_millis(100), duration),
+            None
+        );
+        let midway = settle_progress(0.5, 1.0, Duration::from_millis(50), duration).unwrap();
+        assert!(midway > 0.75 && midway < 1.0);
+        let back = settle_progress(0.5, 0.0, Duration::from_millis(50), duration).unwrap();
+        assert!(back < 0.25 && back > 0.0);
+        assert_eq!(settle_progress(0.0, 0.0, Duration::ZERO, duration), None);
+    }
+
+    fn open_swipe_navigator(cx: &mut TestAppContext) -> &mut VisualTestContext {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                for path in ["/", "/inbox", "/drafts"] {
+                    router.add_route(Route::new(path, |_, _, _| div()));
+                }
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, cx| {
+            let outlet = cx.new(|_| RouterOutlet::new());
+            SwipeNavigator::new(outlet)
+        });
+        cx.run_until_parked();
+        cx
+    }
+
+    fn navigate(cx: &mut VisualTestContext, path: &str) {
+        cx.update(|window, cx| {
+            Navigator::push(cx, path);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    /// Swipe by `fraction` of the window's width and lift the fingers
+    fn swipe(cx: &mut VisualTestContext, fraction: f32) {
+        let width = cx.update(|window, _| f32::from(window.viewport_size().width));
+        let event = |phase, dx: f32| ScrollWheelEvent {
+            position: point(px(10.), px(10.)),
+            delta: ScrollDelta::Pixels(point(px(dx), px(0.))),
+            modifiers: Modifiers::default(),
+            touch_phase: phase,
+        };
+        cx.simulate_event(event(TouchPhase::Started, 0.0));
+        for _ in 0..4 {
+            cx.simulate_event(event(TouchPhase::Moved, width * fraction / 4.0));
+        }
+        cx.simulate_event(event(TouchPhase::Ended, 0.0));
+        for _ in 0..3 {
+            cx.executor().advance_clock(DEFAULT_SETTLE_DURATION);
+            cx.update(|window, _| window.refresh());
+            cx.run_until_parked();
+        }
+    }
+
+    #[gpui::test]
+    fn test_swipe_commits_or_springs_back(cx: &mut TestAppContext) {
+        let cx = open_swipe_navigator(cx);
+        navigate(cx, "/inbox");
+        navigate(cx, "/drafts");
+
+        // Too short: the page springs back
+        swipe(cx, 0.2);
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/drafts"));
+
+        // Far enough: back, then forward again
+        swipe(cx, 0.5);
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/inbox"));
+        swipe(cx, -0.5);
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/drafts"));
+
+        // Nowhere to go forward: rubber band only
+        swipe(cx, -0.9);
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/drafts");
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), 3);
+        });
+    }
+
+    #[gpui::test]
+    fn test_swipe_past_root_runs_app_handler(cx: &mut TestAppContext) {
+        let cx = open_swipe_navigator(cx);
+        let backs = Arc::new(AtomicUsize::new(0));
+        let counted = backs.clone();
+        cx.update(|_, cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.set_config(RouterConfig::new().on_back_at_root(move |_| {
+                    counted.fetch_add(1, Ordering::SeqCst);
+                }));
+            });
+        });
+
+        // Short of the threshold the page only springs back
+        swipe(cx, 0.2);
+        assert_eq!(backs.load(Ordering::SeqCst), 0);
+        swipe(cx, 0.5);
+        assert_eq!(backs.load(Ordering::SeqCst), 1);
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/"));
+    }
+}
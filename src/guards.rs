@@ -3,10 +3,17 @@
 //! Guards are middleware that can block, allow, or redirect navigation.
 //! They're useful for authentication, authorization, and validation.
 
-use crate::{NavigationRequest, RouteMatch};
-use gpui::App;
+use crate::context::GlobalRouter;
+use crate::diagnostics::guard_result;
+use crate::meta::MetaValue;
+use crate::{debug_log, NavigationRequest, RouteMatch};
+use gpui::{App, BorrowAppContext};
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::Poll;
+use std::time::{Duration, Instant};
 
 /// Result of a guard check
 #[derive(Debug, Clone, PartialEq)]
@@ -73,6 +80,15 @@ impl GuardResult {
         matches!(self, GuardResult::Redirect { .. })
     }
 
+    /// Name of the result in diagnostics
+    pub(crate) const fn as_str(&self) -> &'static str {
+        match self {
+            GuardResult::Allow => "allow",
+            GuardResult::Deny { .. } => "deny",
+            GuardResult::Redirect { .. } => "redirect",
+        }
+    }
+
     /// Get redirect path if this is a redirect
     pub fn redirect_path(&self) -> Option<&str> {
         match self {
@@ -169,10 +185,32 @@ pub trait RouteGuard: Send + Sync + 'static {
 
     /// Optional priority for guard execution order
     ///
-    /// Higher priority guards run first. Default is 0.
+    /// Higher priority guards run first; guards of equal priority run in the
+    /// order they were added to the route (or to [`Guards`]), on every
+    /// navigation. The first guard that does not allow decides, so give a
+    /// guard whose redirect should win over another's denial the higher
+    /// priority, or add it first. Default is 0.
     fn priority(&self) -> i32 {
         0
     }
+
+    /// Whether the future returned by `check` resolves immediately
+    ///
+    /// Synchronous guards can be evaluated ahead of navigation by
+    /// [`Navigator::can_access`](crate::Navigator::can_access), e.g. to hide
+    /// links the user cannot follow. Return `false` for guards that do real
+    /// async work (network, disk). Default is `true`.
+    fn synchronous(&self) -> bool {
+        true
+    }
+
+    /// Forget results remembered for route param `key` set to `value`
+    ///
+    /// Called by [`GlobalRouter::invalidate_guard_scope`](crate::GlobalRouter::invalidate_guard_scope)
+    /// on every guard of every route. Guards caching results per param (see
+    /// [`ParamScopedGuard`]) drop them; composite guards pass the call on.
+    /// Does nothing by default.
+    fn invalidate_scope(&self, _key: &str, _value: &str) {}
 }
 
 /// Boxed route guard for dynamic dispatch
@@ -224,6 +262,215 @@ where
     }
 }
 
+/// Result of a synchronous access pre-check
+///
+/// Returned by [`Navigator::can_access`](crate::Navigator::can_access).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessCheck {
+    /// Every guard allows the path
+    Allowed,
+    /// A guard denied or redirected, with its reason
+    Blocked(String),
+    /// At least one guard needs async work, so the outcome is only known on navigation
+    Unknown,
+}
+
+impl AccessCheck {
+    /// Check if access is allowed
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, AccessCheck::Allowed)
+    }
+
+    /// Check if access is blocked
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, AccessCheck::Blocked(_))
+    }
+}
+
+/// Outcome of polling the synchronous guards of a path
+pub(crate) enum GuardPoll {
+    /// No guard blocked; `unknown` if some could not be evaluated synchronously
+    Passed { unknown: bool },
+    /// A guard denied the navigation, with its reason
+    Denied(String),
+    /// A guard redirected the navigation
+    Redirected {
+        to: String,
+        reason: String,
+        guard: String,
+    },
+}
+
+impl GuardPoll {
+    /// This outcome for a navigation to `path` that cannot wait for its
+    /// guards, which denies it if some could not be evaluated
+    pub(crate) fn settled(self, path: &str) -> Self {
+        match self {
+            Self::Passed { unknown: true } => {
+                Self::Denied(format!("Guards of '{}' have not resolved", path))
+            }
+            poll => poll,
+        }
+    }
+}
+
+/// Evaluate guards without navigating
+///
+/// Guards run in priority order until one blocks. Synchronous guards are
+/// polled once; a guard that is not synchronous, or whose future is still
+/// pending, makes the outcome unknown unless another guard blocks.
+pub(crate) fn poll_guards(
+    guards: &[&BoxedGuard],
+    cx: &App,
+    request: &NavigationRequest,
+) -> GuardPoll {
+    let mut sorted_guards = guards.to_vec();
+    sort_by_priority(&mut sorted_guards);
+
+    let mut unknown = false;
+    for guard in sorted_guards {
+        let report = |result| guard_result(&request.to, guard.name(), guard.priority(), result);
+        if !guard.synchronous() {
+            report("skipped");
+            unknown = true;
+            continue;
+        }
+
+        let poll = poll_now(&mut guard.check(cx, request));
+        if let Poll::Ready(result) = &poll {
+            report(result.as_str());
+        }
+        match poll {
+            Poll::Ready(result) => match verdict(guard.name(), result) {
+                GuardVerdict::Allow => {}
+                GuardVerdict::Deny(reason) => return GuardPoll::Denied(reason),
+                GuardVerdict::Redirect { to, reason, guard } => {
+                    return GuardPoll::Redirected { to, reason, guard };
+                }
+            },
+            Poll::Pending => {
+                report("pending");
+                unknown = true;
+            }
+        }
+    }
+
+    GuardPoll::Passed { unknown }
+}
+
+/// What the guards of a navigation decided, once every one has answered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GuardVerdict {
+    /// Every guard allowed the navigation
+    Allow,
+    /// A guard denied the navigation, with its reason
+    Deny(String),
+    /// A guard redirected the navigation
+    Redirect {
+        to: String,
+        reason: String,
+        guard: String,
+    },
+}
+
+/// Future finishing a guard check that could not be settled right away
+pub(crate) type VerdictFuture = Pin<Box<dyn Future<Output = GuardVerdict> + Send>>;
+
+/// What `result` of the guard named `name` decides
+fn verdict(name: &str, result: GuardResult) -> GuardVerdict {
+    match result {
+        GuardResult::Allow => GuardVerdict::Allow,
+        GuardResult::Deny { reason } => GuardVerdict::Deny(reason),
+        GuardResult::Redirect { to, reason } => GuardVerdict::Redirect {
+            reason: reason.unwrap_or_else(|| format!("{} redirects to {}", name, to)),
+            to,
+            guard: name.to_string(),
+        },
+    }
+}
+
+/// Ask `guards`, in the order given, until one blocks a navigation
+///
+/// Unlike [`poll_guards`], every guard is asked, synchronous or not. Results
+/// ready right away settle the check now. Once a guard is pending, the
+/// returned future finishes the check with the futures of the guards after
+/// it, so it needs no `App`.
+pub(crate) fn check_guards(
+    guards: &[&BoxedGuard],
+    cx: &App,
+    request: &NavigationRequest,
+) -> Result<GuardVerdict, VerdictFuture> {
+    let mut pending = Vec::new();
+    for guard in guards {
+        let mut future = guard.check(cx, request);
+        let (name, priority) = (guard.name().to_string(), guard.priority());
+        if pending.is_empty() {
+            match poll_now(&mut future) {
+                Poll::Ready(result) => {
+                    guard_result(&request.to, &name, priority, result.as_str());
+                    match verdict(&name, result) {
+                        GuardVerdict::Allow => continue,
+                        blocked => return Ok(blocked),
+                    }
+                }
+                Poll::Pending => guard_result(&request.to, &name, priority, "pending"),
+            }
+        }
+        pending.push((name, priority, future));
+    }
+    if pending.is_empty() {
+        return Ok(GuardVerdict::Allow);
+    }
+
+    let path = request.to.clone();
+    Err(Box::pin(async move {
+        for (name, priority, future) in pending {
+            let result = future.await;
+            guard_result(&path, &name, priority, result.as_str());
+            match verdict(&name, result) {
+                GuardVerdict::Allow => {}
+                blocked => return blocked,
+            }
+        }
+        GuardVerdict::Allow
+    }))
+}
+
+impl From<GuardPoll> for AccessCheck {
+    fn from(poll: GuardPoll) -> Self {
+        match poll {
+            GuardPoll::Passed { unknown: false } => AccessCheck::Allowed,
+            GuardPoll::Passed { unknown: true } => AccessCheck::Unknown,
+            GuardPoll::Denied(reason) | GuardPoll::Redirected { reason, .. } => {
+                AccessCheck::Blocked(reason)
+            }
+        }
+    }
+}
+
+/// Sort guards into evaluation order: higher priority first, ties in the
+/// order they were added
+///
+/// The sort is stable, so equal priorities never swap between navigations.
+pub(crate) fn sort_by_priority(guards: &mut [&BoxedGuard]) {
+    guards.sort_by_key(|g| std::cmp::Reverse(g.priority()));
+}
+
+/// Waker that does nothing, for polling futures that are expected to be ready
+struct NoopWaker;
+
+impl std::task::Wake for NoopWaker {
+    fn wake(self: std::sync::Arc<Self>) {}
+}
+
+/// Poll `future` once, without a task to wake
+fn poll_now(future: &mut Pin<Box<dyn Future<Output = GuardResult> + Send>>) -> Poll<GuardResult> {
+    let waker = std::task::Waker::from(std::sync::Arc::new(NoopWaker));
+    future
+        .as_mut()
+        .poll(&mut std::task::Context::from_waker(&waker))
+}
+
 /// Guard context provides information about the navigation
 #[derive(Debug, Clone)]
 pub struct GuardContext {
@@ -320,6 +567,7 @@ mod tests {
                 map.insert("page".to_string(), "1".to_string());
                 map
             },
+            fragment: None,
         };
 
         let ctx = GuardContext::new(Some("/".to_string()), "/users/123".to_string(), route_match);
@@ -708,11 +956,29 @@ impl RouteGuard for PermissionGuard {
 // Guard Composition
 // ============================================================================
 
+/// Order in which [`Guards`] evaluates the guards it combines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GuardOrder {
+    /// Higher [`RouteGuard::priority`] first, equal priorities in the order
+    /// the guards were added
+    #[default]
+    PriorityThenInsertion,
+    /// The order the guards were added, ignoring priorities
+    InsertionOnly,
+}
+
 /// Combines multiple guards with AND logic
 ///
 /// All guards must allow navigation for the combined guard to allow.
 /// If any guard denies or redirects, that result is returned.
 ///
+/// Guards are evaluated one at a time in [`GuardOrder`] and stop at the first
+/// that does not allow: later guards are not even asked for their future.
+/// This holds as long as the guards resolve immediately; once a guard is
+/// still pending, the guards after it are asked for their futures right away,
+/// as the app context is not available while awaiting. Those futures are
+/// dropped unpolled if the pending guard blocks.
+///
 /// # Example
 ///
 /// ```ignore
@@ -726,6 +992,7 @@ impl RouteGuard for PermissionGuard {
 /// ```
 pub struct Guards {
     guards: Vec<BoxedGuard>,
+    order: GuardOrder,
 }
 
 impl Guards {
@@ -740,14 +1007,23 @@ impl Guards {
     /// ]);
     /// ```
     pub fn new(guards: Vec<BoxedGuard>) -> Self {
-        Self { guards }
+        Self {
+            guards,
+            order: GuardOrder::default(),
+        }
     }
 
     /// Create from individual guards (auto-boxing)
     pub fn from_guards(guards: impl IntoIterator<Item = BoxedGuard>) -> Self {
-        Self {
-            guards: guards.into_iter().collect(),
-        }
+        Self::new(guards.into_iter().collect())
+    }
+
+    /// Set the order guards are evaluated in
+    ///
+    /// Defaults to [`GuardOrder::PriorityThenInsertion`].
+    pub fn with_order(mut self, order: GuardOrder) -> Self {
+        self.order = order;
+        self
     }
 
     /// Start building a guard composition
@@ -817,13 +1093,23 @@ impl RouteGuard for Guards {
     type Future = Pin<Box<dyn Future<Output = GuardResult> + Send + 'static>>;
 
     fn check(&self, cx: &App, request: &NavigationRequest) -> Self::Future {
-        // Execute guards in priority order
-        let mut sorted_guards: Vec<_> = self.guards.iter().collect();
-        sorted_guards.sort_by_key(|g| -g.priority());
+        let mut ordered: Vec<_> = self.guards.iter().collect();
+        if self.order == GuardOrder::PriorityThenInsertion {
+            sort_by_priority(&mut ordered);
+        }
 
+        // Settle guards that resolve immediately before asking the next one
         let mut futures = Vec::new();
-        for guard in sorted_guards {
-            futures.push(guard.check(cx, request));
+        for guard in ordered {
+            let mut future = guard.check(cx, request);
+            if futures.is_empty() {
+                match poll_now(&mut future) {
+                    Poll::Ready(GuardResult::Allow) => continue,
+                    Poll::Ready(blocked) => return Box::pin(async move { blocked }),
+                    Poll::Pending => {}
+                }
+            }
+            futures.push(future);
         }
 
         Box::pin(async move {
@@ -845,6 +1131,16 @@ impl RouteGuard for Guards {
         // Priority is max of all child guards
         self.guards.iter().map(|g| g.priority()).max().unwrap_or(0)
     }
+
+    fn synchronous(&self) -> bool {
+        self.guards.iter().all(|g| g.synchronous())
+    }
+
+    fn invalidate_scope(&self, key: &str, value: &str) {
+        for guard in &self.guards {
+            guard.invalidate_scope(key, value);
+        }
+    }
 }
 
 /// Inverts a guard result
@@ -902,9 +1198,370 @@ impl RouteGuard for NotGuard {
     fn priority(&self) -> i32 {
         self.guard.priority()
     }
+
+    fn synchronous(&self) -> bool {
+        self.guard.synchronous()
+    }
+
+    fn invalidate_scope(&self, key: &str, value: &str) {
+        self.guard.invalidate_scope(key, value);
+    }
+}
+
+/// Remembers the result of a guard per value of some route params
+///
+/// For guards depending on the concrete params, like "only the author may
+/// edit post `:id`": the inner guard runs once per combination of values of
+/// `keys`, and its result is reused when navigating there again. Paths
+/// lacking one of the params are denied without running it.
+///
+/// Results are kept until [`ParamScopedGuard::ttl`] passes, if set, or until
+/// [`GlobalRouter::invalidate_guard_scope`](crate::GlobalRouter::invalidate_guard_scope)
+/// names one of their param values, e.g. when the post changes owner. The
+/// guard composes like any other, e.g. inside [`Guards`] or [`NotGuard`].
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{guard_fn, GuardResult, ParamScopedGuard, Route};
+///
+/// let owner_only = ParamScopedGuard::new(&["id"], guard_fn(|cx, request| {
+///     let allowed = is_author(cx, request.params.get("id"));
+///     async move {
+///         if allowed { GuardResult::allow() } else { GuardResult::deny("not the author") }
+///     }
+/// }))
+/// .ttl(Duration::from_secs(300));
+///
+/// Route::new("/posts/:id/edit", edit_post).guard(owner_only);
+///
+/// // Later, after the post changed hands
+/// Navigator::invalidate_guard_scope(cx, "id", "42");
+/// ```
+pub struct ParamScopedGuard {
+    keys: Vec<String>,
+    guard: BoxedGuard,
+    ttl: Option<Duration>,
+    memo: Arc<Mutex<ScopeMemo>>,
+}
+
+/// Results of a [`ParamScopedGuard`], by the values of its keys
+#[derive(Default)]
+struct ScopeMemo {
+    results: HashMap<Vec<String>, (GuardResult, Instant)>,
+    /// Bumped by every invalidation, so checks running across one do not
+    /// store their result
+    epoch: u64,
 }
 
-// Additional imports for composition tests
+impl ParamScopedGuard {
+    /// Scope `guard` to the route params named `keys`
+    pub fn new<G>(keys: &[&str], guard: G) -> Self
+    where
+        G: RouteGuard<Future = Pin<Box<dyn Future<Output = GuardResult> + Send>>>,
+    {
+        Self::from_boxed(keys, Box::new(guard))
+    }
+
+    /// Create from a boxed guard
+    pub fn from_boxed(keys: &[&str], guard: BoxedGuard) -> Self {
+        Self {
+            keys: keys.iter().map(|key| (*key).to_string()).collect(),
+            guard,
+            ttl: None,
+            memo: Arc::default(),
+        }
+    }
+
+    /// Run the inner guard again once a result is older than `ttl`
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Param names the results are kept by
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    fn memo(&self) -> std::sync::MutexGuard<'_, ScopeMemo> {
+        self.memo.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl RouteGuard for ParamScopedGuard {
+    type Future = Pin<Box<dyn Future<Output = GuardResult> + Send + 'static>>;
+
+    fn check(&self, cx: &App, request: &NavigationRequest) -> Self::Future {
+        let mut values = Vec::with_capacity(self.keys.len());
+        for key in &self.keys {
+            match request.params.get(key) {
+                Some(value) => values.push(value.clone()),
+                None => {
+                    let reason = format!(
+                        "{} needs route param '{}', which '{}' does not have",
+                        self.guard.name(),
+                        key,
+                        request.to
+                    );
+                    return Box::pin(std::future::ready(GuardResult::deny(reason)));
+                }
+            }
+        }
+
+        let now = cx.background_executor().now();
+        let epoch = {
+            let memo = self.memo();
+            if let Some((result, at)) = memo.results.get(&values) {
+                let fresh = self
+                    .ttl
+                    .map_or(true, |ttl| now.saturating_duration_since(*at) < ttl);
+                if fresh {
+                    return Box::pin(std::future::ready(result.clone()));
+                }
+            }
+            memo.epoch
+        };
+
+        let future = self.guard.check(cx, request);
+        let memo = self.memo.clone();
+        Box::pin(async move {
+            let result = future.await;
+            let mut memo = memo.lock().unwrap_or_else(PoisonError::into_inner);
+            if memo.epoch == epoch {
+                memo.results.insert(values, (result.clone(), now));
+            }
+            result
+        })
+    }
+
+    fn name(&self) -> &str {
+        "ParamScopedGuard"
+    }
+
+    fn priority(&self) -> i32 {
+        self.guard.priority()
+    }
+
+    fn synchronous(&self) -> bool {
+        self.guard.synchronous()
+    }
+
+    fn invalidate_scope(&self, key: &str, value: &str) {
+        if let Some(index) = self.keys.iter().position(|k| k == key) {
+            let mut memo = self.memo();
+            memo.results.retain(|values, _| values[index] != value);
+            memo.epoch += 1;
+        }
+        self.guard.invalidate_scope(key, value);
+    }
+}
+
+// ============================================================================
+// Leave Confirmation
+// ============================================================================
+
+/// Answer to a leave confirmation: `true` leaves the route, `false` stays
+///
+/// May be called later and from any thread, e.g. once a dialog is closed.
+pub type LeaveDecision = Box<dyn FnOnce(bool) + Send>;
+
+/// Asks whether to leave a dirty route, with the route's message
+///
+/// Set with `GlobalRouter::set_confirm_handler`.
+pub type ConfirmHandler = Arc<dyn Fn(&mut App, &str, LeaveDecision) + Send + Sync>;
+
+/// Confirmation asked before leaving a route marked dirty
+///
+/// Unlike the other guards, it checks navigations away from its route, not
+/// to it. Attach it with `Route::confirm_leave`; pages mark themselves dirty
+/// with `Navigator::set_dirty` while they have unsaved changes, and the flag
+/// is cleared once the router navigates away.
+///
+/// While the current route is dirty, a navigation away from it (push,
+/// replace, back, forward, a `NavigatorHandle` chain or, with
+/// `Navigator::confirm_leave_on_close`, closing the window) is held and the
+/// confirm handler is asked. Answering `true` applies the held navigation,
+/// `false` drops it. Navigations made while the answer is pending replace
+/// the held one without asking again. Without a confirm handler, they are
+/// refused with [`NavigationError::GuardBlocked`](crate::NavigationError::GuardBlocked).
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::*;
+///
+/// init_router(cx, |router| {
+///     router.add_route(Route::new("/compose", compose).confirm_leave("Discard the draft?"));
+///     router.set_confirm_handler(|cx, message, decide| {
+///         // Show a dialog, then answer with `decide(true)` or `decide(false)`
+///         show_dialog(cx, message, decide);
+///     });
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfirmLeaveGuard {
+    message: String,
+}
+
+impl ConfirmLeaveGuard {
+    /// Create a guard asking with `message`
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// Message passed to the confirm handler
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Type alias for the check run by a [`TransitionGuard`].
+pub type TransitionCheckFn = Box<dyn Fn(&App, &NavigationRequest) -> GuardResult + Send + Sync>;
+
+/// Type alias for the function picking the navigations a [`TransitionGuard`] checks.
+pub type TransitionMatchFn = Box<dyn Fn(&NavigationRequest) -> bool + Send + Sync>;
+
+/// Guard on moving between kinds of routes
+///
+/// Added with `GlobalRouter::add_transition_guard` rather than to a route,
+/// it is asked wherever route guards are (`Navigator::try_push`, `pop`,
+/// `can_access`), before the guards of the target route, and also on going
+/// forward and on route table switches. Its check only
+/// runs for the navigations it matches, with `from_meta` and `to_meta` of
+/// the request holding the merged meta of the route left and the route
+/// navigated to; other navigations are allowed.
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::*;
+///
+/// init_router(cx, |router| {
+///     router.add_route(Route::new("/vault", vault).meta("secure", "true"));
+///     router.add_transition_guard(TransitionGuard::on_leaving_meta("secure", |cx, _request| {
+///         // Wipe the clipboard and lock the vault on the way out
+///         lock_vault(cx);
+///         GuardResult::allow()
+///     }));
+/// });
+/// ```
+pub struct TransitionGuard {
+    matches: TransitionMatchFn,
+    check: TransitionCheckFn,
+}
+
+impl TransitionGuard {
+    /// Create a guard running `check` on the navigations `matches` picks
+    pub fn new<M, F>(matches: M, check: F) -> Self
+    where
+        M: Fn(&NavigationRequest) -> bool + Send + Sync + 'static,
+        F: Fn(&App, &NavigationRequest) -> GuardResult + Send + Sync + 'static,
+    {
+        Self {
+            matches: Box::new(matches),
+            check: Box::new(check),
+        }
+    }
+
+    /// Create a guard running `check` when leaving a route with meta `key`
+    /// for one without it
+    ///
+    /// Navigations between two routes with `key`, and into one, are not
+    /// checked.
+    pub fn on_leaving_meta<F>(key: impl Into<String>, check: F) -> Self
+    where
+        F: Fn(&App, &NavigationRequest) -> GuardResult + Send + Sync + 'static,
+    {
+        let key = key.into();
+        Self::new(
+            move |request| {
+                request.from_meta.contains_key(&key) && !request.to_meta.contains_key(&key)
+            },
+            check,
+        )
+    }
+
+    /// Create a guard running `check` when leaving a route with typed meta
+    /// `T` for one without it
+    ///
+    /// Like [`TransitionGuard::on_leaving_meta`], for meta set with
+    /// [`Route::meta_typed`](crate::Route::meta_typed).
+    pub fn on_leaving_meta_typed<T, F>(check: F) -> Self
+    where
+        T: MetaValue,
+        F: Fn(&App, &NavigationRequest) -> GuardResult + Send + Sync + 'static,
+    {
+        Self::new(
+            |request| {
+                request.from_typed_meta.contains::<T>() && !request.to_typed_meta.contains::<T>()
+            },
+            check,
+        )
+    }
+
+    /// Check if the guard checks `request`
+    pub fn matches(&self, request: &NavigationRequest) -> bool {
+        (self.matches)(request)
+    }
+}
+
+impl RouteGuard for TransitionGuard {
+    type Future = Pin<Box<dyn Future<Output = GuardResult> + Send>>;
+
+    fn check(&self, cx: &App, request: &NavigationRequest) -> Self::Future {
+        let result = if self.matches(request) {
+            (self.check)(cx, request)
+        } else {
+            GuardResult::allow()
+        };
+        Box::pin(async move { result })
+    }
+
+    fn name(&self) -> &str {
+        "TransitionGuard"
+    }
+}
+
+/// Ask the confirm handler about a held navigation, if it was not asked yet
+///
+/// The router calls this after each navigation that holds one. The
+/// handler's answer is applied on the main thread, then windows are
+/// refreshed.
+pub fn prompt_leave_confirmation(cx: &mut App) {
+    let waiting = cx
+        .try_global::<GlobalRouter>()
+        .is_some_and(GlobalRouter::leave_unprompted);
+    if !waiting {
+        return;
+    }
+
+    let Some((id, message, handler)) =
+        cx.update_global::<GlobalRouter, _>(|router, _| router.take_leave_prompt())
+    else {
+        return;
+    };
+    let router = GlobalRouter::handle(cx);
+    debug_log!("Asking to confirm leaving: {}", message);
+    handler(
+        cx,
+        &message,
+        Box::new(move |leave| router.decide_leave(id, leave)),
+    );
+}
+
+/// Apply the answer to the leave confirmation `id`
+///
+/// Answers to a confirmation that is no longer pending are ignored.
+pub(crate) fn decide_leave(cx: &mut App, id: u64, leave: bool) {
+    let closing =
+        cx.update_global::<GlobalRouter, _>(|router, cx| router.decide_leave(cx, id, leave));
+    if let Some(window) = closing {
+        let _ = window.update(cx, |_, window, _| window.remove_window());
+    }
+}
 
 #[cfg(test)]
 mod auth_tests {
@@ -1005,4 +1662,212 @@ mod auth_tests {
         assert!(result.is_redirect());
         assert_eq!(result.redirect_path(), Some("/forbidden"));
     }
+
+    /// Guard logging its name when asked to check
+    struct Logged {
+        name: &'static str,
+        priority: i32,
+        result: GuardResult,
+        log: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl RouteGuard for Logged {
+        type Future = Pin<Box<dyn Future<Output = GuardResult> + Send>>;
+
+        fn check(&self, _cx: &App, _request: &NavigationRequest) -> Self::Future {
+            self.log.lock().unwrap().push(self.name);
+            let result = self.result.clone();
+            Box::pin(async move { result })
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+    }
+
+    fn logged(
+        log: &Arc<Mutex<Vec<&'static str>>>,
+        name: &'static str,
+        priority: i32,
+        result: GuardResult,
+    ) -> BoxedGuard {
+        Box::new(Logged {
+            name,
+            priority,
+            result,
+            log: log.clone(),
+        })
+    }
+
+    #[gpui::test]
+    fn test_guards_order_is_stable_for_ties(cx: &mut TestAppContext) {
+        let log: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+        let guards = |order| {
+            Guards::new(vec![
+                logged(&log, "a", 0, GuardResult::allow()),
+                logged(&log, "b", 5, GuardResult::allow()),
+                logged(&log, "c", 0, GuardResult::allow()),
+                logged(&log, "d", 5, GuardResult::allow()),
+                logged(&log, "e", i32::MIN, GuardResult::allow()),
+            ])
+            .with_order(order)
+        };
+        let request = NavigationRequest::new("/".to_string());
+
+        let by_priority = guards(GuardOrder::PriorityThenInsertion);
+        for _ in 0..10 {
+            log.lock().unwrap().clear();
+            let result = cx.update(|cx| pollster::block_on(by_priority.check(cx, &request)));
+            assert!(result.is_allow());
+            assert_eq!(*log.lock().unwrap(), ["b", "d", "a", "c", "e"]);
+        }
+
+        log.lock().unwrap().clear();
+        let declared = guards(GuardOrder::InsertionOnly);
+        cx.update(|cx| pollster::block_on(declared.check(cx, &request)));
+        assert_eq!(*log.lock().unwrap(), ["a", "b", "c", "d", "e"]);
+
+        // Of two tied guards that block, the one added first decides
+        let guards = Guards::new(vec![
+            logged(&log, "login", 0, GuardResult::redirect("/login")),
+            logged(&log, "deny", 0, GuardResult::deny("no")),
+        ]);
+        let result = cx.update(|cx| pollster::block_on(guards.check(cx, &request)));
+        assert_eq!(result.redirect_path(), Some("/login"));
+    }
+
+    #[gpui::test]
+    fn test_guards_stop_before_asking_later_guards(cx: &mut TestAppContext) {
+        let log: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+        let guards = Guards::builder()
+            .boxed_guard(logged(&log, "later", 0, GuardResult::allow()))
+            .boxed_guard(logged(&log, "first", 10, GuardResult::deny("no")))
+            .build();
+        let request = NavigationRequest::new("/".to_string());
+
+        let result = cx.update(|cx| pollster::block_on(guards.check(cx, &request)));
+        assert!(result.is_deny());
+        assert_eq!(*log.lock().unwrap(), ["first"]);
+    }
+
+    /// Guard counting its checks, allowing every id but "13"
+    fn counting_guard(checks: &Arc<std::sync::atomic::AtomicUsize>) -> ParamScopedGuard {
+        let checks = checks.clone();
+        ParamScopedGuard::new(
+            &["id"],
+            guard_fn(move |_, request| {
+                checks.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let allowed = request.params.get("id").map(String::as_str) != Some("13");
+                Box::pin(async move {
+                    if allowed {
+                        GuardResult::allow()
+                    } else {
+                        GuardResult::deny("not the author")
+                    }
+                }) as Pin<Box<dyn Future<Output = GuardResult> + Send>>
+            }),
+        )
+    }
+
+    fn edit_request(id: &str) -> NavigationRequest {
+        NavigationRequest::new(format!("/posts/{}/edit", id))
+            .with_params(crate::RouteParams::single("id", id))
+    }
+
+    #[gpui::test]
+    fn test_param_scoped_guard_memoizes_per_value(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        let guard = counting_guard(&checks);
+        let check = |cx: &mut TestAppContext, id: &str| {
+            cx.update(|cx| pollster::block_on(guard.check(cx, &edit_request(id))))
+        };
+
+        assert!(check(cx, "1").is_allow());
+        assert!(check(cx, "2").is_allow());
+        assert_eq!(checks.load(Ordering::SeqCst), 2);
+
+        // The same ids again reuse the results, denials included
+        assert!(check(cx, "1").is_allow());
+        assert!(check(cx, "13").is_deny());
+        assert!(check(cx, "13").is_deny());
+        assert_eq!(checks.load(Ordering::SeqCst), 3);
+
+        // Invalidating a value checks it again, and only it
+        guard.invalidate_scope("id", "1");
+        guard.invalidate_scope("slug", "2");
+        assert!(check(cx, "1").is_allow());
+        assert!(check(cx, "2").is_allow());
+        assert_eq!(checks.load(Ordering::SeqCst), 4);
+    }
+
+    #[gpui::test]
+    fn test_param_scoped_guard_ttl(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        let guard = counting_guard(&checks).ttl(Duration::from_secs(60));
+        let check = |cx: &mut TestAppContext| {
+            cx.update(|cx| pollster::block_on(guard.check(cx, &edit_request("1"))))
+        };
+
+        check(cx);
+        cx.executor().advance_clock(Duration::from_secs(30));
+        check(cx);
+        assert_eq!(checks.load(Ordering::SeqCst), 1);
+
+        cx.executor().advance_clock(Duration::from_secs(31));
+        check(cx);
+        assert_eq!(checks.load(Ordering::SeqCst), 2);
+    }
+
+    #[gpui::test]
+    fn test_param_scoped_guard_denies_missing_param(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        let guard = counting_guard(&checks);
+        let request = NavigationRequest::new("/posts".to_string());
+
+        let result = cx.update(|cx| pollster::block_on(guard.check(cx, &request)));
+        match result {
+            GuardResult::Deny { reason } => assert!(reason.contains("'id'"), "{}", reason),
+            other => panic!("expected a denial, got {:?}", other),
+        }
+        assert_eq!(checks.load(Ordering::SeqCst), 0);
+    }
+
+    #[gpui::test]
+    fn test_router_invalidates_guard_scopes(cx: &mut TestAppContext) {
+        use crate::{init_router, Navigator, Route};
+        use gpui::IntoElement;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| {
+            let guard = Guards::new(vec![Box::new(counting_guard(&checks))]);
+            init_router(cx, move |router| {
+                router.add_route(
+                    Route::new("/posts", |_, _, _| gpui::div().into_any_element()).child(
+                        Route::new(":id/edit", |_, _, _| gpui::div().into_any_element())
+                            .guard(guard)
+                            .into(),
+                    ),
+                );
+            });
+
+            assert!(Navigator::try_push(cx, "/posts/1/edit").is_success());
+            assert!(Navigator::try_push(cx, "/posts/1/edit").is_success());
+            assert_eq!(checks.load(Ordering::SeqCst), 1);
+
+            Navigator::invalidate_guard_scope(cx, "id", "1");
+            assert!(Navigator::try_push(cx, "/posts/1/edit").is_success());
+            assert_eq!(checks.load(Ordering::SeqCst), 2);
+        });
+    }
 }
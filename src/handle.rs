@@ -0,0 +1,276 @@
+//! Navigating from background tasks
+//!
+//! Code running off the main thread (a sync engine, a notification handler)
+//! cannot borrow the `App` the router lives in. A [`RouterHandle`] is `Send`,
+//! `Sync` and `Clone`; it queues navigations, which are applied on the main
+//! thread by a task spawned with the handle:
+//!
+//! ```ignore
+//! use gpui_navigator::GlobalRouter;
+//!
+//! let router = GlobalRouter::handle(cx);
+//! cx.background_spawn(async move {
+//!     let conversation = wait_for_message().await;
+//!     let result = router.push(format!("/conversations/{}", conversation)).await;
+//!     if !result.is_success() {
+//!         eprintln!("could not open the conversation: {:?}", result);
+//!     }
+//! })
+//! .detach();
+//! ```
+//!
+//! Once the app starts quitting, the handle stops accepting navigations:
+//! queued and new ones resolve with a [`NavigationError::NavigationFailed`].
+
+use crate::context::{GlobalRouter, Navigator};
+use crate::error::{report_refused_navigations, NavigationError, NavigationResult};
+use crate::loader::PendingCommit;
+use crate::route::{IntoRoute, RouteDescriptor};
+use crate::{trace_log, NavOptions};
+use futures::channel::{mpsc, oneshot};
+use futures::StreamExt;
+use gpui::{App, BorrowAppContext};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A navigation waiting to be applied on the main thread
+#[derive(Debug)]
+enum QueuedNavigation {
+    Push(RouteDescriptor),
+    Replace(RouteDescriptor),
+    Back,
+    Forward,
+    /// Answer to a leave confirmation
+    #[cfg(feature = "guard")]
+    DecideLeave {
+        id: u64,
+        leave: bool,
+    },
+}
+
+impl QueuedNavigation {
+    fn apply(self, cx: &mut App) -> NavigationResult {
+        let result = match self {
+            Self::Push(route) => Navigator::try_push(cx, route),
+            Self::Replace(route) => cx.update_global::<GlobalRouter, _>(|router, cx| {
+                let options = NavOptions::default();
+                let outcome = router.navigate_in(cx, &route, PendingCommit::Replace, &options);
+                match router.last_error() {
+                    Some(error) => NavigationResult::Error(error.clone()),
+                    None => outcome.into(),
+                }
+            }),
+            Self::Back => travelled(
+                Navigator::pop(cx).into_event().map(|event| event.to),
+                "back",
+            ),
+            Self::Forward => travelled(
+                Navigator::forward(cx).into_event().map(|event| event.to),
+                "forward",
+            ),
+            #[cfg(feature = "guard")]
+            Self::DecideLeave { id, leave } => {
+                crate::guards::decide_leave(cx, id, leave);
+                NavigationResult::Success {
+                    path: Navigator::current_path(cx).to_string(),
+                }
+            }
+        };
+        report_refused_navigations(cx);
+        crate::context::refresh_windows(cx);
+        result
+    }
+}
+
+/// Result of going back or forward, given the path arrived at
+fn travelled(path: Option<String>, direction: &str) -> NavigationResult {
+    path.map_or_else(
+        || {
+            NavigationResult::Error(NavigationError::NavigationFailed {
+                message: format!("Nothing to go {} to", direction),
+            })
+        },
+        |path| NavigationResult::Success { path },
+    )
+}
+
+struct Queued {
+    navigation: QueuedNavigation,
+    reply: oneshot::Sender<NavigationResult>,
+}
+
+/// Thread-safe handle queueing navigations for the main thread
+///
+/// Created with [`GlobalRouter::handle`] or [`NavigatorHandle::downgrade`](crate::NavigatorHandle::downgrade).
+/// Each navigation returns a [`PendingNavigation`] resolving to its result
+/// once applied; dropping it does not cancel the navigation.
+#[derive(Clone)]
+pub struct RouterHandle {
+    sender: mpsc::UnboundedSender<Queued>,
+}
+
+impl std::fmt::Debug for RouterHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RouterHandle")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl RouterHandle {
+    /// Create a handle and spawn the task applying its navigations
+    pub(crate) fn new(cx: &mut App) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded::<Queued>();
+
+        let closed = sender.clone();
+        cx.spawn(async move |cx| {
+            while let Some(queued) = receiver.next().await {
+                // Quitting: drop the reply, failing the navigation
+                if closed.is_closed() {
+                    continue;
+                }
+                trace_log!("Applying queued navigation {:?}", queued.navigation);
+                let Ok(result) = cx.update(|cx| queued.navigation.apply(cx)) else {
+                    break;
+                };
+                let _ = queued.reply.send(result);
+            }
+        })
+        .detach();
+
+        let quitting = sender.clone();
+        cx.on_app_quit(move |_| {
+            quitting.close_channel();
+            async {}
+        })
+        .detach();
+
+        Self { sender }
+    }
+
+    /// Queue a push
+    pub fn push(&self, route: impl IntoRoute) -> PendingNavigation {
+        self.send(QueuedNavigation::Push(route.into_route()))
+    }
+
+    /// Queue a replace
+    pub fn replace(&self, route: impl IntoRoute) -> PendingNavigation {
+        self.send(QueuedNavigation::Replace(route.into_route()))
+    }
+
+    /// Queue going back; fails if there is no previous entry
+    pub fn back(&self) -> PendingNavigation {
+        self.send(QueuedNavigation::Back)
+    }
+
+    /// Queue going forward; fails if there is no next entry
+    pub fn forward(&self) -> PendingNavigation {
+        self.send(QueuedNavigation::Forward)
+    }
+
+    /// Queue the answer to the leave confirmation `id`
+    #[cfg(feature = "guard")]
+    pub(crate) fn decide_leave(&self, id: u64, leave: bool) {
+        // Applied whether or not the result is awaited
+        drop(self.send(QueuedNavigation::DecideLeave { id, leave }));
+    }
+
+    /// Whether the handle stopped accepting navigations (the app is quitting)
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    fn send(&self, navigation: QueuedNavigation) -> PendingNavigation {
+        let (reply, receiver) = oneshot::channel();
+        // A refused send drops the reply, so the navigation resolves as failed
+        let _ = self.sender.unbounded_send(Queued { navigation, reply });
+        PendingNavigation { receiver }
+    }
+}
+
+/// Result of a navigation queued with a [`RouterHandle`]
+///
+/// Resolves once the navigation was applied on the main thread, or with a
+/// [`NavigationError::NavigationFailed`] if the app quit first.
+#[derive(Debug)]
+#[must_use = "navigations are applied even if not awaited; await to get the result"]
+pub struct PendingNavigation {
+    receiver: oneshot::Receiver<NavigationResult>,
+}
+
+impl Future for PendingNavigation {
+    type Output = NavigationResult;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.receiver).poll(cx).map(|result| {
+            result.unwrap_or_else(|_| {
+                NavigationResult::Error(NavigationError::NavigationFailed {
+                    message: "The app quit before the navigation was applied".to_string(),
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{init_router, Route};
+    use gpui::{IntoElement, TestAppContext};
+
+    fn init(cx: &mut TestAppContext) -> RouterHandle {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                for path in ["/", "/conversations/:id"] {
+                    router.add_route(Route::new(path, |_, _, _| gpui::div().into_any_element()));
+                }
+            });
+            GlobalRouter::handle(cx)
+        })
+    }
+
+    #[gpui::test]
+    fn test_navigate_from_background(cx: &mut TestAppContext) {
+        fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+        assert_send_sync::<RouterHandle>();
+
+        let handle = init(cx);
+        let task = cx.background_executor.spawn({
+            let handle = handle.clone();
+            async move {
+                let pushed = handle.push("/conversations/42").await;
+                let back = handle.back().await;
+                let forward = handle.forward().await;
+                let stuck = handle.forward().await;
+                (pushed, back, forward, stuck)
+            }
+        });
+        cx.run_until_parked();
+
+        assert_eq!(cx.read(Navigator::current_path), "/conversations/42");
+        let (pushed, back, forward, stuck) = cx.background_executor.block(task);
+        assert!(
+            matches!(pushed, NavigationResult::Success { path } if path == "/conversations/42")
+        );
+        assert!(matches!(back, NavigationResult::Success { path } if path == "/"));
+        assert!(forward.is_success());
+        assert!(matches!(stuck, NavigationResult::Error(_)));
+
+        // Handles are shared
+        assert!(!cx.update(GlobalRouter::handle).is_closed());
+    }
+
+    #[gpui::test]
+    fn test_navigate_while_quitting(cx: &mut TestAppContext) {
+        let handle = init(cx);
+        cx.quit();
+
+        assert!(handle.is_closed());
+        let result = pollster::block_on(handle.push("/conversations/42"));
+        assert!(matches!(
+            result,
+            NavigationResult::Error(NavigationError::NavigationFailed { .. })
+        ));
+    }
+}
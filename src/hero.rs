@@ -0,0 +1,565 @@
+//! Shared element ("hero") transitions
+//!
+//! When the page left and the page entered both show the same thing, such
+//! as a thumbnail in a grid and the large picture on its detail page, the
+//! outlet can fly it from one place to the other. Pages mark the element
+//! with [`HeroExt::hero`] under the same id:
+//!
+//! ```ignore
+//! use gpui_navigator::{HeroExt, Route, Transition};
+//!
+//! Route::new("/photos", |_, _cx, _params| {
+//!     div().children(photos().map(|photo| {
+//!         div().size_24().bg(photo.color).hero(format!("photo-{}", photo.id))
+//!     }))
+//! });
+//! Route::new("/photos/:id", |_, _cx, params| {
+//!     let id = params.get("id").unwrap();
+//!     div().size_96().bg(photo_color(id)).hero(format!("photo-{id}"))
+//! })
+//! .transition(Transition::fade(300));
+//! ```
+//!
+//! Each hero records its bounds every frame it is laid out. When a
+//! navigation commits, the bounds of the heroes on screen are kept; on the
+//! first frame of the route transition that follows, the ids the new page
+//! lays out again are matched against them. For the length of the
+//! transition both copies of a matched hero are hidden, and a placeholder
+//! filled with the hero's background moves from the old bounds to the new
+//! ones, blending its opacity, while the rest of the pages transition
+//! normally.
+//!
+//! Limitations:
+//!
+//! - The placeholder is a plain rectangle: the hero's content, children and
+//!   rounded corners are not drawn during the flight.
+//! - Only transitions of a [`RouterOutlet`](crate::RouterOutlet) start
+//!   flights, and only when the route change animates; navigations without a
+//!   transition swap heroes in place.
+//! - Heroes are matched by id across all windows. Use ids unique to the
+//!   window, and don't mark two elements on the same page with one id.
+//! - The destination is read from the new page every frame, so with a slide
+//!   transition the placeholder follows the sliding page.
+
+use crate::context::GlobalRouter;
+use crate::transition::apply_easing;
+use gpui::{
+    canvas, fill, hsla, AnyElement, App, Background, BorrowAppContext, Bounds, Element, ElementId,
+    GlobalElementId, InspectorElementId, IntoElement, LayoutId, Pixels, SharedString, Styled,
+    Window,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Mark elements that fly to the element with the same id on the next page
+pub trait HeroExt: Styled + IntoElement + Sized {
+    /// Take part in hero transitions as `id`
+    ///
+    /// Call it last: the background and opacity set on the element so far
+    /// are what its placeholder is drawn with. The element is laid out as
+    /// before.
+    fn hero(mut self, id: impl Into<SharedString>) -> Hero {
+        let style = self.style();
+        let background = style.background.as_ref().and_then(gpui::Fill::color);
+        let opacity = style.opacity.unwrap_or(1.0);
+        Hero {
+            id: id.into(),
+            background,
+            opacity,
+            child: self.into_any_element(),
+        }
+    }
+}
+
+impl<E: Styled + IntoElement> HeroExt for E {}
+
+/// Where a hero is drawn, and how opaque
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeroFrame {
+    /// Bounds in window coordinates
+    pub bounds: Bounds<Pixels>,
+    /// Opacity between 0 and 1
+    pub opacity: f32,
+}
+
+/// The frame `progress` of the way from `from` to `to`
+///
+/// `progress` is clamped to 0..=1 and applied linearly; the outlet eases it
+/// like its transition before calling this.
+pub fn interpolate_hero(from: &HeroFrame, to: &HeroFrame, progress: f32) -> HeroFrame {
+    let t = progress.clamp(0.0, 1.0);
+    let lerp = |a: Pixels, b: Pixels| a + (b - a) * t;
+    HeroFrame {
+        bounds: Bounds {
+            origin: gpui::point(
+                lerp(from.bounds.origin.x, to.bounds.origin.x),
+                lerp(from.bounds.origin.y, to.bounds.origin.y),
+            ),
+            size: gpui::size(
+                lerp(from.bounds.size.width, to.bounds.size.width),
+                lerp(from.bounds.size.height, to.bounds.size.height),
+            ),
+        },
+        opacity: from.opacity + (to.opacity - from.opacity) * t,
+    }
+}
+
+/// Element marked with [`HeroExt::hero`]
+pub struct Hero {
+    id: SharedString,
+    /// Fill of the placeholder, the element's own background
+    background: Option<Background>,
+    opacity: f32,
+    child: AnyElement,
+}
+
+impl IntoElement for Hero {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for Hero {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        (self.child.request_layout(window, cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        if cx.has_global::<GlobalRouter>() {
+            let frame = HeroFrame {
+                bounds,
+                opacity: self.opacity,
+            };
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router
+                    .heroes
+                    .record(self.id.clone(), frame, self.background);
+            });
+        }
+        self.child.prepaint(window, cx);
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        // The placeholder stands in for both copies while it flies
+        let now = cx.background_executor().now();
+        let flying = cx
+            .try_global::<GlobalRouter>()
+            .is_some_and(|router| router.heroes.is_flying(&self.id, now));
+        if !flying {
+            self.child.paint(window, cx);
+        }
+    }
+}
+
+/// A hero as last laid out
+#[derive(Clone)]
+pub(crate) struct SeenHero {
+    frame: HeroFrame,
+    background: Option<Background>,
+    /// Navigation epoch it was laid out in
+    epoch: u64,
+}
+
+/// A hero moving from its old bounds to its new ones
+#[derive(Clone)]
+struct HeroFlight {
+    id: SharedString,
+    from: HeroFrame,
+    background: Option<Background>,
+    started: Instant,
+    duration: Duration,
+}
+
+impl HeroFlight {
+    /// How far along the flight is at `now`, `None` once it landed
+    fn progress(&self, now: Instant) -> Option<f32> {
+        let elapsed = now.saturating_duration_since(self.started);
+        (elapsed < self.duration).then(|| elapsed.as_secs_f32() / self.duration.as_secs_f32())
+    }
+}
+
+/// Bounds of the heroes on screen and the flights between pages
+///
+/// Kept by the [`GlobalRouter`]; navigations committing call
+/// [`HeroRegistry::depart`].
+#[derive(Clone, Default)]
+pub(crate) struct HeroRegistry {
+    /// Heroes by id, as last laid out
+    seen: HashMap<SharedString, SeenHero>,
+    /// Number of navigations committed; heroes laid out since the last one
+    /// are on the current page
+    epoch: u64,
+    /// Heroes of the page left, until a transition starts their flights
+    departed: Option<HashMap<SharedString, SeenHero>>,
+    flights: Vec<HeroFlight>,
+    /// Exiting content being laid out, whose heroes are not recorded
+    exiting: usize,
+}
+
+impl HeroRegistry {
+    /// Record where the hero `id` was laid out this frame
+    fn record(&mut self, id: SharedString, frame: HeroFrame, background: Option<Background>) {
+        if self.exiting > 0 {
+            return;
+        }
+        let epoch = self.epoch;
+        self.seen.insert(
+            id,
+            SeenHero {
+                frame,
+                background,
+                epoch,
+            },
+        );
+    }
+
+    /// Keep the heroes of the page being left, as of its last frame
+    ///
+    /// Called when a navigation commits. Flights still running are dropped.
+    pub(crate) fn depart(&mut self) {
+        let epoch = self.epoch;
+        let mut on_screen = std::mem::take(&mut self.seen);
+        on_screen.retain(|_, hero| hero.epoch == epoch);
+        self.departed = (!on_screen.is_empty()).then_some(on_screen);
+        self.flights.clear();
+        self.epoch += 1;
+    }
+
+    /// Start flights for the heroes both pages laid out
+    ///
+    /// Called on the first frame of a transition, once the new page is laid
+    /// out.
+    fn claim(&mut self, started: Instant, duration: Duration) {
+        let Some(departed) = self.departed.take() else {
+            return;
+        };
+        self.flights = matching_heroes(&departed, &self.seen, self.epoch)
+            .into_iter()
+            .map(|id| {
+                let hero = &departed[&id];
+                HeroFlight {
+                    id,
+                    from: hero.frame,
+                    background: hero.background,
+                    started,
+                    duration,
+                }
+            })
+            .collect();
+    }
+
+    /// Whether the hero `id` is flying at `now`
+    fn is_flying(&self, id: &str, now: Instant) -> bool {
+        self.flights
+            .iter()
+            .any(|flight| flight.id == id && flight.progress(now).is_some())
+    }
+
+    /// Frames of the flights at `now`, dropping those that landed
+    fn flight_frames(&mut self, now: Instant) -> Vec<(HeroFrame, Option<Background>)> {
+        self.flights.retain(|flight| flight.progress(now).is_some());
+        self.flights
+            .iter()
+            .filter_map(|flight| {
+                let to = self
+                    .seen
+                    .get(&flight.id)
+                    .filter(|hero| hero.epoch == self.epoch)?;
+                let progress = apply_easing(flight.progress(now)?);
+                Some((
+                    interpolate_hero(&flight.from, &to.frame, progress),
+                    flight.background,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Ids of `departed` heroes laid out again in `epoch`, sorted
+pub(crate) fn matching_heroes(
+    departed: &HashMap<SharedString, SeenHero>,
+    seen: &HashMap<SharedString, SeenHero>,
+    epoch: u64,
+) -> Vec<SharedString> {
+    let mut ids: Vec<SharedString> = departed
+        .keys()
+        .filter(|id| seen.get(*id).is_some_and(|hero| hero.epoch == epoch))
+        .cloned()
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Content leaving an outlet; heroes in it keep their old bounds
+pub(crate) struct ExitingHeroes(AnyElement);
+
+impl ExitingHeroes {
+    pub(crate) fn new(content: AnyElement) -> Self {
+        Self(content)
+    }
+}
+
+impl IntoElement for ExitingHeroes {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for ExitingHeroes {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        (self.0.request_layout(window, cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        set_exiting(cx, true);
+        self.0.prepaint(window, cx);
+        set_exiting(cx, false);
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        self.0.paint(window, cx);
+    }
+}
+
+/// Enter or leave exiting content
+fn set_exiting(cx: &mut App, exiting: bool) {
+    if !cx.has_global::<GlobalRouter>() {
+        return;
+    }
+    cx.update_global::<GlobalRouter, _>(|router, _| {
+        let heroes = &mut router.heroes;
+        heroes.exiting = if exiting {
+            heroes.exiting + 1
+        } else {
+            heroes.exiting.saturating_sub(1)
+        };
+    });
+}
+
+/// Layer drawing the flying heroes of a transition that started at
+/// `started` and lasts `duration`
+///
+/// Goes last in the outlet, above its content.
+pub(crate) fn hero_overlay(started: Instant, duration: Duration) -> impl IntoElement {
+    canvas(
+        move |_, _, cx| {
+            if !cx.has_global::<GlobalRouter>() {
+                return Vec::new();
+            }
+            let now = cx.background_executor().now();
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.heroes.claim(started, duration);
+                router.heroes.flight_frames(now)
+            })
+        },
+        |_, frames, window, _| {
+            for (frame, background) in frames {
+                let background = background.unwrap_or_else(|| hsla(0.0, 0.0, 0.8, 1.0).into());
+                window.paint_quad(fill(frame.bounds, background.opacity(frame.opacity)));
+            }
+        },
+    )
+    .absolute()
+    .size_full()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{point, px, size};
+
+    fn frame(x: f32, y: f32, width: f32, height: f32, opacity: f32) -> HeroFrame {
+        HeroFrame {
+            bounds: Bounds {
+                origin: point(px(x), px(y)),
+                size: size(px(width), px(height)),
+            },
+            opacity,
+        }
+    }
+
+    fn seen(ids: &[(&'static str, u64)]) -> HashMap<SharedString, SeenHero> {
+        ids.iter()
+            .map(|(id, epoch)| {
+                (
+                    SharedString::new_static(id),
+                    SeenHero {
+                        frame: frame(0.0, 0.0, 10.0, 10.0, 1.0),
+                        background: None,
+                        epoch: *epoch,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_interpolate_hero() {
+        let from = frame(10.0, 20.0, 100.0, 50.0, 0.5);
+        let to = frame(110.0, 220.0, 300.0, 150.0, 1.0);
+
+        assert_eq!(interpolate_hero(&from, &to, 0.0), from);
+        assert_eq!(interpolate_hero(&from, &to, 1.0), to);
+        assert_eq!(
+            interpolate_hero(&from, &to, 0.5),
+            frame(60.0, 120.0, 200.0, 100.0, 0.75)
+        );
+        // Progress outside 0..=1 stays at the ends
+        assert_eq!(interpolate_hero(&from, &to, -1.0), from);
+        assert_eq!(interpolate_hero(&from, &to, 2.0), to);
+    }
+
+    #[test]
+    fn test_heroes_match_by_id_on_the_new_page() {
+        let departed = seen(&[("photo-1", 0), ("photo-2", 0), ("title", 0)]);
+        // `photo-2` was last laid out before the navigation, `avatar` is new
+        let laid_out = seen(&[("photo-1", 1), ("photo-2", 0), ("title", 1), ("avatar", 1)]);
+
+        assert_eq!(
+            matching_heroes(&departed, &laid_out, 1),
+            vec![SharedString::from("photo-1"), SharedString::from("title")]
+        );
+        assert!(matching_heroes(&departed, &HashMap::new(), 1).is_empty());
+    }
+
+    #[test]
+    fn test_flights_start_from_the_page_left() {
+        let mut registry = HeroRegistry::default();
+        registry.record("photo".into(), frame(0.0, 0.0, 10.0, 10.0, 1.0), None);
+        registry.record("grid-only".into(), frame(20.0, 0.0, 10.0, 10.0, 1.0), None);
+        registry.depart();
+
+        // The exiting page lays out both again; only the new page counts
+        registry.exiting = 1;
+        registry.record("grid-only".into(), frame(20.0, 0.0, 10.0, 10.0, 1.0), None);
+        registry.exiting = 0;
+        registry.record("photo".into(), frame(100.0, 100.0, 50.0, 50.0, 1.0), None);
+
+        let started = Instant::now();
+        let duration = Duration::from_millis(200);
+        registry.claim(started, duration);
+        assert!(registry.is_flying("photo", started));
+        assert!(!registry.is_flying("grid-only", started));
+
+        let frames = registry.flight_frames(started + Duration::from_millis(100));
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].0, frame(50.0, 50.0, 30.0, 30.0, 1.0));
+
+        // Landed
+        assert!(registry.flight_frames(started + duration).is_empty());
+        assert!(!registry.is_flying("photo", started + duration));
+    }
+
+    #[gpui::test]
+    fn test_outlet_flies_shared_heroes(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator, Route, RouterOutlet, Transition};
+        use gpui::{div, ParentElement};
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| {
+                    div()
+                        .child(div().size(px(10.0)).hero("photo"))
+                        .child(div().size(px(10.0)).hero("caption"))
+                }));
+                router.add_route(
+                    Route::new("/detail", |_, _, _| div().size(px(100.0)).hero("photo"))
+                        .transition(Transition::fade(200)),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/detail");
+            window.refresh();
+        });
+        cx.run_until_parked();
+        let flying = |cx: &mut gpui::VisualTestContext, id: &str| {
+            cx.update(|_, cx| {
+                let now = cx.background_executor().now();
+                cx.global::<GlobalRouter>().heroes.is_flying(id, now)
+            })
+        };
+        assert!(flying(cx, "photo"));
+        assert!(!flying(cx, "caption"));
+
+        cx.executor().advance_clock(Duration::from_millis(250));
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert!(!flying(cx, "photo"));
+    }
+}
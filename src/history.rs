@@ -2,52 +2,142 @@
 //!
 //! Manages the navigation history stack with support for:
 //! - Forward/backward navigation
-//! - History truncation on new navigation
-//! - Configurable history limits
+//! - History truncation on new navigation, unless the new path is the next
+//!   forward entry (see [`History::set_reuse_forward_on_push`])
+//! - Configurable history limits, evicting the oldest back entries only
 //! - History clearing
-
-use crate::NavigationDirection;
+//! - Listeners notified after every change (see [`HistoryListener`])
+//! - Time spent on each entry (see [`History::current_dwell`])
+//! - In-page anchors: the `#fragment` of a path is kept on its entry, apart
+//!   from the path (see [`HistoryEntry::fragment`])
+
+use crate::{debug_log, NavigationDirection, RouteChangeEvent};
+use gpui::SharedString;
+use std::any::Any;
+use std::sync::Arc;
+#[cfg(feature = "wall-clock")]
+use std::time::SystemTime;
+use std::time::{Duration, Instant};
 
 /// Navigation history entry
-#[derive(Debug, Clone, PartialEq)]
+///
+/// With the `serde` feature, serializes as `{"path":..,"state":..}`, plus
+/// `fragment` when there is one and `entered_at_wall` with the `wall-clock`
+/// feature. `entered_at` is not serialized; deserialized entries were entered
+/// when they were read.
+#[derive(Debug, Clone)]
 pub struct HistoryEntry {
-    /// Path for this history entry
+    /// Path for this history entry, without the fragment
     pub path: String,
+    /// Fragment of the path (`requirements` for `/docs/install#requirements`)
+    pub fragment: Option<String>,
     /// Optional state data associated with this entry
     pub state: Option<HistoryState>,
+    /// When the entry last became current
+    ///
+    /// Set again when back or forward navigation returns to it.
+    pub entered_at: Instant,
+    /// Wall-clock time of `entered_at`, for serializing entries
+    #[cfg(feature = "wall-clock")]
+    pub entered_at_wall: SystemTime,
+    /// How the entry came to be
+    pub kind: EntryKind,
 }
 
 impl HistoryEntry {
     /// Create a new history entry
+    ///
+    /// A `#fragment` at the end of `path` goes to [`HistoryEntry::fragment`].
     pub fn new(path: String) -> Self {
-        Self { path, state: None }
+        let (path, fragment) = match split_fragment(&path) {
+            (route, Some(fragment)) => (route.to_string(), Some(fragment.to_string())),
+            (_, None) => (path, None),
+        };
+        Self {
+            path,
+            fragment,
+            state: None,
+            entered_at: Instant::now(),
+            #[cfg(feature = "wall-clock")]
+            entered_at_wall: SystemTime::now(),
+            kind: EntryKind::Navigation,
+        }
     }
 
     /// Create with state
     pub fn with_state(path: String, state: HistoryState) -> Self {
         Self {
-            path,
             state: Some(state),
+            ..Self::new(path)
+        }
+    }
+
+    /// Record that the entry became current at `now`
+    fn enter(&mut self, now: Instant) {
+        self.entered_at = now;
+        #[cfg(feature = "wall-clock")]
+        {
+            self.entered_at_wall = SystemTime::now();
         }
     }
 }
 
+/// How a history entry came to be
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Navigated to directly
+    #[default]
+    Navigation,
+    /// Committed instead of `from` because a guard redirected there
+    ///
+    /// `Navigator::pop` skips these entries, so going back from the page
+    /// reached after a redirect (e.g. after logging in) does not land on the
+    /// redirect target again.
+    Redirect {
+        /// Path the guard redirected away from
+        from: String,
+    },
+}
+
+impl EntryKind {
+    /// Check if the entry was committed by a guard redirect
+    pub fn is_redirect(&self) -> bool {
+        matches!(self, Self::Redirect { .. })
+    }
+}
+
+/// Entries are equal when their paths and states are, whenever entered
+impl PartialEq for HistoryEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.fragment == other.fragment && self.state == other.state
+    }
+}
+
 /// State data for history entries
 ///
 /// Can store arbitrary data for history restoration
-/// (e.g., scroll position, form data, etc.)
-#[derive(Debug, Clone, PartialEq)]
+/// (e.g., scroll position, form data, etc.): strings with [`set`](Self::set),
+/// which are serialized, and any cloneable value with
+/// [`set_value`](Self::set_value), which is not.
+///
+/// Typed values are shared, not copied, by clones, and states are equal when
+/// their strings are and they hold the same typed values (compared by
+/// pointer). They live as long as their entry: evicted entries drop them, and
+/// serialized histories come back without them.
+///
+/// With the `serde` feature, serializes as `{"data":{..}}`.
+#[derive(Debug, Clone, Default)]
 pub struct HistoryState {
     /// Key-value pairs for state data
     pub data: std::collections::HashMap<String, String>,
+    /// Typed values, by key
+    values: std::collections::HashMap<String, Arc<dyn Any + Send + Sync>>,
 }
 
 impl HistoryState {
     /// Create new empty state
     pub fn new() -> Self {
-        Self {
-            data: std::collections::HashMap::new(),
-        }
+        Self::default()
     }
 
     /// Set a value
@@ -59,14 +149,220 @@ impl HistoryState {
     pub fn get(&self, key: &str) -> Option<&String> {
         self.data.get(key)
     }
+
+    /// Set a typed value, kept apart from the string values
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::history::HistoryState;
+    ///
+    /// let mut state = HistoryState::new();
+    /// state.set_value("selection", 4..9usize);
+    /// assert_eq!(state.get_value::<std::ops::Range<usize>>("selection"), Some(4..9));
+    /// assert_eq!(state.get_value::<String>("selection"), None);
+    /// ```
+    pub fn set_value<T: Any + Clone + Send + Sync>(&mut self, key: impl Into<String>, value: T) {
+        self.values.insert(key.into(), Arc::new(value));
+    }
+
+    /// Get a typed value
+    ///
+    /// `None` if no value was set for `key` or it is not a `T`.
+    pub fn get_value<T: Any + Clone>(&self, key: &str) -> Option<T> {
+        self.values.get(key)?.downcast_ref::<T>().cloned()
+    }
+
+    /// Remove a typed value
+    pub fn remove_value(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+
+    /// Save the scroll position of the entry's page
+    pub fn set_scroll_offset(&mut self, offset: ScrollOffset) {
+        self.set_value(SCROLL_OFFSET_KEY, offset);
+    }
+
+    /// Scroll position saved with [`set_scroll_offset`](Self::set_scroll_offset)
+    pub fn scroll_offset(&self) -> Option<ScrollOffset> {
+        self.get_value(SCROLL_OFFSET_KEY)
+    }
+}
+
+/// Strings must match; typed values must be the same allocations
+impl PartialEq for HistoryState {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+            && self.values.len() == other.values.len()
+            && self.values.iter().all(|(key, value)| {
+                other
+                    .values
+                    .get(key)
+                    .is_some_and(|other| Arc::ptr_eq(value, other))
+            })
+    }
+}
+
+/// Key of the typed scroll position in a [`HistoryState`]
+const SCROLL_OFFSET_KEY: &str = "scroll_offset";
+
+/// Scroll position of a page, in pixels from its top left corner
+///
+/// Saved on the page's history entry so going back can restore it, see
+/// [`HistoryState::set_scroll_offset`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScrollOffset {
+    /// Horizontal offset
+    pub x: f32,
+    /// Vertical offset
+    pub y: f32,
+}
+
+impl ScrollOffset {
+    /// Create a scroll position
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+// ============================================================================
+// History Listeners
+// ============================================================================
+
+/// Observer of history changes
+///
+/// Useful for mirroring navigation into platform integrations such as a
+/// window's document path or an in-app URL bar.
+///
+/// Callbacks run synchronously right after the change, with the updated
+/// history passed in. Listeners only get shared references, so they cannot
+/// mutate the history they observe; to navigate in response to a change,
+/// defer it (e.g. with `cx.defer`) from wherever the listener hands off.
+///
+/// All methods default to doing nothing.
+pub trait HistoryListener: Send + Sync + 'static {
+    /// A new entry was pushed and is now current
+    fn on_push(&self, _entry: &HistoryEntry, _history: &History) {}
+
+    /// The current entry was replaced
+    fn on_replace(&self, _old: &HistoryEntry, _new: &HistoryEntry, _history: &History) {}
+
+    /// The current position moved (back or forward)
+    fn on_travel(&self, _from_index: usize, _to_index: usize, _history: &History) {}
+
+    /// The history was cleared
+    fn on_clear(&self, _history: &History) {}
+
+    /// Entries were dropped to respect the maximum size, oldest first
+    ///
+    /// They can no longer be traveled to, and their state is gone.
+    fn on_evicted(&self, _entries: &[HistoryEntry], _history: &History) {}
+}
+
+/// Built-in listener that logs every history change
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingHistoryListener;
+
+impl HistoryListener for LoggingHistoryListener {
+    fn on_push(&self, entry: &HistoryEntry, history: &History) {
+        debug_log!(
+            "History push: '{}' (index {}, {} entries)",
+            entry.path,
+            history.current_index(),
+            history.len()
+        );
+    }
+
+    fn on_replace(&self, old: &HistoryEntry, new: &HistoryEntry, history: &History) {
+        debug_log!(
+            "History replace: '{}' -> '{}' (index {})",
+            old.path,
+            new.path,
+            history.current_index()
+        );
+    }
+
+    fn on_travel(&self, from_index: usize, to_index: usize, history: &History) {
+        debug_log!(
+            "History travel: {} -> {} ('{}')",
+            from_index,
+            to_index,
+            history.current_path()
+        );
+    }
+
+    fn on_clear(&self, history: &History) {
+        debug_log!("History cleared, now at '{}'", history.current_path());
+    }
+
+    fn on_evicted(&self, entries: &[HistoryEntry], history: &History) {
+        debug_log!(
+            "History evicted {} entries ({} left)",
+            entries.len(),
+            history.len()
+        );
+    }
+}
+
+/// Registered listeners (shared between clones of a history)
+#[derive(Clone, Default)]
+struct Listeners(Vec<Arc<dyn HistoryListener>>);
+
+impl std::fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} listener(s)", self.0.len())
+    }
+}
+
+// ============================================================================
+// Clock
+// ============================================================================
+
+/// Source of the times entries are entered at
+///
+/// The router reads the app's executor, whose clock tests can advance;
+/// standalone histories use [`SystemClock`].
+pub trait Clock: Send + Sync + 'static {
+    /// Current time
+    fn now(&self) -> Instant;
+}
+
+/// Clock reading [`Instant::now`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl Clock for gpui::BackgroundExecutor {
+    fn now(&self) -> Instant {
+        gpui::BackgroundExecutor::now(self)
+    }
 }
 
-impl Default for HistoryState {
+/// Clock of a history (shared between clones of a history)
+#[derive(Clone)]
+struct SharedClock(Arc<dyn Clock>);
+
+impl std::fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+impl Default for SharedClock {
     fn default() -> Self {
-        Self::new()
+        Self(Arc::new(SystemClock))
     }
 }
 
+// ============================================================================
+// History
+// ============================================================================
+
 /// Navigation history stack
 #[derive(Debug, Clone)]
 pub struct History {
@@ -76,30 +372,142 @@ pub struct History {
     current: usize,
     /// Maximum history size (0 = unlimited)
     max_size: usize,
+    /// Listeners notified after each change
+    listeners: Listeners,
+    /// Clock entries are entered by
+    clock: SharedClock,
+    /// When dwell time was paused, if it is
+    paused_at: Option<Instant>,
+    /// Whether pushing the next forward entry's path moves forward instead
+    reuse_forward: bool,
+    /// Path of the current entry, shared with readers without copying
+    current_path: SharedString,
 }
 
 impl History {
     /// Create a new history with initial path
     pub fn new(initial_path: String) -> Self {
-        Self {
-            entries: vec![HistoryEntry::new(initial_path)],
-            current: 0,
-            max_size: 1000, // Default limit
-        }
+        Self::with_max_size(initial_path, 1000) // Default limit
     }
 
     /// Create with custom max size
     pub fn with_max_size(initial_path: String, max_size: usize) -> Self {
+        let entry = HistoryEntry::new(initial_path);
         Self {
-            entries: vec![HistoryEntry::new(initial_path)],
+            current_path: SharedString::from(entry.path.clone()),
+            entries: vec![entry],
             current: 0,
             max_size,
+            listeners: Listeners::default(),
+            clock: SharedClock::default(),
+            paused_at: None,
+            reuse_forward: true,
+        }
+    }
+
+    /// Read times from `clock` instead of the system clock
+    ///
+    /// The current entry counts as entered now.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = SharedClock(clock);
+        self.paused_at = None;
+        self.enter_current();
+    }
+
+    /// Mark the current entry as entered now
+    ///
+    /// Also the one place the shared current path is updated: it is only
+    /// reallocated when the path changed.
+    fn enter_current(&mut self) {
+        let now = self.clock.0.now();
+        let entry = &mut self.entries[self.current];
+        entry.enter(now);
+        if self.current_path != entry.path {
+            self.current_path = SharedString::from(entry.path.clone());
+        }
+    }
+
+    /// Time spent on the current entry since it became current
+    ///
+    /// Measures wall time, including time the app was suspended, except
+    /// while paused with [`History::pause_dwell`].
+    pub fn current_dwell(&self) -> Duration {
+        let until = self.paused_at.unwrap_or_else(|| self.clock.0.now());
+        until.saturating_duration_since(self.current_entry().entered_at)
+    }
+
+    /// Stop counting time spent on the current entry, e.g. while suspended
+    pub fn pause_dwell(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.clock.0.now());
+        }
+    }
+
+    /// Count time spent on the current entry again
+    ///
+    /// The time spent paused is left out of its dwell time.
+    pub fn resume_dwell(&mut self) {
+        let Some(paused_at) = self.paused_at.take() else {
+            return;
+        };
+        let now = self.clock.0.now();
+        let entry = &mut self.entries[self.current];
+        // Entered while paused: dwell time starts now
+        entry.entered_at += now.saturating_duration_since(paused_at.max(entry.entered_at));
+    }
+
+    /// Whether dwell time is paused
+    pub fn is_dwell_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Register a listener notified after each change
+    pub fn add_listener(&mut self, listener: Box<dyn HistoryListener>) {
+        self.listeners.0.push(Arc::from(listener));
+    }
+
+    fn notify_push(&self) {
+        let entry = self.current_entry();
+        for listener in &self.listeners.0 {
+            listener.on_push(entry, self);
+        }
+    }
+
+    fn notify_replace(&self, old: &HistoryEntry) {
+        let new = self.current_entry();
+        for listener in &self.listeners.0 {
+            listener.on_replace(old, new, self);
+        }
+    }
+
+    fn notify_travel(&self, from_index: usize) {
+        for listener in &self.listeners.0 {
+            listener.on_travel(from_index, self.current, self);
+        }
+    }
+
+    fn notify_evicted(&self, evicted: &[HistoryEntry]) {
+        if evicted.is_empty() {
+            return;
+        }
+        for listener in &self.listeners.0 {
+            listener.on_evicted(evicted, self);
         }
     }
 
     /// Get current path
     pub fn current_path(&self) -> &str {
-        &self.entries[self.current].path
+        &self.current_path
+    }
+
+    /// Current path as a [`SharedString`], cloned without copying the path
+    pub fn current_path_shared(&self) -> &SharedString {
+        &self.current_path
+    }
+
+    /// Get the fragment of the current entry
+    pub fn current_fragment(&self) -> Option<&str> {
+        self.entries[self.current].fragment.as_deref()
     }
 
     /// Get current entry
@@ -107,108 +515,197 @@ impl History {
         &self.entries[self.current]
     }
 
+    /// State of the current entry, created empty if it has none
+    ///
+    /// For saving what the page needs when it is returned to, such as its
+    /// scroll position, before navigating away.
+    pub fn current_state_mut(&mut self) -> &mut HistoryState {
+        self.entries[self.current]
+            .state
+            .get_or_insert_with(HistoryState::new)
+    }
+
+    /// Describe a navigation that made the current entry current
+    fn navigated(
+        &self,
+        from: Option<String>,
+        direction: NavigationDirection,
+        previous_dwell: Option<Duration>,
+    ) -> NavigationEvent {
+        let entry = self.current_entry();
+        NavigationEvent {
+            from,
+            to: entry.path.clone(),
+            fragment: entry.fragment.clone(),
+            direction,
+            previous_dwell,
+        }
+    }
+
     /// Push a new path onto history
     ///
-    /// This truncates any forward history and adds the new entry
+    /// This truncates any forward history and adds the new entry. If `path`
+    /// is the path of the next forward entry, this moves forward to that
+    /// entry instead, keeping its state and the entries beyond it, unless
+    /// disabled with [`History::set_reuse_forward_on_push`].
     pub fn push(&mut self, path: String) -> NavigationEvent {
+        let (location, fragment) = split_fragment(&path);
+        if self.reuse_forward
+            && self.entries.get(self.current + 1).is_some_and(|next| {
+                same_location(&next.path, location) && next.fragment.as_deref() == fragment
+            })
+        {
+            if let Some(event) = self.forward() {
+                return event;
+            }
+        }
+
         let from = Some(self.current_path().to_string());
+        let previous_dwell = Some(self.current_dwell());
 
         // Remove forward history when pushing
         self.entries.truncate(self.current + 1);
 
         // Add new entry
-        self.entries.push(HistoryEntry::new(path.clone()));
+        self.entries.push(HistoryEntry::new(path));
         self.current += 1;
+        self.enter_current();
 
         // Enforce max size limit
-        self.enforce_size_limit();
+        let evicted = self.enforce_size_limit();
+        self.notify_push();
+        self.notify_evicted(&evicted);
 
-        NavigationEvent {
-            from,
-            to: path,
-            direction: NavigationDirection::Forward,
-        }
+        self.navigated(from, NavigationDirection::Forward, previous_dwell)
     }
 
     /// Push with state
+    ///
+    /// Always adds an entry carrying `state`, truncating forward history.
     pub fn push_with_state(&mut self, path: String, state: HistoryState) -> NavigationEvent {
         let from = Some(self.current_path().to_string());
+        let previous_dwell = Some(self.current_dwell());
 
         // Remove forward history
         self.entries.truncate(self.current + 1);
 
         // Add new entry with state
-        self.entries
-            .push(HistoryEntry::with_state(path.clone(), state));
+        self.entries.push(HistoryEntry::with_state(path, state));
         self.current += 1;
+        self.enter_current();
 
-        self.enforce_size_limit();
+        let evicted = self.enforce_size_limit();
+        self.notify_push();
+        self.notify_evicted(&evicted);
 
-        NavigationEvent {
-            from,
-            to: path,
-            direction: NavigationDirection::Forward,
-        }
+        self.navigated(from, NavigationDirection::Forward, previous_dwell)
     }
 
     /// Replace current entry
     pub fn replace(&mut self, path: String) -> NavigationEvent {
         let from = Some(self.current_path().to_string());
+        let previous_dwell = Some(self.current_dwell());
 
-        self.entries[self.current] = HistoryEntry::new(path.clone());
+        let old = std::mem::replace(&mut self.entries[self.current], HistoryEntry::new(path));
+        self.enter_current();
+        self.notify_replace(&old);
 
-        NavigationEvent {
-            from,
-            to: path,
-            direction: NavigationDirection::Replace,
-        }
+        self.navigated(from, NavigationDirection::Replace, previous_dwell)
     }
 
     /// Replace current entry with state
     pub fn replace_with_state(&mut self, path: String, state: HistoryState) -> NavigationEvent {
         let from = Some(self.current_path().to_string());
+        let previous_dwell = Some(self.current_dwell());
 
-        self.entries[self.current] = HistoryEntry::with_state(path.clone(), state);
+        let old = std::mem::replace(
+            &mut self.entries[self.current],
+            HistoryEntry::with_state(path, state),
+        );
+        self.enter_current();
+        self.notify_replace(&old);
 
-        NavigationEvent {
-            from,
-            to: path,
-            direction: NavigationDirection::Replace,
+        self.navigated(from, NavigationDirection::Replace, previous_dwell)
+    }
+
+    /// Mark how the current entry came to be
+    pub fn set_current_kind(&mut self, kind: EntryKind) {
+        self.entries[self.current].kind = kind;
+    }
+
+    /// Drop the entries between `index` and the current entry
+    ///
+    /// Going back from the current entry then lands on the entry at `index`.
+    /// Listeners are told the dropped entries were evicted. Does nothing
+    /// unless `index` is at least two entries behind the current one.
+    pub fn collapse_since(&mut self, index: usize) {
+        if index + 1 >= self.current {
+            return;
         }
+        let dropped: Vec<_> = self.entries.drain(index + 1..self.current).collect();
+        self.current = index + 1;
+        self.notify_evicted(&dropped);
     }
 
     /// Go back in history
     pub fn back(&mut self) -> Option<NavigationEvent> {
-        if self.can_go_back() {
-            let from = Some(self.current_path().to_string());
-            self.current -= 1;
-            let to = self.current_path().to_string();
-
-            Some(NavigationEvent {
-                from,
-                to,
-                direction: NavigationDirection::Back,
-            })
-        } else {
-            None
+        self.back_to(self.current.checked_sub(1)?)
+    }
+
+    /// Go back to the entry at `index`, skipping the entries in between
+    ///
+    /// Returns `None` if `index` is not behind the current entry.
+    pub fn back_to(&mut self, index: usize) -> Option<NavigationEvent> {
+        if index >= self.current {
+            return None;
         }
+        let from = Some(self.current_path().to_string());
+        let previous_dwell = Some(self.current_dwell());
+        let from_index = self.current;
+        self.current = index;
+        self.enter_current();
+        self.notify_travel(from_index);
+
+        Some(self.navigated(from, NavigationDirection::Back, previous_dwell))
     }
 
     /// Go forward in history
     pub fn forward(&mut self) -> Option<NavigationEvent> {
-        if self.can_go_forward() {
-            let from = Some(self.current_path().to_string());
-            self.current += 1;
-            let to = self.current_path().to_string();
-
-            Some(NavigationEvent {
-                from,
-                to,
-                direction: NavigationDirection::Forward,
-            })
-        } else {
-            None
+        self.forward_to(self.current + 1)
+    }
+
+    /// Go forward to the entry at `index`, skipping the entries in between
+    ///
+    /// Returns `None` if `index` is not ahead of the current entry.
+    pub fn forward_to(&mut self, index: usize) -> Option<NavigationEvent> {
+        if index <= self.current || index >= self.entries.len() {
+            return None;
         }
+        let from = Some(self.current_path().to_string());
+        let previous_dwell = Some(self.current_dwell());
+        let from_index = self.current;
+        self.current = index;
+        self.enter_current();
+        self.notify_travel(from_index);
+        // The back stack grew; catch up on a limit exceeded earlier
+        let evicted = self.enforce_size_limit();
+        self.notify_evicted(&evicted);
+
+        Some(self.navigated(from, NavigationDirection::Forward, previous_dwell))
+    }
+
+    /// Whether pushing the next forward entry's path moves forward
+    pub fn reuse_forward_on_push(&self) -> bool {
+        self.reuse_forward
+    }
+
+    /// Set whether pushing the next forward entry's path moves forward
+    /// (default: true)
+    ///
+    /// Going back and then following the link navigated back from keeps the
+    /// forward stack that way. When disabled, every push truncates it.
+    pub fn set_reuse_forward_on_push(&mut self, reuse: bool) {
+        self.reuse_forward = reuse;
     }
 
     /// Check if can go back
@@ -226,6 +723,11 @@ impl History {
         self.entries.clear();
         self.entries.push(HistoryEntry::new(initial_path));
         self.current = 0;
+        self.enter_current();
+
+        for listener in &self.listeners.0 {
+            listener.on_clear(self);
+        }
     }
 
     /// Get history length
@@ -249,22 +751,85 @@ impl History {
     }
 
     /// Restore from entries (for deserialization)
+    ///
+    /// Entries beyond the maximum size are evicted like on push.
     pub fn restore(&mut self, entries: Vec<HistoryEntry>, current: usize) {
         if !entries.is_empty() && current < entries.len() {
             self.entries = entries;
             self.current = current;
+            self.enter_current();
+            let evicted = self.enforce_size_limit();
+            self.notify_evicted(&evicted);
         }
     }
 
-    /// Enforce maximum size limit
-    fn enforce_size_limit(&mut self) {
-        if self.max_size > 0 && self.entries.len() > self.max_size {
-            // Remove oldest entries, keeping the current path reachable
-            let excess = self.entries.len() - self.max_size;
-            self.entries.drain(0..excess);
-            self.current = self.current.saturating_sub(excess);
+    /// Maximum number of entries (0 = unlimited)
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Change the maximum number of entries (0 = unlimited)
+    ///
+    /// Shrinking evicts the oldest back entries right away.
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        let evicted = self.enforce_size_limit();
+        self.notify_evicted(&evicted);
+    }
+
+    /// Rough number of bytes held by the entries
+    ///
+    /// Sums the lengths of paths and state keys and values, to help pick a
+    /// maximum size. Typed state values are not counted.
+    pub fn memory_footprint_hint(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let state = entry.state.as_ref().map_or(0, |state| {
+                    state
+                        .data
+                        .iter()
+                        .map(|(key, value)| key.len() + value.len())
+                        .sum()
+                });
+                entry.path.len() + state
+            })
+            .sum()
+    }
+
+    /// Enforce maximum size limit, returning the evicted entries
+    ///
+    /// Only entries before the current one are evicted, oldest first. The
+    /// current entry and the forward stack stay reachable, so the history
+    /// may exceed the limit until the user moves forward or pushes.
+    fn enforce_size_limit(&mut self) -> Vec<HistoryEntry> {
+        if self.max_size == 0 || self.entries.len() <= self.max_size {
+            return Vec::new();
         }
+        let excess = (self.entries.len() - self.max_size).min(self.current);
+        self.current -= excess;
+        self.entries.drain(0..excess).collect()
+    }
+}
+
+/// Split the `#fragment` off `path`
+///
+/// An empty fragment (`/docs#`) counts as none.
+pub(crate) fn split_fragment(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('#') {
+        Some((route, fragment)) => (route, Some(fragment).filter(|f| !f.is_empty())),
+        None => (path, None),
+    }
+}
+
+/// Whether `a` and `b` name the same location, ignoring trailing slashes
+fn same_location(a: &str, b: &str) -> bool {
+    fn normalized(path: &str) -> (&str, &str) {
+        let (route, query) = path.split_once('?').unwrap_or((path, ""));
+        let route = route.trim_end_matches('/');
+        (if route.is_empty() { "/" } else { route }, query)
     }
+    normalized(a) == normalized(b)
 }
 
 impl Default for History {
@@ -274,14 +839,33 @@ impl Default for History {
 }
 
 /// Navigation event from history operations
+///
+/// Only paths: history does not match routes. The router turns it into a
+/// [`RouteChangeEvent`], which also describes the matched route.
 #[derive(Debug, Clone)]
 pub struct NavigationEvent {
     /// Previous path
     pub from: Option<String>,
     /// New path
     pub to: String,
+    /// Fragment of the new entry
+    pub fragment: Option<String>,
     /// Navigation direction
     pub direction: NavigationDirection,
+    /// Time spent on the entry navigated away from
+    pub previous_dwell: Option<Duration>,
+}
+
+/// History knows nothing about routes: `same_route`, `params_diff` and the
+/// resolved route (`pattern`, `params`, ...) are filled in by the router.
+impl From<NavigationEvent> for RouteChangeEvent {
+    fn from(event: NavigationEvent) -> Self {
+        Self {
+            fragment: event.fragment,
+            previous_dwell: event.previous_dwell,
+            ..Self::new(event.from, event.to, event.direction)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -330,6 +914,54 @@ mod tests {
         assert!(!history.can_go_forward());
     }
 
+    #[test]
+    fn test_history_collapse_since() {
+        let mut history = History::new("/".to_string());
+        for path in ["/a", "/b", "/c"] {
+            history.push(path.to_string());
+        }
+
+        history.collapse_since(0);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.current_path(), "/c");
+        assert_eq!(history.back().unwrap().to, "/");
+
+        // Nothing between the entry and the current one
+        history.collapse_since(0);
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn test_history_back_to() {
+        let mut history = History::new("/".to_string());
+        history.push("/page1".to_string());
+        history.push("/page2".to_string());
+
+        assert!(history.back_to(2).is_none());
+        let event = history.back_to(0).unwrap();
+        assert_eq!(event.from.as_deref(), Some("/page2"));
+        assert_eq!(event.to, "/");
+        assert_eq!(event.direction, NavigationDirection::Back);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.forward().unwrap().to, "/page1");
+    }
+
+    #[test]
+    fn test_history_forward_to() {
+        let mut history = History::new("/".to_string());
+        history.push("/page1".to_string());
+        history.push("/page2".to_string());
+        history.back_to(0);
+
+        assert!(history.forward_to(0).is_none());
+        assert!(history.forward_to(3).is_none());
+        let event = history.forward_to(2).unwrap();
+        assert_eq!(event.from.as_deref(), Some("/"));
+        assert_eq!(event.to, "/page2");
+        assert_eq!(event.direction, NavigationDirection::Forward);
+        assert_eq!(history.back().unwrap().to, "/page1");
+    }
+
     #[test]
     fn test_history_truncation_on_push() {
         let mut history = History::new("/".to_string());
@@ -347,6 +979,63 @@ mod tests {
         assert!(!history.can_go_forward());
     }
 
+    #[test]
+    fn test_history_push_reuses_forward_entry() {
+        let mut history = History::new("/".to_string());
+        let mut state = HistoryState::new();
+        state.set("scroll".to_string(), "120".to_string());
+        history.push_with_state("/a".to_string(), state);
+        history.push("/b".to_string());
+        history.push("/c".to_string());
+        history.back();
+        history.back();
+        history.back();
+
+        // Following the link navigated back from moves forward instead
+        let event = history.push("/a/".to_string());
+        assert_eq!(event.to, "/a");
+        assert_eq!(event.direction, NavigationDirection::Forward);
+        assert_eq!(history.current_index(), 1);
+        assert_eq!(history.len(), 4);
+        assert!(history.can_go_forward());
+        assert_eq!(
+            history
+                .current_entry()
+                .state
+                .as_ref()
+                .unwrap()
+                .get("scroll"),
+            Some(&"120".to_string())
+        );
+        let forward: Vec<_> = history.entries()[2..]
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        assert_eq!(forward, ["/b", "/c"]);
+
+        // Replaces and other paths still drop the forward stack
+        history.replace("/b".to_string());
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.current_index(), 1);
+        history.push("/c?tab=2".to_string());
+        assert_eq!(history.len(), 3);
+        assert!(!history.can_go_forward());
+    }
+
+    #[test]
+    fn test_history_push_without_forward_reuse() {
+        let mut history = History::new("/".to_string());
+        history.set_reuse_forward_on_push(false);
+        history.push("/a".to_string());
+        history.push("/b".to_string());
+        history.back();
+        history.back();
+
+        history.push("/a".to_string());
+        assert_eq!(history.len(), 2);
+        assert!(!history.can_go_forward());
+    }
+
     #[test]
     fn test_history_replace() {
         let mut history = History::new("/".to_string());
@@ -389,6 +1078,57 @@ mod tests {
         assert_eq!(saved_state.get("scrollY"), Some(&"100".to_string()));
     }
 
+    #[test]
+    fn test_typed_state_across_back_and_forward() {
+        let mut history = History::new("/".to_string());
+
+        let mut state = HistoryState::new();
+        state.set("tab".to_string(), "posts".to_string());
+        state.set_value("selection", 4..9usize);
+        history.push_with_state("/editor".to_string(), state);
+
+        // Saved on the current entry before leaving
+        history
+            .current_state_mut()
+            .set_scroll_offset(ScrollOffset::new(0.0, 320.0));
+        history.push("/settings".to_string());
+        history.back();
+
+        let state = history.current_entry().state.as_ref().unwrap();
+        assert_eq!(
+            state.get_value::<std::ops::Range<usize>>("selection"),
+            Some(4..9)
+        );
+        assert_eq!(state.scroll_offset(), Some(ScrollOffset::new(0.0, 320.0)));
+        // The string API is unaffected
+        assert_eq!(state.get("tab"), Some(&"posts".to_string()));
+        assert_eq!(state.data.len(), 1);
+
+        history.forward();
+        history.back();
+        let state = history.current_entry().state.as_ref().unwrap();
+        assert_eq!(state.scroll_offset().map(|offset| offset.y), Some(320.0));
+    }
+
+    #[test]
+    fn test_typed_state_mismatch_and_equality() {
+        let mut state = HistoryState::new();
+        state.set_value("count", 3u32);
+        assert_eq!(state.get_value::<u64>("count"), None);
+        assert_eq!(state.get_value::<u32>("missing"), None);
+        assert_eq!(state.get("count"), None);
+
+        // Clones share their values; values set separately differ
+        let clone = state.clone();
+        assert_eq!(clone, state);
+        let mut other = HistoryState::new();
+        other.set_value("count", 3u32);
+        assert_ne!(other, state);
+
+        state.remove_value("count");
+        assert_eq!(state, HistoryState::new());
+    }
+
     #[test]
     fn test_history_max_size() {
         let mut history = History::with_max_size("/".to_string(), 3);
@@ -440,6 +1180,273 @@ mod tests {
         assert_eq!(event.direction, NavigationDirection::Back);
     }
 
+    use std::sync::Mutex;
+
+    /// Clock that only moves when advanced
+    #[derive(Clone)]
+    struct ManualClock(Arc<Mutex<Instant>>);
+
+    impl ManualClock {
+        fn new() -> Self {
+            Self(Arc::new(Mutex::new(Instant::now())))
+        }
+
+        fn advance(&self, seconds: u64) {
+            *self.0.lock().unwrap() += Duration::from_secs(seconds);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn seconds(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_dwell_time() {
+        let clock = ManualClock::new();
+        let mut history = History::new("/".to_string());
+        history.set_clock(Arc::new(clock.clone()));
+
+        clock.advance(2);
+        assert_eq!(history.current_dwell(), Duration::from_secs(2));
+        assert_eq!(
+            history.push("/a".to_string()).previous_dwell,
+            Some(seconds(2))
+        );
+        assert_eq!(history.current_dwell(), Duration::ZERO);
+
+        // A replace ends the replaced entry's dwell and starts a new one
+        clock.advance(3);
+        assert_eq!(
+            history.replace("/b".to_string()).previous_dwell,
+            Some(seconds(3))
+        );
+        clock.advance(4);
+        assert_eq!(history.back().unwrap().previous_dwell, Some(seconds(4)));
+
+        // Going back re-enters the entry: its dwell starts over
+        assert_eq!(history.current_entry().entered_at, clock.now());
+        clock.advance(1);
+        assert_eq!(history.forward().unwrap().previous_dwell, Some(seconds(1)));
+    }
+
+    #[test]
+    fn test_paused_dwell_time() {
+        let clock = ManualClock::new();
+        let mut history = History::new("/".to_string());
+        history.set_clock(Arc::new(clock.clone()));
+
+        clock.advance(2);
+        history.pause_dwell();
+        assert!(history.is_dwell_paused());
+        clock.advance(60);
+        assert_eq!(history.current_dwell(), seconds(2));
+        history.resume_dwell();
+        clock.advance(1);
+        assert_eq!(
+            history.push("/a".to_string()).previous_dwell,
+            Some(seconds(3))
+        );
+
+        // Entered while paused: the dwell starts once resumed
+        history.pause_dwell();
+        history.push("/b".to_string());
+        clock.advance(30);
+        history.resume_dwell();
+        clock.advance(4);
+        assert_eq!(history.current_dwell(), Duration::from_secs(4));
+    }
+
+    /// Listener recording callbacks in order
+    #[derive(Clone, Default)]
+    struct RecordingListener {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HistoryListener for RecordingListener {
+        fn on_push(&self, entry: &HistoryEntry, history: &History) {
+            self.calls.lock().unwrap().push(format!(
+                "push {} @{}",
+                entry.path,
+                history.current_index()
+            ));
+        }
+
+        fn on_replace(&self, old: &HistoryEntry, new: &HistoryEntry, _history: &History) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("replace {} -> {}", old.path, new.path));
+        }
+
+        fn on_travel(&self, from_index: usize, to_index: usize, history: &History) {
+            self.calls.lock().unwrap().push(format!(
+                "travel {} -> {} {}",
+                from_index,
+                to_index,
+                history.current_path()
+            ));
+        }
+
+        fn on_clear(&self, history: &History) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("clear {}", history.current_path()));
+        }
+
+        fn on_evicted(&self, entries: &[HistoryEntry], _history: &History) {
+            let paths: Vec<_> = entries.iter().map(|entry| entry.path.as_str()).collect();
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("evicted {}", paths.join(",")));
+        }
+    }
+
+    #[test]
+    fn test_listener_callback_order() {
+        let listener = RecordingListener::default();
+        let mut history = History::new("/".to_string());
+        history.add_listener(Box::new(listener.clone()));
+
+        history.push("/a".to_string());
+        history.push("/b".to_string());
+        history.replace("/c".to_string());
+        history.back();
+        history.forward();
+        history.forward(); // No-op at the end of history
+        history.clear("/".to_string());
+
+        assert_eq!(
+            *listener.calls.lock().unwrap(),
+            vec![
+                "push /a @1",
+                "push /b @2",
+                "replace /b -> /c",
+                "travel 2 -> 1 /a",
+                "travel 1 -> 2 /c",
+                "clear /",
+            ]
+        );
+    }
+
+    fn entries(paths: &[&str]) -> Vec<HistoryEntry> {
+        paths
+            .iter()
+            .map(|path| HistoryEntry::new((*path).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_eviction_order_and_events() {
+        let listener = RecordingListener::default();
+        let mut history = History::with_max_size("/".to_string(), 3);
+        history.add_listener(Box::new(listener.clone()));
+
+        for path in ["/a", "/b", "/c", "/d"] {
+            history.push(path.to_string());
+        }
+        assert_eq!(
+            *listener.calls.lock().unwrap(),
+            vec![
+                "push /a @1",
+                "push /b @2",
+                "push /c @2",
+                "evicted /",
+                "push /d @2",
+                "evicted /a",
+            ]
+        );
+        let paths: Vec<_> = history.entries().iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["/b", "/c", "/d"]);
+
+        // Shrinking evicts from the oldest side as well
+        history.set_max_size(2);
+        assert_eq!(listener.calls.lock().unwrap().last().unwrap(), "evicted /b");
+        assert_eq!(history.current_path(), "/d");
+    }
+
+    #[test]
+    fn test_eviction_keeps_forward_stack() {
+        let listener = RecordingListener::default();
+        let mut history = History::with_max_size("/".to_string(), 3);
+        history.add_listener(Box::new(listener.clone()));
+
+        // Only `/` is behind the current entry; the forward stack stays
+        // reachable even though it exceeds the limit
+        history.restore(entries(&["/", "/a", "/b", "/c", "/d"]), 1);
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.current_path(), "/a");
+        assert!(!history.can_go_back());
+
+        // Moving forward grows the back stack, which is evicted again
+        history.forward();
+        assert_eq!(history.current_path(), "/b");
+        assert_eq!(history.len(), 3);
+        history.forward();
+        history.forward();
+        assert_eq!(history.current_path(), "/d");
+        assert_eq!(
+            *listener.calls.lock().unwrap(),
+            vec![
+                "evicted /",
+                "travel 0 -> 1 /b",
+                "evicted /a",
+                "travel 0 -> 1 /c",
+                "travel 1 -> 2 /d",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_eviction_never_strands_current() {
+        let mut history = History::with_max_size("/".to_string(), 3);
+
+        for round in 0..20 {
+            let path = format!("/page{round}");
+            history.push(path.clone());
+            history.back();
+            history.push(format!("{path}/detail"));
+            assert_eq!(history.current_path(), format!("{path}/detail"));
+            assert!(history.len() <= 3);
+
+            history.back();
+            history.back();
+            assert!(history.current_index() < history.len());
+            while history.can_go_forward() {
+                history.forward();
+            }
+            assert_eq!(history.current_path(), format!("{path}/detail"));
+        }
+    }
+
+    #[test]
+    fn test_memory_footprint_hint() {
+        let mut history = History::new("/".to_string());
+        let mut state = HistoryState::new();
+        state.set("scrollY".to_string(), "100".to_string());
+        history.push_with_state("/page".to_string(), state);
+
+        assert_eq!(history.memory_footprint_hint(), 1 + 5 + 7 + 3);
+    }
+
+    #[test]
+    fn test_logging_listener() {
+        let mut history = History::new("/".to_string());
+        history.add_listener(Box::new(LoggingHistoryListener));
+
+        history.push("/a".to_string());
+        history.replace("/b".to_string());
+        history.back();
+        assert_eq!(history.current_path(), "/");
+    }
+
     #[test]
     fn test_empty_history_boundaries() {
         let mut history = History::new("/".to_string());
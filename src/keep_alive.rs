@@ -0,0 +1,652 @@
+//! Kept-alive route components
+//!
+//! Components of routes built with [`Route::component`](crate::Route::component)
+//! or [`Route::component_with_params`](crate::Route::component_with_params)
+//! normally live as long as they are rendered. Routes marked with
+//! [`Route::keep_alive`](crate::Route::keep_alive) keep them after navigation
+//! leaves, so going back shows the page as it was left:
+//!
+//! ```ignore
+//! use gpui_navigator::Route;
+//!
+//! Route::component_with_params("/documents/:id", |params| {
+//!     Editor::open(params.get("id").cloned().unwrap_or_default())
+//! })
+//! .keep_alive_max(5)
+//! .on_evict(|editor: &mut Editor, cx| editor.save_draft(cx));
+//! ```
+//!
+//! Each set of params keeps its own instance. Instances beyond a route's
+//! [`keep_alive_max`](crate::Route::keep_alive_max), or beyond
+//! [`RouterConfig::max_kept_alive_entities`](crate::RouterConfig::max_kept_alive_entities)
+//! across routes, are dropped least recently used first, after the route's
+//! `on_evict` callback ran. [`Navigator::drop_kept_alive`](crate::Navigator::drop_kept_alive)
+//! drops them by hand. The instances of the routes on screen are never
+//! dropped.
+//!
+//! [`Route::reuse`](crate::Route::reuse) decides which visits restore the
+//! kept instance: a search page restores its query and results on any
+//! visit ([`ReuseStrategy::Always`], the default), a compose page starts
+//! fresh when pushed but not when returned to with back
+//! ([`ReuseStrategy::OnBackOnly`]), and [`ReuseStrategy::Never`] creates a
+//! new one every time. Instances a strategy drops go like those over the
+//! limit, after `on_evict` ran.
+
+use crate::context::GlobalRouter;
+use crate::route::RouteRef;
+use crate::scope::RouteScope;
+use crate::trace_log;
+use gpui::{AnyEntity, App, AppContext, BorrowAppContext, ElementId, Entity, Window};
+use std::sync::Arc;
+
+/// Runs when a kept-alive component is dropped
+pub type EvictCallback = Arc<dyn Fn(AnyEntity, &mut App) + Send + Sync>;
+
+/// Keep-alive settings of a route, see [`Route::keep_alive`](crate::Route::keep_alive)
+#[derive(Clone)]
+pub struct KeepAlive {
+    /// Instances of the route kept at most
+    pub max: usize,
+    /// Runs when an instance is dropped
+    pub on_evict: Option<EvictCallback>,
+    /// Which visits restore a kept instance
+    pub reuse: ReuseStrategy,
+}
+
+/// Which visits to a kept-alive route restore its component
+///
+/// The navigation bringing the route back on screen decides; while the
+/// route stays on screen (e.g. its children change) the instance is kept.
+/// See [`Route::reuse`](crate::Route::reuse).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReuseStrategy {
+    /// Restore the instance whatever the navigation (the default)
+    #[default]
+    Always,
+    /// Restore it when going back or forward in history; pushes and
+    /// replaces get a new instance
+    OnBackOnly,
+    /// Create a new instance on every visit, dropping the old one as soon
+    /// as the route leaves the screen
+    Never,
+}
+
+impl std::fmt::Debug for KeepAlive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeepAlive")
+            .field("max", &self.max)
+            .field("on_evict", &self.on_evict.is_some())
+            .field("reuse", &self.reuse)
+            .finish()
+    }
+}
+
+/// A kept-alive component
+#[derive(Clone)]
+pub(crate) struct KeptEntity {
+    pub(crate) route: RouteRef,
+    /// Identifies the instance among those of its route
+    pub(crate) key: String,
+    /// Full pattern of the route (`/documents/:id`)
+    pub(crate) pattern: String,
+    /// Part of the path the route matched (`/documents/7`)
+    pub(crate) path: String,
+    pub(crate) entity: AnyEntity,
+}
+
+impl KeptEntity {
+    fn is_rendered(&self, scopes: &[RouteScope<'_>]) -> bool {
+        scopes
+            .iter()
+            .any(|scope| Arc::ptr_eq(scope.route_ref, &self.route) && scope.path == self.path)
+    }
+
+    fn reuse(&self) -> ReuseStrategy {
+        self.route
+            .keep_alive
+            .as_ref()
+            .map_or_else(ReuseStrategy::default, |keep| keep.reuse)
+    }
+}
+
+/// Components kept alive after navigation left their route
+#[derive(Clone, Default)]
+pub struct KeptAlive {
+    /// Least recently used first
+    entries: Vec<KeptEntity>,
+    /// Dropped components waiting for their evict callback
+    evicted: Vec<(AnyEntity, EvictCallback)>,
+    /// Instances dropped by navigations, not yet counted in the cache stats
+    dropped: usize,
+}
+
+impl std::fmt::Debug for KeptAlive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeptAlive")
+            .field("entries", &self.entries.len())
+            .field("evicted", &self.evicted.len())
+            .finish()
+    }
+}
+
+impl KeptAlive {
+    /// Number of components kept alive
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether dropped components wait for their evict callback or to be
+    /// counted in the cache stats
+    pub(crate) fn has_dropped(&self) -> bool {
+        !self.evicted.is_empty() || self.dropped > 0
+    }
+
+    /// Check if no component is kept alive
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of instances of the route matching `path_or_pattern` kept alive
+    pub fn count(&self, path_or_pattern: &str) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.path == path_or_pattern || entry.pattern == path_or_pattern)
+            .count()
+    }
+
+    /// Get the instance `key` of `route`, marking it most recently used
+    pub(crate) fn touch(&mut self, route: &RouteRef, key: &str) -> Option<AnyEntity> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| Arc::ptr_eq(&entry.route, route) && entry.key == key)?;
+        let entry = self.entries.remove(index);
+        let entity = entry.entity.clone();
+        self.entries.push(entry);
+        Some(entity)
+    }
+
+    /// Keep a new instance, then drop the least recently used ones over the
+    /// route's limit and over `global_max`
+    ///
+    /// Returns the number of instances dropped.
+    pub(crate) fn insert(
+        &mut self,
+        entry: KeptEntity,
+        global_max: Option<usize>,
+        rendered: &[RouteScope<'_>],
+    ) -> usize {
+        let route = Arc::clone(&entry.route);
+        let route_max = route
+            .keep_alive
+            .as_ref()
+            .map_or(usize::MAX, |keep| keep.max);
+        self.entries.push(entry);
+
+        let mut dropped = 0;
+        while self
+            .entries
+            .iter()
+            .filter(|entry| Arc::ptr_eq(&entry.route, &route))
+            .count()
+            > route_max
+        {
+            let oldest = self.entries.iter().position(|entry| {
+                Arc::ptr_eq(&entry.route, &route) && !entry.is_rendered(rendered)
+            });
+            let Some(index) = oldest else { break };
+            self.evict(index);
+            dropped += 1;
+        }
+        while self.entries.len() > global_max.unwrap_or(usize::MAX) {
+            let Some(index) = self
+                .entries
+                .iter()
+                .position(|entry| !entry.is_rendered(rendered))
+            else {
+                break;
+            };
+            self.evict(index);
+            dropped += 1;
+        }
+        dropped
+    }
+
+    /// Drop the instances matching `path_or_pattern` that are not rendered
+    pub(crate) fn drop_matching(
+        &mut self,
+        path_or_pattern: &str,
+        rendered: &[RouteScope<'_>],
+    ) -> usize {
+        let mut dropped = 0;
+        while let Some(index) = self.entries.iter().position(|entry| {
+            (entry.path == path_or_pattern || entry.pattern == path_or_pattern)
+                && !entry.is_rendered(rendered)
+        }) {
+            self.evict(index);
+            dropped += 1;
+        }
+        dropped
+    }
+
+    /// Which instances are on screen, in order
+    pub(crate) fn on_screen(&self, rendered: &[RouteScope<'_>]) -> Vec<bool> {
+        self.entries
+            .iter()
+            .map(|entry| entry.is_rendered(rendered))
+            .collect()
+    }
+
+    /// Drop the instances a navigation should not restore
+    ///
+    /// `was_on_screen` comes from [`KeptAlive::on_screen`] before the
+    /// navigation, `traversal` tells whether it went back or forward in
+    /// history. Instances on screen before and after stay; the others
+    /// follow their route's [`ReuseStrategy`].
+    pub(crate) fn navigated(
+        &mut self,
+        was_on_screen: &[bool],
+        rendered: &[RouteScope<'_>],
+        traversal: bool,
+    ) {
+        let drops: Vec<bool> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let before = was_on_screen.get(index).copied().unwrap_or(false);
+                let after = entry.is_rendered(rendered);
+                match entry.reuse() {
+                    ReuseStrategy::Always => false,
+                    ReuseStrategy::OnBackOnly => after && !before && !traversal,
+                    ReuseStrategy::Never => !(before && after),
+                }
+            })
+            .collect();
+        let mut removed = 0;
+        for (index, drop) in drops.into_iter().enumerate() {
+            if drop {
+                self.evict(index - removed);
+                removed += 1;
+            }
+        }
+        self.dropped += removed;
+    }
+
+    /// Drop all instances
+    pub(crate) fn clear(&mut self) {
+        while !self.entries.is_empty() {
+            self.evict(0);
+        }
+    }
+
+    fn evict(&mut self, index: usize) {
+        let entry = self.entries.remove(index);
+        trace_log!("Dropping kept-alive '{}' ({})", entry.pattern, entry.key);
+        if let Some(on_evict) = entry
+            .route
+            .keep_alive
+            .as_ref()
+            .and_then(|keep| keep.on_evict.clone())
+        {
+            self.evicted.push((entry.entity, on_evict));
+        }
+    }
+}
+
+impl GlobalRouter {
+    /// Drop the kept-alive components of routes matching `path_or_pattern`
+    ///
+    /// See [`Navigator::drop_kept_alive`](crate::Navigator::drop_kept_alive).
+    pub fn drop_kept_alive(&mut self, path_or_pattern: &str) -> usize {
+        let dropped = self.state_mut().drop_kept_alive(path_or_pattern);
+        self.record_kept_alive(0, dropped);
+        dropped
+    }
+
+    /// Update the kept-alive counters of the cache stats
+    #[cfg(feature = "cache")]
+    fn record_kept_alive(&mut self, reuses: usize, evictions: usize) {
+        let live = self.state().kept_alive().len();
+        self.nested_cache_mut()
+            .record_kept_alive(live, reuses, evictions);
+    }
+
+    #[cfg(not(feature = "cache"))]
+    #[allow(clippy::unused_self)]
+    fn record_kept_alive(&mut self, _reuses: usize, _evictions: usize) {}
+}
+
+/// Get the component `key` of the route being built, creating it
+///
+/// Routes without keep-alive keep it in the element state, as long as it is
+/// rendered; routes with keep-alive keep it in the router.
+pub(crate) fn component_entity<T: 'static>(
+    window: &mut Window,
+    cx: &mut App,
+    key: String,
+    create: impl FnOnce() -> T,
+) -> Entity<T> {
+    let route = cx
+        .try_global::<GlobalRouter>()
+        .and_then(|router| router.building_route.clone())
+        .filter(|route| route.keep_alive.is_some());
+    let Some(route) = route else {
+        return window.use_keyed_state(ElementId::Name(key.into()), cx, |_, _| create());
+    };
+
+    let kept = cx.update_global::<GlobalRouter, _>(|router, _| {
+        let entity = router.state_mut().kept_alive_mut().touch(&route, &key)?;
+        router.record_kept_alive(1, 0);
+        Some(entity)
+    });
+    if let Some(entity) = kept.and_then(|entity| entity.downcast::<T>().ok()) {
+        return entity;
+    }
+
+    // Instances are told apart by the part of the path their route matched
+    let router = cx.global::<GlobalRouter>();
+    let scope = router
+        .state()
+        .rendered_scopes()
+        .into_iter()
+        .find(|scope| Arc::ptr_eq(scope.route_ref, &route))
+        .map(|scope| (scope.route, scope.path));
+    let Some((pattern, path)) = scope else {
+        // Built for a path that is not current, such as a page sliding out
+        return window.use_keyed_state(ElementId::Name(key.into()), cx, |_, _| create());
+    };
+
+    trace_log!("Keeping '{}' alive ({})", pattern, key);
+    let current_view = window.current_view();
+    let entity = cx.new(|_| create());
+    cx.observe(&entity, move |_, cx| cx.notify(current_view))
+        .detach();
+    let entry = KeptEntity {
+        route,
+        key,
+        pattern,
+        path,
+        entity: entity.clone().into_any(),
+    };
+    cx.update_global::<GlobalRouter, _>(|router, _| {
+        let global_max = router.config().max_kept_alive;
+        let dropped = router.state_mut().keep_alive(entry, global_max);
+        router.record_kept_alive(0, dropped);
+    });
+    run_evict_callbacks(cx);
+    entity
+}
+
+/// Run the evict callbacks of kept-alive components dropped since last time
+///
+/// The router calls this after each navigation. Instances navigations
+/// dropped are counted in the cache stats here.
+pub(crate) fn run_evict_callbacks(cx: &mut App) {
+    let evicted = match cx.try_global::<GlobalRouter>() {
+        Some(router) if router.state().kept_alive().has_dropped() => cx
+            .update_global::<GlobalRouter, _>(|router, _| {
+                let kept = router.state_mut().kept_alive_mut();
+                let dropped = std::mem::take(&mut kept.dropped);
+                let evicted = std::mem::take(&mut kept.evicted);
+                router.record_kept_alive(0, dropped);
+                evicted
+            }),
+        _ => return,
+    };
+    for (entity, on_evict) in evicted {
+        on_evict(entity, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        init_router, GlobalRouter, Navigator, ReuseStrategy, Route, RouterConfig, RouterOutlet,
+    };
+    use gpui::{
+        div, Context, IntoElement, ParentElement, Render, TestAppContext, VisualTestContext, Window,
+    };
+    use std::sync::{Arc, Mutex};
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    struct Editor {
+        id: String,
+        edits: usize,
+    }
+
+    impl Render for Editor {
+        fn render(
+            &mut self,
+            _window: &mut Window,
+            _cx: &mut Context<'_, Self>,
+        ) -> impl IntoElement {
+            div().child(format!("{} ({} edits)", self.id, self.edits))
+        }
+    }
+
+    /// `/documents/:id` keeping at most `max` editors, with the ids of the
+    /// editors created and evicted
+    fn setup(
+        cx: &mut TestAppContext,
+        max: usize,
+        global_max: Option<usize>,
+    ) -> (&mut VisualTestContext, Log, Log) {
+        let created = Log::default();
+        let evicted = Log::default();
+        cx.update(|cx| {
+            let (created, evicted) = (created.clone(), evicted.clone());
+            init_router(cx, move |router| {
+                let mut config = RouterConfig::new();
+                if let Some(global_max) = global_max {
+                    config = config.max_kept_alive_entities(global_max);
+                }
+                router.set_config(config);
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::component_with_params("/documents/:id", move |params| {
+                        let id = params.get("id").cloned().unwrap_or_default();
+                        created.lock().unwrap().push(id.clone());
+                        Editor { id, edits: 0 }
+                    })
+                    .keep_alive_max(max)
+                    .on_evict(move |editor: &mut Editor, _| {
+                        evicted.lock().unwrap().push(editor.id.clone());
+                    }),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        (cx, created, evicted)
+    }
+
+    /// `/compose` with `strategy`, with the number of composers created and
+    /// evicted
+    fn setup_reuse(
+        cx: &mut TestAppContext,
+        strategy: ReuseStrategy,
+    ) -> (&mut VisualTestContext, Log, Log) {
+        let created = Log::default();
+        let evicted = Log::default();
+        cx.update(|cx| {
+            let (created, evicted) = (created.clone(), evicted.clone());
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::component("/compose", move || {
+                        let mut created = created.lock().unwrap();
+                        let id = created.len().to_string();
+                        created.push(id.clone());
+                        Editor { id, edits: 0 }
+                    })
+                    .reuse(strategy)
+                    .on_evict(move |editor: &mut Editor, _| {
+                        evicted.lock().unwrap().push(editor.id.clone());
+                    }),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        (cx, created, evicted)
+    }
+
+    fn back(cx: &mut VisualTestContext) {
+        cx.update(|window, cx| {
+            Navigator::back(cx);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    fn navigate(cx: &mut VisualTestContext, path: &str) {
+        cx.update(|window, cx| {
+            Navigator::push(cx, path);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    fn log(log: &Log) -> Vec<String> {
+        log.lock().unwrap().clone()
+    }
+
+    fn kept(cx: &mut VisualTestContext) -> usize {
+        cx.update(|_, cx| cx.global::<GlobalRouter>().state().kept_alive().len())
+    }
+
+    #[gpui::test]
+    fn test_revisit_reuses_kept_component(cx: &mut TestAppContext) {
+        let (cx, created, _) = setup(cx, 5, None);
+
+        navigate(cx, "/documents/1");
+        navigate(cx, "/");
+        navigate(cx, "/documents/1");
+        assert_eq!(log(&created), vec!["1"]);
+        assert_eq!(kept(cx), 1);
+        #[cfg(feature = "cache")]
+        cx.update(|_, cx| {
+            let stats = cx.global::<GlobalRouter>().cache_stats();
+            assert_eq!(stats.kept_alive, 1);
+            assert!(stats.kept_alive_reuses > 0);
+        });
+    }
+
+    /// Composers created and evicted visiting `/compose`, leaving it, then
+    /// returning with `revisit`
+    fn revisit(
+        cx: &mut TestAppContext,
+        strategy: ReuseStrategy,
+        revisit: fn(&mut VisualTestContext),
+    ) -> (usize, usize) {
+        let (cx, created, evicted) = setup_reuse(cx, strategy);
+        navigate(cx, "/compose");
+        navigate(cx, "/");
+        revisit(cx);
+        // Rendering again keeps the instance shown
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        (log(&created).len(), log(&evicted).len())
+    }
+
+    #[gpui::test]
+    fn test_reuse_strategies_going_back(
+        always: &mut TestAppContext,
+        on_back_only: &mut TestAppContext,
+        never: &mut TestAppContext,
+    ) {
+        assert_eq!(revisit(always, ReuseStrategy::Always, back), (1, 0));
+        assert_eq!(
+            revisit(on_back_only, ReuseStrategy::OnBackOnly, back),
+            (1, 0)
+        );
+        assert_eq!(revisit(never, ReuseStrategy::Never, back), (2, 1));
+    }
+
+    #[gpui::test]
+    fn test_reuse_strategies_pushing_again(
+        always: &mut TestAppContext,
+        on_back_only: &mut TestAppContext,
+        never: &mut TestAppContext,
+    ) {
+        let push = |cx: &mut VisualTestContext| navigate(cx, "/compose");
+        assert_eq!(revisit(always, ReuseStrategy::Always, push), (1, 0));
+        assert_eq!(
+            revisit(on_back_only, ReuseStrategy::OnBackOnly, push),
+            (2, 1)
+        );
+        assert_eq!(revisit(never, ReuseStrategy::Never, push), (2, 1));
+    }
+
+    #[gpui::test]
+    fn test_route_limit_evicts_least_recently_used(cx: &mut TestAppContext) {
+        let (cx, created, evicted) = setup(cx, 2, None);
+
+        navigate(cx, "/documents/1");
+        navigate(cx, "/documents/2");
+        navigate(cx, "/documents/3");
+        assert_eq!(log(&evicted), vec!["1"]);
+        assert_eq!(kept(cx), 2);
+
+        // The dropped editor comes back fresh, the kept one as it was
+        navigate(cx, "/documents/1");
+        navigate(cx, "/documents/3");
+        assert_eq!(log(&created), vec!["1", "2", "3", "1"]);
+        assert_eq!(log(&evicted), vec!["1", "2"]);
+        #[cfg(feature = "cache")]
+        cx.update(|_, cx| {
+            assert_eq!(
+                cx.global::<GlobalRouter>()
+                    .cache_stats()
+                    .kept_alive_evictions,
+                2
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_current_route_never_evicted(cx: &mut TestAppContext) {
+        let (cx, _, evicted) = setup(cx, 0, None);
+
+        navigate(cx, "/documents/1");
+        assert!(log(&evicted).is_empty());
+        assert_eq!(kept(cx), 1);
+
+        // Leaving, the next instance drops it
+        navigate(cx, "/documents/2");
+        assert_eq!(log(&evicted), vec!["1"]);
+        assert_eq!(kept(cx), 1);
+    }
+
+    #[gpui::test]
+    fn test_global_limit(cx: &mut TestAppContext) {
+        let (cx, _, evicted) = setup(cx, 5, Some(2));
+
+        navigate(cx, "/documents/1");
+        navigate(cx, "/documents/2");
+        navigate(cx, "/documents/1");
+        navigate(cx, "/documents/3");
+        assert_eq!(log(&evicted), vec!["2"]);
+        assert_eq!(kept(cx), 2);
+    }
+
+    #[gpui::test]
+    fn test_drop_kept_alive(cx: &mut TestAppContext) {
+        let (cx, created, evicted) = setup(cx, 5, None);
+
+        navigate(cx, "/documents/1");
+        navigate(cx, "/documents/2");
+        navigate(cx, "/documents/3");
+        let dropped = cx.update(|_, cx| Navigator::drop_kept_alive(cx, "/documents/2"));
+        assert_eq!(dropped, 1);
+        assert_eq!(log(&evicted), vec!["2"]);
+
+        // The current document stays
+        let dropped = cx.update(|_, cx| Navigator::drop_kept_alive(cx, "/documents/:id"));
+        assert_eq!(dropped, 1);
+        assert_eq!(log(&evicted), vec!["2", "1"]);
+        assert_eq!(kept(cx), 1);
+        navigate(cx, "/documents/3");
+        assert_eq!(log(&created), vec!["1", "2", "3"]);
+    }
+}
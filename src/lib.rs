@@ -100,8 +100,29 @@
 //!
 //! # Feature Flags
 //!
+//! - `guard` (default) - Route guards (`guards` module)
+//! - `middleware` (default) - Navigation middleware (`middleware` module)
+//! - `transition` (default) - Animated route transitions (`transition` module)
+//! - `cache` (default) - LRU cache of route resolution (`cache` module)
 //! - `log` (default) - Uses the standard `log` crate for logging
-//! - `tracing` - Uses the `tracing` crate for structured logging (mutually exclusive with `log`)
+//! - `tracing` - Uses the `tracing` crate for structured logging (mutually exclusive with `log`),
+//!   tracing each navigation as a `router.navigate` span with `match`, `guards`
+//!   and `render.outlet` spans below it
+//! - `test-util` - Test harness for apps using the router (`testing` module)
+//! - `wall-clock` - `HistoryEntry::entered_at_wall`, the `SystemTime` an entry
+//!   was entered at, for serializing history
+//! - `macros` - `route_path!`, which checks route paths at compile time and
+//!   turns them into pre-parsed [`PathPattern`]s
+//! - `serde` - `Serialize` and `Deserialize` for [`RouteParams`], [`QueryParams`],
+//!   [`RouteMatch`], [`RouteChangeEvent`], [`NavigationDirection`],
+//!   [`HistoryEntry`] and [`HistoryState`](history::HistoryState), with stable
+//!   field names and sorted map keys
+//! - `gestures` - Trackpad swipes navigating back and forward (`SwipeNavigator`)
+//! - `actions` - gpui actions navigating from menus and key bindings
+//!   (`actions` module), implies `serde`
+//!
+//! Any combination of features builds. [`prelude`] imports the core that is
+//! the same under all of them, plus the types of the enabled features.
 
 #![doc(html_root_url = "https://docs.rs/gpui_navigator/0.1.0")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -110,69 +131,228 @@
 // Logging abstraction
 pub mod logging;
 
+// Structured diagnostics (spans with the `tracing` feature)
+mod diagnostics;
+
 // Cache (optional)
 #[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
 pub mod cache;
 
 // Core routing modules
 pub mod history;
 pub mod matcher;
 #[cfg(feature = "middleware")]
+#[cfg_attr(docsrs, doc(cfg(feature = "middleware")))]
 pub mod middleware;
 pub mod route;
 pub mod state;
 
+// Router-wide configuration
+pub mod config;
+
 // Error handling
 pub mod error;
 
 // Route lifecycle
 pub mod lifecycle;
 
+// Route data loaders
+pub mod loader;
+
+// Navigation cancellation
+pub mod cancel;
+
+// Navigation throttling
+pub mod throttle;
+
+// Focus management
+pub mod focus;
+
+// Navigation from background tasks
+pub mod handle;
+
+// Localized route paths
+pub mod locale;
+
+// Relative navigation paths
+pub mod relative;
+
+// Route-scoped state
+pub mod scope;
+
+// Kept-alive route components
+pub mod keep_alive;
+
+// Multi-step flows
+pub mod flow;
+
+// Route meta keyed by type
+pub mod meta;
+
+// Rendering a path without navigating
+pub mod resolved;
+
 // Guards
 #[cfg(feature = "guard")]
+#[cfg_attr(docsrs, doc(cfg(feature = "guard")))]
 pub mod guards;
 
 // Transitions
 #[cfg(feature = "transition")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transition")))]
 pub mod transition;
 
+// Shared element transitions
+#[cfg(feature = "transition")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transition")))]
+pub mod hero;
+
+// Trackpad swipe navigation
+#[cfg(feature = "gestures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gestures")))]
+pub mod gesture;
+
+// Curated imports
+pub mod prelude;
+
 // Other modules
 pub mod nested;
 pub mod params;
 pub mod widgets;
 
+// Outlet render planning (internal)
+#[doc(hidden)]
+pub mod outlet_plan;
+
+// Test harness
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod testing;
+
+// State dumps for debugging
+pub mod dump;
+
+// Window options per route
+pub mod window_hints;
+
+// Command palette entries
+pub mod palette;
+
+// Layout slots filled by the matched routes
+pub mod slot;
+
+// Route filters for guards and middleware added to the router
+#[cfg(any(feature = "guard", feature = "middleware"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "guard", feature = "middleware"))))]
+pub mod filter;
+
+// Navigation as gpui actions
+#[cfg(feature = "actions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "actions")))]
+pub mod actions;
+
 // Context module (router context integration)
 mod context;
 
+// Serialization of core types (optional)
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+// Fixtures shared by the unit tests
+#[cfg(test)]
+mod test_util;
+
 // Re-export main types for convenient access
+#[cfg(feature = "actions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "actions")))]
+pub use actions::{bind_router_actions, NavigateBack, NavigateForward, NavigateReload, NavigateTo};
 #[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
 pub use cache::{CacheStats, RouteCache, RouteId};
+pub use cancel::{Cancelled, NavigationToken};
+pub use config::{
+    DuplicateNamePolicy, DuplicatePolicy, FocusPolicy, HistoryEdgeHandler, NavOptions,
+    PreserveQuery, ReinitPolicy, RenderNavigationPolicy, RouterConfig, TableHistoryPolicy,
+    ThrottlePolicy, TimeoutFallback,
+};
+#[allow(deprecated)]
+pub use context::current_path;
 pub use context::{
-    current_path, init_router, navigate, GlobalRouter, NavigationRequest, Navigator,
-    NavigatorHandle, UseRouter,
+    init_router, init_router_with_initial, init_router_with_initial_async, navigate, GlobalRouter,
+    NavigationRequest, Navigator, NavigatorHandle, OpenRouteHandler, UseRouter, WindowExt,
+    WindowNavigator,
+};
+pub use dump::{HistoryEntryDump, RouteDump, RouterStateDump};
+pub use error::{
+    report_refused_navigations, ErrorBoundary, ErrorHandler, ErrorHandlers, NavigationError,
+    NavigationResult, NotFoundDetails, NotFoundHandler, RefusedHandler, RetryHandle, RouteError,
 };
-pub use error::{ErrorHandler, ErrorHandlers, NavigationError, NavigationResult, NotFoundHandler};
+#[cfg(any(feature = "guard", feature = "middleware"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "guard", feature = "middleware"))))]
+pub use filter::{FilteredEntry, FilteredKind, RouteExplanation, RouteFilter};
+pub use flow::{FlowExit, FlowPolicy, FlowState, FlowStep, FlowTracker};
+pub use focus::{focus_target_handle, RouteFocusExt};
+#[cfg(feature = "gestures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gestures")))]
+pub use gesture::{SwipeDirection, SwipeNavigator};
+/// Check a route path at compile time, producing a [`PathPattern`]
+#[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+pub use gpui_navigator_macros::route_path;
 #[cfg(feature = "guard")]
+#[cfg_attr(docsrs, doc(cfg(feature = "guard")))]
 pub use guards::{
-    guard_fn, AuthGuard, BoxedGuard, GuardBuilder, GuardContext, GuardResult, Guards, NotGuard,
-    PermissionGuard, RoleGuard, RouteGuard,
+    guard_fn, prompt_leave_confirmation, AccessCheck, AuthGuard, BoxedGuard, ConfirmHandler,
+    ConfirmLeaveGuard, GuardBuilder, GuardContext, GuardOrder, GuardResult, Guards, LeaveDecision,
+    NotGuard, ParamScopedGuard, PermissionGuard, RoleGuard, RouteGuard, TransitionCheckFn,
+    TransitionGuard, TransitionMatchFn,
 };
+pub use handle::{PendingNavigation, RouterHandle};
+#[cfg(feature = "transition")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transition")))]
+pub use hero::{interpolate_hero, Hero, HeroExt, HeroFrame};
+pub use history::{EntryKind, History, HistoryEntry, HistoryListener, LoggingHistoryListener};
+pub use keep_alive::{EvictCallback, KeepAlive, KeptAlive, ReuseStrategy};
 pub use lifecycle::{BoxedLifecycle, LifecycleResult, RouteLifecycle};
+pub use loader::{DataState, LoaderData, LoaderMode, RouteDataStore};
+pub use matcher::UrlForError;
+pub use meta::{MetaKey, MetaValue, RouteTitle, TypedMeta};
 #[cfg(feature = "middleware")]
-pub use middleware::{middleware_fn, BoxedMiddleware, RouteMiddleware};
+#[cfg_attr(docsrs, doc(cfg(feature = "middleware")))]
+pub use middleware::{
+    middleware_fn, run_navigation_middleware, AfterNavigation, AnalyticsMiddleware,
+    BoxedMiddleware, DebugLogMiddleware, NavigationOutcome, RouteMiddleware, ScreenView,
+};
 pub use nested::{build_child_path, resolve_child_route};
-pub use params::{QueryParams, RouteParams};
+pub use palette::{NavigationCommand, PaletteHidden, PaletteLabel, PaletteParams, ScoredCommand};
+pub use params::{ParamsDiff, QueryParams, RouteParams};
+pub use resolved::{render_resolved, ResolvedNavigation};
+#[allow(deprecated)]
+pub use route::BuilderFn;
 pub use route::{
-    validate_route_path, BuilderFn, IntoRoute, NamedRoute, NamedRouteRegistry, PageRoute, Route,
-    RouteConfig, RouteDescriptor,
+    validate_route_path, validate_route_tree, IntoRoute, IntoRoutePath, IssueSeverity, NamedRoute,
+    NamedRouteRegistry, NamedTarget, PageRoute, PathPattern, PathSegment, PrepareFn, Presentation,
+    Route, RouteBuilder, RouteConfig, RouteDescriptor, RouteIssue, DEFAULT_MAX_ROUTE_DEPTH,
 };
+pub use scope::{dispose_route_states, RouteState, ScopedStates};
+pub use slot::RouterSlot;
 pub use state::{Router, RouterState};
 #[cfg(feature = "transition")]
-pub use transition::{SlideDirection, Transition, TransitionConfig};
+#[cfg_attr(docsrs, doc(cfg(feature = "transition")))]
+pub use transition::{
+    SlideDirection, Transition, TransitionConfig, TransitionPhase, TransitionProgress,
+};
+#[cfg(feature = "guard")]
+#[cfg_attr(docsrs, doc(cfg(feature = "guard")))]
+pub use widgets::BlockedLink;
+#[allow(deprecated)]
 pub use widgets::{
-    render_router_outlet, router_link, router_outlet, router_outlet_named, DefaultPages,
-    RouterLink, RouterOutlet,
+    render_router_outlet, router_link, router_outlet, router_outlet_named, BackButton,
+    DefaultPages, ForwardButton, HistoryDirection, HistoryMenu, HistoryMenuEntry, LinkState,
+    OutletPlaceholder, RouterLink, RouterLinkTheme, RouterOutlet, SplitRouterView, SplitSize,
 };
+pub use window_hints::{apply_window_hints, apply_window_title, WindowHintApplier, WindowHints};
 
 use std::collections::HashMap;
 
@@ -190,6 +370,8 @@ use std::collections::HashMap;
 ///
 /// assert_eq!(route_match.params.get("id"), Some(&"123".to_string()));
 /// ```
+///
+/// With the `serde` feature, serializes as `{"path":..,"params":{..},"query":{..}}`.
 #[derive(Debug, Clone)]
 pub struct RouteMatch {
     /// The matched path
@@ -198,6 +380,8 @@ pub struct RouteMatch {
     pub params: HashMap<String, String>,
     /// Parsed query string parameters
     pub query: HashMap<String, String>,
+    /// Fragment of the path, for in-page anchors
+    pub fragment: Option<String>,
 }
 
 impl RouteMatch {
@@ -208,6 +392,7 @@ impl RouteMatch {
             path,
             params: HashMap::new(),
             query: HashMap::new(),
+            fragment: None,
         }
     }
 
@@ -229,6 +414,7 @@ impl RouteMatch {
 /// Navigation direction indicator.
 ///
 /// Used to determine the direction of navigation for animations and history management.
+/// With the `serde` feature, serializes as `"forward"`, `"back"` or `"replace"`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NavigationDirection {
     /// Navigating forward to a new route
@@ -239,16 +425,267 @@ pub enum NavigationDirection {
     Replace,
 }
 
+/// What started a navigation
+///
+/// Set on [`RouteChangeEvent::source`] and [`NavigationRequest::source`], so
+/// analytics can tell how users move around and pages can behave differently
+/// depending on how they were reached. With the `serde` feature, serializes
+/// as `{"kind":"link","element_id":..}`, `{"kind":"redirect","from_guard":..}`
+/// or `{"kind":..}` with `programmatic`, `action`, `deep_link` or `history`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum NavigationSource {
+    /// A call to `Navigator` or `GlobalRouter` made by the app
+    ///
+    /// Override it with [`NavOptions::source`].
+    #[default]
+    Programmatic,
+    /// A click on a [`RouterLink`], or its context menu
+    Link {
+        /// Id given with [`RouterLink::element_id`], if any
+        element_id: Option<String>,
+    },
+    /// A navigation action, from a menu item or a key binding
+    Action,
+    /// A URL opened from outside the app, see [`Navigator::handle_external_url`]
+    DeepLink,
+    /// Going back or forward in history, whatever asked for it
+    History,
+    /// A guard redirecting another navigation
+    Redirect {
+        /// Name of the guard that redirected
+        from_guard: String,
+    },
+}
+
 /// Event emitted when the route changes.
 ///
 /// Contains information about the navigation that occurred, including
 /// the source and destination paths and the direction of navigation.
+///
+/// The route `to` resolved to is described too, so subscribers (analytics,
+/// breadcrumbs, window titles) don't have to match the path again. New fields
+/// may be added, so events are built with [`RouteChangeEvent::new`].
+///
+/// With the `serde` feature, serializes with the field names below;
+/// `previous_dwell` is written as `{"secs":..,"nanos":..}`.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct RouteChangeEvent {
-    /// The previous path (None if this is the first navigation)
+    /// The path navigated away from
+    ///
+    /// Always set by the router, including for the first navigation after
+    /// init, which comes from the initial path.
     pub from: Option<String>,
-    /// The new path being navigated to
+    /// The new path being navigated to, without its fragment
     pub to: String,
+    /// Fragment of the location navigated to
+    pub fragment: Option<String>,
+    /// Whether only the fragment changed
+    ///
+    /// The route stays mounted: guards, loaders, middleware and transitions
+    /// are skipped, and pages only need to scroll to the new
+    /// [`fragment`](Self::fragment).
+    pub fragment_only: bool,
     /// The direction of navigation
     pub direction: NavigationDirection,
+    /// Whether both paths matched the same route (e.g. `/users/1` to `/users/2`)
+    pub same_route: bool,
+    /// How the route's params changed, set when `same_route` is true
+    pub params_diff: Option<ParamsDiff>,
+    /// Time spent on the entry navigated away from
+    ///
+    /// Set for committed navigations; for a replace it is the time spent on
+    /// the replaced entry. See [`Navigator::time_on_current_route`].
+    pub previous_dwell: Option<std::time::Duration>,
+    /// Full pattern of the route `to` matched, e.g. `/users/:id/posts`
+    ///
+    /// `None` when no route matched.
+    pub pattern: Option<String>,
+    /// Name of the innermost route `to` matched, if it has one
+    pub route_name: Option<String>,
+    /// Params of the route `to` matched
+    pub params: RouteParams,
+    /// Params of the route `to` matched, split by the level of its route
+    /// chain that introduced them, outermost first
+    ///
+    /// Layouts can key caches on their own level's params only. See
+    /// [`RouterState::params_by_level`].
+    pub level_params: Vec<RouteParams>,
+    /// Query params of `to`
+    pub query: QueryParams,
+    /// Whether a route matched `to`; `false` for the not-found page
+    pub matched: bool,
+    /// What started the navigation
+    pub source: NavigationSource,
+}
+
+impl RouteChangeEvent {
+    /// Create an event for a navigation from `from` to `to`
+    ///
+    /// Everything else starts empty; the router fills it in for the events
+    /// it emits. Useful to test middleware and event subscribers.
+    #[must_use]
+    pub fn new(
+        from: Option<String>,
+        to: impl Into<String>,
+        direction: NavigationDirection,
+    ) -> Self {
+        Self {
+            from,
+            to: to.into(),
+            fragment: None,
+            fragment_only: false,
+            direction,
+            same_route: false,
+            params_diff: None,
+            previous_dwell: None,
+            pattern: None,
+            route_name: None,
+            params: RouteParams::new(),
+            level_params: Vec::new(),
+            query: QueryParams::new(),
+            matched: false,
+            source: NavigationSource::Programmatic,
+        }
+    }
+}
+
+/// What going back or forward did, see [`Navigator::pop`]
+#[derive(Debug, Clone)]
+// Returned once per pop, like the `Option<RouteChangeEvent>` of other navigations
+#[allow(clippy::large_enum_variant)]
+pub enum PopOutcome {
+    /// A history entry, or the table set with
+    /// [`RouterConfig::back_at_root_to_table`], was navigated to
+    Navigated(RouteChangeEvent),
+    /// No entry was left and the handler set with
+    /// [`RouterConfig::on_back_at_root`] or
+    /// [`RouterConfig::on_forward_at_end`] ran
+    Delegated,
+    /// Nothing happened: no entry was left and no handler is set, or the
+    /// navigation was throttled, held or deferred
+    NoOp,
+}
+
+impl PopOutcome {
+    /// Event of the navigation, if one occurred
+    pub fn event(&self) -> Option<&RouteChangeEvent> {
+        match self {
+            Self::Navigated(event) => Some(event),
+            Self::Delegated | Self::NoOp => None,
+        }
+    }
+
+    /// Take the event of the navigation, if one occurred
+    pub fn into_event(self) -> Option<RouteChangeEvent> {
+        match self {
+            Self::Navigated(event) => Some(event),
+            Self::Delegated | Self::NoOp => None,
+        }
+    }
+
+    /// Check if a navigation occurred
+    pub fn is_navigated(&self) -> bool {
+        matches!(self, Self::Navigated(_))
+    }
+
+    /// Check if the app-level handler ran
+    pub fn is_delegated(&self) -> bool {
+        matches!(self, Self::Delegated)
+    }
+}
+
+impl From<Option<RouteChangeEvent>> for PopOutcome {
+    fn from(event: Option<RouteChangeEvent>) -> Self {
+        event.map_or(Self::NoOp, Self::Navigated)
+    }
+}
+
+/// What a push or replace did, see [`Navigator::push`]
+#[derive(Debug, Clone)]
+// Returned once per navigation, like `PopOutcome`
+#[allow(clippy::large_enum_variant)]
+pub enum PushOutcome {
+    /// The navigation went through
+    Navigated(RouteChangeEvent),
+    /// A guard, a strict-mode check, a redirect loop or a target that does
+    /// not resolve refused it, leaving the current path
+    Refused { error: NavigationError },
+    /// Held back by throttling, and applied once the interval has elapsed
+    /// unless a later navigation supersedes it
+    Throttled { path: String },
+    /// Dropped by throttling
+    Dropped { path: String },
+    /// Waits for a blocking loader, for guards that have not resolved yet
+    /// or for leaving a route with unsaved changes to be confirmed, and is
+    /// committed once they are done unless a later navigation supersedes it
+    Pending { path: String },
+}
+
+impl PushOutcome {
+    /// Event of the navigation, if it went through
+    pub fn event(&self) -> Option<&RouteChangeEvent> {
+        match self {
+            Self::Navigated(event) => Some(event),
+            Self::Refused { .. }
+            | Self::Throttled { .. }
+            | Self::Dropped { .. }
+            | Self::Pending { .. } => None,
+        }
+    }
+
+    /// Take the event of the navigation, if it went through
+    pub fn into_event(self) -> Option<RouteChangeEvent> {
+        match self {
+            Self::Navigated(event) => Some(event),
+            Self::Refused { .. }
+            | Self::Throttled { .. }
+            | Self::Dropped { .. }
+            | Self::Pending { .. } => None,
+        }
+    }
+
+    /// Check if the navigation went through
+    pub fn is_navigated(&self) -> bool {
+        matches!(self, Self::Navigated(_))
+    }
+
+    /// Check if the navigation was refused
+    pub fn is_refused(&self) -> bool {
+        matches!(self, Self::Refused { .. })
+    }
+
+    /// Check if the navigation waits to be committed
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending { .. })
+    }
+
+    /// Check if throttling held the navigation back or dropped it
+    pub fn is_throttled(&self) -> bool {
+        matches!(self, Self::Throttled { .. } | Self::Dropped { .. })
+    }
+}
+
+impl From<PushOutcome> for NavigationResult {
+    fn from(outcome: PushOutcome) -> Self {
+        match outcome {
+            PushOutcome::Navigated(event) => Self::Success { path: event.to },
+            PushOutcome::Refused {
+                error: NavigationError::GuardBlocked { reason },
+            } => Self::Blocked {
+                reason,
+                redirect: None,
+            },
+            PushOutcome::Refused {
+                error: NavigationError::RouteNotFound { path, .. },
+            } => Self::NotFound { path },
+            PushOutcome::Refused { error } => Self::Error(error),
+            PushOutcome::Throttled { path } | PushOutcome::Pending { path } => {
+                Self::Pending { path }
+            }
+            PushOutcome::Dropped { path } => Self::Error(NavigationError::NavigationFailed {
+                message: format!("Navigation to '{}' was dropped by throttling", path),
+            }),
+        }
+    }
 }
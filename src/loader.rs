@@ -0,0 +1,839 @@
+//! Route data loaders
+//!
+//! A loader fetches the data a route needs when it is navigated to. Its
+//! result is stored in the router per path and handed to the route's builder
+//! through [`RouteParams::data`]:
+//!
+//! ```ignore
+//! use gpui_navigator::{DataState, LoaderMode, Route};
+//!
+//! Route::new("/users/:id", |_, _, params| match params.data::<User>() {
+//!     Some(DataState::Ready(user)) => user_page(&user),
+//!     Some(DataState::Error(message)) => error_page(&message),
+//!     _ => spinner(),
+//! })
+//! .loader(|params, _cx| async move { fetch_user(params.get("id")).await })
+//! .loader_mode(LoaderMode::Deferred);
+//! ```
+//!
+//! Instead of matching on `Loading` in the builder, a route can show a
+//! [skeleton](crate::Route::skeleton) shaped like the page while its data
+//! loads; the outlet crossfades it to the page when the data is there.
+//!
+//! Loaders are started by the navigation to their route, on the next tick.
+//! Navigating elsewhere before a loader finishes cancels it, and results of
+//! superseded loads are discarded; results of paths no longer in the history
+//! are dropped. Loaders get the
+//! [`NavigationToken`] of their navigation through
+//! [`RouteParams::navigation_token`]. Blocking loaders still running
+//! at the [navigation timeout](crate::RouterConfig::navigation_timeout) are
+//! cancelled too, aborting the navigation they hold.
+
+use crate::cancel::NavigationToken;
+use crate::context::GlobalRouter;
+use crate::{debug_log, trace_log, NavigationDirection, RouteParams};
+use gpui::{App, AsyncApp, BorrowAppContext, Task};
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+/// Type-erased loader output
+pub type LoaderData = Arc<dyn Any + Send + Sync>;
+
+/// Future returned by a type-erased loader
+pub type LoaderFuture = Pin<Box<dyn Future<Output = Result<LoaderData, String>> + Send>>;
+
+/// Type-erased loader function
+pub type LoaderFn = Arc<dyn Fn(RouteParams, &App) -> LoaderFuture + Send + Sync>;
+
+/// Wrap a typed loader closure into a [`LoaderFn`]
+pub(crate) fn loader_fn<F, Fut, T>(loader: F) -> LoaderFn
+where
+    F: Fn(RouteParams, &App) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+    T: Send + Sync + 'static,
+{
+    Arc::new(move |params, cx| {
+        let future = loader(params, cx);
+        Box::pin(async move { future.await.map(|data| Arc::new(data) as LoaderData) })
+    })
+}
+
+// ============================================================================
+// Loader Mode / Data State
+// ============================================================================
+
+/// When navigation to a route with a loader is committed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoaderMode {
+    /// Commit once the loader finishes; the current page stays up meanwhile
+    ///
+    /// The builder only runs with `Ready` data. Loader errors are rendered
+    /// with `DefaultPages::render_error`.
+    Blocking,
+    /// Commit immediately and stream the data in (default)
+    ///
+    /// The builder sees `Loading` first, then `Ready` or `Error`.
+    #[default]
+    Deferred,
+}
+
+/// State of a route's loaded data
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataState<T> {
+    /// The loader is still running
+    Loading,
+    /// The loader finished
+    Ready(T),
+    /// The loader failed with a message
+    Error(String),
+}
+
+impl<T> DataState<T> {
+    /// Check if the loader is still running
+    pub fn is_loading(&self) -> bool {
+        matches!(self, DataState::Loading)
+    }
+
+    /// Check if data is available
+    pub fn is_ready(&self) -> bool {
+        matches!(self, DataState::Ready(_))
+    }
+
+    /// Get the data if available
+    pub fn ready(&self) -> Option<&T> {
+        match self {
+            DataState::Ready(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Get the error message if the loader failed
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            DataState::Error(message) => Some(message),
+            _ => None,
+        }
+    }
+}
+
+impl DataState<LoaderData> {
+    /// Downcast ready data to a concrete type
+    ///
+    /// Data of another type is reported as an error.
+    pub fn downcast<T: Any + Send + Sync>(&self) -> DataState<Arc<T>> {
+        match self {
+            DataState::Loading => DataState::Loading,
+            DataState::Error(message) => DataState::Error(message.clone()),
+            DataState::Ready(data) => match Arc::clone(data).downcast::<T>() {
+                Ok(data) => DataState::Ready(data),
+                Err(_) => DataState::Error(format!(
+                    "Loader data is not a {}",
+                    std::any::type_name::<T>()
+                )),
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Data Store
+// ============================================================================
+
+/// Loader results keyed by navigated path
+#[derive(Debug, Clone, Default)]
+pub struct RouteDataStore {
+    entries: HashMap<String, DataEntry>,
+    next_generation: u64,
+}
+
+#[derive(Debug, Clone)]
+struct DataEntry {
+    generation: u64,
+    state: DataState<LoaderData>,
+}
+
+impl RouteDataStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `path` as loading, superseding any earlier load for it
+    ///
+    /// Returns the generation the result must be stored with.
+    pub fn begin(&mut self, path: &str) -> u64 {
+        self.next_generation += 1;
+        self.entries.insert(
+            path.to_string(),
+            DataEntry {
+                generation: self.next_generation,
+                state: DataState::Loading,
+            },
+        );
+        self.next_generation
+    }
+
+    /// Store the result of a load
+    ///
+    /// Returns `false` (and stores nothing) if the load was superseded.
+    pub fn finish(
+        &mut self,
+        path: &str,
+        generation: u64,
+        result: Result<LoaderData, String>,
+    ) -> bool {
+        match self.entries.get_mut(path) {
+            Some(entry) if entry.generation == generation => {
+                entry.state = match result {
+                    Ok(data) => DataState::Ready(data),
+                    Err(message) => DataState::Error(message),
+                };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the data state for a path
+    pub fn get(&self, path: &str) -> Option<&DataState<LoaderData>> {
+        self.entries.get(path).map(|entry| &entry.state)
+    }
+
+    /// Drop the entry for a path
+    pub fn remove(&mut self, path: &str) {
+        self.entries.remove(path);
+    }
+
+    /// Drop all entries
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Keep only the entries for which `keep` returns `true`
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &DataState<LoaderData>) -> bool) {
+        self.entries
+            .retain(|path, entry| keep(path.as_str(), &entry.state));
+    }
+}
+
+// ============================================================================
+// Running Loaders
+// ============================================================================
+
+/// A load scheduled by a navigation
+pub(crate) struct PendingLoad {
+    pub(crate) path: String,
+    pub(crate) params: RouteParams,
+    pub(crate) load: LoaderFn,
+    pub(crate) generation: u64,
+    /// Token of the navigation the load is for
+    pub(crate) token: NavigationToken,
+    /// Navigation to commit once loaded (blocking loaders only)
+    pub(crate) commit: Option<PendingCommit>,
+    /// When the navigation started and how long it may wait (blocking loaders only)
+    pub(crate) deadline: Option<(Instant, Duration)>,
+}
+
+/// How a blocked navigation is committed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PendingCommit {
+    Push,
+    Replace,
+}
+
+impl PendingCommit {
+    /// Direction of the navigation once committed
+    pub(crate) const fn direction(self) -> NavigationDirection {
+        match self {
+            Self::Push => NavigationDirection::Forward,
+            Self::Replace => NavigationDirection::Replace,
+        }
+    }
+}
+
+/// Run the loader `pending` on the next tick
+///
+/// When the loader finishes, the result is stored (committing a blocked
+/// navigation) and windows are refreshed. A blocked navigation still waiting
+/// at its deadline is aborted instead.
+pub(crate) fn spawn_loader(app: &AsyncApp, pending: PendingLoad) -> Task<()> {
+    trace_log!("Loader task spawned");
+    app.spawn(async move |cx| {
+        let PendingLoad {
+            path,
+            params,
+            load,
+            generation,
+            token,
+            commit,
+            deadline,
+        } = pending;
+        if token.is_cancelled() {
+            return;
+        }
+        debug_log!("Starting loader for '{}'", path);
+        let Ok(mut future) = cx.update(|cx| load(params, cx)) else {
+            return;
+        };
+        // Measured from the navigation
+        let mut timer = deadline.map(|(started, timeout)| {
+            let waited = cx
+                .background_executor()
+                .now()
+                .saturating_duration_since(started);
+            cx.background_executor()
+                .timer(timeout.saturating_sub(waited))
+        });
+
+        // `None` once the deadline passed; dropping the future cancels the load
+        let mut cancelled = token.cancelled();
+        let result = std::future::poll_fn(|task_cx| {
+            if let Poll::Ready(result) = future.as_mut().poll(task_cx) {
+                return Poll::Ready(Some(result));
+            }
+            if Pin::new(&mut cancelled).poll(task_cx).is_ready() {
+                return Poll::Ready(None);
+            }
+            match timer.as_mut().map(|timer| Pin::new(timer).poll(task_cx)) {
+                Some(Poll::Ready(())) => Poll::Ready(None),
+                _ => Poll::Pending,
+            }
+        })
+        .await;
+        let timed_out = result.is_none();
+        let _ = cx.update(|cx| {
+            // Results of cancelled navigations are dropped
+            if token.is_cancelled() || !cx.has_global::<GlobalRouter>() {
+                debug_log!(
+                    "Discarding load for '{}', its navigation was cancelled",
+                    path
+                );
+                return;
+            }
+            let waited = deadline.map_or(Duration::ZERO, |(started, _)| {
+                cx.background_executor()
+                    .now()
+                    .saturating_duration_since(started)
+            });
+            cx.update_global::<GlobalRouter, _>(|router, _| match result {
+                Some(result) => router.finish_load(&path, generation, result, commit),
+                None => router.time_out_load(&path, generation, commit, waited),
+            });
+            if timed_out {
+                crate::error::report_refused_navigations(cx);
+            }
+            crate::context::refresh_windows(cx);
+        });
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{init_router, DefaultPages, Navigator, PushOutcome, Route, RouterOutlet};
+    use gpui::{div, IntoElement, TestAppContext, VisualTestContext};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    const LOAD_TIME: Duration = Duration::from_millis(50);
+
+    /// Records what the builder saw for its data
+    fn record(calls: &Arc<Mutex<Vec<String>>>, params: &RouteParams) {
+        let seen = match params.data::<String>() {
+            Some(DataState::Loading) => "loading".to_string(),
+            Some(DataState::Ready(data)) => format!("ready {}", data),
+            Some(DataState::Error(message)) => format!("error {}", message),
+            None => "no data".to_string(),
+        };
+        calls.lock().unwrap().push(seen);
+    }
+
+    /// Route whose loader resolves to "user <id>" (or fails for id 0) after `LOAD_TIME`
+    fn user_route(calls: &Arc<Mutex<Vec<String>>>, mode: LoaderMode) -> Route {
+        let calls = calls.clone();
+        Route::new("/users/:id", move |_, _, params| {
+            record(&calls, params);
+            div().into_any_element()
+        })
+        .loader(|params, cx| {
+            let timer = cx.background_executor().timer(LOAD_TIME);
+            async move {
+                timer.await;
+                match params.get("id").map(String::as_str) {
+                    Some("0") => Err("no such user".to_string()),
+                    id => Ok(format!("user {}", id.unwrap_or_default())),
+                }
+            }
+        })
+        .loader_mode(mode)
+    }
+
+    fn open_outlet(cx: &mut TestAppContext) -> &mut VisualTestContext {
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx
+    }
+
+    fn navigate(cx: &mut VisualTestContext, path: &str) {
+        cx.update(|window, cx| {
+            Navigator::push(cx, path);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    fn finish_loading(cx: &mut VisualTestContext) {
+        cx.executor().advance_clock(LOAD_TIME);
+        cx.run_until_parked();
+    }
+
+    fn last_call(calls: &Arc<Mutex<Vec<String>>>) -> Option<String> {
+        calls.lock().unwrap().last().cloned()
+    }
+
+    #[gpui::test]
+    fn test_deferred_loader_streams_data(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let route = user_route(&calls, LoaderMode::Deferred);
+        cx.update(|cx| init_router(cx, |router| router.add_route(route)));
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/users/7");
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/users/7"));
+        assert_eq!(last_call(&calls).as_deref(), Some("loading"));
+
+        finish_loading(cx);
+        assert_eq!(last_call(&calls).as_deref(), Some("ready user 7"));
+
+        // Errors are handed to the builder
+        navigate(cx, "/users/0");
+        finish_loading(cx);
+        assert_eq!(last_call(&calls).as_deref(), Some("error no such user"));
+    }
+
+    #[gpui::test]
+    fn test_blocking_loader_delays_commit(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let route = user_route(&calls, LoaderMode::Blocking);
+        cx.update(|cx| init_router(cx, |router| router.add_route(route)));
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/users/7");
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/");
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.pending_navigation(), Some("/users/7"));
+        });
+        assert!(calls.lock().unwrap().is_empty());
+
+        finish_loading(cx);
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/users/7");
+            assert!(cx.global::<GlobalRouter>().pending_navigation().is_none());
+        });
+        // The builder only ever sees ready data
+        assert_eq!(*calls.lock().unwrap(), vec!["ready user 7"]);
+    }
+
+    #[gpui::test]
+    fn test_blocking_loader_error_renders_error_page(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let route = user_route(&calls, LoaderMode::Blocking);
+        let error_page = errors.clone();
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(route);
+                router.set_default_pages(DefaultPages::new().with_error(move |message| {
+                    error_page.lock().unwrap().push(message.to_string());
+                    div().into_any_element()
+                }));
+            });
+        });
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/users/0");
+        finish_loading(cx);
+
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/users/0"));
+        assert_eq!(errors.lock().unwrap().last().unwrap(), "no such user");
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    fn test_navigating_away_cancels_loader(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let route = user_route(&calls, LoaderMode::Deferred);
+        cx.update(|cx| init_router(cx, |router| router.add_route(route)));
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/users/1");
+        navigate(cx, "/users/2");
+        finish_loading(cx);
+
+        cx.update(|_, cx| {
+            let router = cx.global::<GlobalRouter>();
+            // The cancelled load leaves nothing behind
+            assert!(router.route_data("/users/1").is_none());
+            assert!(router.route_data("/users/2").unwrap().is_ready());
+        });
+    }
+
+    #[gpui::test]
+    fn test_navigation_starts_loader_without_outlet(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let route = user_route(&calls, LoaderMode::Blocking);
+        cx.update(|cx| init_router(cx, |router| router.add_route(route)));
+
+        let outcome = cx.update(|cx| Navigator::push(cx, "/users/7"));
+        assert!(matches!(outcome, PushOutcome::Pending { path } if path == "/users/7"));
+
+        // Nothing renders, the navigation started its loader
+        cx.executor().advance_clock(LOAD_TIME);
+        cx.run_until_parked();
+        cx.update(|cx| {
+            assert_eq!(Navigator::current_path(cx), "/users/7");
+            let router = cx.global::<GlobalRouter>();
+            assert!(router.route_data("/users/7").unwrap().is_ready());
+        });
+    }
+
+    #[gpui::test]
+    fn test_data_dropped_with_history_entry(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let route = user_route(&calls, LoaderMode::Deferred);
+        cx.update(|cx| init_router(cx, |router| router.add_route(route)));
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/users/1");
+        finish_loading(cx);
+        navigate(cx, "/users/2");
+        finish_loading(cx);
+        cx.update(|_, cx| Navigator::pop(cx));
+        finish_loading(cx);
+
+        // `/users/2` is still ahead in the history
+        cx.update(|_, cx| {
+            assert!(cx.global::<GlobalRouter>().route_data("/users/2").is_some());
+        });
+
+        // Pushing drops the entries ahead, and their data with them
+        navigate(cx, "/users/3");
+        finish_loading(cx);
+        cx.update(|_, cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert!(router.route_data("/users/1").unwrap().is_ready());
+            assert!(router.route_data("/users/2").is_none());
+            assert!(router.route_data("/users/3").unwrap().is_ready());
+        });
+    }
+
+    #[gpui::test]
+    fn test_superseded_navigation_is_cancelled(cx: &mut TestAppContext) {
+        use crate::history::{History, HistoryEntry, HistoryListener};
+        use crate::NavigationToken;
+
+        struct PushRecorder(Arc<Mutex<Vec<String>>>);
+
+        impl HistoryListener for PushRecorder {
+            fn on_push(&self, entry: &HistoryEntry, _history: &History) {
+                self.0.lock().unwrap().push(entry.path.clone());
+            }
+        }
+
+        let pushes = Arc::new(Mutex::new(Vec::new()));
+        let tokens: Arc<Mutex<Vec<NavigationToken>>> = Arc::default();
+        cx.update(|cx| {
+            let pushes = pushes.clone();
+            let tokens = tokens.clone();
+            init_router(cx, move |router| {
+                router.add_history_listener(Box::new(PushRecorder(pushes)));
+                let seen = tokens.clone();
+                let slow = Route::new("/slow", |_, _, _| div().into_any_element())
+                    .loader(move |params, cx| {
+                        seen.lock()
+                            .unwrap()
+                            .extend(params.navigation_token().cloned());
+                        let timer = cx.background_executor().timer(LOAD_TIME);
+                        async move {
+                            timer.await;
+                            Ok(())
+                        }
+                    })
+                    .loader_mode(LoaderMode::Blocking);
+                #[cfg(feature = "guard")]
+                let slow = slow.guard(crate::guard_fn(move |_, request| {
+                    tokens.lock().unwrap().push(request.token.clone());
+                    Box::pin(std::future::ready(crate::GuardResult::allow())) as _
+                }));
+                router.add_route(slow);
+                router.add_route(Route::new("/fast", |_, _, _| div().into_any_element()));
+            });
+        });
+        let cx = open_outlet(cx);
+
+        cx.update(|window, cx| {
+            Navigator::try_push(cx, "/slow");
+            window.refresh();
+        });
+        cx.run_until_parked();
+        let token = {
+            let tokens = tokens.lock().unwrap();
+            // The guard and the loader share the navigation's token
+            assert!(tokens.iter().all(|token| token.same_navigation(&tokens[0])));
+            assert_eq!(tokens.len(), if cfg!(feature = "guard") { 2 } else { 1 });
+            tokens[0].clone()
+        };
+        assert!(!token.is_cancelled());
+        cx.update(|_, cx| {
+            assert_eq!(
+                cx.global::<GlobalRouter>().pending_navigation(),
+                Some("/slow")
+            );
+        });
+
+        navigate(cx, "/fast");
+        assert!(token.is_cancelled());
+
+        // The slow load finishing afterwards commits nothing
+        finish_loading(cx);
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/fast");
+            assert!(cx.global::<GlobalRouter>().pending_navigation().is_none());
+            assert!(!Navigator::current_navigation_token(cx).is_cancelled());
+        });
+        assert_eq!(*pushes.lock().unwrap(), ["/fast"]);
+    }
+
+    /// [`user_route`] with a skeleton, recording "skeleton" when it is built
+    fn user_route_with_skeleton(calls: &Arc<Mutex<Vec<String>>>, mode: LoaderMode) -> Route {
+        let skeleton_calls = calls.clone();
+        user_route(calls, mode).skeleton(move |_, _| {
+            skeleton_calls.lock().unwrap().push("skeleton".to_string());
+            div()
+        })
+    }
+
+    #[gpui::test]
+    fn test_skeleton_shows_while_loading(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let route = user_route_with_skeleton(&calls, LoaderMode::Deferred);
+        cx.update(|cx| init_router(cx, |router| router.add_route(route)));
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/users/7");
+        // The builder does not run while the data loads
+        assert!(calls.lock().unwrap().iter().all(|call| call == "skeleton"));
+        assert_eq!(last_call(&calls).as_deref(), Some("skeleton"));
+
+        finish_loading(cx);
+        cx.executor().advance_clock(Duration::from_millis(500));
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert_eq!(last_call(&calls).as_deref(), Some("ready user 7"));
+        calls.lock().unwrap().clear();
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert_eq!(*calls.lock().unwrap(), ["ready user 7"]);
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_skeleton_enters_once_and_crossfades(cx: &mut TestAppContext) {
+        use crate::Transition;
+
+        // What was built, with its transition progress
+        let progress: Arc<Mutex<Vec<(&str, crate::TransitionProgress)>>> = Arc::default();
+        let page = progress.clone();
+        let skeleton = progress.clone();
+        let route = Route::new("/users/:id", move |_, _, params| {
+            if params.data::<String>().is_some_and(|data| data.is_ready()) {
+                page.lock()
+                    .unwrap()
+                    .push(("page", params.transition_progress()));
+            }
+            div()
+        })
+        .loader(|_, cx| {
+            let timer = cx.background_executor().timer(LOAD_TIME);
+            async move {
+                timer.await;
+                Ok("user".to_string())
+            }
+        })
+        .skeleton(move |_, params| {
+            skeleton
+                .lock()
+                .unwrap()
+                .push(("skeleton", params.transition_progress()));
+            div()
+        })
+        .transition(Transition::fade(200));
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(route);
+            });
+        });
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/users/7");
+        finish_loading(cx);
+        // The crossfade builds both
+        let built: Vec<&str> = progress
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        assert_eq!(built.first(), Some(&"skeleton"));
+        assert!(built.ends_with(&["page", "skeleton"]), "{built:?}");
+
+        for _ in 0..10 {
+            cx.executor().advance_clock(Duration::from_millis(50));
+            cx.update(|window, _| window.refresh());
+            cx.run_until_parked();
+        }
+        let progress = progress.lock().unwrap();
+        assert_eq!(progress.last().unwrap().0, "page");
+        assert!(progress.last().unwrap().1.is_idle());
+        // A single enter transition, running on from the skeleton to the page
+        let values: Vec<f32> = progress
+            .iter()
+            .map(|(_, progress)| progress.value)
+            .collect();
+        assert!(
+            values.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{values:?}"
+        );
+        assert!(values[0] < 0.5);
+    }
+
+    /// Router with a blocking loader that never finishes on `/hang` and
+    /// `/hang-fast` (which allows 50ms), recording refused navigations
+    fn hanging_router(
+        cx: &mut TestAppContext,
+        fallback: crate::TimeoutFallback,
+    ) -> Arc<Mutex<Vec<crate::NavigationError>>> {
+        use crate::{ErrorHandlers, RouterConfig};
+
+        let refused = Arc::new(Mutex::new(Vec::new()));
+        let hanging = |path: &str| {
+            Route::new(path, |_, _, _| div().into_any_element())
+                .loader(|_, _| std::future::pending::<Result<(), String>>())
+                .loader_mode(LoaderMode::Blocking)
+        };
+        cx.update(|cx| {
+            let refused = refused.clone();
+            init_router(cx, move |router| {
+                router.set_config(
+                    RouterConfig::new()
+                        .navigation_timeout(Duration::from_millis(100))
+                        .on_navigation_timeout(fallback),
+                );
+                router.set_error_handlers(ErrorHandlers::new().on_refused(move |_, error| {
+                    refused.lock().unwrap().push(error.clone());
+                }));
+                router.add_route(Route::new("/about", |_, _, _| div().into_any_element()));
+                router.add_route(hanging("/hang"));
+                router.add_route(hanging("/hang-fast").navigation_timeout(LOAD_TIME));
+            });
+        });
+        refused
+    }
+
+    #[gpui::test]
+    fn test_blocked_navigation_times_out(cx: &mut TestAppContext) {
+        let refused = hanging_router(cx, crate::TimeoutFallback::KeepCurrent);
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/hang");
+        cx.executor().advance_clock(Duration::from_millis(99));
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.pending_navigation(), Some("/hang"));
+        });
+        assert!(refused.lock().unwrap().is_empty());
+
+        cx.executor().advance_clock(Duration::from_millis(1));
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert!(router.pending_navigation().is_none());
+            assert!(router.route_data("/hang").is_none());
+            // No route change: still on the page before, with one entry
+            assert_eq!(router.current_path(), "/");
+            assert_eq!(router.state().history().len(), 1);
+        });
+        assert!(matches!(
+            refused.lock().unwrap().as_slice(),
+            [crate::NavigationError::Timeout { path, elapsed }]
+                if path == "/hang" && *elapsed == Duration::from_millis(100)
+        ));
+
+        // The route's stricter deadline wins
+        navigate(cx, "/hang-fast");
+        finish_loading(cx);
+        cx.update(|_, cx| assert!(cx.global::<GlobalRouter>().pending_navigation().is_none()));
+        assert_eq!(refused.lock().unwrap().len(), 2);
+
+        // Later navigations go through as usual
+        navigate(cx, "/about");
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/about"));
+    }
+
+    #[gpui::test]
+    fn test_timed_out_navigation_renders_error_page(cx: &mut TestAppContext) {
+        let refused = hanging_router(cx, crate::TimeoutFallback::ErrorPage);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let errors = errors.clone();
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.set_default_pages(DefaultPages::new().with_error(move |message| {
+                    errors.lock().unwrap().push(message.to_string());
+                    div().into_any_element()
+                }));
+            });
+        });
+        let cx = open_outlet(cx);
+
+        navigate(cx, "/hang-fast");
+        finish_loading(cx);
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/hang-fast"));
+        assert_eq!(refused.lock().unwrap().len(), 1);
+        assert_eq!(
+            errors.lock().unwrap().last().unwrap(),
+            "Navigation to /hang-fast timed out after 50ms"
+        );
+    }
+
+    #[test]
+    fn test_data_store_generations() {
+        let mut store = RouteDataStore::new();
+        let first = store.begin("/users/1");
+        let second = store.begin("/users/1");
+        assert!(store.get("/users/1").unwrap().is_loading());
+
+        // The superseded load is discarded
+        assert!(!store.finish("/users/1", first, Ok(Arc::new(1_u32))));
+        assert!(store.get("/users/1").unwrap().is_loading());
+
+        assert!(store.finish("/users/1", second, Ok(Arc::new(2_u32))));
+        let state = store.get("/users/1").unwrap().downcast::<u32>();
+        assert_eq!(state.ready().map(|n| **n), Some(2));
+    }
+
+    #[test]
+    fn test_downcast_wrong_type() {
+        let state: DataState<LoaderData> = DataState::Ready(Arc::new("text"));
+        assert!(state.downcast::<u32>().error().is_some());
+        assert!(DataState::<LoaderData>::Loading
+            .downcast::<u32>()
+            .is_loading());
+    }
+}
@@ -0,0 +1,242 @@
+//! Localized route paths
+//!
+//! With [`RouterConfig::locales`](crate::RouterConfig::locales) set, paths
+//! may start with a locale segment, and routes may have a path per locale
+//! ([`Route::localized_path`](crate::Route::localized_path)):
+//!
+//! ```ignore
+//! use gpui_navigator::{init_router, Navigator, Route, RouterConfig};
+//!
+//! init_router(cx, |router| {
+//!     router.set_config(RouterConfig::new().locales(["en", "de"]).default_locale("en"));
+//!     router.add_route(Route::new("/settings", settings_page).localized_path("de", "/einstellungen"));
+//! });
+//!
+//! // All of these show the settings page
+//! Navigator::push(cx, "/settings");
+//! Navigator::push(cx, "/en/settings");
+//! Navigator::push(cx, "/de/einstellungen");
+//! ```
+//!
+//! The router keeps the canonical path (`/settings`) in its history and the
+//! locale separately: a path without a locale segment keeps the current
+//! locale, which matches add as the `locale` param.
+//! [`Navigator::localized_path`](crate::Navigator::localized_path) gives the
+//! current location as written in its locale.
+
+use crate::matcher::CompiledPattern;
+use crate::nested::build_child_path;
+use crate::route::{match_path, match_route_chain, RouteRef};
+use crate::{RouteMatch, RouteParams};
+
+/// Split a leading segment naming one of `locales` off `path`
+///
+/// Returns the locale and the rest of the path, which keeps its query
+/// string. Paths without a locale segment are returned as they are.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::locale::split_locale;
+///
+/// let locales = ["en".to_string(), "de".to_string()];
+/// assert_eq!(split_locale("/de/einstellungen?tab=2", &locales), (Some("de"), "/einstellungen?tab=2".to_string()));
+/// assert_eq!(split_locale("/de", &locales), (Some("de"), "/".to_string()));
+/// assert_eq!(split_locale("/design", &locales), (None, "/design".to_string()));
+/// ```
+pub fn split_locale<'a>(path: &'a str, locales: &[String]) -> (Option<&'a str>, String) {
+    let trimmed = path.strip_prefix('/').unwrap_or(path);
+    let end = trimmed.find(['/', '?']).unwrap_or(trimmed.len());
+    let (segment, rest) = trimmed.split_at(end);
+
+    if !locales.iter().any(|locale| locale == segment) {
+        return (None, path.to_string());
+    }
+    let rest = if rest.starts_with('/') {
+        rest.to_string()
+    } else {
+        format!("/{}", rest)
+    };
+    (Some(segment), rest)
+}
+
+/// Canonical form of `path`, which may use the localized paths of `locale`
+///
+/// Paths matching a canonical pattern, and paths matching no route, are
+/// returned as they are.
+pub fn canonical_path(routes: &[RouteRef], path: &str, locale: &str) -> String {
+    if match_route_chain(routes, path).is_some() {
+        return path.to_string();
+    }
+    let (route_path, query) = split_query(path);
+    match find_chain(routes, route_path, "", 0, &|route| {
+        route.path_for_locale(locale)
+    }) {
+        Some((chain, route_match)) => {
+            let pattern = chain_pattern(&chain, |route| route.config.path.as_str());
+            with_query(fill_pattern(&pattern, &route_match), query)
+        }
+        None => path.to_string(),
+    }
+}
+
+/// `path` in its canonical form written in `locale`, with the locale segment
+///
+/// Routes without a localized path for `locale` keep their canonical one.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::locale::localize_path;
+/// use gpui_navigator::Route;
+/// use gpui::IntoElement;
+/// use std::sync::Arc;
+///
+/// let route = Route::new("/users/:id", |_, _, _| gpui::div().into_any_element())
+///     .localized_path("de", "/benutzer/:id");
+/// let routes = [Arc::new(route)];
+/// assert_eq!(localize_path(&routes, "/users/7?tab=2", "de"), "/de/benutzer/7?tab=2");
+/// assert_eq!(localize_path(&routes, "/users/7", "en"), "/en/users/7");
+/// ```
+pub fn localize_path(routes: &[RouteRef], path: &str, locale: &str) -> String {
+    let (route_path, query) = split_query(path);
+    let localized = match_route_chain(routes, route_path).map_or_else(
+        || route_path.to_string(),
+        |(chain, route_match)| {
+            let pattern = chain_pattern(&chain, |route| route.path_for_locale(locale));
+            fill_pattern(&pattern, &route_match)
+        },
+    );
+
+    let localized = localized.trim_start_matches('/');
+    let prefixed = if localized.is_empty() {
+        format!("/{}", locale)
+    } else {
+        format!("/{}/{}", locale, localized)
+    };
+    with_query(prefixed, query)
+}
+
+/// Find the chain of routes whose full path, built from `path_of`, matches `path`
+///
+/// Like `match_route_chain`, with a choice of pattern per route. `routes`
+/// are `depth` levels below the top.
+fn find_chain<'a>(
+    routes: &'a [RouteRef],
+    path: &str,
+    parent_path: &str,
+    depth: usize,
+    path_of: &dyn Fn(&RouteRef) -> &str,
+) -> Option<(Vec<&'a RouteRef>, RouteMatch)> {
+    if routes.is_empty() || crate::route::lookup_too_deep(depth, parent_path) {
+        return None;
+    }
+    for route in routes {
+        let full_path = if parent_path.is_empty() {
+            path_of(route).to_string()
+        } else {
+            build_child_path(parent_path, path_of(route)).into_owned()
+        };
+
+        if !route.pathless {
+            if let Some(route_match) = match_path(&full_path, path) {
+                return Some((vec![route], route_match));
+            }
+        }
+
+        if let Some((mut chain, route_match)) =
+            find_chain(route.get_children(), path, &full_path, depth + 1, path_of)
+        {
+            chain.insert(0, route);
+            return Some((chain, route_match));
+        }
+    }
+    None
+}
+
+/// Full path pattern of a chain of routes, built from `path_of`
+fn chain_pattern(chain: &[&RouteRef], path_of: impl Fn(&RouteRef) -> &str) -> String {
+    chain.iter().fold(String::new(), |parent, route| {
+        if parent.is_empty() {
+            path_of(route).to_string()
+        } else {
+            build_child_path(&parent, path_of(route)).into_owned()
+        }
+    })
+}
+
+/// Fill the params of `route_match` into `pattern`
+///
+/// Falls back to the matched path if the pattern needs params the match
+/// lacks, e.g. a localized path naming its params differently.
+fn fill_pattern(pattern: &str, route_match: &RouteMatch) -> String {
+    CompiledPattern::parse(pattern)
+        .expand_path(&RouteParams::from_map(route_match.params.clone()))
+        .unwrap_or_else(|_| route_match.path.clone())
+}
+
+fn split_query(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path, None),
+    }
+}
+
+fn with_query(path: String, query: Option<&str>) -> String {
+    match query {
+        Some(query) => format!("{}?{}", path, query),
+        None => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::page;
+    use std::sync::Arc;
+
+    fn routes() -> Vec<RouteRef> {
+        vec![
+            Arc::new(page("/").localized_path("de", "/")),
+            Arc::new(
+                page("/settings")
+                    .localized_path("de", "/einstellungen")
+                    .children(vec![Arc::new(
+                        page("users/:id").localized_path("de", "benutzer/:id"),
+                    )]),
+            ),
+            Arc::new(page("/files/*")),
+        ]
+    }
+
+    #[test]
+    fn test_canonical_path() {
+        let routes = routes();
+        assert_eq!(canonical_path(&routes, "/einstellungen", "de"), "/settings");
+        assert_eq!(
+            canonical_path(&routes, "/einstellungen/benutzer/7?tab=2", "de"),
+            "/settings/users/7?tab=2"
+        );
+        // Canonical and unknown paths stay as they are
+        assert_eq!(canonical_path(&routes, "/settings", "de"), "/settings");
+        assert_eq!(
+            canonical_path(&routes, "/einstellungen", "en"),
+            "/einstellungen"
+        );
+    }
+
+    #[test]
+    fn test_localize_path() {
+        let routes = routes();
+        assert_eq!(localize_path(&routes, "/", "de"), "/de");
+        assert_eq!(
+            localize_path(&routes, "/settings/users/7", "de"),
+            "/de/einstellungen/benutzer/7"
+        );
+        assert_eq!(
+            localize_path(&routes, "/files/a/b.txt", "de"),
+            "/de/files/a/b.txt"
+        );
+        assert_eq!(localize_path(&routes, "/unknown", "de"), "/de/unknown");
+    }
+}
@@ -7,7 +7,8 @@
 //! - `log` (default) - Uses the standard `log` crate
 //! - `tracing` - Uses the `tracing` crate for structured logging
 //!
-//! Choose one feature at compile time. They are mutually exclusive.
+//! Choose one feature at compile time. They are mutually exclusive. Without
+//! either, the macros only type-check their arguments.
 //!
 //! # Usage
 //!
@@ -29,6 +30,10 @@ macro_rules! trace_log {
         ::tracing::trace!($($arg)*);
         #[cfg(feature = "log")]
         ::log::trace!($($arg)*);
+        #[cfg(not(any(feature = "tracing", feature = "log")))]
+        {
+            let _ = ::std::format_args!($($arg)*);
+        }
     };
 }
 
@@ -42,6 +47,10 @@ macro_rules! debug_log {
         ::tracing::debug!($($arg)*);
         #[cfg(feature = "log")]
         ::log::debug!($($arg)*);
+        #[cfg(not(any(feature = "tracing", feature = "log")))]
+        {
+            let _ = ::std::format_args!($($arg)*);
+        }
     };
 }
 
@@ -55,6 +64,10 @@ macro_rules! info_log {
         ::tracing::info!($($arg)*);
         #[cfg(feature = "log")]
         ::log::info!($($arg)*);
+        #[cfg(not(any(feature = "tracing", feature = "log")))]
+        {
+            let _ = ::std::format_args!($($arg)*);
+        }
     };
 }
 
@@ -68,6 +81,10 @@ macro_rules! warn_log {
         ::tracing::warn!($($arg)*);
         #[cfg(feature = "log")]
         ::log::warn!($($arg)*);
+        #[cfg(not(any(feature = "tracing", feature = "log")))]
+        {
+            let _ = ::std::format_args!($($arg)*);
+        }
     };
 }
 
@@ -81,5 +98,9 @@ macro_rules! error_log {
         ::tracing::error!($($arg)*);
         #[cfg(feature = "log")]
         ::log::error!($($arg)*);
+        #[cfg(not(any(feature = "tracing", feature = "log")))]
+        {
+            let _ = ::std::format_args!($($arg)*);
+        }
     };
 }
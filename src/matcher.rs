@@ -8,8 +8,28 @@
 //! - Optional segments support
 //! - Constraint validation
 //! - Better performance with early exit
+//!
+//! [`CompiledPattern`] is the matcher the router itself uses, for matching
+//! and building paths outside of it.
+//!
+//! # Encoding
+//!
+//! Matching compares the raw, still-encoded segments of a path, so `%2F`
+//! stays inside the segment it was written in. Param values are
+//! percent-decoded only once extracted: `/files/:name` matched against
+//! `/files/reports%2Fq3.pdf` gives `name = "reports/q3.pdf"`, and
+//! constraints check the decoded value. [`CompiledPattern::expand`] does the
+//! reverse and encodes the values it fills in (see [`encode_path_segment`]),
+//! so a built path matches back to the same params. What a wildcard matched
+//! keeps its `/` literally, each segment decoded on its own.
 
+use crate::nested::path_segments;
+use crate::params::{encode_uri_component, RouteParams};
+use crate::route::{parse_path, validate_route_path, PathPattern, PathSegment};
+use crate::RouteMatch;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
 
 /// Route path representation
 #[derive(Debug, Clone, PartialEq)]
@@ -84,15 +104,30 @@ impl RoutePattern {
 
     /// Match this pattern against a path
     ///
-    /// Returns extracted parameters if matched
+    /// Returns extracted parameters if matched. Empty segments (e.g. from a
+    /// trailing slash) and the query string are ignored. What a trailing `*`
+    /// matched is stored under `*`.
     pub fn matches(&self, path: &str) -> Option<HashMap<String, String>> {
-        let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        self.match_segments(&path_segments(path), false)
+    }
 
-        self.match_segments(&path_segments)
+    /// Match this pattern against the leading segments of a path
+    ///
+    /// Like [`RoutePattern::matches`], but the path may continue past the
+    /// pattern: `/settings` matches `/settings/profile`. Segments are compared
+    /// whole, so `/settings` does not match `/settings-old`.
+    pub fn matches_prefix(&self, path: &str) -> Option<HashMap<String, String>> {
+        self.match_segments(&path_segments(path), true)
     }
 
     /// Match segments against path segments
-    fn match_segments(&self, path_segments: &[&str]) -> Option<HashMap<String, String>> {
+    ///
+    /// With `prefix`, path segments left over after the pattern are allowed.
+    fn match_segments(
+        &self,
+        path_segments: &[&str],
+        prefix: bool,
+    ) -> Option<HashMap<String, String>> {
         let mut params = HashMap::new();
         let mut path_idx = 0;
         let mut pattern_idx = 0;
@@ -114,32 +149,32 @@ impl RoutePattern {
                         return None;
                     }
 
-                    let value = path_segments[path_idx];
+                    let value = decode_path_segment(path_segments[path_idx]);
 
                     // Validate constraint if present
                     if let Some(constraint) = constraint {
-                        if !constraint.validate(value) {
+                        if !constraint.validate(&value) {
                             return None;
                         }
                     }
 
-                    params.insert(name.clone(), value.to_string());
+                    params.insert(name.clone(), value);
                     path_idx += 1;
                 }
                 Segment::Optional(inner) => {
                     // Try to match, but don't fail if it doesn't
                     if path_idx < path_segments.len() {
                         if let Segment::Param { name, constraint } = &**inner {
-                            let value = path_segments[path_idx];
+                            let value = decode_path_segment(path_segments[path_idx]);
 
                             let is_valid = if let Some(constraint) = constraint {
-                                constraint.validate(value)
+                                constraint.validate(&value)
                             } else {
                                 true
                             };
 
                             if is_valid {
-                                params.insert(name.clone(), value.to_string());
+                                params.insert(name.clone(), value);
                                 path_idx += 1;
                             }
                         }
@@ -147,7 +182,8 @@ impl RoutePattern {
                 }
                 Segment::Wildcard => {
                     // Wildcard matches rest of path - always succeeds
-                    return Some(params);
+                    let rest = &path_segments[path_idx..];
+                    return match_remainder(rest, true, prefix, &mut params).then_some(params);
                 }
             }
 
@@ -155,12 +191,123 @@ impl RoutePattern {
         }
 
         // All segments matched - check that we consumed all path segments
-        if path_idx == path_segments.len() {
-            Some(params)
+        match_remainder(&path_segments[path_idx..], false, prefix, &mut params).then_some(params)
+    }
+}
+
+/// Param holding the part of the path matched by a trailing wildcard
+pub(crate) const WILDCARD_PARAM: &str = "*";
+
+/// Match the path segments left once a pattern's other segments matched
+///
+/// A trailing wildcard matches any remainder, including none (`/files/*`
+/// matches `/files`), and stores it under [`WILDCARD_PARAM`] (empty when
+/// nothing was left), each segment decoded and joined with a literal `/`. Without a wildcard,
+/// leftover segments only match with `prefix`. Shared by this module and
+/// the matcher of `route.rs`, so both treat wildcards alike.
+pub(crate) fn match_remainder(
+    rest: &[&str],
+    wildcard: bool,
+    prefix: bool,
+    params: &mut HashMap<String, String>,
+) -> bool {
+    if wildcard {
+        let rest: Vec<String> = rest
+            .iter()
+            .map(|segment| decode_path_segment(segment))
+            .collect();
+        params.insert(WILDCARD_PARAM.to_string(), rest.join("/"));
+        true
+    } else {
+        prefix || rest.is_empty()
+    }
+}
+
+/// Percent-encode a value for use as one path segment
+///
+/// Unreserved characters and the sub-delimiters allowed in a segment
+/// (`!$&'()*+,;=:@`) are kept, everything else is encoded as UTF-8 bytes,
+/// including `/`, `?`, `#`, `%` and spaces. The inverse of
+/// [`decode_path_segment`].
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::matcher::encode_path_segment;
+///
+/// assert_eq!(encode_path_segment("reports/q3 final.pdf"), "reports%2Fq3%20final.pdf");
+/// ```
+pub fn encode_path_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+            | b':'
+            | b'@' => encoded.push(char::from(byte)),
+            _ => {
+                const HEX: &[u8; 16] = b"0123456789ABCDEF";
+                encoded.push('%');
+                encoded.push(char::from(HEX[usize::from(byte >> 4)]));
+                encoded.push(char::from(HEX[usize::from(byte & 0x0F)]));
+            }
+        }
+    }
+    encoded
+}
+
+/// Percent-decode one path segment
+///
+/// `%XX` escapes are decoded as UTF-8 bytes; malformed escapes are kept as
+/// written, and a segment not decoding to valid UTF-8 is returned as is.
+/// Unlike query strings, `+` stays a `+`.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::matcher::decode_path_segment;
+///
+/// assert_eq!(decode_path_segment("reports%2Fq3%20final.pdf"), "reports/q3 final.pdf");
+/// assert_eq!(decode_path_segment("100%"), "100%");
+/// ```
+pub fn decode_path_segment(segment: &str) -> String {
+    if !segment.contains('%') {
+        return segment.to_string();
+    }
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escape = (bytes[index] == b'%')
+            .then(|| bytes.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escape {
+            decoded.push(byte);
+            index += 3;
         } else {
-            None
+            decoded.push(bytes[index]);
+            index += 1;
         }
     }
+    String::from_utf8(decoded).unwrap_or_else(|_| segment.to_string())
 }
 
 /// A single segment in a route pattern
@@ -220,22 +367,65 @@ impl Segment {
 }
 
 /// Constraint for validating parameter values
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Constraint {
     /// Regex pattern (simple implementation for now)
     Pattern(String),
-    /// Numeric constraint
+    /// Numeric constraint (`int` or `\\d+`)
     Numeric,
     /// UUID constraint
     Uuid,
+    /// Constraint registered with [`register_constraint`], by name
+    Custom(String),
+}
+
+/// Validator of a custom [`Constraint`]
+type ConstraintFn = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Constraints registered with [`register_constraint`]
+fn custom_constraints() -> &'static RwLock<HashMap<String, ConstraintFn>> {
+    static CONSTRAINTS: OnceLock<RwLock<HashMap<String, ConstraintFn>>> = OnceLock::new();
+    CONSTRAINTS.get_or_init(RwLock::default)
+}
+
+/// Register a constraint params can use as `:name{constraint}`
+///
+/// Names are made of ASCII letters, digits and `_`, starting with a letter;
+/// `int` and `uuid` are built in. Registering a name again replaces its
+/// validator. Constraints apply process-wide, to matching as well as to
+/// building URLs. Until a name is registered, its constraint accepts any
+/// value.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::matcher::{register_constraint, CompiledPattern};
+///
+/// register_constraint("slug", |value| {
+///     value.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+/// });
+///
+/// let pattern = CompiledPattern::compile("/posts/:slug{slug}").unwrap();
+/// assert!(pattern.matches("/posts/hello-world").is_some());
+/// assert!(pattern.matches("/posts/Hello_World").is_none());
+/// ```
+pub fn register_constraint(
+    name: impl Into<String>,
+    validate: impl Fn(&str) -> bool + Send + Sync + 'static,
+) {
+    custom_constraints()
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(name.into(), Arc::new(validate));
 }
 
 impl Constraint {
     /// Parse constraint from string
     fn parse(s: &str) -> Self {
         match s {
-            "\\d+" => Constraint::Numeric,
+            "\\d+" | "int" => Constraint::Numeric,
             "uuid" => Constraint::Uuid,
+            _ if is_constraint_name(s) => Constraint::Custom(s.to_string()),
             _ => Constraint::Pattern(s.to_string()),
         }
     }
@@ -244,6 +434,14 @@ impl Constraint {
     pub fn validate(&self, value: &str) -> bool {
         match self {
             Constraint::Numeric => value.chars().all(|c| c.is_ascii_digit()),
+            Constraint::Custom(name) => {
+                let validate = custom_constraints()
+                    .read()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .get(name)
+                    .cloned();
+                validate.map_or(true, |validate| validate(value))
+            }
             Constraint::Uuid => {
                 // Simple UUID validation: 8-4-4-4-12 hex chars
                 let parts: Vec<&str> = value.split('-').collect();
@@ -269,6 +467,473 @@ impl Constraint {
     }
 }
 
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::Pattern(pattern) => f.write_str(pattern),
+            Constraint::Numeric => f.write_str("int"),
+            Constraint::Uuid => f.write_str("uuid"),
+            Constraint::Custom(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Whether a constraint is a name rather than a pattern
+fn is_constraint_name(constraint: &str) -> bool {
+    constraint.starts_with(|c: char| c.is_ascii_alphabetic())
+        && constraint
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// ============================================================================
+// CompiledPattern
+// ============================================================================
+
+/// Compile a route path pattern, see [`CompiledPattern::compile`]
+pub fn compile(pattern: &str) -> Result<CompiledPattern, RoutePathError> {
+    CompiledPattern::compile(pattern)
+}
+
+/// A route path pattern, parsed once to match paths and build them
+///
+/// Follows the rules routes are matched by: `:name` matches one segment,
+/// `:name{constraint}` one the [`Constraint`] accepts, and a trailing `*`
+/// the rest of the path, stored under `*`. [`CompiledPattern::expand`] is
+/// the inverse, filling params into the pattern.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::matcher::CompiledPattern;
+/// use gpui_navigator::RouteParams;
+///
+/// let pattern = CompiledPattern::compile("/users/:id{\\d+}/files/*").unwrap();
+/// let route_match = pattern.matches("/users/7/files/a/b.txt").unwrap();
+/// assert_eq!(route_match.params.get("id"), Some(&"7".to_string()));
+/// assert_eq!(route_match.params.get("*"), Some(&"a/b.txt".to_string()));
+/// assert!(pattern.matches("/users/me/files").is_none());
+///
+/// let params = RouteParams::from_map(route_match.params);
+/// assert_eq!(pattern.expand(&params).unwrap(), "/users/7/files/a/b.txt");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledPattern {
+    source: String,
+    segments: Vec<CompiledSegment>,
+    params: Vec<ParamSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CompiledSegment {
+    Static(String),
+    /// Index into `CompiledPattern::params`
+    Param(usize),
+    Wildcard,
+}
+
+/// A parameter of a [`CompiledPattern`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    /// Name the value is stored under
+    pub name: String,
+    /// Constraint the value must satisfy, from `:name{constraint}`
+    pub constraint: Option<Constraint>,
+}
+
+impl ParamSpec {
+    /// Check if the decoded `value` can fill this parameter
+    ///
+    /// Values must be non-empty and satisfy the constraint. Reserved
+    /// characters such as `/` are fine, they are encoded when the value is
+    /// filled into a path.
+    pub fn accepts(&self, value: &str) -> bool {
+        !value.is_empty()
+            && self
+                .constraint
+                .as_ref()
+                .map_or(true, |constraint| constraint.validate(value))
+    }
+}
+
+impl CompiledPattern {
+    /// Compile a route path pattern
+    ///
+    /// Fails for the patterns [`validate_route_path`] rejects, and for
+    /// constraints not closing their segment (`:id{uuid`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::matcher::{CompiledPattern, Constraint};
+    ///
+    /// let pattern = CompiledPattern::compile("/posts/:id{uuid}").unwrap();
+    /// assert_eq!(pattern.params()[0].name, "id");
+    /// assert_eq!(pattern.params()[0].constraint, Some(Constraint::Uuid));
+    ///
+    /// let error = CompiledPattern::compile("/posts//:id").unwrap_err();
+    /// assert_eq!(error.path, "/posts//:id");
+    /// ```
+    pub fn compile(pattern: &str) -> Result<Self, RoutePathError> {
+        let error = |message: String| RoutePathError {
+            path: pattern.to_string(),
+            message,
+        };
+        validate_route_path(pattern).map_err(error)?;
+
+        for segment in parse_path(pattern) {
+            if let PathSegment::Param(param) = segment {
+                if param.contains('{') && !param.ends_with('}') {
+                    return Err(error(format!(
+                        "Constraint of route parameter '{}' must close its segment",
+                        param
+                    )));
+                }
+            }
+        }
+        Ok(Self::parse(pattern))
+    }
+
+    /// Parse a pattern without validating it
+    ///
+    /// Routes whose paths were changed after validation are matched this way.
+    pub(crate) fn parse(pattern: &str) -> Self {
+        Self::from_segments(pattern, &parse_path(pattern))
+    }
+
+    fn from_segments(source: &str, segments: &[PathSegment<'_>]) -> Self {
+        let mut params = Vec::new();
+        let segments = segments
+            .iter()
+            .map(|segment| match *segment {
+                PathSegment::Static(text) => CompiledSegment::Static(text.to_string()),
+                PathSegment::Wildcard => CompiledSegment::Wildcard,
+                PathSegment::Param(param) => {
+                    let (name, constraint) = match param
+                        .strip_suffix('}')
+                        .and_then(|param| param.split_once('{'))
+                    {
+                        Some((name, constraint)) => (name, Some(Constraint::parse(constraint))),
+                        None => (param, None),
+                    };
+                    params.push(ParamSpec {
+                        name: name.to_string(),
+                        constraint,
+                    });
+                    CompiledSegment::Param(params.len() - 1)
+                }
+            })
+            .collect();
+
+        Self {
+            source: source.to_string(),
+            segments,
+            params,
+        }
+    }
+
+    /// Get the pattern this was compiled from
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Get the named parameters, in order
+    ///
+    /// The wildcard is not among them, see [`CompiledPattern::has_wildcard`].
+    pub fn params(&self) -> &[ParamSpec] {
+        &self.params
+    }
+
+    /// Check if the pattern ends with a wildcard
+    pub fn has_wildcard(&self) -> bool {
+        self.segments.last() == Some(&CompiledSegment::Wildcard)
+    }
+
+    /// Match an actual path against this pattern
+    ///
+    /// Empty segments (e.g. from a trailing slash) and the query string are
+    /// ignored. What the wildcard matched is stored under `*`, see
+    /// [`match_remainder`].
+    pub fn matches(&self, path: &str) -> Option<RouteMatch> {
+        self.match_path(path, false)
+    }
+
+    /// Match the leading segments of a path against this pattern
+    ///
+    /// Like [`CompiledPattern::matches`], but the path may continue past the
+    /// pattern: `/settings` matches `/settings/profile`. Segments are compared
+    /// whole, so `/settings` does not match `/settings-old`.
+    pub fn matches_prefix(&self, path: &str) -> Option<RouteMatch> {
+        self.match_path(path, true)
+    }
+
+    /// Match a path, allowing segments past the pattern with `prefix`
+    fn match_path(&self, path: &str, prefix: bool) -> Option<RouteMatch> {
+        let path_segments = path_segments(path);
+        let mut rest = path_segments.as_slice();
+        let mut route_match = RouteMatch::new(path.to_string());
+
+        for segment in &self.segments {
+            match segment {
+                CompiledSegment::Wildcard => {
+                    return match_remainder(rest, true, false, &mut route_match.params)
+                        .then_some(route_match);
+                }
+                CompiledSegment::Param(index) => {
+                    let (value, tail) = rest.split_first()?;
+                    let param = &self.params[*index];
+                    let value = decode_path_segment(value);
+                    if !param.accepts(&value) {
+                        return None;
+                    }
+                    route_match.params.insert(param.name.clone(), value);
+                    rest = tail;
+                }
+                CompiledSegment::Static(expected) => {
+                    let (value, tail) = rest.split_first()?;
+                    if value != expected {
+                        return None;
+                    }
+                    rest = tail;
+                }
+            }
+        }
+
+        match_remainder(rest, false, prefix, &mut route_match.params).then_some(route_match)
+    }
+
+    /// Build a path by filling `params` into this pattern
+    ///
+    /// Param values are percent-encoded, so `/` or `?` in a value stays in its
+    /// segment; the wildcard takes the `*` param with its `/` kept, or nothing
+    /// without one. Params the pattern has no place for are appended as an
+    /// encoded query string, sorted by name (see
+    /// [`CompiledPattern::expand_strict`] to refuse them). A leading `/` is
+    /// kept and a trailing one dropped, so the root pattern gives `/`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::matcher::{CompiledPattern, UrlForError};
+    /// use gpui_navigator::RouteParams;
+    ///
+    /// let pattern = CompiledPattern::compile("/posts/:post/comments/:id{\\d+}").unwrap();
+    /// let mut params = RouteParams::single("post", "hello");
+    /// assert_eq!(pattern.expand(&params), Err(UrlForError::MissingParams(vec!["id".to_string()])));
+    ///
+    /// params.set("id".to_string(), "42".to_string());
+    /// assert_eq!(pattern.expand(&params).unwrap(), "/posts/hello/comments/42");
+    ///
+    /// params.set("sort".to_string(), "new first".to_string());
+    /// assert_eq!(pattern.expand(&params).unwrap(), "/posts/hello/comments/42?sort=new%20first");
+    /// ```
+    pub fn expand(&self, params: &RouteParams) -> Result<String, UrlForError> {
+        let path = self.expand_path(params)?;
+        let query: Vec<String> = self
+            .extra_params(params)
+            .into_iter()
+            .map(|(name, value)| {
+                format!(
+                    "{}={}",
+                    encode_uri_component(name),
+                    encode_uri_component(value)
+                )
+            })
+            .collect();
+        Ok(if query.is_empty() {
+            path
+        } else {
+            format!("{}?{}", path, query.join("&"))
+        })
+    }
+
+    /// Build the path of [`CompiledPattern::expand`] without a query, leaving
+    /// out the params the pattern has no place for
+    pub(crate) fn expand_path(&self, params: &RouteParams) -> Result<String, UrlForError> {
+        let missing: Vec<String> = self
+            .params
+            .iter()
+            .filter(|param| !params.contains(&param.name))
+            .map(|param| param.name.clone())
+            .collect();
+        if !missing.is_empty() {
+            return Err(UrlForError::MissingParams(missing));
+        }
+
+        let mut segments = Vec::with_capacity(self.segments.len());
+        for segment in &self.segments {
+            match segment {
+                CompiledSegment::Static(text) => segments.push(text.clone()),
+                CompiledSegment::Param(index) => {
+                    let param = &self.params[*index];
+                    let value = params.get(&param.name).map_or("", String::as_str);
+                    if !param.accepts(value) {
+                        return Err(match &param.constraint {
+                            Some(constraint) if !constraint.validate(value) => {
+                                UrlForError::ConstraintViolation {
+                                    param: param.name.clone(),
+                                    constraint: constraint.clone(),
+                                    value: value.to_string(),
+                                }
+                            }
+                            _ => UrlForError::InvalidParam {
+                                name: param.name.clone(),
+                                value: value.to_string(),
+                            },
+                        });
+                    }
+                    segments.push(encode_path_segment(value));
+                }
+                CompiledSegment::Wildcard => {
+                    let rest = params.get(WILDCARD_PARAM).map_or("", String::as_str);
+                    let rest = rest.trim_matches('/');
+                    if !rest.is_empty() {
+                        let rest: Vec<String> = rest.split('/').map(encode_path_segment).collect();
+                        segments.push(rest.join("/"));
+                    }
+                }
+            }
+        }
+
+        let path = segments.join("/");
+        Ok(if self.source.starts_with('/') {
+            format!("/{}", path)
+        } else {
+            path
+        })
+    }
+
+    /// Build a path like [`CompiledPattern::expand`], rejecting params the
+    /// pattern has no place for
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::matcher::{CompiledPattern, UrlForError};
+    /// use gpui_navigator::RouteParams;
+    ///
+    /// let pattern = CompiledPattern::compile("/users/:id").unwrap();
+    /// let mut params = RouteParams::single("id", "7");
+    /// params.set("tab".to_string(), "posts".to_string());
+    /// assert_eq!(pattern.expand_strict(&params), Err(UrlForError::ExtraParams(vec!["tab".to_string()])));
+    /// ```
+    pub fn expand_strict(&self, params: &RouteParams) -> Result<String, UrlForError> {
+        let extra: Vec<String> = self
+            .extra_params(params)
+            .into_iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        if !extra.is_empty() {
+            return Err(UrlForError::ExtraParams(extra));
+        }
+        self.expand_path(params)
+    }
+
+    /// Params of `params` the pattern has no place for, sorted by name
+    fn extra_params<'a>(&self, params: &'a RouteParams) -> Vec<(&'a String, &'a String)> {
+        let mut extra: Vec<_> = params
+            .iter()
+            .filter(|(name, _)| {
+                !self.params.iter().any(|param| &param.name == *name)
+                    && !(self.has_wildcard() && name.as_str() == WILDCARD_PARAM)
+            })
+            .collect();
+        extra.sort();
+        extra
+    }
+}
+
+impl From<PathPattern> for CompiledPattern {
+    /// Use the segments parsed at compile time, without parsing the path again
+    fn from(pattern: PathPattern) -> Self {
+        Self::from_segments(pattern.as_str(), pattern.segments())
+    }
+}
+
+impl fmt::Display for CompiledPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// A route path pattern that failed to compile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePathError {
+    /// The pattern
+    pub path: String,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for RoutePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid route path '{}': {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for RoutePathError {}
+
+/// Why params could not be filled into a [`CompiledPattern`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlForError {
+    /// No route is registered under this name
+    UnknownRoute(String),
+    /// Params the pattern needs are missing, in pattern order
+    MissingParams(Vec<String>),
+    /// An empty value, see [`ParamSpec::accepts`]
+    InvalidParam {
+        /// Param name
+        name: String,
+        /// The rejected value
+        value: String,
+    },
+    /// A value the param's constraint rejects
+    ConstraintViolation {
+        /// Param name
+        param: String,
+        /// The constraint
+        constraint: Constraint,
+        /// The rejected value
+        value: String,
+    },
+    /// Params the pattern has no place for, sorted (strict mode only)
+    ExtraParams(Vec<String>),
+}
+
+impl fmt::Display for UrlForError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlForError::UnknownRoute(name) => write!(f, "Unknown route name '{}'", name),
+            UrlForError::MissingParams(names) => {
+                write!(f, "Missing route parameter(s): {}", names.join(", "))
+            }
+            UrlForError::InvalidParam { name, value } => {
+                write!(
+                    f,
+                    "Invalid value '{}' for route parameter '{}'",
+                    value, name
+                )
+            }
+            UrlForError::ConstraintViolation {
+                param,
+                constraint,
+                value,
+            } => {
+                write!(
+                    f,
+                    "Value '{}' of route parameter '{}' does not satisfy constraint '{}'",
+                    value, param, constraint
+                )
+            }
+            UrlForError::ExtraParams(names) => {
+                write!(f, "Unexpected route parameter(s): {}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for UrlForError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,8 +1007,60 @@ mod tests {
         let pattern = RoutePattern::from_path("/files/*");
 
         assert!(pattern.matches("/files/docs").is_some());
-        assert!(pattern.matches("/files/docs/report.pdf").is_some());
+        let params = pattern.matches("/files/docs/report.pdf?v=2").unwrap();
+        assert_eq!(params.get("*"), Some(&"docs/report.pdf".to_string()));
         assert!(pattern.matches("/other").is_none());
+
+        // The wildcard may match nothing
+        let params = pattern.matches("/files").unwrap();
+        assert_eq!(params.get("*"), Some(&String::new()));
+    }
+
+    #[test]
+    fn test_match_remainder() {
+        let mut params = HashMap::new();
+        assert!(match_remainder(&["a", "b"], true, false, &mut params));
+        assert_eq!(params.get(WILDCARD_PARAM), Some(&"a/b".to_string()));
+        assert!(match_remainder(&[], true, false, &mut params));
+        assert_eq!(params.get(WILDCARD_PARAM), Some(&String::new()));
+
+        let mut params = HashMap::new();
+        assert!(match_remainder(&[], false, false, &mut params));
+        assert!(!match_remainder(&["a"], false, false, &mut params));
+        assert!(match_remainder(&["a"], false, true, &mut params));
+        // Only a wildcard captures anything
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_prefix_matching() {
+        let pattern = RoutePattern::from_path("/settings");
+        assert!(pattern.matches_prefix("/settings").is_some());
+        assert!(pattern.matches_prefix("/settings/profile").is_some());
+        assert!(pattern.matches_prefix("/settings-old").is_none());
+        assert!(pattern.matches_prefix("/").is_none());
+
+        let pattern = RoutePattern::from_path("/inbox/:threadId<\\d+>");
+        let params = pattern.matches_prefix("/inbox/42/reply").unwrap();
+        assert_eq!(params.get("threadId"), Some(&"42".to_string()));
+        assert!(pattern.matches_prefix("/inbox").is_none());
+        assert!(pattern.matches_prefix("/inbox/draft").is_none());
+
+        // The root pattern is a prefix of every path
+        assert!(RoutePattern::from_path("/")
+            .matches_prefix("/anything")
+            .is_some());
+    }
+
+    #[test]
+    fn test_matching_normalizes_path() {
+        let pattern = RoutePattern::from_path("/users/:id/");
+        let params = pattern.matches("/users/123/?tab=posts").unwrap();
+        assert_eq!(params.get("id"), Some(&"123".to_string()));
+        assert!(pattern.matches("//users//123").is_some());
+        assert!(RoutePattern::from_path("/files/*")
+            .matches_prefix("/files/")
+            .is_some());
     }
 
     #[test]
@@ -384,4 +1101,287 @@ mod tests {
         assert_eq!(params.get("userId"), Some(&"42".to_string()));
         assert_eq!(params.get("postId"), Some(&"7".to_string()));
     }
+
+    #[test]
+    fn test_compile_errors() {
+        let error = compile("/users//:id").unwrap_err();
+        assert_eq!(error.path, "/users//:id");
+        assert_eq!(
+            error.to_string(),
+            "Invalid route path '/users//:id': Route path cannot contain consecutive slashes"
+        );
+        assert!(compile("/files/*/meta").is_err());
+        assert!(compile("/users/:id{uuid").is_err());
+        assert!(compile("/users/:id{uuid}x").is_err());
+        assert!(compile("").is_ok());
+    }
+
+    #[test]
+    fn test_compiled_params() {
+        let pattern = compile("/users/:id{\\d+}/posts/:slug/*").unwrap();
+        let names: Vec<&str> = pattern.params().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["id", "slug"]);
+        assert_eq!(pattern.params()[0].constraint, Some(Constraint::Numeric));
+        assert_eq!(pattern.params()[1].constraint, None);
+        assert!(pattern.has_wildcard());
+        assert!(!compile("/users").unwrap().has_wildcard());
+        assert_eq!(pattern.as_str(), "/users/:id{\\d+}/posts/:slug/*");
+    }
+
+    #[test]
+    fn test_compiled_matching() {
+        let pattern = compile("/users/:id{uuid}").unwrap();
+        let route_match = pattern
+            .matches("/users/550e8400-e29b-41d4-a716-446655440000/?tab=1")
+            .unwrap();
+        assert_eq!(
+            route_match.params.get("id"),
+            Some(&"550e8400-e29b-41d4-a716-446655440000".to_string())
+        );
+        assert!(pattern.matches("/users/7").is_none());
+        assert!(pattern.matches("/users").is_none());
+
+        let root = compile("/").unwrap();
+        assert!(root.matches("/").is_some());
+        assert!(root.matches("/users").is_none());
+
+        let files = compile("/files/*").unwrap();
+        assert_eq!(
+            files.matches("/files").unwrap().params.get(WILDCARD_PARAM),
+            Some(&String::new())
+        );
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut params = RouteParams::new();
+        assert_eq!(compile("/").unwrap().expand(&params).unwrap(), "/");
+        assert_eq!(compile("").unwrap().expand(&params).unwrap(), "");
+        assert_eq!(
+            compile("/files/*").unwrap().expand(&params).unwrap(),
+            "/files"
+        );
+
+        let pattern = compile("/users/:id{\\d+}/").unwrap();
+        assert_eq!(
+            pattern.expand(&params),
+            Err(UrlForError::MissingParams(vec!["id".to_string()]))
+        );
+        params.set("id".to_string(), "me".to_string());
+        assert_eq!(
+            pattern.expand(&params),
+            Err(UrlForError::ConstraintViolation {
+                param: "id".to_string(),
+                constraint: Constraint::Numeric,
+                value: "me".to_string()
+            })
+        );
+        params.set("id".to_string(), "7".to_string());
+        assert_eq!(pattern.expand(&params).unwrap(), "/users/7");
+
+        // Values are encoded to stay one segment, only empty ones are invalid
+        params.set("id".to_string(), "7/8".to_string());
+        assert_eq!(
+            compile("/users/:id").unwrap().expand(&params).unwrap(),
+            "/users/7%2F8"
+        );
+        params.set("id".to_string(), String::new());
+        assert_eq!(
+            compile("/users/:id").unwrap().expand(&params),
+            Err(UrlForError::InvalidParam {
+                name: "id".to_string(),
+                value: String::new()
+            })
+        );
+
+        // Relative patterns stay relative
+        params.all_mut().remove("id");
+        params.set("*".to_string(), "a/b".to_string());
+        assert_eq!(
+            compile("items/*").unwrap().expand(&params).unwrap(),
+            "items/a/b"
+        );
+    }
+
+    #[test]
+    fn test_compiled_matches_prefix() {
+        let pattern = compile("/users/:id{int}").unwrap();
+        let route_match = pattern.matches_prefix("/users/7/posts?tab=x").unwrap();
+        assert_eq!(route_match.params.get("id"), Some(&"7".to_string()));
+        assert!(pattern.matches("/users/7/posts").is_none());
+        assert!(pattern.matches_prefix("/users/abc").is_none());
+        assert!(pattern.matches_prefix("/users-old/7").is_none());
+    }
+
+    #[test]
+    fn test_expand_extra_params_into_query() {
+        let pattern = compile("/users/:id").unwrap();
+        let params = RouteParams::from([("id", "1"), ("tab", "x")]);
+        assert_eq!(pattern.expand(&params).unwrap(), "/users/1?tab=x");
+
+        // Sorted by name and encoded; the wildcard param has a place
+        let params = RouteParams::from([("q", "a&b"), ("*", "docs/intro"), ("page", "2")]);
+        assert_eq!(
+            compile("/files/*").unwrap().expand(&params).unwrap(),
+            "/files/docs/intro?page=2&q=a%26b"
+        );
+        assert_eq!(
+            pattern.expand_strict(&RouteParams::from([("id", "1"), ("tab", "x")])),
+            Err(UrlForError::ExtraParams(vec!["tab".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_path_segment_encoding() {
+        for value in [
+            "reports/q3.pdf",
+            "what?",
+            "#1",
+            "100%",
+            "two words",
+            "naïve",
+            "a+b",
+        ] {
+            let encoded = encode_path_segment(value);
+            assert!(!encoded.contains(['/', '?', '#', ' ']), "{}", encoded);
+            assert_eq!(decode_path_segment(&encoded), value);
+        }
+        assert_eq!(encode_path_segment("a b/c?d#e%f"), "a%20b%2Fc%3Fd%23e%25f");
+        assert_eq!(decode_path_segment("a+b"), "a+b");
+
+        // Malformed escapes and invalid UTF-8 are kept as written
+        assert_eq!(decode_path_segment("%zz%4"), "%zz%4");
+        assert_eq!(decode_path_segment("%FF"), "%FF");
+    }
+
+    #[test]
+    fn test_encoded_params_match_within_one_segment() {
+        let pattern = compile("/files/:name/raw").unwrap();
+        let route_match = pattern.matches("/files/reports%2Fq3.pdf/raw").unwrap();
+        assert_eq!(route_match.params.get("name").unwrap(), "reports/q3.pdf");
+        assert!(pattern.matches("/files/reports/q3.pdf/raw").is_none());
+
+        // Constraints see the decoded value
+        let numeric = compile("/items/:id{\\d+}").unwrap();
+        assert!(numeric.matches("/items/%34%32").is_some());
+        assert!(numeric.matches("/items/4%2F2").is_none());
+
+        // Wildcards keep their slashes, each segment decoded
+        let files = compile("/files/*").unwrap();
+        let route_match = files.matches("/files/a%2Fb/c%20d").unwrap();
+        assert_eq!(route_match.params.get(WILDCARD_PARAM).unwrap(), "a/b/c d");
+
+        let legacy = RoutePattern::from_path("/files/:name");
+        assert_eq!(
+            legacy
+                .matches("/files/q3%3F%23%25.pdf")
+                .unwrap()
+                .get("name")
+                .unwrap(),
+            "q3?#%.pdf"
+        );
+    }
+
+    #[test]
+    fn test_reserved_characters_round_trip() {
+        let pattern = compile("/files/:name/*").unwrap();
+        for value in [
+            "reports/q3.pdf",
+            "why?",
+            "#hash",
+            "50%",
+            "with space",
+            "a/b?c#d%e f",
+        ] {
+            let mut params = RouteParams::single("name", value);
+            params.set("*".to_string(), format!("{}/tail", value.replace('/', "")));
+            let path = pattern.expand(&params).unwrap();
+            let route_match = pattern
+                .matches(&path)
+                .unwrap_or_else(|| panic!("'{}' does not match back", path));
+            assert_eq!(&route_match.params, params.all(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_compiled_from_path_pattern() {
+        static SEGMENTS: [PathSegment<'static>; 2] =
+            [PathSegment::Static("users"), PathSegment::Param("id{\\d+}")];
+        let pattern =
+            CompiledPattern::from(PathPattern::from_parsed("/users/:id{\\d+}", &SEGMENTS));
+        assert_eq!(pattern, compile("/users/:id{\\d+}").unwrap());
+    }
+
+    /// Patterns and params from a seeded xorshift generator
+    struct Generator(u64);
+
+    impl Generator {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            usize::try_from(self.0 % bound as u64).unwrap_or_default()
+        }
+
+        fn word(&mut self) -> String {
+            const CHARS: &[u8] = b"abcxyz019-_.~";
+            (0..=self.next(6))
+                .map(|_| CHARS[self.next(CHARS.len())] as char)
+                .collect()
+        }
+
+        /// A param value, with characters that need encoding
+        fn value(&mut self) -> String {
+            const CHARS: &[u8] = b"abc019-_.~/?#% +";
+            (0..=self.next(6))
+                .map(|_| CHARS[self.next(CHARS.len())] as char)
+                .collect()
+        }
+
+        fn number(&mut self) -> String {
+            self.next(100_000).to_string()
+        }
+    }
+
+    #[test]
+    fn test_compile_expand_match_round_trip() {
+        let mut generator = Generator(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let mut segments = Vec::new();
+            let mut params = RouteParams::new();
+            for index in 0..generator.next(6) {
+                let name = format!("p{}", index);
+                match generator.next(3) {
+                    0 => segments.push(generator.word()),
+                    1 => {
+                        segments.push(format!(":{}", name));
+                        params.set(name, generator.value());
+                    }
+                    _ => {
+                        segments.push(format!(":{}{{\\d+}}", name));
+                        params.set(name, generator.number());
+                    }
+                }
+            }
+            if generator.next(2) == 0 {
+                segments.push("*".to_string());
+                let rest: Vec<String> = (0..generator.next(4)).map(|_| generator.word()).collect();
+                params.set("*".to_string(), rest.join("/"));
+            }
+            let pattern = format!("/{}", segments.join("/"));
+
+            let compiled = compile(&pattern).unwrap();
+            let path = compiled.expand(&params).unwrap();
+            let route_match = compiled
+                .matches(&path)
+                .unwrap_or_else(|| panic!("'{}' does not match '{}'", pattern, path));
+            assert_eq!(&route_match.params, params.all(), "{} -> {}", pattern, path);
+            assert_eq!(
+                compiled
+                    .expand(&RouteParams::from_map(route_match.params))
+                    .unwrap(),
+                path
+            );
+        }
+    }
 }
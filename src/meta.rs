@@ -0,0 +1,244 @@
+//! Typed route meta
+//!
+//! String meta ([`Route::meta`](crate::Route::meta)) is a single namespace
+//! shared by the router, the app and route groups from other crates, so two
+//! of them picking the same key overwrite each other's values. Typed meta is
+//! keyed by the type of its value instead: only code that can name the type
+//! reads or writes it.
+//!
+//! ```ignore
+//! use gpui_navigator::{MetaKey, Navigator, Route};
+//!
+//! #[derive(Debug, Clone)]
+//! struct RequiresAuth(bool);
+//!
+//! impl MetaKey for RequiresAuth {
+//!     const NAME: &'static str = "requiresAuth";
+//! }
+//!
+//! router.add_route(
+//!     Route::new("/admin", admin)
+//!         .meta_typed(RequiresAuth(true))
+//!         .children(vec![Route::new("users", users).into()]),
+//! );
+//!
+//! // On /admin/users, inherited from /admin
+//! let required = Navigator::current_meta_typed::<RequiresAuth>(cx).is_some_and(|auth| auth.0);
+//! ```
+//!
+//! Like string meta, a child route's value overrides its parent's. Typed and
+//! string meta are kept apart: `RequiresAuth` above does not see a
+//! `.meta("requiresAuth", ...)` string value, nor the other way around. The
+//! string API stays for meta that is loaded from configuration or serialized.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A value that can be stored as typed meta, keyed by its type
+///
+/// Implemented for `bool`, `i64` and `String`, and for every [`MetaKey`].
+/// As those are keyed by their type, prefer a [`MetaKey`] newtype for
+/// anything other code could also store.
+pub trait MetaValue: Any + Clone + fmt::Debug + Send + Sync {
+    /// Name of the value in debug output
+    fn meta_name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+impl MetaValue for bool {}
+impl MetaValue for i64 {}
+impl MetaValue for String {}
+
+/// A type of its own for a typed meta value
+///
+/// The type is the key, so two crates can both use a `requiresAuth` key
+/// without meeting. `NAME` only labels the value in debug output.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::MetaKey;
+///
+/// #[derive(Debug, Clone)]
+/// struct RequiresAuth(bool);
+///
+/// impl MetaKey for RequiresAuth {
+///     const NAME: &'static str = "requiresAuth";
+/// }
+/// ```
+pub trait MetaKey: Any + Clone + fmt::Debug + Send + Sync {
+    /// Name of the key in debug output
+    const NAME: &'static str;
+}
+
+impl<K: MetaKey> MetaValue for K {
+    fn meta_name() -> &'static str {
+        K::NAME
+    }
+}
+
+/// Title of a route
+///
+/// Labels the route in history menus and command palettes, like the string
+/// meta `title` (which is still read when this is not set).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteTitle(pub String);
+
+impl RouteTitle {
+    /// Create a title
+    pub fn new(title: impl Into<String>) -> Self {
+        Self(title.into())
+    }
+}
+
+impl MetaKey for RouteTitle {
+    const NAME: &'static str = "title";
+}
+
+/// Stored value, cloned along with the route config
+trait AnyMeta: Any + fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any + fmt::Debug + Send + Sync> AnyMeta for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Typed meta of a route, one value per type
+///
+/// Set with [`Route::meta_typed`](crate::Route::meta_typed) and read with
+/// [`Route::get_meta`](crate::Route::get_meta).
+#[derive(Clone, Default)]
+pub struct TypedMeta {
+    values: HashMap<TypeId, (&'static str, Arc<dyn AnyMeta>)>,
+}
+
+impl TypedMeta {
+    /// Create empty typed meta
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `value`, replacing the value of the same type
+    pub fn insert<T: MetaValue>(&mut self, value: T) {
+        self.values
+            .insert(TypeId::of::<T>(), (T::meta_name(), Arc::new(value)));
+    }
+
+    /// Value of type `T`, if stored
+    pub fn get<T: MetaValue>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|(_, value)| AnyMeta::as_any(&**value).downcast_ref())
+    }
+
+    /// Check if a value of type `T` is stored
+    pub fn contains<T: MetaValue>(&self) -> bool {
+        self.contains_type(TypeId::of::<T>())
+    }
+
+    /// Check if a value of the type `type_id` is stored
+    pub(crate) fn contains_type(&self, type_id: TypeId) -> bool {
+        self.values.contains_key(&type_id)
+    }
+
+    /// Number of stored values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if no value is stored
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Store the values of `other`, replacing those of the same types
+    #[cfg(feature = "guard")]
+    pub(crate) fn extend(&mut self, other: &Self) {
+        self.values
+            .extend(other.values.iter().map(|(id, entry)| (*id, entry.clone())));
+    }
+}
+
+impl fmt::Debug for TypedMeta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.values.values().map(|(name, value)| (name, value)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::page;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct RequiresAuth(bool);
+
+    impl MetaKey for RequiresAuth {
+        const NAME: &'static str = "requiresAuth";
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Icon(String);
+
+    impl MetaKey for Icon {
+        const NAME: &'static str = "icon";
+    }
+
+    #[test]
+    fn test_typed_meta_set_and_get() {
+        let route = page("/admin")
+            .meta_typed(RequiresAuth(true))
+            .meta_typed(7_i64)
+            .meta_typed(Icon("shield".into()))
+            .meta_typed(Icon("lock".into()));
+
+        assert_eq!(route.get_meta::<RequiresAuth>(), Some(&RequiresAuth(true)));
+        assert_eq!(route.get_meta::<i64>(), Some(&7));
+        // The last value of a type wins
+        assert_eq!(route.get_meta::<Icon>(), Some(&Icon("lock".into())));
+        assert_eq!(route.get_meta::<bool>(), None);
+        assert_eq!(route.config.typed_meta.len(), 3);
+        assert_eq!(
+            format!(
+                "{:?}",
+                page("/").meta_typed(RequiresAuth(false)).config.typed_meta
+            ),
+            r#"{"requiresAuth": RequiresAuth(false)}"#
+        );
+    }
+
+    #[test]
+    fn test_typed_and_string_meta_do_not_interfere() {
+        let route = page("/admin")
+            .meta("requiresAuth", "false")
+            .meta("icon", "plugin-icon")
+            .meta_typed(RequiresAuth(true))
+            .meta_typed(Icon("ours".into()));
+
+        assert_eq!(
+            route.config.meta.get("requiresAuth").map(String::as_str),
+            Some("false")
+        );
+        assert_eq!(
+            route.config.meta.get("icon").map(String::as_str),
+            Some("plugin-icon")
+        );
+        assert_eq!(route.get_meta::<RequiresAuth>(), Some(&RequiresAuth(true)));
+        assert_eq!(route.get_meta::<Icon>(), Some(&Icon("ours".into())));
+        // A plain String value is keyed by `String`, not by any string key
+        assert_eq!(route.get_meta::<String>(), None);
+        assert!(!page("/")
+            .meta("title", "Home")
+            .config
+            .typed_meta
+            .contains::<RouteTitle>());
+    }
+}
@@ -7,7 +7,7 @@
 //! # Example
 //!
 //! ```no_run
-//! use gpui_navigator::{RouteMiddleware, NavigationRequest};
+//! use gpui_navigator::{AfterNavigation, RouteMiddleware, NavigationRequest};
 //! use std::future::Future;
 //! use std::pin::Pin;
 //!
@@ -21,15 +21,26 @@
 //!         Box::pin(async {})
 //!     }
 //!
-//!     fn after_navigation(&self, cx: &gpui::App, request: &NavigationRequest) -> Self::Future {
-//!         println!("Navigated to: {}", request.to);
+//!     fn after_navigation(&self, cx: &gpui::App, navigation: &AfterNavigation) -> Self::Future {
+//!         println!("Navigated to: {} ({:?})", navigation.event.to, navigation.outcome);
 //!         Box::pin(async {})
 //!     }
 //! }
 //! ```
-use crate::NavigationRequest;
-use gpui::App;
+use crate::diagnostics;
+use crate::route::{match_route_chain, RouteRef};
+use crate::{
+    build_child_path, info_log, warn_log, GlobalRouter, NavigationDirection, NavigationRequest,
+    NavigationSource, QueryParams, RouteChangeEvent, RouteParams,
+};
+use futures::future::{select, Either};
+use gpui::{App, BorrowAppContext};
+use std::collections::hash_map::RandomState;
 use std::future::Future;
+use std::hash::BuildHasher;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Middleware that processes navigation requests.
 ///
@@ -46,7 +57,7 @@ use std::future::Future;
 /// # Example
 ///
 /// ```ignore
-/// use gpui_navigator::{RouteMiddleware, NavigationRequest};
+/// use gpui_navigator::{AfterNavigation, RouteMiddleware, NavigationRequest};
 /// use std::future::Future;
 /// use std::pin::Pin;
 ///
@@ -63,7 +74,7 @@ use std::future::Future;
 ///         })
 ///     }
 ///
-///     fn after_navigation(&self, cx: &gpui::App, request: &NavigationRequest) -> Self::Future {
+///     fn after_navigation(&self, cx: &gpui::App, navigation: &AfterNavigation) -> Self::Future {
 ///         Box::pin(async {})
 ///     }
 /// }
@@ -90,23 +101,27 @@ pub trait RouteMiddleware: Send + Sync + 'static {
     /// ```
     fn before_navigation(&self, cx: &App, request: &NavigationRequest) -> Self::Future;
 
-    /// Called after navigation completes successfully
+    /// Called after a navigation is committed
+    ///
+    /// `navigation` tells which route matched, with its params and query,
+    /// whether the path was not found or reached through a redirect, and
+    /// how long the navigation took.
     ///
     /// Use this to:
-    /// - Log successful navigation
+    /// - Log navigation
     /// - Track analytics
     /// - Clean up resources
-    /// - Calculate metrics
+    /// - Prime caches for the route
     ///
     /// # Example
     ///
     /// ```no_run,ignore
-    /// fn after_navigation(&self, cx: &App, request: &NavigationRequest) -> Self::Future {
-    ///     println!("Successfully navigated to: {}", request.to);
+    /// fn after_navigation(&self, cx: &App, navigation: &AfterNavigation) -> Self::Future {
+    ///     println!("Navigated to: {} in {:?}", navigation.event.to, navigation.pipeline_duration);
     ///     Box::pin(async {})
     /// }
     /// ```
-    fn after_navigation(&self, cx: &App, request: &NavigationRequest) -> Self::Future;
+    fn after_navigation(&self, cx: &App, navigation: &AfterNavigation) -> Self::Future;
 
     /// Middleware name for debugging
     fn name(&self) -> &str {
@@ -134,29 +149,31 @@ pub trait RouteMiddleware: Send + Sync + 'static {
 ///         println!("Before: {}", request.to);
 ///         Box::pin(async {})
 ///     },
-///     |_cx, request| {
-///         println!("After: {}", request.to);
+///     |_cx, navigation| {
+///         println!("After: {}", navigation.event.to);
 ///         Box::pin(async {})
 ///     },
 /// );
 /// ```
-pub fn middleware_fn<F, Fut>(before: F, after: F) -> FnMiddleware<F>
+pub fn middleware_fn<B, A, Fut>(before: B, after: A) -> FnMiddleware<B, A>
 where
-    F: Fn(&App, &NavigationRequest) -> Fut + Send + Sync + 'static,
+    B: Fn(&App, &NavigationRequest) -> Fut + Send + Sync + 'static,
+    A: Fn(&App, &AfterNavigation) -> Fut + Send + Sync + 'static,
     Fut: Future<Output = ()> + Send + 'static,
 {
     FnMiddleware { before, after }
 }
 
 /// Middleware created from functions
-pub struct FnMiddleware<F> {
-    before: F,
-    after: F,
+pub struct FnMiddleware<B, A> {
+    before: B,
+    after: A,
 }
 
-impl<F, Fut> RouteMiddleware for FnMiddleware<F>
+impl<B, A, Fut> RouteMiddleware for FnMiddleware<B, A>
 where
-    F: Fn(&App, &NavigationRequest) -> Fut + Send + Sync + 'static,
+    B: Fn(&App, &NavigationRequest) -> Fut + Send + Sync + 'static,
+    A: Fn(&App, &AfterNavigation) -> Fut + Send + Sync + 'static,
     Fut: Future<Output = ()> + Send + 'static,
 {
     type Future = Fut;
@@ -165,8 +182,88 @@ where
         (self.before)(cx, request)
     }
 
-    fn after_navigation(&self, cx: &App, request: &NavigationRequest) -> Self::Future {
-        (self.after)(cx, request)
+    fn after_navigation(&self, cx: &App, navigation: &AfterNavigation) -> Self::Future {
+        (self.after)(cx, navigation)
+    }
+}
+
+/// How a committed navigation turned out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NavigationOutcome {
+    /// A route matched the path
+    Matched,
+    /// No route matched the path; outlets show their not-found page
+    NotFound,
+    /// A guard or a replace redirected the navigation here
+    Redirected {
+        /// Path the navigation was headed to before the redirect
+        from: String,
+    },
+}
+
+impl NavigationOutcome {
+    /// Short name of the outcome, for logs
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Matched => "matched",
+            Self::NotFound => "not_found",
+            Self::Redirected { .. } => "redirected",
+        }
+    }
+}
+
+/// A committed navigation, as passed to [`RouteMiddleware::after_navigation`]
+#[derive(Debug, Clone)]
+pub struct AfterNavigation {
+    /// The route change, as returned by the navigation
+    pub event: RouteChangeEvent,
+    /// Name of the innermost matched route, if it has one
+    pub route_name: Option<String>,
+    /// Route parameters extracted from the path
+    pub params: RouteParams,
+    /// Query parameters of the path
+    pub query: QueryParams,
+    /// Whether a route matched, none did, or the navigation was redirected
+    pub outcome: NavigationOutcome,
+    /// Time from the navigation call to the commit, including blocking
+    /// loaders and throttling
+    pub pipeline_duration: Duration,
+}
+
+impl AfterNavigation {
+    /// Describe `event` over `routes`, with `params` of the match
+    ///
+    /// The outcome is `Matched` or `NotFound` and the duration zero.
+    pub(crate) fn new(event: RouteChangeEvent, routes: &[RouteRef], params: RouteParams) -> Self {
+        let route_name = match_route_chain(routes, &event.to)
+            .map(|(chain, _)| chain.last().and_then(|route| route.config.name.clone()));
+        let query = event
+            .to
+            .split_once('?')
+            .map(|(_, query)| QueryParams::from_query_string(query))
+            .unwrap_or_default();
+        Self {
+            outcome: if route_name.is_some() {
+                NavigationOutcome::Matched
+            } else {
+                NavigationOutcome::NotFound
+            },
+            route_name: route_name.flatten(),
+            params,
+            query,
+            event,
+            pipeline_duration: Duration::ZERO,
+        }
+    }
+
+    /// The navigation as a [`NavigationRequest`], like `before_navigation` gets
+    pub fn request(&self) -> NavigationRequest {
+        let mut request = NavigationRequest::new(self.event.to.clone())
+            .with_params(self.params.clone())
+            .with_direction(self.event.direction);
+        request.from.clone_from(&self.event.from);
+        request.previous_dwell = self.event.previous_dwell;
+        request.with_source(self.event.source.clone())
     }
 }
 
@@ -174,12 +271,363 @@ where
 pub type BoxedMiddleware =
     Box<dyn RouteMiddleware<Future = std::pin::Pin<Box<dyn Future<Output = ()> + Send>>>>;
 
+/// Middleware of a navigation to a path: the global kind selecting it and
+/// that of the routes it matches
+pub(crate) struct NavigationMiddleware {
+    global: Vec<Arc<BoxedMiddleware>>,
+    routes: Vec<RouteRef>,
+}
+
+impl NavigationMiddleware {
+    /// Collect the middleware of a navigation to `path`
+    pub(crate) fn new(router: &GlobalRouter, path: &str) -> Self {
+        let chain = match_route_chain(router.state().routes(), path)
+            .map(|(chain, _)| chain)
+            .unwrap_or_default();
+        Self {
+            global: router.middleware_for(path, &chain),
+            routes: chain.into_iter().cloned().collect(),
+        }
+    }
+
+    /// Whether there is no middleware to run
+    pub(crate) fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.routes.iter().all(|route| route.middleware.is_empty())
+    }
+
+    /// All of the middleware, by priority
+    pub(crate) fn by_priority(&self) -> Vec<&BoxedMiddleware> {
+        let mut middleware: Vec<_> = self
+            .global
+            .iter()
+            .map(|m| &**m)
+            .chain(self.routes.iter().flat_map(|route| &route.middleware))
+            .collect();
+        middleware.sort_by_key(|m| -m.priority());
+        middleware
+    }
+}
+
+/// Run a middleware future on the background executor, dropping it at
+/// `timeout`
+pub(crate) fn spawn_middleware(
+    cx: &App,
+    future: Pin<Box<dyn Future<Output = ()> + Send>>,
+    phase: &'static str,
+    path: &str,
+    timeout: Option<Duration>,
+) {
+    let Some(timeout) = timeout else {
+        cx.background_executor().spawn(future).detach();
+        return;
+    };
+    let timer = cx.background_executor().timer(timeout);
+    let path = path.to_string();
+    cx.background_executor()
+        .spawn(async move {
+            if let Either::Right(_) = select(future, timer).await {
+                warn_log!(
+                    "Middleware {} '{}' did not finish in {:?}",
+                    phase,
+                    path,
+                    timeout
+                );
+            }
+        })
+        .detach();
+}
+
+/// Run the middleware of navigations made without an `App`
+///
+/// Runs `before_navigation` for the navigations started, then
+/// `after_navigation` for the ones committed. Both the global middleware
+/// (see [`GlobalRouter::add_middleware`] and
+/// [`GlobalRouter::add_middleware_filtered`]) and that of the matched
+/// routes run. The router calls this after each navigation, whether an
+/// outlet is on screen or not. The returned futures
+/// run on the background executor, and are dropped if still running at the
+/// [navigation timeout](crate::RouterConfig::navigation_timeout).
+pub fn run_navigation_middleware(cx: &mut App) {
+    let (navigating, navigated) = match cx.try_global::<GlobalRouter>() {
+        Some(router) if !router.navigating.is_empty() || !router.navigated.is_empty() => {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                let navigating = std::mem::take(&mut router.navigating);
+                let navigated = std::mem::take(&mut router.navigated);
+                let navigating = navigating
+                    .into_iter()
+                    .map(|request| {
+                        let middleware = NavigationMiddleware::new(router, &request.to);
+                        let timeout = router.navigation_timeout(&request.to);
+                        (request, middleware, timeout)
+                    })
+                    .collect::<Vec<_>>();
+                let navigated = navigated
+                    .into_iter()
+                    .map(|(_, navigation)| {
+                        let middleware = NavigationMiddleware::new(router, &navigation.event.to);
+                        let timeout = router.navigation_timeout(&navigation.event.to);
+                        (navigation, middleware, timeout)
+                    })
+                    .collect::<Vec<_>>();
+                (navigating, navigated)
+            })
+        }
+        _ => return,
+    };
+
+    for (request, middleware, timeout) in navigating {
+        let _span = diagnostics::middleware(&request.to, "before");
+        for m in middleware.by_priority() {
+            let future = m.before_navigation(cx, &request);
+            spawn_middleware(cx, future, "before", &request.to, timeout);
+        }
+    }
+    for (navigation, middleware, timeout) in navigated {
+        let _span = diagnostics::middleware(&navigation.event.to, "after");
+        for m in middleware.by_priority() {
+            let future = m.after_navigation(cx, &navigation);
+            spawn_middleware(cx, future, "after", &navigation.event.to, timeout);
+        }
+    }
+}
+
+// ============================================================================
+// AnalyticsMiddleware
+// ============================================================================
+
+/// A screen view reported by [`AnalyticsMiddleware`]
+#[derive(Debug, Clone)]
+pub struct ScreenView {
+    /// Name of the matched route, or its full pattern if it has none
+    ///
+    /// Paths no route matches are reported as is, without the query.
+    pub name: String,
+    /// Path navigated to, with its query
+    pub path: String,
+    /// Route parameters extracted from the path
+    pub params: RouteParams,
+    /// How the navigation moved through history
+    pub direction: NavigationDirection,
+    /// Path the navigation came from, if any
+    pub referrer: Option<String>,
+    /// Position among the views reported by this middleware, starting at 1
+    pub sequence: u64,
+    /// Time spent on the referrer, the screen navigated away from
+    pub previous_dwell: Option<Duration>,
+    /// What started the navigation, e.g. a link or a deep link
+    pub source: NavigationSource,
+}
+
+/// Built-in middleware reporting screen views, e.g. to an analytics service
+///
+/// Register it with [`GlobalRouter::add_middleware`] so every route is
+/// tracked. Views are reported after navigation with the final destination
+/// only, so redirects are not counted. Navigating to the path that was just
+/// viewed (e.g. clicking the link of the current page) is not reported again.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui_navigator::{init_router, AnalyticsMiddleware};
+///
+/// # fn setup(cx: &mut gpui::App) {
+/// init_router(cx, |router| {
+///     router.add_middleware(
+///         AnalyticsMiddleware::new(|view| {
+///             println!("#{} {} ({})", view.sequence, view.name, view.path);
+///         })
+///         .sample_rate(0.25),
+///     );
+/// });
+/// # }
+/// ```
+pub struct AnalyticsMiddleware {
+    callback: Arc<dyn Fn(ScreenView) + Send + Sync>,
+    sample_rate: f32,
+    sampler: RandomState,
+    state: Mutex<AnalyticsState>,
+}
+
+/// What [`AnalyticsMiddleware`] remembers between navigations
+#[derive(Default)]
+struct AnalyticsState {
+    last_path: Option<String>,
+    views: u64,
+    reported: u64,
+}
+
+impl AnalyticsMiddleware {
+    /// Create middleware calling `callback` with every screen view
+    pub fn new(callback: impl Fn(ScreenView) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Arc::new(callback),
+            sample_rate: 1.0,
+            sampler: RandomState::new(),
+            state: Mutex::new(AnalyticsState::default()),
+        }
+    }
+
+    /// Report only this fraction of views, chosen at random
+    ///
+    /// Clamped to `0.0..=1.0`; the default of `1.0` reports every view.
+    pub fn sample_rate(mut self, sample_rate: f32) -> Self {
+        self.sample_rate = sample_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Whether the `view`-th view falls into the sample
+    fn sampled(&self, view: u64) -> bool {
+        if self.sample_rate >= 1.0 {
+            return true;
+        }
+        let roll = self.sampler.hash_one(view) as f64 / u64::MAX as f64;
+        roll < f64::from(self.sample_rate)
+    }
+
+    /// Build the view for `navigation`, unless it is a duplicate or sampled out
+    fn screen_view(&self, cx: &App, navigation: &AfterNavigation) -> Option<ScreenView> {
+        let event = &navigation.event;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.last_path.as_deref() == Some(event.to.as_str()) {
+            return None;
+        }
+        state.last_path = Some(event.to.clone());
+        state.views += 1;
+        if !self.sampled(state.views) {
+            return None;
+        }
+        state.reported += 1;
+
+        Some(ScreenView {
+            name: route_name(cx, &event.to),
+            path: event.to.clone(),
+            params: navigation.params.clone(),
+            direction: event.direction,
+            referrer: event.from.clone(),
+            sequence: state.reported,
+            previous_dwell: event.previous_dwell,
+            source: event.source.clone(),
+        })
+    }
+}
+
+impl RouteMiddleware for AnalyticsMiddleware {
+    type Future = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn before_navigation(&self, _cx: &App, _request: &NavigationRequest) -> Self::Future {
+        Box::pin(async {})
+    }
+
+    fn after_navigation(&self, cx: &App, navigation: &AfterNavigation) -> Self::Future {
+        if let Some(view) = self.screen_view(cx, navigation) {
+            (self.callback)(view);
+        }
+        Box::pin(async {})
+    }
+
+    fn name(&self) -> &str {
+        "AnalyticsMiddleware"
+    }
+}
+
+/// Name of the route matching `path`, or its full pattern
+fn route_name(cx: &App, path: &str) -> String {
+    let chain = cx
+        .try_global::<GlobalRouter>()
+        .and_then(|router| match_route_chain(router.state().routes(), path));
+    let Some((chain, _)) = chain else {
+        return path.split('?').next().unwrap_or(path).to_string();
+    };
+    if let Some(name) = chain.last().and_then(|route| route.config.name.clone()) {
+        return name;
+    }
+    chain.iter().fold(String::new(), |parent, route| {
+        if parent.is_empty() {
+            route.config.path.clone()
+        } else {
+            build_child_path(&parent, &route.config.path).into_owned()
+        }
+    })
+}
+
+// ============================================================================
+// DebugLogMiddleware
+// ============================================================================
+
+/// Built-in middleware logging one line per committed navigation
+///
+/// Lines go through the crate's logging (see [`crate::logging`]) at info
+/// level and look like
+///
+/// ```text
+/// navigation from=/ to=/users/7?tab=posts direction=Forward route=user outcome=matched params=[("id", "7")] query=[("tab", "posts")] duration=1.2ms
+/// ```
+///
+/// Paths no route matches are logged too, with `outcome=not_found`.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui_navigator::{init_router, DebugLogMiddleware};
+///
+/// # fn setup(cx: &mut gpui::App) {
+/// init_router(cx, |router| {
+///     router.add_middleware(DebugLogMiddleware);
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugLogMiddleware;
+
+impl DebugLogMiddleware {
+    /// The line logged for `navigation`
+    pub fn line(navigation: &AfterNavigation) -> String {
+        let event = &navigation.event;
+        let redirected_from = match &navigation.outcome {
+            NavigationOutcome::Redirected { from } => format!(" redirected_from={}", from),
+            _ => String::new(),
+        };
+        let mut params: Vec<_> = navigation.params.all().iter().collect();
+        params.sort();
+        let mut query: Vec<_> = navigation.query.to_map().into_iter().collect();
+        query.sort();
+        format!(
+            "navigation from={} to={} direction={:?} route={} outcome={}{} params={:?} query={:?} duration={:?}",
+            event.from.as_deref().unwrap_or("-"),
+            event.to,
+            event.direction,
+            navigation.route_name.as_deref().unwrap_or("-"),
+            navigation.outcome.as_str(),
+            redirected_from,
+            params,
+            query,
+            navigation.pipeline_duration,
+        )
+    }
+}
+
+impl RouteMiddleware for DebugLogMiddleware {
+    type Future = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn before_navigation(&self, _cx: &App, _request: &NavigationRequest) -> Self::Future {
+        Box::pin(async {})
+    }
+
+    fn after_navigation(&self, _cx: &App, navigation: &AfterNavigation) -> Self::Future {
+        info_log!("{}", Self::line(navigation));
+        Box::pin(async {})
+    }
+
+    fn name(&self) -> &str {
+        "DebugLogMiddleware"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gpui::TestAppContext;
-    use std::pin::Pin;
-    use std::sync::{Arc, Mutex};
+    use crate::{init_router, Navigator, Route, RouterOutlet};
+    use gpui::{div, IntoElement, TestAppContext, VisualTestContext};
 
     struct TestMiddleware {
         calls: Arc<Mutex<Vec<String>>>,
@@ -196,16 +644,17 @@ mod tests {
             Box::pin(async {})
         }
 
-        fn after_navigation(&self, _cx: &App, request: &NavigationRequest) -> Self::Future {
+        fn after_navigation(&self, _cx: &App, navigation: &AfterNavigation) -> Self::Future {
             self.calls
                 .lock()
                 .unwrap()
-                .push(format!("after:{}", request.to));
+                .push(format!("after:{}", navigation.event.to));
             Box::pin(async {})
         }
     }
 
     #[gpui::test]
+    #[allow(clippy::unused_async, clippy::future_not_send)]
     async fn test_middleware_before(cx: &mut TestAppContext) {
         let calls = Arc::new(Mutex::new(Vec::new()));
         let middleware = TestMiddleware {
@@ -221,14 +670,16 @@ mod tests {
     }
 
     #[gpui::test]
+    #[allow(clippy::unused_async, clippy::future_not_send)]
     async fn test_middleware_after(cx: &mut TestAppContext) {
         let calls = Arc::new(Mutex::new(Vec::new()));
         let middleware = TestMiddleware {
             calls: calls.clone(),
         };
-        let request = NavigationRequest::new("/test".to_string());
+        let event = RouteChangeEvent::new(None, "/test", NavigationDirection::Forward);
+        let navigation = AfterNavigation::new(event, &[], RouteParams::new());
 
-        cx.update(|cx| pollster::block_on(middleware.after_navigation(cx, &request)));
+        cx.update(|cx| pollster::block_on(middleware.after_navigation(cx, &navigation)));
 
         let log = calls.lock().unwrap();
         assert_eq!(log.len(), 1);
@@ -248,4 +699,464 @@ mod tests {
         let middleware = TestMiddleware { calls };
         assert_eq!(middleware.priority(), 0);
     }
+
+    type Views = Arc<Mutex<Vec<ScreenView>>>;
+
+    /// Router with `/`, `/about` and a named `/users/:id` reporting screen
+    /// views into the returned list
+    fn analytics_router(
+        cx: &mut TestAppContext,
+        configure: impl FnOnce(AnalyticsMiddleware) -> AnalyticsMiddleware,
+    ) -> (&mut VisualTestContext, Views) {
+        let views = Views::default();
+        let captured = views.clone();
+        let analytics = configure(AnalyticsMiddleware::new(move |view| {
+            captured.lock().unwrap().push(view);
+        }));
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                for path in ["/", "/about", "/admin", "/login"] {
+                    router.add_route(Route::new(path, |_, _, _| div().into_any_element()));
+                }
+                router.add_route(
+                    Route::new("/users/:id", |_, _, _| div().into_any_element()).name("user"),
+                );
+                router.add_middleware(analytics);
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        (cx, views)
+    }
+
+    fn update<R>(cx: &mut VisualTestContext, navigate: impl FnOnce(&mut App) -> R) {
+        cx.update(|window, cx| {
+            navigate(cx);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    fn paths(views: &Views) -> Vec<String> {
+        views
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|view| view.path.clone())
+            .collect()
+    }
+
+    #[gpui::test]
+    fn test_analytics_reports_screen_views(cx: &mut TestAppContext) {
+        let (cx, views) = analytics_router(cx, |analytics| analytics);
+
+        update(cx, |cx| Navigator::push(cx, "/users/7?tab=posts"));
+        update(cx, |cx| Navigator::push(cx, "/about"));
+
+        let views = views.lock().unwrap();
+        assert_eq!(views.len(), 2);
+        assert_eq!(views[0].name, "user");
+        assert_eq!(views[0].path, "/users/7?tab=posts");
+        assert_eq!(views[0].params.get("id"), Some(&"7".to_string()));
+        assert_eq!(views[0].direction, NavigationDirection::Forward);
+        assert_eq!(views[0].referrer.as_deref(), Some("/"));
+        assert_eq!(views[0].sequence, 1);
+        // Routes without a name are reported by pattern
+        assert_eq!(views[1].name, "/about");
+        assert_eq!(views[1].referrer.as_deref(), Some("/users/7?tab=posts"));
+        assert_eq!(views[1].sequence, 2);
+    }
+
+    #[gpui::test]
+    fn test_analytics_reports_dwell_time(cx: &mut TestAppContext) {
+        let (cx, views) = analytics_router(cx, |analytics| analytics);
+
+        update(cx, |cx| Navigator::push(cx, "/users/7"));
+        cx.executor().advance_clock(Duration::from_secs(3));
+        update(cx, |cx| Navigator::push(cx, "/about"));
+        cx.executor().advance_clock(Duration::from_secs(5));
+        update(cx, Navigator::pop);
+
+        let dwells: Vec<_> = views
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|view| view.previous_dwell)
+            .collect();
+        assert_eq!(
+            dwells,
+            vec![
+                Some(Duration::ZERO),
+                Some(Duration::from_secs(3)),
+                Some(Duration::from_secs(5))
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_analytics_reports_source(cx: &mut TestAppContext) {
+        let (cx, views) = analytics_router(cx, |analytics| analytics);
+
+        update(cx, |cx| Navigator::push(cx, "/about"));
+        update(cx, |cx| {
+            Navigator::handle_external_url(cx, "myapp://open/users/7")
+        });
+        update(cx, Navigator::pop);
+
+        let sources: Vec<_> = views
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|view| view.source.clone())
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                NavigationSource::Programmatic,
+                NavigationSource::DeepLink,
+                NavigationSource::History
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_analytics_skips_duplicate_views(cx: &mut TestAppContext) {
+        let (cx, views) = analytics_router(cx, |analytics| analytics);
+
+        update(cx, |cx| Navigator::push(cx, "/about"));
+        update(cx, |cx| Navigator::push(cx, "/about"));
+        update(cx, |cx| Navigator::replace(cx, "/about"));
+        update(cx, |cx| Navigator::push(cx, "/users/1"));
+        update(cx, |cx| Navigator::push(cx, "/about"));
+
+        assert_eq!(paths(&views), vec!["/about", "/users/1", "/about"]);
+        let sequences: Vec<_> = views.lock().unwrap().iter().map(|v| v.sequence).collect();
+        assert_eq!(sequences, vec![1, 2, 3]);
+    }
+
+    #[gpui::test]
+    fn test_analytics_reports_only_redirect_destination(cx: &mut TestAppContext) {
+        let (cx, views) = analytics_router(cx, |analytics| analytics);
+
+        update(cx, |cx| {
+            Navigator::push(cx, "/admin");
+            // Redirected before the navigation was reported
+            Navigator::replace(cx, "/login");
+        });
+
+        let views = views.lock().unwrap();
+        assert_eq!(views.len(), 1);
+        assert_eq!(views[0].path, "/login");
+        assert_eq!(views[0].direction, NavigationDirection::Forward);
+        assert_eq!(views[0].referrer.as_deref(), Some("/"));
+    }
+
+    #[gpui::test]
+    fn test_analytics_reports_back_navigation(cx: &mut TestAppContext) {
+        let (cx, views) = analytics_router(cx, |analytics| analytics);
+
+        update(cx, |cx| Navigator::push(cx, "/users/1"));
+        update(cx, |cx| Navigator::push(cx, "/about"));
+        update(cx, Navigator::pop);
+        update(cx, Navigator::forward);
+
+        let views = views.lock().unwrap();
+        let directions: Vec<_> = views.iter().map(|view| view.direction).collect();
+        assert_eq!(
+            directions,
+            vec![
+                NavigationDirection::Forward,
+                NavigationDirection::Forward,
+                NavigationDirection::Back,
+                NavigationDirection::Forward,
+            ]
+        );
+        assert_eq!(views[2].path, "/users/1");
+        assert_eq!(views[2].referrer.as_deref(), Some("/about"));
+    }
+
+    #[gpui::test]
+    fn test_analytics_sample_rate(cx: &mut TestAppContext) {
+        let (cx, views) = analytics_router(cx, |analytics| analytics.sample_rate(0.0));
+
+        update(cx, |cx| Navigator::push(cx, "/about"));
+        update(cx, |cx| Navigator::push(cx, "/users/1"));
+        assert!(views.lock().unwrap().is_empty());
+
+        let analytics = AnalyticsMiddleware::new(|_| {}).sample_rate(1.5);
+        assert!((0..100).all(|view| analytics.sampled(view)));
+        let analytics = AnalyticsMiddleware::new(|_| {}).sample_rate(0.5);
+        let sampled = (0..1000).filter(|view| analytics.sampled(*view)).count();
+        assert!((350..650).contains(&sampled), "sampled {sampled} of 1000");
+    }
+
+    /// Global middleware keeping every navigation it is passed
+    struct Recorder(Arc<Mutex<Vec<AfterNavigation>>>);
+
+    impl RouteMiddleware for Recorder {
+        type Future = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+        fn before_navigation(&self, _cx: &App, _request: &NavigationRequest) -> Self::Future {
+            Box::pin(async {})
+        }
+
+        fn after_navigation(&self, _cx: &App, navigation: &AfterNavigation) -> Self::Future {
+            self.0.lock().unwrap().push(navigation.clone());
+            Box::pin(async {})
+        }
+    }
+
+    /// Router like [`analytics_router`] recording what `after_navigation` gets
+    fn recording_router(
+        cx: &mut TestAppContext,
+    ) -> (&mut VisualTestContext, Arc<Mutex<Vec<AfterNavigation>>>) {
+        let navigations = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Recorder(navigations.clone());
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                for path in ["/", "/about", "/login"] {
+                    router.add_route(Route::new(path, |_, _, _| div().into_any_element()));
+                }
+                router.add_route(
+                    Route::new("/users/:id", |_, _, _| div().into_any_element()).name("user"),
+                );
+                #[cfg(feature = "guard")]
+                router.add_route(
+                    Route::new("/admin", |_, _, _| div().into_any_element())
+                        .guard(crate::AuthGuard::deny_all("/login")),
+                );
+                router.add_middleware(recorder);
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        (cx, navigations)
+    }
+
+    #[gpui::test]
+    fn test_after_navigation_matched(cx: &mut TestAppContext) {
+        let (cx, navigations) = recording_router(cx);
+
+        update(cx, |cx| Navigator::push(cx, "/users/7?tab=posts"));
+        update(cx, |cx| Navigator::push(cx, "/about"));
+
+        let navigations = navigations.lock().unwrap();
+        assert_eq!(navigations.len(), 2);
+        let user = &navigations[0];
+        assert_eq!(user.outcome, NavigationOutcome::Matched);
+        assert_eq!(user.event.from.as_deref(), Some("/"));
+        assert_eq!(user.event.to, "/users/7?tab=posts");
+        assert_eq!(user.route_name.as_deref(), Some("user"));
+        assert_eq!(user.params.get("id"), Some(&"7".to_string()));
+        assert_eq!(user.query.get("tab"), Some(&"posts".to_string()));
+        assert_eq!(user.pipeline_duration, Duration::ZERO);
+        // Routes without a name
+        assert_eq!(navigations[1].outcome, NavigationOutcome::Matched);
+        assert_eq!(navigations[1].route_name, None);
+    }
+
+    #[gpui::test]
+    fn test_after_navigation_not_found(cx: &mut TestAppContext) {
+        let (cx, navigations) = recording_router(cx);
+
+        update(cx, |cx| Navigator::push(cx, "/missing?q=1"));
+
+        let navigations = navigations.lock().unwrap();
+        assert_eq!(navigations.len(), 1);
+        assert_eq!(navigations[0].outcome, NavigationOutcome::NotFound);
+        assert_eq!(navigations[0].route_name, None);
+        assert!(navigations[0].params.all().is_empty());
+        assert_eq!(navigations[0].query.get("q"), Some(&"1".to_string()));
+    }
+
+    #[gpui::test]
+    fn test_after_navigation_redirected(cx: &mut TestAppContext) {
+        let (cx, navigations) = recording_router(cx);
+
+        update(cx, |cx| {
+            Navigator::push(cx, "/about");
+            // Redirected before the navigation was reported
+            Navigator::replace(cx, "/login");
+        });
+
+        let navigations = navigations.lock().unwrap();
+        assert_eq!(navigations.len(), 1);
+        assert_eq!(
+            navigations[0].outcome,
+            NavigationOutcome::Redirected {
+                from: "/about".to_string()
+            }
+        );
+        assert_eq!(navigations[0].event.to, "/login");
+        assert_eq!(navigations[0].event.direction, NavigationDirection::Forward);
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_after_navigation_guard_redirect(cx: &mut TestAppContext) {
+        let (cx, navigations) = recording_router(cx);
+
+        update(cx, |cx| Navigator::try_push(cx, "/admin"));
+
+        let navigations = navigations.lock().unwrap();
+        assert_eq!(navigations.len(), 1);
+        assert_eq!(navigations[0].event.to, "/login");
+        assert_eq!(
+            navigations[0].outcome,
+            NavigationOutcome::Redirected {
+                from: "/admin".to_string()
+            }
+        );
+    }
+
+    #[gpui::test]
+    fn test_after_navigation_batch(cx: &mut TestAppContext) {
+        let (cx, navigations) = recording_router(cx);
+
+        update(cx, |cx| {
+            Navigator::batch(cx, |cx| {
+                Navigator::push(cx, "/about");
+                Navigator::push(cx, "/users/7");
+                Navigator::push(cx, "/login");
+            })
+        });
+
+        let navigations = navigations.lock().unwrap();
+        assert_eq!(navigations.len(), 1);
+        assert_eq!(navigations[0].event.from.as_deref(), Some("/"));
+        assert_eq!(navigations[0].event.to, "/login");
+        assert_eq!(navigations[0].event.direction, NavigationDirection::Forward);
+    }
+
+    #[gpui::test]
+    fn test_after_navigation_navigator_chain(cx: &mut TestAppContext) {
+        let (cx, navigations) = recording_router(cx);
+
+        update(cx, |cx| {
+            Navigator::of(cx).push("/about").push("/users/7").pop();
+        });
+
+        let navigations = navigations.lock().unwrap();
+        assert_eq!(navigations.len(), 1);
+        assert_eq!(navigations[0].event.from.as_deref(), Some("/"));
+        assert_eq!(navigations[0].event.to, "/about");
+    }
+
+    #[gpui::test]
+    fn test_router_runs_route_middleware(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let middleware = TestMiddleware {
+            calls: calls.clone(),
+        };
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(Route::new("/about", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::new("/users", |_, _, _| div().into_any_element())
+                        .middleware(middleware)
+                        .children(vec![Arc::new(Route::new(":id", |_, _, _| {
+                            div().into_any_element()
+                        }))]),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+
+        // Before runs as the push starts, after once it is committed
+        update(cx, |cx| Navigator::push(cx, "/users/7"));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["before:/users/7", "after:/users/7"]
+        );
+        // Repainting is no navigation
+        update(cx, |_| {});
+        update(cx, |cx| Navigator::push(cx, "/about"));
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["before:/users/7", "after:/users/7"]
+        );
+    }
+
+    #[gpui::test]
+    fn test_before_navigation_without_app(cx: &mut TestAppContext) {
+        let (cx, _) = recording_router(cx);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let middleware = TestMiddleware {
+            calls: calls.clone(),
+        };
+        cx.update(|_, cx| cx.global_mut::<GlobalRouter>().add_middleware(middleware));
+
+        cx.update(|_, cx| {
+            cx.global_mut::<GlobalRouter>().push("/about".to_string());
+            assert!(calls.lock().unwrap().is_empty());
+        });
+
+        // The router runs it once the update is done
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["before:/about", "after:/about"]
+        );
+    }
+
+    #[gpui::test]
+    fn test_navigation_effects_without_outlet(cx: &mut TestAppContext) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let refused = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let middleware = TestMiddleware {
+                calls: calls.clone(),
+            };
+            let refused = refused.clone();
+            init_router(cx, |router| {
+                router.set_config(crate::RouterConfig::new().strict_navigation(true));
+                router.set_error_handlers(crate::ErrorHandlers::new().on_refused(
+                    move |_, error| {
+                        refused.lock().unwrap().push(error.to_string());
+                    },
+                ));
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(Route::new("/about", |_, _, _| div().into_any_element()));
+                router.add_middleware(middleware);
+            });
+        });
+
+        // No window is open, so no outlet renders
+        cx.update(|cx| {
+            Navigator::push(cx, "/about");
+            Navigator::push(cx, "/missing");
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["before:/about", "after:/about"]
+        );
+        assert_eq!(refused.lock().unwrap().len(), 1);
+
+        // Each navigation is finished once
+        cx.update(|cx| cx.refresh_windows());
+        cx.run_until_parked();
+        assert_eq!(calls.lock().unwrap().len(), 2);
+        assert_eq!(refused.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_debug_log_line() {
+        let event = RouteChangeEvent::new(
+            Some("/".to_string()),
+            "/users/7?tab=posts",
+            NavigationDirection::Forward,
+        );
+        let routes: Vec<RouteRef> = vec![Arc::new(
+            Route::new("/users/:id", |_, _, _| div().into_any_element()).name("user"),
+        )];
+        let params = RouteParams::single("id", "7");
+        let mut navigation = AfterNavigation::new(event, &routes, params);
+        navigation.pipeline_duration = Duration::from_millis(3);
+
+        assert_eq!(
+            DebugLogMiddleware::line(&navigation),
+            "navigation from=/ to=/users/7?tab=posts direction=Forward route=user \
+             outcome=matched params=[(\"id\", \"7\")] query=[(\"tab\", \"posts\")] duration=3ms"
+        );
+    }
 }
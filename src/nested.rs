@@ -3,6 +3,7 @@
 //! This module provides functionality for resolving child routes in nested routing scenarios.
 //! The cache functionality has been moved to the `cache` module (available with `cache` feature).
 
+use crate::matcher::decode_path_segment;
 use crate::route::Route;
 use crate::{trace_log, warn_log, RouteParams};
 use std::borrow::Cow;
@@ -18,10 +19,31 @@ pub fn resolve_child_route(
     current_path: &str,
     parent_params: &RouteParams,
     outlet_name: Option<&str>,
+) -> Option<ResolvedChildRoute> {
+    resolve_child_route_at(
+        parent_route,
+        &parent_route.config.path,
+        current_path,
+        parent_params,
+        outlet_name,
+    )
+}
+
+/// Resolve the child of a parent whose full path pattern is `parent_path`
+///
+/// Like [`resolve_child_route`], for parents nested in other routes: the
+/// params of every level of `parent_path` (e.g. `:orgId` in
+/// `/orgs/:orgId/teams/:teamId`) are added to `parent_params`.
+pub(crate) fn resolve_child_route_at(
+    parent_route: &Arc<Route>,
+    parent_path: &str,
+    current_path: &str,
+    parent_params: &RouteParams,
+    outlet_name: Option<&str>,
 ) -> Option<ResolvedChildRoute> {
     trace_log!(
         "resolve_child_route: parent='{}', current_path='{}', children={}, outlet_name={:?}",
-        parent_route.config.path,
+        parent_path,
         current_path,
         parent_route.get_children().len(),
         outlet_name
@@ -58,91 +80,133 @@ pub fn resolve_child_route(
         return None;
     }
 
-    // Extract parent path from the route config
-    let parent_path = &parent_route.config.path;
-
-    // Normalize paths for comparison
-    let parent_path_normalized = parent_path.trim_end_matches('/');
-    let current_path_normalized = current_path.trim_start_matches('/');
-
-    // Check if current path starts with parent path
-    if !current_path_normalized.starts_with(parent_path_normalized.trim_start_matches('/')) {
+    // Match the parent's segments against the start of the current path;
+    // `:param` segments match anything and contribute their value
+    let parent_segments = path_segments(parent_path);
+    let current_segments = path_segments(current_path);
+    if !segments_match_prefix(&parent_segments, &current_segments) {
         return None;
     }
 
-    // Get the remaining path after parent
-    let remaining = if parent_path_normalized.is_empty() || parent_path_normalized == "/" {
-        current_path_normalized
-    } else {
-        current_path_normalized
-            .strip_prefix(parent_path_normalized.trim_start_matches('/'))
-            .unwrap_or("")
-            .trim_start_matches('/')
-    };
+    let mut params = parent_params.clone();
+    for (pattern, value) in parent_segments.iter().zip(&current_segments) {
+        if let Some(name) = pattern.strip_prefix(':') {
+            params.insert(name.to_string(), decode_path_segment(value));
+        }
+    }
 
+    // Segments remaining after the parent
+    let segments = &current_segments[parent_segments.len()..];
     trace_log!(
-        "  normalized: parent='{}', current='{}', remaining='{}'",
-        parent_path_normalized,
-        current_path_normalized,
-        remaining
+        "  parent segments={:?}, remaining={:?}",
+        parent_segments,
+        segments
     );
 
-    if remaining.is_empty() {
-        // No child path, look for index route
-        return find_index_route(children, parent_params.clone());
-    }
-
-    // Split remaining path into segments
-    let segments: Vec<&str> = remaining.split('/').filter(|s| !s.is_empty()).collect();
     if segments.is_empty() {
-        return find_index_route(children, parent_params.clone());
+        // No child path, look for index route
+        return find_index_route(children, params);
     }
 
-    let first_segment = segments[0];
-    trace_log!("  first_segment: '{}'", first_segment);
-
-    // Try to match first segment against child routes
+    // Try to match the child paths against the remaining segments; relative
+    // children may span several segments, absolute ones repeat the parent's
     for child in children {
-        let child_path = child.config.path.trim_start_matches('/');
-
-        // Check for exact match or parameter match
-        if child_path == first_segment || child_path.starts_with(':') {
-            trace_log!("  matched: '{}'", child_path);
-            // Found matching child!
-            let mut combined_params = parent_params.clone();
-
-            // If this is a parameter route, extract the parameter
-            if child_path.starts_with(':') {
-                let param_name = child_path.trim_start_matches(':');
-                combined_params.insert(param_name.to_string(), first_segment.to_string());
+        // A pathless layout is the child when one of its own children matches
+        if child.pathless {
+            if let Some((_, params)) =
+                resolve_child_route_at(child, parent_path, current_path, parent_params, None)
+            {
+                return Some((Arc::clone(child), params));
             }
+            continue;
+        }
+        let absolute = is_absolute_child(parent_path, &child.config.path);
+        let child_segments = path_segments(&child.config.path);
+        let (pattern, path) = if absolute {
+            (&child_segments[parent_segments.len()..], segments)
+        } else {
+            (&child_segments[..], segments)
+        };
 
-            // TODO: Handle nested parameters in deeper child paths
+        if pattern.is_empty() || !segments_match_prefix(pattern, path) {
+            continue;
+        }
+        trace_log!("  matched: '{}'", child.config.path);
 
-            return Some((Arc::clone(child), combined_params));
+        let mut combined_params = params.clone();
+        for (pattern, value) in pattern.iter().zip(path) {
+            if let Some(name) = pattern.strip_prefix(':') {
+                combined_params.insert(name.to_string(), decode_path_segment(value));
+            }
         }
+
+        return Some((Arc::clone(child), combined_params));
     }
 
     None
 }
 
+/// Check if `child_path` is an absolute path under `parent_path`
+///
+/// Absolute child paths repeat the parent's full path, e.g.
+/// `/dashboard/reports` under `/dashboard`. Other child paths, including
+/// ones with a leading slash that don't extend the parent, are relative.
+pub(crate) fn is_absolute_child(parent_path: &str, child_path: &str) -> bool {
+    if !child_path.starts_with('/') {
+        return false;
+    }
+    let parent = path_segments(parent_path);
+    let child = path_segments(child_path);
+    child.len() > parent.len() && child.starts_with(&parent)
+}
+
+/// Split a path into its non-empty segments, ignoring any query string or
+/// fragment
+pub(crate) fn path_segments(path: &str) -> Vec<&str> {
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+/// Check if `pattern` matches the leading segments of `path`
+///
+/// Segments are compared whole, so `/app` does not match `/application`.
+/// `:param` segments match any segment.
+pub(crate) fn segments_match_prefix(pattern: &[&str], path: &[&str]) -> bool {
+    pattern.len() <= path.len()
+        && pattern
+            .iter()
+            .zip(path)
+            .all(|(pattern, segment)| pattern.starts_with(':') || pattern == segment)
+}
+
 /// Find an index route (default child route when no specific child is selected)
+///
+/// A pathless layout is the index route when one of its children is.
 fn find_index_route(children: &[Arc<Route>], params: RouteParams) -> Option<ResolvedChildRoute> {
-    // Look for a child with empty path, "/" or "index"
-    for child in children {
-        let child_path = child.config.path.trim_start_matches('/');
+    children
+        .iter()
+        .find(|child| is_index_route(child))
+        .map(|child| (Arc::clone(child), params))
+}
 
-        if child_path.is_empty() || child_path == "/" || child_path == "index" {
-            return Some((Arc::clone(child), params));
-        }
+/// Check if `route` is shown for an empty remainder: its path is empty, `/`
+/// or `index`, or it is a pathless layout with such a child
+pub(crate) fn is_index_route(route: &Route) -> bool {
+    if route.pathless {
+        return route
+            .get_children()
+            .iter()
+            .any(|child| is_index_route(child));
     }
-
-    None
+    let path = route.config.path.trim_start_matches('/');
+    path.is_empty() || path == "index"
 }
 
 /// Build the full path for a child route
 ///
-/// Combines parent and child paths into a complete route path.
+/// Combines parent and child paths into a complete route path. Relative child
+/// paths are appended to the parent; absolute ones (starting with the parent's
+/// path, see [`Route::children`](crate::Route::children)) are used as is.
 ///
 /// Returns `Cow<str>` to avoid unnecessary allocations when possible.
 /// Uses borrowed string when no modification is needed.
@@ -154,8 +218,20 @@ fn find_index_route(children: &[Arc<Route>], params: RouteParams) -> Option<Reso
 ///
 /// let full_path = build_child_path("/dashboard", "settings");
 /// assert_eq!(full_path, "/dashboard/settings");
+///
+/// let full_path = build_child_path("/dashboard", "/dashboard/reports/annual");
+/// assert_eq!(full_path, "/dashboard/reports/annual");
 /// ```
 pub fn build_child_path<'a>(parent_path: &'a str, child_path: &'a str) -> Cow<'a, str> {
+    if is_absolute_child(parent_path, child_path) {
+        let child = child_path.trim_end_matches('/');
+        return if child == child_path {
+            Cow::Borrowed(child_path)
+        } else {
+            Cow::Owned(child.to_string())
+        };
+    }
+
     let parent = parent_path.trim_end_matches('/');
     let child = child_path.trim_start_matches('/').trim_end_matches('/');
 
@@ -0,0 +1,440 @@
+//! Planning what a `RouterOutlet` renders
+//!
+//! Each render, [`RouterOutlet`](crate::RouterOutlet) hands the router's
+//! current location and what it showed last to [`plan_outlet_render`], then
+//! draws the returned [`OutletPlan`]. The planner only looks at routes and
+//! paths, so the decisions (which route, whether it animates, what exits)
+//! can be checked without a window.
+//!
+//! This module is internal; its API may change in any release.
+
+use crate::nested::{path_segments, segments_match_prefix};
+use crate::route::{shared_chain_depth, RouteRef};
+use crate::state::{is_modal_path, match_routes, top_level_route};
+#[cfg(feature = "transition")]
+use crate::transition::Transition;
+use crate::widgets::join_route_path;
+use crate::RouteParams;
+use gpui::SharedString;
+use std::sync::Arc;
+
+/// Where the router is, as seen by an outlet
+#[derive(Clone)]
+pub struct OutletLocation<'a> {
+    /// Current path, including its query
+    pub path: SharedString,
+    /// Current locale, added to the params as `locale`
+    pub locale: Option<&'a str>,
+    /// Transition the current route is shown with
+    #[cfg(feature = "transition")]
+    pub transition: Transition,
+}
+
+/// A route drawn as one layer of an outlet
+///
+/// The outlet stacks up to three: the page under a modal, the route exiting
+/// during a transition, and the current route.
+#[derive(Clone)]
+pub struct OutletLayer {
+    /// Path the route was matched for
+    pub path: SharedString,
+    /// Params of the match
+    pub params: Arc<RouteParams>,
+    /// Top-level route, `None` for the not found page
+    pub route: Option<RouteRef>,
+}
+
+/// What an outlet showed after its last route change
+#[derive(Clone, Default)]
+pub struct OutletSnapshot {
+    /// Path shown, empty before the first render
+    pub path: SharedString,
+    /// Params of the route shown
+    pub params: Arc<RouteParams>,
+    /// Top-level route shown
+    pub route: Option<RouteRef>,
+    /// Transition the route entered with
+    #[cfg(feature = "transition")]
+    pub transition: Transition,
+    /// Whether the route shown is a modal
+    pub modal: bool,
+    /// Bumped for every animated route change
+    pub animation_epoch: u32,
+    /// Route exiting while the current one enters
+    pub exiting: Option<OutletLayer>,
+}
+
+/// How the outlet's route changed since the last render
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutletChange {
+    /// Same path as before
+    Unchanged,
+    /// First route the outlet shows, without animation
+    Initial,
+    /// New path below the same parent; the parent stays and only its nested
+    /// outlet changes
+    ParentKept,
+    /// Another top-level route (or the same one with other params)
+    Replaced,
+}
+
+/// What an outlet draws this render
+#[derive(Clone)]
+pub struct OutletPlan {
+    /// Current path
+    pub path: SharedString,
+    /// Top-level route to build, `None` for the not found page
+    pub route: Option<RouteRef>,
+    /// Params to build it with
+    pub params: Arc<RouteParams>,
+    /// Whether the route is a modal, drawn above the page underneath
+    pub modal: bool,
+    /// How the route changed since the last render
+    pub change: OutletChange,
+    /// Transition the route is shown with
+    #[cfg(feature = "transition")]
+    pub transition: Transition,
+    /// Route exiting during the transition
+    pub exiting: Option<OutletLayer>,
+    /// Keys the transition's animations; a new value restarts them
+    pub animation_epoch: u32,
+}
+
+impl OutletPlan {
+    /// What the outlet shows once this plan is drawn
+    ///
+    /// Paths and params are shared, so this copies no strings.
+    pub fn snapshot(&self) -> OutletSnapshot {
+        OutletSnapshot {
+            path: self.path.clone(),
+            params: Arc::clone(&self.params),
+            route: self.route.clone(),
+            #[cfg(feature = "transition")]
+            transition: self.transition.clone(),
+            modal: self.modal,
+            animation_epoch: self.animation_epoch,
+            exiting: self.exiting.clone(),
+        }
+    }
+}
+
+/// Decide what an outlet renders at `location`, given what it showed last
+///
+/// A route change animates (and bumps the animation epoch) unless it is
+/// the outlet's first route or the parent route is kept. Modals only animate
+/// against other modals: the page underneath stays in place when one opens,
+/// and closing one just removes it. When the path is unchanged, the running
+/// transition and the exiting route carry over.
+pub fn plan_outlet_render(
+    routes: &[RouteRef],
+    location: &OutletLocation<'_>,
+    shown: &OutletSnapshot,
+) -> OutletPlan {
+    let path = location.path.as_ref();
+    let route = outlet_route(routes, path);
+    let params = Arc::new(
+        match_routes(routes, path, location.locale)
+            .map(|route_match| route_match.params.into_iter().collect::<RouteParams>())
+            .unwrap_or_default(),
+    );
+    let modal = is_modal_path(routes, path);
+
+    if shown.path == path {
+        return OutletPlan {
+            path: location.path.clone(),
+            route,
+            params,
+            modal,
+            change: OutletChange::Unchanged,
+            #[cfg(feature = "transition")]
+            transition: shown.transition.clone(),
+            exiting: shown.exiting.clone().filter(|layer| layer.path != *path),
+            animation_epoch: shown.animation_epoch,
+        };
+    }
+
+    let change = if shown.path.is_empty() {
+        OutletChange::Initial
+    } else if shared_chain_depth(routes, &shown.path, path) > 0 {
+        OutletChange::ParentKept
+    } else {
+        OutletChange::Replaced
+    };
+
+    let exiting = (change == OutletChange::Replaced && modal == shown.modal).then(|| OutletLayer {
+        path: shown.path.clone(),
+        params: Arc::clone(&shown.params),
+        route: shown.route.clone(),
+    });
+
+    #[cfg(feature = "transition")]
+    let transition = if change == OutletChange::ParentKept {
+        shown.transition.clone()
+    } else if shown.modal && !modal {
+        Transition::None
+    } else {
+        location.transition.clone()
+    };
+
+    #[cfg(feature = "transition")]
+    let animation_epoch = if change == OutletChange::Replaced {
+        shown.animation_epoch.wrapping_add(1)
+    } else {
+        shown.animation_epoch
+    };
+    #[cfg(not(feature = "transition"))]
+    let animation_epoch = shown.animation_epoch;
+
+    OutletPlan {
+        path: location.path.clone(),
+        route,
+        params,
+        modal,
+        change,
+        #[cfg(feature = "transition")]
+        transition,
+        exiting,
+        animation_epoch,
+    }
+}
+
+/// Top-level route an outlet renders for `path`
+///
+/// When no route matches, the top-level route above the parent whose
+/// children should have matched is rendered instead, so the layouts stay and
+/// the nested outlet reports the missing child.
+pub(crate) fn outlet_route(routes: &[RouteRef], path: &str) -> Option<RouteRef> {
+    top_level_route(routes, path).cloned().or_else(|| {
+        missing_child_parent(routes, &path_segments(path), "")?
+            .into_iter()
+            .next()
+    })
+}
+
+/// Parent whose children should have matched `current`, with its ancestors
+///
+/// That is the deepest route with children whose full path starts `current`.
+pub(crate) fn missing_child_parent(
+    routes: &[RouteRef],
+    current: &[&str],
+    accumulated_path: &str,
+) -> Option<Vec<RouteRef>> {
+    routes
+        .iter()
+        .filter(|route| !route.get_children().is_empty())
+        .find_map(|route| {
+            let full_route_path = join_route_path(accumulated_path, &route.config.path);
+            if !segments_match_prefix(&path_segments(&full_route_path), current) {
+                return None;
+            }
+            let chain = missing_child_parent(route.get_children(), current, &full_route_path);
+            // Pathless layouts only count when one of their children does
+            let mut chain = if route.pathless {
+                chain?
+            } else {
+                chain.unwrap_or_default()
+            };
+            chain.insert(0, std::sync::Arc::clone(route));
+            Some(chain)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::page;
+    use crate::Presentation;
+    use std::sync::Arc;
+
+    fn routes() -> Vec<RouteRef> {
+        #[cfg_attr(not(feature = "transition"), allow(unused_mut))]
+        let mut home = page("/");
+        #[cfg(feature = "transition")]
+        {
+            home = home.transition(Transition::fade(200));
+        }
+        vec![
+            Arc::new(home),
+            Arc::new(page("/users/:id")),
+            Arc::new(
+                page("/dashboard")
+                    .children(vec![Arc::new(page("overview")), Arc::new(page("settings"))]),
+            ),
+            Arc::new(page("/photos/:id").presentation(Presentation::Modal)),
+        ]
+    }
+
+    fn at(path: &str) -> OutletLocation<'static> {
+        OutletLocation {
+            path: SharedString::from(path.to_string()),
+            locale: None,
+            #[cfg(feature = "transition")]
+            transition: Transition::fade(200),
+        }
+    }
+
+    fn render(routes: &[RouteRef], path: &str, shown: &OutletSnapshot) -> OutletPlan {
+        plan_outlet_render(routes, &at(path), shown)
+    }
+
+    fn route_path(plan: &OutletPlan) -> Option<&str> {
+        plan.route.as_ref().map(|route| route.config.path.as_str())
+    }
+
+    #[test]
+    fn test_initial_render() {
+        let routes = routes();
+        let plan = render(&routes, "/users/7?tab=posts", &OutletSnapshot::default());
+
+        assert_eq!(plan.change, OutletChange::Initial);
+        assert_eq!(route_path(&plan), Some("/users/:id"));
+        assert_eq!(plan.params.get("id"), Some(&"7".to_string()));
+        assert_eq!(plan.params.get("tab"), None);
+        assert!(plan.exiting.is_none());
+        assert_eq!(plan.animation_epoch, 0);
+        #[cfg(feature = "transition")]
+        assert!(matches!(plan.transition, Transition::Fade { .. }));
+    }
+
+    #[test]
+    fn test_locale_param() {
+        let routes = routes();
+        let location = OutletLocation {
+            locale: Some("de"),
+            ..at("/users/7")
+        };
+        let plan = plan_outlet_render(&routes, &location, &OutletSnapshot::default());
+
+        assert_eq!(plan.params.get("locale"), Some(&"de".to_string()));
+        assert_eq!(plan.params.get("id"), Some(&"7".to_string()));
+    }
+
+    #[test]
+    fn test_path_unchanged() {
+        let routes = routes();
+        let first = render(&routes, "/", &OutletSnapshot::default());
+        let second = render(&routes, "/users/1", &first.snapshot());
+
+        let plan = render(&routes, "/users/1", &second.snapshot());
+        assert_eq!(plan.change, OutletChange::Unchanged);
+        assert_eq!(plan.animation_epoch, second.animation_epoch);
+        assert_eq!(
+            plan.exiting.as_ref().map(|layer| layer.path.as_str()),
+            Some("/")
+        );
+
+        // Once the interpreter drops the finished exit, it stays dropped
+        let mut shown = plan.snapshot();
+        shown.exiting = None;
+        let plan = render(&routes, "/users/1", &shown);
+        assert!(plan.exiting.is_none());
+        assert_eq!(plan.animation_epoch, second.animation_epoch);
+    }
+
+    #[test]
+    fn test_replaced_route_exits() {
+        let routes = routes();
+        let first = render(&routes, "/", &OutletSnapshot::default());
+        let plan = render(&routes, "/users/1", &first.snapshot());
+
+        assert_eq!(plan.change, OutletChange::Replaced);
+        let exiting = plan.exiting.as_ref().expect("previous route should exit");
+        assert_eq!(exiting.path, "/");
+        assert_eq!(
+            exiting.route.as_ref().map(|r| r.config.path.as_str()),
+            Some("/")
+        );
+        #[cfg(feature = "transition")]
+        assert_eq!(plan.animation_epoch, 1);
+        #[cfg(not(feature = "transition"))]
+        assert_eq!(plan.animation_epoch, 0);
+    }
+
+    #[test]
+    fn test_interrupted_transition() {
+        let routes = routes();
+        let first = render(&routes, "/", &OutletSnapshot::default());
+        let second = render(&routes, "/users/1", &first.snapshot());
+        // Navigating again before the transition to /users/1 completes
+        let third = render(&routes, "/users/2", &second.snapshot());
+
+        assert_eq!(third.change, OutletChange::Replaced);
+        assert_eq!(
+            third.exiting.as_ref().map(|layer| layer.path.as_str()),
+            Some("/users/1")
+        );
+        assert_eq!(
+            third
+                .exiting
+                .as_ref()
+                .and_then(|layer| layer.params.get("id")),
+            Some(&"1".to_string())
+        );
+        #[cfg(feature = "transition")]
+        assert_eq!(third.animation_epoch, second.animation_epoch + 1);
+    }
+
+    #[test]
+    fn test_nested_parent_kept() {
+        let routes = routes();
+        let first = render(&routes, "/dashboard/overview", &OutletSnapshot::default());
+        assert_eq!(route_path(&first), Some("/dashboard"));
+
+        let plan = render(&routes, "/dashboard/settings", &first.snapshot());
+        assert_eq!(plan.change, OutletChange::ParentKept);
+        assert_eq!(route_path(&plan), Some("/dashboard"));
+        assert!(plan.exiting.is_none());
+        assert_eq!(plan.animation_epoch, first.animation_epoch);
+    }
+
+    #[test]
+    fn test_missing_child_keeps_parent() {
+        let routes = routes();
+        let plan = render(&routes, "/dashboard/missing", &OutletSnapshot::default());
+
+        assert_eq!(route_path(&plan), Some("/dashboard"));
+        assert!(plan.params.is_empty());
+    }
+
+    #[test]
+    fn test_not_found() {
+        let routes = routes();
+        let plan = render(&routes, "/nowhere/at/all", &OutletSnapshot::default());
+
+        assert!(plan.route.is_none());
+        assert!(!plan.modal);
+    }
+
+    #[test]
+    fn test_modal_opens_and_closes_without_exit() {
+        let routes = routes();
+        let page = render(&routes, "/users/1", &OutletSnapshot::default());
+
+        let modal = render(&routes, "/photos/3", &page.snapshot());
+        assert!(modal.modal);
+        assert_eq!(modal.change, OutletChange::Replaced);
+        assert!(modal.exiting.is_none());
+
+        let closed = render(&routes, "/users/1", &modal.snapshot());
+        assert!(!closed.modal);
+        assert!(closed.exiting.is_none());
+        #[cfg(feature = "transition")]
+        assert!(matches!(closed.transition, Transition::None));
+    }
+
+    #[test]
+    fn test_named_outlet_plans_separately() {
+        let routes = routes();
+        let main = render(&routes, "/", &OutletSnapshot::default());
+        let main = render(&routes, "/users/1", &main.snapshot());
+
+        // A named outlet mounted after the navigation has shown nothing yet
+        let named = render(&routes, "/users/1", &OutletSnapshot::default());
+        assert_eq!(named.change, OutletChange::Initial);
+        assert!(named.exiting.is_none());
+        assert_eq!(named.animation_epoch, 0);
+
+        assert_eq!(main.change, OutletChange::Replaced);
+        assert!(main.exiting.is_some());
+    }
+}
@@ -0,0 +1,492 @@
+//! Command palette entries generated from the route table
+//!
+//! [`GlobalRouter::navigation_commands`](crate::GlobalRouter::navigation_commands)
+//! lists a [`NavigationCommand`] per route a "Go to…" palette can offer,
+//! and [`fuzzy_match`] ranks them against what the user typed:
+//!
+//! ```ignore
+//! use gpui_navigator::{palette, Navigator, RouteParams};
+//!
+//! let commands = Navigator::navigation_commands(cx);
+//! for scored in palette::fuzzy_match(&commands, "usr") {
+//!     println!("{} ({})", scored.command.label, scored.command.pattern);
+//! }
+//!
+//! // Once the palette prompted for the params the command needs
+//! Navigator::run_command(cx, &commands[0], &RouteParams::single("id", "42"));
+//! ```
+//!
+//! Routes are listed when they have a name, a title or a palette label, and
+//! are configured through typed meta (see the [`meta`](crate::meta) module):
+//!
+//! - `.meta_typed(PaletteHidden)` leaves the route out (its children are
+//!   still listed)
+//! - `.meta_typed(PaletteLabel::new("Open user"))` replaces the label, which
+//!   is otherwise the [`RouteTitle`] or the humanized name (`user.detail`
+//!   gives `User detail`)
+//! - `.meta_typed(PaletteParams::new().param("id", "me"))` provides a value
+//!   for the param `id`; commands with a param nothing provides a value for
+//!   need input
+//!
+//! The string meta `palette` (set to `hidden`), `palette.label`, `title` and
+//! `palette.param.<name>` are read as well, the typed values winning.
+//!
+//! Pathless layouts and wildcard routes are not listed themselves.
+
+use crate::matcher::{CompiledPattern, ParamSpec, UrlForError};
+use crate::meta::{MetaKey, RouteTitle};
+use crate::nested::build_child_path;
+use crate::route::RouteRef;
+use crate::RouteParams;
+use std::collections::BTreeMap;
+
+/// Meta key hiding a route from the palette when set to `hidden`
+pub const PALETTE_META: &str = "palette";
+
+/// Meta key replacing the label of a route's command
+pub const PALETTE_LABEL_META: &str = "palette.label";
+
+/// Prefix of the meta keys providing param values, followed by the param name
+pub const PALETTE_PARAM_META_PREFIX: &str = "palette.param.";
+
+/// Typed meta hiding a route from the palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteHidden;
+
+impl MetaKey for PaletteHidden {
+    const NAME: &'static str = PALETTE_META;
+}
+
+/// Typed meta replacing the label of a route's command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteLabel(pub String);
+
+impl PaletteLabel {
+    /// Create a label
+    pub fn new(label: impl Into<String>) -> Self {
+        Self(label.into())
+    }
+}
+
+impl MetaKey for PaletteLabel {
+    const NAME: &'static str = PALETTE_LABEL_META;
+}
+
+/// Typed meta providing values for a route's params, by param name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaletteParams(pub BTreeMap<String, String>);
+
+impl PaletteParams {
+    /// Create empty param values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Provide `value` for the param `name`
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.0.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl MetaKey for PaletteParams {
+    const NAME: &'static str = "palette.params";
+}
+
+/// A route a command palette can navigate to
+///
+/// Holds no closures, so palettes can keep, clone and compare commands and
+/// prompt for [`params`](Self::params) before running one with
+/// [`Navigator::run_command`](crate::Navigator::run_command).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavigationCommand {
+    /// Text shown in the palette
+    pub label: String,
+    /// Name of the route, if it has one
+    pub name: Option<String>,
+    /// Full path pattern of the route, e.g. `/users/:id{int}`
+    pub pattern: String,
+    /// Params the pattern needs, with their constraints, in pattern order
+    pub params: Vec<ParamSpec>,
+    /// Param values provided by the route's [`PaletteParams`] and
+    /// `palette.param.*` meta
+    pub hints: BTreeMap<String, String>,
+    /// Whether a param has no provided value, so the palette has to ask
+    pub needs_input: bool,
+}
+
+impl NavigationCommand {
+    /// Describe `route`, registered under `full_path`, as a command
+    ///
+    /// `None` for routes the palette does not list.
+    fn from_route(route: &RouteRef, full_path: &str) -> Option<Self> {
+        let config = &route.config;
+        if route.pathless
+            || route.get_meta::<PaletteHidden>().is_some()
+            || config.meta.get(PALETTE_META).map(String::as_str) == Some("hidden")
+        {
+            return None;
+        }
+        let label = route
+            .get_meta::<PaletteLabel>()
+            .map(|label| &label.0)
+            .or_else(|| config.meta.get(PALETTE_LABEL_META))
+            .or_else(|| route.get_meta::<RouteTitle>().map(|title| &title.0))
+            .or_else(|| config.meta.get("title"))
+            .cloned()
+            .or_else(|| config.name.as_deref().map(humanize))?;
+        let pattern = CompiledPattern::compile(full_path).ok()?;
+        if pattern.has_wildcard() {
+            return None;
+        }
+
+        let mut hints: BTreeMap<String, String> = config
+            .meta
+            .iter()
+            .filter_map(|(key, value)| {
+                let param = key.strip_prefix(PALETTE_PARAM_META_PREFIX)?;
+                Some((param.to_string(), value.clone()))
+            })
+            .collect();
+        if let Some(params) = route.get_meta::<PaletteParams>() {
+            hints.extend(params.0.clone());
+        }
+        let params = pattern.params().to_vec();
+        let needs_input = params.iter().any(|param| !hints.contains_key(&param.name));
+        Some(Self {
+            label,
+            name: config.name.clone(),
+            pattern: pattern.as_str().to_string(),
+            params,
+            hints,
+            needs_input,
+        })
+    }
+
+    /// Params to navigate with: the provided [`hints`](Self::hints),
+    /// overridden by `params`
+    pub fn params_with(&self, params: &RouteParams) -> RouteParams {
+        let mut merged = RouteParams::from_map(self.hints.clone().into_iter().collect());
+        for (key, value) in params.iter() {
+            merged.set(key.clone(), value.clone());
+        }
+        merged
+    }
+
+    /// Path this command navigates to with `params`
+    ///
+    /// Fails like [`CompiledPattern::expand`] when a param is missing or
+    /// rejected by its constraint.
+    pub fn target(&self, params: &RouteParams) -> Result<String, UrlForError> {
+        CompiledPattern::compile(&self.pattern)
+            .map_err(|_| UrlForError::UnknownRoute(self.pattern.clone()))?
+            .expand(&self.params_with(params))
+    }
+}
+
+/// A [`NavigationCommand`] matching a palette query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredCommand {
+    /// The command
+    pub command: NavigationCommand,
+    /// How well the label matches, higher is better
+    pub score: i32,
+    /// Indices of the label's characters matching the query, for highlighting
+    pub positions: Vec<usize>,
+}
+
+/// Commands for `routes` and their children, depth-first
+pub(crate) fn commands_for(routes: &[RouteRef], parent_path: &str) -> Vec<NavigationCommand> {
+    let mut commands = Vec::new();
+    for route in routes {
+        let full_path = if parent_path.is_empty() {
+            route.config.path.clone()
+        } else {
+            build_child_path(parent_path, &route.config.path).into_owned()
+        };
+        commands.extend(NavigationCommand::from_route(route, &full_path));
+        commands.extend(commands_for(route.get_children(), &full_path));
+        for name in route.named_outlet_names() {
+            let children = route.get_named_children(name).unwrap_or_default();
+            commands.extend(commands_for(children, &full_path));
+        }
+    }
+    commands
+}
+
+/// Rank `commands` whose label contains `query` as a subsequence
+///
+/// Matching ignores case and whitespace in `query`. Matches right after a
+/// previous one or at the start of a word score higher, as do matches
+/// closer to the start of the label; ties keep the shorter label first.
+/// An empty query keeps every command, in order, with a score of 0.
+///
+/// # Example
+///
+/// ```ignore
+/// let ranked = palette::fuzzy_match(&commands, "set");
+/// assert_eq!(ranked[0].command.label, "Settings");
+/// ```
+pub fn fuzzy_match(commands: &[NavigationCommand], query: &str) -> Vec<ScoredCommand> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let mut scored: Vec<ScoredCommand> = commands
+        .iter()
+        .filter_map(|command| {
+            let (score, positions) = subsequence_score(&command.label, &query)?;
+            Some(ScoredCommand {
+                command: command.clone(),
+                score,
+                positions,
+            })
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.command.label.len().cmp(&b.command.label.len()))
+    });
+    scored
+}
+
+/// Score of `label` for a lowercased `query`, with the matched positions
+///
+/// Each query character is matched at the first fitting position after the
+/// previous one; the first one is tried at every position it occurs, and
+/// the best scoring start is kept.
+fn subsequence_score(label: &str, query: &[char]) -> Option<(i32, Vec<usize>)> {
+    let chars: Vec<char> = label.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let Some(first) = query.first() else {
+        return Some((0, Vec::new()));
+    };
+
+    let word_start = |index: usize| {
+        index == 0
+            || !chars[index - 1].is_alphanumeric()
+            || (chars[index - 1].is_lowercase() && chars[index].is_uppercase())
+    };
+    let mut best: Option<(i32, Vec<usize>)> = None;
+    for start in (0..lower.len()).filter(|&index| lower[index] == *first) {
+        let mut positions = vec![start];
+        for c in &query[1..] {
+            let from = positions[positions.len() - 1] + 1;
+            match (from..lower.len()).find(|&index| lower[index] == *c) {
+                Some(index) => positions.push(index),
+                None => break,
+            }
+        }
+        if positions.len() < query.len() {
+            // Later starts only leave fewer characters to match
+            break;
+        }
+
+        let mut score = -i32::try_from(start.min(10)).unwrap_or(10);
+        for (i, &index) in positions.iter().enumerate() {
+            score += 1;
+            if i > 0 && index == positions[i - 1] + 1 {
+                score += 5;
+            }
+            if word_start(index) {
+                score += 3;
+            }
+        }
+        if best.as_ref().map_or(true, |(best, _)| score > *best) {
+            best = Some((score, positions));
+        }
+    }
+    best
+}
+
+/// `user.detail` and `user-detail` as `User detail`
+fn humanize(name: &str) -> String {
+    let words = name.replace(['.', '-', '_'], " ");
+    let mut chars = words.trim().chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::page;
+    use crate::{init_router, GlobalRouter, NavigationResult, Navigator, Route};
+    use gpui::{IntoElement, ParentElement, TestAppContext};
+
+    /// Router whose active `app` table nests settings pages under a layout
+    fn init_app(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.define_table("app", |router| {
+                    router.add_route(page("/").name("home"));
+                    router.add_route(
+                        Route::new("/settings", |_, _, _| {
+                            gpui::div().child("settings").into_any_element()
+                        })
+                        .name("settings")
+                        .meta_typed(RouteTitle::new("Settings"))
+                        .children(vec![
+                            page("profile").name("settings.profile").into(),
+                            page("danger-zone")
+                                .name("settings.danger")
+                                .meta_typed(PaletteHidden)
+                                .into(),
+                        ]),
+                    );
+                    router.add_route(
+                        page("/users/:id{int}")
+                            .name("user.detail")
+                            .meta("palette.label", "Open user"),
+                    );
+                    router.add_route(
+                        page("/users/:id{int}/posts/:post")
+                            .name("user.post")
+                            .meta_typed(PaletteParams::new().param("post", "latest")),
+                    );
+                    router.add_route(page("/me").meta("palette.param.unused", "x").name("me"));
+                    router.add_route(page("/files/*").name("files"));
+                    router.add_route(page("/unlabeled"));
+                });
+                router.switch_table("app", "/").unwrap();
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_navigation_commands(cx: &mut TestAppContext) {
+        init_app(cx);
+        let commands = cx.read(Navigator::navigation_commands);
+        let listed: Vec<(&str, &str, bool)> = commands
+            .iter()
+            .map(|command| {
+                (
+                    command.label.as_str(),
+                    command.pattern.as_str(),
+                    command.needs_input,
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("Home", "/", false),
+                ("Settings", "/settings", false),
+                ("Settings profile", "/settings/profile", false),
+                ("Open user", "/users/:id{int}", true),
+                ("User post", "/users/:id{int}/posts/:post", true),
+                ("Me", "/me", false),
+            ]
+        );
+
+        let user = &commands[3];
+        assert_eq!(user.name.as_deref(), Some("user.detail"));
+        assert_eq!(user.params.len(), 1);
+        assert_eq!(user.params[0].name, "id");
+        assert_eq!(
+            user.params[0].constraint,
+            Some(crate::matcher::Constraint::Numeric)
+        );
+
+        let post = &commands[4];
+        assert_eq!(post.hints.get("post").map(String::as_str), Some("latest"));
+        assert_eq!(
+            post.target(&RouteParams::single("id", "7")).unwrap(),
+            "/users/7/posts/latest"
+        );
+        assert!(post.target(&RouteParams::new()).is_err());
+    }
+
+    #[gpui::test]
+    fn test_hidden_routes_are_not_listed(cx: &mut TestAppContext) {
+        init_app(cx);
+        let commands = cx.read(Navigator::navigation_commands);
+        assert!(commands
+            .iter()
+            .all(|command| command.name.as_deref() != Some("settings.danger")));
+        // Still registered and reachable
+        assert!(cx.read(|cx| cx
+            .global::<GlobalRouter>()
+            .has_route("/settings/danger-zone")));
+    }
+
+    #[gpui::test]
+    fn test_run_command(cx: &mut TestAppContext) {
+        init_app(cx);
+        let commands = cx.read(Navigator::navigation_commands);
+        let post = commands
+            .iter()
+            .find(|command| command.label == "User post")
+            .unwrap();
+
+        let result = cx.update(|cx| Navigator::run_command(cx, post, &RouteParams::new()));
+        assert!(result.is_error());
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        let result =
+            cx.update(|cx| Navigator::run_command(cx, post, &RouteParams::single("id", "7")));
+        assert!(result.is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/users/7/posts/latest");
+
+        // Unnamed routes navigate to their expanded pattern
+        let mut unnamed = commands[1].clone();
+        unnamed.name = None;
+        let result = cx.update(|cx| Navigator::run_command(cx, &unnamed, &RouteParams::new()));
+        assert!(matches!(result, NavigationResult::Success { ref path } if path == "/settings"));
+        assert_eq!(cx.read(Navigator::current_path), "/settings");
+    }
+
+    fn command(label: &str) -> NavigationCommand {
+        NavigationCommand {
+            label: label.to_string(),
+            name: None,
+            pattern: "/".to_string(),
+            params: Vec::new(),
+            hints: BTreeMap::new(),
+            needs_input: false,
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_match_ranking() {
+        let commands = vec![
+            command("Reset password"),
+            command("User settings"),
+            command("Settings"),
+            command("Open user"),
+        ];
+        let labels = |query: &str| -> Vec<String> {
+            fuzzy_match(&commands, query)
+                .into_iter()
+                .map(|scored| scored.command.label)
+                .collect()
+        };
+
+        // Word starts and consecutive characters rank first
+        assert_eq!(
+            labels("set"),
+            vec!["Settings", "User settings", "Reset password"]
+        );
+        assert_eq!(labels("us"), vec!["User settings", "Open user"]);
+        assert_eq!(labels("OU"), vec!["Open user"]);
+        assert!(labels("xyz").is_empty());
+        assert_eq!(labels("").len(), commands.len());
+
+        let scored = fuzzy_match(&commands, "u s");
+        assert_eq!(scored[0].command.label, "User settings");
+        assert_eq!(scored[0].positions, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_humanize() {
+        assert_eq!(humanize("user.detail"), "User detail");
+        assert_eq!(humanize("danger-zone_v2"), "Danger zone v2");
+        assert_eq!(humanize(""), "");
+    }
+}
@@ -3,7 +3,13 @@
 //! This module provides types for working with URL parameters extracted from route
 //! patterns (like `:id`) and query strings (like `?page=1&sort=name`).
 
+use crate::cancel::NavigationToken;
+use crate::loader::{DataState, LoaderData};
+#[cfg(feature = "transition")]
+use crate::transition::TransitionProgress;
+use std::any::Any;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Route parameters extracted from path segments
 ///
@@ -14,15 +20,31 @@ use std::collections::HashMap;
 ///
 /// // Route pattern: /users/:id
 /// // Matched path: /users/123
-/// let mut params = RouteParams::new();
-/// params.insert("id".to_string(), "123".to_string());
+/// let params = RouteParams::from([("id", "123")]);
 ///
 /// assert_eq!(params.get("id"), Some(&"123".to_string()));
 /// assert_eq!(params.get_as::<i32>("id"), Some(123));
 /// ```
+///
+/// Params can also be collected from pairs, or built with
+/// [`route_params!`](crate::route_params), which converts values with
+/// `ToString`.
+///
+/// With the `serde` feature, serializes as a map of names to values
+/// (`{"id":"123"}`). Loader data and other render-time attachments are not
+/// serialized.
 #[derive(Debug, Clone, Default)]
 pub struct RouteParams {
     params: HashMap<String, String>,
+    /// Output of the route's loader, attached when rendering
+    data: Option<DataState<LoaderData>>,
+    /// Progress of the route's transition, attached when rendering
+    #[cfg(feature = "transition")]
+    transition: Option<TransitionProgress>,
+    /// Data prepared for a cached route, attached when rendering
+    prepared: Option<LoaderData>,
+    /// Token of the navigation, attached for loaders
+    token: Option<NavigationToken>,
 }
 
 impl RouteParams {
@@ -33,7 +55,91 @@ impl RouteParams {
 
     /// Create from hashmap
     pub fn from_map(params: HashMap<String, String>) -> Self {
-        Self { params }
+        Self {
+            params,
+            ..Self::default()
+        }
+    }
+
+    /// Create params holding only `key`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::RouteParams;
+    ///
+    /// let params = RouteParams::single("id", 42);
+    /// assert_eq!(params.get_as::<u32>("id"), Some(42));
+    /// ```
+    pub fn single(key: impl Into<String>, value: impl ToString) -> Self {
+        std::iter::once((key.into(), value.to_string())).collect()
+    }
+
+    /// Attach loader output
+    pub fn with_data(mut self, data: DataState<LoaderData>) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Get the route loader's output as `T`
+    ///
+    /// Returns `None` if the route has no loader. Data of another type is
+    /// reported as `DataState::Error`.
+    pub fn data<T: Any + Send + Sync>(&self) -> Option<DataState<Arc<T>>> {
+        self.data.as_ref().map(DataState::downcast)
+    }
+
+    /// Get the route loader's output without downcasting
+    pub fn loader_data(&self) -> Option<&DataState<LoaderData>> {
+        self.data.as_ref()
+    }
+
+    /// Attach the token of the navigation the params were matched for
+    pub fn with_navigation_token(mut self, token: NavigationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Token of the navigation these params were matched for
+    ///
+    /// Set for the params loaders get, so they can stop once their
+    /// navigation is cancelled (see [`crate::cancel`]).
+    pub fn navigation_token(&self) -> Option<&NavigationToken> {
+        self.token.as_ref()
+    }
+
+    /// Attach the data prepared for a cached route
+    pub(crate) fn with_prepared(mut self, data: LoaderData) -> Self {
+        self.prepared = Some(data);
+        self
+    }
+
+    /// Data prepared for a cached route, if it is a `T`
+    pub(crate) fn prepared<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.prepared.clone()?.downcast().ok()
+    }
+
+    /// Params as a string, equal for equal params regardless of order
+    pub(crate) fn cache_key(&self) -> String {
+        let mut entries: Vec<_> = self.params.iter().collect();
+        entries.sort();
+        format!("{:?}", entries)
+    }
+
+    /// Attach transition progress
+    #[cfg(feature = "transition")]
+    pub fn with_transition_progress(mut self, progress: TransitionProgress) -> Self {
+        self.transition = Some(progress);
+        self
+    }
+
+    /// Get the progress of the route's transition
+    ///
+    /// The outlet rebuilds the route every frame while its transition runs,
+    /// so content can animate parts of itself. Idle outside of an outlet.
+    #[cfg(feature = "transition")]
+    pub fn transition_progress(&self) -> TransitionProgress {
+        self.transition.unwrap_or_default()
     }
 
     /// Get a parameter value as a string
@@ -90,6 +196,115 @@ impl RouteParams {
     pub fn len(&self) -> usize {
         self.params.len()
     }
+
+    /// Compare two sets of parameters
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::RouteParams;
+    ///
+    /// let old = RouteParams::from([("fileId", "a.rs")]);
+    /// let new = RouteParams::from([("fileId", "b.rs")]);
+    ///
+    /// let diff = RouteParams::diff(&old, &new);
+    /// assert_eq!(diff.changed["fileId"], ("a.rs".to_string(), "b.rs".to_string()));
+    /// assert!(diff.added.is_empty() && diff.removed.is_empty());
+    /// ```
+    pub fn diff(old: &RouteParams, new: &RouteParams) -> ParamsDiff {
+        let mut diff = ParamsDiff::default();
+        for (key, new_value) in &new.params {
+            match old.params.get(key) {
+                None => {
+                    diff.added.insert(key.clone(), new_value.clone());
+                }
+                Some(old_value) if old_value != new_value => {
+                    diff.changed
+                        .insert(key.clone(), (old_value.clone(), new_value.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, old_value) in &old.params {
+            if !new.params.contains_key(key) {
+                diff.removed.insert(key.clone(), old_value.clone());
+            }
+        }
+        diff
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for RouteParams {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(pairs: I) -> Self {
+        Self::from_map(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: Into<String>, V: Into<String>, const N: usize> From<[(K, V); N]> for RouteParams {
+    fn from(pairs: [(K, V); N]) -> Self {
+        pairs.into_iter().collect()
+    }
+}
+
+/// Build [`RouteParams`] from `key => value` pairs
+///
+/// Values are converted with `ToString`, so numbers and other displayable
+/// values can be passed as they are.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::route_params;
+///
+/// let params = route_params! { "id" => 42, "tab" => "posts" };
+/// assert_eq!(params.get_as::<u32>("id"), Some(42));
+/// assert_eq!(params.get("tab"), Some(&"posts".to_string()));
+/// assert!(route_params! {}.is_empty());
+/// ```
+#[macro_export]
+macro_rules! route_params {
+    () => {
+        $crate::RouteParams::new()
+    };
+    ($($key:expr => $value:expr),+ $(,)?) => {
+        $crate::RouteParams::from([$((
+            ::std::string::ToString::to_string(&$key),
+            ::std::string::ToString::to_string(&$value),
+        )),+])
+    };
+}
+
+/// Difference between two sets of route parameters
+///
+/// Created with [`RouteParams::diff`]. With the `serde` feature, serializes
+/// as `{"added":{..},"removed":{..},"changed":{"name":["old","new"]}}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParamsDiff {
+    /// Parameters only in the new set, with their values
+    pub added: HashMap<String, String>,
+    /// Parameters only in the old set, with their values
+    pub removed: HashMap<String, String>,
+    /// Parameters in both sets with different values, as `(old, new)`
+    pub changed: HashMap<String, (String, String)>,
+}
+
+impl ParamsDiff {
+    /// Check if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Check if a parameter was added, removed or changed
+    pub fn contains(&self, key: &str) -> bool {
+        self.added.contains_key(key)
+            || self.removed.contains_key(key)
+            || self.changed.contains_key(key)
+    }
 }
 
 // ============================================================================
@@ -114,9 +329,7 @@ mod tests {
 
     #[test]
     fn test_route_params_get_as() {
-        let mut params = RouteParams::new();
-        params.insert("id".to_string(), "123".to_string());
-        params.insert("active".to_string(), "true".to_string());
+        let params = RouteParams::from([("id", "123"), ("active", "true")]);
 
         assert_eq!(params.get_as::<i32>("id"), Some(123));
         assert_eq!(params.get_as::<u32>("id"), Some(123));
@@ -146,9 +359,7 @@ mod tests {
 
     #[test]
     fn test_route_params_all() {
-        let mut params = RouteParams::new();
-        params.insert("a".to_string(), "1".to_string());
-        params.insert("b".to_string(), "2".to_string());
+        let params = RouteParams::from([("a", "1"), ("b", "2")]);
 
         let all = params.all();
         assert_eq!(all.len(), 2);
@@ -157,14 +368,25 @@ mod tests {
 
     #[test]
     fn test_route_params_iter() {
-        let mut params = RouteParams::new();
-        params.insert("x".to_string(), "1".to_string());
-        params.insert("y".to_string(), "2".to_string());
+        let params = RouteParams::from([("x", "1"), ("y", "2")]);
 
         let count = params.iter().count();
         assert_eq!(count, 2);
     }
 
+    #[test]
+    fn test_route_params_diff() {
+        let old = RouteParams::from([("id", "1"), ("tab", "info")]);
+        let new = RouteParams::from([("id", "2"), ("page", "3")]);
+
+        let diff = RouteParams::diff(&old, &new);
+        assert_eq!(diff.changed["id"], ("1".to_string(), "2".to_string()));
+        assert_eq!(diff.removed["tab"], "info");
+        assert_eq!(diff.added["page"], "3");
+        assert!(diff.contains("tab") && !diff.contains("missing"));
+        assert!(RouteParams::diff(&new, &new).is_empty());
+    }
+
     #[test]
     fn test_route_params_empty() {
         let params = RouteParams::new();
@@ -176,6 +398,24 @@ mod tests {
         assert!(!params.is_empty());
         assert_eq!(params.len(), 1);
     }
+
+    #[test]
+    fn test_route_params_constructors() {
+        let params = RouteParams::from([("id", "42"), ("tab", "posts")]);
+        assert_eq!(params.get("id"), Some(&"42".to_string()));
+        assert_eq!(params.len(), 2);
+
+        let collected: RouteParams = vec![("id".to_string(), "42")].into_iter().collect();
+        assert_eq!(collected.all(), RouteParams::single("id", 42).all());
+
+        let built = crate::route_params! { "id" => 42, "tab" => "posts", };
+        assert_eq!(built.all(), params.all());
+        // Later pairs win, like `insert`
+        assert_eq!(
+            RouteParams::from([("id", "1"), ("id", "2")]).get_as::<u8>("id"),
+            Some(2)
+        );
+    }
 }
 
 // ============================================================================
@@ -197,6 +437,13 @@ mod tests {
 /// assert_eq!(query.get_as::<i32>("page"), Some(1));
 /// assert_eq!(query.get_all("tag").unwrap().len(), 2);
 /// ```
+///
+/// Like [`RouteParams`], query params can be collected from pairs or built
+/// with [`query_params!`](crate::query_params); repeated keys keep every
+/// value.
+///
+/// With the `serde` feature, serializes as a map of names to all their
+/// values (`{"tag":["rust","gpui"]}`).
 #[derive(Debug, Clone, Default)]
 pub struct QueryParams {
     params: HashMap<String, Vec<String>>,
@@ -275,8 +522,7 @@ impl QueryParams {
     /// ```
     /// use gpui_navigator::QueryParams;
     ///
-    /// let mut query = QueryParams::new();
-    /// query.insert("page".to_string(), "1".to_string());
+    /// let query = QueryParams::from([("page", "1")]);
     /// let s = query.to_query_string();
     /// assert!(s.contains("page=1"));
     /// ```
@@ -307,10 +553,95 @@ impl QueryParams {
     pub fn len(&self) -> usize {
         self.params.len()
     }
+
+    /// Iterate over all parameters with all their values
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.params.iter()
+    }
+
+    /// Get the first value of each parameter
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.params
+            .iter()
+            .filter_map(|(key, values)| Some((key.clone(), values.first()?.clone())))
+            .collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for QueryParams {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(pairs: I) -> Self {
+        let mut query = Self::new();
+        for (key, value) in pairs {
+            query.insert(key.into(), value.into());
+        }
+        query
+    }
+}
+
+impl<K: Into<String>, V: Into<String>, const N: usize> From<[(K, V); N]> for QueryParams {
+    fn from(pairs: [(K, V); N]) -> Self {
+        pairs.into_iter().collect()
+    }
+}
+
+/// Build [`QueryParams`] from `key => value` pairs
+///
+/// Values are converted with `ToString`; a key given several times keeps all
+/// its values.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::query_params;
+///
+/// let query = query_params! { "page" => 2, "tag" => "rust", "tag" => "gpui" };
+/// assert_eq!(query.get_as::<u32>("page"), Some(2));
+/// assert_eq!(query.get_all("tag").unwrap().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! query_params {
+    () => {
+        $crate::QueryParams::new()
+    };
+    ($($key:expr => $value:expr),+ $(,)?) => {
+        $crate::QueryParams::from([$((
+            ::std::string::ToString::to_string(&$key),
+            ::std::string::ToString::to_string(&$value),
+        )),+])
+    };
+}
+
+/// Carry query params of the `from` path over to the `to` path
+///
+/// Pairs of `from` whose key passes `keep` are appended to `to` as written,
+/// unless `to` already sets that key.
+pub(crate) fn preserve_query(from: &str, to: &str, keep: impl Fn(&str) -> bool) -> String {
+    let Some((_, from_query)) = from.split_once('?') else {
+        return to.to_string();
+    };
+    let target = QueryParams::from_query_string(to.split_once('?').map_or("", |(_, query)| query));
+
+    let preserved: Vec<&str> = from_query
+        .split('&')
+        .filter(|pair| {
+            let key = decode_uri_component(pair.split_once('=').map_or(pair, |(key, _)| key));
+            !pair.is_empty() && keep(&key) && !target.contains(&key)
+        })
+        .collect();
+    if preserved.is_empty() {
+        return to.to_string();
+    }
+
+    let separator = match to.find('?') {
+        None => "?",
+        Some(_) if to.ends_with('?') || to.ends_with('&') => "",
+        Some(_) => "&",
+    };
+    format!("{}{}{}", to, separator, preserved.join("&"))
 }
 
 /// Simple URI component encoding (encode special characters)
-fn encode_uri_component(s: &str) -> String {
+pub(crate) fn encode_uri_component(s: &str) -> String {
     s.chars()
         .map(|c| match c {
             'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_' | '.' | '~' => c.to_string(),
@@ -435,8 +766,48 @@ fn test_query_params_empty() {
     assert_eq!(query.len(), 1);
 }
 
+#[test]
+fn test_preserve_query() {
+    let keep_theme = |key: &str| key == "theme";
+
+    assert_eq!(
+        preserve_query("/a?theme=dark&page=2", "/b", keep_theme),
+        "/b?theme=dark"
+    );
+    // Keys set by the target win
+    assert_eq!(
+        preserve_query("/a?theme=dark", "/b?theme=light", keep_theme),
+        "/b?theme=light"
+    );
+    assert_eq!(
+        preserve_query("/a?theme=dark%20blue&debug", "/b?page=1", |_| true),
+        "/b?page=1&theme=dark%20blue&debug"
+    );
+    assert_eq!(preserve_query("/a", "/b", |_| true), "/b");
+}
+
+#[test]
+fn test_query_params_to_map() {
+    let query = QueryParams::from_query_string("tag=rust&tag=gpui&page=1");
+    let map = query.to_map();
+    assert_eq!(map.get("tag"), Some(&"rust".to_string()));
+    assert_eq!(map.get("page"), Some(&"1".to_string()));
+}
+
 #[test]
 fn test_empty_query_string() {
     let query = QueryParams::from_query_string("");
     assert!(query.is_empty());
 }
+
+#[test]
+fn test_query_params_constructors() {
+    let query = QueryParams::from([("tag", "rust"), ("tag", "gpui"), ("page", "1")]);
+    assert_eq!(query.get_all("tag").unwrap(), &["rust", "gpui"]);
+    assert_eq!(query.len(), 2);
+
+    let built = crate::query_params! { "tag" => "rust", "tag" => "gpui", "page" => 1 };
+    assert_eq!(built.get_all("tag"), query.get_all("tag"));
+    assert_eq!(built.get("page"), query.get("page"));
+    assert!(crate::query_params! {}.is_empty());
+}
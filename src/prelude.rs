@@ -0,0 +1,67 @@
+//! Commonly used types, for a single glob import
+//!
+//! The prelude holds the stable core every app needs, the same under any
+//! feature set, plus a sub-prelude per optional feature that is glob
+//! imported along with it when the feature is enabled:
+//!
+//! ```
+//! use gpui_navigator::prelude::*;
+//!
+//! let route = Route::new("/users/:id", |_, _, params: &RouteParams| {
+//!     gpui::div().into_any_element()
+//! });
+//! assert_eq!(route.config.path, "/users/:id");
+//! # use gpui::IntoElement;
+//! ```
+//!
+//! Unlike `use gpui_navigator::*`, which brings in every public name of the
+//! enabled features, the prelude only grows when a type becomes part of the
+//! everyday API. Code that has to build under several feature sets can name
+//! the sub-preludes (`prelude::guard`, `prelude::middleware`,
+//! `prelude::transition`, `prelude::cache`) behind the matching `cfg`.
+
+pub use crate::{
+    init_router, GlobalRouter, IntoRoute, NavigationDirection, NavigationResult, NavigationSource,
+    Navigator, PopOutcome, QueryParams, Route, RouteChangeEvent, RouteMatch, RouteParams,
+    RouterConfig, RouterLink, RouterOutlet, UseRouter,
+};
+
+#[cfg(feature = "cache")]
+pub use self::cache::*;
+#[cfg(feature = "guard")]
+pub use self::guard::*;
+#[cfg(feature = "middleware")]
+pub use self::middleware::*;
+#[cfg(feature = "transition")]
+pub use self::transition::*;
+
+/// Route guards, with the `guard` feature
+#[cfg(feature = "guard")]
+#[cfg_attr(docsrs, doc(cfg(feature = "guard")))]
+pub mod guard {
+    pub use crate::{
+        guard_fn, AccessCheck, AuthGuard, GuardResult, NavigationRequest, PermissionGuard,
+        RoleGuard, RouteGuard, TransitionGuard,
+    };
+}
+
+/// Navigation middleware, with the `middleware` feature
+#[cfg(feature = "middleware")]
+#[cfg_attr(docsrs, doc(cfg(feature = "middleware")))]
+pub mod middleware {
+    pub use crate::{middleware_fn, AfterNavigation, NavigationRequest, RouteMiddleware};
+}
+
+/// Route transitions, with the `transition` feature
+#[cfg(feature = "transition")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transition")))]
+pub mod transition {
+    pub use crate::{SlideDirection, Transition, TransitionProgress};
+}
+
+/// Route resolution cache, with the `cache` feature
+#[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+pub mod cache {
+    pub use crate::CacheStats;
+}
@@ -0,0 +1,231 @@
+//! Relative navigation paths
+//!
+//! Paths starting with `./` or `../` resolve against the current route:
+//!
+//! ```ignore
+//! use gpui_navigator::{NavOptions, Navigator};
+//!
+//! // On /shops/5/items/9, with `items/:item` a child of `/shops/:shop`
+//! Navigator::push(cx, "./details"); // /shops/5/items/9/details
+//! Navigator::push(cx, "../reviews"); // /shops/5/reviews
+//!
+//! // Bare paths are only relative when asked for
+//! Navigator::push_opts(cx, "details", NavOptions::new().relative(true));
+//! ```
+//!
+//! Each `../` steps up one level of the matched route chain, not one path
+//! segment, so the params of the levels above stay as they are. Stepping
+//! above the top-level route stops at `/`.
+
+use crate::nested::{build_child_path, path_segments};
+use crate::route::{match_route_chain, RouteRef};
+
+/// Check if `path` starts with `./` or `../` (or is `.` or `..`)
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::relative::is_relative;
+///
+/// assert!(is_relative("../settings"));
+/// assert!(is_relative("./details"));
+/// assert!(is_relative(".."));
+/// assert!(!is_relative("/settings"));
+/// assert!(!is_relative("settings"));
+/// assert!(!is_relative(".hidden"));
+/// ```
+pub fn is_relative(path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or(path);
+    path == "." || path == ".." || path.starts_with("./") || path.starts_with("../")
+}
+
+/// Resolve `path` against the levels of the current route
+///
+/// `levels` are the concrete paths of the routes in the matched chain,
+/// outermost first, e.g. `["/shops/5", "/shops/5/items/9"]`; the root `/`
+/// is implied above them. `./` resolves against the last level and each
+/// `../` steps up one, stopping at the root. Paths without a leading dot
+/// resolve like `./<path>`, while absolute paths are returned as they are.
+/// The query string of `path` is kept and trailing slashes are dropped.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::relative::resolve_relative;
+///
+/// let levels = ["/shops/5".to_string(), "/shops/5/items/9".to_string()];
+/// assert_eq!(resolve_relative(&levels, "./details"), "/shops/5/items/9/details");
+/// assert_eq!(resolve_relative(&levels, "../reviews?sort=new"), "/shops/5/reviews?sort=new");
+/// assert_eq!(resolve_relative(&levels, "../../../.."), "/");
+/// ```
+pub fn resolve_relative(levels: &[String], path: &str) -> String {
+    if path.starts_with('/') {
+        return path.to_string();
+    }
+    let (mut rest, query) = match path.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (path, None),
+    };
+
+    let mut depth = levels.len();
+    loop {
+        if let Some(after) = rest.strip_prefix("./") {
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("../") {
+            depth = depth.saturating_sub(1);
+            rest = after;
+        } else if rest == "." {
+            rest = "";
+        } else if rest == ".." {
+            depth = depth.saturating_sub(1);
+            rest = "";
+        } else {
+            break;
+        }
+    }
+
+    let base = depth
+        .checked_sub(1)
+        .map_or("/", |level| levels[level].as_str());
+    let resolved = build_child_path(base, rest.trim_start_matches('/'));
+    let resolved = if resolved.is_empty() { "/" } else { &resolved };
+    match query {
+        Some(query) => format!("{}?{}", resolved, query),
+        None => resolved.to_string(),
+    }
+}
+
+/// Concrete paths of the routes matching `path`, outermost first
+///
+/// Routes adding no segments (index routes, a `/` root) add no level. A
+/// path matching no route has one level per segment.
+pub(crate) fn route_levels(routes: &[RouteRef], path: &str) -> Vec<String> {
+    let segments = path_segments(path);
+    let prefix = |count: usize| format!("/{}", segments[..count].join("/"));
+
+    let Some((chain, _)) = match_route_chain(routes, path) else {
+        return (1..=segments.len()).map(prefix).collect();
+    };
+
+    let mut levels: Vec<String> = Vec::new();
+    let mut pattern = String::new();
+    for route in chain {
+        pattern = if pattern.is_empty() {
+            route.config.path.clone()
+        } else {
+            build_child_path(&pattern, &route.config.path).into_owned()
+        };
+        let pattern_segments = path_segments(&pattern);
+        let count = if pattern_segments.contains(&"*") {
+            segments.len()
+        } else {
+            pattern_segments.len().min(segments.len())
+        };
+        let level = prefix(count);
+        if count > 0 && levels.last() != Some(&level) {
+            levels.push(level);
+        }
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::page;
+    use std::sync::Arc;
+
+    fn levels() -> Vec<String> {
+        vec!["/shops/5".to_string(), "/shops/5/items/9".to_string()]
+    }
+
+    #[test]
+    fn test_resolve_current_level() {
+        let levels = levels();
+        assert_eq!(
+            resolve_relative(&levels, "./details"),
+            "/shops/5/items/9/details"
+        );
+        assert_eq!(
+            resolve_relative(&levels, "././details"),
+            "/shops/5/items/9/details"
+        );
+        assert_eq!(
+            resolve_relative(&levels, "details"),
+            "/shops/5/items/9/details"
+        );
+        assert_eq!(resolve_relative(&levels, "."), "/shops/5/items/9");
+        assert_eq!(resolve_relative(&levels, "./"), "/shops/5/items/9");
+    }
+
+    #[test]
+    fn test_resolve_parent_levels() {
+        let levels = levels();
+        assert_eq!(resolve_relative(&levels, "../reviews"), "/shops/5/reviews");
+        assert_eq!(resolve_relative(&levels, ".."), "/shops/5");
+        assert_eq!(resolve_relative(&levels, "../"), "/shops/5");
+        assert_eq!(
+            resolve_relative(&levels, "./../reviews"),
+            "/shops/5/reviews"
+        );
+        assert_eq!(resolve_relative(&levels, "../../cart"), "/cart");
+        assert_eq!(resolve_relative(&levels, "../.."), "/");
+    }
+
+    #[test]
+    fn test_resolve_clamps_at_root() {
+        let levels = levels();
+        assert_eq!(resolve_relative(&levels, "../../../../cart"), "/cart");
+        assert_eq!(resolve_relative(&levels, "../../../.."), "/");
+        assert_eq!(resolve_relative(&[], "../cart"), "/cart");
+        assert_eq!(resolve_relative(&[], "./cart"), "/cart");
+        assert_eq!(resolve_relative(&[], "."), "/");
+    }
+
+    #[test]
+    fn test_resolve_trailing_slashes_and_query() {
+        let levels = levels();
+        assert_eq!(resolve_relative(&levels, "../reviews/"), "/shops/5/reviews");
+        assert_eq!(
+            resolve_relative(&levels, "../reviews//"),
+            "/shops/5/reviews"
+        );
+        assert_eq!(
+            resolve_relative(&levels, "./details?tab=2"),
+            "/shops/5/items/9/details?tab=2"
+        );
+        assert_eq!(resolve_relative(&levels, "..?tab=2"), "/shops/5?tab=2");
+        assert_eq!(resolve_relative(&levels, "../a/b/"), "/shops/5/a/b");
+    }
+
+    #[test]
+    fn test_resolve_absolute_unchanged() {
+        let levels = levels();
+        assert_eq!(resolve_relative(&levels, "/settings"), "/settings");
+        assert_eq!(resolve_relative(&levels, "/"), "/");
+        assert_eq!(resolve_relative(&levels, "/a/../b"), "/a/../b");
+    }
+
+    #[test]
+    fn test_route_levels() {
+        let routes: Vec<RouteRef> = vec![
+            Arc::new(page("/")),
+            Arc::new(
+                page("/shops/:shop")
+                    .children(vec![Arc::new(page("")), Arc::new(page("items/:item"))]),
+            ),
+            Arc::new(page("/files/*")),
+        ];
+
+        assert_eq!(
+            route_levels(&routes, "/shops/5/items/9?tab=2"),
+            ["/shops/5", "/shops/5/items/9"]
+        );
+        // The index route adds no level
+        assert_eq!(route_levels(&routes, "/shops/5"), ["/shops/5"]);
+        assert!(route_levels(&routes, "/").is_empty());
+        assert_eq!(route_levels(&routes, "/files/a/b"), ["/files/a/b"]);
+        // Unknown paths step up one segment at a time
+        assert_eq!(route_levels(&routes, "/x/y"), ["/x", "/x/y"]);
+    }
+}
@@ -0,0 +1,325 @@
+//! Rendering a path without navigating
+//!
+//! Snapshot tests of pages want the element tree a path leads to without
+//! setting up history, guards and a window of outlets.
+//! [`Router::resolve_for_test`](crate::Router::resolve_for_test) matches a
+//! path against a route table without an `App`, and [`render_resolved`]
+//! builds the matched chain, the outlet of each layout showing the next route:
+//!
+//! ```ignore
+//! use gpui_navigator::{render_resolved, Route, Router};
+//!
+//! let mut router = Router::new();
+//! router.state_mut().add_route(Route::new("/orgs/:org", org_layout).children(vec![
+//!     Route::new("members", members).into(),
+//! ]));
+//! let resolved = router.resolve_for_test("/orgs/7/members?page=2").unwrap();
+//!
+//! let cx = cx.add_empty_window();
+//! cx.draw(point(px(0.), px(0.)), size(px(800.), px(600.)), |window, cx| {
+//!     render_resolved(window, cx, &resolved)
+//! });
+//! ```
+//!
+//! Only the route builders run: guards, middleware, loaders and transitions
+//! are skipped, and the outlets need no router global. Widgets of the pages
+//! that read the router, such as `RouterLink`, still do. Named outlets render
+//! nothing.
+//!
+//! The routes of the running router resolve the same way through
+//! [`RouterState::resolve_for_test`](crate::RouterState::resolve_for_test),
+//! as `GlobalRouter::state().resolve_for_test(path)`.
+
+use crate::error::NotFoundDetails;
+use crate::history::split_fragment;
+use crate::route::{chain_level_params, chain_pattern, match_route_chain, RouteRef};
+use crate::{QueryParams, RouteParams};
+use gpui::{
+    div, AnyElement, App, Bounds, Element, ElementId, Global, GlobalElementId, InspectorElementId,
+    IntoElement, LayoutId, Pixels, Window,
+};
+use std::sync::Arc;
+
+/// Route chain a path resolves to, see [`Router::resolve_for_test`]
+///
+/// [`Router::resolve_for_test`]: crate::Router::resolve_for_test
+#[derive(Debug, Clone)]
+pub struct ResolvedNavigation {
+    /// Path resolved, without its query and fragment
+    pub path: String,
+    /// Full pattern of the matched chain, e.g. `/orgs/:org/members`
+    pub pattern: String,
+    /// Query params of the path
+    pub query: QueryParams,
+    /// Routes matched, outermost first, pathless layouts included
+    pub chain: Vec<RouteRef>,
+    /// Params each route of `chain` is built with, as in an outlet: those
+    /// of its own level and of the levels above
+    pub params: Vec<RouteParams>,
+}
+
+impl ResolvedNavigation {
+    /// Resolve `path` against `routes`, or explain why nothing matches
+    pub(crate) fn resolve(routes: &[RouteRef], path: &str) -> Result<Self, NotFoundDetails> {
+        let (path, _) = split_fragment(path);
+        let (route_path, query) = path.split_once('?').unwrap_or((path, ""));
+        let Some((chain, route_match)) = match_route_chain(routes, route_path) else {
+            return Err(NotFoundDetails::new(routes, path));
+        };
+        let mut merged = RouteParams::new();
+        let params = chain_level_params(&chain, &route_match.params)
+            .into_iter()
+            .map(|level| {
+                for (key, value) in level.iter() {
+                    merged.insert(key.clone(), value.clone());
+                }
+                merged.clone()
+            })
+            .collect();
+        Ok(Self {
+            path: route_path.to_string(),
+            pattern: chain_pattern(&chain),
+            query: QueryParams::from_query_string(query),
+            chain: chain.into_iter().cloned().collect(),
+            params,
+        })
+    }
+}
+
+/// Render the routes of `resolved`, layouts first, without navigating
+///
+/// Each route's builder is called with its [`params`](ResolvedNavigation::params);
+/// the outlets layouts render show the next route of the chain. See the
+/// [module docs](self) for what is skipped.
+pub fn render_resolved(
+    window: &mut Window,
+    cx: &mut App,
+    resolved: &ResolvedNavigation,
+) -> AnyElement {
+    build_level(window, cx, &Arc::new(resolved.clone()), 0)
+}
+
+/// Content of an outlet rendered inside [`render_resolved`], if it is
+pub(crate) fn resolved_outlet(
+    window: &mut Window,
+    cx: &mut App,
+    name: Option<&str>,
+) -> Option<AnyElement> {
+    let frame = cx.try_global::<ResolvedFrame>()?.0.clone()?;
+    Some(match name {
+        Some(_) => div().into_any_element(),
+        None => build_level(window, cx, &frame.resolved, frame.depth),
+    })
+}
+
+/// Navigation being rendered and the level its outlets show next
+#[derive(Clone)]
+struct Frame {
+    resolved: Arc<ResolvedNavigation>,
+    depth: usize,
+}
+
+#[derive(Default)]
+struct ResolvedFrame(Option<Frame>);
+
+impl Global for ResolvedFrame {}
+
+/// Make `frame` the current one, returning the one it replaces
+fn set_frame(cx: &mut App, frame: Option<Frame>) -> Option<Frame> {
+    std::mem::replace(&mut cx.default_global::<ResolvedFrame>().0, frame)
+}
+
+/// Build the route at `depth` of the chain, its outlets showing the next one
+///
+/// A route without a builder shows the next one itself.
+fn build_level(
+    window: &mut Window,
+    cx: &mut App,
+    resolved: &Arc<ResolvedNavigation>,
+    depth: usize,
+) -> AnyElement {
+    let Some(route) = resolved.chain.get(depth) else {
+        return div().into_any_element();
+    };
+    let frame = Frame {
+        resolved: Arc::clone(resolved),
+        depth: depth + 1,
+    };
+    let outer = set_frame(cx, Some(frame.clone()));
+    let content = match route.build(window, cx, &resolved.params[depth]) {
+        Some(content) => content,
+        None => build_level(window, cx, resolved, depth + 1),
+    };
+    set_frame(cx, outer);
+    ResolvedLevel { frame, content }.into_any_element()
+}
+
+/// Content of one level, whose outlet views render the next when laid out
+struct ResolvedLevel {
+    frame: Frame,
+    content: AnyElement,
+}
+
+impl IntoElement for ResolvedLevel {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for ResolvedLevel {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let outer = set_frame(cx, Some(self.frame.clone()));
+        let layout_id = self.content.request_layout(window, cx);
+        set_frame(cx, outer);
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let outer = set_frame(cx, Some(self.frame.clone()));
+        self.content.prepaint(window, cx);
+        set_frame(cx, outer);
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        self.content.paint(window, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render_router_outlet, Route, Router, RouterOutlet};
+    use gpui::{
+        point, px, size, AppContext, Context, InteractiveElement, ParentElement, Render,
+        TestAppContext,
+    };
+    use std::sync::Mutex;
+
+    type BuildLog = Arc<Mutex<Vec<String>>>;
+
+    /// Org layout with an outlet view, a members layout with an outlet
+    /// function and a member page, logging the params each is built with
+    fn org_router(log: &BuildLog) -> Router {
+        let logged = |name: &'static str, params: &RouteParams, log: &BuildLog| {
+            let mut params: Vec<_> = params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            params.sort();
+            log.lock()
+                .unwrap()
+                .push(format!("{name} {}", params.join(",")));
+        };
+        let (org, members, member) = (log.clone(), log.clone(), log.clone());
+        let mut router = Router::new();
+        router.state_mut().add_route(
+            Route::new("/orgs/:org{int}", move |_, cx, params| {
+                logged("org", params, &org);
+                div()
+                    .child(cx.new(|_| RouterOutlet::new()))
+                    .child(cx.new(|_| RouterOutlet::named("sidebar")))
+            })
+            .children(vec![Route::new("members", move |window, cx, params| {
+                logged("members", params, &members);
+                div().child(render_router_outlet(window, cx, None))
+            })
+            .children(vec![Route::new(":member", move |_, _, params| {
+                logged("member", params, &member);
+                let id = params.get("member").cloned().unwrap_or_default();
+                div().debug_selector(move || format!("member {id}"))
+            })
+            .into()])
+            .into()]),
+        );
+        router
+    }
+
+    #[test]
+    fn test_resolve_nested_parameterized_path() {
+        let router = org_router(&BuildLog::default());
+        let resolved = router
+            .resolve_for_test("/orgs/7/members/3?tab=roles#top")
+            .unwrap();
+
+        assert_eq!(resolved.path, "/orgs/7/members/3");
+        assert_eq!(resolved.pattern, "/orgs/:org{int}/members/:member");
+        assert_eq!(resolved.query.get("tab").map(String::as_str), Some("roles"));
+        let paths: Vec<_> = resolved
+            .chain
+            .iter()
+            .map(|route| route.config.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["/orgs/:org{int}", "members", ":member"]);
+        let params: Vec<_> = resolved.params.iter().map(RouteParams::len).collect();
+        assert_eq!(params, vec![1, 1, 2]);
+        assert_eq!(resolved.params[2].get("org").map(String::as_str), Some("7"));
+
+        let missing = router.resolve_for_test("/orgs/7/teams").unwrap_err();
+        assert_eq!(missing.matched_prefix, "/orgs/7");
+        assert_eq!(missing.failing_segment.as_deref(), Some("teams"));
+    }
+
+    struct Snapshot(ResolvedNavigation);
+
+    impl Render for Snapshot {
+        fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+            render_resolved(window, cx, &self.0)
+        }
+    }
+
+    #[gpui::test]
+    fn test_render_resolved_without_router(cx: &mut TestAppContext) {
+        let log = BuildLog::default();
+        let resolved = org_router(&log)
+            .resolve_for_test("/orgs/7/members/3")
+            .unwrap();
+
+        let (_, cx) = cx.add_window_view(|_, _| Snapshot(resolved.clone()));
+        cx.run_until_parked();
+        let built = vec!["org org=7", "members org=7", "member member=3,org=7"];
+        assert!(cx.debug_bounds("member 3").is_some());
+        assert_eq!(*log.lock().unwrap(), built);
+
+        // Drawn directly too, the view being redrawn after it
+        log.lock().unwrap().clear();
+        cx.draw(
+            point(px(0.), px(0.)),
+            size(px(800.), px(600.)),
+            |window, cx| render_resolved(window, cx, &resolved),
+        );
+        assert_eq!(log.lock().unwrap()[..3], built);
+    }
+}
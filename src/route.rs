@@ -1,17 +1,28 @@
 //! Route definition and configuration
 
+use crate::error::{close_matches, ErrorBoundary, RetryHandle, RouteError};
+use crate::flow::FlowStep;
 #[cfg(feature = "guard")]
 use crate::guards::BoxedGuard;
+use crate::keep_alive::{component_entity, EvictCallback, KeepAlive, ReuseStrategy};
 use crate::lifecycle::BoxedLifecycle;
+use crate::loader::{loader_fn, LoaderData, LoaderFn, LoaderMode};
+use crate::matcher::{CompiledPattern, UrlForError};
+use crate::meta::{MetaValue, TypedMeta};
 #[cfg(feature = "middleware")]
 use crate::middleware::BoxedMiddleware;
-use crate::params::RouteParams;
+use crate::nested::{build_child_path, is_absolute_child, is_index_route, path_segments};
+use crate::params::{QueryParams, RouteParams};
+use crate::scope::{RouteState, StateDispose};
 #[cfg(feature = "transition")]
 use crate::transition::TransitionConfig;
-use crate::RouteMatch;
-use gpui::{AnyElement, App, IntoElement, Render, Window};
-use std::collections::HashMap;
-use std::sync::Arc;
+use crate::window_hints::WindowHints;
+use crate::{error_log, warn_log, RouteMatch};
+use gpui::{AnyElement, App, IntoElement, Render, SharedString, Window};
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
 
 // ============================================================================
 // NamedRouteRegistry
@@ -20,8 +31,16 @@ use std::sync::Arc;
 /// Registry for named routes
 #[derive(Clone, Debug, Default)]
 pub struct NamedRouteRegistry {
-    /// Map of route names to path patterns
-    routes: HashMap<String, String>,
+    /// Map of route names to compiled path patterns
+    routes: HashMap<String, CompiledPattern>,
+    /// Map of route names to the registered routes
+    route_refs: HashMap<String, RouteRef>,
+    /// Whether URLs are refused for params their pattern has no place for
+    strict: bool,
+    /// Map of old route names to the names they were renamed to
+    aliases: HashMap<String, String>,
+    /// Aliases whose deprecation was logged, shared by clones
+    warned_aliases: Arc<Mutex<HashSet<String>>>,
 }
 
 impl NamedRouteRegistry {
@@ -29,48 +48,242 @@ impl NamedRouteRegistry {
     pub fn new() -> Self {
         Self {
             routes: HashMap::new(),
+            route_refs: HashMap::new(),
+            strict: false,
+            aliases: HashMap::new(),
+            warned_aliases: Arc::default(),
         }
     }
 
     /// Register a named route
-    pub fn register(&mut self, name: impl Into<String>, path: impl Into<String>) {
-        self.routes.insert(name.into(), path.into());
+    pub fn register(&mut self, name: impl Into<String>, path: impl IntoRoutePath) {
+        self.routes.insert(name.into(), compile_route_path(path));
+    }
+
+    /// Register a named route along with the route itself
+    ///
+    /// `path` should be the full path pattern (including parent segments for
+    /// nested routes).
+    pub fn register_route(
+        &mut self,
+        name: impl Into<String>,
+        path: impl IntoRoutePath,
+        route: RouteRef,
+    ) {
+        let name = name.into();
+        self.route_refs.insert(name.clone(), route);
+        self.routes.insert(name, compile_route_path(path));
+    }
+
+    /// Make `alias` an old name of the route named `name`
+    ///
+    /// Lookups through `alias` resolve to `name`, logging a deprecation
+    /// warning the first time each alias is used. Names of registered routes
+    /// take precedence over aliases.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::{NamedRouteRegistry, RouteParams};
+    ///
+    /// let mut registry = NamedRouteRegistry::new();
+    /// registry.register("users.detail", "/users/:id");
+    /// registry.register_alias("user.profile", "users.detail");
+    ///
+    /// let url = registry.try_url_for("user.profile", &RouteParams::single("id", "7"));
+    /// assert_eq!(url.unwrap(), "/users/7");
+    /// ```
+    pub fn register_alias(&mut self, alias: impl Into<String>, name: impl Into<String>) {
+        self.aliases.insert(alias.into(), name.into());
+    }
+
+    /// Old names of the route named `name`, sorted
+    pub fn aliases_of(&self, name: &str) -> Vec<&str> {
+        let mut aliases: Vec<&str> = self
+            .aliases
+            .iter()
+            .filter(|(_, target)| *target == name)
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        aliases.sort_unstable();
+        aliases
+    }
+
+    /// Name of the registered route `name` refers to, following an alias
+    fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        if self.routes.contains_key(name) {
+            return name;
+        }
+        match self.aliases.get(name) {
+            Some(target) => {
+                self.note_alias_use(name, target);
+                target
+            }
+            None => name,
+        }
+    }
+
+    /// Log the deprecation of `alias`, once per alias
+    ///
+    /// Returns whether it was logged now.
+    fn note_alias_use(&self, alias: &str, target: &str) -> bool {
+        let mut warned = self
+            .warned_aliases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if warned.contains(alias) {
+            return false;
+        }
+        warned.insert(alias.to_string());
+        warn_log!(
+            "Route name '{}' is deprecated, use '{}' instead",
+            alias,
+            target
+        );
+        true
+    }
+
+    /// Registered names and aliases close to `name`, closest first
+    pub fn suggestions(&self, name: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .routes
+            .keys()
+            .chain(self.aliases.keys())
+            .map(String::as_str)
+            .collect();
+        names.sort_unstable();
+        close_matches(name, names)
     }
 
     /// Get path pattern for a named route
     pub fn get(&self, name: &str) -> Option<&str> {
-        self.routes.get(name).map(|s| s.as_str())
+        self.pattern(name).map(CompiledPattern::as_str)
+    }
+
+    /// Get the compiled path pattern of a named route, constraints included
+    pub fn pattern(&self, name: &str) -> Option<&CompiledPattern> {
+        self.routes.get(self.resolve(name))
     }
 
-    /// Check if a route name exists
+    /// Get the route registered under a name
+    ///
+    /// Only available for routes registered with [`register_route`](Self::register_route).
+    pub fn get_route(&self, name: &str) -> Option<&RouteRef> {
+        self.route_refs.get(self.resolve(name))
+    }
+
+    /// List parameters of a named route's pattern that are missing from `params`
+    ///
+    /// Returns `None` if the name is not registered.
+    pub fn missing_params(&self, name: &str, params: &RouteParams) -> Option<Vec<String>> {
+        let pattern = self.get(name)?;
+        Some(
+            pattern_param_names(pattern)
+                .filter(|param| !params.contains(param))
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// Names of the registered routes, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.routes.keys().map(String::as_str)
+    }
+
+    /// Check if a route name exists, or is an alias of one
     pub fn contains(&self, name: &str) -> bool {
         self.routes.contains_key(name)
+            || self
+                .aliases
+                .get(name)
+                .is_some_and(|target| self.routes.contains_key(target))
     }
 
     /// Generate URL for a named route with parameters
     ///
+    /// Fails if the name is not registered, or if `params` cannot fill its
+    /// pattern: a required param is missing or a value does not satisfy its
+    /// param's constraint (see [`CompiledPattern::expand`]). Params the
+    /// pattern has no place for go into the query string, unless the
+    /// registry is strict (see [`set_strict`](Self::set_strict)). Unknown names are logged as
+    /// errors with the close registered names.
+    ///
     /// # Example
     ///
     /// ```
+    /// use gpui_navigator::matcher::{Constraint, UrlForError};
     /// use gpui_navigator::{NamedRouteRegistry, RouteParams};
     ///
     /// let mut registry = NamedRouteRegistry::new();
-    /// registry.register("user.detail", "/users/:id");
-    ///
-    /// let mut params = RouteParams::new();
-    /// params.set("id".to_string(), "123".to_string());
-    ///
-    /// let url = registry.url_for("user.detail", &params).unwrap();
-    /// assert_eq!(url, "/users/123");
+    /// registry.register("user.detail", "/users/:id{int}");
+    ///
+    /// let url = registry.try_url_for("user.detail", &RouteParams::single("id", "123"));
+    /// assert_eq!(url.unwrap(), "/users/123");
+    ///
+    /// let error = registry.try_url_for("user.detail", &RouteParams::single("id", "abc"));
+    /// assert_eq!(
+    ///     error,
+    ///     Err(UrlForError::ConstraintViolation {
+    ///         param: "id".to_string(),
+    ///         constraint: Constraint::Numeric,
+    ///         value: "abc".to_string(),
+    ///     })
+    /// );
     /// ```
+    pub fn try_url_for(&self, name: &str, params: &RouteParams) -> Result<String, UrlForError> {
+        let Some(pattern) = self.pattern(name) else {
+            error_log!("{}", self.unknown_name_message(name));
+            return Err(UrlForError::UnknownRoute(name.to_string()));
+        };
+        if self.strict {
+            pattern.expand_strict(params)
+        } else {
+            pattern.expand(params)
+        }
+    }
+
+    /// Generate URL for a named route with parameters
+    ///
+    /// Returns `None` where [`try_url_for`](Self::try_url_for) fails.
+    #[deprecated(
+        since = "0.1.4",
+        note = "use `try_url_for`, which says why no URL was generated"
+    )]
     pub fn url_for(&self, name: &str, params: &RouteParams) -> Option<String> {
-        let pattern = self.get(name)?;
-        Some(substitute_params(pattern, params))
+        self.try_url_for(name, params).ok()
+    }
+
+    /// Refuse URLs for params their route's pattern has no place for
+    /// (default: false, they are appended as a query string)
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Whether URLs are refused for params their pattern has no place for
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Describe a lookup of the unregistered name `name`
+    fn unknown_name_message(&self, name: &str) -> String {
+        let suggestions = self.suggestions(name);
+        if suggestions.is_empty() {
+            format!("Unknown route name '{}'", name)
+        } else {
+            format!(
+                "Unknown route name '{}', did you mean '{}'?",
+                name,
+                suggestions.join("', '")
+            )
+        }
     }
 
     /// Clear all registered routes
+    ///
+    /// Aliases are kept, for the routes registered again.
     pub fn clear(&mut self) {
         self.routes.clear();
+        self.route_refs.clear();
     }
 
     /// Get number of registered routes
@@ -84,89 +297,437 @@ impl NamedRouteRegistry {
     }
 }
 
-/// Substitute route parameters in a path pattern
-///
-/// Replaces `:param` with actual values from RouteParams
-fn substitute_params(pattern: &str, params: &RouteParams) -> String {
-    let mut result = pattern.to_string();
-
-    // Replace :param with actual values
-    for (key, value) in params.iter() {
-        let placeholder = format!(":{}", key);
-        result = result.replace(&placeholder, value);
+/// Compile a registered path, reusing the segments of a checked path
+fn compile_route_path(path: impl IntoRoutePath) -> CompiledPattern {
+    match path.into_route_path() {
+        (_, Some(pattern)) => pattern.into(),
+        (path, None) => CompiledPattern::parse(&path),
     }
+}
 
-    result
+/// Names of the required parameters in a path pattern
+///
+/// Constraints (`:id{uuid}`, `:id<\d+>`) are stripped; optional
+/// parameters (`:id?`) are skipped.
+fn pattern_param_names(pattern: &str) -> impl Iterator<Item = &str> {
+    pattern.split('/').filter_map(|segment| {
+        let param = segment.strip_prefix(':')?;
+        if param.ends_with('?') {
+            return None;
+        }
+        let end = param.find(['{', '<']).unwrap_or(param.len());
+        Some(&param[..end])
+    })
 }
 
 // ============================================================================
 // Route Validation
 // ============================================================================
 
-/// Validate a route path pattern
+// Shared with `route_path!`, which rejects the same paths at compile time
+pub use gpui_navigator_paths::validate_route_path;
+
+/// How serious a [`RouteIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// The route tree cannot work as written; the route is not registered
+    Error,
+    /// The route tree works, but likely not as intended
+    Warning,
+}
+
+/// A problem in a route tree, found when it is registered
 ///
-/// Returns an error message if the path is invalid, None otherwise.
+/// See [`validate_route_tree`] and
+/// [`GlobalRouter::route_issues`](crate::GlobalRouter::route_issues).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteIssue {
+    /// How serious the problem is
+    pub severity: IssueSeverity,
+    /// Full path of the route the problem is in
+    pub path: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl RouteIssue {
+    fn error(path: &str, message: String) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            path: path.to_string(),
+            message,
+        }
+    }
+
+    fn warning(path: &str, message: String) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            path: path.to_string(),
+            message,
+        }
+    }
+
+    /// Check if the route tree cannot be registered because of this issue
+    pub fn is_error(&self) -> bool {
+        self.severity == IssueSeverity::Error
+    }
+}
+
+impl std::fmt::Display for RouteIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Check `route` and all its descendants, named outlet children included
 ///
-/// # Validation Rules
+/// Errors:
+/// - a path [`validate_route_path`] rejects (e.g. `a//b` set on a config)
+/// - an absolute child path not under its parent's full path
+/// - a route that is its own ancestor, reported with the chain of paths
+/// - a tree nested deeper than [`DEFAULT_MAX_ROUTE_DEPTH`] levels (see
+///   [`RouterConfig::max_route_depth`](crate::RouterConfig::max_route_depth))
 ///
-/// - Path can be empty (for index routes)
-/// - Path must start with '/' or be relative (no leading '/')
-/// - No consecutive slashes ('//')
-/// - Trailing slashes are allowed but not recommended (normalized internally)
-/// - Parameter names must be alphanumeric and not empty
-/// - No duplicate parameter names
-pub fn validate_route_path(path: &str) -> Result<(), String> {
-    // Empty path is allowed for index routes
-    if path.is_empty() {
-        return Ok(());
-    }
-
-    // Consecutive slashes check
-    if path.contains("//") {
-        return Err("Route path cannot contain consecutive slashes".to_string());
-    }
-
-    // Note: Trailing slashes are allowed for compatibility
-    // They are normalized during route matching
-
-    // Extract and validate parameters
-    let mut param_names = std::collections::HashSet::new();
-    for segment in path.split('/') {
-        if let Some(param) = segment.strip_prefix(':') {
-            // Check parameter name is not empty
-            if param.is_empty() {
-                return Err("Route parameter name cannot be empty".to_string());
-            }
+/// Warnings:
+/// - several children resolving for an empty remainder (index routes with
+///   an empty or `index` path), in the default or a named outlet, where only
+///   the first one is ever shown
+/// - named outlets of a route with [`Route::declares_outlets`] that its
+///   builder does not render
+///
+/// `GlobalRouter::add_route` runs this on every route it registers.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui_navigator::{validate_route_tree, Route};
+/// use gpui::*;
+///
+/// let route = Route::new("/settings", |_, _cx, _params| div())
+///     .child(Route::new("", |_, _cx, _params| div()).into())
+///     .child(Route::new("index", |_, _cx, _params| div()).into());
+/// let issues = validate_route_tree(&route);
+/// assert_eq!(issues.len(), 1);
+/// assert!(!issues[0].is_error());
+/// ```
+pub fn validate_route_tree(route: &Route) -> Vec<RouteIssue> {
+    route_tree_issues(route, DEFAULT_MAX_ROUTE_DEPTH)
+}
 
-            // Check for constraint syntax (:id{uuid})
-            let param_name = if let Some(pos) = param.find('{') {
-                &param[..pos]
-            } else {
-                param
-            };
+/// Levels a route tree may have by default, its top-level route included
+pub const DEFAULT_MAX_ROUTE_DEPTH: usize = 32;
+
+/// Levels lookups descend through before giving up
+///
+/// Registration refuses trees deeper than
+/// [`RouterConfig::max_route_depth`](crate::RouterConfig::max_route_depth),
+/// which is capped here, so only trees that skipped it can reach this.
+pub(crate) const ROUTE_LOOKUP_DEPTH: usize = 256;
+
+/// Whether a lookup reached `depth` levels down to `path`, logging an error if so
+///
+/// Recursive lookups stop there rather than overflow the stack on a tree
+/// that is cyclic or pathologically deep.
+pub(crate) fn lookup_too_deep(depth: usize, path: &str) -> bool {
+    if depth < ROUTE_LOOKUP_DEPTH {
+        return false;
+    }
+    error_log!(
+        "Route lookup stopped at '{}': the route tree is deeper than {} levels",
+        path,
+        ROUTE_LOOKUP_DEPTH
+    );
+    true
+}
+
+/// Issues of the tree of `route`, allowing `max_depth` levels
+pub(crate) fn route_tree_issues(route: &Route, max_depth: usize) -> Vec<RouteIssue> {
+    let mut issues = Vec::new();
+    let mut walk = TreeWalk {
+        ancestors: Vec::new(),
+        max_depth,
+        issues: &mut issues,
+    };
+    walk.visit(route, "");
+    issues
+}
+
+/// State of the validation walk down a route tree
+struct TreeWalk<'a> {
+    /// Address and full path of each route above the one visited
+    ancestors: Vec<(*const Route, String)>,
+    /// Levels the tree may have
+    max_depth: usize,
+    issues: &'a mut Vec<RouteIssue>,
+}
+
+impl TreeWalk<'_> {
+    /// Check `route`, whose parent's full path is `parent_path`, and its descendants
+    ///
+    /// Children that are their own ancestors and levels past the maximum
+    /// depth are reported and not descended into.
+    fn visit(&mut self, route: &Route, parent_path: &str) {
+        let full_path = build_child_path(parent_path, &route.config.path);
+        if self.ancestors.len() >= self.max_depth {
+            self.issues.push(RouteIssue::error(
+                &full_path,
+                format!(
+                    "Route '{}' is nested deeper than {} levels",
+                    full_path, self.max_depth
+                ),
+            ));
+            return;
+        }
+        self.ancestors
+            .push((route as *const Route, full_path.to_string()));
+        collect_route_issues(route, &full_path, self);
+        self.ancestors.pop();
+    }
+
+    /// Report `child` of the route at `full_path` if it is its own ancestor
+    fn is_cycle(&mut self, child: &RouteRef, full_path: &str) -> bool {
+        let Some(start) = self
+            .ancestors
+            .iter()
+            .position(|(ancestor, _)| std::ptr::eq(*ancestor, Arc::as_ptr(child)))
+        else {
+            return false;
+        };
+        let chain: Vec<&str> = self.ancestors[start..]
+            .iter()
+            .map(|(_, path)| path.as_str())
+            .chain([self.ancestors[start].1.as_str()])
+            .collect();
+        self.issues.push(RouteIssue::error(
+            full_path,
+            format!(
+                "Route '{}' is its own ancestor: {}",
+                self.ancestors[start].1,
+                chain.join(" -> ")
+            ),
+        ));
+        true
+    }
+}
 
-            // Check parameter name is alphanumeric
-            if !param_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                return Err(format!(
-                    "Route parameter '{}' must contain only alphanumeric characters and underscores",
-                    param_name
+/// Add the issues of `route`, whose full path is `full_path`, and of its
+/// descendants to the walk's issues
+fn collect_route_issues(route: &Route, full_path: &str, walk: &mut TreeWalk<'_>) {
+    if let Err(e) = validate_route_path(&route.config.path) {
+        walk.issues.push(RouteIssue::error(
+            full_path,
+            format!("Invalid route path '{}': {}", route.config.path, e),
+        ));
+    }
+
+    let mut outlets: Vec<&str> = route.named_outlet_names();
+    outlets.sort_unstable();
+    let groups = std::iter::once((None, route.get_children())).chain(outlets.iter().map(|name| {
+        (
+            Some(*name),
+            route.get_named_children(name).unwrap_or_default(),
+        )
+    }));
+    for (outlet, children) in groups {
+        let index_routes: Vec<&str> = children
+            .iter()
+            .filter(|child| is_index_route(child))
+            .map(|child| child.config.path.as_str())
+            .collect();
+        if index_routes.len() > 1 {
+            let outlet = outlet.map_or_else(
+                || "default outlet".to_string(),
+                |name| format!("outlet '{}'", name),
+            );
+            walk.issues.push(RouteIssue::warning(
+                full_path,
+                format!(
+                    "Route '{}' has {} index routes in its {} ({}); only the first is shown",
+                    full_path,
+                    index_routes.len(),
+                    outlet,
+                    index_routes
+                        .iter()
+                        .map(|path| format!("'{}'", path))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ));
+        }
+
+        for child in children {
+            let child_path = &child.config.path;
+            if !path_segments(child_path).is_empty()
+                && child_path.starts_with('/')
+                && !is_absolute_child(full_path, child_path)
+            {
+                walk.issues.push(RouteIssue::error(
+                    full_path,
+                    format!(
+                        "Absolute child path '{}' is not under its parent '{}'",
+                        child_path, full_path
+                    ),
                 ));
             }
+            if !walk.is_cycle(child, full_path) {
+                walk.visit(child, full_path);
+            }
+        }
+    }
 
-            // Check for duplicate parameters
-            if !param_names.insert(param_name.to_string()) {
-                return Err(format!("Duplicate route parameter: '{}'", param_name));
+    if let Some(declared) = &route.declared_outlets {
+        for name in outlets {
+            if !declared.iter().any(|declared| declared == name) {
+                walk.issues.push(RouteIssue::warning(
+                    full_path,
+                    format!(
+                        "Named outlet '{}' of '{}' is not rendered by its builder (declared: {})",
+                        name,
+                        full_path,
+                        declared.join(", ")
+                    ),
+                ));
             }
         }
     }
+}
+
+// ============================================================================
+// Pre-parsed Paths
+// ============================================================================
+
+/// A segment of a route path pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    /// A literal segment (`users`)
+    Static(&'a str),
+    /// A parameter (`:id`), holding everything after the colon
+    Param(&'a str),
+    /// A wildcard (`*`) matching the rest of the path
+    Wildcard,
+}
+
+/// A route path validated and parsed at compile time
+///
+/// Created by the `route_path!` macro (`macros` feature), which rejects
+/// the paths [`validate_route_path`] rejects, with the same messages. Routes
+/// created from a `PathPattern` match against its segments instead of parsing
+/// the path again.
+///
+/// ```
+/// use gpui_navigator::{route_path, PathPattern, PathSegment, Route};
+///
+/// const USER: PathPattern = route_path!("/users/:id");
+/// assert_eq!(USER.segments(), [PathSegment::Static("users"), PathSegment::Param("id")]);
+///
+/// let route = Route::new(USER, |_, _, _| gpui::div());
+/// assert!(route.matches("/users/7").is_some());
+/// ```
+///
+/// Invalid paths fail to compile:
+///
+/// ```compile_fail
+/// gpui_navigator::route_path!("/users//:id");
+/// ```
+///
+/// ```compile_fail
+/// gpui_navigator::route_path!("/users/:");
+/// ```
+///
+/// ```compile_fail
+/// gpui_navigator::route_path!("/users/:id/posts/:id");
+/// ```
+///
+/// ```compile_fail
+/// gpui_navigator::route_path!("/users/:user-id");
+/// ```
+///
+/// ```compile_fail
+/// gpui_navigator::route_path!("/files/*/meta");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathPattern {
+    path: &'static str,
+    segments: &'static [PathSegment<'static>],
+}
+
+impl PathPattern {
+    /// Create a pattern from a path and its segments
+    ///
+    /// Used by `route_path!`; the segments are trusted to match the path.
+    #[doc(hidden)]
+    pub const fn from_parsed(
+        path: &'static str,
+        segments: &'static [PathSegment<'static>],
+    ) -> Self {
+        Self { path, segments }
+    }
+
+    /// Get the path
+    pub const fn as_str(&self) -> &'static str {
+        self.path
+    }
+
+    /// Get the parsed segments
+    pub const fn segments(&self) -> &'static [PathSegment<'static>] {
+        self.segments
+    }
+
+    /// Match an actual path against this pattern
+    pub fn matches(&self, path: &str) -> Option<RouteMatch> {
+        CompiledPattern::from(*self).matches(path)
+    }
+}
+
+impl std::fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.path)
+    }
+}
 
-    Ok(())
+impl AsRef<str> for PathPattern {
+    fn as_ref(&self) -> &str {
+        self.path
+    }
+}
+
+/// A path a route can be created from
+///
+/// Implemented for anything that converts into a `String`, and for
+/// [`PathPattern`], whose segments are kept for matching.
+pub trait IntoRoutePath {
+    /// Convert into the path and its pre-parsed pattern, if any
+    fn into_route_path(self) -> (String, Option<PathPattern>);
+}
+
+impl<T: Into<String>> IntoRoutePath for T {
+    fn into_route_path(self) -> (String, Option<PathPattern>) {
+        (self.into(), None)
+    }
+}
+
+impl IntoRoutePath for PathPattern {
+    fn into_route_path(self) -> (String, Option<PathPattern>) {
+        (self.path.to_string(), Some(self))
+    }
 }
 
 // ============================================================================
 // RouteConfig
 // ============================================================================
 
+/// How a route is presented by `RouterOutlet`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Presentation {
+    /// Replaces the previous route (default)
+    #[default]
+    Page,
+    /// Rendered in a layer above the last page route, which stays mounted
+    ///
+    /// Popping the modal returns to that page.
+    Modal,
+}
+
 /// Route configuration
 #[derive(Debug, Clone)]
 pub struct RouteConfig {
@@ -174,10 +735,22 @@ pub struct RouteConfig {
     pub path: String,
     /// Route name (optional)
     pub name: Option<String>,
-    /// Child routes (NOTE: For nested routing, use Route.children() instead)
+    /// Child route configs
+    ///
+    /// Only honored by [`Route::from_config`] (and `GlobalRouter::add_route_config`),
+    /// which turns them into child routes. Routes built in code should use
+    /// [`Route::children`] instead.
     pub children: Vec<RouteConfig>,
     /// Route metadata
     pub meta: HashMap<String, String>,
+    /// Metadata keyed by type, see the [`meta`](crate::meta) module
+    pub typed_meta: TypedMeta,
+    /// How the route is presented
+    pub presentation: Presentation,
+    /// Pre-parsed `path`, set when created from a [`PathPattern`]
+    ///
+    /// Only used for matching while it agrees with `path`.
+    pub pattern: Option<PathPattern>,
 }
 
 impl RouteConfig {
@@ -193,30 +766,37 @@ impl RouteConfig {
     /// # Panics
     ///
     /// Panics if the path is invalid. Use `try_new` for non-panicking validation.
-    pub fn new(path: impl Into<String>) -> Self {
-        let path_str = path.into();
-        if let Err(e) = validate_route_path(&path_str) {
-            panic!("Invalid route path '{}': {}", path_str, e);
-        }
-        Self {
-            path: path_str,
-            name: None,
-            children: Vec::new(),
-            meta: HashMap::new(),
+    /// A [`PathPattern`] has been validated at compile time already.
+    pub fn new(path: impl IntoRoutePath) -> Self {
+        match Self::validated(path) {
+            Ok(config) => config,
+            Err((path, e)) => panic!("Invalid route path '{}': {}", path, e),
         }
     }
 
     /// Create a new route with validation, returning Result
     ///
     /// Use this if you want to handle validation errors instead of panicking.
-    pub fn try_new(path: impl Into<String>) -> Result<Self, String> {
-        let path_str = path.into();
-        validate_route_path(&path_str)?;
+    pub fn try_new(path: impl IntoRoutePath) -> Result<Self, String> {
+        Self::validated(path).map_err(|(_, e)| e)
+    }
+
+    /// Validate the path unless it is pre-parsed, keeping it for the error
+    fn validated(path: impl IntoRoutePath) -> Result<Self, (String, String)> {
+        let (path_str, pattern) = path.into_route_path();
+        if pattern.is_none() {
+            if let Err(e) = validate_route_path(&path_str) {
+                return Err((path_str, e));
+            }
+        }
         Ok(Self {
             path: path_str,
             name: None,
             children: Vec::new(),
             meta: HashMap::new(),
+            typed_meta: TypedMeta::new(),
+            presentation: Presentation::Page,
+            pattern,
         })
     }
 
@@ -243,9 +823,29 @@ impl RouteConfig {
         self.meta.insert(key.into(), value.into());
         self
     }
+
+    /// Add metadata keyed by its type
+    pub fn meta_typed<T: MetaValue>(mut self, value: T) -> Self {
+        self.typed_meta.insert(value);
+        self
+    }
+
+    /// Set how the route is presented
+    pub fn presentation(mut self, presentation: Presentation) -> Self {
+        self.presentation = presentation;
+        self
+    }
+
+    /// Compile `path`, from `pattern`'s segments while they agree
+    pub(crate) fn compile(&self) -> CompiledPattern {
+        match self.pattern {
+            Some(pattern) if pattern.as_str() == self.path => pattern.into(),
+            _ => CompiledPattern::parse(&self.path),
+        }
+    }
 }
 
-/// Type for route builder function
+/// Route builder function
 ///
 /// Builder receives Window, context and parameters, returns an AnyElement.
 /// Through context you have access to App, global state, and Navigator.
@@ -254,10 +854,119 @@ impl RouteConfig {
 /// `window.use_state()` or `window.use_keyed_state()` for caching expensive computations
 /// or creating persistent entities across renders.
 ///
-/// Note: When using `Route::new()`, your builder can return any type that implements
-/// `IntoElement` - the conversion to `AnyElement` is done automatically.
-pub type RouteBuilder =
-    Arc<dyn Fn(&mut Window, &mut App, &RouteParams) -> AnyElement + Send + Sync>;
+/// The one builder type of the crate, used by [`Route`], [`PageRoute`] and
+/// [`RouteDescriptor`]. Any function taking `(window, cx, params)` and
+/// returning an `IntoElement` converts into it; builders written without the
+/// window convert with [`RouteBuilder::without_window`], or through `From`
+/// when they are shared as `Arc<dyn Fn(&mut App, &RouteParams) -> AnyElement>`
+/// or are `fn` pointers of that shape. Clones share the function; call it with
+/// [`RouteBuilder::build`].
+///
+/// # Example
+///
+/// Helpers can accept any builder:
+///
+/// ```
+/// use gpui::{div, App, Div, ParentElement, Window};
+/// use gpui_navigator::{Route, RouteBuilder, RouteParams};
+///
+/// fn titled(path: &str, title: &str, builder: impl Into<RouteBuilder>) -> Route {
+///     Route::with_builder(path, builder).meta("title", title)
+/// }
+///
+/// fn home(_window: &mut Window, _cx: &mut App, _params: &RouteParams) -> Div {
+///     div().child("Home")
+/// }
+///
+/// let home = titled("/", "Home", home);
+/// let about = titled(
+///     "/about",
+///     "About",
+///     RouteBuilder::without_window(|_cx, _params| div().child("About")),
+/// );
+/// assert!(home.builder.is_some() && about.builder.is_some());
+/// ```
+#[derive(Clone)]
+pub struct RouteBuilder(Arc<BuildFn>);
+
+/// Function a [`RouteBuilder`] calls
+type BuildFn = dyn Fn(&mut Window, &mut App, &RouteParams) -> AnyElement + Send + Sync;
+
+/// Builder function written without the window
+type WindowlessBuildFn = dyn Fn(&mut App, &RouteParams) -> AnyElement + Send + Sync;
+
+impl RouteBuilder {
+    /// Create a builder from a function of the window, context and params
+    pub fn new<F, E>(builder: F) -> Self
+    where
+        E: IntoElement,
+        F: Fn(&mut Window, &mut App, &RouteParams) -> E + Send + Sync + 'static,
+    {
+        Self(Arc::new(move |window, cx, params| {
+            builder(window, cx, params).into_any_element()
+        }))
+    }
+
+    /// Create a builder from a function of the context and params only
+    pub fn without_window<F, E>(builder: F) -> Self
+    where
+        E: IntoElement,
+        F: Fn(&mut App, &RouteParams) -> E + Send + Sync + 'static,
+    {
+        Self::new(
+            move |_window: &mut Window, cx: &mut App, params: &RouteParams| builder(cx, params),
+        )
+    }
+
+    /// Build the view
+    pub fn build(&self, window: &mut Window, cx: &mut App, params: &RouteParams) -> AnyElement {
+        (self.0)(window, cx, params)
+    }
+
+    /// Whether both builders share the same function
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<F, E> From<F> for RouteBuilder
+where
+    E: IntoElement,
+    F: Fn(&mut Window, &mut App, &RouteParams) -> E + Send + Sync + 'static,
+{
+    fn from(builder: F) -> Self {
+        Self::new(builder)
+    }
+}
+
+impl From<Arc<BuildFn>> for RouteBuilder {
+    fn from(builder: Arc<BuildFn>) -> Self {
+        Self(builder)
+    }
+}
+
+impl From<Arc<WindowlessBuildFn>> for RouteBuilder {
+    fn from(builder: Arc<WindowlessBuildFn>) -> Self {
+        Self::new(
+            move |_window: &mut Window, cx: &mut App, params: &RouteParams| builder(cx, params),
+        )
+    }
+}
+
+impl From<fn(&mut App, &RouteParams) -> AnyElement> for RouteBuilder {
+    fn from(builder: fn(&mut App, &RouteParams) -> AnyElement) -> Self {
+        Self::without_window(builder)
+    }
+}
+
+impl std::fmt::Debug for RouteBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RouteBuilder")
+    }
+}
+
+/// Prepares the data of a [`Route::cached`] route
+pub type PrepareFn = Arc<dyn Fn(&mut App, &RouteParams) -> LoaderData + Send + Sync>;
 
 /// Shared route handle.
 ///
@@ -272,23 +981,71 @@ pub struct Route {
     pub config: RouteConfig,
     /// Builder function to create the view for this route
     pub builder: Option<RouteBuilder>,
+    /// Prepares the data the builder renders, see [`Route::cached`]
+    pub prepare: Option<PrepareFn>,
+    /// Whether prepared data is shared by builds within a frame, see
+    /// [`Route::builder_cached`]
+    pub builder_cached: bool,
+    /// Path patterns used in place of `config.path` per locale
+    ///
+    /// See [`Route::localized_path`]
+    pub localized_paths: HashMap<String, String>,
     /// Child routes with their own builders
     /// This is the preferred way to define nested routes (instead of RouteConfig.children)
     pub children: Vec<RouteRef>,
+    /// Whether this route only wraps its children in a layout, without a
+    /// path of its own, see [`Route::group_layout`]
+    pub pathless: bool,
     /// Named outlets - map of outlet name to child routes
     /// Allows multiple outlet areas in a single parent route
     pub named_children: HashMap<String, Vec<RouteRef>>,
     /// Guards that control access to this route
     #[cfg(feature = "guard")]
     pub guards: Vec<BoxedGuard>,
+    /// Confirmation asked before leaving this route while it is dirty
+    #[cfg(feature = "guard")]
+    pub confirm_leave: Option<crate::guards::ConfirmLeaveGuard>,
     /// Middleware that runs before and after navigation to this route
     #[cfg(feature = "middleware")]
     pub middleware: Vec<BoxedMiddleware>,
     /// Lifecycle hooks for this route
     pub lifecycle: Option<BoxedLifecycle>,
+    /// Data loader run when navigating to this route
+    pub loader: Option<LoaderFn>,
+    /// Whether navigation waits for the loader
+    pub loader_mode: LoaderMode,
+    /// Placeholder shown while the loader runs, see [`Route::skeleton`]
+    pub skeleton: Option<RouteBuilder>,
+    /// How long navigation to this route may wait for its blocking loader,
+    /// if stricter than [`RouterConfig::navigation_timeout`](crate::RouterConfig::navigation_timeout)
+    pub navigation_timeout: Option<Duration>,
+    /// Minimum time between navigations to this route, overriding
+    /// [`RouterConfig::min_navigation_interval`](crate::RouterConfig::min_navigation_interval)
+    pub debounce: Option<Duration>,
+    /// Key of the element focused after navigating to this route, see
+    /// [`FocusPolicy::Custom`](crate::FocusPolicy::Custom)
+    pub focus_target: Option<String>,
+    /// Window options asked for while this route is shown
+    pub window_hints: Option<WindowHints>,
+    /// State scoped to this route, see [`Route::state`]
+    pub states: Vec<RouteState>,
+    /// Renders errors of this route and its descendants, see
+    /// [`Route::error_boundary`]
+    pub error_boundary: Option<ErrorBoundary>,
+    /// Whether components of this route outlive navigation, see
+    /// [`Route::keep_alive`]
+    pub keep_alive: Option<KeepAlive>,
+    /// Named outlets the builder renders, if declared, see
+    /// [`Route::declares_outlets`]
+    pub declared_outlets: Option<Vec<String>>,
+    /// Content this route puts in the layouts' slots, by slot name, see
+    /// [`Route::slot`]
+    pub slots: HashMap<String, RouteBuilder>,
     /// Transition animation for this route
     #[cfg(feature = "transition")]
     pub transition: TransitionConfig,
+    /// `config.path` compiled for matching
+    compiled: CompiledPattern,
 }
 
 impl Route {
@@ -315,28 +1072,299 @@ impl Route {
     ///     div().child(format!("User: {}", id))
     /// });
     /// ```
-    pub fn new<F, E>(path: impl Into<String>, builder: F) -> Self
+    ///
+    /// Builders taking only `(cx, params)` go through [`Route::with_builder`],
+    /// which accepts anything converting into a [`RouteBuilder`].
+    pub fn new<F, E>(path: impl IntoRoutePath, builder: F) -> Self
     where
         E: IntoElement,
         F: Fn(&mut Window, &mut App, &RouteParams) -> E + Send + Sync + 'static,
     {
+        Self::with_builder(path, RouteBuilder::new(builder))
+    }
+
+    /// Create a route with a [`RouteBuilder`]
+    ///
+    /// Like [`Route::new`], for builders that are not closures written in
+    /// place: functions, builders shared between routes, or builders written
+    /// without the window (see [`RouteBuilder::without_window`]).
+    pub fn with_builder(path: impl IntoRoutePath, builder: impl Into<RouteBuilder>) -> Self {
+        Self::with_config(RouteConfig::new(path), builder.into())
+    }
+
+    /// Create a route from its config and a builder
+    fn with_config(config: RouteConfig, builder: RouteBuilder) -> Self {
         Self {
-            config: RouteConfig::new(path),
-            builder: Some(Arc::new(move |window, cx, params| {
-                builder(window, cx, params).into_any_element()
-            })),
+            compiled: config.compile(),
+            config,
+            builder: Some(builder),
+            prepare: None,
+            builder_cached: false,
+            localized_paths: HashMap::new(),
             children: Vec::new(),
+            pathless: false,
             named_children: HashMap::new(),
             #[cfg(feature = "guard")]
             guards: Vec::new(),
+            #[cfg(feature = "guard")]
+            confirm_leave: None,
             #[cfg(feature = "middleware")]
             middleware: Vec::new(),
             lifecycle: None,
+            loader: None,
+            skeleton: None,
+            loader_mode: LoaderMode::default(),
+            navigation_timeout: None,
+            debounce: None,
+            focus_target: None,
+            window_hints: None,
+            error_boundary: None,
+            keep_alive: None,
+            declared_outlets: None,
+            slots: HashMap::new(),
+            states: Vec::new(),
             #[cfg(feature = "transition")]
             transition: TransitionConfig::default(),
         }
     }
 
+    /// Create a layout around its children without a path of its own
+    ///
+    /// The layout adds no segment: its children keep their paths, and the
+    /// builder wraps an outlet rendering whichever child matched. It never
+    /// matches on its own and takes no part in URLs, names or the matched
+    /// routes of state dumps, but its guards and middleware apply to all its
+    /// children, as do its error boundary and scoped state. Layouts side by
+    /// side nest different children.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{render_router_outlet, Route};
+    /// use gpui::*;
+    ///
+    /// Route::group_layout(|window, cx, _params| {
+    ///     div()
+    ///         .flex()
+    ///         .justify_center()
+    ///         .child(div().w_96().child(render_router_outlet(window, cx, None)))
+    /// })
+    /// .children(vec![
+    ///     Route::new("/login", |_, _cx, _params| div().child("Log in")).into(),
+    ///     Route::new("/register", |_, _cx, _params| div().child("Register")).into(),
+    /// ]);
+    /// ```
+    pub fn group_layout<F, E>(builder: F) -> Self
+    where
+        E: IntoElement,
+        F: Fn(&mut Window, &mut App, &RouteParams) -> E + Send + Sync + 'static,
+    {
+        Self {
+            pathless: true,
+            ..Self::new("", builder)
+        }
+    }
+
+    /// Create a route whose builder receives its transition progress
+    ///
+    /// While the route's transition runs (entering or exiting), the outlet
+    /// calls the builder again every frame, so parts of the page can animate
+    /// on their own. Once it completes the builder sees
+    /// [`TransitionProgress::IDLE`](crate::TransitionProgress::IDLE).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{Route, Transition};
+    /// use gpui::*;
+    ///
+    /// Route::animated("/items", |_, _cx, _params, progress| {
+    ///     div().children((0..3).map(|i| div().opacity(progress.staggered(i, 3))))
+    /// })
+    /// .transition(Transition::fade(300));
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn animated<E, F>(path: impl IntoRoutePath, builder: F) -> Self
+    where
+        E: IntoElement,
+        F: Fn(&mut Window, &mut App, &RouteParams, crate::transition::TransitionProgress) -> E
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self::new(path, move |window, cx, params| {
+            builder(window, cx, params, params.transition_progress())
+        })
+    }
+
+    /// Create a route that prepares its data apart from building its view
+    ///
+    /// Outlets may build a route several times in a frame: the exiting and
+    /// entering page of a transition, or several outlets showing it.
+    /// Elements cannot be reused, but the expensive part can: `prepare`
+    /// computes the data the page shows and runs once per frame for the same
+    /// params; every build in that frame passes the shared result to
+    /// `render`. See [`Route::builder_cached`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::cached(
+    ///     "/reports/:id",
+    ///     |_cx, params| format!("Report {}", params.get("id").unwrap()),
+    ///     |_window, _cx, _params, title: &String| div().child(title.clone()),
+    /// );
+    /// ```
+    pub fn cached<D, P, R, E>(path: impl IntoRoutePath, prepare: P, render: R) -> Self
+    where
+        D: Send + Sync + 'static,
+        P: Fn(&mut App, &RouteParams) -> D + Send + Sync + 'static,
+        R: Fn(&mut Window, &mut App, &RouteParams, &D) -> E + Send + Sync + 'static,
+        E: IntoElement,
+    {
+        let prepare = Arc::new(prepare);
+        // Builds outside an outlet get no prepared data
+        let fallback = Arc::clone(&prepare);
+        let mut route = Self::new(path, move |window, cx, params| {
+            let data = params
+                .prepared::<D>()
+                .unwrap_or_else(|| Arc::new(fallback(cx, params)));
+            render(window, cx, params, &data)
+        });
+        route.prepare = Some(Arc::new(move |cx, params| {
+            let data: LoaderData = Arc::new(prepare(cx, params));
+            data
+        }));
+        route.builder_cached = true;
+        route
+    }
+
+    /// Share the data prepared for a [`Route::cached`] route within a frame
+    ///
+    /// On by default for cached routes; turn it off when `prepare` reads
+    /// state that can change while a frame is drawn, so every build prepares
+    /// its own data. Has no effect on other routes, whose builders always run
+    /// in full.
+    pub fn builder_cached(mut self, enabled: bool) -> Self {
+        self.builder_cached = enabled;
+        self
+    }
+
+    /// Register the path pattern this route has in `locale`
+    ///
+    /// With [`RouterConfig::locales`](crate::RouterConfig::locales) set,
+    /// `/de/einstellungen` resolves to the route registered as `/settings`
+    /// with `localized_path("de", "/einstellungen")`. Child routes give their
+    /// localized path relative to the parent, like their canonical one.
+    /// Parameter names must be the same as in the canonical path. Locales
+    /// without a localized path use the canonical one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path is invalid.
+    pub fn localized_path(mut self, locale: impl Into<String>, path: impl Into<String>) -> Self {
+        let path = path.into();
+        if let Err(e) = validate_route_path(&path) {
+            panic!("Invalid route path '{}': {}", path, e);
+        }
+        self.localized_paths.insert(locale.into(), path);
+        self
+    }
+
+    /// Path pattern of this route in `locale`
+    ///
+    /// Falls back to the canonical `config.path`.
+    pub fn path_for_locale(&self, locale: &str) -> &str {
+        self.localized_paths
+            .get(locale)
+            .map_or(self.config.path.as_str(), String::as_str)
+    }
+
+    /// Create a route tree from a declarative config
+    ///
+    /// Each config (and each of its `children`, recursively) is resolved to a
+    /// builder by looking its `name` up in `builders`. This allows route tables
+    /// to be loaded from data and wired to views by name.
+    ///
+    /// Returns an error if a config has no name or no builder is registered for it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{Route, RouteConfig};
+    /// use gpui_navigator::route::RouteBuilder;
+    /// use gpui::*;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut builders: HashMap<String, RouteBuilder> = HashMap::new();
+    /// builders.insert("dashboard".into(), RouteBuilder::new(|_, _, _| div()));
+    /// builders.insert("overview".into(), RouteBuilder::new(|_, _, _| div()));
+    ///
+    /// let config = RouteConfig::new("/dashboard")
+    ///     .name("dashboard")
+    ///     .child(RouteConfig::new("overview").name("overview"));
+    ///
+    /// let route = Route::from_config(config, &builders).unwrap();
+    /// assert_eq!(route.get_children().len(), 1);
+    /// ```
+    pub fn from_config(
+        config: RouteConfig,
+        builders: &HashMap<String, RouteBuilder>,
+    ) -> Result<Self, String> {
+        let name = config
+            .name
+            .as_deref()
+            .ok_or_else(|| format!("Route config '{}' has no name", config.path))?;
+        let builder = builders.get(name).cloned().ok_or_else(|| {
+            format!(
+                "No builder registered for route '{}' ('{}')",
+                name, config.path
+            )
+        })?;
+
+        let children = config
+            .children
+            .iter()
+            .map(|child| Self::from_config(child.clone(), builders).map(Arc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            compiled: config.compile(),
+            config,
+            builder: Some(builder),
+            prepare: None,
+            builder_cached: false,
+            localized_paths: HashMap::new(),
+            children,
+            pathless: false,
+            named_children: HashMap::new(),
+            #[cfg(feature = "guard")]
+            guards: Vec::new(),
+            #[cfg(feature = "guard")]
+            confirm_leave: None,
+            #[cfg(feature = "middleware")]
+            middleware: Vec::new(),
+            lifecycle: None,
+            loader: None,
+            skeleton: None,
+            loader_mode: LoaderMode::default(),
+            navigation_timeout: None,
+            debounce: None,
+            focus_target: None,
+            window_hints: None,
+            error_boundary: None,
+            keep_alive: None,
+            declared_outlets: None,
+            slots: HashMap::new(),
+            states: Vec::new(),
+            #[cfg(feature = "transition")]
+            transition: TransitionConfig::default(),
+        })
+    }
+
     /// Create a stateless route from a simple view function
     ///
     /// Use this for simple, stateless pages that don't need access to route params,
@@ -352,7 +1380,7 @@ impl Route {
     ///     div().child("About Page").into_any_element()
     /// });
     /// ```
-    pub fn view<F>(path: impl Into<String>, view: F) -> Self
+    pub fn view<F>(path: impl IntoRoutePath, view: F) -> Self
     where
         F: Fn() -> AnyElement + Send + Sync + 'static,
     {
@@ -362,8 +1390,9 @@ impl Route {
     /// Create a stateful route with an Entity-based component
     ///
     /// Use this for pages that maintain internal state across navigation.
-    /// The component is cached using `window.use_keyed_state()`, so navigating
-    /// back to the route will preserve the component's state.
+    /// The component is cached using `window.use_keyed_state()` while the
+    /// route is rendered; with [`Route::keep_alive`] it also survives
+    /// navigating away and back.
     ///
     /// # Example
     ///
@@ -389,21 +1418,23 @@ impl Route {
     ///
     /// Route::component("/counter", CounterPage::new);
     /// ```
-    pub fn component<T, F>(path: impl Into<String>, create: F) -> Self
+    pub fn component<T, F>(path: impl IntoRoutePath, create: F) -> Self
     where
         T: Render + 'static,
         F: Fn() -> T + Send + Sync + 'static + Clone,
     {
-        let path_str = path.into();
-        let key_path = path_str.clone();
-
-        Self::new(path_str, move |window, cx, _| {
-            let key = format!("route:{}", key_path);
-            let create_fn = create.clone();
-            let entity =
-                window.use_keyed_state(gpui::ElementId::Name(key.into()), cx, |_, _| create_fn());
-            entity.clone().into_any_element()
-        })
+        let config = RouteConfig::new(path);
+        let key_path = config.path.clone();
+
+        Self::with_config(
+            config,
+            RouteBuilder::new(move |window, cx, _| {
+                let key = format!("route:{}", key_path);
+                let create_fn = create.clone();
+                let entity = component_entity(window, cx, key, create_fn);
+                entity.into_any_element()
+            }),
+        )
     }
 
     /// Create a stateful route with parameters
@@ -439,37 +1470,42 @@ impl Route {
     ///     UserPage::new(id)
     /// });
     /// ```
-    pub fn component_with_params<T, F>(path: impl Into<String>, create: F) -> Self
+    pub fn component_with_params<T, F>(path: impl IntoRoutePath, create: F) -> Self
     where
         T: Render + 'static,
         F: Fn(&RouteParams) -> T + Send + Sync + 'static + Clone,
     {
-        let path_str = path.into();
-        let key_path = path_str.clone();
-
-        Self::new(path_str, move |window, cx, params| {
-            // Create unique key from path + parameter values
-            let params_key = params
-                .iter()
-                .map(|(k, v)| format!("{}={}", k, v))
-                .collect::<Vec<_>>()
-                .join("&");
-            let key = format!("route:{}?{}", key_path, params_key);
-
-            let params_clone = params.clone();
-            let create_fn = create.clone();
-            let entity =
-                window.use_keyed_state(gpui::ElementId::Name(key.into()), cx, move |_, _| {
-                    create_fn(&params_clone)
-                });
-            entity.clone().into_any_element()
-        })
+        let config = RouteConfig::new(path);
+        let key_path = config.path.clone();
+
+        Self::with_config(
+            config,
+            RouteBuilder::new(move |window, cx, params| {
+                // Create unique key from path + parameter values
+                let params_key = params
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect::<Vec<_>>()
+                    .join("&");
+                let key = format!("route:{}?{}", key_path, params_key);
+
+                let params_clone = params.clone();
+                let create_fn = create.clone();
+                let entity = component_entity(window, cx, key, move || create_fn(&params_clone));
+                entity.into_any_element()
+            }),
+        )
     }
 
     /// Add child routes to this route
     ///
     /// Child routes will be rendered in a RouterOutlet within the parent's layout.
     ///
+    /// Child paths are relative to the parent and may span several segments
+    /// (`reports/:year`). A path starting with `/` is absolute and must start
+    /// with the parent's full path (`/dashboard/reports` under `/dashboard`);
+    /// `GlobalRouter::add_route` rejects absolute children outside it.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -542,6 +1578,43 @@ impl Route {
         self
     }
 
+    /// Add metadata keyed by its type
+    ///
+    /// Unlike [`Route::meta`], values cannot collide with those other code
+    /// stores under the same name: the type is the key. A value of the same
+    /// type replaces the previous one. See the [`meta`](crate::meta) module.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{MetaKey, Route, RouteTitle};
+    /// use gpui::*;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct RequiresAuth(bool);
+    ///
+    /// impl MetaKey for RequiresAuth {
+    ///     const NAME: &'static str = "requiresAuth";
+    /// }
+    ///
+    /// Route::new("/admin", |_, _cx, _params| div())
+    ///     .meta_typed(RequiresAuth(true))
+    ///     .meta_typed(RouteTitle::new("Admin Panel"));
+    /// ```
+    pub fn meta_typed<T: MetaValue>(mut self, value: T) -> Self {
+        self.config.typed_meta.insert(value);
+        self
+    }
+
+    /// This route's own metadata of type `T`, set with [`Route::meta_typed`]
+    ///
+    /// Values of parent routes are not included; see
+    /// [`Navigator::current_meta_typed`](crate::Navigator::current_meta_typed)
+    /// for the merged view.
+    pub fn get_meta<T: MetaValue>(&self) -> Option<&T> {
+        self.config.typed_meta.get()
+    }
+
     /// Add routes for a named outlet
     ///
     /// Named outlets allow you to have multiple content areas in a single parent route.
@@ -570,6 +1643,62 @@ impl Route {
         self
     }
 
+    /// Declare the named outlets this route's builder renders
+    ///
+    /// Optional; once declared, registering the route warns about named
+    /// outlets (see [`Route::named_outlet`]) the builder never renders, so
+    /// their children could not show. See [`validate_route_tree`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/dashboard", |_, _cx, _params| div())
+    ///     .named_outlet("sidebar", vec![
+    ///         Route::new("stats", |_, _cx, _params| div()).into(),
+    ///     ])
+    ///     .declares_outlets(["sidebar"]);
+    /// ```
+    pub fn declares_outlets<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.declared_outlets = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Put content in the slot `name` of the layouts above
+    ///
+    /// Layouts render a [`RouterSlot`](crate::RouterSlot) where the slot
+    /// goes, e.g. a toolbar in their header. The deepest matched route
+    /// defining the slot fills it, so a parent's content shows on children
+    /// that define none. The builder gets the params this route's builder
+    /// gets. See the [`slot`](crate::slot) module.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("reports/:year", |_, _cx, _params| div())
+    ///     .slot("toolbar", |_cx, params| {
+    ///         div().child(format!("Export {}", params.get("year").unwrap()))
+    ///     });
+    /// ```
+    pub fn slot<F, E>(mut self, name: impl Into<String>, builder: F) -> Self
+    where
+        E: IntoElement,
+        F: Fn(&mut App, &RouteParams) -> E + Send + Sync + 'static,
+    {
+        self.slots
+            .insert(name.into(), RouteBuilder::without_window(builder));
+        self
+    }
+
     /// Add a guard to this route
     ///
     /// Guards control access to routes. If any guard denies access, navigation is blocked.
@@ -600,6 +1729,29 @@ impl Route {
         self
     }
 
+    /// Ask before leaving this route while it is dirty
+    ///
+    /// Pages mark themselves dirty with `Navigator::set_dirty`. Navigating
+    /// away from a dirty route (including back, forward and, with
+    /// `Navigator::confirm_leave_on_close`, closing the window) is held, and
+    /// the handler set with `GlobalRouter::set_confirm_handler` is asked with
+    /// `message`. See [`ConfirmLeaveGuard`](crate::ConfirmLeaveGuard).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/compose", |_, _cx, _params| div())
+    ///     .confirm_leave("You have unsaved changes");
+    /// ```
+    #[cfg(feature = "guard")]
+    pub fn confirm_leave(mut self, message: impl Into<String>) -> Self {
+        self.confirm_leave = Some(crate::guards::ConfirmLeaveGuard::new(message));
+        self
+    }
+
     /// Add multiple guards at once
     ///
     /// # Example
@@ -621,7 +1773,8 @@ impl Route {
 
     /// Add middleware to this route
     ///
-    /// Middleware runs before and after navigation.
+    /// It runs before and after navigations to this route or its children,
+    /// along with the global middleware, by priority.
     ///
     /// # Example
     ///
@@ -689,6 +1842,390 @@ impl Route {
         self
     }
 
+    /// Load data for this route during navigation
+    ///
+    /// The loader gets the matched parameters and returns the data (or an
+    /// error message). Builders read the result with [`RouteParams::data`].
+    /// See [`LoaderMode`] for when the navigation is committed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{DataState, Route};
+    /// use gpui::*;
+    ///
+    /// Route::new("/users/:id", |_, _cx, params| match params.data::<String>() {
+    ///     Some(DataState::Ready(name)) => div().child(name.to_string()),
+    ///     _ => div().child("Loading..."),
+    /// })
+    /// .loader(|params, _cx| async move {
+    ///     params
+    ///         .get("id")
+    ///         .map(|id| format!("User {}", id))
+    ///         .ok_or_else(|| "missing id".to_string())
+    /// });
+    /// ```
+    pub fn loader<F, Fut, T>(mut self, loader: F) -> Self
+    where
+        F: Fn(RouteParams, &App) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<T, String>> + Send + 'static,
+        T: Send + Sync + 'static,
+    {
+        self.loader = Some(loader_fn(loader));
+        self
+    }
+
+    /// Keep a `T` for as long as this route is active
+    ///
+    /// `factory` creates the value the first time a builder asks for it with
+    /// [`Navigator::route_state`](crate::Navigator::route_state). It is dropped
+    /// when navigation leaves the route; each set of params gets its own
+    /// value. Child routes can use it too. See the [`scope`](crate::scope) module.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{Navigator, Route};
+    /// use gpui::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Selection(Vec<usize>);
+    ///
+    /// Route::new("/files", |_, cx, _params| {
+    ///     let selection = Navigator::route_state::<Selection>(cx).unwrap();
+    ///     div().child(format!("{} selected", selection.read(cx).0.len()))
+    /// })
+    /// .state(Selection::default);
+    /// ```
+    pub fn state<T: 'static>(mut self, factory: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        self.states
+            .retain(|state| state.type_id != TypeId::of::<T>());
+        self.states.push(RouteState {
+            type_id: TypeId::of::<T>(),
+            create: Arc::new(move || Box::new(factory())),
+            dispose: None,
+        });
+        self
+    }
+
+    /// Call `on_dispose` when this route's `T` state is dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if no `T` state was registered with [`Route::state`] before.
+    pub fn on_state_dispose<T: 'static>(
+        mut self,
+        on_dispose: impl Fn(&mut T, &mut App) + Send + Sync + 'static,
+    ) -> Self {
+        let Some(state) = self
+            .states
+            .iter_mut()
+            .find(|state| state.type_id == TypeId::of::<T>())
+        else {
+            panic!(
+                "Route '{}' has no {} state; register it with Route::state first",
+                self.config.path,
+                std::any::type_name::<T>()
+            );
+        };
+        let dispose: StateDispose = Arc::new(move |entity, cx| {
+            if let Ok(entity) = entity.downcast::<T>() {
+                entity.update(cx, |value, cx| on_dispose(value, cx));
+            }
+        });
+        state.dispose = Some(dispose);
+        self
+    }
+
+    /// Set whether navigation waits for the loader (default: deferred)
+    pub fn loader_mode(mut self, mode: LoaderMode) -> Self {
+        self.loader_mode = mode;
+        self
+    }
+
+    /// Show a placeholder shaped like the page while its loader runs
+    ///
+    /// Outlets render the skeleton in place of the route's builder as long
+    /// as the data of a deferred loader is loading (blocking loaders keep
+    /// the current page up instead). It enters with the route's transition,
+    /// and is crossfaded to the page once the data is there, over
+    /// [`RouterConfig::skeleton_crossfade`](crate::RouterConfig::skeleton_crossfade).
+    /// The skeleton gets the params the builder gets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/inbox", |_, _cx, _params| div())
+    ///     .loader(|_params, _cx| async { Ok(vec!["Welcome".to_string()]) })
+    ///     .skeleton(|_cx, _params| {
+    ///         div().children((0..5).map(|_| div().h(px(24.)).bg(rgb(0xeeeeee))))
+    ///     });
+    /// ```
+    pub fn skeleton<F, E>(mut self, builder: F) -> Self
+    where
+        E: IntoElement,
+        F: Fn(&mut App, &RouteParams) -> E + Send + Sync + 'static,
+    {
+        self.skeleton = Some(RouteBuilder::without_window(builder));
+        self
+    }
+
+    /// Abort navigation to this route if its blocking loader takes longer
+    ///
+    /// The stricter of this and
+    /// [`RouterConfig::navigation_timeout`](crate::RouterConfig::navigation_timeout)
+    /// applies. Has no effect on deferred loaders, whose navigation commits
+    /// right away.
+    pub fn navigation_timeout(mut self, timeout: Duration) -> Self {
+        self.navigation_timeout = Some(timeout);
+        self
+    }
+
+    /// Throttle navigations to this route
+    ///
+    /// A navigation to this route arriving within `interval` of the previous
+    /// navigation is coalesced or dropped according to the router's
+    /// [`ThrottlePolicy`](crate::ThrottlePolicy). Overrides
+    /// [`RouterConfig::min_navigation_interval`](crate::RouterConfig::min_navigation_interval).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    /// use std::time::Duration;
+    ///
+    /// Route::new("/items/:id", |_, _cx, _params| div().into_any_element())
+    ///     .debounce(Duration::from_millis(300));
+    /// ```
+    pub fn debounce(mut self, interval: Duration) -> Self {
+        self.debounce = Some(interval);
+        self
+    }
+
+    /// Focus the element marked with `key` after navigating to this route
+    ///
+    /// Used with [`FocusPolicy::Custom`](crate::FocusPolicy::Custom). The page
+    /// marks the element with
+    /// [`RouteFocusExt::route_focus_target`](crate::RouteFocusExt::route_focus_target).
+    /// In nested routes the deepest route with a focus target wins.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{Route, RouteFocusExt};
+    /// use gpui::*;
+    ///
+    /// Route::new("/settings", |_, cx, _params| {
+    ///     div().child(div().route_focus_target("heading", cx).child("Settings"))
+    /// })
+    /// .focus_target("heading");
+    /// ```
+    pub fn focus_target(mut self, key: impl Into<String>) -> Self {
+        self.focus_target = Some(key.into());
+        self
+    }
+
+    /// Ask for window options while this route is shown
+    ///
+    /// Nested routes inherit the hints of their parents and override the
+    /// fields they set; see [`WindowHints`] and the
+    /// [`window_hints`](crate::window_hints) module.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{Route, WindowHints};
+    /// use gpui::*;
+    ///
+    /// Route::new("/settings", |_, _, _params| div().child("Settings")).window_hints(
+    ///     WindowHints::new()
+    ///         .title("Settings")
+    ///         .min_size(size(px(640.), px(480.))),
+    /// );
+    /// ```
+    pub fn window_hints(mut self, hints: WindowHints) -> Self {
+        self.window_hints = Some(hints);
+        self
+    }
+
+    /// Make this route step `step` of the flow called `flow`
+    ///
+    /// The router tracks the steps reached in the flow; see
+    /// [`Navigator::flow_state`](crate::Navigator::flow_state) and the
+    /// [`flow`](crate::flow) module. Ordinals need not be contiguous.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/checkout/payment", |_, _, _params| div().child("Payment"))
+    ///     .flow("checkout", 2);
+    /// ```
+    pub fn flow(self, flow: impl Into<String>, step: usize) -> Self {
+        self.meta_typed(FlowStep {
+            flow: flow.into(),
+            step,
+        })
+    }
+
+    /// Keep the components of this route alive after navigation leaves it
+    ///
+    /// For routes built with [`Route::component`] or
+    /// [`Route::component_with_params`]: going back shows the page as it was
+    /// left instead of a new one. Every set of params keeps its own instance,
+    /// without limit; see [`Route::keep_alive_max`] and the
+    /// [`keep_alive`](crate::keep_alive) module.
+    pub fn keep_alive(mut self) -> Self {
+        self.keep_alive.get_or_insert(KeepAlive {
+            max: usize::MAX,
+            on_evict: None,
+            reuse: ReuseStrategy::Always,
+        });
+        self
+    }
+
+    /// Keep at most `max` instances of this route's component alive
+    ///
+    /// Turns keep-alive on. Beyond `max` the least recently used instance is
+    /// dropped, unless it is on screen.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// struct Editor(String);
+    ///
+    /// impl Render for Editor {
+    ///     fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+    ///         div().child(self.0.clone())
+    ///     }
+    /// }
+    ///
+    /// Route::component_with_params("/documents/:id", |params| {
+    ///     Editor(params.get("id").cloned().unwrap_or_default())
+    /// })
+    /// .keep_alive_max(5);
+    /// ```
+    pub fn keep_alive_max(mut self, max: usize) -> Self {
+        self = self.keep_alive();
+        if let Some(keep) = self.keep_alive.as_mut() {
+            keep.max = max;
+        }
+        self
+    }
+
+    /// Call `on_evict` when a kept-alive `T` component of this route is dropped
+    ///
+    /// Runs before the component goes, so the page can save what it holds.
+    /// Turns keep-alive on.
+    pub fn on_evict<T: 'static>(
+        mut self,
+        on_evict: impl Fn(&mut T, &mut App) + Send + Sync + 'static,
+    ) -> Self {
+        let callback: EvictCallback = Arc::new(move |entity, cx| {
+            if let Ok(entity) = entity.downcast::<T>() {
+                entity.update(cx, |value, cx| on_evict(value, cx));
+            }
+        });
+        self = self.keep_alive();
+        if let Some(keep) = self.keep_alive.as_mut() {
+            keep.on_evict = Some(callback);
+        }
+        self
+    }
+
+    /// Choose which visits restore this route's kept-alive component
+    ///
+    /// Turns keep-alive on. The default, [`ReuseStrategy::Always`],
+    /// restores the instance for its params on any visit;
+    /// [`ReuseStrategy::OnBackOnly`] only when going back or forward in
+    /// history, and [`ReuseStrategy::Never`] creates a new one each time.
+    /// Dropped instances get `on_evict` first. With
+    /// [`Route::keep_alive_max`], the limit still drops the least recently
+    /// used instances whatever the strategy; `Never` instances are dropped
+    /// once off screen, so they do not count toward it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{ReuseStrategy, Route};
+    /// use gpui::*;
+    ///
+    /// struct Compose;
+    ///
+    /// impl Render for Compose {
+    ///     fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+    ///         div().child("New message")
+    ///     }
+    /// }
+    ///
+    /// // Back returns to the draft; the compose button starts a new one
+    /// Route::component("/compose", || Compose).reuse(ReuseStrategy::OnBackOnly);
+    /// ```
+    pub fn reuse(mut self, strategy: ReuseStrategy) -> Self {
+        self = self.keep_alive();
+        if let Some(keep) = self.keep_alive.as_mut() {
+            keep.reuse = strategy;
+        }
+        self
+    }
+
+    /// Render errors of this route in place of its content
+    ///
+    /// Covers builder panics, failed blocking loaders and, for routes with
+    /// children, paths none of the children match. Errors of routes without
+    /// a boundary go to the nearest ancestor route that has one (rendered in
+    /// the failing route's place, so the layouts around it stay), and
+    /// finally to [`DefaultPages`](crate::DefaultPages). The boundary gets a
+    /// [`RetryHandle`] to build the route again.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{Route, RouteError};
+    /// use gpui::*;
+    ///
+    /// Route::new("/reports", |_, _cx, _params| div().into_any_element())
+    ///     .error_boundary(|error, retry, _cx| {
+    ///         div()
+    ///             .id("retry")
+    ///             .child(format!("Reports failed: {}", error))
+    ///             .on_click(move |_, _, cx| retry.retry(cx))
+    ///     });
+    /// ```
+    pub fn error_boundary<F, E>(mut self, boundary: F) -> Self
+    where
+        E: IntoElement,
+        F: Fn(&RouteError, RetryHandle, &mut App) -> E + Send + Sync + 'static,
+    {
+        self.error_boundary = Some(Arc::new(move |error, retry, cx| {
+            boundary(error, retry, cx).into_any_element()
+        }));
+        self
+    }
+
+    /// Set how this route is presented
+    ///
+    /// Modal routes are drawn above the last page route in history, which keeps
+    /// being built underneath. `Navigator::pop` dismisses the modal.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{Presentation, Route};
+    /// use gpui::*;
+    ///
+    /// Route::new("/photos/:id/edit", |_, _cx, _params| div().into_any_element())
+    ///     .presentation(Presentation::Modal);
+    /// ```
+    pub fn presentation(mut self, presentation: Presentation) -> Self {
+        self.config.presentation = presentation;
+        self
+    }
+
+    /// Check if this route is presented as a modal
+    pub fn is_modal(&self) -> bool {
+        self.config.presentation == Presentation::Modal
+    }
+
     /// Set the transition animation for this route
     ///
     /// # Example
@@ -723,8 +2260,18 @@ impl Route {
     }
 
     /// Match a path against this route
+    ///
+    /// Pathless layouts match no path.
     pub fn matches(&self, path: &str) -> Option<RouteMatch> {
-        match_path(&self.config.path, path)
+        if self.pathless {
+            return None;
+        }
+        // `config.path` is public and may have changed since
+        if self.compiled.as_str() == self.config.path {
+            self.compiled.matches(path)
+        } else {
+            match_path(&self.config.path, path)
+        }
     }
 
     /// Build the view for this route
@@ -734,7 +2281,9 @@ impl Route {
         cx: &mut App,
         params: &RouteParams,
     ) -> Option<AnyElement> {
-        self.builder.as_ref().map(|b| b(window, cx, params))
+        self.builder
+            .as_ref()
+            .map(|builder| builder.build(window, cx, params))
     }
 
     /// Find a child route by path segment
@@ -757,56 +2306,236 @@ impl std::fmt::Debug for Route {
         f.debug_struct("Route")
             .field("config", &self.config)
             .field("builder", &self.builder.is_some())
+            .field(
+                "builder_cached",
+                &(self.prepare.is_some() && self.builder_cached),
+            )
+            .field("localized_paths", &self.localized_paths)
             .field("children", &self.children.len())
+            .field("pathless", &self.pathless)
+            .field("loader", &self.loader.is_some())
+            .field("skeleton", &self.skeleton.is_some())
+            .field("error_boundary", &self.error_boundary.is_some())
+            .field("keep_alive", &self.keep_alive)
             .field(
                 "named_children",
                 &self.named_children.keys().collect::<Vec<_>>(),
             )
+            .field("slots", &self.slots.keys().collect::<Vec<_>>())
             .finish()
     }
 }
 
-/// Match a path pattern against an actual path
+/// Match a path pattern against an actual path
+///
+/// Supports:
+/// - Static paths: `/users`
+/// - Dynamic segments: `/users/:id`, constrained with `/users/:id{uuid}`
+/// - Wildcard: `/files/*`, capturing the rest of the path as `*`
+///
+/// Routes keep their path compiled instead, see [`Route::matches`].
+pub(crate) fn match_path(pattern: &str, path: &str) -> Option<RouteMatch> {
+    CompiledPattern::parse(pattern).matches(path)
+}
+
+/// Split a path pattern into its segments
+pub(crate) fn parse_path(pattern: &str) -> Vec<PathSegment<'_>> {
+    pattern
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|segment| {
+            if segment == "*" {
+                PathSegment::Wildcard
+            } else if let Some(param_name) = segment.strip_prefix(':') {
+                PathSegment::Param(param_name)
+            } else {
+                PathSegment::Static(segment)
+            }
+        })
+        .collect()
+}
+
+/// Full paths of `routes` and all their descendants, depth-first
+///
+/// Pathless layouts have none; their children are listed.
+pub(crate) fn route_full_paths(routes: &[RouteRef], parent_path: &str) -> Vec<String> {
+    fn collect(routes: &[RouteRef], parent_path: &str, depth: usize, paths: &mut Vec<String>) {
+        if routes.is_empty() || lookup_too_deep(depth, parent_path) {
+            return;
+        }
+        for route in routes {
+            let full_path = if parent_path.is_empty() {
+                route.config.path.clone()
+            } else {
+                crate::nested::build_child_path(parent_path, &route.config.path).into_owned()
+            };
+            if !route.pathless {
+                paths.push(full_path.clone());
+            }
+            collect(route.get_children(), &full_path, depth + 1, paths);
+            for name in route.named_outlet_names() {
+                let children = route.get_named_children(name).unwrap_or_default();
+                collect(children, &full_path, depth + 1, paths);
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    collect(routes, parent_path, 0, &mut paths);
+    paths
+}
+
+/// Find the chain of routes (outermost first) whose full path matches `path`
 ///
-/// Supports:
-/// - Static paths: `/users`
-/// - Dynamic segments: `/users/:id`
-/// - Wildcard: `/files/*`
-fn match_path(pattern: &str, path: &str) -> Option<RouteMatch> {
-    let pattern_segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
-    let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-
-    // Handle wildcard
-    if pattern_segments.last() == Some(&"*") {
-        if path_segments.len() < pattern_segments.len() - 1 {
+/// Query strings are ignored. The returned match holds the leaf's parameters.
+/// Pathless layouts are part of the chain of their matching children only.
+pub(crate) fn match_route_chain<'a>(
+    routes: &'a [RouteRef],
+    path: &str,
+) -> Option<(Vec<&'a RouteRef>, RouteMatch)> {
+    fn find<'a>(
+        routes: &'a [RouteRef],
+        path: &str,
+        parent_path: &str,
+        depth: usize,
+    ) -> Option<(Vec<&'a RouteRef>, RouteMatch)> {
+        if routes.is_empty() || lookup_too_deep(depth, parent_path) {
             return None;
         }
-    } else if pattern_segments.len() != path_segments.len() {
-        return None;
+        for route in routes {
+            let full_path = if parent_path.is_empty() {
+                route.config.path.clone()
+            } else {
+                crate::nested::build_child_path(parent_path, &route.config.path).into_owned()
+            };
+
+            if !route.pathless {
+                if let Some(route_match) = match_path(&full_path, path) {
+                    return Some((vec![route], route_match));
+                }
+            }
+
+            if let Some((mut chain, route_match)) =
+                find(route.get_children(), path, &full_path, depth + 1)
+            {
+                chain.insert(0, route);
+                return Some((chain, route_match));
+            }
+        }
+        None
     }
 
-    let mut route_match = RouteMatch::new(path.to_string());
+    let path = path.split('?').next().unwrap_or(path);
+    find(routes, path, "", 0)
+}
 
-    for (i, pattern_seg) in pattern_segments.iter().enumerate() {
-        if *pattern_seg == "*" {
-            // Wildcard matches rest of path
-            break;
+/// Full path pattern of a matched chain of routes, e.g. `/users/:id/posts`
+pub(crate) fn chain_pattern(chain: &[&RouteRef]) -> String {
+    chain.iter().fold(String::new(), |parent, route| {
+        if parent.is_empty() {
+            route.config.path.clone()
+        } else {
+            crate::nested::build_child_path(&parent, &route.config.path).into_owned()
         }
+    })
+}
 
-        if let Some(param_name) = pattern_seg.strip_prefix(':') {
-            // Dynamic segment
-            if let Some(path_seg) = path_segments.get(i) {
-                route_match
-                    .params
-                    .insert(param_name.to_string(), path_seg.to_string());
+/// Params of a matched chain split by the route whose path introduced them
+///
+/// One map per route of `chain`, outermost first, with the values of
+/// `params` (the leaf's match). Pathless layouts introduce none, and a param
+/// an absolute child path repeats belongs to the ancestor that introduced it.
+pub(crate) fn chain_level_params(
+    chain: &[&RouteRef],
+    params: &HashMap<String, String>,
+) -> Vec<RouteParams> {
+    let mut seen = HashSet::new();
+    chain
+        .iter()
+        .map(|route| {
+            let mut level = RouteParams::new();
+            if route.pathless {
+                return level;
             }
-        } else if pattern_segments.get(i) != path_segments.get(i) {
-            // Static segment mismatch
-            return None;
-        }
+            for segment in path_segments(&route.config.path) {
+                let name = if segment == "*" {
+                    segment
+                } else if let Some(param) = segment.strip_prefix(':') {
+                    &param[..param.find(['{', '<', '?']).unwrap_or(param.len())]
+                } else {
+                    continue;
+                };
+                if let Some(value) = params.get(name).filter(|_| seen.insert(name)) {
+                    level.set(name.to_string(), value.clone());
+                }
+            }
+            level
+        })
+        .collect()
+}
+
+/// Meta of a matched chain of routes, inner routes overriding outer ones
+#[cfg(feature = "guard")]
+pub(crate) fn chain_meta(chain: &[&RouteRef]) -> HashMap<String, String> {
+    chain
+        .iter()
+        .flat_map(|route| route.config.meta.clone())
+        .collect()
+}
+
+/// Typed meta of a matched chain of routes, inner routes overriding outer ones
+#[cfg(feature = "guard")]
+pub(crate) fn chain_typed_meta(chain: &[&RouteRef]) -> TypedMeta {
+    let mut meta = TypedMeta::new();
+    for route in chain {
+        meta.extend(&route.config.typed_meta);
     }
+    meta
+}
 
-    Some(route_match)
+/// Value of type `T` in the typed meta of a matched chain, the innermost
+/// route's first
+pub(crate) fn chain_meta_typed<'a, T: MetaValue>(chain: &[&'a RouteRef]) -> Option<&'a T> {
+    chain.iter().rev().find_map(|route| route.get_meta())
+}
+
+/// Number of leading routes two paths' matched chains have in common
+///
+/// A level counts as shared when both paths match the same route there with
+/// the same parameters, so its content can stay mounted. Unmatched paths share
+/// nothing.
+pub(crate) fn shared_chain_depth(routes: &[RouteRef], from: &str, to: &str) -> usize {
+    let (Some((from_chain, _)), Some((to_chain, _))) = (
+        match_route_chain(routes, from),
+        match_route_chain(routes, to),
+    ) else {
+        return 0;
+    };
+
+    let segments = |path: &'_ str| -> Vec<String> {
+        let path = path.split('?').next().unwrap_or(path);
+        path.split('/')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let (from_segments, to_segments) = (segments(from), segments(to));
+
+    let mut full_path = String::new();
+    from_chain
+        .iter()
+        .zip(&to_chain)
+        .take_while(|(from_route, to_route)| {
+            if !Arc::ptr_eq(from_route, to_route) {
+                return false;
+            }
+            full_path =
+                crate::nested::build_child_path(&full_path, &from_route.config.path).into_owned();
+            // Same route with the same segments up to its depth, i.e. same params
+            let depth = full_path.split('/').filter(|s| !s.is_empty()).count();
+            from_segments.get(..depth) == to_segments.get(..depth)
+        })
+        .count()
 }
 
 // ============================================================================
@@ -836,10 +2565,12 @@ pub trait IntoRoute {
     fn into_route(self) -> RouteDescriptor;
 }
 
-/// Type for route builder function
-pub type BuilderFn = Arc<dyn Fn(&mut Window, &mut App, &RouteParams) -> AnyElement + Send + Sync>;
+/// Former name of [`RouteBuilder`]
+#[deprecated(since = "0.1.4", note = "use `RouteBuilder`")]
+pub type BuilderFn = RouteBuilder;
 
 /// A route descriptor containing path, parameters, and optional builder
+#[derive(Debug)]
 pub struct RouteDescriptor {
     /// The route path (e.g., "/users/:id")
     pub path: String,
@@ -848,7 +2579,29 @@ pub struct RouteDescriptor {
     pub params: RouteParams,
 
     /// Optional builder function to create the view
-    pub builder: Option<BuilderFn>,
+    pub builder: Option<RouteBuilder>,
+
+    /// Name of the route to navigate to, if the target is a named route
+    ///
+    /// The path is then looked up when navigating and `params` and `query`
+    /// are filled in; `path` is not used.
+    pub name: Option<String>,
+
+    /// Query params added to the path of a named route
+    pub query: QueryParams,
+}
+
+impl RouteDescriptor {
+    /// Descriptor navigating to `path`
+    fn path(path: String) -> Self {
+        Self {
+            path,
+            params: RouteParams::new(),
+            builder: None,
+            name: None,
+            query: QueryParams::new(),
+        }
+    }
 }
 
 // RouteParams is now imported from crate::params::RouteParams
@@ -856,22 +2609,34 @@ pub struct RouteDescriptor {
 // Implement IntoRoute for String (simple path navigation)
 impl IntoRoute for String {
     fn into_route(self) -> RouteDescriptor {
-        RouteDescriptor {
-            path: self,
-            params: RouteParams::new(),
-            builder: None,
-        }
+        RouteDescriptor::path(self)
     }
 }
 
 // Implement IntoRoute for &str
 impl IntoRoute for &str {
     fn into_route(self) -> RouteDescriptor {
-        RouteDescriptor {
-            path: self.to_string(),
-            params: RouteParams::new(),
-            builder: None,
-        }
+        RouteDescriptor::path(self.to_string())
+    }
+}
+
+// Implement IntoRoute for SharedString, as returned by `Navigator::current_path`
+impl IntoRoute for SharedString {
+    fn into_route(self) -> RouteDescriptor {
+        RouteDescriptor::path(self.to_string())
+    }
+}
+
+impl IntoRoute for RouteDescriptor {
+    fn into_route(self) -> RouteDescriptor {
+        self
+    }
+}
+
+// Implement IntoRoute for PathPattern (compile-time checked path)
+impl IntoRoute for PathPattern {
+    fn into_route(self) -> RouteDescriptor {
+        self.path.into_route()
     }
 }
 
@@ -903,7 +2668,7 @@ impl IntoRoute for &str {
 pub struct PageRoute {
     path: String,
     params: RouteParams,
-    builder: Option<BuilderFn>,
+    builder: Option<RouteBuilder>,
 }
 
 impl PageRoute {
@@ -928,9 +2693,7 @@ impl PageRoute {
         Self {
             path: path.into(),
             params: RouteParams::new(),
-            builder: Some(Arc::new(move |window, cx, params| {
-                builder(window, cx, params).into_any_element()
-            })),
+            builder: Some(RouteBuilder::new(builder)),
         }
     }
 
@@ -943,9 +2706,7 @@ impl PageRoute {
         E: IntoElement,
         F: Fn(&mut Window, &mut App, &RouteParams) -> E + Send + Sync + 'static,
     {
-        self.builder = Some(Arc::new(move |window, cx, params| {
-            builder(window, cx, params).into_any_element()
-        }));
+        self.builder = Some(RouteBuilder::new(builder));
         self
     }
 
@@ -968,6 +2729,8 @@ impl IntoRoute for PageRoute {
             path: self.path,
             params: self.params,
             builder: self.builder,
+            name: None,
+            query: QueryParams::new(),
         }
     }
 }
@@ -979,8 +2742,7 @@ impl IntoRoute for PageRoute {
 /// ```ignore
 /// use gpui_navigator::{Navigator, NamedRoute, RouteParams};
 ///
-/// let mut params = RouteParams::new();
-/// params.set("userId".to_string(), "123".to_string());
+/// let params = RouteParams::single("userId", "123");
 /// Navigator::push_named(cx, "user_profile", &params);
 /// ```
 pub struct NamedRoute {
@@ -1013,13 +2775,119 @@ impl NamedRoute {
 impl IntoRoute for NamedRoute {
     fn into_route(self) -> RouteDescriptor {
         RouteDescriptor {
-            path: self.name,
+            path: self.name.clone(),
+            params: self.params,
+            builder: None,
+            name: Some(self.name),
+            query: QueryParams::new(),
+        }
+    }
+}
+
+/// A named route with its params and query, looked up when navigating
+///
+/// Keeps path literals out of navigation code: the path comes from the
+/// route registered under the name, so moving a route only changes its
+/// registration. Navigating to a name that is not registered, or without
+/// the params its path needs, is refused like
+/// [`Navigator::push_named`](crate::Navigator::push_named). Use [`targets!`](crate::targets)
+/// to declare the names as constants.
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{NamedTarget, Navigator, RouterLink};
+///
+/// Navigator::push(cx, NamedTarget::new("user.detail").param("id", 42).query("tab", "posts"));
+///
+/// RouterLink::to(NamedTarget::new("user.detail").param("id", 42))
+///     .child("Profile")
+///     .build(cx)
+/// ```
+#[derive(Debug, Clone)]
+pub struct NamedTarget<'a> {
+    name: &'a str,
+    params: RouteParams,
+    query: QueryParams,
+}
+
+impl<'a> NamedTarget<'a> {
+    /// Target the route registered under `name`
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            params: RouteParams::new(),
+            query: QueryParams::new(),
+        }
+    }
+
+    /// Set a path param
+    pub fn param(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.params.set(key.into(), value.to_string());
+        self
+    }
+
+    /// Add a query param
+    pub fn query(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.query.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Name of the targeted route
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Path params of the target
+    pub fn params(&self) -> &RouteParams {
+        &self.params
+    }
+
+    /// Query params of the target
+    pub fn query_params(&self) -> &QueryParams {
+        &self.query
+    }
+}
+
+impl IntoRoute for NamedTarget<'_> {
+    fn into_route(self) -> RouteDescriptor {
+        RouteDescriptor {
+            path: self.name.to_string(),
             params: self.params,
             builder: None,
+            name: Some(self.name.to_string()),
+            query: self.query,
         }
     }
 }
 
+/// Declare route names as constants
+///
+/// A misspelled constant fails to compile, where a misspelled name would
+/// only fail when navigating.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::{targets, NamedTarget};
+///
+/// targets! {
+///     USER_DETAIL = "user.detail";
+///     /// The settings page
+///     pub SETTINGS = "settings";
+/// }
+///
+/// assert_eq!(USER_DETAIL, "user.detail");
+/// let target = NamedTarget::new(SETTINGS).query("tab", "account");
+/// assert_eq!(target.name(), "settings");
+/// ```
+#[macro_export]
+macro_rules! targets {
+    ($($(#[$attr:meta])* $vis:vis $name:ident = $value:expr;)*) => {
+        $($(#[$attr])* $vis const $name: &str = $value;)*
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1052,7 +2920,7 @@ mod tests {
         registry.register("home", "/");
 
         let params = RouteParams::new();
-        assert_eq!(registry.url_for("home", &params), Some("/".to_string()));
+        assert_eq!(registry.try_url_for("home", &params), Ok("/".to_string()));
     }
 
     #[test]
@@ -1060,12 +2928,11 @@ mod tests {
         let mut registry = NamedRouteRegistry::new();
         registry.register("user.detail", "/users/:id");
 
-        let mut params = RouteParams::new();
-        params.set("id".to_string(), "123".to_string());
+        let params = RouteParams::single("id", "123");
 
         assert_eq!(
-            registry.url_for("user.detail", &params),
-            Some("/users/123".to_string())
+            registry.try_url_for("user.detail", &params),
+            Ok("/users/123".to_string())
         );
     }
 
@@ -1074,13 +2941,11 @@ mod tests {
         let mut registry = NamedRouteRegistry::new();
         registry.register("post.comment", "/posts/:postId/comments/:commentId");
 
-        let mut params = RouteParams::new();
-        params.set("postId".to_string(), "42".to_string());
-        params.set("commentId".to_string(), "99".to_string());
+        let params = RouteParams::from([("postId", "42"), ("commentId", "99")]);
 
         assert_eq!(
-            registry.url_for("post.comment", &params),
-            Some("/posts/42/comments/99".to_string())
+            registry.try_url_for("post.comment", &params),
+            Ok("/posts/42/comments/99".to_string())
         );
     }
 
@@ -1089,7 +2954,10 @@ mod tests {
         let registry = NamedRouteRegistry::new();
         let params = RouteParams::new();
 
-        assert_eq!(registry.url_for("unknown", &params), None);
+        assert_eq!(
+            registry.try_url_for("unknown", &params),
+            Err(UrlForError::UnknownRoute("unknown".to_string()))
+        );
     }
 
     #[test]
@@ -1107,13 +2975,172 @@ mod tests {
     }
 
     #[test]
-    fn test_substitute_params() {
-        let mut params = RouteParams::new();
-        params.set("id".to_string(), "123".to_string());
-        params.set("action".to_string(), "edit".to_string());
+    fn test_registry_missing_params() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("post.comment", "/posts/:postId/comments/:id{uuid}");
+
+        let mut params = RouteParams::single("postId", "1");
+
+        assert_eq!(
+            registry.missing_params("post.comment", &params),
+            Some(vec!["id".to_string()])
+        );
+
+        params.set("id".to_string(), "abc".to_string());
+        assert_eq!(
+            registry.missing_params("post.comment", &params),
+            Some(vec![])
+        );
+        assert_eq!(registry.missing_params("unknown", &params), None);
+    }
+
+    #[test]
+    fn test_url_for_checks_constraints() {
+        use crate::matcher::{register_constraint, Constraint};
+
+        register_constraint("test_even", |value| {
+            value.parse::<u32>().is_ok_and(|n| n % 2 == 0)
+        });
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("user", "/users/:id{int}");
+        registry.register("post", "/posts/:id{uuid}");
+        registry.register("page", "/pages/:n{test_even}");
+
+        let violation = |param: &str, constraint: Constraint, value: &str| {
+            Err(UrlForError::ConstraintViolation {
+                param: param.to_string(),
+                constraint,
+                value: value.to_string(),
+            })
+        };
+        let id = |value: &str| RouteParams::single("id", value);
+        assert_eq!(
+            registry.try_url_for("user", &id("abc")),
+            violation("id", Constraint::Numeric, "abc")
+        );
+        assert_eq!(
+            registry.try_url_for("post", &id("42")),
+            violation("id", Constraint::Uuid, "42")
+        );
+        assert_eq!(
+            registry.try_url_for("post", &id("550e8400-e29b-41d4-a716-446655440000")),
+            Ok("/posts/550e8400-e29b-41d4-a716-446655440000".to_string())
+        );
+        assert_eq!(
+            registry.try_url_for("page", &RouteParams::single("n", "3")),
+            violation("n", Constraint::Custom("test_even".to_string()), "3")
+        );
+        assert_eq!(
+            registry.try_url_for("page", &RouteParams::single("n", "4")),
+            Ok("/pages/4".to_string())
+        );
+        assert_eq!(
+            registry.try_url_for("user", &RouteParams::new()),
+            Err(UrlForError::MissingParams(vec!["id".to_string()]))
+        );
+
+        // Extra params go into the query unless the registry is strict
+        let mut params = RouteParams::from([("id", "7"), ("tab", "posts")]);
+        assert_eq!(
+            registry.try_url_for("user", &params),
+            Ok("/users/7?tab=posts".to_string())
+        );
+        registry.set_strict(true);
+        assert_eq!(
+            registry.try_url_for("user", &params),
+            Err(UrlForError::ExtraParams(vec!["tab".to_string()]))
+        );
+        params.all_mut().remove("tab");
+        assert_eq!(
+            registry.try_url_for("user", &params),
+            Ok("/users/7".to_string())
+        );
+    }
+
+    #[test]
+    fn test_url_for_expands_pattern() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("user.action", "/users/:id/:action");
+        registry.register("files", "/files/*");
+
+        let mut params = RouteParams::from([("id", "123"), ("action", "edit")]);
+        assert_eq!(
+            registry.try_url_for("user.action", &params),
+            Ok("/users/123/edit".to_string())
+        );
+        assert_eq!(
+            registry.try_url_for("files", &params),
+            Ok("/files?action=edit&id=123".to_string())
+        );
+
+        // `:id` is not a prefix match of `:identity`
+        registry.register("identity", "/me/:identity");
+        params.set("identity".to_string(), "alice".to_string());
+        assert_eq!(
+            registry.try_url_for("identity", &params),
+            Ok("/me/alice?action=edit&id=123".to_string())
+        );
+
+        // A missing param gives no URL rather than one with `:action` in it
+        params.all_mut().remove("action");
+        assert_eq!(
+            registry.try_url_for("user.action", &params),
+            Err(UrlForError::MissingParams(vec!["action".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_registry_alias() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("users.detail", "/users/:id");
+        registry.register_alias("user.profile", "users.detail");
+
+        assert!(registry.contains("user.profile"));
+        assert_eq!(registry.get("user.profile"), Some("/users/:id"));
+        assert_eq!(registry.aliases_of("users.detail"), vec!["user.profile"]);
+        assert_eq!(
+            registry.try_url_for("user.profile", &RouteParams::single("id", "7")),
+            Ok("/users/7".to_string())
+        );
+
+        // A registered name wins over an alias of the same name
+        registry.register("user.profile", "/profile");
+        assert_eq!(registry.get("user.profile"), Some("/profile"));
+    }
+
+    #[test]
+    fn test_alias_deprecation_logged_once() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("users.detail", "/users/:id");
+        registry.register_alias("user.profile", "users.detail");
+        registry.register_alias("member", "users.detail");
+
+        assert!(registry.note_alias_use("user.profile", "users.detail"));
+        assert!(!registry.note_alias_use("user.profile", "users.detail"));
+        assert!(registry.note_alias_use("member", "users.detail"));
+
+        // Clones share what was logged
+        let clone = registry.clone();
+        assert!(!clone.note_alias_use("member", "users.detail"));
+    }
+
+    #[test]
+    fn test_unknown_name_suggestions() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("users.detail", "/users/:id");
+        registry.register("users.list", "/users");
+        registry.register("settings", "/settings");
 
-        let result = substitute_params("/users/:id/:action", &params);
-        assert_eq!(result, "/users/123/edit");
+        assert_eq!(registry.suggestions("users.detial"), vec!["users.detail"]);
+        assert_eq!(
+            registry.unknown_name_message("users.detial"),
+            "Unknown route name 'users.detial', did you mean 'users.detail'?"
+        );
+        assert!(registry.suggestions("billing").is_empty());
+        assert_eq!(
+            registry.unknown_name_message("billing"),
+            "Unknown route name 'billing'"
+        );
     }
 
     // Route tests
@@ -1143,6 +3170,58 @@ mod tests {
 
         let result = match_path("/files/*", "/other/path");
         assert!(result.is_none());
+
+        let result = match_path("/files/:id/*", "/files/7/a/b.txt").unwrap();
+        assert_eq!(result.params.get("id"), Some(&"7".to_string()));
+        assert_eq!(result.params.get("*"), Some(&"a/b.txt".to_string()));
+
+        // Matching nothing leaves an empty remainder
+        let result = match_path("/files/*", "/files/").unwrap();
+        assert_eq!(result.params.get("*"), Some(&String::new()));
+        assert!(match_path("/files/:id/*", "/files").is_none());
+    }
+
+    #[test]
+    fn test_pre_parsed_pattern_skips_parsing() {
+        // Segments disagreeing with the path show which one the matcher reads
+        static SEGMENTS: [PathSegment<'static>; 2] =
+            [PathSegment::Static("people"), PathSegment::Param("id")];
+        let pattern = PathPattern::from_parsed("/users/:id", &SEGMENTS);
+
+        let mut route = Route::new(pattern, |_, _, _| gpui::div());
+        assert!(route.matches("/users/1").is_none());
+        assert_eq!(
+            route.matches("/people/1").unwrap().params.get("id"),
+            Some(&"1".to_string())
+        );
+
+        // A changed path is parsed again
+        route.config.path = "/members/:id".to_string();
+        assert!(route.matches("/members/1").is_some());
+        assert!(route.matches("/people/1").is_none());
+    }
+
+    #[test]
+    fn test_route_honors_param_constraints() {
+        let route = Route::new("/users/:id{\\d+}", |_, _, _| gpui::div());
+        assert_eq!(
+            route.matches("/users/7").unwrap().params.get("id"),
+            Some(&"7".to_string())
+        );
+        assert!(route.matches("/users/me").is_none());
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("/files/:id/*"),
+            vec![
+                PathSegment::Static("files"),
+                PathSegment::Param("id"),
+                PathSegment::Wildcard
+            ]
+        );
+        assert!(parse_path("/").is_empty());
     }
 
     #[test]
@@ -1217,6 +3296,101 @@ mod tests {
         assert!(result.unwrap_err().contains("Duplicate"));
     }
 
+    #[test]
+    fn test_validate_wildcard_position() {
+        assert!(validate_route_path("/files/*").is_ok());
+        assert!(validate_route_path("/files/*/").is_ok());
+        assert_eq!(
+            validate_route_path("/files/*/meta").unwrap_err(),
+            "Wildcard '*' must be the last segment of a route path"
+        );
+        assert!(validate_route_path("/*/*").is_err());
+    }
+
+    /// Route `l0` with the chain `l1`, ... `l<levels - 1>` below it
+    fn deep_chain(levels: usize) -> Route {
+        (0..levels)
+            .rev()
+            .fold(None, |child: Option<Route>, level| {
+                let route = Route::new(format!("l{level}"), |_, _cx, _params| gpui::div());
+                Some(match child {
+                    Some(child) => route.child(child.into()),
+                    None => route,
+                })
+            })
+            .unwrap()
+    }
+
+    /// Full path of the last route of a [`deep_chain`] of `levels`
+    fn chain_path(levels: usize) -> String {
+        let segments: Vec<String> = (0..levels).map(|level| format!("l{level}")).collect();
+        format!("/{}", segments.join("/"))
+    }
+
+    #[test]
+    fn test_route_that_is_its_own_ancestor_is_reported() {
+        // Safe code cannot build a cyclic tree, so the walk starts below
+        // the shared subtree, as if it listed its own ancestor as a child
+        let tabs: RouteRef = Arc::new(Route::new("tabs", |_, _cx, _params| gpui::div()));
+        let app = Route::new("app", |_, _cx, _params| gpui::div()).child(
+            Route::new("inbox", |_, _cx, _params| gpui::div())
+                .child(Arc::clone(&tabs))
+                .into(),
+        );
+        let mut issues = Vec::new();
+        let mut walk = TreeWalk {
+            ancestors: vec![(Arc::as_ptr(&tabs), "/tabs".to_string())],
+            max_depth: DEFAULT_MAX_ROUTE_DEPTH,
+            issues: &mut issues,
+        };
+        walk.visit(&app, "/tabs");
+
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_error());
+        assert_eq!(issues[0].path, "/tabs/app/inbox");
+        assert_eq!(
+            issues[0].message,
+            "Route '/tabs' is its own ancestor: /tabs -> /tabs/app -> /tabs/app/inbox -> /tabs"
+        );
+
+        // The same subtree under two parents is not a cycle
+        let shared = Route::new("/app", |_, _cx, _params| gpui::div()).children(vec![
+            Arc::new(Route::new("a", |_, _cx, _params| gpui::div()).child(Arc::clone(&tabs))),
+            Arc::new(Route::new("b", |_, _cx, _params| gpui::div()).child(tabs)),
+        ]);
+        assert!(validate_route_tree(&shared).is_empty());
+    }
+
+    #[test]
+    fn test_route_tree_depth_is_limited() {
+        let issues = validate_route_tree(&deep_chain(100));
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_error());
+        // The first level past the limit is reported
+        assert_eq!(issues[0].path, chain_path(DEFAULT_MAX_ROUTE_DEPTH + 1));
+        assert!(issues[0]
+            .message
+            .ends_with("is nested deeper than 32 levels"));
+
+        assert!(route_tree_issues(&deep_chain(100), 100).is_empty());
+        assert_eq!(route_tree_issues(&deep_chain(101), 100).len(), 1);
+    }
+
+    #[test]
+    fn test_lookups_stop_at_the_depth_limit() {
+        // Deeper than any registered tree can be
+        let routes = vec![Arc::new(deep_chain(ROUTE_LOOKUP_DEPTH + 10))];
+        let path = chain_path(ROUTE_LOOKUP_DEPTH + 10);
+        assert!(match_route_chain(&routes, &path).is_none());
+        assert_eq!(route_full_paths(&routes, "").len(), ROUTE_LOOKUP_DEPTH);
+
+        // Within the limit they descend all the way
+        let routes = vec![Arc::new(deep_chain(100))];
+        let path = chain_path(100);
+        let (chain, _) = match_route_chain(&routes, &path).unwrap();
+        assert_eq!(chain.len(), 100);
+    }
+
     #[test]
     fn test_route_config_try_new_valid() {
         let result = RouteConfig::try_new("/users/:id");
@@ -1235,4 +3409,120 @@ mod tests {
     fn test_route_config_new_panics_on_invalid() {
         RouteConfig::new("/users//profile");
     }
+
+    // Config-driven route tests
+
+    fn config_builders(names: &[&str]) -> HashMap<String, RouteBuilder> {
+        names
+            .iter()
+            .map(|name| {
+                let builder = RouteBuilder::new(|_, _, _| gpui::div());
+                ((*name).to_string(), builder)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_route_from_config_two_levels() {
+        let builders = config_builders(&["dashboard", "overview", "settings", "profile"]);
+        let config = RouteConfig::new("/dashboard")
+            .name("dashboard")
+            .child(RouteConfig::new("overview").name("overview"))
+            .child(
+                RouteConfig::new("settings")
+                    .name("settings")
+                    .child(RouteConfig::new("profile").name("profile")),
+            );
+
+        let route = Route::from_config(config, &builders).unwrap();
+        assert!(route.builder.is_some());
+        assert_eq!(route.get_children().len(), 2);
+
+        let settings = route.find_child("settings").unwrap();
+        assert!(settings.builder.is_some());
+        assert_eq!(settings.get_children().len(), 1);
+        assert_eq!(settings.get_children()[0].config.path, "profile");
+    }
+
+    #[test]
+    fn test_route_from_config_missing_builder() {
+        let builders = config_builders(&["dashboard"]);
+        let config = RouteConfig::new("/dashboard")
+            .name("dashboard")
+            .child(RouteConfig::new("overview").name("overview"));
+
+        let err = Route::from_config(config, &builders).unwrap_err();
+        assert!(err.contains("overview"));
+    }
+
+    #[test]
+    fn test_route_builder_from_windowless_builders() {
+        fn about(_cx: &mut App, _params: &RouteParams) -> AnyElement {
+            gpui::div().into_any_element()
+        }
+
+        let shared: Arc<WindowlessBuildFn> =
+            Arc::new(|_cx, _params| gpui::div().into_any_element());
+        let home = Route::with_builder("/", Arc::clone(&shared));
+        let settings = Route::with_builder("/settings", shared);
+        let about = Route::with_builder("/about", about as fn(&mut App, &RouteParams) -> _);
+        assert!(home.builder.is_some() && settings.builder.is_some() && about.builder.is_some());
+
+        let window_aware: Arc<BuildFn> =
+            Arc::new(|_window, _cx, _params| gpui::div().into_any_element());
+        let builder = RouteBuilder::from(Arc::clone(&window_aware));
+        assert!(builder.ptr_eq(&RouteBuilder::from(window_aware)));
+    }
+
+    #[test]
+    fn test_route_from_config_unnamed() {
+        let builders = config_builders(&["dashboard"]);
+        let result = Route::from_config(RouteConfig::new("/dashboard"), &builders);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_group_layouts_are_transparent() {
+        let page = |path: &str| Arc::new(Route::new(path, |_, _, _| gpui::div()));
+        let layout =
+            |children| Arc::new(Route::group_layout(|_, _, _| gpui::div()).children(children));
+        let auth = layout(vec![page("/login"), page("/register")]);
+        let app = layout(vec![
+            page("/inbox"),
+            Arc::new(
+                Route::new("/settings", |_, _, _| gpui::div())
+                    .children(vec![layout(vec![page("profile")])]),
+            ),
+        ]);
+        let routes = vec![Arc::clone(&auth), Arc::clone(&app), page("/")];
+
+        let chain = |path| {
+            let (chain, _) = match_route_chain(&routes, path).unwrap();
+            chain.into_iter().cloned().collect::<Vec<_>>()
+        };
+        assert!(Arc::ptr_eq(&chain("/login")[0], &auth));
+        assert_eq!(chain("/register")[1].config.path, "/register");
+        assert!(Arc::ptr_eq(&chain("/inbox")[0], &app));
+        let profile = chain("/settings/profile");
+        assert_eq!(profile.len(), 4);
+        assert!(profile[2].pathless);
+        assert_eq!(profile[3].config.path, "profile");
+
+        // The layouts match nothing themselves, not even the root
+        assert_eq!(chain("/").len(), 1);
+        assert!(match_route_chain(&routes, "/signup").is_none());
+        assert!(auth.matches("/").is_none());
+        assert_eq!(
+            route_full_paths(&routes, ""),
+            [
+                "/login",
+                "/register",
+                "/inbox",
+                "/settings",
+                "/settings/profile",
+                "/"
+            ]
+        );
+        assert_eq!(shared_chain_depth(&routes, "/login", "/register"), 1);
+    }
 }
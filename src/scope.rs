@@ -0,0 +1,368 @@
+//! Route-scoped state
+//!
+//! State registered with [`Route::state`](crate::Route::state) lives exactly
+//! as long as its route is in the matched chain. It is created the first time
+//! a builder asks for it with [`Navigator::route_state`](crate::Navigator::route_state)
+//! and dropped when navigation leaves the route:
+//!
+//! ```ignore
+//! use gpui_navigator::{Navigator, Route};
+//!
+//! #[derive(Default)]
+//! struct Draft {
+//!     text: String,
+//! }
+//!
+//! Route::new("/compose", |_, cx, _params| {
+//!     let draft = Navigator::route_state::<Draft>(cx).unwrap();
+//!     editor(draft)
+//! })
+//! .state(Draft::default)
+//! .on_state_dispose(|draft: &mut Draft, _cx| save_for_later(&draft.text));
+//! ```
+//!
+//! Each set of params gets its own instance: `/users/1` and `/users/2` do not
+//! share the state of `/users/:id`. Nested routes see the state of their
+//! parents. Dispose callbacks are run by [`dispose_route_states`], which
+//! `RouterOutlet` calls on render.
+
+use crate::context::GlobalRouter;
+use crate::nested::{build_child_path, path_segments};
+use crate::route::{match_route_chain, RouteRef};
+use crate::trace_log;
+use gpui::{AnyEntity, App, AppContext, BorrowAppContext, Entity};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Creates a route's scoped state
+pub type StateFactory = Arc<dyn Fn() -> Box<dyn Any> + Send + Sync>;
+
+/// Runs when a route's scoped state is dropped
+pub type StateDispose = Arc<dyn Fn(AnyEntity, &mut App) + Send + Sync>;
+
+/// Scoped state registered on a route with `Route::state`
+#[derive(Clone)]
+pub struct RouteState {
+    pub(crate) type_id: TypeId,
+    pub(crate) create: StateFactory,
+    pub(crate) dispose: Option<StateDispose>,
+}
+
+/// Identifies a scoped state instance
+///
+/// `route` is the route's full pattern (`/users/:id`), `path` the part of the
+/// current path it matched (`/users/7`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ScopeKey {
+    route: String,
+    path: String,
+    type_id: TypeId,
+}
+
+#[derive(Clone)]
+pub(crate) struct ScopedState {
+    entity: AnyEntity,
+    dispose: Option<StateDispose>,
+}
+
+/// Scoped state of the routes in the matched chain
+#[derive(Clone, Default)]
+pub struct ScopedStates {
+    states: HashMap<ScopeKey, ScopedState>,
+    /// Dropped states waiting for their dispose callback
+    disposed: Vec<ScopedState>,
+}
+
+impl std::fmt::Debug for ScopedStates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScopedStates")
+            .field("states", &self.states.len())
+            .field("disposed", &self.disposed.len())
+            .finish()
+    }
+}
+
+impl ScopedStates {
+    /// Number of live scoped states
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Whether dropped states wait for their dispose callback
+    pub(crate) fn has_disposed(&self) -> bool {
+        !self.disposed.is_empty()
+    }
+
+    /// Check if no scoped state is alive
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Drop states whose route and matched path are not in `scopes`
+    ///
+    /// States with a dispose callback are kept until [`dispose_route_states`].
+    pub(crate) fn retain_scopes(&mut self, scopes: &[RouteScope<'_>]) {
+        let (kept, dropped): (HashMap<_, _>, HashMap<_, _>) = std::mem::take(&mut self.states)
+            .into_iter()
+            .partition(|(key, _)| {
+                scopes
+                    .iter()
+                    .any(|scope| scope.route == key.route && scope.path == key.path)
+            });
+        self.states = kept;
+        for (key, state) in dropped {
+            trace_log!("Dropping scoped state of '{}' ({})", key.route, key.path);
+            if state.dispose.is_some() {
+                self.disposed.push(state);
+            }
+        }
+    }
+
+    /// Drop all states
+    pub(crate) fn clear(&mut self) {
+        self.retain_scopes(&[]);
+    }
+}
+
+/// A route in the matched chain with its full pattern and matched path
+pub(crate) struct RouteScope<'a> {
+    pub(crate) route_ref: &'a RouteRef,
+    pub(crate) route: String,
+    pub(crate) path: String,
+}
+
+/// Scopes of the routes matching `path`, outermost first
+pub(crate) fn route_scopes<'a>(routes: &'a [RouteRef], path: &str) -> Vec<RouteScope<'a>> {
+    let Some((chain, _)) = match_route_chain(routes, path) else {
+        return Vec::new();
+    };
+    let segments = path_segments(path);
+
+    let mut full_path = String::new();
+    chain
+        .into_iter()
+        .map(|route_ref| {
+            full_path = build_child_path(&full_path, &route_ref.config.path).into_owned();
+            let depth = path_segments(&full_path).len().min(segments.len());
+            RouteScope {
+                route_ref,
+                route: full_path.clone(),
+                path: format!("/{}", segments[..depth].join("/")),
+            }
+        })
+        .collect()
+}
+
+/// Get the scoped state of type `T` for the current route, creating it
+///
+/// See [`Navigator::route_state`](crate::Navigator::route_state).
+pub(crate) fn route_state<T: 'static>(cx: &mut App) -> Option<Entity<T>> {
+    dispose_route_states(cx);
+
+    let type_id = TypeId::of::<T>();
+    let router = cx.try_global::<GlobalRouter>()?;
+    let scopes = route_scopes(router.state().routes(), router.current_path());
+    let (scope, state) = scopes.iter().rev().find_map(|scope| {
+        scope
+            .route_ref
+            .states
+            .iter()
+            .find(|state| state.type_id == type_id)
+            .map(|state| (scope, state))
+    })?;
+    let key = ScopeKey {
+        route: scope.route.clone(),
+        path: scope.path.clone(),
+        type_id,
+    };
+
+    if let Some(existing) = router.state().scoped_states().states.get(&key) {
+        return existing.entity.clone().downcast::<T>().ok();
+    }
+
+    let value = (state.create)().downcast::<T>().ok()?;
+    let dispose = state.dispose.clone();
+    trace_log!("Creating scoped state of '{}' ({})", key.route, key.path);
+    let entity = cx.new(|_| *value);
+    cx.update_global::<GlobalRouter, _>(|router, _| {
+        router.state_mut().scoped_states_mut().states.insert(
+            key,
+            ScopedState {
+                entity: entity.clone().into_any(),
+                dispose,
+            },
+        );
+    });
+    Some(entity)
+}
+
+/// Run the dispose callbacks of scoped state dropped by navigation
+///
+/// The router calls this after each navigation, so apps only need it to
+/// dispose of dropped states sooner.
+pub fn dispose_route_states(cx: &mut App) {
+    let disposed = match cx.try_global::<GlobalRouter>() {
+        Some(router) if router.state().scoped_states().has_disposed() => cx
+            .update_global::<GlobalRouter, _>(|router, _| {
+                std::mem::take(&mut router.state_mut().scoped_states_mut().disposed)
+            }),
+        _ => return,
+    };
+
+    for state in disposed {
+        if let Some(dispose) = state.dispose {
+            dispose(state.entity, cx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{init_router, render_router_outlet, Navigator, Route, RouterOutlet};
+    use gpui::{div, EntityId, IntoElement, ParentElement, TestAppContext, VisualTestContext};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Draft {
+        text: String,
+    }
+
+    /// Entity ids of the drafts builders saw, with the path they rendered
+    type Seen = Arc<Mutex<Vec<(String, EntityId)>>>;
+
+    fn record(seen: &Seen, cx: &mut gpui::App, path: &str) {
+        let draft = Navigator::route_state::<Draft>(cx).expect("draft state");
+        seen.lock()
+            .unwrap()
+            .push((path.to_string(), draft.entity_id()));
+    }
+
+    /// `/compose` with a draft and a nested `preview`, `/users/:id` with its
+    /// own draft and `/` without one
+    fn setup(cx: &mut TestAppContext) -> (&mut VisualTestContext, Seen, Arc<Mutex<Vec<String>>>) {
+        let seen = Seen::default();
+        let disposed = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let (compose_seen, preview_seen, user_seen) =
+                (seen.clone(), seen.clone(), seen.clone());
+            let disposed = disposed.clone();
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::new("/compose", move |window, cx, _| {
+                        record(&compose_seen, cx, "/compose");
+                        div()
+                            .child(render_router_outlet(window, cx, None))
+                            .into_any_element()
+                    })
+                    .state(Draft::default)
+                    .on_state_dispose(move |draft: &mut Draft, _| {
+                        disposed.lock().unwrap().push(draft.text.clone());
+                    })
+                    .child(
+                        Route::new("preview", move |_, cx, _| {
+                            record(&preview_seen, cx, "/compose/preview");
+                            div().into_any_element()
+                        })
+                        .into(),
+                    ),
+                );
+                router.add_route(
+                    Route::new("/users/:id", move |_, cx, params| {
+                        let path = format!("/users/{}", params.get("id").unwrap());
+                        record(&user_seen, cx, &path);
+                        div().into_any_element()
+                    })
+                    .state(Draft::default),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        (cx, seen, disposed)
+    }
+
+    fn navigate(cx: &mut VisualTestContext, path: &str) {
+        cx.update(|window, cx| {
+            Navigator::push(cx, path);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    fn ids(seen: &Seen, path: &str) -> Vec<EntityId> {
+        let seen = seen.lock().unwrap();
+        let mut ids: Vec<_> = seen
+            .iter()
+            .filter(|(seen_path, _)| seen_path == path)
+            .map(|(_, id)| *id)
+            .collect();
+        ids.dedup();
+        ids
+    }
+
+    #[gpui::test]
+    fn test_route_state_persists_across_renders(cx: &mut TestAppContext) {
+        let (cx, seen, _) = setup(cx);
+
+        navigate(cx, "/compose");
+        cx.update(|window, cx| {
+            let draft = Navigator::route_state::<Draft>(cx).unwrap();
+            draft.update(cx, |draft, _| draft.text = "Hello".to_string());
+            window.refresh();
+        });
+        cx.run_until_parked();
+        assert_eq!(ids(&seen, "/compose").len(), 1);
+
+        // The nested route sees its parent's state
+        navigate(cx, "/compose/preview");
+        assert_eq!(ids(&seen, "/compose/preview"), ids(&seen, "/compose"));
+        cx.update(|_, cx| {
+            let draft = Navigator::route_state::<Draft>(cx).unwrap();
+            assert_eq!(draft.read(cx).text, "Hello");
+        });
+    }
+
+    #[gpui::test]
+    fn test_route_state_disposed_on_leave(cx: &mut TestAppContext) {
+        let (cx, seen, disposed) = setup(cx);
+
+        navigate(cx, "/compose");
+        cx.update(|_, cx| {
+            let draft = Navigator::route_state::<Draft>(cx).unwrap();
+            draft.update(cx, |draft, _| draft.text = "unsent".to_string());
+        });
+        navigate(cx, "/");
+        assert_eq!(*disposed.lock().unwrap(), vec!["unsent"]);
+        assert!(cx
+            .update(|_, cx| Navigator::route_state::<Draft>(cx))
+            .is_none());
+
+        // Coming back starts from a fresh instance
+        navigate(cx, "/compose");
+        assert_eq!(ids(&seen, "/compose").len(), 2);
+        cx.update(|_, cx| {
+            let draft = Navigator::route_state::<Draft>(cx).unwrap();
+            assert_eq!(draft.read(cx).text, "");
+        });
+    }
+
+    #[gpui::test]
+    fn test_route_state_per_params(cx: &mut TestAppContext) {
+        let (cx, seen, _) = setup(cx);
+
+        navigate(cx, "/users/1");
+        navigate(cx, "/users/2");
+        let (first, second) = (ids(&seen, "/users/1"), ids(&seen, "/users/2"));
+        assert_eq!((first.len(), second.len()), (1, 1));
+        assert_ne!(first, second);
+        assert_eq!(
+            cx.update(|_, cx| cx
+                .global::<crate::GlobalRouter>()
+                .state()
+                .scoped_states()
+                .len()),
+            1
+        );
+    }
+}
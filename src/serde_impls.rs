@@ -0,0 +1,623 @@
+//! Serialization of routes, navigation events and history (`serde` feature)
+//!
+//! The field names and shapes written here are a compatibility contract:
+//! data written by one version reads back in later ones. Maps are written
+//! with their keys sorted, so the output is the same for equal values.
+//! Each type documents its format.
+
+use crate::history::{EntryKind, HistoryEntry, HistoryState};
+use crate::params::{ParamsDiff, QueryParams, RouteParams};
+use crate::{NavigationDirection, NavigationSource, RouteChangeEvent, RouteMatch};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "wall-clock")]
+use std::time::SystemTime;
+use std::time::{Duration, Instant};
+
+/// `map` with its keys sorted
+fn sorted<V>(map: &HashMap<String, V>) -> BTreeMap<&String, &V> {
+    map.iter().collect()
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for RouteParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        sorted(self.all()).serialize(serializer)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for RouteParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::deserialize(deserializer).map(RouteParams::from_map)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for QueryParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.iter()
+            .collect::<BTreeMap<_, _>>()
+            .serialize(serializer)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for QueryParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let params = HashMap::<String, Vec<String>>::deserialize(deserializer)?;
+        let mut query = QueryParams::new();
+        for (key, values) in params {
+            for value in values {
+                query.insert(key.clone(), value);
+            }
+        }
+        Ok(query)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for RouteMatch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = 3 + usize::from(self.fragment.is_some());
+        let mut state = serializer.serialize_struct("RouteMatch", fields)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field("params", &sorted(&self.params))?;
+        state.serialize_field("query", &sorted(&self.query))?;
+        match &self.fragment {
+            Some(fragment) => state.serialize_field("fragment", fragment)?,
+            None => state.skip_field("fragment")?,
+        }
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct RouteMatchRepr {
+    path: String,
+    #[serde(default)]
+    params: HashMap<String, String>,
+    #[serde(default)]
+    query: HashMap<String, String>,
+    #[serde(default)]
+    fragment: Option<String>,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for RouteMatch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = RouteMatchRepr::deserialize(deserializer)?;
+        Ok(RouteMatch {
+            path: repr.path,
+            params: repr.params,
+            query: repr.query,
+            fragment: repr.fragment,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "NavigationDirection", rename_all = "lowercase")]
+enum DirectionRepr {
+    Forward,
+    Back,
+    Replace,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for NavigationDirection {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NavigationDirection::Forward => DirectionRepr::Forward,
+            NavigationDirection::Back => DirectionRepr::Back,
+            NavigationDirection::Replace => DirectionRepr::Replace,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for NavigationDirection {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match DirectionRepr::deserialize(deserializer)? {
+            DirectionRepr::Forward => NavigationDirection::Forward,
+            DirectionRepr::Back => NavigationDirection::Back,
+            DirectionRepr::Replace => NavigationDirection::Replace,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "NavigationSource", tag = "kind", rename_all = "snake_case")]
+enum SourceRepr {
+    Programmatic,
+    Link {
+        #[serde(default)]
+        element_id: Option<String>,
+    },
+    Action,
+    DeepLink,
+    History,
+    Redirect {
+        from_guard: String,
+    },
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for NavigationSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.clone() {
+            NavigationSource::Programmatic => SourceRepr::Programmatic,
+            NavigationSource::Link { element_id } => SourceRepr::Link { element_id },
+            NavigationSource::Action => SourceRepr::Action,
+            NavigationSource::DeepLink => SourceRepr::DeepLink,
+            NavigationSource::History => SourceRepr::History,
+            NavigationSource::Redirect { from_guard } => SourceRepr::Redirect { from_guard },
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for NavigationSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match SourceRepr::deserialize(deserializer)? {
+            SourceRepr::Programmatic => NavigationSource::Programmatic,
+            SourceRepr::Link { element_id } => NavigationSource::Link { element_id },
+            SourceRepr::Action => NavigationSource::Action,
+            SourceRepr::DeepLink => NavigationSource::DeepLink,
+            SourceRepr::History => NavigationSource::History,
+            SourceRepr::Redirect { from_guard } => NavigationSource::Redirect { from_guard },
+        })
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for ParamsDiff {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ParamsDiff", 3)?;
+        state.serialize_field("added", &sorted(&self.added))?;
+        state.serialize_field("removed", &sorted(&self.removed))?;
+        state.serialize_field("changed", &sorted(&self.changed))?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct ParamsDiffRepr {
+    #[serde(default)]
+    added: HashMap<String, String>,
+    #[serde(default)]
+    removed: HashMap<String, String>,
+    #[serde(default)]
+    changed: HashMap<String, (String, String)>,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for ParamsDiff {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ParamsDiffRepr::deserialize(deserializer)?;
+        Ok(ParamsDiff {
+            added: repr.added,
+            removed: repr.removed,
+            changed: repr.changed,
+        })
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for RouteChangeEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = 7
+            + usize::from(self.fragment.is_some())
+            + usize::from(self.fragment_only)
+            + usize::from(self.pattern.is_some())
+            + usize::from(self.route_name.is_some())
+            + usize::from(!self.params.is_empty())
+            + usize::from(!self.level_params.is_empty())
+            + usize::from(!self.query.is_empty())
+            + usize::from(self.source != NavigationSource::Programmatic);
+        let mut state = serializer.serialize_struct("RouteChangeEvent", fields)?;
+        state.serialize_field("from", &self.from)?;
+        state.serialize_field("to", &self.to)?;
+        match &self.fragment {
+            Some(fragment) => state.serialize_field("fragment", fragment)?,
+            None => state.skip_field("fragment")?,
+        }
+        if self.fragment_only {
+            state.serialize_field("fragment_only", &true)?;
+        } else {
+            state.skip_field("fragment_only")?;
+        }
+        state.serialize_field("direction", &self.direction)?;
+        state.serialize_field("same_route", &self.same_route)?;
+        state.serialize_field("params_diff", &self.params_diff)?;
+        state.serialize_field("previous_dwell", &self.previous_dwell)?;
+        match &self.pattern {
+            Some(pattern) => state.serialize_field("pattern", pattern)?,
+            None => state.skip_field("pattern")?,
+        }
+        match &self.route_name {
+            Some(name) => state.serialize_field("route_name", name)?,
+            None => state.skip_field("route_name")?,
+        }
+        if self.params.is_empty() {
+            state.skip_field("params")?;
+        } else {
+            state.serialize_field("params", &self.params)?;
+        }
+        if self.level_params.is_empty() {
+            state.skip_field("level_params")?;
+        } else {
+            state.serialize_field("level_params", &self.level_params)?;
+        }
+        if self.query.is_empty() {
+            state.skip_field("query")?;
+        } else {
+            state.serialize_field("query", &self.query)?;
+        }
+        state.serialize_field("matched", &self.matched)?;
+        if self.source == NavigationSource::Programmatic {
+            state.skip_field("source")?;
+        } else {
+            state.serialize_field("source", &self.source)?;
+        }
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct RouteChangeEventRepr {
+    #[serde(default)]
+    from: Option<String>,
+    to: String,
+    #[serde(default)]
+    fragment: Option<String>,
+    #[serde(default)]
+    fragment_only: bool,
+    direction: NavigationDirection,
+    #[serde(default)]
+    same_route: bool,
+    #[serde(default)]
+    params_diff: Option<ParamsDiff>,
+    #[serde(default)]
+    previous_dwell: Option<Duration>,
+    #[serde(default)]
+    pattern: Option<String>,
+    #[serde(default)]
+    route_name: Option<String>,
+    #[serde(default)]
+    params: RouteParams,
+    #[serde(default)]
+    level_params: Vec<RouteParams>,
+    #[serde(default)]
+    query: QueryParams,
+    #[serde(default)]
+    matched: bool,
+    #[serde(default)]
+    source: NavigationSource,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for RouteChangeEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = RouteChangeEventRepr::deserialize(deserializer)?;
+        Ok(RouteChangeEvent {
+            from: repr.from,
+            to: repr.to,
+            fragment: repr.fragment,
+            fragment_only: repr.fragment_only,
+            direction: repr.direction,
+            same_route: repr.same_route,
+            params_diff: repr.params_diff,
+            previous_dwell: repr.previous_dwell,
+            pattern: repr.pattern,
+            route_name: repr.route_name,
+            params: repr.params,
+            level_params: repr.level_params,
+            query: repr.query,
+            matched: repr.matched,
+            source: repr.source,
+        })
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for HistoryState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Typed values cannot be written, only the strings are
+        let mut state = serializer.serialize_struct("HistoryState", 1)?;
+        state.serialize_field("data", &sorted(&self.data))?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct HistoryStateRepr {
+    #[serde(default)]
+    data: HashMap<String, String>,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for HistoryState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = HistoryStateRepr::deserialize(deserializer)?;
+        let mut state = HistoryState::new();
+        state.data = repr.data;
+        Ok(state)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for HistoryEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = if cfg!(feature = "wall-clock") { 3 } else { 2 };
+        let fields =
+            fields + usize::from(self.kind.is_redirect()) + usize::from(self.fragment.is_some());
+        let mut state = serializer.serialize_struct("HistoryEntry", fields)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field("state", &self.state)?;
+        match &self.fragment {
+            Some(fragment) => state.serialize_field("fragment", fragment)?,
+            None => state.skip_field("fragment")?,
+        }
+        #[cfg(feature = "wall-clock")]
+        state.serialize_field("entered_at_wall", &self.entered_at_wall)?;
+        match &self.kind {
+            EntryKind::Redirect { from } => state.serialize_field("redirected_from", from)?,
+            EntryKind::Navigation => state.skip_field("redirected_from")?,
+        }
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct HistoryEntryRepr {
+    path: String,
+    #[serde(default)]
+    fragment: Option<String>,
+    #[serde(default)]
+    state: Option<HistoryState>,
+    #[cfg(feature = "wall-clock")]
+    #[serde(default)]
+    entered_at_wall: Option<SystemTime>,
+    #[serde(default)]
+    redirected_from: Option<String>,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for HistoryEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = HistoryEntryRepr::deserialize(deserializer)?;
+        Ok(HistoryEntry {
+            path: repr.path,
+            fragment: repr.fragment,
+            state: repr.state,
+            entered_at: Instant::now(),
+            #[cfg(feature = "wall-clock")]
+            entered_at_wall: repr.entered_at_wall.unwrap_or_else(SystemTime::now),
+            kind: repr
+                .redirected_from
+                .map_or(EntryKind::Navigation, |from| EntryKind::Redirect { from }),
+        })
+    }
+}
+
+#[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "serde", feature = "cache"))))]
+impl Serialize for crate::CacheStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CacheStats", 8)?;
+        state.serialize_field("parent_hits", &self.parent_hits)?;
+        state.serialize_field("parent_misses", &self.parent_misses)?;
+        state.serialize_field("child_hits", &self.child_hits)?;
+        state.serialize_field("child_misses", &self.child_misses)?;
+        state.serialize_field("invalidations", &self.invalidations)?;
+        state.serialize_field("kept_alive", &self.kept_alive)?;
+        state.serialize_field("kept_alive_reuses", &self.kept_alive_reuses)?;
+        state.serialize_field("kept_alive_evictions", &self.kept_alive_evictions)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        let json = serde_json::to_string(value).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_route_params() {
+        let route_params = RouteParams::from_map(params(&[("b", "2"), ("a", "1"), ("c", "3")]));
+        assert_eq!(
+            serde_json::to_string(&route_params).unwrap(),
+            r#"{"a":"1","b":"2","c":"3"}"#
+        );
+        assert_eq!(round_trip(&route_params).all(), route_params.all());
+    }
+
+    #[test]
+    fn test_query_params() {
+        let query = QueryParams::from_query_string("tag=rust&page=2&tag=gpui");
+        assert_eq!(
+            serde_json::to_string(&query).unwrap(),
+            r#"{"page":["2"],"tag":["rust","gpui"]}"#
+        );
+        let back = round_trip(&query);
+        assert_eq!(back.get_all("tag"), query.get_all("tag"));
+        assert_eq!(back.get("page"), Some(&"2".to_string()));
+    }
+
+    #[test]
+    fn test_route_match() {
+        let mut route_match = RouteMatch::new("/users/7".to_string())
+            .with_param("id".to_string(), "7".to_string())
+            .with_param("org".to_string(), "acme".to_string());
+        route_match
+            .query
+            .insert("tab".to_string(), "posts".to_string());
+        assert_eq!(
+            serde_json::to_string(&route_match).unwrap(),
+            r#"{"path":"/users/7","params":{"id":"7","org":"acme"},"query":{"tab":"posts"}}"#
+        );
+
+        let back = round_trip(&route_match);
+        assert_eq!(back.path, route_match.path);
+        assert_eq!(back.params, route_match.params);
+        assert_eq!(back.query, route_match.query);
+    }
+
+    #[test]
+    fn test_route_change_event() {
+        let event = RouteChangeEvent {
+            same_route: true,
+            params_diff: Some(RouteParams::diff(
+                &RouteParams::from_map(params(&[("id", "1"), ("tab", "x")])),
+                &RouteParams::from_map(params(&[("id", "2"), ("sort", "y")])),
+            )),
+            previous_dwell: Some(Duration::from_millis(1500)),
+            pattern: Some("/users/:id".to_string()),
+            params: RouteParams::single("id", "2"),
+            matched: true,
+            ..RouteChangeEvent::new(
+                Some("/users/1".to_string()),
+                "/users/2",
+                NavigationDirection::Back,
+            )
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            concat!(
+                r#"{"from":"/users/1","to":"/users/2","direction":"back","same_route":true,"#,
+                r#""params_diff":{"added":{"sort":"y"},"removed":{"tab":"x"},"changed":{"id":["1","2"]}},"#,
+                r#""previous_dwell":{"secs":1,"nanos":500000000},"#,
+                r#""pattern":"/users/:id","params":{"id":"2"},"matched":true}"#
+            )
+        );
+
+        let back = round_trip(&event);
+        assert_eq!(back.from, event.from);
+        assert_eq!(back.to, event.to);
+        assert_eq!(back.direction, event.direction);
+        assert_eq!(back.same_route, event.same_route);
+        assert_eq!(back.params_diff, event.params_diff);
+        assert_eq!(back.previous_dwell, event.previous_dwell);
+        assert_eq!(back.pattern, event.pattern);
+        assert_eq!(back.params.get("id"), Some(&"2".to_string()));
+        assert!(back.query.is_empty());
+        assert!(back.matched);
+    }
+
+    #[test]
+    fn test_navigation_direction() {
+        for direction in [
+            NavigationDirection::Forward,
+            NavigationDirection::Back,
+            NavigationDirection::Replace,
+        ] {
+            assert_eq!(round_trip(&direction), direction);
+        }
+        assert_eq!(
+            serde_json::to_string(&NavigationDirection::Replace).unwrap(),
+            r#""replace""#
+        );
+    }
+
+    #[test]
+    fn test_navigation_source() {
+        for source in [
+            NavigationSource::Programmatic,
+            NavigationSource::Link { element_id: None },
+            NavigationSource::Action,
+            NavigationSource::DeepLink,
+            NavigationSource::History,
+        ] {
+            assert_eq!(round_trip(&source), source);
+        }
+        let link = NavigationSource::Link {
+            element_id: Some("sidebar-inbox".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_string(&link).unwrap(),
+            r#"{"kind":"link","element_id":"sidebar-inbox"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&NavigationSource::DeepLink).unwrap(),
+            r#"{"kind":"deep_link"}"#
+        );
+
+        // Only sources other than the default are written with events
+        let event = RouteChangeEvent {
+            source: NavigationSource::Redirect {
+                from_guard: "AuthGuard".to_string(),
+            },
+            ..RouteChangeEvent::new(None, "/login", NavigationDirection::Replace)
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.ends_with(r#""source":{"kind":"redirect","from_guard":"AuthGuard"}}"#));
+        assert_eq!(round_trip(&event).source, event.source);
+        assert_eq!(
+            serde_json::from_str::<RouteChangeEvent>(r#"{"to":"/","direction":"forward"}"#)
+                .unwrap()
+                .source,
+            NavigationSource::Programmatic
+        );
+    }
+
+    #[test]
+    fn test_history_entry() {
+        let mut state = HistoryState::new();
+        state.set("scroll".to_string(), "120".to_string());
+        state.set("draft".to_string(), "hi".to_string());
+        let entry = HistoryEntry::with_state("/inbox".to_string(), state);
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(
+            json.starts_with(r#"{"path":"/inbox","state":{"data":{"draft":"hi","scroll":"120"}}"#)
+        );
+        let back = round_trip(&entry);
+        assert_eq!(back, entry);
+        #[cfg(feature = "wall-clock")]
+        assert_eq!(back.entered_at_wall, entry.entered_at_wall);
+
+        // Typed values are left out
+        let mut typed = entry.clone();
+        if let Some(state) = typed.state.as_mut() {
+            state.set_scroll_offset(crate::history::ScrollOffset::new(0.0, 120.0));
+        }
+        assert_eq!(serde_json::to_string(&typed).unwrap(), json);
+        let back = round_trip(&typed);
+        assert_eq!(back.state.and_then(|state| state.scroll_offset()), None);
+
+        // Entries without state or times read back too
+        let bare: HistoryEntry = serde_json::from_str(r#"{"path":"/"}"#).unwrap();
+        assert_eq!(bare, HistoryEntry::new("/".to_string()));
+        assert_eq!(bare.kind, EntryKind::Navigation);
+
+        let mut redirect = HistoryEntry::new("/login".to_string());
+        redirect.kind = EntryKind::Redirect {
+            from: "/settings".to_string(),
+        };
+        let json = serde_json::to_string(&redirect).unwrap();
+        assert!(json.contains(r#""redirected_from":"/settings""#));
+        assert_eq!(round_trip(&redirect).kind, redirect.kind);
+
+        let anchored = HistoryEntry::new("/docs/install#requirements".to_string());
+        let json = serde_json::to_string(&anchored).unwrap();
+        assert!(json.contains(r#""path":"/docs/install""#));
+        assert!(json.contains(r#""fragment":"requirements""#));
+        assert_eq!(round_trip(&anchored), anchored);
+    }
+}
@@ -0,0 +1,359 @@
+//! Named view slots filled by the matched routes
+//!
+//! A layout often shows parts that depend on the page below it: a toolbar
+//! in its header, actions in a sidebar. Instead of reading the current path
+//! and switching on it, the layout renders a [`RouterSlot`] and each route
+//! puts its content there with [`Route::slot`]:
+//!
+//! ```ignore
+//! use gpui_navigator::{Route, RouterOutlet, RouterSlot};
+//! use gpui::*;
+//!
+//! Route::new("dashboard", |window, cx, _params| {
+//!     div()
+//!         .child(div().child("Dashboard").child(RouterSlot::new("toolbar").build(window, cx)))
+//!         .child(cx.new(|_| RouterOutlet::new()))
+//! })
+//! .slot("toolbar", |_cx, _params| div().child("Refresh"))
+//! .children(vec![
+//!     Route::new("reports/:year", |_, _cx, _params| div())
+//!         .slot("toolbar", |_cx, params| {
+//!             div().child(format!("Export {}", params.get("year").unwrap()))
+//!         })
+//!         .into(),
+//!     // Shows the dashboard's "Refresh"
+//!     Route::new("overview", |_, _cx, _params| div()).into(),
+//! ]);
+//! ```
+//!
+//! The deepest route of the matched chain defining the slot fills it, with
+//! the params its own builder gets; a slot no matched route defines renders
+//! nothing. Slot content changes without a transition unless
+//! [`RouterConfig::animate_slots`](crate::RouterConfig::animate_slots) is on.
+
+use crate::context::GlobalRouter;
+use crate::route::{chain_level_params, match_route_chain, RouteBuilder};
+use crate::widgets::locale_params;
+use crate::RouteParams;
+use gpui::{AnyElement, App, IntoElement, SharedString, Window};
+
+#[cfg(feature = "transition")]
+use crate::route::RouteRef;
+#[cfg(feature = "transition")]
+use crate::transition::TransitionProgress;
+#[cfg(feature = "transition")]
+use crate::widgets::{animate_child_enter, animation_slot, running_transition};
+#[cfg(feature = "transition")]
+use gpui::ElementId;
+#[cfg(feature = "transition")]
+use std::time::Instant;
+
+/// Place in a layout where the matched routes put their slot content
+///
+/// See the [`slot`](self) module.
+///
+/// # Example
+///
+/// ```ignore
+/// div()
+///     .child(RouterSlot::new("toolbar").build(window, cx))
+///     .child(cx.new(|_| RouterOutlet::new()))
+/// ```
+#[derive(Debug, Clone)]
+pub struct RouterSlot {
+    /// Name routes fill the slot under
+    name: SharedString,
+}
+
+impl RouterSlot {
+    /// Slot filled by the routes' [`Route::slot`](crate::Route::slot) content for `name`
+    pub fn new(name: impl Into<SharedString>) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// Build the content of the deepest matched route defining the slot
+    ///
+    /// Renders nothing when no matched route defines it, or no router is
+    /// initialized.
+    pub fn build(self, window: &mut Window, cx: &mut App) -> AnyElement {
+        let Some(source) = cx
+            .try_global::<GlobalRouter>()
+            .and_then(|router| slot_source(router, &self.name))
+        else {
+            #[cfg(not(feature = "transition"))]
+            let _ = window;
+            return gpui::Empty.into_any_element();
+        };
+
+        #[cfg(feature = "transition")]
+        let (source, animation) = animated_source(&self.name, source, window, cx);
+
+        let was_rendering = crate::context::set_in_render(cx, true);
+        let content = source.builder.build(window, cx, &source.params);
+        crate::context::set_in_render(cx, was_rendering);
+
+        #[cfg(feature = "transition")]
+        let content = match animation {
+            Some((transition, counter)) => animate_child_enter(
+                content,
+                &transition,
+                ElementId::NamedInteger(slot_key(&self.name), animation_slot(counter)),
+                counter,
+            ),
+            None => content,
+        };
+        content
+    }
+}
+
+/// Route filling a slot, with the builder and params to fill it with
+struct SlotSource {
+    #[cfg(feature = "transition")]
+    route: RouteRef,
+    builder: RouteBuilder,
+    params: RouteParams,
+}
+
+/// The deepest route of the current match defining the slot `name`
+fn slot_source(router: &GlobalRouter, name: &str) -> Option<SlotSource> {
+    let (chain, route_match) =
+        match_route_chain(router.state().routes(), router.current_path_shared())?;
+    let depth = chain
+        .iter()
+        .rposition(|route| route.slots.contains_key(name))?;
+    let route = chain[depth];
+    let mut params = locale_params(Some(router));
+    if let Some(own) = chain_level_params(&chain, &route_match.params)
+        .into_iter()
+        .nth(depth)
+    {
+        for (key, value) in own.iter() {
+            params.insert(key.clone(), value.clone());
+        }
+    }
+    Some(SlotSource {
+        #[cfg(feature = "transition")]
+        route: std::sync::Arc::clone(route),
+        builder: route.slots[name].clone(),
+        params,
+    })
+}
+
+/// Window state key of the slot `name`
+#[cfg(feature = "transition")]
+fn slot_key(name: &str) -> SharedString {
+    SharedString::from(format!("router-slot-{name}"))
+}
+
+/// What a slot showed, to tell when its content changes
+#[cfg(feature = "transition")]
+#[derive(Default)]
+struct SlotState {
+    /// Route and params the content was built for
+    shown: Option<(RouteRef, RouteParams)>,
+    animation_counter: u32,
+    /// When the content last changed
+    transition_started: Option<Instant>,
+}
+
+/// `source` with the transition progress, and the transition to play
+///
+/// With [`RouterConfig::animate_slots`](crate::RouterConfig::animate_slots)
+/// on, content entering the slot plays the transition of the route filling
+/// it; otherwise it is swapped in place.
+#[cfg(feature = "transition")]
+fn animated_source(
+    name: &str,
+    mut source: SlotSource,
+    window: &mut Window,
+    cx: &mut App,
+) -> (SlotSource, Option<(crate::transition::Transition, u32)>) {
+    let animate = cx
+        .try_global::<GlobalRouter>()
+        .is_some_and(|router| router.config().animate_slots);
+    if !animate {
+        return (source, None);
+    }
+
+    let state = window.use_keyed_state(slot_key(name), cx, |_, _| SlotState::default());
+    let (changed, counter, started) = {
+        let state = state.read(cx);
+        let changed = state.shown.as_ref().is_some_and(|(route, params)| {
+            !std::sync::Arc::ptr_eq(route, &source.route) || params.all() != source.params.all()
+        });
+        (changed, state.animation_counter, state.transition_started)
+    };
+    let (counter, started) = if changed {
+        (
+            counter.wrapping_add(1),
+            Some(cx.background_executor().now()),
+        )
+    } else {
+        (counter, started)
+    };
+    state.update(cx, |state, _| {
+        state.shown = Some((std::sync::Arc::clone(&source.route), source.params.clone()));
+        state.animation_counter = counter;
+        state.transition_started = started;
+    });
+
+    let transition = cx.try_global::<GlobalRouter>().map_or_else(
+        || source.route.transition.active().clone(),
+        |router| router.state().resolve_transition(&source.route.transition),
+    );
+    let progress = running_transition(window, cx, started, &transition)
+        .map_or_else(TransitionProgress::default, TransitionProgress::enter);
+    source.params = source.params.with_transition_progress(progress);
+    (source, Some((transition, counter)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{init_router, Navigator, Route};
+    use gpui::{div, ParentElement, Render, TestAppContext, VisualTestContext};
+    use std::sync::{Arc, Mutex};
+
+    /// Layout rendering the toolbar slot, recording what filled it
+    struct Shell {
+        shown: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Render for Shell {
+        fn render(
+            &mut self,
+            window: &mut Window,
+            cx: &mut gpui::Context<'_, Self>,
+        ) -> impl IntoElement {
+            self.shown.lock().unwrap().clear();
+            div().child(RouterSlot::new("toolbar").build(window, cx))
+        }
+    }
+
+    fn toolbar(
+        shown: &Arc<Mutex<Vec<String>>>,
+        label: &'static str,
+    ) -> impl Fn(&mut App, &RouteParams) -> gpui::Div + Send + Sync + 'static {
+        let shown = Arc::clone(shown);
+        move |_, params| {
+            let id = params
+                .get("id")
+                .map_or(String::new(), |id| format!(" {id}"));
+            shown.lock().unwrap().push(format!("{label}{id}"));
+            div()
+        }
+    }
+
+    fn open_app(cx: &mut TestAppContext) -> (Arc<Mutex<Vec<String>>>, &mut VisualTestContext) {
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(
+                    Route::new("/dashboard", |_, _, _| div())
+                        .slot("toolbar", toolbar(&shown, "dashboard"))
+                        .children(vec![
+                            Route::new("users/:id", |_, _, _| div())
+                                .slot("toolbar", toolbar(&shown, "user"))
+                                .into(),
+                            Route::new("overview", |_, _, _| div()).into(),
+                        ]),
+                );
+            });
+        });
+        let layout = Arc::clone(&shown);
+        let (_, cx) = cx.add_window_view(move |_, _| Shell {
+            shown: Arc::clone(&layout),
+        });
+        (shown, cx)
+    }
+
+    fn navigate(cx: &mut VisualTestContext, path: &str) {
+        cx.update(|window, cx| {
+            Navigator::push(cx, path);
+            window.refresh();
+        });
+        cx.run_until_parked();
+    }
+
+    fn shown(recorded: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+        recorded.lock().unwrap().clone()
+    }
+
+    #[gpui::test]
+    fn test_slot_content_follows_navigation(cx: &mut TestAppContext) {
+        let (recorded, cx) = open_app(cx);
+        // No matched route defines the slot
+        assert!(shown(&recorded).is_empty());
+
+        navigate(cx, "/dashboard/users/7");
+        assert_eq!(shown(&recorded), vec!["user 7"]);
+
+        navigate(cx, "/dashboard/users/8");
+        assert_eq!(shown(&recorded), vec!["user 8"]);
+
+        navigate(cx, "/");
+        assert!(shown(&recorded).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_slot_falls_back_to_parent(cx: &mut TestAppContext) {
+        let (recorded, cx) = open_app(cx);
+
+        navigate(cx, "/dashboard/overview");
+        assert_eq!(shown(&recorded), vec!["dashboard"]);
+
+        navigate(cx, "/dashboard/users/7");
+        assert_eq!(shown(&recorded), vec!["user 7"]);
+
+        cx.update(|window, cx| {
+            Navigator::back(cx);
+            window.refresh();
+        });
+        cx.run_until_parked();
+        assert_eq!(shown(&recorded), vec!["dashboard"]);
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_slots_animate_when_configured(cx: &mut TestAppContext) {
+        use crate::{RouterConfig, Transition};
+        use std::time::Duration;
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&progress);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().animate_slots(true));
+                for path in ["/a", "/b"] {
+                    let recorded = Arc::clone(&recorded);
+                    router.add_route(
+                        Route::new(path, |_, _, _| div())
+                            .transition(Transition::fade(200))
+                            .slot("toolbar", move |_, params| {
+                                recorded
+                                    .lock()
+                                    .unwrap()
+                                    .push(!params.transition_progress().is_idle());
+                                div()
+                            }),
+                    );
+                }
+                router.add_route(Route::new("/", |_, _, _| div()));
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| Shell {
+            shown: Arc::default(),
+        });
+
+        navigate(cx, "/a");
+        assert_eq!(progress.lock().unwrap().last(), Some(&false));
+        navigate(cx, "/b");
+        assert_eq!(progress.lock().unwrap().last(), Some(&true));
+        cx.executor().advance_clock(Duration::from_millis(300));
+        cx.run_until_parked();
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert_eq!(progress.lock().unwrap().last(), Some(&false));
+    }
+}
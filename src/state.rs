@@ -1,31 +1,89 @@
 //! Router state management
 
-use crate::route::Route;
-use crate::{NavigationDirection, RouteChangeEvent, RouteMatch};
+use crate::error::NotFoundDetails;
+use crate::flow::{FlowExit, FlowPolicy, FlowTracker};
+use crate::history::{
+    split_fragment, Clock, EntryKind, History, HistoryEntry, HistoryListener, HistoryState,
+};
+use crate::keep_alive::{KeptAlive, KeptEntity};
+use crate::loader::RouteDataStore;
+use crate::resolved::ResolvedNavigation;
+use crate::route::{
+    chain_level_params, chain_pattern, match_route_chain, shared_chain_depth, Route,
+};
+use crate::scope::{route_scopes, RouteScope, ScopedStates};
+#[cfg(feature = "transition")]
+use crate::transition::auto_slide_direction;
+use crate::{
+    NavigationDirection, NavigationSource, ParamsDiff, QueryParams, RouteChangeEvent, RouteMatch,
+    RouteParams,
+};
+#[cfg(feature = "transition")]
+use crate::{SlideDirection, Transition, TransitionConfig};
+use gpui::SharedString;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Router state
 #[derive(Debug, Clone)]
 pub struct RouterState {
     /// Navigation history stack
-    history: Vec<String>,
-    /// Current position in history
-    current: usize,
+    history: History,
     /// Registered routes
     routes: Vec<Arc<Route>>,
     /// Route cache
     cache: HashMap<String, RouteMatch>,
+    /// Loader results by path
+    data: RouteDataStore,
+    /// Params of the route navigated away from by the last navigation
+    previous_params: Option<RouteParams>,
+    /// When the last navigation was applied, for throttling
+    last_navigation: Option<Instant>,
+    /// State scoped to the routes in the matched chain
+    scoped: ScopedStates,
+    /// Components of routes with keep-alive
+    kept: KeptAlive,
+    /// Progress through the flows navigated
+    flows: FlowTracker,
+    /// Transition requested by the last navigation
+    #[cfg(feature = "transition")]
+    navigation_transition: Option<Transition>,
+    /// Direction automatic slides take for the last navigation
+    #[cfg(feature = "transition")]
+    effective_direction: Option<SlideDirection>,
+    /// Current locale, added to matches as the `locale` param
+    locale: Option<String>,
+    /// Whether the current route has unsaved changes
+    dirty: bool,
+    /// Whether an outlet is building a route
+    in_render: bool,
+    /// What started the navigations being made
+    source: NavigationSource,
 }
 
 impl RouterState {
     /// Create a new router state
     pub fn new() -> Self {
         Self {
-            history: vec!["/".to_string()],
-            current: 0,
+            // The router never drops entries on its own
+            history: History::with_max_size("/".to_string(), 0),
             routes: Vec::new(),
             cache: HashMap::new(),
+            data: RouteDataStore::new(),
+            previous_params: None,
+            last_navigation: None,
+            scoped: ScopedStates::default(),
+            kept: KeptAlive::default(),
+            flows: FlowTracker::default(),
+            #[cfg(feature = "transition")]
+            navigation_transition: None,
+            #[cfg(feature = "transition")]
+            effective_direction: None,
+            locale: None,
+            dirty: false,
+            in_render: false,
+            source: NavigationSource::Programmatic,
         }
     }
 
@@ -36,9 +94,86 @@ impl RouterState {
         self.cache.clear();
     }
 
+    /// Unregister the top-level route at `index`
+    pub(crate) fn remove_route(&mut self, index: usize) -> Arc<Route> {
+        self.cache.clear();
+        self.routes.remove(index)
+    }
+
+    /// Put `route` in place of the top-level route at `index`
+    pub(crate) fn replace_route(&mut self, index: usize, route: Route) -> Arc<Route> {
+        self.cache.clear();
+        std::mem::replace(&mut self.routes[index], Arc::new(route))
+    }
+
+    /// Put `routes` in place of the registered ones, returning those
+    pub(crate) fn swap_routes(&mut self, routes: Vec<Arc<Route>>) -> Vec<Arc<Route>> {
+        self.cache.clear();
+        std::mem::replace(&mut self.routes, routes)
+    }
+
+    /// Put `routes` in place of the registered ones and start over at `initial_path`
+    ///
+    /// `history` (entries and current index) is restored instead of starting
+    /// over when given. Loader results, scoped state and flow progress belong
+    /// to the old routes and are dropped. Returns the old routes and the change of
+    /// location, a replace that never has `same_route` set.
+    pub(crate) fn switch_routes(
+        &mut self,
+        routes: Vec<Arc<Route>>,
+        history: Option<(Vec<HistoryEntry>, usize)>,
+        initial_path: String,
+    ) -> (Vec<Arc<Route>>, RouteChangeEvent) {
+        let previous = self.current_params();
+        let from = self.current_path().to_string();
+        let previous_dwell = Some(self.history.current_dwell());
+        let old = self.swap_routes(routes);
+        match history {
+            Some((entries, current)) => self.history.restore(entries, current),
+            None => self.history.clear(initial_path),
+        }
+
+        self.data.clear();
+        self.scoped.clear();
+        self.kept.clear();
+        self.flows.clear();
+        self.flows
+            .navigated(&self.routes, self.history.current_path());
+        self.previous_params = Some(previous);
+        self.dirty = false;
+        #[cfg(feature = "transition")]
+        {
+            self.navigation_transition = None;
+            self.effective_direction = None;
+        }
+        let event = self.resolve_change(RouteChangeEvent {
+            fragment: self.history.current_fragment().map(str::to_string),
+            previous_dwell,
+            ..RouteChangeEvent::new(
+                Some(from),
+                self.current_path(),
+                NavigationDirection::Replace,
+            )
+        });
+        (old, event)
+    }
+
+    /// History entries and the current index, to restore with [`RouterState::switch_routes`]
+    pub(crate) fn history_snapshot(&self) -> (Vec<HistoryEntry>, usize) {
+        (
+            self.history.entries().to_vec(),
+            self.history.current_index(),
+        )
+    }
+
     /// Get current path
     pub fn current_path(&self) -> &str {
-        &self.history[self.current]
+        self.history.current_path()
+    }
+
+    /// Current path as a [`SharedString`], see [`History::current_path_shared`]
+    pub fn current_path_shared(&self) -> &SharedString {
+        self.history.current_path_shared()
     }
 
     /// Get all registered routes
@@ -52,18 +187,16 @@ impl RouterState {
 
         // Check cache first
         if let Some(cached) = self.cache.get(path) {
-            return Some(cached.clone());
+            let mut route_match = cached.clone();
+            route_match.fragment = self.history.current_fragment().map(str::to_string);
+            return Some(route_match);
         }
 
         // Find matching route
-        for route in &self.routes {
-            if let Some(route_match) = route.matches(path) {
-                self.cache.insert(path.to_string(), route_match.clone());
-                return Some(route_match);
-            }
-        }
-
-        None
+        let mut route_match = self.match_path(path)?;
+        self.cache.insert(path.to_string(), route_match.clone());
+        route_match.fragment = self.history.current_fragment().map(str::to_string);
+        Some(route_match)
     }
 
     /// Get current route match without caching (immutable)
@@ -74,18 +207,36 @@ impl RouterState {
         let path = self.current_path();
 
         // Check cache first
-        if let Some(cached) = self.cache.get(path) {
-            return Some(cached.clone());
-        }
+        let mut route_match = match self.cache.get(path) {
+            Some(cached) => cached.clone(),
+            // Find matching route without caching
+            None => self.match_path(path)?,
+        };
+        route_match.fragment = self.history.current_fragment().map(str::to_string);
+        Some(route_match)
+    }
 
-        // Find matching route without caching
-        for route in &self.routes {
-            if let Some(route_match) = route.matches(path) {
-                return Some(route_match);
-            }
-        }
+    /// Match a path against the top-level routes, then their children
+    ///
+    /// The query string is left out of matching and parsed into `query`.
+    fn match_path(&self, path: &str) -> Option<RouteMatch> {
+        match_routes(&self.routes, path, self.locale.as_deref())
+    }
 
-        None
+    /// Current locale
+    ///
+    /// `None` unless [`RouterConfig::locales`](crate::RouterConfig::locales)
+    /// is set.
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Switch the locale matches are made in
+    pub fn set_locale(&mut self, locale: Option<String>) {
+        if self.locale != locale {
+            self.locale = locale;
+            self.cache.clear();
+        }
     }
 
     /// Get the matched Route for current path
@@ -93,97 +244,579 @@ impl RouterState {
     /// Returns the Route object that matched, not just the RouteMatch.
     /// This is needed for rendering and accessing the route's builder.
     pub fn current_route(&self) -> Option<&Arc<Route>> {
-        let path = self.current_path();
+        self.route_for_path(self.current_path())
+    }
+
+    /// Get the top-level Route matching a path
+    ///
+    /// For a nested path this is the outermost parent, e.g. `/dashboard` for
+    /// `/dashboard/overview`.
+    pub fn route_for_path(&self, path: &str) -> Option<&Arc<Route>> {
+        top_level_route(&self.routes, path)
+    }
+
+    /// Number of leading routes the matched chains of two paths share
+    ///
+    /// See [`GlobalRouter::shared_route_depth`](crate::GlobalRouter::shared_route_depth).
+    pub fn shared_route_depth(&self, from: &str, to: &str) -> usize {
+        shared_chain_depth(&self.routes, from, to)
+    }
+
+    /// Path of the page shown underneath the current modal route
+    ///
+    /// A path is modal when any route of its matched chain is, so a modal
+    /// child of a page layout counts. Returns the closest earlier history
+    /// entry that is not modal, or `None` if the current path is not modal or
+    /// no such entry exists.
+    pub fn modal_base_path(&self) -> Option<&str> {
+        if !is_modal_path(&self.routes, self.current_path()) {
+            return None;
+        }
 
-        self.routes
+        self.history.entries()[..self.history.current_index()]
             .iter()
-            .find(|route| route.matches(path).is_some())
+            .rev()
+            .map(|entry| entry.path.as_str())
+            .find(|path| self.route_for_path(path).is_some() && !is_modal_path(&self.routes, path))
     }
 
     /// Navigate to a new path
     pub fn push(&mut self, path: String) -> RouteChangeEvent {
-        let from = Some(self.current_path().to_string());
+        let departure = self.departure();
+        let event = self.history.push(path);
+        self.route_changed(event.into(), departure, false)
+    }
 
-        // Remove forward history when pushing
-        self.history.truncate(self.current + 1);
+    /// Replace current path
+    pub fn replace(&mut self, path: String) -> RouteChangeEvent {
+        let departure = self.departure();
+        let event = self.history.replace(path);
+        self.route_changed(event.into(), departure, false)
+    }
 
-        // Add new path
-        self.history.push(path.clone());
-        self.current += 1;
+    /// Navigate to a new path whose entry carries `state`
+    ///
+    /// Typed values of `state` (see [`HistoryState::set_value`]) are kept
+    /// as they are.
+    pub fn push_with_state(&mut self, path: String, state: HistoryState) -> RouteChangeEvent {
+        let departure = self.departure();
+        let event = self.history.push_with_state(path, state);
+        self.route_changed(event.into(), departure, false)
+    }
 
-        RouteChangeEvent {
-            from,
-            to: path,
-            direction: NavigationDirection::Forward,
-        }
+    /// Replace current path with an entry carrying `state`
+    pub fn replace_with_state(&mut self, path: String, state: HistoryState) -> RouteChangeEvent {
+        let departure = self.departure();
+        let event = self.history.replace_with_state(path, state);
+        self.route_changed(event.into(), departure, false)
     }
 
-    /// Replace current path
-    pub fn replace(&mut self, path: String) -> RouteChangeEvent {
-        let from = Some(self.current_path().to_string());
+    /// State of the current history entry, if it has one
+    pub fn current_state(&self) -> Option<&HistoryState> {
+        self.history.current_entry().state.as_ref()
+    }
 
-        self.history[self.current] = path.clone();
+    /// State of the current history entry, created empty if it has none
+    ///
+    /// See [`History::current_state_mut`].
+    pub fn current_state_mut(&mut self) -> &mut HistoryState {
+        self.history.current_state_mut()
+    }
 
-        RouteChangeEvent {
-            from,
-            to: path,
-            direction: NavigationDirection::Replace,
-        }
+    /// Set whether pushing the next forward entry's path moves forward
+    ///
+    /// See [`History::set_reuse_forward_on_push`].
+    pub(crate) fn set_reuse_forward_on_push(&mut self, reuse: bool) {
+        self.history.set_reuse_forward_on_push(reuse);
+    }
+
+    /// What started the navigations being made
+    pub(crate) fn source(&self) -> &NavigationSource {
+        &self.source
+    }
+
+    /// Set what starts the next navigations, returning the previous source
+    pub(crate) fn replace_source(&mut self, source: NavigationSource) -> NavigationSource {
+        std::mem::replace(&mut self.source, source)
+    }
+
+    /// Set the policies of the flows
+    ///
+    /// See [`RouterConfig::flow_policy`](crate::RouterConfig::flow_policy).
+    pub(crate) fn set_flow_policies(
+        &mut self,
+        policies: HashMap<String, FlowPolicy>,
+        exits: HashMap<String, FlowExit>,
+    ) {
+        self.flows.set_policies(policies, exits);
     }
 
     /// Go back in history
     pub fn back(&mut self) -> Option<RouteChangeEvent> {
-        if self.current > 0 {
-            let from = Some(self.current_path().to_string());
-            self.current -= 1;
-            let to = self.current_path().to_string();
-
-            Some(RouteChangeEvent {
-                from,
-                to,
-                direction: NavigationDirection::Back,
-            })
-        } else {
-            None
-        }
+        let departure = self.departure();
+        let event = self.history.back()?;
+        Some(self.route_changed(event.into(), departure, true))
+    }
+
+    /// Go back to the history entry at `index`
+    ///
+    /// Returns `None` if `index` is not behind the current entry.
+    pub fn back_to(&mut self, index: usize) -> Option<RouteChangeEvent> {
+        let departure = self.departure();
+        let event = self.history.back_to(index)?;
+        Some(self.route_changed(event.into(), departure, true))
+    }
+
+    /// Mark the current history entry as committed by a guard redirect
+    pub(crate) fn mark_redirect(&mut self, from: String) {
+        self.history.set_current_kind(EntryKind::Redirect { from });
+    }
+
+    /// Drop the history entries between `index` and the current one
+    pub(crate) fn collapse_history_since(&mut self, index: usize) {
+        self.history.collapse_since(index);
+    }
+
+    /// Whether the current route has unsaved changes
+    ///
+    /// Set with `Navigator::set_dirty`; cleared when navigating to another
+    /// path.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Mark the current route as having unsaved changes, or not
+    pub(crate) fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    /// Whether an outlet is building a route
+    ///
+    /// Navigations made meanwhile follow
+    /// [`RouterConfig::on_render_navigation`](crate::RouterConfig::on_render_navigation).
+    pub fn in_render(&self) -> bool {
+        self.in_render
+    }
+
+    /// Mark an outlet as building a route, or done, returning the previous flag
+    pub(crate) fn set_in_render(&mut self, in_render: bool) -> bool {
+        std::mem::replace(&mut self.in_render, in_render)
     }
 
     /// Go forward in history
     pub fn forward(&mut self) -> Option<RouteChangeEvent> {
-        if self.current < self.history.len() - 1 {
-            let from = Some(self.current_path().to_string());
-            self.current += 1;
-            let to = self.current_path().to_string();
-
-            Some(RouteChangeEvent {
-                from,
-                to,
-                direction: NavigationDirection::Forward,
-            })
-        } else {
-            None
+        let departure = self.departure();
+        let event = self.history.forward()?;
+        Some(self.route_changed(event.into(), departure, true))
+    }
+
+    /// Go forward to the history entry at `index`
+    ///
+    /// Returns `None` if `index` is not ahead of the current entry.
+    pub fn forward_to(&mut self, index: usize) -> Option<RouteChangeEvent> {
+        let departure = self.departure();
+        let event = self.history.forward_to(index)?;
+        Some(self.route_changed(event.into(), departure, true))
+    }
+
+    /// When the last navigation was applied
+    pub fn last_navigation(&self) -> Option<Instant> {
+        self.last_navigation
+    }
+
+    /// Record when a navigation was applied
+    pub(crate) fn set_last_navigation(&mut self, at: Instant) {
+        self.last_navigation = Some(at);
+    }
+
+    /// Transition requested by the last navigation
+    ///
+    /// Set for navigations made with a transition, like
+    /// `Navigator::push_with_transition`; `None` after any other navigation.
+    #[cfg(feature = "transition")]
+    pub fn navigation_transition(&self) -> Option<&Transition> {
+        self.navigation_transition.as_ref()
+    }
+
+    /// Record the transition requested by the navigation just applied
+    #[cfg(feature = "transition")]
+    pub(crate) fn set_navigation_transition(&mut self, transition: Option<Transition>) {
+        self.navigation_transition = transition;
+    }
+
+    /// Transition the current route is shown with
+    ///
+    /// The transition requested by the last navigation if any, otherwise the
+    /// route's own (see [`TransitionConfig::resolve`](crate::TransitionConfig::resolve)).
+    #[cfg(feature = "transition")]
+    pub fn current_transition(&self) -> Transition {
+        self.current_route().map_or(Transition::None, |route| {
+            self.resolve_transition(&route.transition)
+        })
+    }
+
+    /// Transition a route's config resolves to for the last navigation
+    ///
+    /// Like [`current_transition`](Self::current_transition), with automatic
+    /// slides turned to the [`effective_direction`](Self::effective_direction).
+    #[cfg(feature = "transition")]
+    pub fn resolve_transition(&self, config: &TransitionConfig) -> Transition {
+        config
+            .resolve(self.navigation_transition.as_ref())
+            .clone()
+            .resolve_auto(self.effective_direction)
+    }
+
+    /// Direction automatic slides take for the last navigation
+    ///
+    /// Picked by [`auto_slide_direction`] from the paths navigated between;
+    /// `None` before the first navigation and between paths of the same
+    /// depth.
+    #[cfg(feature = "transition")]
+    pub fn effective_direction(&self) -> Option<SlideDirection> {
+        self.effective_direction
+    }
+
+    /// What a navigation leaves, taken before it is applied
+    fn departure(&self) -> Departure {
+        Departure {
+            params: self.current_params(),
+            fragment: self.history.current_fragment().map(str::to_string),
+            kept_on_screen: if self.kept.is_empty() {
+                Vec::new()
+            } else {
+                self.kept.on_screen(&self.rendered_scopes())
+            },
         }
     }
 
+    /// Remember the params navigated away from and describe the change
+    ///
+    /// Staying on the same path with a fragment on either side is a
+    /// fragment-only change. `traversal` tells whether the navigation went
+    /// back or forward in history, for the kept-alive components.
+    fn route_changed(
+        &mut self,
+        mut event: RouteChangeEvent,
+        departure: Departure,
+        traversal: bool,
+    ) -> RouteChangeEvent {
+        event.fragment_only = event.from.as_deref() == Some(event.to.as_str())
+            && (departure.fragment.is_some() || event.fragment.is_some());
+        self.previous_params = Some(departure.params);
+        if event.from.as_deref() != Some(event.to.as_str()) {
+            self.dirty = false;
+        }
+        #[cfg(feature = "transition")]
+        {
+            self.navigation_transition = None;
+            self.effective_direction = event
+                .from
+                .as_deref()
+                .and_then(|from| auto_slide_direction(from, &event.to, event.direction));
+        }
+        self.retain_scoped_states();
+        self.retain_route_data();
+        if !self.kept.is_empty() {
+            let scopes = rendered_scopes(&self.routes, self.current_path(), self.modal_base_path());
+            self.kept
+                .navigated(&departure.kept_on_screen, &scopes, traversal);
+        }
+        self.flows
+            .navigated(&self.routes, self.history.current_path());
+        let mut event = self.describe_change(event);
+        if traversal {
+            event.source = NavigationSource::History;
+        }
+        event
+    }
+
+    /// Drop scoped state of routes no longer matched
+    ///
+    /// The page under a modal route keeps its state.
+    pub(crate) fn retain_scoped_states(&mut self) {
+        let scopes = rendered_scopes(&self.routes, self.current_path(), self.modal_base_path());
+        self.scoped.retain_scopes(&scopes);
+    }
+
+    /// Drop loader results of paths no longer in the history
+    ///
+    /// Loads still running are kept: a blocking one is for a path not
+    /// committed yet.
+    fn retain_route_data(&mut self) {
+        let entries = self.history.entries();
+        self.data.retain(|path, state| {
+            state.is_loading() || entries.iter().any(|entry| entry.path == path)
+        });
+    }
+
+    /// Scopes of the routes on screen: the current route's chain and, under
+    /// a modal route, the chain of the page below
+    pub(crate) fn rendered_scopes(&self) -> Vec<RouteScope<'_>> {
+        rendered_scopes(&self.routes, self.current_path(), self.modal_base_path())
+    }
+
+    /// Fill in `same_route`, `params_diff` and `source` of a navigation event
+    pub(crate) fn describe_change(&self, mut event: RouteChangeEvent) -> RouteChangeEvent {
+        event.source = self.source.clone();
+        let from = event.from.clone().unwrap_or_default();
+        let leaf = |path: &str| {
+            match_route_chain(&self.routes, path).and_then(|(chain, _)| chain.last().copied())
+        };
+        event.same_route = match (leaf(&from), leaf(&event.to)) {
+            (Some(from_route), Some(to_route)) => Arc::ptr_eq(from_route, to_route),
+            _ => false,
+        };
+        event.params_diff = event.same_route.then(|| {
+            RouteParams::diff(
+                &self.params_for_path(&from),
+                &self.params_for_path(&event.to),
+            )
+        });
+        self.resolve_change(event)
+    }
+
+    /// Fill in the route `event.to` resolved to: pattern, name, params and query
+    pub(crate) fn resolve_change(&self, mut event: RouteChangeEvent) -> RouteChangeEvent {
+        let (route_path, query) = event.to.split_once('?').unwrap_or((&event.to, ""));
+        event.query = QueryParams::from_query_string(query);
+        event.pattern = None;
+        event.route_name = None;
+        if let Some((chain, _)) = match_route_chain(&self.routes, route_path) {
+            event.pattern = Some(chain_pattern(&chain));
+            event.route_name = chain.last().and_then(|route| route.config.name.clone());
+        }
+        event.matched = event.pattern.is_some();
+        event.params = self.params_for_path(&event.to);
+        event.level_params = self.params_by_level(&event.to);
+        event
+    }
+
+    /// Params matched from a path, split by the level of the matched route
+    /// chain that introduced them
+    ///
+    /// One map per route of the chain, outermost first, pathless layouts
+    /// included (with no params): on `/orgs/7/members/3`, with `members/:member`
+    /// a child of `/orgs/:org`, that is `[{org: 7}, {member: 3}]`. The
+    /// `locale` param belongs to no level. Empty if no route matches.
+    pub fn params_by_level(&self, path: &str) -> Vec<RouteParams> {
+        let (path, _) = split_fragment(path);
+        let route_path = path.split('?').next().unwrap_or(path);
+        match_route_chain(&self.routes, route_path)
+            .map(|(chain, route_match)| chain_level_params(&chain, &route_match.params))
+            .unwrap_or_default()
+    }
+
+    /// Route chain `path` resolves to, for rendering it with
+    /// [`render_resolved`](crate::render_resolved) in snapshot tests
+    ///
+    /// Only matches the path: guards, middleware and history are not
+    /// involved. Paths matching no route give the details of the miss.
+    pub fn resolve_for_test(&self, path: &str) -> Result<ResolvedNavigation, NotFoundDetails> {
+        ResolvedNavigation::resolve(&self.routes, path)
+    }
+
+    /// Params matched from a path (empty if no route matches)
+    pub fn params_for_path(&self, path: &str) -> RouteParams {
+        self.match_path(path)
+            .map(|route_match| RouteParams::from_map(route_match.params))
+            .unwrap_or_default()
+    }
+
+    /// Params of the current route
+    pub fn current_params(&self) -> RouteParams {
+        self.params_for_path(self.current_path())
+    }
+
+    /// Params of the route before the last navigation
+    ///
+    /// `None` until the first navigation.
+    pub fn previous_params(&self) -> Option<&RouteParams> {
+        self.previous_params.as_ref()
+    }
+
+    /// How params changed with the last navigation, even across routes
+    ///
+    /// Navigating to a route without a param reports it as removed; navigating
+    /// to the same path again gives an empty diff. `None` until the first
+    /// navigation.
+    pub fn params_diff(&self) -> Option<ParamsDiff> {
+        let previous = self.previous_params.as_ref()?;
+        Some(RouteParams::diff(previous, &self.current_params()))
+    }
+
     /// Check if can go back
     pub fn can_go_back(&self) -> bool {
-        self.current > 0
+        self.history.can_go_back()
     }
 
     /// Check if can go forward
     pub fn can_go_forward(&self) -> bool {
-        self.current < self.history.len() - 1
+        self.history.can_go_forward()
+    }
+
+    /// Get loader results
+    pub fn data(&self) -> &RouteDataStore {
+        &self.data
+    }
+
+    /// Get loader results (mutable)
+    pub fn data_mut(&mut self) -> &mut RouteDataStore {
+        &mut self.data
+    }
+
+    /// Get state scoped to the active routes
+    pub fn scoped_states(&self) -> &ScopedStates {
+        &self.scoped
+    }
+
+    /// Get state scoped to the active routes (mutable)
+    pub(crate) fn scoped_states_mut(&mut self) -> &mut ScopedStates {
+        &mut self.scoped
+    }
+
+    /// Get the components kept alive by routes with keep-alive
+    pub fn kept_alive(&self) -> &KeptAlive {
+        &self.kept
+    }
+
+    /// Get the components kept alive by routes with keep-alive (mutable)
+    pub(crate) fn kept_alive_mut(&mut self) -> &mut KeptAlive {
+        &mut self.kept
+    }
+
+    /// Keep a new component alive, dropping the least recently used ones
+    /// over its route's limit and over `global_max`
+    ///
+    /// Components of routes on screen are never dropped. Returns the number
+    /// of components dropped.
+    pub(crate) fn keep_alive(&mut self, entry: KeptEntity, global_max: Option<usize>) -> usize {
+        let scopes = rendered_scopes(&self.routes, self.current_path(), self.modal_base_path());
+        self.kept.insert(entry, global_max, &scopes)
+    }
+
+    /// Drop the kept-alive components matching a path or route pattern,
+    /// except those on screen
+    pub(crate) fn drop_kept_alive(&mut self, path_or_pattern: &str) -> usize {
+        let scopes = rendered_scopes(&self.routes, self.current_path(), self.modal_base_path());
+        self.kept.drop_matching(path_or_pattern, &scopes)
+    }
+
+    /// Get the progress through the flows navigated
+    pub fn flows(&self) -> &FlowTracker {
+        &self.flows
+    }
+
+    /// Get the navigation history
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Limit the number of history entries (0 = unlimited, the default)
+    ///
+    /// See [`History::set_max_size`].
+    pub fn set_history_max_size(&mut self, max_size: usize) {
+        self.history.set_max_size(max_size);
+    }
+
+    /// Read history times from `clock`
+    pub(crate) fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.history.set_clock(clock);
+    }
+
+    /// Stop counting time spent on the current entry
+    pub fn pause_dwell(&mut self) {
+        self.history.pause_dwell();
+    }
+
+    /// Count time spent on the current entry again
+    pub fn resume_dwell(&mut self) {
+        self.history.resume_dwell();
+    }
+
+    /// Register a listener notified after each history change
+    pub fn add_history_listener(&mut self, listener: Box<dyn HistoryListener>) {
+        self.history.add_listener(listener);
     }
 
     /// Clear navigation history
     pub fn clear(&mut self) {
-        self.history.clear();
-        self.history.push("/".to_string());
-        self.current = 0;
+        self.history.clear("/".to_string());
         self.cache.clear();
+        self.data.clear();
+        self.previous_params = None;
+        self.last_navigation = None;
+        self.scoped.clear();
+        self.kept.clear();
+        self.flows.clear();
+        self.dirty = false;
     }
 }
 
+/// What a navigation leaves, see `RouterState::route_changed`
+struct Departure {
+    /// Params of the route navigated away from
+    params: RouteParams,
+    /// Fragment navigated away from
+    fragment: Option<String>,
+    /// Which kept-alive components were on screen
+    kept_on_screen: Vec<bool>,
+}
+
+/// Scopes of the routes matching `current` and, if given, `modal_base`
+fn rendered_scopes<'a>(
+    routes: &'a [Arc<Route>],
+    current: &str,
+    modal_base: Option<&str>,
+) -> Vec<RouteScope<'a>> {
+    let mut scopes = route_scopes(routes, current);
+    if let Some(base) = modal_base {
+        scopes.extend(route_scopes(routes, base));
+    }
+    scopes
+}
+
+/// Match a path against the top-level routes, then their children
+///
+/// The query string and fragment are left out of matching and parsed into
+/// `query` and `fragment`; a `locale` is added to the params.
+pub(crate) fn match_routes(
+    routes: &[Arc<Route>],
+    path: &str,
+    locale: Option<&str>,
+) -> Option<RouteMatch> {
+    let (path, fragment) = split_fragment(path);
+    let (route_path, query) = path.split_once('?').unwrap_or((path, ""));
+    let mut route_match = routes
+        .iter()
+        .find_map(|route| route.matches(route_path))
+        .or_else(|| match_route_chain(routes, route_path).map(|(_, route_match)| route_match))?;
+    route_match.query = QueryParams::from_query_string(query).to_map();
+    route_match.fragment = fragment.map(str::to_string);
+    if let Some(locale) = locale {
+        route_match
+            .params
+            .insert("locale".to_string(), locale.to_string());
+    }
+    Some(route_match)
+}
+
+/// Top-level route matching `path`, see [`RouterState::route_for_path`]
+pub(crate) fn top_level_route<'a>(routes: &'a [Arc<Route>], path: &str) -> Option<&'a Arc<Route>> {
+    routes
+        .iter()
+        .find(|route| route.matches(path).is_some())
+        .or_else(|| match_route_chain(routes, path).map(|(chain, _)| chain[0]))
+}
+
+/// Whether any route of the chain matching `path` is presented as a modal
+pub(crate) fn is_modal_path(routes: &[Arc<Route>], path: &str) -> bool {
+    match_route_chain(routes, path).map_or_else(
+        || top_level_route(routes, path).is_some_and(|route| route.is_modal()),
+        |(chain, _)| chain.iter().any(|route| route.is_modal()),
+    )
+}
+
 impl Default for RouterState {
     fn default() -> Self {
         Self::new()
@@ -212,6 +845,11 @@ impl Router {
     pub fn state(&self) -> &RouterState {
         &self.state
     }
+
+    /// Route chain `path` resolves to, see [`RouterState::resolve_for_test`]
+    pub fn resolve_for_test(&self, path: &str) -> Result<ResolvedNavigation, NotFoundDetails> {
+        self.state.resolve_for_test(path)
+    }
 }
 
 impl Default for Router {
@@ -253,4 +891,99 @@ mod tests {
         assert_eq!(state.current_path(), "/posts");
         assert_eq!(state.history.len(), 2);
     }
+
+    #[cfg(feature = "transition")]
+    #[test]
+    fn test_auto_slide_per_navigation() {
+        use gpui::IntoElement;
+
+        let mut state = RouterState::new();
+        for path in ["/", "/products", "/products/:id"] {
+            state.add_route(
+                Route::new(path, |_, _, _| gpui::div().into_any_element())
+                    .transition(Transition::slide_auto(300).same_depth(SlideDirection::Down)),
+            );
+        }
+        let direction = |state: &RouterState| match state.current_transition() {
+            Transition::Slide { direction, .. } => direction,
+            other => panic!("Expected Slide transition, got {:?}", other),
+        };
+
+        state.push("/products".to_string());
+        assert_eq!(state.effective_direction(), Some(SlideDirection::Left));
+        state.push("/products/123".to_string());
+        assert_eq!(direction(&state), SlideDirection::Left);
+
+        state.back();
+        assert_eq!(direction(&state), SlideDirection::Right);
+        state.forward();
+        assert_eq!(direction(&state), SlideDirection::Left);
+
+        // Same depth falls back to the transition's choice
+        state.replace("/products/456".to_string());
+        assert_eq!(state.effective_direction(), None);
+        assert_eq!(direction(&state), SlideDirection::Down);
+    }
+
+    #[test]
+    fn test_modal_base_path() {
+        use crate::Presentation;
+        use gpui::IntoElement;
+
+        let mut state = RouterState::new();
+        state.add_route(Route::new("/photos/:id", |_, _, _| {
+            gpui::div().into_any_element()
+        }));
+        state.add_route(
+            Route::new("/photos/:id/edit", |_, _, _| gpui::div().into_any_element())
+                .presentation(Presentation::Modal),
+        );
+        state.add_route(
+            Route::new("/photos/:id/share", |_, _, _| {
+                gpui::div().into_any_element()
+            })
+            .presentation(Presentation::Modal),
+        );
+
+        state.push("/photos/42".to_string());
+        assert_eq!(state.modal_base_path(), None);
+
+        // Navigating between modals keeps the same page underneath
+        state.push("/photos/42/edit".to_string());
+        state.push("/photos/42/share".to_string());
+        assert_eq!(state.modal_base_path(), Some("/photos/42"));
+
+        state.back();
+        state.back();
+        assert_eq!(state.modal_base_path(), None);
+    }
+
+    #[test]
+    fn test_nested_modal_base_path() {
+        use crate::Presentation;
+        use gpui::IntoElement;
+
+        let page =
+            |path: &str| Arc::new(Route::new(path, |_, _, _| gpui::div().into_any_element()));
+        let mut state = RouterState::new();
+        state.add_route(
+            Route::new("/photos", |_, _, _| gpui::div().into_any_element()).children(vec![
+                page(":id"),
+                Arc::new(
+                    Route::new(":id/edit", |_, _, _| gpui::div().into_any_element())
+                        .presentation(Presentation::Modal),
+                ),
+            ]),
+        );
+
+        state.push("/photos/42".to_string());
+        assert_eq!(state.modal_base_path(), None);
+
+        // The modal child makes the path modal though its parent is a page
+        state.push("/photos/42/edit".to_string());
+        assert_eq!(state.modal_base_path(), Some("/photos/42"));
+
+        state.back();
+        assert_eq!(state.modal_base_path(), None);
+    }
 }
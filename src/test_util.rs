@@ -0,0 +1,9 @@
+//! Fixtures shared by the unit tests
+
+use crate::Route;
+use gpui::IntoElement;
+
+/// Route at `path` rendering an empty element
+pub fn page(path: &str) -> Route {
+    Route::new(path, |_, _, _| gpui::div().into_any_element())
+}
@@ -0,0 +1,357 @@
+//! Test harness for router-based apps
+//!
+//! Available with the `test-util` feature. [`TestRouter`] sets up the global
+//! router in a test window, runs guards and middleware when navigating and
+//! records which route builders ran:
+//!
+//! ```ignore
+//! use gpui_navigator::testing::{MockGuard, TestRouter};
+//! use gpui_navigator::Route;
+//!
+//! #[gpui::test]
+//! fn redirects_to_login(cx: &mut gpui::TestAppContext) {
+//!     let auth = MockGuard::redirect("/login");
+//!     let mut router = TestRouter::new(cx)
+//!         .page("/login")
+//!         .route(Route::new("/admin", |_, _, _| gpui::div().into_any_element()).guard(auth.clone()));
+//!
+//!     assert_eq!(router.navigate("/admin").redirect_path(), Some("/login"));
+//!     router.assert_current("/login");
+//!     assert_eq!(auth.calls(), vec!["/admin"]);
+//! }
+//! ```
+
+#[cfg(feature = "guard")]
+use crate::guards::{GuardResult, RouteGuard};
+#[cfg(feature = "middleware")]
+use crate::middleware::{AfterNavigation, RouteMiddleware};
+use crate::route::{match_route_chain, RouteBuilder};
+#[cfg(any(feature = "guard", feature = "middleware"))]
+use crate::NavigationRequest;
+use crate::{
+    build_child_path, init_router, GlobalRouter, NavOptions, NavigationError, NavigationResult,
+    NavigationSource, Navigator, PushOutcome, Route, RouterOutlet,
+};
+#[cfg(any(feature = "guard", feature = "middleware"))]
+use gpui::App;
+use gpui::{div, BorrowAppContext, IntoElement, SharedString, TestAppContext, VisualTestContext};
+#[cfg(any(feature = "guard", feature = "middleware"))]
+use std::future::Future;
+#[cfg(any(feature = "guard", feature = "middleware"))]
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// Builder invocations recorded by instrumented routes
+type RenderLog = Arc<Mutex<Vec<String>>>;
+
+// ============================================================================
+// TestRouter
+// ============================================================================
+
+/// Router set up for tests
+///
+/// Creates the global router with a `/` route and a window rendering a
+/// [`RouterOutlet`]. Routes added through [`TestRouter::route`] and
+/// [`TestRouter::page`] record their builder calls, named after the route
+/// name or, failing that, its full path.
+pub struct TestRouter {
+    cx: VisualTestContext,
+    rendered: RenderLog,
+}
+
+impl TestRouter {
+    /// Initialize the router with a root page and open a window for it
+    pub fn new(cx: &mut TestAppContext) -> Self {
+        let rendered = RenderLog::default();
+        let root = instrument(
+            Route::new("/", |_, _, _| div().into_any_element()),
+            "",
+            &rendered,
+        );
+        cx.update(|cx| init_router(cx, |router| router.add_route(root)));
+
+        let window = cx.add_window(|_, _| RouterOutlet::new());
+        let cx = VisualTestContext::from_window(window.into(), cx);
+        cx.run_until_parked();
+
+        Self { cx, rendered }
+    }
+
+    /// Register a route, recording its builder calls (and its children's)
+    pub fn route(mut self, route: Route) -> Self {
+        let route = instrument(route, "", &self.rendered);
+        self.cx.update(|_, cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.add_route(route));
+        });
+        self
+    }
+
+    /// Register a route rendering an empty page
+    pub fn page(self, path: impl Into<String>) -> Self {
+        self.route(Route::new(path, |_, _, _| div().into_any_element()))
+    }
+
+    /// Navigate to `path` through the router and render the result
+    ///
+    /// Pushes the path like [`Navigator::push_opts`], so the middleware
+    /// (global and of the matched route chain) and guards run as in an app,
+    /// then runs the app until idle. A denying guard leaves the current path
+    /// as is; a redirecting guard navigates to its target and reports
+    /// `Blocked` with the redirect. Unknown paths are still pushed (the
+    /// outlet shows its not-found page) and report `NotFound`. Relative
+    /// paths resolve against the current route first (see
+    /// [`Navigator::resolve_path`]).
+    pub fn navigate(&mut self, path: impl Into<String>) -> NavigationResult {
+        let path = path.into();
+        let (path, found) = self.cx.update(|_, cx| {
+            let path = Navigator::resolve_path(cx, &path, &NavOptions::default());
+            let found =
+                match_route_chain(cx.global::<GlobalRouter>().state().routes(), &path).is_some();
+            (path, found)
+        });
+        self.rendered.lock().unwrap().clear();
+
+        let outcome = self.cx.update(|window, cx| {
+            let outcome = Navigator::push_opts(
+                cx,
+                path.clone(),
+                NavOptions::new().source(NavigationSource::Programmatic),
+            );
+            window.refresh();
+            outcome
+        });
+        self.cx.run_until_parked();
+
+        if !found {
+            return NavigationResult::NotFound { path };
+        }
+        if let Some(NavigationError::GuardBlocked { reason }) =
+            self.cx.update(|_, cx| Navigator::last_error(cx))
+        {
+            return NavigationResult::Blocked {
+                reason,
+                redirect: None,
+            };
+        }
+        let event = match outcome {
+            PushOutcome::Navigated(event) => event,
+            outcome => return outcome.into(),
+        };
+        match event.source {
+            NavigationSource::Redirect { from_guard } => NavigationResult::Blocked {
+                reason: format!("{} redirects to {}", from_guard, event.to),
+                redirect: Some(event.to),
+            },
+            _ => NavigationResult::Success { path },
+        }
+    }
+
+    /// Go back and render the result
+    pub fn back(&mut self) {
+        self.rendered.lock().unwrap().clear();
+        self.cx.update(|window, cx| {
+            Navigator::pop(cx);
+            window.refresh();
+        });
+        self.cx.run_until_parked();
+    }
+
+    /// Get the current path
+    pub fn current_path(&mut self) -> SharedString {
+        self.cx.update(|_, cx| Navigator::current_path(cx))
+    }
+
+    /// Panic unless the router is at `path`
+    #[track_caller]
+    pub fn assert_current(&mut self, path: &str) {
+        let current = self.current_path();
+        assert_eq!(
+            current, path,
+            "expected router at '{}', but it is at '{}'",
+            path, current
+        );
+    }
+
+    /// Routes whose builders ran since the last navigation, in call order
+    pub fn rendered_route_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for name in self.rendered.lock().unwrap().iter() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    /// Access the test context, e.g. to read other globals
+    pub fn cx(&mut self) -> &mut VisualTestContext {
+        &mut self.cx
+    }
+}
+
+/// Wrap the builders of `route` and its children to record their calls
+fn instrument(mut route: Route, parent_path: &str, rendered: &RenderLog) -> Route {
+    let mut full_path = build_child_path(parent_path, &route.config.path).into_owned();
+    if full_path.is_empty() {
+        full_path.push('/');
+    }
+    let name = route
+        .config
+        .name
+        .clone()
+        .unwrap_or_else(|| full_path.clone());
+
+    if let Some(builder) = route.builder.take() {
+        let rendered = Arc::clone(rendered);
+        let wrapped = RouteBuilder::new(move |window, cx, params| {
+            rendered.lock().unwrap().push(name.clone());
+            builder.build(window, cx, params)
+        });
+        route.builder = Some(wrapped);
+    }
+
+    let children = std::mem::take(&mut route.children);
+    route.children = children
+        .into_iter()
+        .map(|child| match Arc::try_unwrap(child) {
+            Ok(child) => Arc::new(instrument(child, &full_path, rendered)),
+            // Shared elsewhere; leave it uninstrumented
+            Err(child) => child,
+        })
+        .collect();
+    route
+}
+
+// ============================================================================
+// Mocks
+// ============================================================================
+
+/// Guard with a programmable result that records the paths it checked
+///
+/// Clones share their result and call log, so keep a clone to inspect the
+/// guard after adding it to a route.
+#[cfg(feature = "guard")]
+#[derive(Clone)]
+pub struct MockGuard {
+    result: Arc<Mutex<GuardResult>>,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+#[cfg(feature = "guard")]
+impl MockGuard {
+    /// Create a guard returning `result`
+    pub fn new(result: GuardResult) -> Self {
+        Self {
+            result: Arc::new(Mutex::new(result)),
+            calls: Arc::default(),
+        }
+    }
+
+    /// Create a guard that allows navigation
+    pub fn allow() -> Self {
+        Self::new(GuardResult::allow())
+    }
+
+    /// Create a guard that denies navigation
+    pub fn deny(reason: impl Into<String>) -> Self {
+        Self::new(GuardResult::deny(reason))
+    }
+
+    /// Create a guard that redirects to `to`
+    pub fn redirect(to: impl Into<String>) -> Self {
+        Self::new(GuardResult::redirect(to))
+    }
+
+    /// Change the result of later checks
+    pub fn set_result(&self, result: GuardResult) {
+        *self.result.lock().unwrap() = result;
+    }
+
+    /// Paths this guard was asked about, in order
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[cfg(feature = "guard")]
+impl RouteGuard for MockGuard {
+    type Future = Pin<Box<dyn Future<Output = GuardResult> + Send>>;
+
+    fn check(&self, _cx: &App, request: &NavigationRequest) -> Self::Future {
+        self.calls.lock().unwrap().push(request.to.clone());
+        let result = self.result.lock().unwrap().clone();
+        Box::pin(async move { result })
+    }
+
+    fn name(&self) -> &str {
+        "MockGuard"
+    }
+}
+
+/// Hook run by [`MockMiddleware`] with the phase and request
+#[cfg(feature = "middleware")]
+type MiddlewareHook = Arc<dyn Fn(&str, &NavigationRequest) + Send + Sync>;
+
+/// Middleware recording `before <path>` and `after <path>` for each call
+///
+/// Clones share their call log. A programmable hook can run on each call.
+#[cfg(feature = "middleware")]
+#[derive(Clone, Default)]
+pub struct MockMiddleware {
+    calls: Arc<Mutex<Vec<String>>>,
+    on_call: Option<MiddlewareHook>,
+}
+
+#[cfg(feature = "middleware")]
+impl MockMiddleware {
+    /// Create middleware that only records its calls
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also run `hook` with the phase (`"before"` or `"after"`) on each call
+    pub fn on_call(
+        mut self,
+        hook: impl Fn(&str, &NavigationRequest) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_call = Some(Arc::new(hook));
+        self
+    }
+
+    /// Recorded calls, e.g. `["before /users", "after /users"]`
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn record(
+        &self,
+        phase: &str,
+        request: &NavigationRequest,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", phase, request.to));
+        if let Some(hook) = &self.on_call {
+            hook(phase, request);
+        }
+        Box::pin(async {})
+    }
+}
+
+#[cfg(feature = "middleware")]
+impl RouteMiddleware for MockMiddleware {
+    type Future = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn before_navigation(&self, _cx: &App, request: &NavigationRequest) -> Self::Future {
+        self.record("before", request)
+    }
+
+    fn after_navigation(&self, _cx: &App, navigation: &AfterNavigation) -> Self::Future {
+        self.record("after", &navigation.request())
+    }
+
+    fn name(&self) -> &str {
+        "MockMiddleware"
+    }
+}
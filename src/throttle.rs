@@ -0,0 +1,221 @@
+//! Navigation throttling
+//!
+//! With [`RouterConfig::min_navigation_interval`](crate::RouterConfig::min_navigation_interval)
+//! or [`Route::debounce`](crate::Route::debounce) set, a push or replace
+//! arriving too soon after the previous navigation is held back or dropped,
+//! depending on the [`ThrottlePolicy`](crate::ThrottlePolicy):
+//!
+//! ```ignore
+//! use gpui_navigator::{init_router, RouterConfig, ThrottlePolicy};
+//! use std::time::Duration;
+//!
+//! init_router(cx, |router| {
+//!     router.set_config(
+//!         RouterConfig::new()
+//!             .min_navigation_interval(Duration::from_millis(300))
+//!             .throttle_policy(ThrottlePolicy::Coalesce),
+//!     );
+//! });
+//! ```
+//!
+//! A held navigation is applied by a timer the navigation starts, once the
+//! interval has elapsed; the push or replace returns
+//! [`PushOutcome::Throttled`](crate::PushOutcome::Throttled) or
+//! [`PushOutcome::Dropped`](crate::PushOutcome::Dropped). If more navigations
+//! arrive in the meantime, only the last one is applied.
+
+use crate::context::GlobalRouter;
+use crate::loader::PendingCommit;
+use crate::{trace_log, NavigationSource};
+use gpui::{AsyncApp, BorrowAppContext, Task};
+use std::time::{Duration, Instant};
+
+/// A navigation held back until the throttle interval has elapsed
+#[derive(Debug, Clone)]
+pub(crate) struct ThrottledNavigation {
+    pub(crate) path: String,
+    pub(crate) commit: PendingCommit,
+    /// What started the navigation
+    pub(crate) source: NavigationSource,
+    /// When the navigation may be applied
+    pub(crate) due: Instant,
+}
+
+/// Apply the held navigation after `delay`
+///
+/// If a later navigation was held with a longer interval in the meantime,
+/// waits for that one instead. Windows are refreshed after the navigation
+/// is applied.
+pub(crate) fn spawn_throttle_timer(app: &AsyncApp, delay: Duration) -> Task<()> {
+    trace_log!("Throttle timer spawned");
+    app.spawn(async move |cx| {
+        let mut delay = delay;
+        loop {
+            cx.background_executor().timer(delay).await;
+            let remaining = cx.update(|cx| {
+                if !cx.has_global::<GlobalRouter>() {
+                    return None;
+                }
+                let remaining =
+                    cx.update_global::<GlobalRouter, _>(|router, _| router.flush_throttled());
+                if remaining.is_none() {
+                    crate::context::refresh_windows(cx);
+                }
+                remaining
+            });
+            match remaining {
+                // A later navigation was held with a longer interval
+                Ok(Some(remaining)) => delay = remaining,
+                _ => break,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{History, HistoryEntry, HistoryListener};
+    use crate::{
+        init_router, Navigator, PushOutcome, Route, RouterConfig, RouterOutlet, ThrottlePolicy,
+    };
+    use gpui::{div, IntoElement, SharedString, TestAppContext, VisualTestContext};
+    use std::sync::{Arc, Mutex};
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    struct PushRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl HistoryListener for PushRecorder {
+        fn on_push(&self, entry: &HistoryEntry, _history: &History) {
+            self.0.lock().unwrap().push(entry.path.clone());
+        }
+    }
+
+    /// Set up `/items/:id` and `/search` routes and an outlet, recording pushes
+    fn setup(
+        cx: &mut TestAppContext,
+        config: RouterConfig,
+        search_debounce: Option<Duration>,
+    ) -> (&mut VisualTestContext, Arc<Mutex<Vec<String>>>) {
+        let pushes = Arc::new(Mutex::new(Vec::new()));
+        let recorder = PushRecorder(pushes.clone());
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(config);
+                router.add_history_listener(Box::new(recorder));
+                router.add_route(Route::new("/items/:id", |_, _, _| div().into_any_element()));
+                let mut search = Route::new("/search", |_, _, _| div().into_any_element());
+                search.debounce = search_debounce;
+                router.add_route(search);
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        (cx, pushes)
+    }
+
+    fn navigate(cx: &mut VisualTestContext, path: &str) -> PushOutcome {
+        let outcome = cx.update(|window, cx| {
+            let outcome = Navigator::push(cx, path);
+            window.refresh();
+            outcome
+        });
+        cx.run_until_parked();
+        outcome
+    }
+
+    fn current_path(cx: &mut VisualTestContext) -> SharedString {
+        cx.update(|_, cx| Navigator::current_path(cx))
+    }
+
+    fn throttled(cx: &mut VisualTestContext) -> Option<String> {
+        cx.update(|_, cx| {
+            cx.global::<GlobalRouter>()
+                .throttled_navigation()
+                .map(str::to_string)
+        })
+    }
+
+    #[gpui::test]
+    fn test_coalesced_navigation_applies_last(cx: &mut TestAppContext) {
+        let config = RouterConfig::new().min_navigation_interval(INTERVAL);
+        let (cx, pushes) = setup(cx, config, None);
+
+        assert!(navigate(cx, "/items/1").is_navigated());
+        assert!(matches!(
+            navigate(cx, "/items/2"),
+            PushOutcome::Throttled { path } if path == "/items/2"
+        ));
+        navigate(cx, "/items/3");
+        assert_eq!(current_path(cx), "/items/1");
+        assert_eq!(throttled(cx).as_deref(), Some("/items/3"));
+
+        cx.executor().advance_clock(INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(current_path(cx), "/items/3");
+        assert_eq!(throttled(cx), None);
+        // One event for the coalesced navigations
+        assert_eq!(*pushes.lock().unwrap(), vec!["/items/1", "/items/3"]);
+    }
+
+    #[gpui::test]
+    fn test_held_navigation_applies_without_outlet(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_config(RouterConfig::new().min_navigation_interval(INTERVAL));
+                router.add_route(Route::new("/items/:id", |_, _, _| div().into_any_element()));
+            });
+            Navigator::push(cx, "/items/1");
+            assert!(!Navigator::push(cx, "/items/2").is_navigated());
+        });
+
+        // Nothing renders, the navigation's own timer applies it
+        cx.executor().advance_clock(INTERVAL);
+        cx.run_until_parked();
+        cx.update(|cx| assert_eq!(Navigator::current_path(cx), "/items/2"));
+    }
+
+    #[gpui::test]
+    fn test_dropped_navigation(cx: &mut TestAppContext) {
+        let config = RouterConfig::new()
+            .min_navigation_interval(INTERVAL)
+            .throttle_policy(ThrottlePolicy::Drop);
+        let (cx, pushes) = setup(cx, config, None);
+
+        navigate(cx, "/items/1");
+        assert!(matches!(
+            navigate(cx, "/items/1"),
+            PushOutcome::Dropped { path } if path == "/items/1"
+        ));
+        assert_eq!(throttled(cx), None);
+
+        cx.executor().advance_clock(INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(*pushes.lock().unwrap(), vec!["/items/1"]);
+
+        // Once the interval has elapsed navigation goes through again
+        navigate(cx, "/items/2");
+        assert_eq!(current_path(cx), "/items/2");
+    }
+
+    #[gpui::test]
+    fn test_route_debounce_and_history_exemption(cx: &mut TestAppContext) {
+        let (cx, _) = setup(cx, RouterConfig::new(), Some(INTERVAL));
+
+        // Only the debounced route is throttled
+        navigate(cx, "/items/1");
+        navigate(cx, "/items/2");
+        assert_eq!(current_path(cx), "/items/2");
+        navigate(cx, "/search");
+        assert_eq!(throttled(cx).as_deref(), Some("/search"));
+
+        // Going back is not throttled and supersedes the held navigation
+        cx.update(|_, cx| Navigator::pop(cx));
+        assert_eq!(current_path(cx), "/items/1");
+        assert_eq!(throttled(cx), None);
+
+        cx.executor().advance_clock(INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(current_path(cx), "/items/1");
+    }
+}
@@ -3,7 +3,9 @@
 //! This module provides a transition system for route changes,
 //! allowing separate enter and exit animations for incoming and outgoing content.
 
-use gpui::{div, px, Div, IntoElement, ParentElement, Styled};
+use crate::nested::path_segments;
+use crate::NavigationDirection;
+use gpui::{div, px, relative, Div, IntoElement, ParentElement, Styled};
 use std::time::Duration;
 
 /// Direction for slide transitions
@@ -17,6 +19,11 @@ pub enum SlideDirection {
     Up,
     /// Slide from bottom to top
     Down,
+    /// Slide left when navigating deeper and right when navigating back up
+    ///
+    /// Picked for each navigation by [`auto_slide_direction`]; slides left
+    /// until resolved.
+    Auto,
 }
 
 /// Built-in transition types
@@ -38,6 +45,30 @@ pub enum Transition {
         direction: SlideDirection,
         /// Duration in milliseconds
         duration_ms: u64,
+        /// How far the exiting page moves relative to the entering one, from
+        /// 0.0 (stays in place) to 1.0 (moves fully off-screen, the default)
+        parallax: f32,
+        /// Opacity of the black scrim drawn over the exiting page as the
+        /// transition completes (0.0, the default, draws none)
+        dim_old: f32,
+        /// Direction of a [`SlideDirection::Auto`] slide between paths of the
+        /// same depth (left by default)
+        same_depth: SlideDirection,
+    },
+
+    /// Scale transition (zoom combined with a fade)
+    ///
+    /// GPUI has no transform API for regular elements, so scaling is
+    /// approximated by resizing a centered box relative to the outlet.
+    /// Content is laid out at the intermediate size: text reflows rather
+    /// than scaling smoothly.
+    Scale {
+        /// Scale factor at the start of the animation
+        from: f32,
+        /// Scale factor at the end of the animation
+        to: f32,
+        /// Duration in milliseconds
+        duration_ms: u64,
     },
 }
 
@@ -52,10 +83,26 @@ impl std::fmt::Debug for Transition {
             Self::Slide {
                 direction,
                 duration_ms,
+                parallax,
+                dim_old,
+                same_depth,
             } => f
                 .debug_struct("Transition::Slide")
                 .field("direction", direction)
                 .field("duration_ms", duration_ms)
+                .field("parallax", parallax)
+                .field("dim_old", dim_old)
+                .field("same_depth", same_depth)
+                .finish(),
+            Self::Scale {
+                from,
+                to,
+                duration_ms,
+            } => f
+                .debug_struct("Transition::Scale")
+                .field("from", from)
+                .field("to", to)
+                .field("duration_ms", duration_ms)
                 .finish(),
         }
     }
@@ -71,9 +118,24 @@ impl Clone for Transition {
             Self::Slide {
                 direction,
                 duration_ms,
+                parallax,
+                dim_old,
+                same_depth,
             } => Self::Slide {
                 direction: *direction,
                 duration_ms: *duration_ms,
+                parallax: *parallax,
+                dim_old: *dim_old,
+                same_depth: *same_depth,
+            },
+            Self::Scale {
+                from,
+                to,
+                duration_ms,
+            } => Self::Scale {
+                from: *from,
+                to: *to,
+                duration_ms: *duration_ms,
             },
         }
     }
@@ -87,32 +149,134 @@ impl Transition {
 
     /// Create a slide-left transition
     pub fn slide_left(duration_ms: u64) -> Self {
-        Self::Slide {
-            direction: SlideDirection::Left,
-            duration_ms,
-        }
+        Self::slide(SlideDirection::Left, duration_ms, 1.0)
     }
 
     /// Create a slide-right transition
     pub fn slide_right(duration_ms: u64) -> Self {
-        Self::Slide {
-            direction: SlideDirection::Right,
-            duration_ms,
-        }
+        Self::slide(SlideDirection::Right, duration_ms, 1.0)
     }
 
     /// Create a slide-up transition
     pub fn slide_up(duration_ms: u64) -> Self {
-        Self::Slide {
-            direction: SlideDirection::Up,
-            duration_ms,
-        }
+        Self::slide(SlideDirection::Up, duration_ms, 1.0)
     }
 
     /// Create a slide-down transition
     pub fn slide_down(duration_ms: u64) -> Self {
+        Self::slide(SlideDirection::Down, duration_ms, 1.0)
+    }
+
+    /// Create a slide that picks its direction from each navigation
+    ///
+    /// Navigating to a deeper path slides left, to a shallower one (or back
+    /// between paths of the same depth) slides right; see
+    /// [`auto_slide_direction`]. Other navigations between paths of the same
+    /// depth slide left, or as set with [`Transition::same_depth`]. Give the
+    /// routes on both sides this transition, since the entering route's
+    /// transition is played.
+    pub fn slide_auto(duration_ms: u64) -> Self {
+        Self::slide(SlideDirection::Auto, duration_ms, 1.0)
+    }
+
+    /// Create a slide-left transition where the exiting page moves `parallax` as far
+    ///
+    /// iOS-style navigation uses about 0.3, usually with [`Transition::dim_old`].
+    pub fn slide_left_parallax(duration_ms: u64, parallax: f32) -> Self {
+        Self::slide(SlideDirection::Left, duration_ms, parallax)
+    }
+
+    /// Create a slide-right transition where the exiting page moves `parallax` as far
+    pub fn slide_right_parallax(duration_ms: u64, parallax: f32) -> Self {
+        Self::slide(SlideDirection::Right, duration_ms, parallax)
+    }
+
+    /// Create a slide-up transition where the exiting page moves `parallax` as far
+    pub fn slide_up_parallax(duration_ms: u64, parallax: f32) -> Self {
+        Self::slide(SlideDirection::Up, duration_ms, parallax)
+    }
+
+    /// Create a slide-down transition where the exiting page moves `parallax` as far
+    pub fn slide_down_parallax(duration_ms: u64, parallax: f32) -> Self {
+        Self::slide(SlideDirection::Down, duration_ms, parallax)
+    }
+
+    fn slide(direction: SlideDirection, duration_ms: u64, parallax: f32) -> Self {
         Self::Slide {
-            direction: SlideDirection::Down,
+            direction,
+            duration_ms,
+            parallax: parallax.clamp(0.0, 1.0),
+            dim_old: 0.0,
+            same_depth: SlideDirection::Left,
+        }
+    }
+
+    /// Set the direction of an automatic slide between paths of the same depth
+    ///
+    /// Has no effect on other transitions, or when `direction` is
+    /// [`SlideDirection::Auto`].
+    pub fn same_depth(mut self, direction: SlideDirection) -> Self {
+        if let Self::Slide { same_depth, .. } = &mut self {
+            if direction != SlideDirection::Auto {
+                *same_depth = direction;
+            }
+        }
+        self
+    }
+
+    /// Resolve an automatic slide to a concrete direction
+    ///
+    /// `effective` is the direction picked for the navigation by
+    /// [`auto_slide_direction`]; with `None` the slide takes its
+    /// [`same_depth`](Self::same_depth) direction. Other transitions are
+    /// returned unchanged.
+    pub fn resolve_auto(mut self, effective: Option<SlideDirection>) -> Self {
+        if let Self::Slide {
+            direction,
+            same_depth,
+            ..
+        } = &mut self
+        {
+            if *direction == SlideDirection::Auto {
+                *direction = effective.unwrap_or(*same_depth);
+            }
+        }
+        self
+    }
+
+    /// Dim the exiting page of a slide with a black scrim up to `opacity`
+    ///
+    /// Has no effect on other transitions.
+    pub fn dim_old(mut self, opacity: f32) -> Self {
+        if let Self::Slide { dim_old, .. } = &mut self {
+            *dim_old = opacity.clamp(0.0, 1.0);
+        }
+        self
+    }
+
+    /// Create a zoom-in transition (content grows into place)
+    pub fn zoom_in(duration_ms: u64) -> Self {
+        Self::Scale {
+            from: 0.8,
+            to: 1.0,
+            duration_ms,
+        }
+    }
+
+    /// Create a zoom-out transition (content shrinks into place)
+    pub fn zoom_out(duration_ms: u64) -> Self {
+        Self::Scale {
+            from: 1.2,
+            to: 1.0,
+            duration_ms,
+        }
+    }
+
+    /// Create a scale transition between two factors
+    pub fn scale(from: f32, to: f32, duration_ms: u64) -> Self {
+        Self::Scale {
+            from,
+            to,
             duration_ms,
         }
     }
@@ -123,6 +287,7 @@ impl Transition {
             Self::None => Duration::ZERO,
             Self::Fade { duration_ms, .. } => Duration::from_millis(*duration_ms),
             Self::Slide { duration_ms, .. } => Duration::from_millis(*duration_ms),
+            Self::Scale { duration_ms, .. } => Duration::from_millis(*duration_ms),
         }
     }
 
@@ -165,6 +330,14 @@ impl TransitionConfig {
         self.override_next.as_ref().unwrap_or(&self.default)
     }
 
+    /// Get the transition for a navigation that may request its own
+    ///
+    /// The navigation's transition (e.g. from `Navigator::push_with_transition`)
+    /// wins over the override, which wins over the default.
+    pub fn resolve<'a>(&'a self, navigation: Option<&'a Transition>) -> &'a Transition {
+        navigation.unwrap_or_else(|| self.active())
+    }
+
     /// Set an override transition for the next navigation
     pub fn set_override(&mut self, transition: Transition) {
         self.override_next = Some(transition);
@@ -181,6 +354,85 @@ impl TransitionConfig {
     }
 }
 
+// ============================================================================
+// Transition Progress
+// ============================================================================
+
+/// Which side of a transition a route is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionPhase {
+    /// The route is being navigated to
+    Enter,
+    /// The route is being navigated away from
+    Exit,
+    /// No transition is running
+    Idle,
+}
+
+/// How far the transition of a route has progressed
+///
+/// Route content reads it with [`RouteParams::transition_progress`](crate::RouteParams::transition_progress)
+/// or receives it in a [`Route::animated`](crate::Route::animated) builder.
+/// While a transition runs the outlet rebuilds the route every frame, with
+/// `value` going from 0.0 to 1.0 for both the entering and the exiting
+/// route. Once it completes, the current route sees `value` 1.0 and
+/// [`TransitionPhase::Idle`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitionProgress {
+    /// Progress from 0.0 (started) to 1.0 (done)
+    pub value: f32,
+    /// Whether the route is entering, exiting or settled
+    pub phase: TransitionPhase,
+}
+
+impl TransitionProgress {
+    /// No transition running
+    pub const IDLE: Self = Self {
+        value: 1.0,
+        phase: TransitionPhase::Idle,
+    };
+
+    /// Progress of an entering route
+    pub fn enter(value: f32) -> Self {
+        Self {
+            value: value.clamp(0.0, 1.0),
+            phase: TransitionPhase::Enter,
+        }
+    }
+
+    /// Progress of an exiting route
+    pub fn exit(value: f32) -> Self {
+        Self {
+            value: value.clamp(0.0, 1.0),
+            phase: TransitionPhase::Exit,
+        }
+    }
+
+    /// Check if no transition is running
+    pub fn is_idle(&self) -> bool {
+        self.phase == TransitionPhase::Idle
+    }
+
+    /// Progress of an item in a staggered sequence
+    ///
+    /// Splits the transition so `count` items start one after another,
+    /// each animating over an equal share of it. Returns 0.0 to 1.0 for item
+    /// `index`.
+    pub fn staggered(&self, index: usize, count: usize) -> f32 {
+        if count == 0 {
+            return self.value;
+        }
+        let step = 1.0 / count as f32;
+        ((self.value - index as f32 * step) / step).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for TransitionProgress {
+    fn default() -> Self {
+        Self::IDLE
+    }
+}
+
 // ============================================================================
 // Transition Builder
 // ============================================================================
@@ -198,6 +450,10 @@ pub struct TransitionContext {
 /// Takes an element, a transition type, and a progress value (0.0 to 1.0),
 /// then returns a `Div` with the appropriate visual transformation applied.
 pub fn apply_transition(element: impl IntoElement, transition: &Transition, progress: f32) -> Div {
+    if let Transition::Scale { from, to, .. } = transition {
+        return scaled(element, interpolate_scale(*from, *to, progress), progress);
+    }
+
     // Always use consistent method chain to avoid recursion limit
     // Calculate all values first, then apply them in one chain
     let (x, y, opacity) = match transition {
@@ -211,13 +467,16 @@ pub fn apply_transition(element: impl IntoElement, transition: &Transition, prog
         Transition::Slide { direction, .. } => {
             let offset_px = (1.0 - progress) * 100.0;
             let (x, y) = match direction {
-                SlideDirection::Left => (offset_px, 0.0),
+                SlideDirection::Left | SlideDirection::Auto => (offset_px, 0.0),
                 SlideDirection::Right => (-offset_px, 0.0),
                 SlideDirection::Up => (0.0, offset_px),
                 SlideDirection::Down => (0.0, -offset_px),
             };
             (x, y, progress)
         }
+
+        // Handled above
+        Transition::Scale { .. } => (0.0, 0.0, progress),
     };
 
     // Unified return type - same method chain for all branches
@@ -229,6 +488,121 @@ pub fn apply_transition(element: impl IntoElement, transition: &Transition, prog
         .child(element)
 }
 
+/// Interpolate a scale factor for the given progress
+///
+/// Progress is clamped to `[0.0, 1.0]`, so `0.0` yields `from` and `1.0` yields `to`.
+pub fn interpolate_scale(from: f32, to: f32, progress: f32) -> f32 {
+    let progress = progress.clamp(0.0, 1.0);
+    from + (to - from) * progress
+}
+
+/// Offsets of the exiting and entering page of a slide at `progress`
+///
+/// Offsets are fractions of the outlet's width (or height for vertical
+/// slides), positive to the right (or down). The exiting page moves
+/// `parallax` times as far as the entering one; with 1.0 both move at the
+/// same speed and the exiting page ends fully off-screen.
+pub fn slide_offsets(direction: SlideDirection, progress: f32, parallax: f32) -> (f32, f32) {
+    let progress = progress.clamp(0.0, 1.0);
+    let exit = progress * parallax;
+    let enter = 1.0 - progress;
+    match direction {
+        SlideDirection::Left | SlideDirection::Up | SlideDirection::Auto => (-exit, enter),
+        SlideDirection::Right | SlideDirection::Down => (exit, -enter),
+    }
+}
+
+/// Direction of an automatic slide navigating from one path to another
+///
+/// Compares the number of segments in both paths, ignoring query strings:
+/// a deeper path slides left, a shallower one slides right. Between paths of
+/// the same depth, going back slides right; other navigations return `None`,
+/// leaving the choice to the transition (see [`Transition::same_depth`]).
+///
+/// ```
+/// use gpui_navigator::transition::auto_slide_direction;
+/// use gpui_navigator::{NavigationDirection, SlideDirection};
+///
+/// let push = NavigationDirection::Forward;
+/// assert_eq!(auto_slide_direction("/users", "/users/42", push), Some(SlideDirection::Left));
+/// assert_eq!(auto_slide_direction("/users/42", "/", push), Some(SlideDirection::Right));
+/// assert_eq!(auto_slide_direction("/users/1", "/users/2", push), None);
+/// ```
+pub fn auto_slide_direction(
+    from: &str,
+    to: &str,
+    direction: NavigationDirection,
+) -> Option<SlideDirection> {
+    use std::cmp::Ordering;
+
+    match path_segments(to).len().cmp(&path_segments(from).len()) {
+        Ordering::Greater => Some(SlideDirection::Left),
+        Ordering::Less => Some(SlideDirection::Right),
+        Ordering::Equal => {
+            (direction == NavigationDirection::Back).then_some(SlideDirection::Right)
+        }
+    }
+}
+
+/// Move a layer of a slide by `offset`, a fraction of the outlet's size
+///
+/// Horizontal slides offset the layer from the left, vertical ones from the top.
+pub(crate) fn offset_layer(layer: Div, direction: SlideDirection, offset: f32) -> Div {
+    match direction {
+        SlideDirection::Left | SlideDirection::Right | SlideDirection::Auto => {
+            layer.left(relative(offset))
+        }
+        SlideDirection::Up | SlideDirection::Down => layer.top(relative(offset)),
+    }
+}
+
+/// Draw the exiting and entering page of a slide frozen at `progress`
+///
+/// Lays the pages out like the slide transition of `RouterOutlet`, for
+/// callers driving the progress themselves (such as `SwipeNavigator`).
+pub fn slide_layers(
+    exiting: impl IntoElement,
+    entering: impl IntoElement,
+    direction: SlideDirection,
+    progress: f32,
+    parallax: f32,
+) -> Div {
+    let (exit, enter) = slide_offsets(direction, progress, parallax);
+    let layer = || div().absolute().w_full().h_full();
+    div()
+        .relative()
+        .w_full()
+        .h_full()
+        .overflow_hidden()
+        .child(offset_layer(layer().child(exiting), direction, exit))
+        .child(offset_layer(layer().child(entering), direction, enter))
+}
+
+/// Opacity of the scrim over the exiting page of a slide at `progress`
+pub fn slide_scrim_opacity(progress: f32, dim_old: f32) -> f32 {
+    progress.clamp(0.0, 1.0) * dim_old.clamp(0.0, 1.0)
+}
+
+/// Render `element` at `scale` of the available size, centered, with the given opacity
+///
+/// Used for `Transition::Scale`; see its docs for limitations.
+fn scaled(element: impl IntoElement, scale: f32, opacity: f32) -> Div {
+    div()
+        .size_full()
+        .flex()
+        .items_center()
+        .justify_center()
+        .child(
+            div()
+                .flex_none()
+                .w(relative(scale.max(0.0)))
+                .h(relative(scale.max(0.0)))
+                .overflow_hidden()
+                .opacity(opacity.clamp(0.0, 1.0))
+                .child(element),
+        )
+}
+
 /// Easing function - ease in out cubic
 pub fn ease_in_out_cubic(t: f32) -> f32 {
     if t < 0.5 {
@@ -280,6 +654,142 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_slide_parallax() {
+        let Transition::Slide {
+            parallax, dim_old, ..
+        } = Transition::slide_left(300)
+        else {
+            panic!("Expected Slide transition");
+        };
+        assert!((parallax - 1.0).abs() < f32::EPSILON);
+        assert!(dim_old.abs() < f32::EPSILON);
+
+        let Transition::Slide {
+            direction,
+            parallax,
+            dim_old,
+            ..
+        } = Transition::slide_right_parallax(300, 0.3).dim_old(0.2)
+        else {
+            panic!("Expected Slide transition");
+        };
+        assert_eq!(direction, SlideDirection::Right);
+        assert!((parallax - 0.3).abs() < f32::EPSILON);
+        assert!((dim_old - 0.2).abs() < f32::EPSILON);
+
+        // Only slides dim the old page
+        assert!(Transition::fade(200).dim_old(0.5).duration() == Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_slide_offsets() {
+        // Full parallax moves both pages at the same speed
+        assert_eq!(slide_offsets(SlideDirection::Left, 0.0, 1.0), (0.0, 1.0));
+        assert_eq!(
+            slide_offsets(SlideDirection::Left, 0.25, 1.0),
+            (-0.25, 0.75)
+        );
+        assert_eq!(slide_offsets(SlideDirection::Left, 1.0, 1.0), (-1.0, 0.0));
+        assert_eq!(slide_offsets(SlideDirection::Right, 0.5, 1.0), (0.5, -0.5));
+
+        // Partial parallax moves the old page a fraction as far
+        let (exit, enter) = slide_offsets(SlideDirection::Left, 1.0, 0.3);
+        assert!((exit + 0.3).abs() < 1e-6);
+        assert!(enter.abs() < f32::EPSILON);
+        let (exit, enter) = slide_offsets(SlideDirection::Down, 0.5, 0.5);
+        assert!((exit - 0.25).abs() < 1e-6);
+        assert!((enter + 0.5).abs() < 1e-6);
+
+        // No parallax keeps the old page in place
+        assert_eq!(slide_offsets(SlideDirection::Up, 0.75, 0.0), (0.0, 0.25));
+
+        // Progress is clamped
+        assert_eq!(slide_offsets(SlideDirection::Left, 1.5, 1.0), (-1.0, 0.0));
+    }
+
+    #[test]
+    fn test_auto_slide_direction() {
+        let push = NavigationDirection::Forward;
+        let back = NavigationDirection::Back;
+
+        // Deeper slides left, shallower slides right
+        assert_eq!(
+            auto_slide_direction("/products", "/products/:id", push),
+            Some(SlideDirection::Left)
+        );
+        assert_eq!(
+            auto_slide_direction("/products/123/reviews", "/products/123", back),
+            Some(SlideDirection::Right)
+        );
+
+        // Depth wins over the history direction
+        assert_eq!(
+            auto_slide_direction("/products", "/products/123", back),
+            Some(SlideDirection::Left)
+        );
+        assert_eq!(
+            auto_slide_direction("/products/123", "/about", push),
+            Some(SlideDirection::Right)
+        );
+
+        // The root path has no segments; query strings are ignored
+        assert_eq!(
+            auto_slide_direction("/", "/settings?tab=1", push),
+            Some(SlideDirection::Left)
+        );
+        assert_eq!(
+            auto_slide_direction("/settings/", "/", push),
+            Some(SlideDirection::Right)
+        );
+
+        // Same depth: back slides right, anything else is left to the transition
+        assert_eq!(auto_slide_direction("/", "/", push), None);
+        assert_eq!(
+            auto_slide_direction("/users/1", "/users/2", NavigationDirection::Replace),
+            None
+        );
+        assert_eq!(
+            auto_slide_direction("/users/1", "/users/2", back),
+            Some(SlideDirection::Right)
+        );
+    }
+
+    #[test]
+    fn test_slide_auto_resolve() {
+        let resolved = |transition: Transition, effective| {
+            let Transition::Slide { direction, .. } = transition.resolve_auto(effective) else {
+                panic!("Expected Slide transition");
+            };
+            direction
+        };
+
+        let auto = Transition::slide_auto(300);
+        assert_eq!(
+            resolved(auto.clone(), Some(SlideDirection::Right)),
+            SlideDirection::Right
+        );
+        assert_eq!(resolved(auto.clone(), None), SlideDirection::Left);
+        assert_eq!(
+            resolved(auto.same_depth(SlideDirection::Up), None),
+            SlideDirection::Up
+        );
+
+        // Fixed directions are kept
+        assert_eq!(
+            resolved(Transition::slide_down(300), Some(SlideDirection::Left)),
+            SlideDirection::Down
+        );
+        assert!(Transition::fade(200).resolve_auto(None).duration() == Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_slide_scrim_opacity() {
+        assert!(slide_scrim_opacity(1.0, 0.0).abs() < f32::EPSILON);
+        assert!((slide_scrim_opacity(0.5, 0.4) - 0.2).abs() < 1e-6);
+        assert!((slide_scrim_opacity(2.0, 0.4) - 0.4).abs() < 1e-6);
+    }
+
     #[test]
     fn test_transition_config_default() {
         let config = TransitionConfig::default();
@@ -307,6 +817,24 @@ mod tests {
         assert_eq!(config.active().duration(), Duration::from_millis(200));
     }
 
+    #[test]
+    fn test_transition_config_resolve() {
+        let mut config = TransitionConfig::new(Transition::fade(200));
+        assert!(matches!(config.resolve(None), Transition::Fade { .. }));
+
+        config.set_override(Transition::zoom_in(250));
+        assert!(matches!(config.resolve(None), Transition::Scale { .. }));
+
+        let navigation = Transition::slide_left(300);
+        assert!(matches!(
+            config.resolve(Some(&navigation)),
+            Transition::Slide {
+                direction: SlideDirection::Left,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_transition_helpers() {
         // Test all helper methods
@@ -315,5 +843,46 @@ mod tests {
         let _ = Transition::slide_right(300);
         let _ = Transition::slide_up(300);
         let _ = Transition::slide_down(300);
+        let _ = Transition::zoom_in(300);
+        let _ = Transition::zoom_out(300);
+    }
+
+    #[test]
+    fn test_transition_scale() {
+        let transition = Transition::zoom_in(250);
+        assert_eq!(transition.duration(), Duration::from_millis(250));
+
+        if let Transition::Scale { from, to, .. } = Transition::zoom_out(250) {
+            assert!(from > to);
+        } else {
+            panic!("Expected Scale transition");
+        }
+    }
+
+    #[test]
+    fn test_interpolate_scale() {
+        assert!((interpolate_scale(0.8, 1.0, 0.0) - 0.8).abs() < f32::EPSILON);
+        assert!((interpolate_scale(0.8, 1.0, 1.0) - 1.0).abs() < f32::EPSILON);
+        assert!((interpolate_scale(0.8, 1.0, 0.5) - 0.9).abs() < 1e-6);
+        assert!((interpolate_scale(1.2, 1.0, 0.5) - 1.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_interpolate_scale_clamps_progress() {
+        assert!((interpolate_scale(0.8, 1.0, -0.5) - 0.8).abs() < f32::EPSILON);
+        assert!((interpolate_scale(0.8, 1.0, 1.5) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_transition_progress_staggered() {
+        let progress = TransitionProgress::enter(0.5);
+        assert!((progress.staggered(0, 3) - 1.0).abs() < f32::EPSILON);
+        assert!((progress.staggered(1, 3) - 0.5).abs() < 1e-6);
+        assert!(progress.staggered(2, 3).abs() < f32::EPSILON);
+
+        assert!(!progress.is_idle());
+        assert!(TransitionProgress::default().is_idle());
+        assert!((TransitionProgress::IDLE.staggered(2, 3) - 1.0).abs() < f32::EPSILON);
+        assert!((TransitionProgress::exit(1.5).value - 1.0).abs() < f32::EPSILON);
     }
 }
@@ -4,21 +4,42 @@
 //! When a parent route contains child routes, the outlet determines where
 //! the matched child's content appears within the parent's layout.
 
+use crate::config::{FocusPolicy, NavOptions, PreserveQuery};
 use crate::context::GlobalRouter;
-use crate::nested::resolve_child_route;
+use crate::diagnostics;
+use crate::error::{RetryHandle, RouteError};
+use crate::focus::focus_route_content;
 #[cfg(feature = "transition")]
-use crate::transition::{SlideDirection, Transition};
-use crate::{debug_log, error_log, trace_log, warn_log};
-use gpui::{div, AnyElement, App, Div, IntoElement, ParentElement, SharedString, Styled, Window};
+use crate::hero::{hero_overlay, ExitingHeroes};
+use crate::history::split_fragment;
+use crate::loader::{DataState, LoaderData, LoaderMode};
+use crate::meta::RouteTitle;
+use crate::nested::{build_child_path, path_segments, segments_match_prefix};
+use crate::outlet_plan::{
+    missing_child_parent, outlet_route, plan_outlet_render, OutletChange, OutletLayer,
+    OutletLocation, OutletSnapshot,
+};
+use crate::resolved::resolved_outlet;
+use crate::route::{chain_level_params, match_route_chain, RouteRef};
+#[cfg(feature = "transition")]
+use crate::transition::{
+    interpolate_scale, offset_layer, slide_offsets, slide_scrim_opacity, SlideDirection,
+    Transition, TransitionProgress,
+};
+use crate::{debug_log, error_log, trace_log, warn_log, NavigationSource};
+use gpui::{
+    div, AnyElement, App, Div, ElementId, FocusHandle, Hsla, InteractiveElement, IntoElement,
+    ParentElement, SharedString, Styled, Window,
+};
 
 #[cfg(feature = "transition")]
 use gpui::{relative, Animation, AnimationExt};
 
-#[cfg(feature = "transition")]
 use gpui::prelude::FluentBuilder;
 
-#[cfg(feature = "transition")]
 use std::time::Duration;
+#[cfg(feature = "transition")]
+use std::time::Instant;
 
 /// RouterOutlet component that renders the active child route
 ///
@@ -52,12 +73,84 @@ pub struct RouterOutlet {
     /// Optional name for named outlets
     /// Default outlet has no name
     name: Option<String>,
+    /// Background drawn between a modal route and the page underneath
+    modal_backdrop: Hsla,
+    /// Shown by nested outlets that have no child to render
+    placeholder: Option<OutletPlaceholder>,
+    /// Element id set with [`RouterOutlet::with_id`]
+    id: Option<ElementId>,
 }
 
+/// Builds what a nested outlet shows when it has no child to render
+pub type OutletPlaceholder = std::sync::Arc<dyn Fn() -> AnyElement + Send + Sync>;
+
 impl RouterOutlet {
     /// Create a new default outlet
     pub fn new() -> Self {
-        Self { name: None }
+        Self {
+            name: None,
+            modal_backdrop: Self::DEFAULT_MODAL_BACKDROP,
+            placeholder: None,
+            id: None,
+        }
+    }
+
+    /// Semi-transparent black
+    const DEFAULT_MODAL_BACKDROP: Hsla = Hsla {
+        h: 0.0,
+        s: 0.0,
+        l: 0.0,
+        a: 0.5,
+    };
+
+    /// Set the backdrop drawn behind modal routes
+    ///
+    /// See [`Presentation::Modal`](crate::Presentation::Modal).
+    pub fn modal_backdrop(mut self, color: impl Into<Hsla>) -> Self {
+        self.modal_backdrop = color.into();
+        self
+    }
+
+    /// Set the element id of the outlet
+    ///
+    /// The outlet keeps its state, and the element state of the routes it
+    /// shows, under this id. It defaults to one derived from the outlet name,
+    /// so set distinct ids to render several outlets of the same name side by
+    /// side in one view.
+    ///
+    /// ```ignore
+    /// div()
+    ///     .child(cx.new(|_| RouterOutlet::new().with_id("main")))
+    ///     .child(cx.new(|_| RouterOutlet::new().with_id("preview")))
+    /// ```
+    pub fn with_id(mut self, id: impl Into<ElementId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Element id of the outlet, see [`RouterOutlet::with_id`]
+    ///
+    /// Stays the same across navigations; only the animated layers inside
+    /// the outlet change identity when the route does.
+    fn element_id(&self) -> ElementId {
+        match (&self.id, &self.name) {
+            (Some(id), _) => id.clone(),
+            (None, Some(name)) => ElementId::Name(format!("router_outlet_{}", name).into()),
+            (None, None) => ElementId::Name("router_outlet".into()),
+        }
+    }
+
+    /// Set what nested outlets inside this outlet show when they have no child
+    ///
+    /// Used when the current path is a parent route with children but none
+    /// of them (not even an index route) matches, and when the matched child
+    /// has no builder. Overrides [`DefaultPages::with_empty_outlet`].
+    pub fn placeholder<F>(mut self, builder: F) -> Self
+    where
+        F: Fn() -> AnyElement + Send + Sync + 'static,
+    {
+        self.placeholder = Some(std::sync::Arc::new(builder));
+        self
     }
 
     /// Create a named outlet
@@ -81,6 +174,7 @@ impl RouterOutlet {
     pub fn named(name: impl Into<String>) -> Self {
         Self {
             name: Some(name.into()),
+            ..Self::new()
         }
     }
 }
@@ -94,190 +188,591 @@ impl Default for RouterOutlet {
 use gpui::{Context, Render};
 
 /// State for RouterOutlet animation tracking
-#[derive(Clone)]
+#[derive(Clone, Default)]
 struct OutletState {
-    current_path: String,
-    animation_counter: u32,
-    // Current route data (will become previous on next transition)
-    current_params: crate::RouteParams,
-    current_builder: Option<crate::route::RouteBuilder>,
+    /// What the outlet showed after its last route change
+    shown: OutletSnapshot,
+    /// When the current transition started, for builders reading its progress
     #[cfg(feature = "transition")]
-    current_transition: crate::transition::Transition,
-    // Previous route info for exit animation
-    previous_route: Option<PreviousRoute>,
-}
-
-#[derive(Clone)]
-struct PreviousRoute {
-    path: String,
-    params: crate::RouteParams,
-    builder: Option<crate::route::RouteBuilder>,
+    transition_started: Option<Instant>,
+    /// Focus handle of the container wrapping the content
+    focus_handle: Option<FocusHandle>,
+    /// Whether focus moves to the content once its transition completes
+    focus_pending: bool,
+    /// [`GlobalRouter::generation`] of the router `shown` was resolved with
+    generation: u64,
 }
 
-impl Default for OutletState {
-    fn default() -> Self {
+impl OutletLayer {
+    /// Build the top-level route matching `path`
+    ///
+    /// A layout whose children matched gets the params of its own level
+    /// only, like nested levels do (see [`RouterState::params_by_level`]).
+    ///
+    /// [`RouterState::params_by_level`]: crate::RouterState::params_by_level
+    pub(crate) fn for_path(router: &GlobalRouter, path: &str) -> Self {
+        let top_level = router.state().routes();
+        let route = outlet_route(top_level, path);
+        let own = route
+            .as_ref()
+            .and_then(|route| match match_route_chain(top_level, path) {
+                Some((chain, route_match)) if std::sync::Arc::ptr_eq(chain[0], route) => {
+                    chain_level_params(&chain, &route_match.params)
+                        .into_iter()
+                        .next()
+                }
+                _ => route
+                    .matches(path)
+                    .map(|route_match| crate::RouteParams::from_map(route_match.params)),
+            });
         Self {
-            current_path: String::new(),
-            animation_counter: 0,
-            current_params: crate::RouteParams::new(),
-            current_builder: None,
-            #[cfg(feature = "transition")]
-            current_transition: crate::transition::Transition::None,
-            previous_route: None,
+            path: SharedString::from(path.to_string()),
+            params: std::sync::Arc::new(
+                own.map(|own| {
+                    let mut params = locale_params(Some(router));
+                    for (key, value) in own.iter() {
+                        params.insert(key.clone(), value.clone());
+                    }
+                    params
+                })
+                .unwrap_or_default(),
+            ),
+            route,
+        }
+    }
+
+    /// Build the route, or the not found page if no route matched
+    pub(crate) fn build(
+        &self,
+        window: &mut Window,
+        cx: &mut App,
+        pages: &DefaultPages,
+    ) -> AnyElement {
+        match &self.route {
+            Some(route) => build_route(&[route], &self.path, window, cx, &self.params, pages),
+            None => handled_not_found(&self.path, cx)
+                .unwrap_or_else(|| not_found_page().into_any_element()),
         }
     }
 }
 
-impl Render for RouterOutlet {
-    fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
-        trace_log!("🔄 RouterOutlet::render() called");
+/// Build the last route of `chain` (its ancestors first) for `path`
+///
+/// While the route's loader runs its [skeleton](crate::Route::skeleton) is
+/// shown, if it has one, and crossfaded to the page once the data is there;
+/// see [`build_page`] for the rest.
+fn build_route(
+    chain: &[&RouteRef],
+    path: &str,
+    window: &mut Window,
+    cx: &mut App,
+    params: &crate::RouteParams,
+    pages: &DefaultPages,
+) -> AnyElement {
+    let Some(route) = chain.last() else {
+        return not_found_page().into_any_element();
+    };
+    let Some(skeleton) = route.skeleton.clone() else {
+        return build_page(chain, path, window, cx, params, pages);
+    };
+    let loading = matches!(loader_state(cx, route, path).0, Some(DataState::Loading));
+    #[cfg(feature = "transition")]
+    let crossfade = skeleton_crossfade(window, cx, path, loading);
+    if loading {
+        return build_skeleton(&skeleton, window, cx, params);
+    }
 
-        // Use keyed state to persist animation counter and content across renders
-        let state_key = SharedString::from(format!("outlet_{:?}", self.name));
-        let state = window.use_keyed_state(state_key.clone(), cx, |_, _| OutletState::default());
+    let page = build_page(chain, path, window, cx, params, pages);
+    #[cfg(feature = "transition")]
+    let page = match crossfade {
+        Some((duration, counter)) => {
+            let skeleton = build_skeleton(&skeleton, window, cx, params);
+            crossfade_from_skeleton(skeleton, page, duration, counter)
+        }
+        None => page,
+    };
+    page
+}
+
+/// Build a route's skeleton with the params its builder gets
+fn build_skeleton(
+    skeleton: &crate::route::RouteBuilder,
+    window: &mut Window,
+    cx: &mut App,
+    params: &crate::RouteParams,
+) -> AnyElement {
+    let was_rendering = crate::context::set_in_render(cx, true);
+    let element = skeleton.build(window, cx, params);
+    crate::context::set_in_render(cx, was_rendering);
+    element
+}
+
+/// Whether a route at `path` showed its skeleton, and when the page replaced it
+#[cfg(feature = "transition")]
+#[derive(Default, Clone, Copy)]
+struct SkeletonState {
+    showing: bool,
+    animation_counter: u32,
+    replaced: Option<Instant>,
+}
 
-        let (prev_path, animation_counter) = {
-            let guard = state.read(cx);
-            (guard.current_path.clone(), guard.animation_counter)
+/// Crossfade duration and animation counter, while the page of the route at
+/// `path` replaces its skeleton
+///
+/// Tracks whether the skeleton is shown (`loading`), starting the crossfade
+/// when the page takes its place. Requests frames until it is done.
+#[cfg(feature = "transition")]
+fn skeleton_crossfade(
+    window: &mut Window,
+    cx: &mut App,
+    path: &str,
+    loading: bool,
+) -> Option<(Duration, u32)> {
+    let key = SharedString::from(format!("route-skeleton-{path}"));
+    let state = window.use_keyed_state(key, cx, |_, _| SkeletonState::default());
+    let mut next = *state.read(cx);
+    if loading {
+        next.showing = true;
+    } else if next.showing {
+        next = SkeletonState {
+            showing: false,
+            animation_counter: next.animation_counter.wrapping_add(1),
+            replaced: Some(cx.background_executor().now()),
         };
+    }
+    state.update(cx, |state, _| *state = next);
+
+    let duration = cx
+        .try_global::<GlobalRouter>()
+        .map_or(Duration::ZERO, |router| router.config().skeleton_crossfade);
+    let elapsed = cx
+        .background_executor()
+        .now()
+        .saturating_duration_since(next.replaced?);
+    if loading || elapsed >= duration {
+        return None;
+    }
+    window.request_animation_frame();
+    Some((duration, next.animation_counter))
+}
 
-        // Get current router info
-        #[cfg(feature = "transition")]
-        let (router_path, route_params, route_transition, builder_opt) = cx
-            .try_global::<crate::context::GlobalRouter>()
-            .map(|router| {
-                let path = router.current_path().to_string();
-
-                let params = router
-                    .current_match_immutable()
-                    .map(|m| {
-                        let mut rp = crate::RouteParams::new();
-                        for (k, v) in m.params {
-                            rp.insert(k, v);
-                        }
-                        rp
-                    })
-                    .unwrap_or_else(crate::RouteParams::new);
+/// Fade `page` in over `skeleton` fading out
+///
+/// Only the two swap; the route's enter transition is not played again.
+#[cfg(feature = "transition")]
+fn crossfade_from_skeleton(
+    skeleton: AnyElement,
+    page: AnyElement,
+    duration: Duration,
+    counter: u32,
+) -> AnyElement {
+    div()
+        .relative()
+        .size_full()
+        .child(div().size_full().child(page).with_animation(
+            animation_id("skeleton-crossfade-page", counter),
+            Animation::new(duration),
+            |this, delta| this.opacity(delta.clamp(0.0, 1.0)),
+        ))
+        .child(
+            div()
+                .absolute()
+                .top_0()
+                .left_0()
+                .size_full()
+                .child(skeleton)
+                .with_animation(
+                    animation_id("skeleton-crossfade-skeleton", counter),
+                    Animation::new(duration),
+                    |this, delta| this.opacity(1.0 - delta.clamp(0.0, 1.0)),
+                ),
+        )
+        .into_any_element()
+}
+
+/// Build the page of the last route of `chain` for `path`
+///
+/// The route gets the output of its loader. While a blocking loader runs the
+/// loading page is shown instead; when it fails, or the builder panics, the
+/// error goes to the nearest error boundary in `chain` (see
+/// [`render_route_error`]). A builder that panicked is not called again
+/// until the next navigation or a retry. Panics propagate when
+/// [`RouterConfig::catch_builder_panics`](crate::RouterConfig::catch_builder_panics)
+/// is disabled.
+fn build_page(
+    chain: &[&RouteRef],
+    path: &str,
+    window: &mut Window,
+    cx: &mut App,
+    params: &crate::RouteParams,
+    pages: &DefaultPages,
+) -> AnyElement {
+    let Some(route) = chain.last() else {
+        return not_found_page().into_any_element();
+    };
+    let Some(builder) = route.builder.as_ref() else {
+        return not_found_page().into_any_element();
+    };
 
-                let transition = router
-                    .current_route()
-                    .map(|route| route.transition.default.clone())
-                    .unwrap_or(Transition::None);
+    let (data, mode) = loader_state(cx, route, path);
+    if mode == Some(LoaderMode::Blocking) {
+        match &data {
+            Some(DataState::Loading) => return pages.render_loading(),
+            Some(DataState::Error(message)) => {
+                let error = RouteError::Loader {
+                    message: message.clone(),
+                };
+                return render_route_error(chain, &error, path, cx, pages);
+            }
+            _ => {}
+        }
+    }
+    let params = match data {
+        Some(data) => params.clone().with_data(data),
+        None => params.clone(),
+    };
 
-                let builder = router
-                    .current_route()
-                    .and_then(|route| route.builder.clone());
+    // Outlets the builder renders show this route's children
+    let outer = cx
+        .try_global::<GlobalRouter>()
+        .and_then(|router| router.building_route.clone());
+    let outer_rendering = cx
+        .try_global::<GlobalRouter>()
+        .is_some_and(|router| router.state().in_render());
+    let build = |window: &mut Window, cx: &mut App| {
+        let params = prepared_params(route, builder, params, cx);
+        set_building_route(cx, Some(std::sync::Arc::clone(route)));
+        let was_rendering = crate::context::set_in_render(cx, true);
+        let element = builder.build(window, cx, &params);
+        set_building_route(cx, outer.clone());
+        crate::context::set_in_render(cx, was_rendering);
+        element
+    };
 
-                (path, params, transition, builder)
-            })
-            .unwrap_or_else(|| {
-                (
-                    "/".to_string(),
-                    crate::RouteParams::new(),
-                    Transition::None,
-                    None,
-                )
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
+        return build(window, cx);
+    };
+    if !router.config().catch_builder_panics {
+        return build(window, cx);
+    }
+    if let Some(message) = router.builder_panic(builder) {
+        let error = RouteError::Panic {
+            message: message.to_string(),
+        };
+        return render_route_error(chain, &error, path, cx, pages);
+    }
+
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| build(window, cx))) {
+        Ok(element) => element,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| (*message).to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Route builder panicked".to_string());
+            error_log!("Route builder for '{}' panicked: {}", path, message);
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.poison_builder(builder, message.clone());
+                router.building_route.clone_from(&outer);
+                router.state_mut().set_in_render(outer_rendering);
             });
+            render_route_error(chain, &RouteError::Panic { message }, path, cx, pages)
+        }
+    }
+}
 
-        #[cfg(not(feature = "transition"))]
-        let (router_path, route_params, builder_opt) = cx
-            .try_global::<crate::context::GlobalRouter>()
-            .map(|router| {
-                let path = router.current_path().to_string();
-
-                let params = router
-                    .current_match_immutable()
-                    .map(|m| {
-                        let mut rp = crate::RouteParams::new();
-                        for (k, v) in m.params {
-                            rp.insert(k, v);
-                        }
-                        rp
-                    })
-                    .unwrap_or_else(crate::RouteParams::new);
+/// Params every match has: the router's current locale, if any
+pub(crate) fn locale_params(router: Option<&GlobalRouter>) -> crate::RouteParams {
+    let mut params = crate::RouteParams::new();
+    if let Some(locale) = router.and_then(GlobalRouter::locale) {
+        params.insert("locale".to_string(), locale.to_string());
+    }
+    params
+}
 
-                let builder = router
-                    .current_route()
-                    .and_then(|route| route.builder.clone());
+/// `params` with the data prepared by `route`'s `prepare` function
+///
+/// For [`Route::builder_cached`] routes, data prepared for the same params
+/// earlier in the frame is reused; the cache is cleared once the frame's
+/// effects are flushed.
+fn prepared_params(
+    route: &crate::Route,
+    builder: &crate::route::RouteBuilder,
+    params: crate::RouteParams,
+    cx: &mut App,
+) -> crate::RouteParams {
+    let Some(prepare) = route.prepare.as_ref() else {
+        return params;
+    };
+    if !route.builder_cached || !cx.has_global::<GlobalRouter>() {
+        let data = prepare(cx, &params);
+        return params.with_prepared(data);
+    }
 
-                (path, params, builder)
-            })
-            .unwrap_or_else(|| ("/".to_string(), crate::RouteParams::new(), None));
+    let key = params.cache_key();
+    if let Some(data) = cx.global::<GlobalRouter>().prepared_data(builder, &key) {
+        return params.with_prepared(data);
+    }
+    let data = prepare(cx, &params);
+    let first = cx.update_global::<GlobalRouter, _>(|router, _| {
+        router.store_prepared(builder, key, std::sync::Arc::clone(&data))
+    });
+    if first {
+        cx.defer(|cx| {
+            if cx.has_global::<GlobalRouter>() {
+                cx.update_global::<GlobalRouter, _>(|router, _| router.clear_prepared());
+            }
+        });
+    }
+    params.with_prepared(data)
+}
 
-        // Check if path actually changed (not just first render)
-        let path_changed = router_path != prev_path;
+/// Loader output and mode for `route` at `path`
+///
+/// Loaders belong to the deepest route matching a path, so parents of that
+/// route get neither.
+fn loader_state(
+    cx: &App,
+    route: &RouteRef,
+    path: &str,
+) -> (Option<DataState<LoaderData>>, Option<LoaderMode>) {
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
+        return (None, None);
+    };
+    let owns_loader = match_route_chain(router.state().routes(), path)
+        .and_then(|(chain, _)| chain.last().map(|leaf| std::sync::Arc::ptr_eq(leaf, route)))
+        .unwrap_or(true);
+    if !owns_loader {
+        return (None, None);
+    }
+    (router.route_data(path).cloned(), router.loader_mode(path))
+}
 
-        // Update state if path changed
-        #[cfg_attr(not(feature = "transition"), allow(unused_variables))]
-        let animation_counter = if path_changed {
-            #[cfg_attr(not(feature = "transition"), allow(unused_variables))]
-            let is_initial = prev_path.is_empty();
+/// Render `error` with the nearest error boundary in `chain`
+///
+/// Searches from the last route up; without a boundary the error goes to
+/// the router's [`DefaultPages`].
+fn render_route_error(
+    chain: &[&RouteRef],
+    error: &RouteError,
+    path: &str,
+    cx: &mut App,
+    pages: &DefaultPages,
+) -> AnyElement {
+    let boundary = chain
+        .iter()
+        .rev()
+        .find_map(|route| route.error_boundary.clone());
+    match (boundary, error) {
+        (Some(boundary), _) => boundary(error, RetryHandle::new(path), cx),
+        (None, RouteError::NotFound { .. }) => {
+            handled_not_found(path, cx).unwrap_or_else(|| pages.render_not_found())
+        }
+        (None, _) => pages.render_error(&error.to_string()),
+    }
+}
 
-            #[cfg(feature = "transition")]
-            let new_counter = if is_initial {
-                debug_log!("Initial route: '{}', no animation", router_path);
-                animation_counter
-            } else {
-                let counter = animation_counter.wrapping_add(1);
-                debug_log!(
-                    "Route changed: '{}' -> '{}', transition={:?}, animation_counter={}",
-                    prev_path,
-                    router_path,
-                    route_transition,
-                    counter
-                );
-                counter
+/// The router's not found handler's page for `path`, if it has one
+///
+/// [`ErrorHandlers::on_not_found`](crate::ErrorHandlers::on_not_found) takes
+/// precedence over [`DefaultPages`], as it gets the path's
+/// [`NotFoundDetails`](crate::NotFoundDetails).
+fn handled_not_found(path: &str, cx: &mut App) -> Option<AnyElement> {
+    let handlers = cx.try_global::<GlobalRouter>()?.error_handlers().clone();
+    handlers.render_not_found(cx, path)
+}
+
+/// Routes from the top level down to `target`, or `None` if it is not registered
+fn route_ancestry(routes: &[RouteRef], target: &RouteRef) -> Option<Vec<RouteRef>> {
+    fn find(routes: &[RouteRef], target: &RouteRef, depth: usize) -> Option<Vec<RouteRef>> {
+        if routes.is_empty() || crate::route::lookup_too_deep(depth, &target.config.path) {
+            return None;
+        }
+        for route in routes {
+            if std::sync::Arc::ptr_eq(route, target) {
+                return Some(vec![std::sync::Arc::clone(route)]);
+            }
+            let found = std::iter::once(route.get_children())
+                .chain(route.named_children.values().map(Vec::as_slice))
+                .find_map(|children| find(children, target, depth + 1));
+            if let Some(mut chain) = found {
+                chain.insert(0, std::sync::Arc::clone(route));
+                return Some(chain);
+            }
+        }
+        None
+    }
+
+    find(routes, target, 0)
+}
+
+impl Render for RouterOutlet {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        trace_log!("🔄 RouterOutlet::render() called");
+
+        // Inside render_resolved, show the next route of the resolved chain
+        if let Some(content) = resolved_outlet(window, cx, self.name.as_deref()) {
+            return div()
+                .id(self.element_id())
+                .size_full()
+                .child(content)
+                .into_any_element();
+        }
+
+        // Navigations apply their window hints to the windows with an outlet
+        crate::context::note_outlet_window(window, cx);
+        // Nested outlets built below use this outlet's placeholder
+        let outer_placeholder = set_outlet_placeholder(cx, self.placeholder.clone());
+        let pages = cx
+            .try_global::<GlobalRouter>()
+            .map(|router| std::sync::Arc::clone(router.default_pages()))
+            .unwrap_or_default();
+
+        // Use keyed state to persist animation counter and content across renders
+        let element_id = self.element_id();
+
+        // The initial path is still being resolved
+        if cx
+            .try_global::<GlobalRouter>()
+            .is_some_and(GlobalRouter::resolving_initial)
+        {
+            set_outlet_placeholder(cx, outer_placeholder);
+            return div()
+                .id(element_id)
+                .size_full()
+                .child(pages.render_loading())
+                .into_any_element();
+        }
+        // No router (yet, or any more): show the placeholder and look again
+        // next frame instead of remembering what this outlet drew
+        let Some(generation) = cx
+            .try_global::<GlobalRouter>()
+            .map(GlobalRouter::generation)
+        else {
+            window.request_animation_frame();
+            let content = match &self.placeholder {
+                Some(placeholder) => placeholder(),
+                None => pages.render_empty_outlet("No router is initialized; call init_router"),
+            };
+            return div()
+                .id(element_id)
+                .size_full()
+                .child(content)
+                .into_any_element();
+        };
+        // See `RouterConfig::suppress_transitions_until_first_frame`
+        if cx
+            .try_global::<GlobalRouter>()
+            .is_some_and(|router| !router.has_presented_frame())
+        {
+            window.on_next_frame(|_, cx| {
+                cx.update_global::<GlobalRouter, _>(|router, _| router.frame_presented());
+            });
+        }
+        let state = window.use_keyed_state(element_id.clone(), cx, |_, _| OutletState {
+            generation,
+            ..OutletState::default()
+        });
+        // The router or its route tree was replaced: what was shown belongs
+        // to the old one, so resolve from scratch without a transition
+        if state.read(cx).generation != generation {
+            debug_log!("Router replaced, resolving the outlet from scratch");
+            state.update(cx, |s, _| {
+                s.shown = OutletSnapshot {
+                    animation_epoch: s.shown.animation_epoch,
+                    ..OutletSnapshot::default()
+                };
+                #[cfg(feature = "transition")]
+                {
+                    s.transition_started = None;
+                }
+                s.focus_pending = false;
+                s.generation = generation;
+            });
+        }
+
+        // Decide what to draw from the router's location and what was shown last
+        let plan = {
+            let shown = &state.read(cx).shown;
+            let router = cx.global::<GlobalRouter>();
+            let location = OutletLocation {
+                path: router.current_path_shared().clone(),
+                locale: router.state().locale(),
+                #[cfg(feature = "transition")]
+                transition: router.state().current_transition(),
             };
+            plan_outlet_render(router.state().routes(), &location, shown)
+        };
+        let router_path = plan.path.clone();
+
+        let span = diagnostics::render_outlet(self.name.as_deref(), &router_path);
+        span.record_with("child", || {
+            let router = cx.try_global::<GlobalRouter>()?;
+            Some(router.current_route()?.config.path.clone())
+        });
 
-            #[cfg(not(feature = "transition"))]
-            let new_counter = {
+        // A modal route is drawn above the last page route in history
+        let is_modal = plan.modal;
+        let base_layer = cx
+            .try_global::<crate::context::GlobalRouter>()
+            .and_then(|router| {
+                router
+                    .modal_base_path()
+                    .map(|path| OutletLayer::for_path(router, path))
+            });
+
+        match plan.change {
+            OutletChange::Unchanged => {
+                trace_log!("Route unchanged: '{}'", router_path);
+            }
+            OutletChange::Initial => {
+                debug_log!("Initial route: '{}', no animation", router_path);
+            }
+            OutletChange::ParentKept => {
                 debug_log!(
-                    "Route changed: '{}' -> '{}' (no transition)",
-                    prev_path,
+                    "Route changed: '{}' -> '{}' below the same parent, no animation",
+                    state.read(cx).shown.path,
                     router_path
                 );
-                animation_counter
-            };
+            }
+            OutletChange::Replaced => {
+                debug_log!(
+                    "Route changed: '{}' -> '{}', animation_counter={}",
+                    state.read(cx).shown.path,
+                    router_path,
+                    plan.animation_epoch
+                );
+            }
+        }
+
+        #[cfg(feature = "transition")]
+        let now = cx.background_executor().now();
 
-            // Update state and save previous route for exit animation
+        // Remember what is shown once the route changed
+        if plan.change != OutletChange::Unchanged {
             state.update(cx, |s, _| {
-                // When path changes, replace previous_route with current route data
-                // This way, the old previous_route (from a previous transition) is discarded
-                // and we only keep the immediately previous route for the current transition
-                if !is_initial {
-                    s.previous_route = Some(PreviousRoute {
-                        path: s.current_path.clone(),
-                        params: s.current_params.clone(),
-                        builder: s.current_builder.clone(),
-                    });
-                } else {
-                    // Initial navigation - no previous route
-                    s.previous_route = None;
-                }
-                // Update state with NEW route data
-                s.current_path = router_path.clone();
-                s.current_params = route_params.clone();
-                s.current_builder = builder_opt.clone();
                 #[cfg(feature = "transition")]
-                {
-                    s.current_transition = route_transition.clone();
+                if plan.animation_epoch != s.shown.animation_epoch {
+                    s.transition_started = Some(now);
                 }
-                s.animation_counter = new_counter;
+                s.focus_pending = plan.change != OutletChange::Initial;
+                s.shown = plan.snapshot();
             });
-
-            new_counter
-        } else {
-            trace_log!("Route unchanged: '{}'", router_path);
-            animation_counter
-        };
+        }
+        #[cfg_attr(not(feature = "transition"), allow(unused_variables))]
+        let animation_counter = plan.animation_epoch;
 
         #[cfg(feature = "transition")]
-        {
+        let (content, transition_running) = {
+            // Use the transition chosen when the path changed (e.g. none when closing a modal)
+            let route_transition = plan.transition.clone();
+
             // Determine animation duration based on transition type
             // Don't zero out duration on subsequent renders - let animation complete!
             let duration_ms = match &route_transition {
                 Transition::Fade { duration_ms, .. } => *duration_ms,
                 Transition::Slide { duration_ms, .. } => *duration_ms,
+                Transition::Scale { duration_ms, .. } => *duration_ms,
                 Transition::None => 0,
             };
 
@@ -288,15 +783,8 @@ impl Render for RouterOutlet {
                 duration_ms
             );
 
-            // Get previous route info for exit animation
-            // Show it if it exists and its path differs from current path
-            // (if paths are same, no transition is needed)
-            let previous_route = state
-                .read(cx)
-                .previous_route
-                .as_ref()
-                .filter(|prev| prev.path != router_path)
-                .cloned();
+            // Route exiting during the transition
+            let previous_route = plan.exiting.clone();
 
             debug_log!(
                 "Previous route exists: {}, path: {:?}",
@@ -304,153 +792,179 @@ impl Render for RouterOutlet {
                 previous_route.as_ref().map(|p| &p.path)
             );
 
-            // Build OLD and NEW content ONCE before match to avoid multiple builder() calls per render
-            let old_content_opt = previous_route.map(|prev| {
-                if let Some(builder) = prev.builder.as_ref() {
-                    builder(window, cx, &prev.params)
-                } else {
-                    not_found_page().into_any_element()
-                }
-            });
+            // Progress handed to builders, which are rebuilt every frame until it completes
+            let started = state.read(cx).transition_started;
+            let running = running_transition(window, cx, started, &route_transition);
 
-            let new_content = if let Some(builder) = builder_opt.as_ref() {
-                builder(window, cx, &route_params)
-            } else {
-                not_found_page().into_any_element()
-            };
+            // The exiting route is gone once the transition is done: drop it
+            // (and its params) instead of keeping it until the next navigation
+            if running.is_none() && state.read(cx).shown.exiting.is_some() {
+                state.update(cx, |s, _| s.shown.exiting = None);
+            }
+
+            // Build OLD and NEW content ONCE before match to avoid multiple builder() calls per render
+            // (without a running transition the old content is never shown, so skip it)
+            let old_content_opt = previous_route
+                .filter(|_| running.is_some())
+                .map(|mut prev| {
+                    let progress = TransitionProgress::exit(running.unwrap_or(1.0));
+                    prev.params = std::sync::Arc::new(
+                        prev.params
+                            .as_ref()
+                            .clone()
+                            .with_transition_progress(progress),
+                    );
+                    ExitingHeroes::new(prev.build(window, cx, &pages)).into_any_element()
+                });
+
+            let new_content = OutletLayer {
+                path: router_path.clone(),
+                params: std::sync::Arc::new(plan.params.as_ref().clone().with_transition_progress(
+                    running.map_or_else(TransitionProgress::default, TransitionProgress::enter),
+                )),
+                route: plan.route.clone(),
+            }
+            .build(window, cx, &pages);
 
             // Build container with both old (exiting) and new (entering) content
             // For SLIDE transitions, use a different approach
-            match &route_transition {
-                Transition::Slide { direction, .. } => {
+            let content = match &route_transition {
+                Transition::Slide {
+                    direction,
+                    parallax,
+                    dim_old,
+                    ..
+                } => {
                     // Create animated container that holds BOTH elements side-by-side
-                    let animation_id = SharedString::from(format!(
-                        "outlet_slide_{:?}_{}",
-                        self.name, animation_counter
-                    ));
-
-                    match direction {
-                        SlideDirection::Left | SlideDirection::Right => {
-                            // Horizontal slide: use absolute positioning for proper side-by-side layout
-                            let is_left = matches!(direction, SlideDirection::Left);
-
-                            div()
-                                .relative()
-                                .w_full()
-                                .h_full()
-                                .overflow_hidden()
-                                // Old content (exits to left for SlideLeft, or stays for SlideRight)
-                                .when_some(old_content_opt, |container, old| {
-                                    container.child(
-                                        div()
-                                            .absolute()
-                                            .w_full()
-                                            .h_full()
-                                            .child(old)
-                                            .left(relative(0.0)) // Starts at normal position
-                                            .with_animation(
-                                                animation_id.clone(),
-                                                Animation::new(Duration::from_millis(duration_ms)),
-                                                move |this, delta| {
-                                                    let progress = delta.clamp(0.0, 1.0);
-                                                    // Old content exits
-                                                    let offset = if is_left {
-                                                        -progress // SlideLeft: old goes left (-1.0)
-                                                    } else {
-                                                        progress // SlideRight: old goes right (+1.0)
-                                                    };
-                                                    this.left(relative(offset))
-                                                },
-                                            ),
-                                    )
-                                })
-                                // New content (enters from right for SlideLeft, from left for SlideRight)
-                                .child(
-                                    div()
-                                        .absolute()
-                                        .w_full()
-                                        .h_full()
-                                        .child(new_content)
-                                        .left(relative(if is_left { 1.0 } else { -1.0 })) // Starts off-screen
-                                        .with_animation(
-                                            animation_id.clone(),
-                                            Animation::new(Duration::from_millis(duration_ms)),
-                                            move |this, delta| {
-                                                let progress = delta.clamp(0.0, 1.0);
-                                                // New content enters
-                                                let start = if is_left { 1.0 } else { -1.0 };
-                                                let offset = start * (1.0 - progress);
-                                                this.left(relative(offset))
-                                            },
-                                        ),
-                                )
-                                .into_any_element()
-                        }
-                        SlideDirection::Up | SlideDirection::Down => {
-                            // Vertical slide: use absolute positioning for proper stacked layout
-                            let is_up = matches!(direction, SlideDirection::Up);
+                    let animation_id = animation_id("outlet_slide", animation_counter);
+                    let (direction, parallax, dim_old) = (*direction, *parallax, *dim_old);
+                    let offset =
+                        move |this: Div, offset: f32| offset_layer(this, direction, offset);
+                    let dimmed = dim_old > 0.0 && old_content_opt.is_some();
 
-                            div()
-                                .relative()
-                                .w_full()
-                                .h_full()
-                                .overflow_hidden()
-                                // Old content (exits up for SlideUp, or down for SlideDown)
-                                .when_some(old_content_opt, |container, old| {
-                                    container.child(
-                                        div()
-                                            .absolute()
-                                            .w_full()
-                                            .h_full()
-                                            .child(old)
-                                            .top(relative(0.0)) // Starts at normal position
-                                            .with_animation(
-                                                animation_id.clone(),
-                                                Animation::new(Duration::from_millis(duration_ms)),
-                                                move |this, delta| {
-                                                    let progress = delta.clamp(0.0, 1.0);
-                                                    // Old content exits
-                                                    let offset = if is_up {
-                                                        -progress // SlideUp: old goes up (-1.0)
-                                                    } else {
-                                                        progress // SlideDown: old goes down (+1.0)
-                                                    };
-                                                    this.top(relative(offset))
-                                                },
-                                            ),
-                                    )
-                                })
-                                // New content (enters from bottom for SlideUp, from top for SlideDown)
-                                .child(
-                                    div()
-                                        .absolute()
-                                        .w_full()
-                                        .h_full()
-                                        .child(new_content)
-                                        .top(relative(if is_up { 1.0 } else { -1.0 })) // Starts off-screen
-                                        .with_animation(
-                                            animation_id.clone(),
-                                            Animation::new(Duration::from_millis(duration_ms)),
-                                            move |this, delta| {
-                                                let progress = delta.clamp(0.0, 1.0);
-                                                // New content enters
-                                                let start = if is_up { 1.0 } else { -1.0 };
-                                                let offset = start * (1.0 - progress);
-                                                this.top(relative(offset))
-                                            },
-                                        ),
-                                )
-                                .into_any_element()
-                        }
-                    }
-                }
-                Transition::Fade { .. } => {
+                    // Use absolute positioning for proper side-by-side (or stacked) layout
                     div()
                         .relative()
                         .w_full()
                         .h_full()
                         .overflow_hidden()
-                        // Old content (fades out)
+                        // Old content (starts at its normal position and moves
+                        // `parallax` as far as the new content)
+                        .when_some(old_content_opt, |container, old| {
+                            container.child(
+                                offset(div().absolute().w_full().h_full().child(old), 0.0)
+                                    .with_animation(
+                                        animation_id.clone(),
+                                        Animation::new(Duration::from_millis(duration_ms)),
+                                        move |this, delta| {
+                                            let (exit, _) =
+                                                slide_offsets(direction, delta, parallax);
+                                            offset(this, exit)
+                                        },
+                                    ),
+                            )
+                        })
+                        // Scrim darkening the old content as the new content covers it
+                        .when(dimmed, |container| {
+                            container.child(
+                                div()
+                                    .absolute()
+                                    .w_full()
+                                    .h_full()
+                                    .bg(gpui::black())
+                                    .opacity(0.0)
+                                    .with_animation(
+                                        animation_id.clone(),
+                                        Animation::new(Duration::from_millis(duration_ms)),
+                                        move |this, delta| {
+                                            this.opacity(slide_scrim_opacity(delta, dim_old))
+                                        },
+                                    ),
+                            )
+                        })
+                        // New content (starts off-screen, on the side the
+                        // direction points away from)
+                        .child(
+                            offset(
+                                div().absolute().w_full().h_full().child(new_content),
+                                slide_offsets(direction, 0.0, parallax).1,
+                            )
+                            .with_animation(
+                                animation_id.clone(),
+                                Animation::new(Duration::from_millis(duration_ms)),
+                                move |this, delta| {
+                                    let (_, enter) = slide_offsets(direction, delta, parallax);
+                                    offset(this, enter)
+                                },
+                            ),
+                        )
+                        .into_any_element()
+                }
+                Transition::Scale { from, to, .. } => {
+                    let (from, to) = (*from, *to);
+
+                    div()
+                        .relative()
+                        .w_full()
+                        .h_full()
+                        .overflow_hidden()
+                        // Old content (fades out in place)
+                        .when_some(old_content_opt, |container, old| {
+                            container.child(
+                                div()
+                                    .absolute()
+                                    .w_full()
+                                    .h_full()
+                                    .child(old)
+                                    .with_animation(
+                                        animation_id("outlet_scale_exit", animation_counter),
+                                        Animation::new(Duration::from_millis(duration_ms)),
+                                        |this, delta| {
+                                            let progress = delta.clamp(0.0, 1.0);
+                                            this.opacity(1.0 - progress)
+                                        },
+                                    ),
+                            )
+                        })
+                        // New content (scales from `from` to `to` around the center while fading in)
+                        .child(
+                            div()
+                                .absolute()
+                                .w_full()
+                                .h_full()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .child(
+                                    div()
+                                        .flex_none()
+                                        .w(relative(from))
+                                        .h(relative(from))
+                                        .overflow_hidden()
+                                        .opacity(0.0)
+                                        .child(new_content)
+                                        .with_animation(
+                                            animation_id("outlet_scale_enter", animation_counter),
+                                            Animation::new(Duration::from_millis(duration_ms)),
+                                            move |this, delta| {
+                                                let progress = delta.clamp(0.0, 1.0);
+                                                let scale = interpolate_scale(from, to, progress);
+                                                this.w(relative(scale))
+                                                    .h(relative(scale))
+                                                    .opacity(progress)
+                                            },
+                                        ),
+                                ),
+                        )
+                        .into_any_element()
+                }
+                Transition::Fade { .. } => {
+                    div()
+                        .relative()
+                        .w_full()
+                        .h_full()
+                        .overflow_hidden()
+                        // Old content (fades out)
                         .when_some(old_content_opt, |container, old| {
                             container.child(
                                 div()
@@ -459,10 +973,7 @@ impl Render for RouterOutlet {
                                     .h_full()
                                     .child(old)
                                     .with_animation(
-                                        SharedString::from(format!(
-                                            "outlet_fade_exit_{:?}_{}",
-                                            self.name, animation_counter
-                                        )),
+                                        animation_id("outlet_fade_exit", animation_counter),
                                         Animation::new(Duration::from_millis(duration_ms)),
                                         |this, delta| {
                                             let progress = delta.clamp(0.0, 1.0);
@@ -480,10 +991,7 @@ impl Render for RouterOutlet {
                                 .child(new_content)
                                 .opacity(0.0)
                                 .with_animation(
-                                    SharedString::from(format!(
-                                        "outlet_fade_enter_{:?}_{}",
-                                        self.name, animation_counter
-                                    )),
+                                    animation_id("outlet_fade_enter", animation_counter),
                                     Animation::new(Duration::from_millis(duration_ms)),
                                     |this, delta| {
                                         let progress = delta.clamp(0.0, 1.0);
@@ -493,8 +1001,8 @@ impl Render for RouterOutlet {
                         )
                         .into_any_element()
                 }
-                _ => {
-                    // No transition or unsupported - just show new content
+                Transition::None => {
+                    // No transition - just show new content
                     div()
                         .relative()
                         .w_full()
@@ -502,23 +1010,109 @@ impl Render for RouterOutlet {
                         .child(new_content)
                         .into_any_element()
                 }
-            }
-        }
+            };
+            // Heroes on both pages fly between them above the content
+            let content = match (running, started) {
+                (Some(_), Some(started)) => div()
+                    .relative()
+                    .w_full()
+                    .h_full()
+                    .child(content)
+                    .child(hero_overlay(started, route_transition.duration()))
+                    .into_any_element(),
+                _ => content,
+            };
+            (content, running.is_some())
+        };
 
+        // No animation support - just build content directly
         #[cfg(not(feature = "transition"))]
-        {
-            // No animation support - just build content directly
-            let animation_id = SharedString::from(format!("outlet_{:?}", self.name));
-            build_animated_route_content(
-                cx,
-                window,
-                builder_opt.as_ref(),
-                &route_params,
-                animation_id,
-                &(),
-                0,
-            )
+        let content = OutletLayer {
+            path: router_path.clone(),
+            params: std::sync::Arc::clone(&plan.params),
+            route: plan.route.clone(),
+        }
+        .build(window, cx, &pages);
+        #[cfg(not(feature = "transition"))]
+        let transition_running = false;
+
+        let content = if is_modal {
+            // Keep building the page underneath so it stays mounted
+            let base_content = base_layer.map(|base| base.build(window, cx, &pages));
+            let mut layers = div().relative().w_full().h_full();
+            if let Some(base) = base_content {
+                layers = layers.child(div().absolute().w_full().h_full().child(base));
+            }
+            layers
+                .child(
+                    div()
+                        .absolute()
+                        .w_full()
+                        .h_full()
+                        .bg(self.modal_backdrop)
+                        .child(content),
+                )
+                .into_any_element()
+        } else {
+            content
+        };
+
+        // A blocking loader is running: keep the current page and show progress
+        let pending = cx
+            .try_global::<GlobalRouter>()
+            .is_some_and(|router| router.pending_navigation().is_some());
+        let content = if pending {
+            div()
+                .relative()
+                .w_full()
+                .h_full()
+                .child(content)
+                .child(
+                    div()
+                        .absolute()
+                        .w_full()
+                        .h_full()
+                        .child(pages.render_loading()),
+                )
+                .into_any_element()
+        } else {
+            content
+        };
+
+        // Move focus to the new content once its transition has completed
+        let focus_policy = cx
+            .try_global::<GlobalRouter>()
+            .map(|router| router.config().focus_policy)
+            .unwrap_or_default();
+        set_outlet_placeholder(cx, outer_placeholder);
+        if focus_policy == FocusPolicy::None {
+            return div()
+                .id(element_id)
+                .size_full()
+                .child(content)
+                .into_any_element();
+        }
+        let (focus_handle, focus_now) = state.update(cx, |s, cx| {
+            let handle = s
+                .focus_handle
+                .get_or_insert_with(|| cx.focus_handle())
+                .clone();
+            let focus_now = s.focus_pending && !transition_running;
+            if focus_now {
+                s.focus_pending = false;
+            }
+            (handle, focus_now)
+        });
+        if focus_now {
+            focus_route_content(window, cx, &router_path, &focus_handle);
         }
+
+        div()
+            .id(element_id)
+            .size_full()
+            .track_focus(&focus_handle)
+            .child(content)
+            .into_any_element()
     }
 }
 
@@ -587,50 +1181,86 @@ pub fn router_outlet_named(
 /// This is the functional approach that actually works with route builders.
 /// It returns a function that will be called with App context to render child routes.
 ///
+/// Outside a route builder with no layout above it, an unnamed outlet renders
+/// the top-level route itself, leaf or layout, with its enter transition. A
+/// view can then render a flat route table through a single outlet.
+///
 /// # Example
 ///
 /// ```ignore
 /// use gpui_navigator::{render_router_outlet, RouteParams};
 /// use gpui::*;
 ///
-/// fn layout(cx: &mut App, _params: &RouteParams) -> AnyElement {
+/// fn layout(window: &mut Window, cx: &mut App, _params: &RouteParams) -> AnyElement {
 ///     div()
 ///         .child("Header")
-///         .child(render_router_outlet(cx, None)) // Pass cx explicitly
+///         .child(render_router_outlet(window, cx, None))
 ///         .into_any_element()
 /// }
 /// ```
 pub fn render_router_outlet(window: &mut Window, cx: &mut App, name: Option<&str>) -> AnyElement {
     trace_log!("render_router_outlet called with name: {:?}", name);
 
-    // Access GlobalRouter
-    let router = cx.try_global::<GlobalRouter>();
+    if let Some(content) = resolved_outlet(window, cx, name) {
+        return content;
+    }
 
-    let Some(router) = router else {
+    // Access GlobalRouter
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
         error_log!("No global router found - call init_router() first");
         return div()
             .child("RouterOutlet: No global router found. Call init_router() first.")
             .into_any_element();
     };
 
-    let current_path = router.current_path();
+    let current_path = router.current_path_shared().clone();
+    let current_path = current_path.as_ref();
     trace_log!("Current path: '{}'", current_path);
-
-    // Find the parent route that has children and matches the current path
-    // This searches through the route tree to find the correct parent
-    let parent_route = find_parent_route_for_path(router.state().routes(), current_path);
+    let span = diagnostics::render_outlet(name, current_path);
+
+    // The outlet shows the children of the route whose builder renders it.
+    // Outlets rendered outside a builder (e.g. in a component's view) search
+    // the route tree (or the cache) for the parent matching the current path
+    let parent_route = match router.building_route.clone() {
+        Some(route) => Some(route),
+        None => {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.find_parent_route(current_path))
+        }
+    };
 
     let Some(parent_route) = parent_route else {
+        // No child matched: report it to the parents that should have had one
+        let chain = cx.try_global::<GlobalRouter>().and_then(|router| {
+            missing_child_parent(router.state().routes(), &path_segments(current_path), "")
+        });
+        let pages = cx
+            .try_global::<GlobalRouter>()
+            .map(|router| std::sync::Arc::clone(router.default_pages()))
+            .unwrap_or_default();
+        if let Some(chain) = chain {
+            let chain: Vec<_> = chain.iter().collect();
+            let error = RouteError::NotFound {
+                path: current_path.to_string(),
+            };
+            return render_route_error(&chain, &error, current_path, cx, &pages);
+        }
+        // No route nests this outlet: it is at the top of the chain
+        if name.is_none() {
+            return render_top_level_route(window, cx, current_path, &pages);
+        }
         warn_log!(
-            "No parent route with children found for path '{}'",
+            "No parent route has a '{}' outlet for path '{}'",
+            name.unwrap_or_default(),
             current_path
         );
-        return div()
-            .child(format!(
-                "RouterOutlet: No parent route with children found for path '{}'",
+        return empty_outlet(
+            cx,
+            &format!(
+                "RouterOutlet: No parent route has a '{}' outlet for path '{}'",
+                name.unwrap_or_default(),
                 current_path
-            ))
-            .into_any_element();
+            ),
+        );
     };
 
     trace_log!(
@@ -639,45 +1269,420 @@ pub fn render_router_outlet(window: &mut Window, cx: &mut App, name: Option<&str
         parent_route.get_children().len()
     );
 
-    // Check if parent route has children
-    if parent_route.get_children().is_empty() {
-        return div()
-            .child(format!(
+    // Check if parent route has children for this outlet
+    let children = match name {
+        Some(name) => parent_route.get_named_children(name).unwrap_or_default(),
+        None => parent_route.get_children(),
+    };
+    if children.is_empty() {
+        return empty_outlet(
+            cx,
+            &format!(
                 "RouterOutlet: Route '{}' has no child routes",
                 parent_route.config.path
-            ))
-            .into_any_element();
+            ),
+        );
     }
 
-    // Resolve which child route should be rendered.
-    // We pass the current parent params; the resolver returns (route, merged_params).
-    let route_params = crate::RouteParams::new();
+    // The parent and its ancestors, whose error boundaries cover the child
+    let ancestry = cx
+        .try_global::<GlobalRouter>()
+        .and_then(|router| route_ancestry(router.state().routes(), &parent_route))
+        .unwrap_or_else(|| vec![std::sync::Arc::clone(&parent_route)]);
 
-    let resolved = resolve_child_route(parent_route, current_path, &route_params, name);
+    // Resolve which child route should be rendered.
+    // The resolver matches the parent's full pattern against the path, adding
+    // the params of every level above (e.g. `:orgId` and `:teamId`) and the
+    // child's own to the locale; it returns (route, merged_params).
+    let parent_path = ancestry.iter().fold(String::new(), |parent, route| {
+        if parent.is_empty() {
+            route.config.path.clone()
+        } else {
+            build_child_path(&parent, &route.config.path).into_owned()
+        }
+    });
+    let route_params = locale_params(cx.try_global::<GlobalRouter>());
+
+    // A named outlet given its own path resolves its child there
+    let outlet_path = name.and_then(|name| {
+        let path = cx.try_global::<GlobalRouter>()?.outlet_path(name)?;
+        Some(build_child_path(&parent_path, path).into_owned())
+    });
+    let current_path = outlet_path.as_deref().unwrap_or(current_path);
+
+    let resolved = cx.update_global::<GlobalRouter, _>(|router, _| {
+        router.resolve_outlet_child(
+            &parent_route,
+            &parent_path,
+            current_path,
+            &route_params,
+            name,
+        )
+    });
+    let pages = cx
+        .try_global::<GlobalRouter>()
+        .map(|router| std::sync::Arc::clone(router.default_pages()))
+        .unwrap_or_default();
 
     let Some((child_route, child_params)) = resolved else {
         warn_log!("No child route matched for path '{}'", current_path);
-        return div()
-            .child(format!(
-                "RouterOutlet: No child route matched for path '{}'",
-                current_path
-            ))
-            .into_any_element();
+        // On the parent's own path there is just no index route to show
+        let on_parent = cx.try_global::<GlobalRouter>().is_some_and(|router| {
+            match_route_chain(router.state().routes(), current_path).is_some_and(|(chain, _)| {
+                chain
+                    .last()
+                    .is_some_and(|route| std::sync::Arc::ptr_eq(route, &parent_route))
+            })
+        });
+        if on_parent {
+            return empty_outlet(
+                cx,
+                &format!(
+                    "RouterOutlet: No child route matched for path '{}'",
+                    current_path
+                ),
+            );
+        }
+        let chain: Vec<_> = ancestry.iter().collect();
+        let error = RouteError::NotFound {
+            path: current_path.to_string(),
+        };
+        return render_route_error(&chain, &error, current_path, cx, &pages);
     };
 
     trace_log!("Matched child route: '{}'", child_route.config.path);
+    span.record("child", &child_route.config.path);
 
     // Render the child route
-    if let Some(builder) = &child_route.builder {
-        // Call the builder with window, cx and parameters
-        builder(window, cx, &child_params)
-    } else {
-        div()
-            .child(format!(
+    if child_route.builder.is_none() {
+        return empty_outlet(
+            cx,
+            &format!(
                 "RouterOutlet: Child route '{}' has no builder",
                 child_route.config.path
+            ),
+        );
+    }
+    #[cfg(feature = "transition")]
+    let (counter, child_params, transition) = {
+        let (counter, started) =
+            child_animation_counter(window, cx, Some(&parent_route), &child_route, name);
+        let transition = cx.try_global::<GlobalRouter>().map_or_else(
+            || child_route.transition.active().clone(),
+            |router| router.state().resolve_transition(&child_route.transition),
+        );
+        let progress = running_transition(window, cx, started, &transition)
+            .map_or_else(TransitionProgress::default, TransitionProgress::enter);
+        (
+            counter,
+            child_params.with_transition_progress(progress),
+            transition,
+        )
+    };
+
+    // Call the builder with window, cx and parameters
+    let chain: Vec<_> = ancestry.iter().chain([&child_route]).collect();
+    let content = build_route(&chain, current_path, window, cx, &child_params, &pages);
+
+    #[cfg(feature = "transition")]
+    let content = {
+        animate_child_enter(
+            content,
+            &transition,
+            ElementId::NamedInteger(
+                child_outlet_key(Some(&parent_route), name),
+                animation_slot(counter),
+            ),
+            counter,
+        )
+    };
+
+    content
+}
+
+/// Render the top-level route matching `path`, the leaf case of an outlet
+///
+/// Routes without children render their own builder here, so a single outlet
+/// at the root serves flat route tables as well as layouts; a layout's nested
+/// outlets then show its children. Enter transitions run like a child's.
+fn render_top_level_route(
+    window: &mut Window,
+    cx: &mut App,
+    path: &str,
+    pages: &DefaultPages,
+) -> AnyElement {
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
+        return not_found_page().into_any_element();
+    };
+    #[cfg_attr(not(feature = "transition"), allow(unused_mut))]
+    let mut layer = OutletLayer::for_path(router, path);
+    let Some(route) = layer.route.clone() else {
+        return layer.build(window, cx, pages);
+    };
+
+    #[cfg(feature = "transition")]
+    let (counter, transition) = {
+        let (counter, started) = child_animation_counter(window, cx, None, &route, None);
+        let transition = cx.try_global::<GlobalRouter>().map_or_else(
+            || route.transition.active().clone(),
+            |router| router.state().resolve_transition(&route.transition),
+        );
+        let progress = running_transition(window, cx, started, &transition)
+            .map_or_else(TransitionProgress::default, TransitionProgress::enter);
+        layer.params = std::sync::Arc::new(
+            layer
+                .params
+                .as_ref()
+                .clone()
+                .with_transition_progress(progress),
+        );
+        (counter, transition)
+    };
+    #[cfg(not(feature = "transition"))]
+    let _ = route;
+
+    let content = layer.build(window, cx, pages);
+    #[cfg(feature = "transition")]
+    let content = animate_child_enter(
+        content,
+        &transition,
+        ElementId::NamedInteger(child_outlet_key(None, None), animation_slot(counter)),
+        counter,
+    );
+    content
+}
+
+/// Render what a nested outlet shows when it has no child to render
+///
+/// The placeholder of the enclosing [`RouterOutlet`] wins over the router's
+/// [`DefaultPages`]; `message` explains the situation when outlet
+/// diagnostics are on.
+fn empty_outlet(cx: &App, message: &str) -> AnyElement {
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
+        return div().into_any_element();
+    };
+    match &router.outlet_placeholder {
+        Some(placeholder) => placeholder(),
+        None => router.default_pages().render_empty_outlet(message),
+    }
+}
+
+/// Make nested outlets render the children of `route`, returning the one it replaces
+fn set_building_route(cx: &mut App, route: Option<RouteRef>) -> Option<RouteRef> {
+    if !cx.has_global::<GlobalRouter>() {
+        return None;
+    }
+    cx.update_global::<GlobalRouter, _>(|router, _| {
+        std::mem::replace(&mut router.building_route, route)
+    })
+}
+
+/// Make nested outlets use `placeholder`, returning the one it replaces
+fn set_outlet_placeholder(
+    cx: &mut App,
+    placeholder: Option<OutletPlaceholder>,
+) -> Option<OutletPlaceholder> {
+    if !cx.has_global::<GlobalRouter>() {
+        return None;
+    }
+    cx.update_global::<GlobalRouter, _>(|router, _| {
+        std::mem::replace(&mut router.outlet_placeholder, placeholder)
+    })
+}
+
+/// State of a nested outlet, tracking when its child changes
+#[cfg(feature = "transition")]
+#[derive(Default)]
+struct ChildOutletState {
+    path: SharedString,
+    animation_counter: u32,
+    /// When the child last changed
+    transition_started: Option<Instant>,
+}
+
+/// Bump the nested outlet's animation counter if the navigation changed its child
+///
+/// Navigations below the child (or that only touch the query) keep the counter.
+/// Returns the counter and when the child last changed.
+/// Window state key of a nested outlet, or of a top-level one without a parent
+#[cfg(feature = "transition")]
+fn child_outlet_key(
+    parent_route: Option<&crate::route::RouteRef>,
+    name: Option<&str>,
+) -> SharedString {
+    let parent = parent_route.map_or(std::ptr::null(), std::sync::Arc::as_ptr);
+    SharedString::from(format!("child_outlet_{:p}_{:?}", parent, name))
+}
+
+#[cfg(feature = "transition")]
+fn child_animation_counter(
+    window: &mut Window,
+    cx: &mut App,
+    parent_route: Option<&crate::route::RouteRef>,
+    child_route: &crate::route::RouteRef,
+    name: Option<&str>,
+) -> (u32, Option<Instant>) {
+    let key = child_outlet_key(parent_route, name);
+    let state = window.use_keyed_state(key, cx, |_, _| ChildOutletState::default());
+
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
+        return (0, None);
+    };
+    let current_path = router.current_path_shared().clone();
+    let (prev_path, counter, started) = {
+        let state = state.read(cx);
+        (
+            state.path.clone(),
+            state.animation_counter,
+            state.transition_started,
+        )
+    };
+    if prev_path == current_path {
+        return (counter, started);
+    }
+
+    // Depth of the child in the matched chain; shallower shared levels mean it changed
+    let child_depth = crate::route::match_route_chain(router.state().routes(), &current_path)
+        .and_then(|(chain, _)| {
+            chain
+                .iter()
+                .position(|route| std::sync::Arc::ptr_eq(route, child_route))
+        });
+    let child_changed = !prev_path.is_empty()
+        && child_depth
+            .is_some_and(|depth| router.shared_route_depth(&prev_path, &current_path) <= depth);
+    let (counter, started) = if child_changed {
+        (
+            counter.wrapping_add(1),
+            Some(cx.background_executor().now()),
+        )
+    } else {
+        (counter, started)
+    };
+
+    state.update(cx, |state, _| {
+        state.path = current_path;
+        state.animation_counter = counter;
+        state.transition_started = started;
+    });
+    (counter, started)
+}
+
+/// Number of animation ids an outlet cycles through
+///
+/// GPUI keeps the state of an animation (when it started) under its element
+/// id, so each navigation needs an id the previous one did not use for the
+/// transition to restart. Rather than a new id per navigation, outlets
+/// number their ids with the animation counter modulo this pool: at most
+/// the exiting and the entering route animate at once, and GPUI drops the
+/// state of ids not rendered in a frame, so the ids and states alive stay
+/// bounded however many navigations a session makes. Keep new animations
+/// on [`animation_id`] rather than formatting the counter into a name.
+#[cfg(feature = "transition")]
+const ANIMATION_ID_SLOTS: u32 = 4;
+
+/// Slot of the pool of animation ids used for `counter`
+#[cfg(feature = "transition")]
+pub(crate) fn animation_slot(counter: u32) -> u64 {
+    u64::from(counter % ANIMATION_ID_SLOTS)
+}
+
+/// Id of the animation `name` for `counter`, see [`ANIMATION_ID_SLOTS`]
+#[cfg(feature = "transition")]
+fn animation_id(name: &'static str, counter: u32) -> ElementId {
+    let id = ElementId::NamedInteger(SharedString::new_static(name), animation_slot(counter));
+    #[cfg(test)]
+    tests::ANIMATION_IDS.with(|ids| ids.borrow_mut().insert(id.clone()));
+    id
+}
+
+/// Progress of a transition started at `started`, or `None` once it is done
+///
+/// Requests another frame while the transition runs, so route builders are
+/// called with each step.
+#[cfg(feature = "transition")]
+pub(crate) fn running_transition(
+    window: &mut Window,
+    cx: &App,
+    started: Option<Instant>,
+    transition: &Transition,
+) -> Option<f32> {
+    let duration = transition.duration();
+    let elapsed = cx
+        .background_executor()
+        .now()
+        .saturating_duration_since(started?);
+    if elapsed >= duration {
+        return None;
+    }
+    window.request_animation_frame();
+    Some(elapsed.as_secs_f32() / duration.as_secs_f32())
+}
+
+/// Play `transition` on content entering a nested outlet
+///
+/// Only the entering child is animated; the parent layout around the outlet
+/// stays in place. Nothing is animated before the first child change.
+#[cfg(feature = "transition")]
+pub(crate) fn animate_child_enter(
+    content: AnyElement,
+    transition: &Transition,
+    animation_id: ElementId,
+    animation_counter: u32,
+) -> AnyElement {
+    if animation_counter == 0 {
+        return content;
+    }
+    let animation = Animation::new(transition.duration());
+
+    match *transition {
+        Transition::None => content,
+        Transition::Fade { .. } => div()
+            .size_full()
+            .opacity(0.0)
+            .child(content)
+            .with_animation(animation_id, animation, |this, delta| {
+                this.opacity(delta.clamp(0.0, 1.0))
+            })
+            .into_any_element(),
+        Transition::Slide { direction, .. } => div()
+            .relative()
+            .size_full()
+            .overflow_hidden()
+            .child(div().absolute().size_full().child(content).with_animation(
+                animation_id,
+                animation,
+                move |this, delta| {
+                    let offset = 1.0 - delta.clamp(0.0, 1.0);
+                    match direction {
+                        SlideDirection::Left | SlideDirection::Auto => this.left(relative(offset)),
+                        SlideDirection::Right => this.left(relative(-offset)),
+                        SlideDirection::Up => this.top(relative(offset)),
+                        SlideDirection::Down => this.top(relative(-offset)),
+                    }
+                },
             ))
-            .into_any_element()
+            .into_any_element(),
+        Transition::Scale { from, to, .. } => div()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .flex_none()
+                    .w(relative(from))
+                    .h(relative(from))
+                    .overflow_hidden()
+                    .opacity(0.0)
+                    .child(content)
+                    .with_animation(animation_id, animation, move |this, delta| {
+                        let progress = delta.clamp(0.0, 1.0);
+                        let scale = interpolate_scale(from, to, progress);
+                        this.w(relative(scale)).h(relative(scale)).opacity(progress)
+                    }),
+            )
+            .into_any_element(),
     }
 }
 
@@ -712,94 +1717,335 @@ pub fn render_router_outlet(window: &mut Window, cx: &mut App, name: Option<&str
 /// For path "/dashboard/analytics":
 ///   Returns: /dashboard route (has children)
 /// ```
+#[cfg(test)]
 fn find_parent_route_for_path<'a>(
     routes: &'a [std::sync::Arc<crate::route::Route>],
     current_path: &str,
 ) -> Option<&'a std::sync::Arc<crate::route::Route>> {
-    find_parent_route_internal(routes, current_path, "")
+    find_parent_route_with_path(routes, current_path).map(|(route, _)| route)
 }
 
-fn find_parent_route_internal<'a>(
+/// Same as `find_parent_route_for_path`, also returning the parent's full path
+/// (e.g. `/dashboard/settings`) so the result can be cached by [`crate::RouteId`].
+pub(crate) fn find_parent_route_with_path<'a>(
     routes: &'a [std::sync::Arc<crate::route::Route>],
     current_path: &str,
-    accumulated_path: &str,
+) -> Option<(&'a std::sync::Arc<crate::route::Route>, String)> {
+    find_parent_route_internal(routes, &path_segments(current_path), "", 0)
+        .map(|(route, full_path)| (route, format!("/{}", full_path)))
+}
+
+/// Look up a route by the full path returned from `find_parent_route_with_path`
+#[cfg(feature = "cache")]
+pub(crate) fn find_route_by_full_path<'a>(
+    routes: &'a [std::sync::Arc<crate::route::Route>],
+    full_path: &str,
 ) -> Option<&'a std::sync::Arc<crate::route::Route>> {
-    let current_normalized = current_path.trim_start_matches('/').trim_end_matches('/');
+    fn find<'a>(
+        routes: &'a [std::sync::Arc<crate::route::Route>],
+        target: &str,
+        accumulated_path: &str,
+        depth: usize,
+    ) -> Option<&'a std::sync::Arc<crate::route::Route>> {
+        if routes.is_empty() || crate::route::lookup_too_deep(depth, accumulated_path) {
+            return None;
+        }
+        for route in routes {
+            let full_route_path = join_route_path(accumulated_path, &route.config.path);
+            // Pathless layouts share their parent's full path
+            if full_route_path == target && !route.pathless {
+                return Some(route);
+            }
+            if let Some(found) = find(route.get_children(), target, &full_route_path, depth + 1) {
+                return Some(found);
+            }
+        }
+        None
+    }
 
+    find(routes, full_path.trim_matches('/'), "", 0)
+}
+
+/// Join a route's path onto its parent's accumulated path (no leading slash)
+///
+/// Absolute child paths replace the accumulated path, see [`build_child_path`].
+pub(crate) fn join_route_path(accumulated_path: &str, route_path: &str) -> String {
+    build_child_path(&format!("/{}", accumulated_path), route_path)
+        .trim_start_matches('/')
+        .to_string()
+}
+
+/// Depth-first search for the parent route whose outlet renders `current`
+///
+/// Containment is checked on whole segments (`/app` never contains
+/// `/application/settings`), with `:param` segments matching any segment.
+/// `routes` are `depth` levels below the top.
+fn find_parent_route_internal<'a>(
+    routes: &'a [std::sync::Arc<crate::route::Route>],
+    current: &[&str],
+    accumulated_path: &str,
+    depth: usize,
+) -> Option<(&'a std::sync::Arc<crate::route::Route>, String)> {
+    if routes.is_empty() || crate::route::lookup_too_deep(depth, accumulated_path) {
+        return None;
+    }
     for route in routes {
         // Early exit: skip routes without children (can't be parent routes)
-        if route.get_children().is_empty() {
+        if route.get_children().is_empty() && route.named_children.is_empty() {
             continue;
         }
 
-        let route_segment = route
-            .config
-            .path
-            .trim_start_matches('/')
-            .trim_end_matches('/');
-
         // Build full path for this route
-        let full_route_path = if accumulated_path.is_empty() {
-            if route_segment.is_empty() || route_segment == "/" {
-                String::new()
-            } else {
-                route_segment.to_string()
-            }
-        } else if route_segment.is_empty() || route_segment == "/" {
-            accumulated_path.to_string()
-        } else {
-            format!("{}/{}", accumulated_path, route_segment)
-        };
+        let full_route_path = join_route_path(accumulated_path, &route.config.path);
+        let route_segments = path_segments(&full_route_path);
 
         // Check if current path is under this route's subtree
-        let is_under = if full_route_path.is_empty() {
-            !current_normalized.is_empty()
+        let is_under = if route_segments.is_empty() {
+            !current.is_empty()
         } else {
-            current_normalized.starts_with(&full_route_path)
-                && (current_normalized.len() == full_route_path.len()
-                    || current_normalized[full_route_path.len()..].starts_with('/'))
+            segments_match_prefix(&route_segments, current)
         };
+        if !is_under {
+            continue;
+        }
 
-        if is_under {
-            // Depth-first: check children first for a deeper matching parent
-            if let Some(deeper) =
-                find_parent_route_internal(route.get_children(), current_path, &full_route_path)
-            {
-                return Some(deeper);
-            }
+        // Depth-first: check children first for a deeper matching parent
+        if let Some(deeper) =
+            find_parent_route_internal(route.get_children(), current, &full_route_path, depth + 1)
+        {
+            return Some(deeper);
+        }
 
-            // No deeper parent found - check if any direct child matches or contains the current path
-            // This route is the parent if current path matches or is under one of its children
-            for child in route.get_children() {
-                let child_segment = child
-                    .config
-                    .path
-                    .trim_start_matches('/')
-                    .trim_end_matches('/');
-                let child_full_path = if full_route_path.is_empty() {
-                    child_segment.to_string()
-                } else {
-                    format!("{}/{}", full_route_path, child_segment)
-                };
+        // No deeper parent found - this route is the parent if the current
+        // path matches or is under one of its children
+        let child_matches = route.get_children().iter().any(|child| {
+            let child_full_path = join_route_path(&full_route_path, &child.config.path);
+            let child_segments = path_segments(&child_full_path);
+            child_segments.len() > route_segments.len()
+                && segments_match_prefix(&child_segments, current)
+        });
+        if child_matches {
+            return Some((route, full_route_path));
+        }
 
-                // Check if current path matches this child or is under it
-                if current_normalized == child_full_path
-                    || current_normalized.starts_with(&format!("{}/", child_full_path))
-                {
-                    return Some(route);
+        // If path exactly matches this route and no children matched,
+        // return this route as parent (for rendering outlet when on the route itself)
+        // Only do this if we're at the top level (accumulated_path is empty or this is the root)
+        if current.len() == route_segments.len() && accumulated_path.is_empty() {
+            return Some((route, full_route_path));
+        }
+    }
+
+    None
+}
+
+// ============================================================================
+// SplitRouterView - Master-Detail Layout
+// ============================================================================
+//
+// Shows two named outlets side by side: a list that stays put and a detail
+// pane navigated on its own with `Navigator::set_outlet`.
+
+/// Width of the list pane of a [`SplitRouterView`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitSize {
+    /// Fixed width in pixels
+    Px(f32),
+    /// Fraction of the view's width, between 0 and 1
+    Ratio(f32),
+}
+
+/// Master-detail view showing two named outlets side by side
+///
+/// The list outlet is drawn on the left, the detail outlet on the right.
+/// Each pane is its own cached view: navigating the detail outlet with
+/// [`Navigator::set_outlet`] redraws only the detail pane, so the list keeps
+/// its scroll position and state and its route is not rebuilt. Both panes
+/// are redrawn when the current path changes.
+///
+/// Use it as the builder of the route owning both named outlets.
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{Navigator, Route, SplitRouterView, SplitSize};
+///
+/// Route::component("/inbox", || {
+///     SplitRouterView::new("list", "detail")
+///         .list_size(SplitSize::Ratio(0.3))
+///         .detail_placeholder(|| div().child("Select a message").into_any_element())
+/// })
+/// .named_outlet("list", vec![Route::new("", message_list).into()])
+/// .named_outlet("detail", vec![Route::new("messages/:id", message).into()]);
+///
+/// // Later: show message 42, leaving the list as it is
+/// Navigator::set_outlet(cx, "detail", "messages/42");
+/// ```
+pub struct SplitRouterView {
+    list_outlet: SharedString,
+    detail_outlet: SharedString,
+    list_size: SplitSize,
+    divider: Hsla,
+    detail_placeholder: Option<OutletPlaceholder>,
+    panes: Option<SplitPanes>,
+}
+
+/// The pane views of a [`SplitRouterView`], created on first render
+struct SplitPanes {
+    list: Entity<OutletPane>,
+    detail: Entity<OutletPane>,
+    /// Location each pane last drew, as (current path, outlet path)
+    shown: [(SharedString, Option<String>); 2],
+    _router_changed: Subscription,
+}
+
+impl SplitRouterView {
+    /// Create a split view of the named outlets `list_outlet` and `detail_outlet`
+    pub fn new(
+        list_outlet: impl Into<SharedString>,
+        detail_outlet: impl Into<SharedString>,
+    ) -> Self {
+        Self {
+            list_outlet: list_outlet.into(),
+            detail_outlet: detail_outlet.into(),
+            list_size: SplitSize::Px(280.0),
+            divider: Self::DEFAULT_DIVIDER,
+            detail_placeholder: None,
+            panes: None,
+        }
+    }
+
+    /// Light gray
+    const DEFAULT_DIVIDER: Hsla = Hsla {
+        h: 0.0,
+        s: 0.0,
+        l: 0.87,
+        a: 1.0,
+    };
+
+    /// Set the width of the list pane (280px by default)
+    pub fn list_size(mut self, size: SplitSize) -> Self {
+        self.list_size = size;
+        self
+    }
+
+    /// Set the color of the line between the panes
+    pub fn divider(mut self, color: impl Into<Hsla>) -> Self {
+        self.divider = color.into();
+        self
+    }
+
+    /// Set what the detail pane shows while nothing is selected
+    ///
+    /// That is when the detail outlet has no child matching, like
+    /// [`RouterOutlet::placeholder`].
+    pub fn detail_placeholder<F>(mut self, builder: F) -> Self
+    where
+        F: Fn() -> AnyElement + Send + Sync + 'static,
+    {
+        self.detail_placeholder = Some(std::sync::Arc::new(builder));
+        self
+    }
+
+    /// Create the pane views and start redrawing them when their outlet changes
+    fn panes(&mut self, cx: &mut Context<'_, Self>) -> &SplitPanes {
+        self.panes.get_or_insert_with(|| {
+            let outlets = [self.list_outlet.clone(), self.detail_outlet.clone()];
+            let list = cx.new(|_| OutletPane {
+                outlet: outlets[0].clone(),
+                placeholder: None,
+            });
+            let detail = cx.new(|_| OutletPane {
+                outlet: outlets[1].clone(),
+                placeholder: self.detail_placeholder.clone(),
+            });
+            let router_changed = cx.observe_global::<GlobalRouter>(move |view, cx| {
+                let Some(panes) = view.panes.as_mut() else {
+                    return;
+                };
+                for (index, outlet) in outlets.iter().enumerate() {
+                    let location = pane_location(cx, outlet);
+                    if panes.shown[index] != location {
+                        panes.shown[index] = location;
+                        let pane = if index == 0 {
+                            &panes.list
+                        } else {
+                            &panes.detail
+                        };
+                        pane.update(cx, |_, cx| cx.notify());
+                    }
                 }
+            });
+            SplitPanes {
+                list,
+                detail,
+                shown: [
+                    pane_location(cx, &self.list_outlet),
+                    pane_location(cx, &self.detail_outlet),
+                ],
+                _router_changed: router_changed,
             }
+        })
+    }
+}
 
-            // If path exactly matches this route and no children matched,
-            // return this route as parent (for rendering outlet when on the route itself)
-            // Only do this if we're at the top level (accumulated_path is empty or this is the root)
-            if current_normalized == full_route_path && accumulated_path.is_empty() {
-                return Some(route);
-            }
-        }
+/// Location a pane showing the named outlet `outlet` draws
+fn pane_location(cx: &App, outlet: &str) -> (SharedString, Option<String>) {
+    cx.try_global::<GlobalRouter>()
+        .map(|router| {
+            (
+                router.current_path_shared().clone(),
+                router.outlet_path(outlet).map(str::to_string),
+            )
+        })
+        .unwrap_or_default()
+}
+
+impl Render for SplitRouterView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let list_size = self.list_size;
+        let divider = self.divider;
+        let panes = self.panes(cx);
+        let pane_style = || StyleRefinement::default().size_full();
+        let list = AnyView::from(panes.list.clone()).cached(pane_style());
+        let detail = AnyView::from(panes.detail.clone()).cached(pane_style());
+
+        let list_pane = div()
+            .flex_none()
+            .h_full()
+            .border_r_1()
+            .border_color(divider);
+        let list_pane = match list_size {
+            SplitSize::Px(width) => list_pane.w(px(width)),
+            SplitSize::Ratio(ratio) => list_pane.w(relative(ratio.clamp(0.0, 1.0))),
+        };
+        div()
+            .flex()
+            .flex_row()
+            .size_full()
+            .child(list_pane.child(list))
+            .child(div().flex_1().h_full().min_w_0().child(detail))
     }
+}
 
-    None
+/// One pane of a [`SplitRouterView`], rendering a named outlet
+struct OutletPane {
+    outlet: SharedString,
+    placeholder: Option<OutletPlaceholder>,
+}
+
+impl Render for OutletPane {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let outer_placeholder = set_outlet_placeholder(cx, self.placeholder.clone());
+        let content = render_router_outlet(window, cx, Some(&self.outlet));
+        set_outlet_placeholder(cx, outer_placeholder);
+        div()
+            .id(ElementId::Name(
+                format!("split_pane_{}", self.outlet).into(),
+            ))
+            .size_full()
+            .child(content)
+    }
 }
 
 // ============================================================================
@@ -809,11 +2055,17 @@ fn find_parent_route_internal<'a>(
 // Provides a clickable link that navigates to a route when clicked.
 // Similar to:
 
+use crate::context::OpenRouteHandler;
+use crate::route::{IntoRoute, NamedTarget, RouteDescriptor};
 use crate::Navigator;
 use gpui::*;
 
 /// A clickable link component for router navigation
 ///
+/// A left click navigates. A middle click, or a left click with the
+/// secondary modifier (Cmd on macOS, Ctrl elsewhere), opens the target with
+/// [`RouterLink::on_secondary_open`] or the router's new window handler.
+///
 /// # Example
 ///
 /// ```ignore
@@ -822,14 +2074,142 @@ use gpui::*;
 /// RouterLink::new("/products")
 ///     .child("View Products")
 ///     .build(cx)
+///
+/// // Relative to the current route
+/// RouterLink::new("../reviews")
+///     .child("Reviews")
+///     .build(cx)
+///
+/// // A named route, showing its URL on hover
+/// RouterLink::to(NamedTarget::new("user.detail").param("id", 42))
+///     .child("Profile")
+///     .url_tooltip(true)
+///     .build(cx)
 /// ```
 pub struct RouterLink {
     /// Target route path
     path: SharedString,
     /// Optional custom styling when link is active
     active_class: Option<Box<dyn Fn(Div) -> Div>>,
+    /// Styling for every state, replacing the theme and `active_class`
+    style: Option<Box<dyn Fn(Div, LinkState) -> Div>>,
+    /// Whether the link is also active on paths below its target
+    active_prefix: bool,
     /// Child elements
     children: Vec<AnyElement>,
+    /// How to render when a guard blocks the target
+    #[cfg(feature = "guard")]
+    when_blocked: Option<BlockedLink>,
+    /// Handler for middle clicks and modifier clicks
+    secondary_open: Option<OpenRouteHandler>,
+    /// Whether a right click opens the link menu
+    context_menu: bool,
+    /// Options for the navigation when the link is followed
+    options: NavOptions,
+    /// Named route the link goes to, looked up when building it
+    target: Option<RouteDescriptor>,
+    /// Whether hovering shows the target URL in a tooltip
+    url_tooltip: bool,
+    /// Id naming the link in the navigations it starts
+    element_id: Option<String>,
+}
+
+/// Colors of links without styling of their own
+///
+/// Used by [`router_link`] and by [`RouterLink`]s without
+/// [`RouterLink::style`] or [`RouterLink::active_class`]. Set it with
+/// [`GlobalRouter::set_link_theme`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouterLinkTheme {
+    /// Text color of links to the current route
+    pub active: Hsla,
+    /// Text color of other links
+    pub inactive: Hsla,
+    /// Text color of hovered links
+    pub hover: Hsla,
+    /// Text color of links that cannot be followed
+    pub disabled: Hsla,
+}
+
+impl Default for RouterLinkTheme {
+    fn default() -> Self {
+        Self {
+            active: rgb(0x2196f3).into(),
+            inactive: rgb(0x333333).into(),
+            hover: rgb(0x2196f3).into(),
+            disabled: rgba(0x33333380).into(),
+        }
+    }
+}
+
+impl RouterLinkTheme {
+    /// Text color of a link in `state`
+    pub fn color(&self, state: LinkState) -> Hsla {
+        if state.disabled {
+            self.disabled
+        } else if state.hovered {
+            self.hover
+        } else if state.active {
+            self.active
+        } else {
+            self.inactive
+        }
+    }
+}
+
+/// State of a link, passed to [`RouterLink::style`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkState {
+    /// The link goes to the current route
+    pub active: bool,
+    /// The mouse is over the link
+    pub hovered: bool,
+    /// The link cannot be followed (blocked or unresolvable target)
+    pub disabled: bool,
+}
+
+/// Theme of links, the default one without a router
+fn link_theme(cx: &App) -> RouterLinkTheme {
+    cx.try_global::<GlobalRouter>()
+        .map(|router| *router.link_theme())
+        .unwrap_or_default()
+}
+
+/// `link` styled by `style` in `state`, its hovered styles applied on hover
+///
+/// Only the styles `style` sets for the hovered state are used, not
+/// children or listeners.
+fn style_link(link: Div, state: LinkState, style: &dyn Fn(Div, LinkState) -> Div) -> Div {
+    let mut hovered = style(
+        div(),
+        LinkState {
+            hovered: true,
+            ..state
+        },
+    );
+    let hovered = hovered.style().clone();
+    style(link, state).hover(move |base| base.refined(hovered))
+}
+
+/// `link` colored by the router's link theme in `state`
+fn theme_link(link: Div, state: LinkState, cx: &App) -> Div {
+    let theme = link_theme(cx);
+    let link = link.text_color(theme.color(state));
+    if state.disabled {
+        link
+    } else {
+        link.hover(move |base| base.text_color(theme.hover))
+    }
+}
+
+/// How a [`RouterLink`] renders when a guard blocks its target
+#[cfg(feature = "guard")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockedLink {
+    /// Render nothing
+    Hide,
+    /// Render dimmed and ignore clicks
+    Disable,
 }
 
 impl RouterLink {
@@ -838,10 +2218,39 @@ impl RouterLink {
         Self {
             path: path.into(),
             active_class: None,
+            style: None,
+            active_prefix: false,
             children: Vec::new(),
+            #[cfg(feature = "guard")]
+            when_blocked: None,
+            secondary_open: None,
+            context_menu: false,
+            options: NavOptions::default(),
+            target: None,
+            url_tooltip: false,
+            element_id: None,
+        }
+    }
+
+    /// Create a link to a named route
+    ///
+    /// The path is looked up when the link is built. If the name is not
+    /// registered or the params do not fit its path, the link renders
+    /// dimmed and ignores clicks.
+    pub fn to(target: NamedTarget<'_>) -> Self {
+        let target = target.into_route();
+        Self {
+            target: Some(target),
+            ..Self::new(SharedString::default())
         }
     }
 
+    /// Show the URL the link goes to in a tooltip on hover
+    pub fn url_tooltip(mut self, enabled: bool) -> Self {
+        self.url_tooltip = enabled;
+        self
+    }
+
     /// Add a child element
     pub fn child(mut self, child: impl IntoElement) -> Self {
         self.children.push(child.into_any_element());
@@ -854,37 +2263,399 @@ impl RouterLink {
         self
     }
 
+    /// Style the link in every state with one closure
+    ///
+    /// Replaces the theme colors and [`RouterLink::active_class`]. The
+    /// hovered state is applied as a hover style, so only the styles the
+    /// closure sets for it are used, not children or listeners.
+    ///
+    /// ```ignore
+    /// RouterLink::new("/inbox")
+    ///     .style(|link, state| {
+    ///         link.text_color(if state.active { accent } else { text })
+    ///             .when(state.hovered, |link| link.underline())
+    ///             .when(state.disabled, |link| link.opacity(0.4))
+    ///     })
+    /// ```
+    pub fn style(mut self, style: impl Fn(Div, LinkState) -> Div + 'static) -> Self {
+        self.style = Some(Box::new(style));
+        self
+    }
+
+    /// Also treat the link as active on paths below its target
+    ///
+    /// A link to `/settings` is then active on `/settings/profile` too. The
+    /// target may be a pattern like `/inbox/:threadId` (see
+    /// [`Navigator::is_active`]).
+    pub fn active_prefix(mut self) -> Self {
+        self.active_prefix = true;
+        self
+    }
+
+    /// Open the target this way on middle clicks and modifier clicks
+    ///
+    /// Without it the router's handler is used (see
+    /// [`GlobalRouter::set_new_window_handler`]); without either, such clicks
+    /// navigate like a left click.
+    pub fn on_secondary_open(
+        mut self,
+        handler: impl Fn(&str, &mut App) + Send + Sync + 'static,
+    ) -> Self {
+        self.secondary_open = Some(std::sync::Arc::new(handler));
+        self
+    }
+
+    /// Open a menu on right click with Open, Open in New Window and Copy Path
+    pub fn with_context_menu(mut self, enabled: bool) -> Self {
+        self.context_menu = enabled;
+        self
+    }
+
+    /// Keep the current location's query params when following the link
+    ///
+    /// `true` keeps all of them and `false` none, instead of the keys from
+    /// [`RouterConfig::preserve_query_keys`](crate::RouterConfig::preserve_query_keys).
+    pub fn preserve_query(mut self, preserve: bool) -> Self {
+        self.options.preserve_query = Some(if preserve {
+            PreserveQuery::All
+        } else {
+            PreserveQuery::None
+        });
+        self
+    }
+
+    /// Resolve a bare target like `details` against the current route
+    ///
+    /// Targets starting with `./` or `../` always are (see
+    /// [`Navigator::resolve_path`]).
+    pub fn relative(mut self, relative: bool) -> Self {
+        self.options.relative = relative;
+        self
+    }
+
+    /// Name the link in the [`NavigationSource::Link`] of its navigations
+    ///
+    /// Lets analytics tell apart links to the same page, say the sidebar's
+    /// and a dashboard card's.
+    pub fn element_id(mut self, id: impl Into<String>) -> Self {
+        self.element_id = Some(id.into());
+        self
+    }
+
+    /// Whether the current path activates this link
+    ///
+    /// A link to a fragment is only active on that fragment.
+    fn is_active(&self, cx: &App) -> bool {
+        let (path, fragment) = split_fragment(&self.path);
+        if fragment.is_some_and(|fragment| {
+            cx.global::<GlobalRouter>().current_fragment() != Some(fragment)
+        }) {
+            return false;
+        }
+        if self.active_prefix {
+            Navigator::is_active(cx, path)
+        } else {
+            Navigator::is_active_exact(cx, path)
+        }
+    }
+
+    /// Render nothing when a guard blocks the target
+    ///
+    /// Only synchronous guards are consulted (see [`Navigator::can_access`]);
+    /// if the outcome is unknown the link renders normally.
+    #[cfg(feature = "guard")]
+    pub fn hide_when_blocked(mut self) -> Self {
+        self.when_blocked = Some(BlockedLink::Hide);
+        self
+    }
+
+    /// Render dimmed and ignore clicks when a guard blocks the target
+    ///
+    /// Only synchronous guards are consulted (see [`Navigator::can_access`]);
+    /// if the outcome is unknown the link renders normally.
+    #[cfg(feature = "guard")]
+    pub fn disable_when_blocked(mut self) -> Self {
+        self.when_blocked = Some(BlockedLink::Disable);
+        self
+    }
+
+    /// How this link should render, if its target is blocked
+    #[cfg(feature = "guard")]
+    fn blocked(&self, cx: &App) -> Option<BlockedLink> {
+        let behavior = self.when_blocked?;
+        Navigator::can_access(cx, &self.path)
+            .is_blocked()
+            .then_some(behavior)
+    }
+
+    /// The link rendered dimmed, ignoring clicks
+    fn disabled(self, cx: &App) -> Div {
+        let state = LinkState {
+            disabled: true,
+            ..LinkState::default()
+        };
+        let link = match &self.style {
+            Some(style) => style(div(), state),
+            None if self.active_class.is_some() => div().opacity(0.5),
+            None => div().text_color(link_theme(cx).color(state)),
+        };
+        link.children(self.children)
+    }
+
     /// Build the link element with the given context
-    pub fn build<V: 'static>(self, cx: &mut Context<'_, V>) -> Div {
+    pub fn build<V: 'static>(mut self, cx: &mut Context<'_, V>) -> Div {
+        if let Some(target) = self.target.take() {
+            match cx.global::<GlobalRouter>().target_path(&target) {
+                Ok(path) => self.path = path.into(),
+                Err(error) => {
+                    warn_log!("RouterLink target cannot be resolved: {}", error);
+                    return self.disabled(cx);
+                }
+            }
+        }
+        self.path = Navigator::resolve_path(cx, &self.path, &self.options).into();
+        if self.options.source.is_none() {
+            self.options.source = Some(NavigationSource::Link {
+                element_id: self.element_id.clone(),
+            });
+        }
         let path = self.path.clone();
-        let current_path = Navigator::current_path(cx);
-        let is_active = current_path == path.as_ref();
+        let is_active = self.is_active(cx);
 
-        let mut link = div().cursor_pointer().on_mouse_down(
-            MouseButton::Left,
-            cx.listener(move |_view, _event, _window, cx| {
-                Navigator::push(cx, path.to_string());
-                cx.notify();
-            }),
-        );
+        #[cfg(feature = "guard")]
+        match self.blocked(cx) {
+            Some(BlockedLink::Hide) => return div(),
+            Some(BlockedLink::Disable) => return self.disabled(cx),
+            None => {}
+        }
+
+        let mut link = div().cursor_pointer();
+        for button in [MouseButton::Left, MouseButton::Middle, MouseButton::Right] {
+            let (path, secondary) = (path.clone(), self.secondary_open.clone());
+            let (context_menu, options) = (self.context_menu, self.options.clone());
+            link = link.on_mouse_down(
+                button,
+                cx.listener(move |_view, event: &MouseDownEvent, _window, cx| {
+                    match link_action(event.button, event.modifiers, context_menu) {
+                        Some(LinkAction::Navigate) => {
+                            Navigator::push_opts(cx, path.to_string(), options.clone());
+                        }
+                        Some(LinkAction::OpenSecondary) => {
+                            open_secondary(&path, secondary.as_ref(), &options, cx);
+                        }
+                        Some(LinkAction::ContextMenu) => cx.set_global(LinkMenu {
+                            path: path.clone(),
+                            position: event.position,
+                            secondary: secondary.clone(),
+                            options: options.clone(),
+                        }),
+                        None => return,
+                    }
+                    cx.notify();
+                }),
+            );
+        }
 
-        // Apply active styling if provided and link is active
-        if is_active {
-            if let Some(active_fn) = self.active_class {
+        if let Some(menu) = cx
+            .try_global::<LinkMenu>()
+            .filter(|menu| self.context_menu && menu.path == path)
+        {
+            link = link.child(menu.render());
+        }
+
+        let state = LinkState {
+            active: is_active,
+            ..LinkState::default()
+        };
+        if let Some(style) = &self.style {
+            link = style_link(link, state, style);
+        } else if let Some(active_fn) = &self.active_class {
+            if is_active {
                 link = active_fn(link);
             }
+        } else {
+            link = theme_link(link, state, cx);
         }
 
-        // Add children
-        for child in self.children {
-            link = link.child(child);
+        // Add children, in a tooltip area showing the URL if asked for
+        if self.url_tooltip {
+            let url = path.clone();
+            link = link.child(
+                div()
+                    .id(ElementId::Name(path))
+                    .children(self.children)
+                    .tooltip(move |_, cx| {
+                        let url = url.clone();
+                        cx.new(|_| LinkTooltip { url }).into()
+                    }),
+            );
+        } else {
+            for child in self.children {
+                link = link.child(child);
+            }
         }
 
         link
     }
 }
 
+/// Tooltip of a [`RouterLink`] showing its URL
+struct LinkTooltip {
+    url: SharedString,
+}
+
+impl Render for LinkTooltip {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let url = self.url.clone();
+        div()
+            .px_2()
+            .py_1()
+            .bg(rgb(0xffffff))
+            .text_color(rgb(0x333333))
+            .text_sm()
+            .border_1()
+            .border_color(rgb(0xdddddd))
+            .rounded_md()
+            .shadow_md()
+            .debug_selector(|| format!("router-link-tooltip {}", url))
+            .child(self.url.clone())
+    }
+}
+
+/// What a mouse down on a [`RouterLink`] does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkAction {
+    Navigate,
+    OpenSecondary,
+    ContextMenu,
+}
+
+fn link_action(
+    button: MouseButton,
+    modifiers: Modifiers,
+    context_menu: bool,
+) -> Option<LinkAction> {
+    match button {
+        MouseButton::Left if modifiers.secondary() => Some(LinkAction::OpenSecondary),
+        MouseButton::Left => Some(LinkAction::Navigate),
+        MouseButton::Middle => Some(LinkAction::OpenSecondary),
+        MouseButton::Right if context_menu => Some(LinkAction::ContextMenu),
+        _ => None,
+    }
+}
+
+/// Open `path` with the link's handler, the router's new window handler or
+/// by navigating with `options`
+fn open_secondary(
+    path: &str,
+    handler: Option<&OpenRouteHandler>,
+    options: &NavOptions,
+    cx: &mut App,
+) {
+    if let Some(handler) = handler {
+        handler(path, cx);
+    } else if !Navigator::open_in_new_window(cx, path) {
+        debug_log!("No new window handler, navigating to '{}' instead", path);
+        Navigator::push_opts(cx, path.to_string(), options.clone());
+    }
+}
+
+/// The open [`RouterLink`] context menu (one at a time, app-wide)
+#[derive(Clone)]
+struct LinkMenu {
+    path: SharedString,
+    position: Point<Pixels>,
+    secondary: Option<OpenRouteHandler>,
+    options: NavOptions,
+}
+
+impl Global for LinkMenu {}
+
+/// Entries of the [`RouterLink`] context menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkMenuItem {
+    Open,
+    OpenInNewWindow,
+    CopyPath,
+}
+
+impl LinkMenuItem {
+    const ALL: [Self; 3] = [Self::Open, Self::OpenInNewWindow, Self::CopyPath];
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::OpenInNewWindow => "Open in New Window",
+            Self::CopyPath => "Copy Path",
+        }
+    }
+
+    fn run(self, menu: &LinkMenu, cx: &mut App) {
+        let path = &menu.path;
+        match self {
+            Self::Open => {
+                Navigator::push_opts(cx, path.to_string(), menu.options.clone());
+            }
+            Self::OpenInNewWindow => {
+                open_secondary(path, menu.secondary.as_ref(), &menu.options, cx);
+            }
+            Self::CopyPath => cx.write_to_clipboard(ClipboardItem::new_string(path.to_string())),
+        }
+    }
+}
+
+fn close_link_menu(cx: &mut App) {
+    if cx.has_global::<LinkMenu>() {
+        cx.remove_global::<LinkMenu>();
+    }
+}
+
+impl LinkMenu {
+    /// Menu drawn above everything else at the click position
+    fn render(&self) -> impl IntoElement {
+        let items = LinkMenuItem::ALL.into_iter().map(|item| {
+            let menu = self.clone();
+            div()
+                .px_3()
+                .py_1()
+                .cursor_pointer()
+                .hover(|this| this.bg(rgb(0xe3f2fd)))
+                .child(item.label())
+                .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                    cx.stop_propagation();
+                    close_link_menu(cx);
+                    item.run(&menu, cx);
+                    window.refresh();
+                })
+        });
+
+        deferred(
+            anchored().position(self.position).child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .min_w(px(160.))
+                    .py_1()
+                    .bg(rgb(0xffffff))
+                    .text_color(rgb(0x333333))
+                    .border_1()
+                    .border_color(rgb(0xdddddd))
+                    .rounded_md()
+                    .shadow_md()
+                    .on_mouse_down_out(|_, window, cx| {
+                        close_link_menu(cx);
+                        window.refresh();
+                    })
+                    .children(items),
+            ),
+        )
+        .with_priority(1)
+    }
+}
+
 /// Helper function to create a simple text link
+///
+/// Colored with the router's [`RouterLinkTheme`].
 pub fn router_link<V: 'static>(
     cx: &mut Context<'_, V>,
     path: impl Into<SharedString>,
@@ -892,48 +2663,502 @@ pub fn router_link<V: 'static>(
 ) -> Div {
     let path_str: SharedString = path.into();
     let label_str: SharedString = label.into();
-    let current_path = Navigator::current_path(cx);
-    let is_active = current_path == path_str.as_ref();
+    let state = LinkState {
+        active: Navigator::is_active_exact(cx, &path_str),
+        ..LinkState::default()
+    };
+    let theme = link_theme(cx);
 
     div()
         .cursor_pointer()
-        .text_color(if is_active {
-            rgb(0x2196f3)
-        } else {
-            rgb(0x333333)
-        })
-        .hover(|this| this.text_color(rgb(0x2196f3)))
+        .text_color(theme.color(state))
+        .hover(move |this| this.text_color(theme.hover))
         .child(label_str)
         .on_mouse_down(
             MouseButton::Left,
             cx.listener(move |_view, _event, _window, cx| {
-                Navigator::push(cx, path_str.to_string());
+                let options = NavOptions::new().source(NavigationSource::Link { element_id: None });
+                Navigator::push_opts(cx, path_str.to_string(), options);
                 cx.notify();
             }),
         )
 }
 
 // ============================================================================
-// Default Pages System
+// HistoryMenu - History Jump List
 // ============================================================================
+//
+// Lists the history entries behind or ahead of the current one to jump to,
+// like long-pressing a browser's back button.
+
+/// How long a history button is held down before its menu opens
+const LONG_PRESS: Duration = Duration::from_millis(500);
+
+/// Which side of the current history entry a [`HistoryMenu`] lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryDirection {
+    /// Entries before the current one, the most recent first
+    Back,
+    /// Entries after the current one, the nearest first
+    Forward,
+}
 
-/// Configuration for default router pages (404, loading, error, etc.)
-pub struct DefaultPages {
-    /// Custom 404 not found page builder
-    pub not_found: Option<Box<dyn Fn() -> AnyElement + Send + Sync>>,
-    /// Custom loading page builder
-    pub loading: Option<Box<dyn Fn() -> AnyElement + Send + Sync>>,
-    /// Custom error page builder
-    pub error: Option<Box<dyn Fn(&str) -> AnyElement + Send + Sync>>,
+/// A history entry listed by a [`HistoryMenu`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryMenuEntry {
+    /// Index of the entry in history
+    pub index: usize,
+    /// Path of the entry
+    pub path: String,
+    /// The `title` meta of the entry's route, else its name, else the path
+    pub label: String,
+    /// Number of entries between the current one and this one, itself included
+    pub steps: usize,
 }
 
-impl DefaultPages {
-    /// Create new default pages configuration with built-in defaults
-    pub fn new() -> Self {
-        Self {
-            not_found: None,
-            loading: None,
-            error: None,
+/// Popover listing history entries to jump to
+///
+/// Renders while it is open for its direction (see [`HistoryMenu::open`]),
+/// above everything else. Clicking an entry goes there with
+/// [`Navigator::go_to_index`], skipping the entries in between as one
+/// navigation. Clicking outside the menu or navigating closes it.
+/// [`BackButton`] and [`ForwardButton`] open it on right click or long press.
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{HistoryDirection, HistoryMenu};
+///
+/// div()
+///     .child("History")
+///     .on_mouse_down(MouseButton::Left, |event, window, cx| {
+///         HistoryMenu::open(cx, HistoryDirection::Back, event.position);
+///         window.refresh();
+///     })
+///     .child(HistoryMenu::new(HistoryDirection::Back).max_entries(5).build(cx))
+/// ```
+pub struct HistoryMenu {
+    direction: HistoryDirection,
+    max_entries: usize,
+    style: Option<Box<dyn Fn(Div, LinkState) -> Div>>,
+}
+
+/// The open [`HistoryMenu`] (one at a time, app-wide)
+#[derive(Clone)]
+struct OpenHistoryMenu {
+    direction: HistoryDirection,
+    position: Point<Pixels>,
+    /// History index and path the menu was opened on
+    opened_on: (usize, String),
+}
+
+impl Global for OpenHistoryMenu {}
+
+impl HistoryMenu {
+    /// Create a menu of up to 10 entries in `direction`
+    pub fn new(direction: HistoryDirection) -> Self {
+        Self {
+            direction,
+            max_entries: 10,
+            style: None,
+        }
+    }
+
+    /// List at most `max` entries, the nearest ones
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max;
+        self
+    }
+
+    /// Style the entries with one closure, like [`RouterLink::style`]
+    ///
+    /// Entries are never active or disabled: every listed entry can be
+    /// navigated to.
+    pub fn style(mut self, style: impl Fn(Div, LinkState) -> Div + 'static) -> Self {
+        self.style = Some(Box::new(style));
+        self
+    }
+
+    /// Entries the menu lists, nearest first
+    pub fn entries(&self, cx: &App) -> Vec<HistoryMenuEntry> {
+        let Some(router) = cx.try_global::<GlobalRouter>() else {
+            return Vec::new();
+        };
+        let history = router.state().history();
+        let current = history.current_index();
+        let indices: Vec<usize> = match self.direction {
+            HistoryDirection::Back => (0..current).rev().take(self.max_entries).collect(),
+            HistoryDirection::Forward => (current + 1..history.len())
+                .take(self.max_entries)
+                .collect(),
+        };
+        indices
+            .into_iter()
+            .map(|index| {
+                let path = history.entries()[index].path.clone();
+                HistoryMenuEntry {
+                    index,
+                    label: history_entry_label(router, &path),
+                    steps: index.abs_diff(current),
+                    path,
+                }
+            })
+            .collect()
+    }
+
+    /// Open the menu for `direction` at `position`, closing any other
+    pub fn open(cx: &mut App, direction: HistoryDirection, position: Point<Pixels>) {
+        let Some(router) = cx.try_global::<GlobalRouter>() else {
+            return;
+        };
+        let opened_on = (
+            router.state().history().current_index(),
+            router.current_path().to_string(),
+        );
+        cx.set_global(OpenHistoryMenu {
+            direction,
+            position,
+            opened_on,
+        });
+    }
+
+    /// Close the open menu, if any
+    pub fn close(cx: &mut App) {
+        if cx.has_global::<OpenHistoryMenu>() {
+            cx.remove_global::<OpenHistoryMenu>();
+        }
+    }
+
+    /// Whether the menu for `direction` is open
+    ///
+    /// A menu opened before the last navigation counts as closed.
+    pub fn is_open(cx: &App, direction: HistoryDirection) -> bool {
+        open_history_menu(cx).is_some_and(|menu| menu.direction == direction)
+    }
+
+    /// Build the menu, empty while it is not open
+    pub fn build(self, cx: &App) -> AnyElement {
+        let Some(menu) = open_history_menu(cx).filter(|menu| menu.direction == self.direction)
+        else {
+            return div().into_any_element();
+        };
+
+        let rows = self.entries(cx).into_iter().map(|entry| {
+            let index = entry.index;
+            let sign = match self.direction {
+                HistoryDirection::Back => '-',
+                HistoryDirection::Forward => '+',
+            };
+            let row = div()
+                .flex()
+                .justify_between()
+                .gap_4()
+                .px_3()
+                .py_1()
+                .cursor_pointer()
+                .debug_selector(move || format!("history-menu-entry {}", index));
+            let row = match &self.style {
+                Some(style) => style_link(row, LinkState::default(), style),
+                None => theme_link(row, LinkState::default(), cx),
+            };
+            let title = div()
+                .flex()
+                .flex_col()
+                .child(entry.label.clone())
+                .when(entry.label != entry.path, |title| {
+                    title.child(div().text_xs().opacity(0.6).child(entry.path))
+                });
+            row.child(title)
+                .child(
+                    div()
+                        .text_xs()
+                        .opacity(0.6)
+                        .child(format!("{}{}", sign, entry.steps)),
+                )
+                .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                    cx.stop_propagation();
+                    HistoryMenu::close(cx);
+                    Navigator::go_to_index(cx, index);
+                    window.refresh();
+                })
+        });
+
+        deferred(
+            anchored().position(menu.position).child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .min_w(px(200.))
+                    .py_1()
+                    .bg(rgb(0xffffff))
+                    .border_1()
+                    .border_color(rgb(0xdddddd))
+                    .rounded_md()
+                    .shadow_md()
+                    .on_mouse_down_out(|_, window, cx| {
+                        HistoryMenu::close(cx);
+                        window.refresh();
+                    })
+                    .children(rows),
+            ),
+        )
+        .with_priority(1)
+        .into_any_element()
+    }
+}
+
+/// The open history menu, unless the router navigated since it opened
+fn open_history_menu(cx: &App) -> Option<&OpenHistoryMenu> {
+    let menu = cx.try_global::<OpenHistoryMenu>()?;
+    let router = cx.try_global::<GlobalRouter>()?;
+    let (index, path) = &menu.opened_on;
+    (*index == router.state().history().current_index() && path == router.current_path())
+        .then_some(menu)
+}
+
+/// Label of a history entry: its route's title or name, else `path`
+fn history_entry_label(router: &GlobalRouter, path: &str) -> String {
+    let route = match_route_chain(router.state().routes(), path)
+        .and_then(|(chain, _)| chain.last().copied());
+    route
+        .and_then(|route| {
+            let config = &route.config;
+            route
+                .get_meta::<RouteTitle>()
+                .map(|title| &title.0)
+                .or_else(|| config.meta.get("title"))
+                .or(config.name.as_ref())
+                .cloned()
+        })
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Press of a history button that opens its menu once it is long enough
+struct HistoryButtonPress {
+    id: u64,
+    direction: HistoryDirection,
+}
+
+impl Global for HistoryButtonPress {}
+
+/// Button going back or forward, see [`BackButton`] and [`ForwardButton`]
+struct HistoryButton {
+    direction: HistoryDirection,
+    children: Vec<AnyElement>,
+    style: Option<Box<dyn Fn(Div, LinkState) -> Div>>,
+    menu: HistoryMenu,
+}
+
+impl HistoryButton {
+    fn new(direction: HistoryDirection) -> Self {
+        Self {
+            direction,
+            children: Vec::new(),
+            style: None,
+            menu: HistoryMenu::new(direction),
+        }
+    }
+
+    fn build(self, cx: &App) -> Div {
+        let direction = self.direction;
+        let enabled = match direction {
+            HistoryDirection::Back => Navigator::can_go_back(cx),
+            HistoryDirection::Forward => Navigator::can_go_forward(cx),
+        };
+        let state = LinkState {
+            disabled: !enabled,
+            ..LinkState::default()
+        };
+        let button = match &self.style {
+            Some(style) => style_link(div(), state, style),
+            None => theme_link(div(), state, cx),
+        };
+        if !enabled {
+            return button.children(self.children);
+        }
+
+        button
+            .cursor_pointer()
+            .on_mouse_down(MouseButton::Left, move |event, _window, cx| {
+                start_history_press(cx, direction, event.position);
+            })
+            .on_mouse_up(MouseButton::Left, move |_, window, cx| {
+                // Released before the menu opened: a click
+                let pressed = cx
+                    .try_global::<HistoryButtonPress>()
+                    .is_some_and(|press| press.direction == direction);
+                if pressed {
+                    cx.remove_global::<HistoryButtonPress>();
+                    match direction {
+                        HistoryDirection::Back => Navigator::pop(cx),
+                        HistoryDirection::Forward => Navigator::forward(cx),
+                    };
+                    window.refresh();
+                }
+            })
+            .on_mouse_down(MouseButton::Right, move |event, window, cx| {
+                HistoryMenu::open(cx, direction, event.position);
+                window.refresh();
+            })
+            .children(self.children)
+            .child(self.menu.build(cx))
+    }
+}
+
+/// Start pressing a history button, opening its menu after [`LONG_PRESS`]
+fn start_history_press(cx: &mut App, direction: HistoryDirection, position: Point<Pixels>) {
+    let id = cx
+        .try_global::<HistoryButtonPress>()
+        .map_or(0, |press| press.id.wrapping_add(1));
+    cx.set_global(HistoryButtonPress { id, direction });
+    cx.spawn(async move |cx| {
+        cx.background_executor().timer(LONG_PRESS).await;
+        drop(cx.update(|cx| {
+            let held = cx
+                .try_global::<HistoryButtonPress>()
+                .is_some_and(|press| press.id == id);
+            if held {
+                cx.remove_global::<HistoryButtonPress>();
+                HistoryMenu::open(cx, direction, position);
+                crate::context::refresh_windows(cx);
+            }
+        }));
+    })
+    .detach();
+}
+
+/// Button going back in history
+///
+/// A click goes back like [`Navigator::pop`]; a right click or a long press
+/// opens a [`HistoryMenu`] of the entries behind the current one. Dimmed and
+/// inert while there is nothing to go back to. Colored with the router's
+/// [`RouterLinkTheme`] unless styled with [`BackButton::style`].
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{BackButton, ForwardButton};
+///
+/// div()
+///     .flex()
+///     .child(BackButton::new().child("←").build(cx))
+///     .child(ForwardButton::new().child("→").build(cx))
+/// ```
+pub struct BackButton(HistoryButton);
+
+impl BackButton {
+    /// Create a back button without content
+    pub fn new() -> Self {
+        Self(HistoryButton::new(HistoryDirection::Back))
+    }
+
+    /// Add a child element
+    pub fn child(mut self, child: impl IntoElement) -> Self {
+        self.0.children.push(child.into_any_element());
+        self
+    }
+
+    /// Style the button in every state with one closure, like
+    /// [`RouterLink::style`]
+    pub fn style(mut self, style: impl Fn(Div, LinkState) -> Div + 'static) -> Self {
+        self.0.style = Some(Box::new(style));
+        self
+    }
+
+    /// Use `menu`'s entry limit and style for the history menu
+    pub fn menu(mut self, menu: HistoryMenu) -> Self {
+        self.0.menu = HistoryMenu {
+            direction: HistoryDirection::Back,
+            ..menu
+        };
+        self
+    }
+
+    /// Build the button element
+    pub fn build(self, cx: &App) -> Div {
+        self.0.build(cx)
+    }
+}
+
+impl Default for BackButton {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Button going forward in history
+///
+/// Like [`BackButton`], for the entries ahead of the current one.
+pub struct ForwardButton(HistoryButton);
+
+impl ForwardButton {
+    /// Create a forward button without content
+    pub fn new() -> Self {
+        Self(HistoryButton::new(HistoryDirection::Forward))
+    }
+
+    /// Add a child element
+    pub fn child(mut self, child: impl IntoElement) -> Self {
+        self.0.children.push(child.into_any_element());
+        self
+    }
+
+    /// Style the button in every state with one closure, like
+    /// [`RouterLink::style`]
+    pub fn style(mut self, style: impl Fn(Div, LinkState) -> Div + 'static) -> Self {
+        self.0.style = Some(Box::new(style));
+        self
+    }
+
+    /// Use `menu`'s entry limit and style for the history menu
+    pub fn menu(mut self, menu: HistoryMenu) -> Self {
+        self.0.menu = HistoryMenu {
+            direction: HistoryDirection::Forward,
+            ..menu
+        };
+        self
+    }
+
+    /// Build the button element
+    pub fn build(self, cx: &App) -> Div {
+        self.0.build(cx)
+    }
+}
+
+impl Default for ForwardButton {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Default Pages System
+// ============================================================================
+
+/// Configuration for default router pages (404, loading, error, etc.)
+pub struct DefaultPages {
+    /// Custom 404 not found page builder
+    pub not_found: Option<Box<dyn Fn() -> AnyElement + Send + Sync>>,
+    /// Custom loading page builder
+    pub loading: Option<Box<dyn Fn() -> AnyElement + Send + Sync>>,
+    /// Custom error page builder
+    #[allow(clippy::type_complexity)]
+    pub error: Option<Box<dyn Fn(&str) -> AnyElement + Send + Sync>>,
+    /// Custom builder for nested outlets with no child to render
+    pub empty_outlet: Option<Box<dyn Fn() -> AnyElement + Send + Sync>>,
+    /// Whether empty outlets explain why they are empty
+    pub outlet_diagnostics: bool,
+}
+
+impl DefaultPages {
+    /// Create new default pages configuration with built-in defaults
+    pub fn new() -> Self {
+        Self {
+            not_found: None,
+            loading: None,
+            error: None,
+            empty_outlet: None,
+            outlet_diagnostics: cfg!(debug_assertions),
         }
     }
 
@@ -964,6 +3189,28 @@ impl DefaultPages {
         self
     }
 
+    /// Set what nested outlets show when they have no child to render
+    ///
+    /// Used when the current path is a parent route with children but no
+    /// matching child or index route, and when the matched child has no
+    /// builder. `RouterOutlet::placeholder` overrides it per outlet.
+    pub fn with_empty_outlet<F>(mut self, builder: F) -> Self
+    where
+        F: Fn() -> AnyElement + Send + Sync + 'static,
+    {
+        self.empty_outlet = Some(Box::new(builder));
+        self
+    }
+
+    /// Set whether empty outlets explain why they are empty
+    ///
+    /// On by default in debug builds only. The explanation is meant for
+    /// developers; without it an empty outlet renders nothing.
+    pub fn with_outlet_diagnostics(mut self, enabled: bool) -> Self {
+        self.outlet_diagnostics = enabled;
+        self
+    }
+
     /// Render 404 not found page (custom or default)
     pub fn render_not_found(&self) -> AnyElement {
         if let Some(builder) = &self.not_found {
@@ -990,6 +3237,25 @@ impl DefaultPages {
             default_error_page(message).into_any_element()
         }
     }
+
+    /// Render an empty nested outlet (custom or default)
+    ///
+    /// The default is an empty element, showing `message` when outlet
+    /// diagnostics are on.
+    pub fn render_empty_outlet(&self, message: &str) -> AnyElement {
+        if let Some(builder) = &self.empty_outlet {
+            return builder();
+        }
+        match self.outlet_diagnostic(message) {
+            Some(message) => div().child(message.to_string()).into_any_element(),
+            None => div().into_any_element(),
+        }
+    }
+
+    /// Text an empty outlet shows by default, if any
+    fn outlet_diagnostic<'a>(&self, message: &'a str) -> Option<&'a str> {
+        self.outlet_diagnostics.then_some(message)
+    }
 }
 
 impl Default for DefaultPages {
@@ -1221,6 +3487,13 @@ mod tests {
     use gpui::{div, IntoElement, ParentElement};
     use std::sync::Arc;
 
+    #[cfg(feature = "transition")]
+    thread_local! {
+        /// Ids handed out by `animation_id` on this thread
+        pub(super) static ANIMATION_IDS: std::cell::RefCell<std::collections::HashSet<gpui::ElementId>> =
+            std::cell::RefCell::default();
+    }
+
     #[test]
     fn test_outlet_creation() {
         let outlet = RouterOutlet::default();
@@ -1230,6 +3503,26 @@ mod tests {
         assert_eq!(named.name.as_deref(), Some("sidebar"));
     }
 
+    #[test]
+    fn test_top_level_layer_gets_its_own_params() {
+        let page = |path: &str| Route::new(path, |_, _, _| div().into_any_element());
+        let mut router = crate::GlobalRouter::new();
+        router.add_route(
+            page("/orgs/:orgId").child(
+                page("members/:memberId")
+                    .child(page("roles/:roleId").into())
+                    .into(),
+            ),
+        );
+
+        let layer = super::OutletLayer::for_path(&router, "/orgs/7/members/3/roles/2");
+        assert_eq!(layer.params.get("orgId").map(String::as_str), Some("7"));
+        assert_eq!(layer.params.len(), 1);
+
+        let layer = super::OutletLayer::for_path(&router, "/orgs/7");
+        assert_eq!(layer.params.get("orgId").map(String::as_str), Some("7"));
+    }
+
     #[test]
     fn test_outlet_name() {
         let outlet = RouterOutlet::new();
@@ -1239,6 +3532,23 @@ mod tests {
         assert_eq!(named.name, Some("main".to_string()));
     }
 
+    #[test]
+    fn test_outlet_element_id() {
+        use gpui::ElementId;
+
+        let id = |outlet: RouterOutlet| outlet.element_id();
+        assert_eq!(id(RouterOutlet::new()), id(RouterOutlet::new()));
+        assert_ne!(id(RouterOutlet::new()), id(RouterOutlet::named("sidebar")));
+        assert_eq!(
+            id(RouterOutlet::named("sidebar")),
+            ElementId::Name("router_outlet_sidebar".into())
+        );
+        assert_eq!(
+            id(RouterOutlet::named("sidebar").with_id("left")),
+            ElementId::from("left")
+        );
+    }
+
     // Helper to create a dummy builder
     fn dummy_builder(
         _window: &mut gpui::Window,
@@ -1350,6 +3660,111 @@ mod tests {
         assert_eq!(result.unwrap().config.path, "/settings");
     }
 
+    #[test]
+    fn test_find_parent_route_segment_prefix_collisions() {
+        let parent = |path: &str, child: &str| {
+            Arc::new(
+                Route::new(path, dummy_builder)
+                    .children(vec![Arc::new(Route::new(child, dummy_builder))]),
+            )
+        };
+
+        // Either order: a textual prefix is not a parent segment
+        for routes in [
+            vec![
+                parent("/app", "settings"),
+                parent("/application", "settings"),
+            ],
+            vec![
+                parent("/application", "settings"),
+                parent("/app", "settings"),
+            ],
+        ] {
+            let result = find_parent_route_for_path(&routes, "/application/settings");
+            assert_eq!(result.unwrap().config.path, "/application");
+            let result = find_parent_route_for_path(&routes, "/app/settings");
+            assert_eq!(result.unwrap().config.path, "/app");
+        }
+
+        let routes = vec![parent("/user", "profile"), parent("/users", "list")];
+        let result = find_parent_route_for_path(&routes, "/users/list");
+        assert_eq!(result.unwrap().config.path, "/users");
+        assert!(find_parent_route_for_path(&routes, "/users/profile").is_none());
+    }
+
+    #[test]
+    fn test_find_parent_route_parameterized_parent() {
+        let routes = vec![Arc::new(
+            Route::new("/:tenant", dummy_builder)
+                .children(vec![Arc::new(Route::new("settings", dummy_builder))]),
+        )];
+
+        let result = find_parent_route_for_path(&routes, "/acme/settings");
+        assert_eq!(result.unwrap().config.path, "/:tenant");
+
+        // The outlet resolves the child with the parent's params
+        let (child, params) = crate::resolve_child_route(
+            result.unwrap(),
+            "/acme/settings",
+            &crate::RouteParams::new(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(child.config.path, "settings");
+        assert_eq!(params.get("tenant").map(String::as_str), Some("acme"));
+    }
+
+    #[test]
+    fn test_resolve_multi_segment_child() {
+        let routes = vec![Arc::new(Route::new("/dashboard", dummy_builder).children(
+            vec![
+                Arc::new(Route::new("reports/annual", dummy_builder)),
+                Arc::new(Route::new("reports/:year/:quarter", dummy_builder)),
+            ],
+        ))];
+
+        let parent = find_parent_route_for_path(&routes, "/dashboard/reports/annual").unwrap();
+        assert_eq!(parent.config.path, "/dashboard");
+        let resolve = |path| {
+            crate::resolve_child_route(parent, path, &crate::RouteParams::new(), None).unwrap()
+        };
+
+        let (child, _) = resolve("/dashboard/reports/annual");
+        assert_eq!(child.config.path, "reports/annual");
+
+        let (child, params) = resolve("/dashboard/reports/2024/q3");
+        assert_eq!(child.config.path, "reports/:year/:quarter");
+        assert_eq!(params.get("year").map(String::as_str), Some("2024"));
+        assert_eq!(params.get("quarter").map(String::as_str), Some("q3"));
+
+        // A partial match of a multi-segment child is no match
+        assert!(find_parent_route_for_path(&routes, "/dashboard/reports").is_none());
+    }
+
+    #[test]
+    fn test_resolve_absolute_child() {
+        let routes = vec![Arc::new(Route::new("/:tenant", dummy_builder).children(
+            vec![
+                Arc::new(Route::new("/:tenant/reports/:id", dummy_builder)),
+                Arc::new(Route::new("settings", dummy_builder)),
+            ],
+        ))];
+
+        let parent = find_parent_route_for_path(&routes, "/acme/reports/7").unwrap();
+        assert_eq!(parent.config.path, "/:tenant");
+        let (child, params) =
+            crate::resolve_child_route(parent, "/acme/reports/7", &crate::RouteParams::new(), None)
+                .unwrap();
+        assert_eq!(child.config.path, "/:tenant/reports/:id");
+        assert_eq!(params.get("tenant").map(String::as_str), Some("acme"));
+        assert_eq!(params.get("id").map(String::as_str), Some("7"));
+
+        assert_eq!(
+            super::join_route_path(":tenant", "/:tenant/reports/:id"),
+            ":tenant/reports/:id"
+        );
+    }
+
     #[test]
     fn test_find_parent_route_no_match() {
         let routes = vec![Arc::new(
@@ -1416,4 +3831,1921 @@ mod tests {
         assert!(result.is_some());
         assert_eq!(result.unwrap().config.path, "dashboard");
     }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_router_link_blocked_rendering(cx: &mut gpui::TestAppContext) {
+        use super::{BlockedLink, RouterLink};
+        use crate::{init_router, AuthGuard, RoleGuard};
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    Route::new("/admin", |_, _, _| div().into_any_element())
+                        .guard(AuthGuard::new(|_| false, "/login")),
+                );
+                router.add_route(
+                    Route::new("/reports", |_, _, _| div().into_any_element()).guard(
+                        RoleGuard::new(|_| Some("user".to_string()), "admin", None::<String>),
+                    ),
+                );
+                router.add_route(
+                    Route::new("/profile", |_, _, _| div().into_any_element())
+                        .guard(AuthGuard::new(|_| true, "/login")),
+                );
+            });
+
+            assert_eq!(
+                RouterLink::new("/admin").hide_when_blocked().blocked(cx),
+                Some(BlockedLink::Hide)
+            );
+            assert_eq!(
+                RouterLink::new("/reports")
+                    .disable_when_blocked()
+                    .blocked(cx),
+                Some(BlockedLink::Disable)
+            );
+            // Allowed targets and links without the option render normally
+            assert_eq!(
+                RouterLink::new("/profile").hide_when_blocked().blocked(cx),
+                None
+            );
+            assert_eq!(RouterLink::new("/admin").blocked(cx), None);
+        });
+    }
+
+    #[gpui::test]
+    fn test_modal_route_keeps_base_mounted(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator, Presentation};
+        use gpui::AppContext;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let base_builds = Arc::new(AtomicUsize::new(0));
+        let modal_builds = Arc::new(AtomicUsize::new(0));
+
+        cx.update(|cx| {
+            let base_builds = base_builds.clone();
+            let modal_builds = modal_builds.clone();
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/photos/:id", move |_, _, _| {
+                    base_builds.fetch_add(1, Ordering::SeqCst);
+                    div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/photos/:id/edit", move |_, _, _| {
+                        modal_builds.fetch_add(1, Ordering::SeqCst);
+                        div().into_any_element()
+                    })
+                    .presentation(Presentation::Modal),
+                );
+            });
+            Navigator::push(cx, "/photos/42");
+        });
+
+        let (outlet, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        let navigate = |cx: &mut gpui::VisualTestContext, f: fn(&mut gpui::App)| {
+            cx.update(|_, cx| f(cx));
+            cx.update_entity(&outlet, |_, cx| cx.notify());
+            cx.run_until_parked();
+        };
+
+        let base_before_modal = base_builds.load(Ordering::SeqCst);
+        assert!(base_before_modal > 0);
+
+        navigate(cx, |cx| {
+            Navigator::push(cx, "/photos/42/edit");
+        });
+        assert!(modal_builds.load(Ordering::SeqCst) > 0);
+        let base_with_modal = base_builds.load(Ordering::SeqCst);
+        assert!(base_with_modal > base_before_modal);
+
+        // Re-rendering while the modal is open still builds the base page
+        navigate(cx, |_| {});
+        assert!(base_builds.load(Ordering::SeqCst) > base_with_modal);
+
+        // Popping dismisses only the modal
+        let modal_open_builds = modal_builds.load(Ordering::SeqCst);
+        navigate(cx, |cx| {
+            Navigator::pop(cx);
+        });
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/photos/42"));
+        assert_eq!(modal_builds.load(Ordering::SeqCst), modal_open_builds);
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_sibling_navigation_keeps_parent_layout(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator, Transition};
+        use gpui::{AppContext, Render};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct DashboardLayout(Arc<AtomicUsize>);
+
+        impl Render for DashboardLayout {
+            fn render(
+                &mut self,
+                window: &mut gpui::Window,
+                cx: &mut gpui::Context<'_, Self>,
+            ) -> impl IntoElement {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                div().child(super::render_router_outlet(window, cx, None))
+            }
+        }
+
+        let layouts_created = Arc::new(AtomicUsize::new(0));
+        let layout_renders = Arc::new(AtomicUsize::new(0));
+        let child_builds = Arc::new(AtomicUsize::new(0));
+
+        cx.update(|cx| {
+            let layouts_created = layouts_created.clone();
+            let layout_renders = layout_renders.clone();
+            let child_builds = child_builds.clone();
+            let child = move |path: &str| -> crate::route::RouteRef {
+                let child_builds = child_builds.clone();
+                Arc::new(Route::new(path, move |_, _, _| {
+                    child_builds.fetch_add(1, Ordering::SeqCst);
+                    div().into_any_element()
+                }))
+            };
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::component("/dashboard", move || {
+                        layouts_created.fetch_add(1, Ordering::SeqCst);
+                        DashboardLayout(layout_renders.clone())
+                    })
+                    .transition(Transition::slide_left(200))
+                    .children(vec![child("overview"), child("analytics")]),
+                );
+            });
+            Navigator::push(cx, "/dashboard/overview");
+        });
+
+        let (outlet, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        let navigate = |cx: &mut gpui::VisualTestContext, path: &'static str| {
+            cx.update(|_, cx| Navigator::push(cx, path));
+            cx.update_entity(&outlet, |_, cx| cx.notify());
+            cx.run_until_parked();
+        };
+
+        assert_eq!(layouts_created.load(Ordering::SeqCst), 1);
+        navigate(cx, "/dashboard/analytics");
+        navigate(cx, "/dashboard/overview");
+        assert_eq!(layouts_created.load(Ordering::SeqCst), 1);
+        assert!(child_builds.load(Ordering::SeqCst) >= 3);
+
+        // The layout is not drawn a second time as an exiting page
+        let renders = layout_renders.load(Ordering::SeqCst);
+        navigate(cx, "/dashboard/analytics");
+        assert_eq!(layout_renders.load(Ordering::SeqCst), renders + 1);
+
+        // Leaving the parent still transitions as a whole
+        navigate(cx, "/");
+        cx.update(|_, cx| {
+            let router = cx.global::<crate::GlobalRouter>();
+            assert_eq!(router.shared_route_depth("/dashboard/analytics", "/"), 0);
+            assert_eq!(
+                router.shared_route_depth("/dashboard/analytics", "/dashboard/overview"),
+                1
+            );
+        });
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_fragment_change_keeps_route_mounted(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator, Transition};
+        use gpui::{AppContext, Render};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct DocsPage;
+
+        impl Render for DocsPage {
+            fn render(
+                &mut self,
+                _window: &mut gpui::Window,
+                _cx: &mut gpui::Context<'_, Self>,
+            ) -> impl IntoElement {
+                div()
+            }
+        }
+
+        let pages_created = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| {
+            let pages_created = pages_created.clone();
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::component("/docs", move || {
+                        pages_created.fetch_add(1, Ordering::SeqCst);
+                        DocsPage
+                    })
+                    .transition(Transition::slide_left(200)),
+                );
+            });
+            Navigator::push(cx, "/docs");
+        });
+
+        let (outlet, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        let navigate = |cx: &mut gpui::VisualTestContext, f: fn(&mut gpui::App)| {
+            cx.update(|_, cx| f(cx));
+            cx.update_entity(&outlet, |_, cx| cx.notify());
+            cx.run_until_parked();
+        };
+
+        assert_eq!(pages_created.load(Ordering::SeqCst), 1);
+        navigate(cx, |cx| {
+            Navigator::push(cx, "#usage");
+        });
+        navigate(cx, |cx| {
+            Navigator::push(cx, "/docs#faq");
+        });
+        navigate(cx, |cx| {
+            Navigator::pop(cx);
+        });
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/docs");
+            assert_eq!(Navigator::current_fragment(cx).as_deref(), Some("usage"));
+        });
+        assert_eq!(pages_created.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_animated_route_sees_transition_progress(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator, Transition, TransitionPhase, TransitionProgress};
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        let entering: Arc<Mutex<Vec<TransitionProgress>>> = Arc::default();
+        let exiting: Arc<Mutex<Vec<TransitionProgress>>> = Arc::default();
+        cx.update(|cx| {
+            let (entering, exiting) = (entering.clone(), exiting.clone());
+            init_router(cx, move |router| {
+                router.add_route(Route::animated("/", move |_, _, _, progress| {
+                    exiting.lock().unwrap().push(progress);
+                    div()
+                }));
+                router.add_route(
+                    Route::animated("/items", move |_, _, _, progress| {
+                        entering.lock().unwrap().push(progress);
+                        div()
+                    })
+                    .transition(Transition::fade(100)),
+                );
+            });
+        });
+
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+        assert_eq!(
+            exiting.lock().unwrap().last(),
+            Some(&TransitionProgress::IDLE)
+        );
+
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/items");
+            window.refresh();
+        });
+        cx.run_until_parked();
+        for _ in 0..5 {
+            cx.executor().advance_clock(Duration::from_millis(25));
+            cx.update(|window, _| window.refresh());
+            cx.run_until_parked();
+        }
+
+        let entering = entering.lock().unwrap();
+        assert!(entering.len() >= 5);
+        assert!(entering
+            .windows(2)
+            .all(|pair| pair[0].value <= pair[1].value));
+        assert!(entering
+            .iter()
+            .any(|p| p.phase == TransitionPhase::Enter && p.value > 0.0 && p.value < 1.0));
+        assert_eq!(entering.last(), Some(&TransitionProgress::IDLE));
+
+        // The page navigated away from is rebuilt while it fades out
+        assert!(exiting
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|p| p.phase == TransitionPhase::Exit));
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_flat_routes_render_through_a_view_outlet(cx: &mut gpui::TestAppContext) {
+        use crate::{
+            init_router, render_router_outlet, Navigator, Transition, TransitionPhase,
+            TransitionProgress,
+        };
+        use gpui::{Context, Render, Window};
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        // A root view rendering the outlet outside any route builder
+        struct Shell;
+
+        impl Render for Shell {
+            fn render(
+                &mut self,
+                window: &mut Window,
+                cx: &mut Context<'_, Self>,
+            ) -> impl IntoElement {
+                div().child(render_router_outlet(window, cx, None))
+            }
+        }
+
+        let home: Arc<Mutex<Vec<TransitionProgress>>> = Arc::default();
+        let about: Arc<Mutex<Vec<TransitionProgress>>> = Arc::default();
+        let not_found: Arc<Mutex<Vec<String>>> = Arc::default();
+        cx.update(|cx| {
+            let (home, about, not_found) = (home.clone(), about.clone(), not_found.clone());
+            init_router(cx, move |router| {
+                router.set_error_handlers(crate::ErrorHandlers::new().on_not_found(
+                    move |_, details| {
+                        not_found.lock().unwrap().push(details.attempted.clone());
+                        div().into_any_element()
+                    },
+                ));
+                router.add_route(Route::animated("/", move |_, _, _, progress| {
+                    home.lock().unwrap().push(progress);
+                    div()
+                }));
+                router.add_route(
+                    Route::animated("/about", move |_, _, _, progress| {
+                        about.lock().unwrap().push(progress);
+                        div()
+                    })
+                    .transition(Transition::fade(100)),
+                );
+            });
+        });
+
+        let (_, cx) = cx.add_window_view(|_, _| Shell);
+        cx.run_until_parked();
+        assert_eq!(home.lock().unwrap().last(), Some(&TransitionProgress::IDLE));
+
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/about");
+            window.refresh();
+        });
+        cx.run_until_parked();
+        for _ in 0..5 {
+            cx.executor().advance_clock(Duration::from_millis(25));
+            cx.update(|window, _| window.refresh());
+            cx.run_until_parked();
+        }
+
+        {
+            let about = about.lock().unwrap();
+            assert!(about
+                .iter()
+                .any(|p| { p.phase == TransitionPhase::Enter && p.value > 0.0 && p.value < 1.0 }));
+            assert_eq!(about.last(), Some(&TransitionProgress::IDLE));
+        }
+
+        // Unknown paths show the not found page rather than an outlet error
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/missing");
+            drop(render_router_outlet(window, cx, None));
+        });
+        assert_eq!(*not_found.lock().unwrap(), ["/missing"]);
+    }
+
+    #[gpui::test]
+    fn test_cached_route_prepares_once_per_frame(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator};
+        use gpui::{AppContext, Context, Entity, Render, Window};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct TwoOutlets {
+            first: Entity<RouterOutlet>,
+            second: Entity<RouterOutlet>,
+        }
+
+        impl Render for TwoOutlets {
+            fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+                div().child(self.first.clone()).child(self.second.clone())
+            }
+        }
+
+        let counters: [Arc<AtomicUsize>; 4] = Default::default();
+        cx.update(|cx| {
+            let [prepared, rendered, uncached_prepared, uncached_rendered] = counters.clone();
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(Route::cached(
+                    "/reports/:id",
+                    move |_, params| {
+                        prepared.fetch_add(1, Ordering::SeqCst);
+                        format!("Report {}", params.get("id").unwrap())
+                    },
+                    move |_, _, _, title: &String| {
+                        rendered.fetch_add(1, Ordering::SeqCst);
+                        div().child(title.clone())
+                    },
+                ));
+                router.add_route(
+                    Route::cached(
+                        "/live/:id",
+                        move |_, _| uncached_prepared.fetch_add(1, Ordering::SeqCst),
+                        move |_, _, _, _: &usize| {
+                            uncached_rendered.fetch_add(1, Ordering::SeqCst);
+                            div()
+                        },
+                    )
+                    .builder_cached(false),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, cx| TwoOutlets {
+            first: cx.new(|_| RouterOutlet::new()),
+            second: cx.new(|_| RouterOutlet::new()),
+        });
+        let count = |index: usize| counters[index].load(Ordering::SeqCst);
+
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/reports/7");
+            window.refresh();
+        });
+        cx.run_until_parked();
+        // Both outlets build the page, from data prepared once
+        assert!(count(0) > 0);
+        assert_eq!(count(1), 2 * count(0));
+
+        // The next frame prepares again
+        let prepared = count(0);
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert!(count(0) > prepared);
+        assert_eq!(count(1), 2 * count(0));
+
+        // Without caching, each build prepares its own data
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/live/1");
+            window.refresh();
+        });
+        cx.run_until_parked();
+        assert!(count(3) >= 2);
+        assert_eq!(count(2), count(3));
+    }
+
+    #[gpui::test]
+    fn test_outlets_keep_independent_state(cx: &mut gpui::TestAppContext) {
+        use super::OutletState;
+        use crate::{init_router, Navigator};
+        use gpui::{AppContext, Context, ElementId, Entity, EntityId, Render, Window};
+        use std::sync::Mutex;
+
+        type Seen = Arc<Mutex<Vec<(EntityId, String)>>>;
+
+        /// Reads the state of each outlet, as of the last frame, before they render
+        struct Probe {
+            outlets: Vec<Entity<RouterOutlet>>,
+            seen: Seen,
+        }
+
+        impl Render for Probe {
+            fn render(
+                &mut self,
+                window: &mut Window,
+                cx: &mut Context<'_, Self>,
+            ) -> impl IntoElement {
+                let mut seen = self.seen.lock().unwrap();
+                seen.clear();
+                for outlet in &self.outlets {
+                    let key = outlet.read(cx).element_id();
+                    let state = window.with_id(ElementId::View(outlet.entity_id()), |window| {
+                        window.use_keyed_state(key, cx, |_, _| OutletState::default())
+                    });
+                    seen.push((state.entity_id(), state.read(cx).shown.path.to_string()));
+                }
+                div().children(self.outlets.iter().cloned())
+            }
+        }
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/a", |_, _, _| div()));
+                router.add_route(Route::new("/b", |_, _, _| div()));
+            });
+            Navigator::push(cx, "/a");
+        });
+        let seen = Seen::default();
+        let (_, cx) = cx.add_window_view({
+            let seen = seen.clone();
+            move |_, cx| Probe {
+                outlets: vec![
+                    cx.new(|_| RouterOutlet::new()),
+                    cx.new(|_| RouterOutlet::new()),
+                    cx.new(|_| RouterOutlet::new().with_id("preview")),
+                ],
+                seen,
+            }
+        });
+        let frame = |cx: &mut gpui::VisualTestContext| {
+            cx.update(|window, _| window.refresh());
+            cx.run_until_parked();
+        };
+
+        cx.update(|_, cx| {
+            Navigator::push(cx, "/b");
+        });
+        frame(cx);
+        frame(cx);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        let states: std::collections::HashSet<EntityId> =
+            seen.iter().map(|(state, _)| *state).collect();
+        assert_eq!(states.len(), 3, "outlets share state: {:?}", seen);
+        assert!(seen.iter().all(|(_, path)| path == "/b"));
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_animation_ids_stay_bounded(cx: &mut gpui::TestAppContext) {
+        use super::{OutletState, ANIMATION_ID_SLOTS};
+        use crate::{init_router, Navigator, Transition};
+        use gpui::{AppContext, Context, ElementId, Entity, Render, Window};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::time::Duration;
+
+        /// Records, before the outlet renders, whether it keeps an exiting route
+        struct Probe {
+            outlet: Entity<RouterOutlet>,
+            exiting: Arc<AtomicBool>,
+        }
+
+        impl Render for Probe {
+            fn render(
+                &mut self,
+                window: &mut Window,
+                cx: &mut Context<'_, Self>,
+            ) -> impl IntoElement {
+                let key = self.outlet.read(cx).element_id();
+                let state = window.with_id(ElementId::View(self.outlet.entity_id()), |window| {
+                    window.use_keyed_state(key, cx, |_, _| OutletState::default())
+                });
+                let exiting = state.read(cx).shown.exiting.is_some();
+                self.exiting.store(exiting, Ordering::SeqCst);
+                div().child(self.outlet.clone())
+            }
+        }
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router
+                    .add_route(Route::new("/a", |_, _, _| div()).transition(Transition::fade(10)));
+                router
+                    .add_route(Route::new("/b", |_, _, _| div()).transition(Transition::fade(10)));
+            });
+            Navigator::push(cx, "/a");
+        });
+        let exiting = Arc::new(AtomicBool::new(false));
+        let (_, cx) = cx.add_window_view({
+            let exiting = exiting.clone();
+            move |_, cx| Probe {
+                outlet: cx.new(|_| RouterOutlet::new()),
+                exiting,
+            }
+        });
+        let frame = |cx: &mut gpui::VisualTestContext| {
+            cx.update(|window, _| window.refresh());
+            cx.run_until_parked();
+        };
+        ANIMATION_IDS.with(|ids| ids.borrow_mut().clear());
+
+        for i in 0..10_000 {
+            cx.update(|_, cx| Navigator::replace(cx, if i % 2 == 0 { "/b" } else { "/a" }));
+            frame(cx);
+        }
+
+        // Fade enter and exit, one id per slot each
+        let ids = ANIMATION_IDS.with(|ids| ids.borrow().len());
+        assert!(ids > 0);
+        assert!(
+            ids <= 2 * ANIMATION_ID_SLOTS as usize,
+            "{} animation ids",
+            ids
+        );
+
+        // The exiting route is dropped once the transition is done
+        frame(cx);
+        assert!(exiting.load(Ordering::SeqCst));
+        cx.executor().advance_clock(Duration::from_millis(20));
+        frame(cx);
+        frame(cx);
+        assert!(!exiting.load(Ordering::SeqCst));
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_batch_shows_last_route_without_exiting_layer(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator, Transition};
+        use std::sync::Mutex;
+
+        let built: Arc<Mutex<Vec<String>>> = Arc::default();
+        cx.update(|cx| {
+            let built = built.clone();
+            init_router(cx, move |router| {
+                for path in ["/", "/a", "/b", "/c"] {
+                    let built = built.clone();
+                    router.add_route(
+                        Route::new(path, move |_, _, _| {
+                            built.lock().unwrap().push(path.to_string());
+                            div()
+                        })
+                        .transition(Transition::fade(200)),
+                    );
+                }
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+        built.lock().unwrap().clear();
+
+        cx.update(|window, cx| {
+            Navigator::batch(cx, |cx| {
+                Navigator::push(cx, "/a");
+                Navigator::push(cx, "/b");
+                Navigator::push(cx, "/c");
+            });
+            window.refresh();
+        });
+        cx.run_until_parked();
+
+        // Neither the page left nor the ones passed through are drawn
+        let built = built.lock().unwrap();
+        assert!(!built.is_empty());
+        assert!(built.iter().all(|path| path == "/c"), "built {:?}", built);
+    }
+
+    #[gpui::test]
+    fn test_navigator_chain_skips_intermediate_routes(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, Navigator};
+        use std::sync::Mutex;
+
+        let built: Arc<Mutex<Vec<String>>> = Arc::default();
+        cx.update(|cx| {
+            let built = built.clone();
+            init_router(cx, move |router| {
+                for path in ["/", "/a", "/b", "/c"] {
+                    let built = built.clone();
+                    router.add_route(Route::new(path, move |_, _, _| {
+                        built.lock().unwrap().push(path.to_string());
+                        div()
+                    }));
+                }
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+        built.lock().unwrap().clear();
+
+        cx.update(|window, cx| {
+            Navigator::of(cx).push("/a").push("/b").push("/c");
+            window.refresh();
+        });
+        cx.run_until_parked();
+
+        let built = built.lock().unwrap();
+        assert!(built.contains(&"/c".to_string()), "built {:?}", built);
+        assert!(
+            !built.iter().any(|path| path == "/a" || path == "/b"),
+            "built {:?}",
+            built
+        );
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_navigation_transition_overrides_route(cx: &mut gpui::TestAppContext) {
+        use crate::{
+            init_router, GlobalRouter, Navigator, SlideDirection, Transition, TransitionPhase,
+        };
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        let phases: Arc<Mutex<Vec<TransitionPhase>>> = Arc::default();
+        cx.update(|cx| {
+            let phases = phases.clone();
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(
+                    Route::animated("/page", move |_, _, _, progress| {
+                        phases.lock().unwrap().push(progress.phase);
+                        div()
+                    })
+                    .transition(Transition::fade(100)),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+
+        cx.update(|window, cx| {
+            Navigator::push_with_transition(cx, "/page", Transition::slide_left(300));
+            window.refresh();
+        });
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            let transition = cx.global::<GlobalRouter>().state().current_transition();
+            assert!(matches!(
+                transition,
+                Transition::Slide {
+                    direction: SlideDirection::Left,
+                    duration_ms: 300,
+                    ..
+                }
+            ));
+        });
+
+        // Past the route's fade but still inside the slide
+        cx.executor().advance_clock(Duration::from_millis(150));
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert_eq!(phases.lock().unwrap().last(), Some(&TransitionPhase::Enter));
+
+        // Navigations without a transition of their own use the route's again
+        cx.update(|window, cx| {
+            Navigator::pop(cx);
+            Navigator::forward(cx);
+            window.refresh();
+        });
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            let state = cx.global::<GlobalRouter>().state();
+            assert!(state.navigation_transition().is_none());
+            assert!(matches!(
+                state.current_transition(),
+                Transition::Fade { duration_ms: 100 }
+            ));
+        });
+    }
+
+    /// Routes for the builder panic tests: `/` and a `/broken` route that panics
+    fn init_broken_routes(
+        cx: &mut gpui::TestAppContext,
+        config: crate::RouterConfig,
+        builds: &Arc<std::sync::atomic::AtomicUsize>,
+        errors: &Arc<std::sync::Mutex<Vec<String>>>,
+    ) {
+        use crate::{init_router, DefaultPages};
+        use std::sync::atomic::Ordering;
+
+        let (builds, errors) = (builds.clone(), errors.clone());
+        cx.update(|cx| {
+            init_router(cx, move |router| {
+                router.set_config(config);
+                router.set_default_pages(DefaultPages::new().with_error(move |message| {
+                    errors.lock().unwrap().push(message.to_string());
+                    div().into_any_element()
+                }));
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(Route::new("/broken", move |_, _, _| -> gpui::Div {
+                    builds.fetch_add(1, Ordering::SeqCst);
+                    panic!("boom");
+                }));
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_panicking_builder_renders_error_page(cx: &mut gpui::TestAppContext) {
+        use crate::Navigator;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Mutex;
+
+        let builds = Arc::new(AtomicUsize::new(0));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        init_broken_routes(cx, crate::RouterConfig::new(), &builds, &errors);
+
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        let navigate = |cx: &mut gpui::VisualTestContext, path: &'static str| {
+            cx.update(|window, cx| {
+                Navigator::push(cx, path);
+                window.refresh();
+            });
+            cx.run_until_parked();
+        };
+
+        navigate(cx, "/broken");
+        assert_eq!(builds.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            errors.lock().unwrap().last().map(String::as_str),
+            Some("boom")
+        );
+        // The outlet is not left building the route
+        cx.update(|_, cx| assert!(!cx.global::<crate::GlobalRouter>().state().in_render()));
+
+        // The route is poisoned: later renders show the error page without retrying
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert_eq!(builds.load(Ordering::SeqCst), 1);
+        assert_eq!(errors.lock().unwrap().len(), 2);
+
+        // The app keeps working, and navigating gives the route another chance
+        navigate(cx, "/");
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/"));
+        navigate(cx, "/broken");
+        assert_eq!(builds.load(Ordering::SeqCst), 2);
+    }
+
+    /// Errors and retry handles seen by an error boundary
+    type Caught = Arc<std::sync::Mutex<Vec<(crate::RouteError, crate::RetryHandle)>>>;
+
+    fn boundary(
+        caught: &Caught,
+    ) -> impl Fn(&crate::RouteError, crate::RetryHandle, &mut gpui::App) -> gpui::Div
+           + Send
+           + Sync
+           + 'static {
+        let caught = caught.clone();
+        move |error, retry, _| {
+            caught.lock().unwrap().push((error.clone(), retry));
+            div()
+        }
+    }
+
+    #[gpui::test]
+    fn test_error_boundary_replaces_only_child(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, render_router_outlet, DefaultPages, Navigator, RouteError};
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::Mutex;
+
+        let layout_builds = Arc::new(AtomicUsize::new(0));
+        let child_builds = Arc::new(AtomicUsize::new(0));
+        let healthy = Arc::new(AtomicBool::new(false));
+        let parent_caught = Caught::default();
+        let child_caught = Caught::default();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let (layout_builds, child_builds, healthy) =
+                (layout_builds.clone(), child_builds.clone(), healthy.clone());
+            let (parent_caught, child_caught, errors) =
+                (parent_caught.clone(), child_caught.clone(), errors.clone());
+            init_router(cx, move |router| {
+                router.set_default_pages(DefaultPages::new().with_error(move |message| {
+                    errors.lock().unwrap().push(message.to_string());
+                    div().into_any_element()
+                }));
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(
+                    Route::new("/dashboard", move |window, cx, _| {
+                        layout_builds.fetch_add(1, Ordering::SeqCst);
+                        div()
+                            .child("Sidebar")
+                            .child(render_router_outlet(window, cx, None))
+                    })
+                    .error_boundary(boundary(&parent_caught))
+                    .children(vec![
+                        Route::new("flaky", move |_, _, _| {
+                            child_builds.fetch_add(1, Ordering::SeqCst);
+                            assert!(healthy.load(Ordering::SeqCst), "boom");
+                            div()
+                        })
+                        .into(),
+                        Route::new("guarded", |_, _, _| -> gpui::Div { panic!("own") })
+                            .error_boundary(boundary(&child_caught))
+                            .into(),
+                    ]),
+                );
+            });
+        });
+
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        let navigate = |cx: &mut gpui::VisualTestContext, path: &'static str| {
+            cx.update(|window, cx| {
+                Navigator::push(cx, path);
+                window.refresh();
+            });
+            cx.run_until_parked();
+        };
+
+        // The child has no boundary: the parent's renders in its place while
+        // the layout itself is still built
+        navigate(cx, "/dashboard/flaky");
+        assert_eq!(layout_builds.load(Ordering::SeqCst), 1);
+        assert_eq!(child_builds.load(Ordering::SeqCst), 1);
+        let (error, retry) = parent_caught.lock().unwrap()[0].clone();
+        assert_eq!(
+            error,
+            RouteError::Panic {
+                message: "boom".to_string()
+            }
+        );
+        assert_eq!(retry.path(), "/dashboard/flaky");
+        assert!(errors.lock().unwrap().is_empty());
+
+        // Retrying builds the child again
+        healthy.store(true, Ordering::SeqCst);
+        cx.update(|_, cx| retry.retry(cx));
+        cx.run_until_parked();
+        assert_eq!(child_builds.load(Ordering::SeqCst), 2);
+        assert_eq!(parent_caught.lock().unwrap().len(), 1);
+
+        // The nearest boundary wins
+        navigate(cx, "/dashboard/guarded");
+        assert_eq!(child_caught.lock().unwrap().len(), 1);
+        assert_eq!(parent_caught.lock().unwrap().len(), 1);
+
+        // Paths none of the children match are errors of the parent
+        navigate(cx, "/dashboard/missing");
+        assert_eq!(
+            parent_caught.lock().unwrap().last().unwrap().0,
+            RouteError::NotFound {
+                path: "/dashboard/missing".to_string()
+            }
+        );
+        assert!(errors.lock().unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    fn test_outlet_placeholder(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, render_router_outlet, DefaultPages, GlobalRouter, Navigator};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let placeholders = Arc::new(AtomicUsize::new(0));
+        let defaults = Arc::new(AtomicUsize::new(0));
+        let not_found = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| {
+            let (defaults, not_found) = (defaults.clone(), not_found.clone());
+            init_router(cx, move |router| {
+                router.set_default_pages(
+                    DefaultPages::new()
+                        .with_empty_outlet(move || {
+                            defaults.fetch_add(1, Ordering::SeqCst);
+                            div().into_any_element()
+                        })
+                        .with_not_found(move || {
+                            not_found.fetch_add(1, Ordering::SeqCst);
+                            div().into_any_element()
+                        }),
+                );
+                router.add_route(Route::new("/", |_, _, _| div()));
+                let mut unbuilt = Route::new("draft", |_, _, _| div());
+                unbuilt.builder = None;
+                router.add_route(
+                    Route::new("/dashboard", |window, cx, _| {
+                        div().child(render_router_outlet(window, cx, None))
+                    })
+                    .children(vec![
+                        Route::new("stats", |_, _, _| div()).into(),
+                        unbuilt.into(),
+                    ]),
+                );
+            });
+        });
+
+        let counter = placeholders.clone();
+        let (_, cx) = cx.add_window_view(move |_, _| {
+            let counter = counter.clone();
+            RouterOutlet::new().placeholder(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                div().into_any_element()
+            })
+        });
+        let navigate = |cx: &mut gpui::VisualTestContext, path: &'static str| {
+            cx.update(|window, cx| {
+                Navigator::push(cx, path);
+                window.refresh();
+            });
+            cx.run_until_parked();
+        };
+
+        // A parent with children but no index route shows the placeholder
+        navigate(cx, "/dashboard");
+        assert_eq!(placeholders.load(Ordering::SeqCst), 1);
+
+        navigate(cx, "/dashboard/stats");
+        assert_eq!(placeholders.load(Ordering::SeqCst), 1);
+
+        // So does a child without a builder
+        navigate(cx, "/dashboard/draft");
+        assert_eq!(placeholders.load(Ordering::SeqCst), 2);
+
+        // Unknown children are still not found
+        navigate(cx, "/dashboard/missing");
+        assert_eq!(placeholders.load(Ordering::SeqCst), 2);
+        assert_eq!(not_found.load(Ordering::SeqCst), 1);
+
+        // The outlet's placeholder wins over the default pages, and only
+        // applies while that outlet renders
+        assert_eq!(defaults.load(Ordering::SeqCst), 0);
+        cx.update(|window, cx| {
+            assert!(cx.global::<GlobalRouter>().outlet_placeholder.is_none());
+            Navigator::push(cx, "/dashboard");
+            let _ = render_router_outlet(window, cx, None);
+        });
+        assert_eq!(defaults.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_outlet_diagnostics() {
+        use crate::DefaultPages;
+
+        let message = "RouterOutlet: No child route matched for path '/dashboard'";
+        let release = DefaultPages::new().with_outlet_diagnostics(false);
+        assert_eq!(release.outlet_diagnostic(message), None);
+
+        let debug = DefaultPages::new().with_outlet_diagnostics(true);
+        assert_eq!(debug.outlet_diagnostic(message), Some(message));
+        assert_eq!(
+            DefaultPages::new().outlet_diagnostics,
+            cfg!(debug_assertions)
+        );
+    }
+
+    #[gpui::test]
+    fn test_error_boundary_catches_loader_errors(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, render_router_outlet, LoaderMode, Navigator, RouteError};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let loads = Arc::new(AtomicUsize::new(0));
+        let caught = Caught::default();
+        cx.update(|cx| {
+            let (loads, caught) = (loads.clone(), caught.clone());
+            init_router(cx, move |router| {
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(
+                    Route::new("/reports", |window, cx, _| {
+                        div().child(render_router_outlet(window, cx, None))
+                    })
+                    .children(vec![Route::new(":year", |_, _, _| div())
+                        .loader(move |_, _| {
+                            let attempt = loads.fetch_add(1, Ordering::SeqCst);
+                            async move {
+                                if attempt == 0 {
+                                    Err("offline".to_string())
+                                } else {
+                                    Ok(attempt)
+                                }
+                            }
+                        })
+                        .loader_mode(LoaderMode::Blocking)
+                        .error_boundary(boundary(&caught))
+                        .into()]),
+                );
+            });
+        });
+
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.update(|window, cx| {
+            Navigator::push(cx, "/reports/2024");
+            window.refresh();
+        });
+        cx.run_until_parked();
+
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/reports/2024"));
+        let (error, retry) = caught.lock().unwrap().last().unwrap().clone();
+        assert_eq!(
+            error,
+            RouteError::Loader {
+                message: "offline".to_string()
+            }
+        );
+
+        // Retrying runs the loader again
+        let reported = caught.lock().unwrap().len();
+        cx.update(|_, cx| retry.retry(cx));
+        cx.run_until_parked();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert_eq!(caught.lock().unwrap().len(), reported);
+    }
+
+    #[gpui::test]
+    fn test_links_use_router_theme(cx: &mut gpui::TestAppContext) {
+        use super::{router_link, LinkState, RouterLink, RouterLinkTheme};
+        use crate::route::NamedTarget;
+        use crate::GlobalRouter;
+        use gpui::{hsla, BorrowAppContext, Div, FontWeight, Styled};
+        use std::sync::Mutex;
+
+        struct Blank;
+
+        impl gpui::Render for Blank {
+            fn render(
+                &mut self,
+                _: &mut gpui::Window,
+                _: &mut gpui::Context<'_, Self>,
+            ) -> impl IntoElement {
+                div()
+            }
+        }
+
+        cx.update(|cx| {
+            crate::init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(Route::new("/about", |_, _, _| div()));
+            });
+        });
+        let (view, cx) = cx.add_window_view(|_, _| Blank);
+        let color = |mut link: Div| link.style().text.as_ref().and_then(|text| text.color);
+
+        let defaults = RouterLinkTheme::default();
+        view.update(cx, |_, cx| {
+            assert_eq!(color(router_link(cx, "/", "Home")), Some(defaults.active));
+            assert_eq!(
+                color(router_link(cx, "/about", "About")),
+                Some(defaults.inactive)
+            );
+        });
+
+        let dark = RouterLinkTheme {
+            active: hsla(0.55, 0.9, 0.7, 1.),
+            inactive: hsla(0., 0., 0.85, 1.),
+            hover: hsla(0.55, 0.9, 0.8, 1.),
+            disabled: hsla(0., 0., 0.85, 0.4),
+        };
+        cx.update(|_, cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.set_link_theme(dark));
+        });
+        let states: Arc<Mutex<Vec<LinkState>>> = Arc::default();
+        view.update(cx, |_, cx| {
+            assert_eq!(
+                color(router_link(cx, "/about", "About")),
+                Some(dark.inactive)
+            );
+            assert_eq!(color(RouterLink::new("/").build(cx)), Some(dark.active));
+            assert_eq!(
+                color(RouterLink::to(NamedTarget::new("missing")).build(cx)),
+                Some(dark.disabled)
+            );
+            // Links with styling of their own do not get theme colors
+            let styled =
+                RouterLink::new("/").active_class(|link| link.font_weight(FontWeight::BOLD));
+            assert_eq!(color(styled.build(cx)), None);
+
+            let record = |states: &Arc<Mutex<Vec<LinkState>>>| {
+                let states = Arc::clone(states);
+                move |link: Div, state| {
+                    states.lock().unwrap().push(state);
+                    link
+                }
+            };
+            assert_eq!(
+                color(RouterLink::new("/").style(record(&states)).build(cx)),
+                None
+            );
+            RouterLink::to(NamedTarget::new("missing"))
+                .style(record(&states))
+                .build(cx);
+        });
+        let active = LinkState {
+            active: true,
+            ..LinkState::default()
+        };
+        assert_eq!(
+            *states.lock().unwrap(),
+            [
+                LinkState {
+                    hovered: true,
+                    ..active
+                },
+                active,
+                LinkState {
+                    disabled: true,
+                    ..LinkState::default()
+                },
+            ]
+        );
+    }
+
+    /// View with a full-size link to `/target`
+    struct LinkView {
+        opened: Option<Arc<std::sync::Mutex<Vec<String>>>>,
+        preserve_query: Option<bool>,
+    }
+
+    impl gpui::Render for LinkView {
+        fn render(
+            &mut self,
+            _window: &mut gpui::Window,
+            cx: &mut gpui::Context<'_, Self>,
+        ) -> impl IntoElement {
+            use gpui::Styled;
+
+            let mut link = super::RouterLink::new("/target")
+                .child("Target")
+                .element_id("target-link")
+                .with_context_menu(true);
+            if let Some(opened) = self.opened.clone() {
+                link = link.on_secondary_open(move |path, _| {
+                    opened.lock().unwrap().push(path.to_string());
+                });
+            }
+            if let Some(preserve) = self.preserve_query {
+                link = link.preserve_query(preserve);
+            }
+            div().size_full().child(link.build(cx).size_full())
+        }
+    }
+
+    fn link_window(
+        cx: &mut gpui::TestAppContext,
+        opened: Option<Arc<std::sync::Mutex<Vec<String>>>>,
+    ) -> &mut gpui::VisualTestContext {
+        cx.update(|cx| {
+            crate::init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(Route::new("/target", |_, _, _| div().into_any_element()));
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| LinkView {
+            opened,
+            preserve_query: None,
+        });
+        cx.run_until_parked();
+        cx
+    }
+
+    #[test]
+    fn test_link_action_per_modifiers() {
+        use super::{link_action, LinkAction};
+        use gpui::{Modifiers, MouseButton};
+
+        let none = Modifiers::none();
+        let shift = Modifiers::shift();
+        let secondary = Modifiers::secondary_key();
+        let cases = [
+            (MouseButton::Left, none, false, Some(LinkAction::Navigate)),
+            (MouseButton::Left, shift, false, Some(LinkAction::Navigate)),
+            (
+                MouseButton::Left,
+                secondary,
+                false,
+                Some(LinkAction::OpenSecondary),
+            ),
+            (
+                MouseButton::Middle,
+                none,
+                false,
+                Some(LinkAction::OpenSecondary),
+            ),
+            (
+                MouseButton::Middle,
+                shift,
+                false,
+                Some(LinkAction::OpenSecondary),
+            ),
+            (MouseButton::Right, none, false, None),
+            (
+                MouseButton::Right,
+                none,
+                true,
+                Some(LinkAction::ContextMenu),
+            ),
+        ];
+        for (button, modifiers, context_menu, expected) in cases {
+            assert_eq!(
+                link_action(button, modifiers, context_menu),
+                expected,
+                "{button:?} with {modifiers:?}"
+            );
+        }
+    }
+
+    #[gpui::test]
+    fn test_router_link_secondary_open(cx: &mut gpui::TestAppContext) {
+        use crate::Navigator;
+        use gpui::{point, px, Modifiers, MouseButton};
+
+        let opened = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let cx = link_window(cx, Some(opened.clone()));
+        let position = point(px(10.), px(10.));
+        let current =
+            |cx: &mut gpui::VisualTestContext| cx.update(|_, cx| Navigator::current_path(cx));
+
+        cx.simulate_click(position, Modifiers::secondary_key());
+        cx.simulate_mouse_down(position, MouseButton::Middle, Modifiers::none());
+        cx.simulate_mouse_up(position, MouseButton::Middle, Modifiers::none());
+        assert_eq!(*opened.lock().unwrap(), vec!["/target", "/target"]);
+        assert_eq!(current(cx), "/");
+
+        cx.simulate_click(position, Modifiers::none());
+        assert_eq!(opened.lock().unwrap().len(), 2);
+        assert_eq!(current(cx), "/target");
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_router_link_waits_for_async_guard(cx: &mut gpui::TestAppContext) {
+        use crate::{guard_fn, GlobalRouter, GuardResult, NavigationError, Navigator};
+        use gpui::{point, px, Modifiers};
+        use std::time::Duration;
+
+        const CHECK: Duration = Duration::from_millis(30);
+        let cx = link_window(cx, None);
+        cx.update(|_, cx| {
+            cx.global_mut::<GlobalRouter>()
+                .add_transition_guard(guard_fn(|cx, _| {
+                    let check = cx.background_executor().timer(CHECK);
+                    Box::pin(async move {
+                        check.await;
+                        GuardResult::deny("Not signed in")
+                    }) as _
+                }));
+        });
+
+        cx.simulate_click(point(px(10.), px(10.)), Modifiers::none());
+        cx.update(|_, cx| assert_eq!(Navigator::current_path(cx), "/"));
+        cx.executor().advance_clock(CHECK);
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert!(matches!(
+                Navigator::last_error(cx),
+                Some(NavigationError::GuardBlocked { .. })
+            ));
+        });
+    }
+
+    #[cfg(feature = "middleware")]
+    #[gpui::test]
+    fn test_router_link_stamps_link_source(cx: &mut gpui::TestAppContext) {
+        use crate::{
+            run_navigation_middleware, AnalyticsMiddleware, GlobalRouter, NavigationSource,
+            Navigator, ScreenView,
+        };
+        use gpui::{point, px, Modifiers};
+
+        let cx = link_window(cx, None);
+        let views = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = views.clone();
+        cx.update(|_, cx| {
+            cx.global_mut::<GlobalRouter>()
+                .add_middleware(AnalyticsMiddleware::new(move |view: ScreenView| {
+                    captured.lock().unwrap().push(view.source);
+                }));
+        });
+
+        // No outlet is on screen to run the middleware
+        let position = point(px(10.), px(10.));
+        cx.simulate_click(position, Modifiers::none());
+        cx.update(|window, cx| {
+            run_navigation_middleware(cx);
+            Navigator::push(cx, "/");
+            run_navigation_middleware(cx);
+            window.refresh();
+        });
+        // Without a new window handler, a modifier click navigates too
+        cx.simulate_click(position, Modifiers::secondary_key());
+        cx.update(|_, cx| run_navigation_middleware(cx));
+
+        let link = NavigationSource::Link {
+            element_id: Some("target-link".to_string()),
+        };
+        assert_eq!(
+            *views.lock().unwrap(),
+            vec![link.clone(), NavigationSource::Programmatic, link]
+        );
+    }
+
+    #[gpui::test]
+    fn test_router_link_to_named_target(cx: &mut gpui::TestAppContext) {
+        use crate::{NamedTarget, Navigator};
+        use gpui::{point, px, Modifiers};
+        use std::time::Duration;
+
+        /// Links to a named route and to one with missing params
+        struct NamedLinks;
+
+        impl gpui::Render for NamedLinks {
+            fn render(
+                &mut self,
+                _window: &mut gpui::Window,
+                cx: &mut gpui::Context<'_, Self>,
+            ) -> impl IntoElement {
+                use gpui::Styled;
+
+                let target = NamedTarget::new("user.detail")
+                    .param("id", 42)
+                    .query("tab", "posts");
+                div()
+                    .size_full()
+                    .child(
+                        super::RouterLink::to(target)
+                            .child("Profile")
+                            .url_tooltip(true)
+                            .build(cx)
+                            .w_full()
+                            .h_8(),
+                    )
+                    .child(
+                        super::RouterLink::to(NamedTarget::new("user.detail"))
+                            .child("Broken")
+                            .build(cx)
+                            .w_full()
+                            .h_8(),
+                    )
+            }
+        }
+
+        cx.update(|cx| {
+            crate::init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::new("/users/:id", |_, _, _| div().into_any_element())
+                        .name("user.detail"),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| NamedLinks);
+        cx.run_until_parked();
+
+        // Hovering shows the resolved URL
+        cx.simulate_mouse_move(point(px(5.), px(5.)), None, Modifiers::none());
+        cx.executor().advance_clock(Duration::from_millis(600));
+        cx.run_until_parked();
+        assert!(cx
+            .debug_bounds("router-link-tooltip /users/42?tab=posts")
+            .is_some());
+
+        cx.simulate_click(point(px(5.), px(5.)), Modifiers::none());
+        assert_eq!(
+            cx.update(|_, cx| Navigator::current_path(cx)),
+            "/users/42?tab=posts"
+        );
+
+        // The unresolvable link ignores clicks
+        cx.simulate_click(point(px(5.), px(40.)), Modifiers::none());
+        assert_eq!(
+            cx.update(|_, cx| Navigator::current_path(cx)),
+            "/users/42?tab=posts"
+        );
+    }
+
+    #[gpui::test]
+    fn test_router_link_preserve_query(cx: &mut gpui::TestAppContext) {
+        use crate::{Navigator, RouterConfig};
+        use gpui::{point, px, Modifiers};
+
+        cx.update(|cx| {
+            crate::init_router(cx, |router| {
+                router.set_config(RouterConfig::new().preserve_query_keys(["theme"]));
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(Route::new("/target", |_, _, _| div().into_any_element()));
+            });
+            Navigator::replace(cx, "/?theme=dark&page=2");
+        });
+        let (view, cx) = cx.add_window_view(|_, _| LinkView {
+            opened: None,
+            preserve_query: Some(true),
+        });
+        cx.run_until_parked();
+        let position = point(px(10.), px(10.));
+        let current =
+            |cx: &mut gpui::VisualTestContext| cx.update(|_, cx| Navigator::current_path(cx));
+
+        cx.simulate_click(position, Modifiers::none());
+        assert_eq!(current(cx), "/target?theme=dark&page=2");
+
+        // Turned off, not even the router's keys are kept
+        cx.update(|window, cx| {
+            Navigator::replace(cx, "/?theme=dark");
+            view.update(cx, |view, _| view.preserve_query = Some(false));
+            window.refresh();
+        });
+        cx.run_until_parked();
+        cx.simulate_click(position, Modifiers::none());
+        assert_eq!(current(cx), "/target");
+    }
+
+    #[gpui::test]
+    fn test_router_link_uses_new_window_handler(cx: &mut gpui::TestAppContext) {
+        use crate::{GlobalRouter, Navigator};
+        use gpui::{point, px, BorrowAppContext, Modifiers};
+
+        let cx = link_window(cx, None);
+        let position = point(px(10.), px(10.));
+
+        // Without any handler a modifier click navigates
+        cx.simulate_click(position, Modifiers::secondary_key());
+        assert_eq!(cx.update(|_, cx| Navigator::current_path(cx)), "/target");
+
+        let opened = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = opened.clone();
+        cx.update(|_, cx| {
+            Navigator::push(cx, "/");
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.set_new_window_handler(move |path, _| {
+                    recorded.lock().unwrap().push(path.to_string());
+                });
+            });
+        });
+        cx.simulate_click(position, Modifiers::secondary_key());
+        assert_eq!(*opened.lock().unwrap(), vec!["/target"]);
+        assert_eq!(cx.update(|_, cx| Navigator::current_path(cx)), "/");
+    }
+
+    #[gpui::test]
+    fn test_router_link_context_menu(cx: &mut gpui::TestAppContext) {
+        use super::{LinkMenu, LinkMenuItem};
+        use crate::Navigator;
+        use gpui::{point, px, Modifiers, MouseButton};
+
+        let cx = link_window(cx, None);
+        let position = point(px(10.), px(10.));
+
+        cx.simulate_mouse_down(position, MouseButton::Right, Modifiers::none());
+        cx.simulate_mouse_up(position, MouseButton::Right, Modifiers::none());
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            let menu = cx.global::<LinkMenu>().clone();
+            assert_eq!(menu.path, "/target");
+            assert_eq!(Navigator::current_path(cx), "/");
+
+            LinkMenuItem::CopyPath.run(&menu, cx);
+            assert_eq!(
+                cx.read_from_clipboard().and_then(|item| item.text()),
+                Some("/target".to_string())
+            );
+            LinkMenuItem::Open.run(&menu, cx);
+            assert_eq!(Navigator::current_path(cx), "/target");
+        });
+    }
+
+    /// View with a full-size back button
+    struct BackButtonView;
+
+    impl gpui::Render for BackButtonView {
+        fn render(
+            &mut self,
+            _window: &mut gpui::Window,
+            cx: &mut gpui::Context<'_, Self>,
+        ) -> impl IntoElement {
+            use gpui::Styled;
+
+            div()
+                .size_full()
+                .child(super::BackButton::new().child("Back").build(cx).size_full())
+        }
+    }
+
+    /// Window with a back button after visiting `/a`, `/b` and `/c`
+    fn history_window(cx: &mut gpui::TestAppContext) -> &mut gpui::VisualTestContext {
+        use crate::Navigator;
+
+        cx.update(|cx| {
+            crate::init_router(cx, |router| {
+                for path in ["/", "/a", "/b", "/c"] {
+                    router.add_route(Route::new(path, |_, _, _| div().into_any_element()));
+                }
+                router.add_route(
+                    Route::new("/named", |_, _, _| div().into_any_element())
+                        .name("named")
+                        .meta_typed(crate::RouteTitle::new("Named page")),
+                );
+            });
+        });
+        let (_, cx) = cx.add_window_view(|_, _| BackButtonView);
+        cx.update(|window, cx| {
+            for path in ["/named", "/a", "/b"] {
+                Navigator::push(cx, path);
+            }
+            window.refresh();
+        });
+        cx.run_until_parked();
+        cx
+    }
+
+    #[gpui::test]
+    fn test_history_menu_entries(cx: &mut gpui::TestAppContext) {
+        use super::{HistoryDirection, HistoryMenu, HistoryMenuEntry};
+        use crate::Navigator;
+
+        let cx = history_window(cx);
+        cx.update(|_, cx| {
+            let entry = |index: usize, path: &str, label: &str, steps: usize| HistoryMenuEntry {
+                index,
+                path: path.to_string(),
+                label: label.to_string(),
+                steps,
+            };
+            let back = HistoryMenu::new(HistoryDirection::Back);
+            assert_eq!(
+                back.entries(cx),
+                [
+                    entry(2, "/a", "/a", 1),
+                    entry(1, "/named", "Named page", 2),
+                    entry(0, "/", "/", 3),
+                ]
+            );
+            assert!(HistoryMenu::new(HistoryDirection::Forward)
+                .entries(cx)
+                .is_empty());
+
+            Navigator::go_to_index(cx, 1);
+            let forward = HistoryMenu::new(HistoryDirection::Forward).max_entries(1);
+            assert_eq!(forward.entries(cx), [entry(2, "/a", "/a", 1)]);
+        });
+    }
+
+    #[gpui::test]
+    fn test_back_button_menu(cx: &mut gpui::TestAppContext) {
+        use super::{HistoryDirection, HistoryMenu};
+        use crate::{GlobalRouter, Navigator};
+        use gpui::{point, px, Modifiers, MouseButton};
+
+        let cx = history_window(cx);
+        let position = point(px(10.), px(10.));
+
+        // A right click opens the menu; picking an entry jumps there at once
+        cx.simulate_mouse_down(position, MouseButton::Right, Modifiers::none());
+        cx.simulate_mouse_up(position, MouseButton::Right, Modifiers::none());
+        cx.run_until_parked();
+        assert!(cx.update(|_, cx| HistoryMenu::is_open(cx, HistoryDirection::Back)));
+        let events = cx.update(|_, cx| cx.global::<GlobalRouter>().state().history().len());
+        let entry = cx.debug_bounds("history-menu-entry 1").unwrap().center();
+        cx.simulate_click(entry, Modifiers::none());
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/named");
+            assert_eq!(cx.global::<GlobalRouter>().state().history().len(), events);
+            assert!(!HistoryMenu::is_open(cx, HistoryDirection::Back));
+        });
+
+        // A click goes back one entry
+        cx.simulate_click(position, Modifiers::none());
+        cx.run_until_parked();
+        assert_eq!(cx.update(|_, cx| Navigator::current_path(cx)), "/");
+
+        // Nothing left behind: the button is inert
+        cx.simulate_mouse_down(position, MouseButton::Right, Modifiers::none());
+        cx.run_until_parked();
+        assert!(!cx.update(|_, cx| HistoryMenu::is_open(cx, HistoryDirection::Back)));
+    }
+
+    #[gpui::test]
+    fn test_back_button_long_press(cx: &mut gpui::TestAppContext) {
+        use super::{HistoryDirection, HistoryMenu, LONG_PRESS};
+        use crate::Navigator;
+        use gpui::{point, px, Modifiers, MouseButton};
+
+        let cx = history_window(cx);
+        let position = point(px(10.), px(10.));
+
+        cx.simulate_mouse_down(position, MouseButton::Left, Modifiers::none());
+        cx.executor().advance_clock(LONG_PRESS);
+        cx.run_until_parked();
+        cx.simulate_mouse_up(position, MouseButton::Left, Modifiers::none());
+        cx.run_until_parked();
+        cx.update(|_, cx| {
+            assert!(HistoryMenu::is_open(cx, HistoryDirection::Back));
+            // Releasing after the menu opened does not go back
+            assert_eq!(Navigator::current_path(cx), "/b");
+        });
+    }
+
+    #[gpui::test]
+    #[should_panic(expected = "boom")]
+    fn test_builder_panics_propagate_when_not_caught(cx: &mut gpui::TestAppContext) {
+        use crate::Navigator;
+        use std::sync::atomic::AtomicUsize;
+
+        let config = crate::RouterConfig::new().catch_builder_panics(false);
+        init_broken_routes(cx, config, &Arc::new(AtomicUsize::new(0)), &Arc::default());
+        cx.update(|cx| Navigator::push(cx, "/broken"));
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    fn test_split_view_redraws_only_detail_pane(cx: &mut gpui::TestAppContext) {
+        use super::SplitRouterView;
+        use crate::Navigator;
+        use gpui::InteractiveElement;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let list_builds = Arc::new(AtomicUsize::new(0));
+        let detail_builds = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| {
+            let (list, detail) = (Arc::clone(&list_builds), Arc::clone(&detail_builds));
+            crate::init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(
+                    Route::component("/inbox", || {
+                        SplitRouterView::new("list", "detail").detail_placeholder(|| {
+                            div()
+                                .debug_selector(|| "split-placeholder".into())
+                                .into_any_element()
+                        })
+                    })
+                    .named_outlet(
+                        "list",
+                        vec![Arc::new(Route::new("", move |_, _, _| {
+                            list.fetch_add(1, Ordering::SeqCst);
+                            div()
+                                .debug_selector(|| "split-list".into())
+                                .into_any_element()
+                        }))],
+                    )
+                    .named_outlet(
+                        "detail",
+                        vec![Arc::new(Route::new("items/:id", move |_, _, params| {
+                            detail.fetch_add(1, Ordering::SeqCst);
+                            let id = params.get("id").cloned().unwrap_or_default();
+                            div()
+                                .debug_selector(move || format!("split-detail {}", id))
+                                .into_any_element()
+                        }))],
+                    ),
+                );
+            });
+            Navigator::push(cx, "/inbox");
+        });
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("split-list").is_some());
+        assert!(cx.debug_bounds("split-placeholder").is_some());
+        assert_eq!(detail_builds.load(Ordering::SeqCst), 0);
+
+        let list_before = list_builds.load(Ordering::SeqCst);
+        cx.update(|_, cx| Navigator::set_outlet(cx, "detail", "items/42"));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("split-detail 42").is_some());
+        assert!(detail_builds.load(Ordering::SeqCst) > 0);
+        assert_eq!(list_builds.load(Ordering::SeqCst), list_before);
+        assert_eq!(cx.update(|_, cx| Navigator::current_path(cx)), "/inbox");
+
+        cx.update(|_, cx| Navigator::set_outlet(cx, "detail", "items/7"));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("split-detail 7").is_some());
+        assert_eq!(list_builds.load(Ordering::SeqCst), list_before);
+
+        cx.update(|_, cx| Navigator::clear_outlet(cx, "detail"));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("split-placeholder").is_some());
+    }
+
+    #[gpui::test]
+    fn test_outlet_not_found_details(cx: &mut gpui::TestAppContext) {
+        use crate::{init_router, render_router_outlet, ErrorHandlers, Navigator, NotFoundDetails};
+
+        let seen: Arc<std::sync::Mutex<Vec<NotFoundDetails>>> = Arc::default();
+        cx.update(|cx| {
+            let seen = Arc::clone(&seen);
+            init_router(cx, move |router| {
+                router.set_error_handlers(ErrorHandlers::new().on_not_found(move |_, details| {
+                    seen.lock().unwrap().push(details.clone());
+                    div().into_any_element()
+                }));
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(
+                    Route::new("/settings", |window, cx, _| {
+                        div().child(render_router_outlet(window, cx, None))
+                    })
+                    .children(vec![Route::new("profile", |_, _, _| div()).into()]),
+                );
+            });
+        });
+
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        let navigate = |cx: &mut gpui::VisualTestContext, path: &'static str| {
+            cx.update(|window, cx| {
+                Navigator::push(cx, path);
+                window.refresh();
+            });
+            cx.run_until_parked();
+        };
+
+        navigate(cx, "/setings");
+        navigate(cx, "/settings/profil");
+        let seen = seen.lock().unwrap();
+        let last = seen.last().unwrap();
+        assert_eq!(last.matched_prefix, "/settings");
+        assert_eq!(last.suggestions(), ["profile"]);
+        let first = seen.first().unwrap();
+        assert_eq!(first.attempted, "/setings");
+        assert_eq!(first.suggestions(), ["settings"]);
+    }
+
+    /// Router whose `/` page pushes `/login` from its builder, counting pushes
+    fn redirecting_builder_router(
+        cx: &mut gpui::TestAppContext,
+        policy: crate::RenderNavigationPolicy,
+    ) -> Arc<std::sync::atomic::AtomicUsize> {
+        use crate::{init_router, Navigator, RouterConfig};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let pushes = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| {
+            let pushes = Arc::clone(&pushes);
+            init_router(cx, move |router| {
+                router.set_config(RouterConfig::new().on_render_navigation(policy));
+                router.add_route(Route::new("/", move |_, cx, _| {
+                    pushes.fetch_add(1, Ordering::SeqCst);
+                    Navigator::push(cx, "/login");
+                    div()
+                }));
+                router.add_route(Route::new("/login", |_, _, _| div()));
+            });
+        });
+        pushes
+    }
+
+    #[gpui::test]
+    fn test_navigation_during_render_is_deferred(cx: &mut gpui::TestAppContext) {
+        use crate::{GlobalRouter, Navigator, RenderNavigationPolicy};
+        use std::sync::atomic::Ordering;
+
+        let pushes = redirecting_builder_router(cx, RenderNavigationPolicy::Defer);
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+
+        assert_eq!(pushes.load(Ordering::SeqCst), 1);
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/login");
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.state().history().len(), 2);
+            assert!(!router.state().in_render());
+        });
+    }
+
+    #[gpui::test]
+    fn test_navigation_during_render_panics(cx: &mut gpui::TestAppContext) {
+        use crate::{GlobalRouter, Navigator, RenderNavigationPolicy};
+
+        redirecting_builder_router(cx, RenderNavigationPolicy::Panic);
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+
+        // The panic is rendered like any builder panic; nothing navigated
+        cx.update(|_, cx| {
+            assert_eq!(Navigator::current_path(cx), "/");
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.state().history().len(), 1);
+            assert!(!router.state().in_render());
+        });
+    }
+
+    /// Router with one route at `/` recording `label` when it is built
+    fn labelled_router(
+        cx: &mut gpui::App,
+        built: &Arc<std::sync::Mutex<Vec<&'static str>>>,
+        label: &'static str,
+    ) {
+        let built = Arc::clone(built);
+        crate::init_router(cx, move |router| {
+            router.set_config(crate::RouterConfig::new().on_reinit(crate::ReinitPolicy::Replace));
+            router.add_route(Route::new("/", move |_, _, _| {
+                built.lock().unwrap().push(label);
+                div()
+            }));
+        });
+    }
+
+    #[gpui::test]
+    fn test_outlet_follows_a_replaced_router(cx: &mut gpui::TestAppContext) {
+        use crate::GlobalRouter;
+
+        let built = Arc::new(std::sync::Mutex::new(Vec::new()));
+        cx.update(|cx| labelled_router(cx, &built, "old"));
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        cx.run_until_parked();
+        assert_eq!(built.lock().unwrap().last(), Some(&"old"));
+
+        let generation = cx.update(|_, cx| cx.global::<GlobalRouter>().generation());
+        cx.update(|_, cx| labelled_router(cx, &built, "new"));
+        cx.run_until_parked();
+
+        assert_eq!(built.lock().unwrap().last(), Some(&"new"));
+        cx.update(|_, cx| assert!(cx.global::<GlobalRouter>().generation() > generation));
+    }
+
+    #[gpui::test]
+    fn test_outlet_waits_for_a_missing_router(cx: &mut gpui::TestAppContext) {
+        use crate::GlobalRouter;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let built = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let placeholders = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| labelled_router(cx, &built, "first"));
+        let counter = Arc::clone(&placeholders);
+        let (_, cx) = cx.add_window_view(move |_, _| {
+            let counter = Arc::clone(&counter);
+            RouterOutlet::new().placeholder(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                div().into_any_element()
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(placeholders.load(Ordering::SeqCst), 0);
+
+        // Without a router the outlet shows its placeholder
+        cx.update(|window, cx| {
+            cx.remove_global::<GlobalRouter>();
+            window.refresh();
+        });
+        cx.run_until_parked();
+        assert!(placeholders.load(Ordering::SeqCst) > 0);
+
+        // and renders the routes of the next router installed
+        cx.update(|_, cx| labelled_router(cx, &built, "second"));
+        cx.run_until_parked();
+        assert_eq!(built.lock().unwrap().last(), Some(&"second"));
+    }
 }
@@ -0,0 +1,219 @@
+//! Window options per route
+//!
+//! Routes can ask for a window title, minimum size or resizability while
+//! they are shown:
+//!
+//! ```ignore
+//! use gpui_navigator::{init_router, Route, WindowHints};
+//!
+//! init_router(cx, |router| {
+//!     router.set_base_window_hints(WindowHints::new().title("Workspace"));
+//!     router.add_route(Route::new("/", |_, _, _| workspace()));
+//!     router.add_route(
+//!         Route::new("/settings", |_, _, _| settings())
+//!             .window_hints(WindowHints::new().title("Settings").min_size(size(px(640.), px(480.)))),
+//!     );
+//! });
+//! ```
+//!
+//! After a navigation commits, `RouterOutlet` applies the hints of the matched
+//! routes to its window: the base hints, overridden by each route of the
+//! chain from the outermost in, so nested routes inherit their parent's hints.
+//! Leaving a route restores the base hints for whatever the next route does
+//! not set, so give the base hints a value for every field routes override.
+//!
+//! What applying means is up to the app: the default applier only sets the
+//! title, as GPUI cannot change a window's minimum size or resizability once
+//! it is open. Install another with
+//! [`GlobalRouter::set_window_hint_applier`](crate::GlobalRouter::set_window_hint_applier).
+
+use crate::context::GlobalRouter;
+use crate::trace_log;
+use gpui::{App, BorrowAppContext, Pixels, Size, Window};
+use std::sync::Arc;
+
+/// Window options a route asks for while it is shown
+///
+/// Fields left `None` have no preference and keep the value of the parent
+/// route or the base hints.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowHints {
+    /// Window title
+    pub title: Option<String>,
+    /// Minimum window size
+    pub min_size: Option<Size<Pixels>>,
+    /// Whether the user can resize the window
+    pub resizable: Option<bool>,
+}
+
+impl WindowHints {
+    /// Hints without any preference
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask for a window title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Ask for a minimum window size
+    pub fn min_size(mut self, size: Size<Pixels>) -> Self {
+        self.min_size = Some(size);
+        self
+    }
+
+    /// Ask for the window to be resizable or not
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = Some(resizable);
+        self
+    }
+
+    /// These hints with the fields `other` sets taking precedence
+    pub fn merged(&self, other: &WindowHints) -> WindowHints {
+        WindowHints {
+            title: other.title.clone().or_else(|| self.title.clone()),
+            min_size: other.min_size.or(self.min_size),
+            resizable: other.resizable.or(self.resizable),
+        }
+    }
+}
+
+/// Applies [`WindowHints`] to a window
+pub type WindowHintApplier = Arc<dyn Fn(&WindowHints, &mut Window, &mut App) + Send + Sync>;
+
+/// The default [`WindowHintApplier`]: sets the window title
+pub fn apply_window_title(hints: &WindowHints, window: &mut Window, _cx: &mut App) {
+    if let Some(title) = &hints.title {
+        window.set_window_title(title);
+    }
+}
+
+/// Hints of the current route, unless they are the hints applied last, or
+/// the base hints before any route set its own
+pub(crate) fn pending_window_hints(router: &GlobalRouter) -> Option<WindowHints> {
+    let hints = router.window_hints(router.current_path());
+    let unchanged = match &router.applied_window_hints {
+        Some(applied) => *applied == hints,
+        None => hints == *router.base_window_hints(),
+    };
+    (!unchanged).then_some(hints)
+}
+
+/// Apply the current route's window hints to `window`
+///
+/// Does nothing while they are the hints applied last, or the base hints
+/// before any route set its own. The router applies them to the windows
+/// showing a `RouterOutlet` after each navigation; call this yourself for
+/// windows rendering routes without one.
+pub fn apply_window_hints(window: &mut Window, cx: &mut App) {
+    let Some(hints) = cx
+        .try_global::<GlobalRouter>()
+        .and_then(pending_window_hints)
+    else {
+        return;
+    };
+    let router = cx.global::<GlobalRouter>();
+
+    trace_log!("Applying window hints {:?}", hints);
+    let applier = Arc::clone(router.window_hint_applier());
+    cx.update_global::<GlobalRouter, _>(|router, _| {
+        router.applied_window_hints = Some(hints.clone());
+    });
+    applier(&hints, window, cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{init_router, Navigator, Route, RouterOutlet};
+    use gpui::{div, px, size, TestAppContext, VisualTestContext};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_merged_hints() {
+        let parent = WindowHints::new()
+            .title("Settings")
+            .min_size(size(px(600.), px(400.)));
+        let merged = parent.merged(&WindowHints::new().title("Advanced").resizable(false));
+        assert_eq!(merged.title.as_deref(), Some("Advanced"));
+        assert_eq!(merged.min_size, Some(size(px(600.), px(400.))));
+        assert_eq!(merged.resizable, Some(false));
+        assert_eq!(parent.merged(&WindowHints::new()), parent);
+    }
+
+    #[gpui::test]
+    fn test_window_hints_applied_per_navigation(cx: &mut TestAppContext) {
+        let applied: Arc<Mutex<Vec<WindowHints>>> = Arc::default();
+        cx.update(|cx| {
+            let applied = Arc::clone(&applied);
+            init_router(cx, move |router| {
+                router.set_base_window_hints(WindowHints::new().title("Workspace"));
+                router.set_window_hint_applier(move |hints, _, _| {
+                    applied.lock().unwrap().push(hints.clone());
+                });
+                router.add_route(Route::new("/", |_, _, _| div()));
+                router.add_route(Route::new("/about", |_, _, _| div()));
+                router.add_route(
+                    Route::new("/settings", |_, _, _| div())
+                        .window_hints(
+                            WindowHints::new()
+                                .title("Settings")
+                                .min_size(size(px(600.), px(400.))),
+                        )
+                        .children(vec![
+                            Route::new("", |_, _, _| div()).into(),
+                            Route::new("advanced", |_, _, _| div())
+                                .window_hints(WindowHints::new().title("Advanced"))
+                                .into(),
+                        ]),
+                );
+                router.add_route(
+                    Route::new("/quick-capture", |_, _, _| div())
+                        .window_hints(WindowHints::new().title("Quick Capture").resizable(false)),
+                );
+            });
+        });
+
+        let (_, cx) = cx.add_window_view(|_, _| RouterOutlet::new());
+        // Push `path`, or go back without one
+        let navigate = |cx: &mut VisualTestContext, path: Option<&str>| {
+            cx.update(|window, cx| {
+                if let Some(path) = path {
+                    Navigator::push(cx, path);
+                } else {
+                    Navigator::pop(cx);
+                }
+                window.refresh();
+            });
+            cx.run_until_parked();
+        };
+        let settings = WindowHints::new()
+            .title("Settings")
+            .min_size(size(px(600.), px(400.)));
+
+        // Routes without hints leave the window alone
+        navigate(cx, Some("/about"));
+        assert!(applied.lock().unwrap().is_empty());
+
+        navigate(cx, Some("/settings"));
+        navigate(cx, Some("/settings/advanced"));
+        navigate(cx, Some("/quick-capture"));
+        navigate(cx, None);
+        navigate(cx, None);
+        navigate(cx, None);
+        assert_eq!(cx.update(|_, cx| Navigator::current_path(cx)), "/about");
+        assert_eq!(
+            *applied.lock().unwrap(),
+            [
+                settings.clone(),
+                settings.clone().title("Advanced"),
+                WindowHints::new().title("Quick Capture").resizable(false),
+                settings.clone().title("Advanced"),
+                settings,
+                WindowHints::new().title("Workspace"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Allocations made by the read-side router APIs
+//!
+//! Links and nav items read the current path on every render, so these
+//! reads must not allocate. The allocator of this test binary counts the
+//! (de)allocations made inside `assert_no_alloc`; it only does so in debug
+//! builds.
+#![cfg(debug_assertions)]
+
+use assert_no_alloc::{assert_no_alloc, reset_violation_count, violation_count, AllocDisabler};
+use gpui::{div, IntoElement, TestAppContext};
+use gpui_navigator::*;
+
+#[global_allocator]
+static ALLOCATOR: AllocDisabler = AllocDisabler;
+
+/// Reads per measurement, as in a second of frames across many links
+const ITERATIONS: usize = 10_000;
+
+/// Allocator calls `read` makes over [`ITERATIONS`] calls
+///
+/// The count is kept per thread, so tests running alongside do not add to it.
+fn allocations(mut read: impl FnMut()) -> u32 {
+    reset_violation_count();
+    assert_no_alloc(|| {
+        for _ in 0..ITERATIONS {
+            read();
+        }
+    });
+    violation_count()
+}
+
+fn init(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        init_router(cx, |router| {
+            router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+            router.add_route(Route::new("/users/:id/posts", |_, _, _| {
+                div().into_any_element()
+            }));
+            router.add_route(Route::new("/settings", |_, _, _| div().into_any_element()));
+        });
+        Navigator::push(cx, "/users/7/posts#latest");
+    });
+}
+
+#[gpui::test]
+fn test_current_path_reads_do_not_allocate(cx: &mut TestAppContext) {
+    init(cx);
+    cx.read(|cx| {
+        let shared = allocations(|| {
+            std::hint::black_box(Navigator::current_path(cx));
+        });
+        let borrowed = allocations(|| {
+            std::hint::black_box(Navigator::with_current_path(cx, str::len));
+        });
+        #[allow(deprecated)]
+        let owned = allocations(|| {
+            std::hint::black_box(Navigator::current_path_string(cx));
+        });
+
+        assert_eq!(shared, 0);
+        assert_eq!(borrowed, 0);
+        // The shim still copies the path on every call
+        assert!(owned as usize >= ITERATIONS, "{owned} allocator calls");
+    });
+}
+
+#[gpui::test]
+fn test_active_checks_of_static_paths_do_not_allocate(cx: &mut TestAppContext) {
+    init(cx);
+    cx.read(|cx| {
+        let checks = allocations(|| {
+            std::hint::black_box(Navigator::is_active(cx, "/users"));
+            std::hint::black_box(Navigator::is_active(cx, "/settings"));
+            std::hint::black_box(Navigator::is_active_exact(cx, "/users/7/posts"));
+            std::hint::black_box(Navigator::is_active_exact(cx, "/users/7"));
+        });
+        assert_eq!(checks, 0);
+
+        assert!(Navigator::is_active(cx, "/users"));
+        assert!(!Navigator::is_active(cx, "/settings"));
+        assert!(Navigator::is_active_exact(cx, "/users/7/posts"));
+        assert!(!Navigator::is_active_exact(cx, "/users/7"));
+        // Patterns with params are matched in full
+        assert!(Navigator::is_active_exact(cx, "/users/:id/posts"));
+        assert!(!Navigator::is_active_exact(cx, "/users/:id"));
+    });
+}
@@ -0,0 +1,11 @@
+//! Route paths `route_path!` rejects at compile time
+//!
+//! The expected errors are in `tests/ui/*.stderr`; regenerate them with
+//! `TRYBUILD=overwrite cargo test --test compile_fail` after changing the
+//! rules in `gpui-navigator-paths`.
+
+#[test]
+fn route_path_rejects_invalid_paths() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -3,8 +3,20 @@
 //! These tests verify the complete router workflow including initialization,
 //! navigation, guards, and route matching.
 
+#![allow(
+    clippy::unused_async,
+    clippy::future_not_send,
+    clippy::redundant_closure
+)]
+
 use gpui::{div, IntoElement, ParentElement, TestAppContext};
+#[cfg(feature = "guard")]
+use gpui_navigator::testing::MockGuard;
+#[cfg(all(feature = "guard", feature = "middleware"))]
+use gpui_navigator::testing::MockMiddleware;
+use gpui_navigator::testing::TestRouter;
 use gpui_navigator::*;
+use std::sync::{Arc, Mutex};
 
 // ============================================================================
 // Router Initialization Tests
@@ -42,10 +54,9 @@ async fn test_router_with_named_routes(cx: &mut TestAppContext) {
     });
 
     // Test URL generation
-    let mut params = RouteParams::new();
-    params.set("id".to_string(), "123".to_string());
+    let params = RouteParams::single("id", "123");
 
-    let url = cx.read(|cx| Navigator::url_for(cx, "user-profile", &params));
+    let url = cx.read(|cx| Navigator::try_url_for(cx, "user-profile", &params).ok());
     assert_eq!(url, Some("/users/123".to_string()));
 }
 
@@ -135,6 +146,44 @@ async fn test_forward_navigation(cx: &mut TestAppContext) {
     assert_eq!(cx.read(Navigator::current_path), "/page1");
 }
 
+#[gpui::test]
+fn test_push_to_forward_entry_keeps_forward_stack(cx: &mut TestAppContext) {
+    let routes = |router: &mut GlobalRouter| {
+        for path in ["/", "/a", "/b", "/c"] {
+            router.add_route(Route::new(path, |_, _, _| div().into_any_element()));
+        }
+    };
+    let visit = |cx: &mut TestAppContext| {
+        cx.update(|cx| {
+            for path in ["/a", "/b", "/c"] {
+                Navigator::push(cx, path);
+            }
+            for _ in 0..3 {
+                Navigator::pop(cx);
+            }
+            Navigator::push(cx, "/a");
+        });
+    };
+
+    cx.update(|cx| init_router(cx, routes));
+    visit(cx);
+    assert_eq!(cx.read(Navigator::current_path), "/a");
+    assert!(cx.read(Navigator::can_go_forward));
+    cx.update(Navigator::forward);
+    cx.update(Navigator::forward);
+    assert_eq!(cx.read(Navigator::current_path), "/c");
+
+    cx.update(|cx| {
+        init_router(cx, |router| {
+            router.set_config(RouterConfig::new().reuse_forward_on_push(false));
+            routes(router);
+        });
+    });
+    visit(cx);
+    assert_eq!(cx.read(Navigator::current_path), "/a");
+    assert!(!cx.read(Navigator::can_go_forward));
+}
+
 // ============================================================================
 // Route Parameters Tests
 // ============================================================================
@@ -158,10 +207,11 @@ async fn test_route_params_extraction(cx: &mut TestAppContext) {
 
 #[test]
 fn test_route_params_type_conversion() {
-    let mut params = RouteParams::new();
-    params.insert("id".to_string(), "123".to_string());
-    params.insert("active".to_string(), "true".to_string());
-    params.insert("invalid".to_string(), "not_a_number".to_string());
+    let params = RouteParams::from([
+        ("id", "123"),
+        ("active", "true"),
+        ("invalid", "not_a_number"),
+    ]);
 
     assert_eq!(params.get_as::<i32>("id"), Some(123));
     assert_eq!(params.get_as::<bool>("active"), Some(true));
@@ -198,6 +248,7 @@ fn test_query_params_multiple_values() {
 // Guard Tests
 // ============================================================================
 
+#[cfg(feature = "guard")]
 #[gpui::test]
 async fn test_auth_guard_allows_authenticated(cx: &mut TestAppContext) {
     let guard = AuthGuard::new(|_| true, "/login");
@@ -208,6 +259,7 @@ async fn test_auth_guard_allows_authenticated(cx: &mut TestAppContext) {
     assert!(result.is_allow());
 }
 
+#[cfg(feature = "guard")]
 #[gpui::test]
 async fn test_auth_guard_redirects_unauthenticated(cx: &mut TestAppContext) {
     let guard = AuthGuard::new(|_| false, "/login");
@@ -219,6 +271,7 @@ async fn test_auth_guard_redirects_unauthenticated(cx: &mut TestAppContext) {
     assert_eq!(result.redirect_path(), Some("/login"));
 }
 
+#[cfg(feature = "guard")]
 #[gpui::test]
 async fn test_role_guard_allows_correct_role(cx: &mut TestAppContext) {
     let guard = RoleGuard::new(|_| Some("admin".to_string()), "admin", None::<String>);
@@ -229,6 +282,7 @@ async fn test_role_guard_allows_correct_role(cx: &mut TestAppContext) {
     assert!(result.is_allow());
 }
 
+#[cfg(feature = "guard")]
 #[gpui::test]
 async fn test_role_guard_denies_wrong_role(cx: &mut TestAppContext) {
     let guard = RoleGuard::new(|_| Some("user".to_string()), "admin", None::<String>);
@@ -239,6 +293,7 @@ async fn test_role_guard_denies_wrong_role(cx: &mut TestAppContext) {
     assert!(result.is_deny());
 }
 
+#[cfg(feature = "guard")]
 #[gpui::test]
 async fn test_permission_guard(cx: &mut TestAppContext) {
     let guard = PermissionGuard::new(|_, perm| perm == "users.read", "users.read");
@@ -249,10 +304,271 @@ async fn test_permission_guard(cx: &mut TestAppContext) {
     assert!(result.is_allow());
 }
 
+#[cfg(all(feature = "guard", feature = "middleware"))]
+#[gpui::test]
+fn test_guarded_navigation_pipeline(cx: &mut TestAppContext) {
+    let auth = MockGuard::redirect("/login");
+    let audit = MockMiddleware::new();
+    let mut router = TestRouter::new(cx).page("/login").route(
+        Route::new("/admin", |_, _, _| div().into_any_element())
+            .guard(auth.clone())
+            .middleware(audit.clone()),
+    );
+
+    let result = router.navigate("/admin");
+    assert_eq!(result.redirect_path(), Some("/login"));
+    router.assert_current("/login");
+    assert_eq!(router.rendered_route_names(), vec!["/login"]);
+
+    auth.set_result(GuardResult::deny("read only"));
+    assert!(router.navigate("/admin").is_blocked());
+    router.assert_current("/login");
+
+    auth.set_result(GuardResult::allow());
+    assert!(router.navigate("/admin").is_success());
+    router.assert_current("/admin");
+
+    assert_eq!(auth.calls(), vec!["/admin", "/admin", "/admin"]);
+    // Middleware runs before guards, and after only when navigation happened
+    assert_eq!(
+        audit.calls(),
+        vec![
+            "before /admin",
+            "before /admin",
+            "before /admin",
+            "after /admin"
+        ]
+    );
+}
+
+#[cfg(all(feature = "guard", feature = "middleware"))]
+#[gpui::test]
+fn test_analytics_skips_guard_redirects(cx: &mut TestAppContext) {
+    let views = Arc::new(Mutex::new(Vec::new()));
+    let captured = views.clone();
+    let mut router = TestRouter::new(cx).page("/login").route(
+        Route::new("/admin", |_, _, _| div().into_any_element())
+            .guard(MockGuard::redirect("/login")),
+    );
+    router.cx().update(|_, cx| {
+        cx.global_mut::<GlobalRouter>()
+            .add_middleware(AnalyticsMiddleware::new(move |view: ScreenView| {
+                captured.lock().unwrap().push(view);
+            }));
+    });
+
+    assert!(router.navigate("/admin").is_blocked());
+    router.assert_current("/login");
+
+    let views = views.lock().unwrap();
+    assert_eq!(views.len(), 1);
+    assert_eq!(views[0].path, "/login");
+    assert_eq!(views[0].referrer.as_deref(), Some("/"));
+    assert_eq!(
+        views[0].source,
+        NavigationSource::Redirect {
+            from_guard: "MockGuard".to_string()
+        }
+    );
+}
+
+#[gpui::test]
+fn test_nested_routes_render_parent_and_child(cx: &mut TestAppContext) {
+    let mut router = TestRouter::new(cx).route(
+        Route::new("/dashboard", |window, cx, _| {
+            div()
+                .child(render_router_outlet(window, cx, None))
+                .into_any_element()
+        })
+        .children(vec![
+            Route::new("overview", |_, _, _| div().into_any_element()).into(),
+            Route::new("analytics", |_, _, _| div().into_any_element())
+                .name("analytics")
+                .into(),
+        ]),
+    );
+
+    router.navigate("/dashboard/overview");
+    assert_eq!(
+        router.rendered_route_names(),
+        vec!["/dashboard", "/dashboard/overview"]
+    );
+
+    router.navigate("/dashboard/analytics");
+    assert_eq!(
+        router.rendered_route_names(),
+        vec!["/dashboard", "analytics"]
+    );
+    assert!(router.navigate("/missing").is_not_found());
+}
+
+#[cfg(feature = "guard")]
+#[gpui::test]
+fn test_group_layouts_wrap_children_without_a_segment(cx: &mut TestAppContext) {
+    let layout = |name: &str| {
+        Route::group_layout(|window, cx, _| div().child(render_router_outlet(window, cx, None)))
+            .name(name)
+    };
+    let page = |path: &str| Route::new(path, |_, _, _| div()).into();
+    let guard = MockGuard::allow();
+    let mut router = TestRouter::new(cx)
+        .route(
+            layout("auth")
+                .guard(guard.clone())
+                .children(vec![page("/login"), page("/register")]),
+        )
+        .route(layout("app").children(vec![
+            page("/inbox"),
+            Route::new("/settings", |window, cx, _| {
+                div().child(render_router_outlet(window, cx, None))
+            })
+            .children(vec![layout("settings-chrome")
+                .children(vec![page("profile")])
+                .into()])
+            .into(),
+        ]));
+
+    assert!(router.navigate("/login").is_success());
+    assert_eq!(router.rendered_route_names(), ["auth", "/login"]);
+    assert!(router.navigate("/inbox").is_success());
+    assert_eq!(router.rendered_route_names(), ["app", "/inbox"]);
+    assert!(router.navigate("/register").is_success());
+    assert_eq!(router.rendered_route_names(), ["auth", "/register"]);
+    // The layout's guard covers its children only
+    assert_eq!(guard.calls(), ["/login", "/register"]);
+
+    assert!(router.navigate("/settings/profile").is_success());
+    assert_eq!(
+        router.rendered_route_names(),
+        ["app", "/settings", "settings-chrome", "/settings/profile"]
+    );
+    let dump = router
+        .cx()
+        .update(|_, cx| cx.global::<GlobalRouter>().dump_state());
+    let matched: Vec<_> = dump
+        .matched
+        .iter()
+        .map(|route| route.path.as_str())
+        .collect();
+    assert_eq!(matched, ["/settings", "/settings/profile"]);
+    // Layout names are not routes to navigate to
+    assert!(router
+        .cx()
+        .update(|_, cx| Navigator::try_url_for(cx, "auth", &RouteParams::new()))
+        .is_err());
+}
+
+#[gpui::test]
+fn test_relative_navigation_from_nested_route(cx: &mut TestAppContext) {
+    let page = |_: &mut gpui::Window, _: &mut gpui::App, _: &RouteParams| div().into_any_element();
+    let mut router = TestRouter::new(cx)
+        .route(Route::new("/products", page).children(vec![
+            Route::new("", page).into(),
+            Route::new("list", page).into(),
+            Route::new(":id", page).into(),
+        ]))
+        .route(Route::new("/shops/:shop", page).children(vec![
+            Route::new("items/:item", page).into(),
+            Route::new("reviews", page).into(),
+        ]));
+
+    // Like the nested demo's "Back to List" button
+    router.navigate("/products/3");
+    assert!(router.navigate("../list").is_success());
+    router.assert_current("/products/list");
+    router.navigate("../2");
+    router.assert_current("/products/2");
+
+    // Params of the parent route keep their values
+    router.navigate("/shops/5/items/9");
+    assert!(router.navigate("../reviews").is_success());
+    router.assert_current("/shops/5/reviews");
+
+    router.navigate("/shops/5/items/9");
+    router.cx().update(|_, cx| {
+        Navigator::push(cx, "./details/");
+        assert_eq!(Navigator::current_path(cx), "/shops/5/items/9/details");
+        Navigator::push(cx, "../../../../..");
+        assert_eq!(Navigator::current_path(cx), "/");
+
+        // Bare paths are only relative when asked for
+        Navigator::push(cx, "/shops/5");
+        Navigator::push_opts(cx, "reviews", NavOptions::new().relative(true));
+        assert_eq!(Navigator::current_path(cx), "/shops/5/reviews");
+        assert_eq!(
+            Navigator::resolve_path(cx, "reviews", &NavOptions::new()),
+            "reviews"
+        );
+    });
+}
+
+#[gpui::test]
+fn test_nested_routes_propagate_parent_params(cx: &mut TestAppContext) {
+    type Seen = Arc<Mutex<Vec<(&'static str, RouteParams)>>>;
+    let seen = Seen::default();
+    let page = |label: &'static str, seen: &Seen, outlets: &'static [Option<&'static str>]| {
+        let seen = seen.clone();
+        move |window: &mut gpui::Window, cx: &mut gpui::App, params: &RouteParams| {
+            seen.lock().unwrap().push((label, params.clone()));
+            let mut layout = div();
+            for outlet in outlets {
+                layout = layout.child(render_router_outlet(window, cx, *outlet));
+            }
+            layout.into_any_element()
+        }
+    };
+
+    let mut router = TestRouter::new(cx).route(
+        Route::new("/orgs/:orgId", page("org", &seen, &[None])).children(vec![Route::new(
+            "teams/:teamId",
+            page("team", &seen, &[None, Some("sidebar")]),
+        )
+        .children(vec![Route::new(
+            "members/:memberId",
+            page("member", &seen, &[]),
+        )
+        .into()])
+        .named_outlet(
+            "sidebar",
+            vec![Route::new("members/:memberId", page("sidebar", &seen, &[])).into()],
+        )
+        .into()]),
+    );
+
+    router.navigate("/orgs/7/teams/3/members/42");
+    assert_eq!(
+        router.rendered_route_names(),
+        vec![
+            "/orgs/:orgId",
+            "/orgs/:orgId/teams/:teamId",
+            "/orgs/:orgId/teams/:teamId/members/:memberId",
+        ]
+    );
+
+    let seen = seen.lock().unwrap();
+    let params_of = |label| {
+        seen.iter()
+            .rev()
+            .find(|(seen_label, _)| *seen_label == label)
+            .map(|(_, params)| params.clone())
+            .unwrap_or_else(|| panic!("{} was not rendered", label))
+    };
+    for leaf in ["member", "sidebar"] {
+        let params = params_of(leaf);
+        assert_eq!(params.get("orgId"), Some(&"7".to_string()), "{}", leaf);
+        assert_eq!(params.get("teamId"), Some(&"3".to_string()), "{}", leaf);
+        assert_eq!(params.get("memberId"), Some(&"42".to_string()), "{}", leaf);
+    }
+    let team = params_of("team");
+    assert_eq!(team.get("orgId"), Some(&"7".to_string()));
+    assert_eq!(team.get("teamId"), Some(&"3".to_string()));
+}
+
 // ============================================================================
 // Guard Result Tests
 // ============================================================================
 
+#[cfg(feature = "guard")]
 #[test]
 fn test_guard_result_variants() {
     let allow = GuardResult::allow();
@@ -273,6 +589,7 @@ fn test_guard_result_variants() {
 // Transition Tests
 // ============================================================================
 
+#[cfg(feature = "transition")]
 #[test]
 fn test_transition_duration() {
     use std::time::Duration;
@@ -289,6 +606,7 @@ fn test_transition_duration() {
     );
 }
 
+#[cfg(feature = "transition")]
 #[test]
 fn test_transition_is_none() {
     assert!(Transition::None.is_none());
@@ -319,17 +637,14 @@ fn test_named_route_url_generation() {
     registry.register("user", "/users/:id");
     registry.register("post", "/users/:userId/posts/:postId");
 
-    let mut params = RouteParams::new();
-    params.set("id".to_string(), "42".to_string());
+    let params = RouteParams::single("id", "42");
 
-    let url = registry.url_for("user", &params);
+    let url = registry.try_url_for("user", &params).ok();
     assert_eq!(url, Some("/users/42".to_string()));
 
-    let mut params = RouteParams::new();
-    params.set("userId".to_string(), "1".to_string());
-    params.set("postId".to_string(), "99".to_string());
+    let params = RouteParams::from([("userId", "1"), ("postId", "99")]);
 
-    let url = registry.url_for("post", &params);
+    let url = registry.try_url_for("post", &params).ok();
     assert_eq!(url, Some("/users/1/posts/99".to_string()));
 }
 
@@ -434,6 +749,44 @@ async fn test_dynamic_route_matching(cx: &mut TestAppContext) {
     assert_eq!(cx.read(Navigator::current_path), "/posts/123");
 }
 
+#[gpui::test]
+fn test_route_path_macro(cx: &mut TestAppContext) {
+    const USER: PathPattern = route_path!("/users/:id");
+    assert_eq!(USER.as_str(), "/users/:id");
+
+    cx.update(|cx| {
+        init_router(cx, |router| {
+            router.add_route(Route::new(route_path!("/"), |_, _, _| {
+                div().into_any_element()
+            }));
+            router.add_route(
+                Route::new(USER, |_, _, params| {
+                    let id = params.get("id").cloned().unwrap_or_default();
+                    div().child(format!("User {}", id)).into_any_element()
+                })
+                .name("user"),
+            );
+            router.add_route(Route::new(route_path!("/files/*"), |_, _, _| {
+                div().into_any_element()
+            }));
+        });
+    });
+
+    cx.update(|cx| Navigator::push(cx, "/users/42"));
+    let route_match = cx.read(|cx| cx.global::<GlobalRouter>().current_match_immutable());
+    assert_eq!(
+        route_match.unwrap().params.get("id"),
+        Some(&"42".to_string())
+    );
+
+    cx.update(|cx| Navigator::push(cx, route_path!("/files/*")));
+    assert_eq!(cx.read(Navigator::current_path), "/files/*");
+
+    let mut registry = NamedRouteRegistry::new();
+    registry.register("user", USER);
+    assert_eq!(registry.get("user"), Some("/users/:id"));
+}
+
 // ============================================================================
 // Lifecycle Tests
 // ============================================================================
@@ -458,6 +811,7 @@ fn test_lifecycle_result_variants() {
 // Cache Tests
 // ============================================================================
 
+#[cfg(feature = "cache")]
 #[test]
 fn test_route_cache_stats() {
     let mut cache = RouteCache::new();
@@ -521,6 +875,7 @@ async fn test_error_handler_rendering(cx: &mut TestAppContext) {
     // Test error handler
     let error = NavigationError::RouteNotFound {
         path: "/test".to_string(),
+        details: None,
     };
     let error_element = cx.update(|cx| handlers.render_error(cx, &error));
     assert!(error_element.is_some());
@@ -561,6 +916,7 @@ async fn test_custom_error_pages(cx: &mut TestAppContext) {
 // Integration: Full Navigation Flow
 // ============================================================================
 
+#[cfg(feature = "transition")]
 #[gpui::test]
 async fn test_full_navigation_flow(cx: &mut TestAppContext) {
     // Initialize router with multiple routes
@@ -614,8 +970,38 @@ async fn test_full_navigation_flow(cx: &mut TestAppContext) {
     assert_eq!(cx.read(Navigator::current_path), "/users");
 
     // Navigate by name
-    let mut params = RouteParams::new();
-    params.set("id".to_string(), "99".to_string());
+    let params = RouteParams::single("id", "99");
     cx.update(|cx| Navigator::push_named(cx, "user-detail", &params));
     assert_eq!(cx.read(Navigator::current_path), "/users/99");
+
+    // try_push reports whether the path matches a route
+    let result = cx.update(|cx| Navigator::try_push(cx, "/users/7"));
+    assert!(result.is_success());
+    assert_eq!(cx.read(Navigator::current_path), "/users/7");
+
+    // Without strict navigation an unknown path is reported but still pushed
+    let result = cx.update(|cx| Navigator::try_push(cx, "/typo"));
+    assert!(result.is_not_found());
+    assert_eq!(cx.read(Navigator::current_path), "/typo");
+    assert!(cx.read(Navigator::last_error).is_none());
+    cx.update(Navigator::pop);
+
+    // In strict mode it is refused, by plain pushes too
+    cx.update(|cx| {
+        cx.global_mut::<GlobalRouter>()
+            .set_config(RouterConfig::new().strict_navigation(true));
+    });
+    let result = cx.update(|cx| Navigator::try_push(cx, "/typo"));
+    assert!(result.is_not_found());
+    cx.update(|cx| Navigator::push(cx, "/users/7/typo"));
+    assert_eq!(cx.read(Navigator::current_path), "/users/7");
+    assert!(matches!(
+        cx.read(Navigator::last_error),
+        Some(NavigationError::RouteNotFound { path, .. }) if path == "/users/7/typo"
+    ));
+
+    let result = cx.update(|cx| Navigator::try_push(cx, "/users"));
+    assert!(result.is_success());
+    assert_eq!(cx.read(Navigator::current_path), "/users");
+    assert!(cx.read(Navigator::last_error).is_none());
 }
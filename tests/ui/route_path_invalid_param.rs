@@ -0,0 +1,7 @@
+use gpui_navigator::{route_path, PathPattern};
+
+const PATH: PathPattern = route_path!("/users/:user-id");
+
+fn main() {
+    let _ = PATH;
+}
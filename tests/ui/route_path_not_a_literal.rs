@@ -0,0 +1,6 @@
+use gpui_navigator::route_path;
+
+fn main() {
+    let path = "/users/:id";
+    let _ = route_path!(path);
+}